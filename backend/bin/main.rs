@@ -2,7 +2,11 @@ use std::error::Error;
 
 use tokio::signal::unix::{signal, SignalKind};
 use tracing::{event, Level};
-use yaiss_backend::{configuration::Configuration, server::Server, state::State};
+use yaiss_backend::{
+    configuration::{Change, Configuration},
+    server::Server,
+    state::State,
+};
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn Error>> {
@@ -26,11 +30,18 @@ async fn main() -> Result<(), Box<dyn Error>> {
                 event!(Level::INFO,"SIGINT Shuting down");
                 break;
             },
-            Some(()) = configuration.has_change() => {
-                event!(Level::INFO,"Configuration changed");
-                let configuration = Configuration::new();
-                let state = State::new(&configuration);
-                server_handler.reload(state, configuration).await
+            Some(change) = configuration.has_change() => match change {
+                Change::Configuration => {
+                    event!(Level::INFO,"Configuration changed");
+                    // The new one watches the certificate it names.
+                    configuration = Configuration::new();
+                    let state = State::new(&configuration);
+                    server_handler.reload(state, &configuration).await
+                }
+                Change::Certificate => {
+                    event!(Level::INFO,"Certificate changed");
+                    server_handler.reload_certificate().await
+                }
             }
         };
     }
@@ -1,5 +1,9 @@
 #!/bin/bash
 DB_URL=${DB_URL:-sqlite:backend/sql/images.db}
+case "$DB_URL" in
+    postgres:*|postgresql:*) MIGRATIONS=backend/sql/postgres_migrations ;;
+    *) MIGRATIONS=backend/sql/migrations ;;
+esac
 
 sqlx db create --database-url "$DB_URL"
-sqlx migrate run --source backend/sql/migrations --database-url "$DB_URL"
\ No newline at end of file
+sqlx migrate run --source "$MIGRATIONS" --database-url "$DB_URL"
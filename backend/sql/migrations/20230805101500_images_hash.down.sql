@@ -0,0 +1,3 @@
+-- Add down migration script here
+DROP INDEX IF EXISTS images_hash_idx;
+ALTER TABLE images DROP COLUMN hash;
@@ -0,0 +1,3 @@
+-- Add up migration script here
+ALTER TABLE images ADD COLUMN hash VARCHAR(64);
+CREATE UNIQUE INDEX IF NOT EXISTS images_hash_idx ON images (hash);
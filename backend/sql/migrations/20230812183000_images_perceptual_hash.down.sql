@@ -0,0 +1,2 @@
+-- Add down migration script here
+ALTER TABLE images DROP COLUMN perceptual_hash;
@@ -0,0 +1,10 @@
+-- Add up migration script here
+CREATE TABLE IF NOT EXISTS renditions (
+    id INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+    image_id INTEGER NOT NULL REFERENCES images (id) ON DELETE CASCADE,
+    size VARCHAR(16) NOT NULL,
+    path VARCHAR(4096) NOT NULL,
+    updated_on TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE (image_id, size),
+    UNIQUE (path)
+);
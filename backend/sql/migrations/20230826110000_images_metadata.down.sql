@@ -0,0 +1,10 @@
+-- Add down migration script here
+ALTER TABLE images DROP COLUMN filename;
+ALTER TABLE images DROP COLUMN stored_size;
+ALTER TABLE images DROP COLUMN original_size;
+ALTER TABLE images DROP COLUMN original_format;
+ALTER TABLE images DROP COLUMN bit_depth;
+ALTER TABLE images DROP COLUMN color_type;
+ALTER TABLE images DROP COLUMN height;
+ALTER TABLE images DROP COLUMN width;
+ALTER TABLE images DROP COLUMN created_on;
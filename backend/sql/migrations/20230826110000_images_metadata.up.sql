@@ -0,0 +1,11 @@
+-- Add up migration script here
+ALTER TABLE images ADD COLUMN created_on TEXT;
+ALTER TABLE images ADD COLUMN width INTEGER;
+ALTER TABLE images ADD COLUMN height INTEGER;
+ALTER TABLE images ADD COLUMN color_type VARCHAR(16);
+ALTER TABLE images ADD COLUMN bit_depth INTEGER;
+ALTER TABLE images ADD COLUMN original_format VARCHAR(16);
+ALTER TABLE images ADD COLUMN original_size INTEGER;
+ALTER TABLE images ADD COLUMN stored_size INTEGER;
+ALTER TABLE images ADD COLUMN filename VARCHAR(4096);
+UPDATE images SET created_on = updated_on;
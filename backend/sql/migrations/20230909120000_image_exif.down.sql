@@ -0,0 +1,3 @@
+-- Add down migration script here
+DROP INDEX IF EXISTS image_exif_captured_on_idx;
+DROP TABLE IF EXISTS image_exif;
@@ -0,0 +1,17 @@
+-- Add up migration script here
+CREATE TABLE IF NOT EXISTS image_exif (
+    image_id INTEGER NOT NULL PRIMARY KEY REFERENCES images(id) ON DELETE CASCADE,
+    captured_on TEXT,
+    make VARCHAR(256),
+    model VARCHAR(256),
+    lens VARCHAR(256),
+    exposure_time VARCHAR(32),
+    f_number REAL,
+    iso INTEGER,
+    focal_length REAL,
+    latitude REAL,
+    longitude REAL,
+    altitude REAL,
+    orientation INTEGER
+);
+CREATE INDEX IF NOT EXISTS image_exif_captured_on_idx ON image_exif (captured_on);
@@ -0,0 +1,4 @@
+-- Add down migration script here
+DROP INDEX IF EXISTS image_tags_tag_id_idx;
+DROP TABLE IF EXISTS image_tags;
+DROP TABLE IF EXISTS tags;
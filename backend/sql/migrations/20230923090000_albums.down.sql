@@ -0,0 +1,5 @@
+-- Add down migration script here
+DROP INDEX IF EXISTS album_images_image_id_idx;
+DROP TABLE IF EXISTS album_images;
+DROP INDEX IF EXISTS albums_parent_id_idx;
+DROP TABLE IF EXISTS albums;
@@ -0,0 +1,16 @@
+-- Add up migration script here
+CREATE TABLE IF NOT EXISTS albums (
+    id INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+    name VARCHAR(255) NOT NULL,
+    parent_id INTEGER REFERENCES albums(id) ON DELETE CASCADE,
+    cover_image_id INTEGER REFERENCES images(id) ON DELETE SET NULL,
+    created_on TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS albums_parent_id_idx ON albums (parent_id);
+CREATE TABLE IF NOT EXISTS album_images (
+    album_id INTEGER NOT NULL REFERENCES albums(id) ON DELETE CASCADE,
+    image_id INTEGER NOT NULL REFERENCES images(id) ON DELETE CASCADE,
+    position INTEGER NOT NULL,
+    PRIMARY KEY (album_id, image_id)
+);
+CREATE INDEX IF NOT EXISTS album_images_image_id_idx ON album_images (image_id);
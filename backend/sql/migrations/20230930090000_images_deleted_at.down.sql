@@ -0,0 +1,3 @@
+-- Add down migration script here
+DROP INDEX IF EXISTS images_deleted_at_idx;
+ALTER TABLE images DROP COLUMN deleted_at;
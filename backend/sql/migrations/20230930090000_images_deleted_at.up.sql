@@ -0,0 +1,3 @@
+-- Add up migration script here
+ALTER TABLE images ADD COLUMN deleted_at TEXT;
+CREATE INDEX IF NOT EXISTS images_deleted_at_idx ON images (deleted_at);
@@ -0,0 +1,4 @@
+-- Add down migration script here
+DROP INDEX IF EXISTS images_checked_on_idx;
+ALTER TABLE images DROP COLUMN checked_on;
+ALTER TABLE images DROP COLUMN checksum;
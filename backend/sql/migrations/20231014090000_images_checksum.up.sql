@@ -0,0 +1,4 @@
+-- Add up migration script here
+ALTER TABLE images ADD COLUMN checksum TEXT;
+ALTER TABLE images ADD COLUMN checked_on TEXT;
+CREATE INDEX IF NOT EXISTS images_checked_on_idx ON images (checked_on);
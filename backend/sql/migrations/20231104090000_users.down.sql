@@ -0,0 +1,9 @@
+-- Add down migration script here
+DROP INDEX IF EXISTS images_owner_hash_idx;
+CREATE UNIQUE INDEX IF NOT EXISTS images_hash_idx ON images (hash);
+DROP INDEX IF EXISTS albums_owner_id_idx;
+DROP INDEX IF EXISTS images_owner_id_idx;
+ALTER TABLE albums DROP COLUMN owner_id;
+ALTER TABLE images DROP COLUMN owner_id;
+DROP TABLE IF EXISTS sessions;
+DROP TABLE IF EXISTS users;
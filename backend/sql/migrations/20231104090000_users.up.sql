@@ -0,0 +1,24 @@
+-- Add up migration script here
+CREATE TABLE IF NOT EXISTS users (
+    id INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+    name VARCHAR(64) NOT NULL,
+    password_hash TEXT NOT NULL,
+    created_on TEXT NOT NULL,
+    UNIQUE (name)
+);
+CREATE TABLE IF NOT EXISTS sessions (
+    id INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+    user_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    token_hash VARCHAR(64) NOT NULL,
+    expires_on TEXT NOT NULL,
+    UNIQUE (token_hash)
+);
+-- No foreign keys on the owner columns: sqlite cannot drop those again.
+-- Rows without an owner stay visible to API keys only.
+ALTER TABLE images ADD COLUMN owner_id INTEGER;
+ALTER TABLE albums ADD COLUMN owner_id INTEGER;
+CREATE INDEX IF NOT EXISTS images_owner_id_idx ON images (owner_id);
+CREATE INDEX IF NOT EXISTS albums_owner_id_idx ON albums (owner_id);
+-- Users may upload the same image as others.
+DROP INDEX IF EXISTS images_hash_idx;
+CREATE UNIQUE INDEX IF NOT EXISTS images_owner_hash_idx ON images (IFNULL(owner_id, 0), hash);
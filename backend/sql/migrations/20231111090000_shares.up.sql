@@ -0,0 +1,9 @@
+-- Add up migration script here
+CREATE TABLE IF NOT EXISTS shares (
+    id INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+    image_id INTEGER NOT NULL REFERENCES images(id) ON DELETE CASCADE,
+    created_on TEXT NOT NULL,
+    expires_on TEXT NOT NULL,
+    revoked_on TEXT
+);
+CREATE INDEX IF NOT EXISTS shares_image_id_idx ON shares (image_id);
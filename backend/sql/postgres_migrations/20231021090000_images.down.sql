@@ -0,0 +1,8 @@
+-- Add down migration script here
+DROP TABLE IF EXISTS album_images;
+DROP TABLE IF EXISTS albums;
+DROP TABLE IF EXISTS image_tags;
+DROP TABLE IF EXISTS tags;
+DROP TABLE IF EXISTS image_exif;
+DROP TABLE IF EXISTS renditions;
+DROP TABLE IF EXISTS images;
@@ -0,0 +1,81 @@
+-- Add up migration script here
+CREATE TABLE IF NOT EXISTS images (
+    id BIGSERIAL PRIMARY KEY,
+    path VARCHAR(4096) NOT NULL,
+    updated_on TEXT NOT NULL,
+    hash VARCHAR(64),
+    perceptual_hash BIGINT,
+    created_on TEXT,
+    width BIGINT,
+    height BIGINT,
+    color_type VARCHAR(16),
+    bit_depth BIGINT,
+    original_format VARCHAR(16),
+    original_size BIGINT,
+    stored_size BIGINT,
+    filename VARCHAR(4096),
+    original_path VARCHAR(4096),
+    deleted_at TEXT,
+    status TEXT NOT NULL DEFAULT 'ok',
+    checksum TEXT,
+    checked_on TEXT,
+    UNIQUE (path)
+);
+CREATE UNIQUE INDEX IF NOT EXISTS images_hash_idx ON images (hash);
+CREATE INDEX IF NOT EXISTS images_deleted_at_idx ON images (deleted_at);
+CREATE INDEX IF NOT EXISTS images_checked_on_idx ON images (checked_on);
+
+CREATE TABLE IF NOT EXISTS renditions (
+    id BIGSERIAL PRIMARY KEY,
+    image_id BIGINT NOT NULL REFERENCES images (id) ON DELETE CASCADE,
+    size VARCHAR(16) NOT NULL,
+    path VARCHAR(4096) NOT NULL,
+    updated_on TEXT NOT NULL,
+    UNIQUE (image_id, size),
+    UNIQUE (path)
+);
+
+CREATE TABLE IF NOT EXISTS image_exif (
+    image_id BIGINT NOT NULL PRIMARY KEY REFERENCES images (id) ON DELETE CASCADE,
+    captured_on TEXT,
+    make VARCHAR(256),
+    model VARCHAR(256),
+    lens VARCHAR(256),
+    exposure_time VARCHAR(32),
+    f_number DOUBLE PRECISION,
+    iso BIGINT,
+    focal_length DOUBLE PRECISION,
+    latitude DOUBLE PRECISION,
+    longitude DOUBLE PRECISION,
+    altitude DOUBLE PRECISION,
+    orientation BIGINT
+);
+CREATE INDEX IF NOT EXISTS image_exif_captured_on_idx ON image_exif (captured_on);
+
+CREATE TABLE IF NOT EXISTS tags (
+    id BIGSERIAL PRIMARY KEY,
+    name VARCHAR(64) NOT NULL,
+    UNIQUE (name)
+);
+CREATE TABLE IF NOT EXISTS image_tags (
+    image_id BIGINT NOT NULL REFERENCES images (id) ON DELETE CASCADE,
+    tag_id BIGINT NOT NULL REFERENCES tags (id) ON DELETE CASCADE,
+    PRIMARY KEY (image_id, tag_id)
+);
+CREATE INDEX IF NOT EXISTS image_tags_tag_id_idx ON image_tags (tag_id);
+
+CREATE TABLE IF NOT EXISTS albums (
+    id BIGSERIAL PRIMARY KEY,
+    name VARCHAR(255) NOT NULL,
+    parent_id BIGINT REFERENCES albums (id) ON DELETE CASCADE,
+    cover_image_id BIGINT REFERENCES images (id) ON DELETE SET NULL,
+    created_on TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS albums_parent_id_idx ON albums (parent_id);
+CREATE TABLE IF NOT EXISTS album_images (
+    album_id BIGINT NOT NULL REFERENCES albums (id) ON DELETE CASCADE,
+    image_id BIGINT NOT NULL REFERENCES images (id) ON DELETE CASCADE,
+    position BIGINT NOT NULL,
+    PRIMARY KEY (album_id, image_id)
+);
+CREATE INDEX IF NOT EXISTS album_images_image_id_idx ON album_images (image_id);
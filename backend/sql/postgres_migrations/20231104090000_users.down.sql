@@ -0,0 +1,7 @@
+-- Add down migration script here
+DROP INDEX IF EXISTS images_owner_hash_idx;
+CREATE UNIQUE INDEX IF NOT EXISTS images_hash_idx ON images (hash);
+ALTER TABLE albums DROP COLUMN IF EXISTS owner_id;
+ALTER TABLE images DROP COLUMN IF EXISTS owner_id;
+DROP TABLE IF EXISTS sessions;
+DROP TABLE IF EXISTS users;
@@ -1,3 +1,8 @@
+use std::{
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
 use futures::{
     channel::mpsc::{unbounded, UnboundedReceiver},
     SinkExt, StreamExt,
@@ -5,13 +10,72 @@ use futures::{
 use ini::Ini;
 use notify::{
     event::{DataChange, ModifyKind},
-    Config, Event, RecommendedWatcher, RecursiveMode, Watcher,
+    Config, Event, EventKind, NullWatcher, RecommendedWatcher, RecursiveMode, Watcher,
 };
 
+use crate::services::images::domain::storage_layout::StorageLayout;
+
+/// When the storage is checked for consistency outside the admin endpoint.
+#[derive(Debug, Clone, Default)]
+pub struct ConsistencySchedule {
+    pub on_startup: bool,
+    /// Runs the check periodically when set.
+    pub interval: Option<Duration>,
+    /// Quarantines orphans and marks rows instead of only reporting them.
+    pub repair: bool,
+}
+
+/// Connection to an S3 compatible object store.
+#[derive(Debug, Clone)]
+pub struct S3Settings {
+    pub endpoint: String,
+    pub bucket: String,
+    pub region: String,
+    pub access_key: String,
+    pub secret_key: String,
+}
+
+/// Where image files are written.
+#[derive(Debug, Clone)]
+pub enum BlobStoreBackend {
+    Filesystem,
+    S3(S3Settings),
+    Memory,
+}
+
+/// Metadata store, picked by the scheme of `[DATABASE] url`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DatabaseBackend {
+    Sqlite,
+    Postgres,
+    /// Nothing survives a restart, for tests and demos.
+    Memory,
+}
+
+/// Certificate and key the server terminates TLS with, both PEM files.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TlsSettings {
+    pub cert_path: String,
+    pub key_path: String,
+    /// Port answering plain HTTP with a redirect to HTTPS when set.
+    pub redirect_port: Option<u16>,
+}
+
+/// A watched file that changed.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Change {
+    /// The configuration file itself.
+    Configuration,
+    /// The TLS certificate or key.
+    Certificate,
+}
+
 pub struct Configuration {
     configuration: ini::Ini,
     watcher: UnboundedReceiver<notify::Result<Event>>,
     _w: Box<dyn Watcher>,
+    path: Option<PathBuf>,
+    tls_paths: Vec<PathBuf>,
 }
 
 impl Configuration {
@@ -31,12 +95,44 @@ impl Configuration {
             )
             .expect("Error creating watcher"),
         );
-        w.watch(path.as_ref(), RecursiveMode::NonRecursive)
-            .expect("Error starting watcher");
-        Self {
+        let mut configuration = Self {
             configuration: Ini::load_from_file(&path).expect("Error loading configuration file"),
             watcher: rx,
-            _w: w,
+            _w: Box::new(NullWatcher),
+            path: Some(watched_path(&path)),
+            tls_paths: vec![],
+        };
+        if let Some(tls) = configuration.tls() {
+            configuration.tls_paths =
+                vec![watched_path(&tls.cert_path), watched_path(&tls.key_path)];
+        }
+        // Directories rather than files are watched, so a certificate
+        // replaced by a rename or a symlink swap is still seen.
+        let mut directories: Vec<&Path> = configuration
+            .path
+            .iter()
+            .chain(configuration.tls_paths.iter())
+            .filter_map(|path| path.parent())
+            .collect();
+        directories.sort();
+        directories.dedup();
+        for directory in directories {
+            w.watch(directory, RecursiveMode::NonRecursive)
+                .expect("Error starting watcher");
+        }
+        configuration._w = w;
+        configuration
+    }
+
+    /// Configuration that is not read from a file, so it never changes.
+    pub fn from_ini(configuration: Ini) -> Self {
+        let (_, rx) = unbounded();
+        Self {
+            configuration,
+            watcher: rx,
+            _w: Box::new(NullWatcher),
+            path: None,
+            tls_paths: vec![],
         }
     }
 
@@ -46,10 +142,21 @@ impl Configuration {
             .expect("Invalid url")
     }
 
-    pub(crate) fn migrations_path(&self) -> &str {
+    pub(crate) fn database_backend(&self) -> DatabaseBackend {
+        let url = self.database_url();
+        match url.split_once(':').map(|(scheme, _)| scheme) {
+            Some("sqlite") => DatabaseBackend::Sqlite,
+            Some("postgres" | "postgresql") => DatabaseBackend::Postgres,
+            Some("memory") => DatabaseBackend::Memory,
+            _ => panic!("Invalid database url: {}", url),
+        }
+    }
+
+    /// A directory read at startup instead of the migrations built in for
+    /// the backend in use.
+    pub(crate) fn migrations_path(&self) -> Option<&str> {
         self.configuration
             .get_from(Some("DATABASE"), "migrations_path")
-            .expect("Invalid migrations path")
     }
 
     pub(crate) fn address(&self) -> ([u8; 4], u16) {
@@ -69,14 +176,44 @@ impl Configuration {
         (address.try_into().expect("Invalid address value"), port)
     }
 
-    pub async fn has_change(&mut self) -> Option<()> {
+    /// HTTPS is served when `[SERVER]` has both `tls_cert_path` and
+    /// `tls_key_path`.
+    pub(crate) fn tls(&self) -> Option<TlsSettings> {
+        let setting = |key: &str| {
+            self.configuration
+                .get_from(Some("SERVER"), key)
+                .map(str::to_string)
+        };
+        match (setting("tls_cert_path"), setting("tls_key_path")) {
+            (None, None) => None,
+            (Some(cert_path), Some(key_path)) => Some(TlsSettings {
+                cert_path,
+                key_path,
+                redirect_port: setting("http_redirect_port")
+                    .map(|port| port.parse::<u16>().expect("Invalid redirect port")),
+            }),
+            _ => panic!("Invalid TLS configuration: set both tls_cert_path and tls_key_path"),
+        }
+    }
+
+    /// Waits for the file or the TLS certificate to change; `None` once
+    /// nothing watches them.
+    pub async fn has_change(&mut self) -> Option<Change> {
         loop {
-            if let Some(Ok(Event {
-                kind: notify::EventKind::Modify(ModifyKind::Data(DataChange::Content)),
-                ..
-            })) = self.watcher.next().await
+            let event = match self.watcher.next().await? {
+                Ok(event) => event,
+                Err(_) => continue,
+            };
+            let touches = |path: &PathBuf| event.paths.contains(path);
+            if self.tls_paths.iter().any(touches)
+                && matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_))
+            {
+                return Some(Change::Certificate);
+            }
+            if self.path.iter().any(touches)
+                && event.kind == EventKind::Modify(ModifyKind::Data(DataChange::Content))
             {
-                return Some(());
+                return Some(Change::Configuration);
             }
         }
     }
@@ -86,6 +223,127 @@ impl Configuration {
             .get_from(Some("IMAGE_SERVICE"), "base_path")
             .expect("Invalid base path")
     }
+
+    /// Directory for the untouched uploaded bytes; originals are dropped
+    /// when it is not set.
+    pub(crate) fn images_originals_path(&self) -> Option<&str> {
+        self.configuration
+            .get_from(Some("IMAGE_SERVICE"), "originals_path")
+    }
+
+    /// How new files are spread below their directory, flat unless
+    /// `layout = sharded`.
+    pub(crate) fn storage_layout(&self) -> StorageLayout {
+        self.configuration
+            .get_from(Some("IMAGE_SERVICE"), "layout")
+            .map(|layout| {
+                layout
+                    .parse::<StorageLayout>()
+                    .expect("Invalid storage layout")
+            })
+            .unwrap_or_default()
+    }
+
+    /// Where the consistency check moves files no image refers to; a
+    /// `quarantine` directory below the base path unless set.
+    pub(crate) fn images_quarantine_path(&self) -> String {
+        match self
+            .configuration
+            .get_from(Some("IMAGE_SERVICE"), "quarantine_path")
+        {
+            Some(quarantine_path) => quarantine_path.to_string(),
+            None => Path::new(self.images_base_path())
+                .join("quarantine")
+                .to_string_lossy()
+                .to_string(),
+        }
+    }
+
+    pub(crate) fn consistency_schedule(&self) -> ConsistencySchedule {
+        let flag = |key: &str| {
+            self.configuration
+                .get_from(Some("CONSISTENCY"), key)
+                .map(|value| value.parse::<bool>().expect("Invalid consistency flag"))
+                .unwrap_or(false)
+        };
+        let interval = self
+            .configuration
+            .get_from(Some("CONSISTENCY"), "interval_hours")
+            .map(|hours| hours.parse::<u64>().expect("Invalid consistency interval"))
+            .map(|hours| Duration::from_secs(hours * 60 * 60));
+        ConsistencySchedule {
+            on_startup: flag("on_startup"),
+            interval,
+            repair: flag("repair"),
+        }
+    }
+
+    /// Files the scrubber re-reads every minute, 10 unless set; 0 turns it
+    /// off.
+    pub(crate) fn scrub_rate(&self) -> i64 {
+        self.configuration
+            .get_from(Some("SCRUBBER"), "files_per_minute")
+            .map(|rate| rate.parse::<i64>().expect("Invalid scrub rate"))
+            .unwrap_or(10)
+    }
+
+    /// The `[BLOB_STORE]` backend, the local filesystem unless set to `s3`
+    /// or `memory`.
+    pub(crate) fn blob_store_backend(&self) -> BlobStoreBackend {
+        let setting = |key: &str| {
+            self.configuration
+                .get_from(Some("BLOB_STORE"), key)
+                .map(str::to_string)
+        };
+        match setting("backend").as_deref() {
+            None | Some("fs") => BlobStoreBackend::Filesystem,
+            Some("memory") => BlobStoreBackend::Memory,
+            Some("s3") => BlobStoreBackend::S3(S3Settings {
+                endpoint: setting("endpoint").expect("Invalid S3 endpoint"),
+                bucket: setting("bucket").expect("Invalid S3 bucket"),
+                region: setting("region").unwrap_or_else(|| "us-east-1".to_string()),
+                access_key: setting("access_key").expect("Invalid S3 access key"),
+                secret_key: setting("secret_key").expect("Invalid S3 secret key"),
+            }),
+            Some(backend) => panic!("Invalid blob store backend: {}", backend),
+        }
+    }
+
+    /// An admin key accepted without being stored, to create the first API
+    /// keys with.
+    pub(crate) fn bootstrap_key(&self) -> Option<&str> {
+        self.configuration.get_from(Some("AUTH"), "bootstrap_key")
+    }
+
+    /// The HMAC key share links are signed with.
+    pub(crate) fn share_signing_key(&self) -> Option<&str> {
+        self.configuration.get_from(Some("SHARES"), "signing_key")
+    }
+
+    /// How long deleted images stay in the trash before they are purged,
+    /// 30 days unless `trash_retention_days` says otherwise.
+    pub(crate) fn trash_retention(&self) -> Duration {
+        let days = self
+            .configuration
+            .get_from(Some("IMAGE_SERVICE"), "trash_retention_days")
+            .map(|days| days.parse::<u64>().expect("Invalid trash retention"))
+            .unwrap_or(30);
+        Duration::from_secs(days * 24 * 60 * 60)
+    }
+}
+
+/// The path as the watcher reports it: below the canonical directory, with
+/// the file name itself left unresolved.
+fn watched_path(path: &str) -> PathBuf {
+    let path = Path::new(path);
+    let directory = match path.parent() {
+        Some(directory) if !directory.as_os_str().is_empty() => directory,
+        _ => Path::new("."),
+    };
+    directory
+        .canonicalize()
+        .expect("Invalid watched path")
+        .join(path.file_name().expect("Invalid watched path"))
 }
 
 impl Default for Configuration {
@@ -0,0 +1,170 @@
+use std::path::{Path, PathBuf};
+
+use async_trait::async_trait;
+use futures::StreamExt;
+use tokio::io::AsyncWriteExt;
+use tokio_util::io::ReaderStream;
+
+use crate::services::images::ports::outgoing::blob_store_port::{
+    BlobInfo, BlobStoreError, BlobStorePort, BlobStream,
+};
+
+/// Blobs as plain files, the key being the path of the file.
+#[derive(Debug, Clone, Default)]
+pub struct FsBlobStore {}
+
+impl FsBlobStore {
+    pub fn new() -> Self {
+        Self {}
+    }
+
+    /// Writes and fsyncs `bytes` to a temporary file next to `path`, so a
+    /// crash never leaves a truncated file at the final path.
+    async fn write_temp(path: &Path, bytes: &[u8]) -> std::io::Result<PathBuf> {
+        let file_name = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .unwrap_or_default();
+        let temp =
+            path.with_file_name(format!(".{}.{:016x}.tmp", file_name, rand::random::<u64>()));
+        let mut file = tokio::fs::File::create(&temp).await?;
+        let written = match file.write_all(bytes).await {
+            Ok(()) => file.sync_all().await,
+            Err(e) => Err(e),
+        };
+        if let Err(e) = written {
+            let _ = tokio::fs::remove_file(&temp).await;
+            return Err(e);
+        }
+        Ok(temp)
+    }
+
+    /// Makes a rename into `parent` durable.
+    async fn sync_dir(parent: Option<&Path>) -> std::io::Result<()> {
+        match parent {
+            Some(parent) if !parent.as_os_str().is_empty() => {
+                tokio::fs::File::open(parent).await?.sync_all().await
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+#[async_trait]
+impl BlobStorePort for FsBlobStore {
+    async fn put(&self, key: &str, bytes: Vec<u8>) -> Result<(), BlobStoreError> {
+        let path = Path::new(key);
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        let temp = Self::write_temp(path, &bytes).await?;
+        if let Err(e) = tokio::fs::rename(&temp, path).await {
+            let _ = tokio::fs::remove_file(&temp).await;
+            return Err(e.into());
+        }
+        Self::sync_dir(path.parent()).await?;
+        Ok(())
+    }
+
+    async fn get(&self, key: &str) -> Result<BlobStream, BlobStoreError> {
+        let file = tokio::fs::File::open(key).await?;
+        Ok(ReaderStream::new(file).boxed())
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), BlobStoreError> {
+        match tokio::fs::remove_file(key).await {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+
+    async fn exists(&self, key: &str) -> Result<bool, BlobStoreError> {
+        Ok(tokio::fs::try_exists(key).await?)
+    }
+
+    async fn size(&self, key: &str) -> Result<u64, BlobStoreError> {
+        Ok(tokio::fs::metadata(key).await?.len())
+    }
+
+    async fn list(&self, prefix: &str) -> Result<Vec<BlobInfo>, BlobStoreError> {
+        let mut blobs = vec![];
+        let mut directories = vec![PathBuf::from(prefix)];
+        while let Some(directory) = directories.pop() {
+            let mut entries = match tokio::fs::read_dir(&directory).await {
+                Ok(entries) => entries,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+                Err(e) => return Err(e.into()),
+            };
+            while let Some(entry) = entries.next_entry().await? {
+                let path = entry.path();
+                let metadata = entry.metadata().await?;
+                if metadata.is_dir() {
+                    directories.push(path);
+                    continue;
+                }
+                blobs.push(BlobInfo {
+                    key: path.to_string_lossy().to_string(),
+                    size: metadata.len(),
+                    modified: metadata.modified().ok(),
+                });
+            }
+        }
+        blobs.sort_by(|a, b| a.key.cmp(&b.key));
+        Ok(blobs)
+    }
+
+    async fn rename(&self, from: &str, to: &str) -> Result<(), BlobStoreError> {
+        let target = Path::new(to);
+        if let Some(parent) = target.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        tokio::fs::rename(from, target).await?;
+        Self::sync_dir(target.parent()).await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use crate::services::images::ports::outgoing::blob_store_port::{
+        BlobStoreError, BlobStorePort,
+    };
+
+    use super::FsBlobStore;
+
+    #[tokio::test]
+    async fn test_put_get_and_delete_file() {
+        let dir = env::temp_dir().join("yaiss_fs_blob_store");
+        let key = dir.join("nested/some.qoi").display().to_string();
+        let store = FsBlobStore::new();
+        store.put(&key, b"some content".to_vec()).await.unwrap();
+        assert_eq!(store.read(&key).await.unwrap(), b"some content");
+        assert_eq!(store.size(&key).await.unwrap(), 12);
+        assert!(store.exists(&key).await.unwrap());
+        // Only the final file is left, no temporary one.
+        assert_eq!(std::fs::read_dir(dir.join("nested")).unwrap().count(), 1);
+        let listed = store.list(&dir.display().to_string()).await.unwrap();
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].key, key);
+        store.delete(&key).await.unwrap();
+        store.delete(&key).await.unwrap();
+        assert!(!store.exists(&key).await.unwrap());
+        assert_eq!(store.size(&key).await, Err(BlobStoreError::NotFound));
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_rename_creates_target_directory() {
+        let dir = env::temp_dir().join("yaiss_fs_blob_store_rename");
+        let from = dir.join("some.qoi").display().to_string();
+        let to = dir.join("moved/some.qoi").display().to_string();
+        let store = FsBlobStore::new();
+        store.put(&from, b"some content".to_vec()).await.unwrap();
+        store.rename(&from, &to).await.unwrap();
+        assert!(!store.exists(&from).await.unwrap());
+        assert_eq!(store.read(&to).await.unwrap(), b"some content");
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,87 @@
+use std::{collections::BTreeMap, sync::Mutex, time::SystemTime};
+
+use async_trait::async_trait;
+use bytes::Bytes;
+use futures::StreamExt;
+
+use crate::services::images::ports::outgoing::blob_store_port::{
+    BlobInfo, BlobStoreError, BlobStorePort, BlobStream,
+};
+
+/// Blobs kept in a map, for tests and throwaway instances.
+#[derive(Debug, Default)]
+pub struct MemoryBlobStore {
+    blobs: Mutex<BTreeMap<String, (Bytes, SystemTime)>>,
+}
+
+impl MemoryBlobStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Stores `bytes` as if they were written at `modified`.
+    pub fn insert(&self, key: &str, bytes: impl Into<Bytes>, modified: SystemTime) {
+        self.blobs
+            .lock()
+            .unwrap()
+            .insert(key.to_string(), (bytes.into(), modified));
+    }
+
+    pub fn keys(&self) -> Vec<String> {
+        self.blobs.lock().unwrap().keys().cloned().collect()
+    }
+}
+
+#[async_trait]
+impl BlobStorePort for MemoryBlobStore {
+    async fn put(&self, key: &str, bytes: Vec<u8>) -> Result<(), BlobStoreError> {
+        self.insert(key, bytes, SystemTime::now());
+        Ok(())
+    }
+
+    async fn get(&self, key: &str) -> Result<BlobStream, BlobStoreError> {
+        match self.blobs.lock().unwrap().get(key) {
+            Some((bytes, _)) => Ok(futures::stream::iter([Ok(bytes.clone())]).boxed()),
+            None => Err(BlobStoreError::NotFound),
+        }
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), BlobStoreError> {
+        self.blobs.lock().unwrap().remove(key);
+        Ok(())
+    }
+
+    async fn exists(&self, key: &str) -> Result<bool, BlobStoreError> {
+        Ok(self.blobs.lock().unwrap().contains_key(key))
+    }
+
+    async fn size(&self, key: &str) -> Result<u64, BlobStoreError> {
+        match self.blobs.lock().unwrap().get(key) {
+            Some((bytes, _)) => Ok(bytes.len() as u64),
+            None => Err(BlobStoreError::NotFound),
+        }
+    }
+
+    async fn list(&self, prefix: &str) -> Result<Vec<BlobInfo>, BlobStoreError> {
+        let directory = format!("{}/", prefix.trim_end_matches('/'));
+        Ok(self
+            .blobs
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(key, _)| prefix.is_empty() || key.starts_with(&directory))
+            .map(|(key, (bytes, modified))| BlobInfo {
+                key: key.clone(),
+                size: bytes.len() as u64,
+                modified: Some(*modified),
+            })
+            .collect())
+    }
+
+    async fn rename(&self, from: &str, to: &str) -> Result<(), BlobStoreError> {
+        let mut blobs = self.blobs.lock().unwrap();
+        let blob = blobs.remove(from).ok_or(BlobStoreError::NotFound)?;
+        blobs.insert(to.to_string(), blob);
+        Ok(())
+    }
+}
@@ -0,0 +1,3 @@
+pub mod fs_blob_store;
+pub mod memory_blob_store;
+pub mod s3_blob_store;
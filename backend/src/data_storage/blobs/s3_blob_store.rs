@@ -0,0 +1,554 @@
+use std::{collections::BTreeMap, time::SystemTime};
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use futures::{StreamExt, TryStreamExt};
+use hmac::{Hmac, Mac};
+use reqwest::{header, Method, Response, StatusCode};
+use sha2::{Digest, Sha256};
+use tracing::error;
+
+use crate::{
+    configuration::S3Settings,
+    services::images::ports::outgoing::blob_store_port::{
+        BlobInfo, BlobStoreError, BlobStorePort, BlobStream,
+    },
+};
+
+/// Blobs as objects of one bucket, addressed path style so that MinIO and
+/// other S3 compatible stores work as well. Requests are signed with AWS
+/// signature version 4.
+pub struct S3BlobStore {
+    client: reqwest::Client,
+    settings: S3Settings,
+    host: String,
+}
+
+impl From<reqwest::Error> for BlobStoreError {
+    fn from(value: reqwest::Error) -> Self {
+        BlobStoreError::InternalError(value.to_string())
+    }
+}
+
+/// Percent-encodes everything but the unreserved characters, and `/` too
+/// unless `path` is set.
+fn uri_encode(value: &str, path: bool) -> String {
+    let mut encoded = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                encoded.push(byte as char)
+            }
+            b'/' if path => encoded.push('/'),
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    encoded
+}
+
+fn hmac(key: &[u8], data: &str) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts any key length");
+    mac.update(data.as_bytes());
+    mac.finalize().into_bytes().to_vec()
+}
+
+fn signing_key(secret_key: &str, date: &str, region: &str, service: &str) -> Vec<u8> {
+    let key = hmac(format!("AWS4{}", secret_key).as_bytes(), date);
+    let key = hmac(&key, region);
+    let key = hmac(&key, service);
+    hmac(&key, "aws4_request")
+}
+
+/// Contents of every `<tag>` element of `xml`, in document order.
+fn elements<'a>(xml: &'a str, tag: &str) -> Vec<&'a str> {
+    let open = format!("<{}>", tag);
+    let close = format!("</{}>", tag);
+    let mut found = vec![];
+    let mut rest = xml;
+    while let Some(start) = rest.find(&open) {
+        rest = &rest[start + open.len()..];
+        match rest.find(&close) {
+            Some(end) => {
+                found.push(&rest[..end]);
+                rest = &rest[end + close.len()..];
+            }
+            None => break,
+        }
+    }
+    found
+}
+
+fn element<'a>(xml: &'a str, tag: &str) -> Option<&'a str> {
+    elements(xml, tag).into_iter().next()
+}
+
+fn unescape(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+impl S3BlobStore {
+    pub fn new(settings: S3Settings) -> Self {
+        let url = reqwest::Url::parse(&settings.endpoint).expect("Invalid S3 endpoint");
+        let host = match url.port() {
+            Some(port) => format!("{}:{}", url.host_str().unwrap_or_default(), port),
+            None => url.host_str().unwrap_or_default().to_string(),
+        };
+        Self {
+            client: reqwest::Client::new(),
+            settings,
+            host,
+        }
+    }
+
+    fn object_path(&self, key: &str) -> String {
+        format!(
+            "/{}/{}",
+            uri_encode(&self.settings.bucket, false),
+            uri_encode(key, true)
+        )
+    }
+
+    /// Signs and sends a request. `path` must already be encoded.
+    async fn send(
+        &self,
+        method: Method,
+        path: &str,
+        query: &[(&str, &str)],
+        extra_headers: &[(&str, String)],
+        body: Vec<u8>,
+    ) -> Result<Response, BlobStoreError> {
+        let now = Utc::now();
+        let amz_date = now.format("%Y%m%dT%H%M%SZ").to_string();
+        let date = now.format("%Y%m%d").to_string();
+        let payload_hash = hex::encode(Sha256::digest(&body));
+        let mut query = query
+            .iter()
+            .map(|(name, value)| (uri_encode(name, false), uri_encode(value, false)))
+            .collect::<Vec<(String, String)>>();
+        query.sort();
+        let query = query
+            .into_iter()
+            .map(|(name, value)| format!("{}={}", name, value))
+            .collect::<Vec<String>>()
+            .join("&");
+        let mut headers = BTreeMap::from([
+            ("host".to_string(), self.host.clone()),
+            ("x-amz-content-sha256".to_string(), payload_hash.clone()),
+            ("x-amz-date".to_string(), amz_date.clone()),
+        ]);
+        for (name, value) in extra_headers {
+            headers.insert(name.to_lowercase(), value.trim().to_string());
+        }
+        let canonical_headers = headers
+            .iter()
+            .map(|(name, value)| format!("{}:{}\n", name, value))
+            .collect::<String>();
+        let signed_headers = headers.keys().cloned().collect::<Vec<String>>().join(";");
+        let canonical_request = format!(
+            "{}\n{}\n{}\n{}\n{}\n{}",
+            method, path, query, canonical_headers, signed_headers, payload_hash
+        );
+        let scope = format!("{}/{}/s3/aws4_request", date, self.settings.region);
+        let string_to_sign = format!(
+            "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+            amz_date,
+            scope,
+            hex::encode(Sha256::digest(canonical_request.as_bytes()))
+        );
+        let key = signing_key(
+            &self.settings.secret_key,
+            &date,
+            &self.settings.region,
+            "s3",
+        );
+        let signature = hex::encode(hmac(&key, &string_to_sign));
+        let authorization = format!(
+            "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+            self.settings.access_key, scope, signed_headers, signature
+        );
+        let mut url = format!("{}{}", self.settings.endpoint.trim_end_matches('/'), path);
+        if !query.is_empty() {
+            url = format!("{}?{}", url, query);
+        }
+        let mut request = self
+            .client
+            .request(method, url)
+            .header(header::AUTHORIZATION, authorization);
+        for (name, value) in headers.iter().filter(|(name, _)| *name != "host") {
+            request = request.header(name, value);
+        }
+        Ok(request.body(body).send().await?)
+    }
+
+    /// Lets successful responses through, a 404 becomes `NotFound`.
+    async fn check(response: Response) -> Result<Response, BlobStoreError> {
+        let status = response.status();
+        if status.is_success() {
+            return Ok(response);
+        }
+        if status == StatusCode::NOT_FOUND {
+            return Err(BlobStoreError::NotFound);
+        }
+        let body = response.text().await.unwrap_or_default();
+        error!("S3 request failed with {}: {}", status, body);
+        Err(BlobStoreError::InternalError(format!(
+            "S3 request failed with {}",
+            status
+        )))
+    }
+
+    async fn head(&self, key: &str) -> Result<Response, BlobStoreError> {
+        let response = self
+            .send(Method::HEAD, &self.object_path(key), &[], &[], vec![])
+            .await?;
+        Self::check(response).await
+    }
+}
+
+#[async_trait]
+impl BlobStorePort for S3BlobStore {
+    async fn put(&self, key: &str, bytes: Vec<u8>) -> Result<(), BlobStoreError> {
+        let response = self
+            .send(Method::PUT, &self.object_path(key), &[], &[], bytes)
+            .await?;
+        Self::check(response).await.map(|_| ())
+    }
+
+    async fn get(&self, key: &str) -> Result<BlobStream, BlobStoreError> {
+        let response = self
+            .send(Method::GET, &self.object_path(key), &[], &[], vec![])
+            .await?;
+        let response = Self::check(response).await?;
+        Ok(response
+            .bytes_stream()
+            .map_err(std::io::Error::other)
+            .boxed())
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), BlobStoreError> {
+        let response = self
+            .send(Method::DELETE, &self.object_path(key), &[], &[], vec![])
+            .await?;
+        match Self::check(response).await {
+            Err(BlobStoreError::NotFound) | Ok(_) => Ok(()),
+            Err(e) => Err(e),
+        }
+    }
+
+    async fn exists(&self, key: &str) -> Result<bool, BlobStoreError> {
+        match self.head(key).await {
+            Ok(_) => Ok(true),
+            Err(BlobStoreError::NotFound) => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+
+    async fn size(&self, key: &str) -> Result<u64, BlobStoreError> {
+        self.head(key)
+            .await?
+            .headers()
+            .get(header::CONTENT_LENGTH)
+            .and_then(|length| length.to_str().ok())
+            .and_then(|length| length.parse::<u64>().ok())
+            .ok_or(BlobStoreError::InternalError(
+                "Missing content length".to_string(),
+            ))
+    }
+
+    async fn list(&self, prefix: &str) -> Result<Vec<BlobInfo>, BlobStoreError> {
+        let prefix = match prefix.trim_end_matches('/') {
+            "" => String::new(),
+            directory => format!("{}/", directory),
+        };
+        let path = format!("/{}", uri_encode(&self.settings.bucket, false));
+        let mut blobs = vec![];
+        let mut token: Option<String> = None;
+        loop {
+            let mut query = vec![("list-type", "2"), ("prefix", prefix.as_str())];
+            if let Some(token) = &token {
+                query.push(("continuation-token", token.as_str()));
+            }
+            let response = self.send(Method::GET, &path, &query, &[], vec![]).await?;
+            let xml = Self::check(response).await?.text().await?;
+            for contents in elements(&xml, "Contents") {
+                let key = match element(contents, "Key") {
+                    Some(key) => unescape(key),
+                    None => continue,
+                };
+                blobs.push(BlobInfo {
+                    key,
+                    size: element(contents, "Size")
+                        .and_then(|size| size.parse::<u64>().ok())
+                        .unwrap_or_default(),
+                    modified: element(contents, "LastModified")
+                        .and_then(|modified| DateTime::parse_from_rfc3339(modified).ok())
+                        .map(SystemTime::from),
+                });
+            }
+            token = element(&xml, "NextContinuationToken").map(unescape);
+            if element(&xml, "IsTruncated") != Some("true") || token.is_none() {
+                break;
+            }
+        }
+        Ok(blobs)
+    }
+
+    /// S3 has no rename, the object is copied and the source deleted.
+    async fn rename(&self, from: &str, to: &str) -> Result<(), BlobStoreError> {
+        let source = self.object_path(from);
+        let response = self
+            .send(
+                Method::PUT,
+                &self.object_path(to),
+                &[],
+                &[("x-amz-copy-source", source)],
+                vec![],
+            )
+            .await?;
+        // A copy can fail after the status line went out, with the error
+        // in the body instead.
+        let body = Self::check(response).await?.text().await?;
+        if body.contains("<Error>") {
+            error!("Error copying {} to {}: {}", from, to, body);
+            return Err(BlobStoreError::InternalError(format!(
+                "Error copying {}",
+                from
+            )));
+        }
+        self.delete(from).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        collections::{BTreeMap, HashMap},
+        net::TcpListener,
+        sync::{Arc, Mutex},
+    };
+
+    use axum::{
+        body::Bytes,
+        extract::{Path, Query, State},
+        http::{HeaderMap, StatusCode},
+        response::IntoResponse,
+        routing::get,
+        Router,
+    };
+    use sha2::{Digest, Sha256};
+
+    use crate::{
+        configuration::S3Settings,
+        services::images::ports::outgoing::blob_store_port::{BlobStoreError, BlobStorePort},
+    };
+
+    use super::{signing_key, uri_encode, S3BlobStore};
+
+    type Objects = Arc<Mutex<BTreeMap<String, Vec<u8>>>>;
+
+    /// Rejects requests that are not signed with the test credentials or
+    /// whose payload does not match the signed hash.
+    fn authorized(headers: &HeaderMap, body: &[u8]) -> bool {
+        let signed = headers
+            .get("authorization")
+            .and_then(|value| value.to_str().ok())
+            .is_some_and(|value| {
+                value.starts_with("AWS4-HMAC-SHA256 Credential=minio/")
+                    && value.contains("/us-east-1/s3/aws4_request")
+            });
+        let hash = headers
+            .get("x-amz-content-sha256")
+            .and_then(|value| value.to_str().ok());
+        signed && hash == Some(hex::encode(Sha256::digest(body)).as_str())
+    }
+
+    async fn list_objects(
+        State(objects): State<Objects>,
+        Query(query): Query<HashMap<String, String>>,
+        headers: HeaderMap,
+    ) -> impl IntoResponse {
+        if !authorized(&headers, &[]) {
+            return (StatusCode::FORBIDDEN, String::new());
+        }
+        let prefix = query.get("prefix").cloned().unwrap_or_default();
+        let after = query.get("continuation-token").cloned().unwrap_or_default();
+        // One object per page, so that continuation is exercised.
+        let mut matching = objects
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(key, _)| key.starts_with(&prefix) && **key > after)
+            .map(|(key, content)| (key.clone(), content.len()))
+            .collect::<Vec<(String, usize)>>()
+            .into_iter();
+        let mut xml = "<ListBucketResult>".to_string();
+        if let Some((key, size)) = matching.next() {
+            xml.push_str(&format!(
+                "<Contents><Key>{}</Key><LastModified>2023-10-21T09:00:00.000Z</LastModified><Size>{}</Size></Contents>",
+                key.replace('&', "&amp;"),
+                size
+            ));
+            if matching.next().is_some() {
+                xml.push_str(&format!(
+                    "<IsTruncated>true</IsTruncated><NextContinuationToken>{}</NextContinuationToken>",
+                    key.replace('&', "&amp;")
+                ));
+            }
+        }
+        xml.push_str("</ListBucketResult>");
+        (StatusCode::OK, xml)
+    }
+
+    async fn get_object(
+        State(objects): State<Objects>,
+        Path((_bucket, key)): Path<(String, String)>,
+        headers: HeaderMap,
+    ) -> impl IntoResponse {
+        if !authorized(&headers, &[]) {
+            return (StatusCode::FORBIDDEN, vec![]);
+        }
+        match objects.lock().unwrap().get(&key) {
+            Some(content) => (StatusCode::OK, content.clone()),
+            None => (StatusCode::NOT_FOUND, vec![]),
+        }
+    }
+
+    async fn put_object(
+        State(objects): State<Objects>,
+        Path((bucket, key)): Path<(String, String)>,
+        headers: HeaderMap,
+        body: Bytes,
+    ) -> StatusCode {
+        if !authorized(&headers, &body) {
+            return StatusCode::FORBIDDEN;
+        }
+        let mut objects = objects.lock().unwrap();
+        let content = match headers.get("x-amz-copy-source") {
+            Some(source) => {
+                let source = source.to_str().unwrap();
+                let source = source.strip_prefix(&format!("/{}/", bucket)).unwrap();
+                match objects.get(source) {
+                    Some(content) => content.clone(),
+                    None => return StatusCode::NOT_FOUND,
+                }
+            }
+            None => body.to_vec(),
+        };
+        objects.insert(key, content);
+        StatusCode::OK
+    }
+
+    async fn delete_object(
+        State(objects): State<Objects>,
+        Path((_bucket, key)): Path<(String, String)>,
+        headers: HeaderMap,
+    ) -> StatusCode {
+        if !authorized(&headers, &[]) {
+            return StatusCode::FORBIDDEN;
+        }
+        objects.lock().unwrap().remove(&key);
+        StatusCode::NO_CONTENT
+    }
+
+    /// A bucket in memory answering the handful of S3 calls the store
+    /// makes, like a local MinIO would.
+    fn stand_in() -> (S3BlobStore, Objects) {
+        let objects = Objects::default();
+        let router = Router::new()
+            .route("/:bucket", get(list_objects))
+            .route(
+                "/:bucket/*key",
+                get(get_object).put(put_object).delete(delete_object),
+            )
+            .with_state(objects.clone());
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(
+            axum::Server::from_tcp(listener)
+                .unwrap()
+                .serve(router.into_make_service()),
+        );
+        let store = S3BlobStore::new(S3Settings {
+            endpoint: format!("http://{}", address),
+            bucket: "images".to_string(),
+            region: "us-east-1".to_string(),
+            access_key: "minio".to_string(),
+            secret_key: "minio123".to_string(),
+        });
+        (store, objects)
+    }
+
+    #[test]
+    fn test_signing_key() {
+        // Example from the AWS signature version 4 documentation.
+        let key = signing_key(
+            "wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY",
+            "20120215",
+            "us-east-1",
+            "iam",
+        );
+        assert_eq!(
+            hex::encode(key),
+            "f4780e2d9f65fa895f9c67b32ce1baf0b0d8a43505a000a1a9e090d414db404d"
+        );
+    }
+
+    #[test]
+    fn test_uri_encode() {
+        assert_eq!(uri_encode("data/a b+c.qoi", true), "data/a%20b%2Bc.qoi");
+        assert_eq!(uri_encode("data/", false), "data%2F");
+    }
+
+    #[tokio::test]
+    async fn test_put_get_and_delete_object() {
+        let (store, objects) = stand_in();
+        store
+            .put("data/some.qoi", b"some content".to_vec())
+            .await
+            .unwrap();
+        assert_eq!(
+            objects.lock().unwrap().get("data/some.qoi").unwrap(),
+            b"some content"
+        );
+        assert_eq!(store.read("data/some.qoi").await.unwrap(), b"some content");
+        assert_eq!(store.size("data/some.qoi").await.unwrap(), 12);
+        assert!(store.exists("data/some.qoi").await.unwrap());
+        store.delete("data/some.qoi").await.unwrap();
+        assert!(!store.exists("data/some.qoi").await.unwrap());
+        assert_eq!(
+            store.read("data/some.qoi").await,
+            Err(BlobStoreError::NotFound)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_list_follows_continuation_and_rename_copies() {
+        let (store, objects) = stand_in();
+        for key in [
+            "data/a.qoi",
+            "data/b&c.qoi",
+            "data/d/e.qoi",
+            "database/f.qoi",
+        ] {
+            store.put(key, b"some content".to_vec()).await.unwrap();
+        }
+        let keys = store
+            .list("data")
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|blob| blob.key)
+            .collect::<Vec<String>>();
+        assert_eq!(keys, vec!["data/a.qoi", "data/b&c.qoi", "data/d/e.qoi"]);
+        store
+            .rename("data/a.qoi", "quarantine/a.qoi")
+            .await
+            .unwrap();
+        assert!(!objects.lock().unwrap().contains_key("data/a.qoi"));
+        assert!(objects.lock().unwrap().contains_key("quarantine/a.qoi"));
+    }
+}
@@ -0,0 +1,1690 @@
+use std::{
+    cmp::{Ordering, Reverse},
+    collections::{BTreeMap, BTreeSet, HashSet},
+};
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use tokio::sync::Mutex;
+use tracing::error;
+
+use crate::services::images::{
+    domain::{
+        album::{Album, AlbumUpdate},
+        api_key::{ApiKey, Scope},
+        consistency::ImageFile,
+        cursor::Cursor,
+        exif::ExifData,
+        image::Image,
+        image_metadata::ImageMetadata,
+        image_status::ImageStatus,
+        rendition::{Rendition, RenditionSize},
+        search::{SearchQuery, Shape},
+        share::Share,
+        sort_key::{SortDirection, SortKey},
+        storage_layout::ImagePaths,
+        tag::{Tag, TagMatch},
+        user::{current_user_id, User},
+    },
+    ports::outgoing::{
+        albums_port::{AlbumsError, AlbumsPort},
+        api_keys_port::{ApiKeysError, ApiKeysPort},
+        batch_delete_image_port::{BatchDeleteError, BatchDeleteImagePort},
+        batch_query_image_port::{self, BatchQueryImagesPort},
+        consistency_port::{ConsistencyError, ConsistencyPort},
+        delete_image_port::{DeleteImageError, DeleteImagePort, RemoveFiles},
+        insert_image_port::{InsertImageError, InsertImagePort, Persist},
+        layout_port::{LayoutError, LayoutPort},
+        query_image_by_hash_port::QueryImageByHashPort,
+        query_image_port::{self, QueryImagePort},
+        rendition_port::RenditionPort,
+        scrub_port::{ScrubError, ScrubPort},
+        search_images_port::SearchImagesPort,
+        shares_port::{SharesError, SharesPort},
+        similar_images_port::SimilarImagesPort,
+        tags_port::{TagsError, TagsPort},
+        trash_port::{TrashError, TrashPort},
+        users_port::{UsersError, UsersPort},
+    },
+};
+
+/// Metadata kept in the process, for tests and demos that should run
+/// without a database file. Everything is lost on restart.
+///
+/// Writes hold the lock while `persist` runs, so a failed persist leaves the
+/// tables as they were, like a rolled back transaction.
+#[derive(Default)]
+pub struct ImagesInMemoryDS {
+    tables: Mutex<Tables>,
+}
+
+#[derive(Default)]
+struct Tables {
+    last_image_id: i64,
+    images: BTreeMap<i64, ImageRow>,
+    /// In insertion order, like the rows of the `renditions` table.
+    renditions: Vec<Rendition>,
+    /// Tag names and the images carrying them. Names stay after their last
+    /// image is deleted, as they do in the `tags` table.
+    tags: BTreeMap<String, BTreeSet<i64>>,
+    last_album_id: i64,
+    albums: BTreeMap<i64, AlbumRow>,
+    last_api_key_id: i64,
+    /// Keys by hash.
+    api_keys: BTreeMap<String, ApiKey>,
+    last_user_id: i64,
+    /// Users by name, with their password hash.
+    users: BTreeMap<String, (User, String)>,
+    /// User ids and expiry by token hash.
+    sessions: BTreeMap<String, (i64, DateTime<Utc>)>,
+    last_share_id: i64,
+    shares: BTreeMap<i64, Share>,
+}
+
+struct ImageRow {
+    id: i64,
+    path: String,
+    updated_on: DateTime<Utc>,
+    created_on: DateTime<Utc>,
+    hash: Option<String>,
+    perceptual_hash: Option<u64>,
+    metadata: Option<ImageMetadata>,
+    original_path: Option<String>,
+    exif: Option<ExifData>,
+    deleted_at: Option<DateTime<Utc>>,
+    status: ImageStatus,
+    checksum: Option<String>,
+    checked_on: Option<DateTime<Utc>>,
+    owner_id: Option<i64>,
+}
+
+struct AlbumRow {
+    owner_id: Option<i64>,
+    name: String,
+    parent_id: Option<i64>,
+    cover_image_id: Option<i64>,
+    created_on: DateTime<Utc>,
+    /// Member images by position.
+    images: Vec<i64>,
+}
+
+impl ImageRow {
+    fn new(id: i64, image: &Image) -> Self {
+        Self {
+            id,
+            path: image.path().to_string(),
+            updated_on: image.updated_on(),
+            created_on: image.created_on(),
+            hash: image.hash().map(str::to_string),
+            perceptual_hash: image.perceptual_hash(),
+            metadata: image.metadata().cloned(),
+            original_path: image.original_path().map(str::to_string),
+            exif: image.exif().cloned(),
+            deleted_at: None,
+            status: ImageStatus::default(),
+            checksum: image.checksum().map(str::to_string),
+            checked_on: None,
+            owner_id: current_user_id(),
+        }
+    }
+
+    fn is_live(&self) -> bool {
+        self.deleted_at.is_none()
+    }
+
+    fn is_owned(&self) -> bool {
+        owned(self.owner_id)
+    }
+
+    fn captured_on(&self) -> Option<DateTime<Utc>> {
+        self.exif.as_ref().and_then(|exif| exif.captured_on())
+    }
+
+    fn width(&self) -> Option<u32> {
+        self.metadata.as_ref().map(|metadata| metadata.width())
+    }
+
+    fn height(&self) -> Option<u32> {
+        self.metadata.as_ref().map(|metadata| metadata.height())
+    }
+
+    fn to_image(&self) -> Image {
+        let mut image = Image::new(self.id, self.path.clone(), self.updated_on)
+            .with_created_on(self.created_on)
+            .with_status(self.status);
+        if let Some(hash) = &self.hash {
+            image = image.with_hash(hash.clone());
+        }
+        if let Some(perceptual_hash) = self.perceptual_hash {
+            image = image.with_perceptual_hash(perceptual_hash);
+        }
+        if let Some(metadata) = &self.metadata {
+            image = image.with_metadata(metadata.clone());
+        }
+        if let Some(original_path) = &self.original_path {
+            image = image.with_original_path(original_path.clone());
+        }
+        if let Some(exif) = &self.exif {
+            image = image.with_exif(exif.clone());
+        }
+        if let Some(deleted_at) = self.deleted_at {
+            image = image.with_deleted_at(deleted_at);
+        }
+        if let Some(checksum) = &self.checksum {
+            image = image.with_checksum(checksum.clone());
+        }
+        image
+    }
+
+    fn to_image_file(&self) -> ImageFile {
+        ImageFile::new(self.id, self.path.clone(), self.status)
+    }
+}
+
+/// Same order as `records::order_by`; images without a capture date come
+/// last in either direction.
+fn compare(sort: SortKey, direction: SortDirection, a: &ImageRow, b: &ImageRow) -> Ordering {
+    let ordering = match sort {
+        SortKey::UpdatedOn => (a.updated_on, a.id).cmp(&(b.updated_on, b.id)),
+        SortKey::CreatedOn => (a.created_on, a.id).cmp(&(b.created_on, b.id)),
+        SortKey::CapturedOn => match (a.captured_on(), b.captured_on()) {
+            (Some(_), None) => return Ordering::Less,
+            (None, Some(_)) => return Ordering::Greater,
+            (captured_a, captured_b) => {
+                (captured_a, a.updated_on, a.id).cmp(&(captured_b, b.updated_on, b.id))
+            }
+        },
+    };
+    match direction {
+        SortDirection::Ascending => ordering,
+        SortDirection::Descending => ordering.reverse(),
+    }
+}
+
+/// Rows are visible to their owner, and all of them when no user is acting.
+fn owned(owner_id: Option<i64>) -> bool {
+    current_user_id().is_none_or(|user_id| owner_id == Some(user_id))
+}
+
+/// A bound on a missing value excludes the image, as NULL does in SQL.
+fn within<T: PartialOrd>(value: Option<T>, min: Option<T>, max: Option<T>) -> bool {
+    let above = match &min {
+        Some(min) => value.as_ref().is_some_and(|value| value >= min),
+        None => true,
+    };
+    let below = match &max {
+        Some(max) => value.as_ref().is_some_and(|value| value <= max),
+        None => true,
+    };
+    above && below
+}
+
+fn page<T>(items: impl Iterator<Item = T>, count: i64, offset: i64) -> Vec<T> {
+    items
+        .skip(offset.max(0) as usize)
+        .take(count.max(0) as usize)
+        .collect()
+}
+
+impl Tables {
+    fn live_images(&self) -> impl Iterator<Item = &ImageRow> {
+        self.images
+            .values()
+            .filter(|row| row.is_live() && row.is_owned())
+    }
+
+    fn live_image(&self, id: i64) -> Option<&ImageRow> {
+        self.images
+            .get(&id)
+            .filter(|row| row.is_live() && row.is_owned())
+    }
+
+    fn sorted(mut rows: Vec<&ImageRow>, sort: SortKey, direction: SortDirection) -> Vec<&ImageRow> {
+        rows.sort_by(|a, b| compare(sort, direction, a, b));
+        rows
+    }
+
+    /// Images with any, or all, of `tags`.
+    fn tagged(&self, tags: &[String], tag_match: TagMatch) -> HashSet<i64> {
+        let requested = tags.iter().collect::<BTreeSet<&String>>();
+        let mut matches = BTreeMap::<i64, usize>::new();
+        for tag in requested {
+            for image_id in self.tags.get(tag).into_iter().flatten() {
+                *matches.entry(*image_id).or_default() += 1;
+            }
+        }
+        matches
+            .into_iter()
+            .filter(|(_, count)| tag_match == TagMatch::Any || *count == tags.len())
+            .map(|(image_id, _)| image_id)
+            .collect()
+    }
+
+    fn image_tags(&self, image_id: i64) -> Vec<String> {
+        self.tags
+            .iter()
+            .filter(|(_, images)| images.contains(&image_id))
+            .map(|(name, _)| name.clone())
+            .collect()
+    }
+
+    /// The id the image gets, unless it clashes with a stored one.
+    fn next_image_id(&self, image: &Image) -> Result<i64, InsertImageError> {
+        // An id of 0 takes the next free one.
+        let id = match image.id() {
+            0 => self.last_image_id + 1,
+            id => id,
+        };
+        let clash = self.images.contains_key(&id)
+            || self.images.values().any(|row| {
+                row.path == image.path()
+                    || (row.hash.is_some()
+                        && row.hash.as_deref() == image.hash()
+                        && row.owner_id == current_user_id())
+            });
+        match clash {
+            true => Err(InsertImageError::AlreadyExists),
+            false => Ok(id),
+        }
+    }
+
+    fn insert_image(&mut self, id: i64, image: &Image) {
+        self.last_image_id = self.last_image_id.max(id);
+        self.images.insert(id, ImageRow::new(id, image));
+    }
+
+    /// Drops the image with everything referring to it, returning the row
+    /// and the paths of its renditions.
+    fn remove_image(&mut self, id: i64) -> Option<(ImageRow, Vec<String>)> {
+        if !self.images.get(&id)?.is_owned() {
+            return None;
+        }
+        let row = self.images.remove(&id)?;
+        let mut renditions = vec![];
+        self.renditions.retain(|rendition| {
+            if rendition.image_id() != id {
+                return true;
+            }
+            renditions.push(rendition.path().to_string());
+            false
+        });
+        for images in self.tags.values_mut() {
+            images.remove(&id);
+        }
+        self.shares.retain(|_, share| share.image_id() != id);
+        for album in self.albums.values_mut() {
+            album.images.retain(|image_id| *image_id != id);
+            if album.cover_image_id == Some(id) {
+                album.cover_image_id = None;
+            }
+        }
+        Some((row, renditions))
+    }
+
+    /// The paths of every file the image uses, with the original first.
+    fn image_paths(&self, id: i64) -> Option<Vec<String>> {
+        let row = self.images.get(&id).filter(|row| row.is_owned())?;
+        let mut paths = vec![row.path.clone()];
+        paths.extend(row.original_path.clone());
+        paths.extend(
+            self.renditions
+                .iter()
+                .filter(|rendition| rendition.image_id() == id)
+                .map(|rendition| rendition.path().to_string()),
+        );
+        Some(paths)
+    }
+
+    /// The images moved to the trash before `before`.
+    fn trashed_before(&self, before: DateTime<Utc>) -> Vec<i64> {
+        self.images
+            .values()
+            .filter(|row| {
+                row.is_owned()
+                    && row
+                        .deleted_at
+                        .is_some_and(|deleted_at| deleted_at <= before)
+            })
+            .map(|row| row.id)
+            .collect()
+    }
+
+    /// Deletes the images, returning the paths of every file they used.
+    fn remove_images(&mut self, indexes: &[i64]) -> Vec<String> {
+        let mut paths = vec![];
+        let mut renditions = vec![];
+        let mut originals = vec![];
+        for id in indexes {
+            if let Some((row, rendition_paths)) = self.remove_image(*id) {
+                paths.push(row.path);
+                renditions.extend(rendition_paths);
+                originals.extend(row.original_path);
+            }
+        }
+        paths.extend(renditions);
+        paths.extend(originals);
+        paths
+    }
+
+    fn album(&self, id: i64) -> Result<Album, AlbumsError> {
+        let row = self
+            .albums
+            .get(&id)
+            .filter(|row| owned(row.owner_id))
+            .ok_or(AlbumsError::AlbumNotFound)?;
+        let image_count = row
+            .images
+            .iter()
+            .filter(|image_id| self.live_image(**image_id).is_some())
+            .count();
+        let mut album =
+            Album::new(id, row.name.clone(), row.created_on).with_image_count(image_count as i64);
+        if let Some(parent_id) = row.parent_id {
+            album = album.with_parent_id(parent_id);
+        }
+        if let Some(cover_image_id) = row.cover_image_id {
+            album = album.with_cover_image_id(cover_image_id);
+        }
+        Ok(album)
+    }
+
+    fn album_row(&mut self, id: i64) -> Result<&mut AlbumRow, AlbumsError> {
+        self.albums
+            .get_mut(&id)
+            .filter(|row| owned(row.owner_id))
+            .ok_or(AlbumsError::AlbumNotFound)
+    }
+
+    /// The album and every album nested below it.
+    fn album_subtree(&self, id: i64) -> Vec<i64> {
+        let mut subtree = vec![id];
+        let mut next = 0;
+        while let Some(parent_id) = subtree.get(next).copied() {
+            subtree.extend(
+                self.albums
+                    .iter()
+                    .filter(|(_, album)| album.parent_id == Some(parent_id))
+                    .map(|(id, _)| *id),
+            );
+            next += 1;
+        }
+        subtree
+    }
+
+    fn trash_images(&mut self, indexes: &[i64], deleted_at: DateTime<Utc>) -> u64 {
+        let mut trashed = 0;
+        for id in indexes {
+            if let Some(row) = self
+                .images
+                .get_mut(id)
+                .filter(|row| row.is_live() && row.is_owned())
+            {
+                row.deleted_at = Some(deleted_at);
+                trashed += 1;
+            }
+        }
+        trashed
+    }
+}
+
+#[async_trait]
+impl QueryImagePort for ImagesInMemoryDS {
+    async fn query_image(&self, index: i64) -> Result<Image, query_image_port::QueryError> {
+        self.tables
+            .lock()
+            .await
+            .live_image(index)
+            .map(ImageRow::to_image)
+            .ok_or(query_image_port::QueryError::RecordNotFound)
+    }
+}
+#[async_trait]
+impl QueryImageByHashPort for ImagesInMemoryDS {
+    async fn query_image_by_hash(&self, hash: &str) -> Result<Image, query_image_port::QueryError> {
+        self.tables
+            .lock()
+            .await
+            .images
+            .values()
+            .find(|row| row.hash.as_deref() == Some(hash) && row.is_owned())
+            .map(ImageRow::to_image)
+            .ok_or(query_image_port::QueryError::RecordNotFound)
+    }
+}
+#[async_trait]
+impl BatchQueryImagesPort for ImagesInMemoryDS {
+    async fn query_images(
+        &self,
+        count: i64,
+        offset: i64,
+        sort: SortKey,
+    ) -> Result<Vec<Image>, batch_query_image_port::QueryError> {
+        let tables = self.tables.lock().await;
+        let rows = Tables::sorted(
+            tables.live_images().collect(),
+            sort,
+            SortDirection::Ascending,
+        );
+        Ok(page(
+            rows.into_iter().map(ImageRow::to_image),
+            count,
+            offset,
+        ))
+    }
+
+    async fn query_images_after(
+        &self,
+        cursor: Option<Cursor>,
+        count: i64,
+    ) -> Result<Vec<Image>, batch_query_image_port::QueryError> {
+        let tables = self.tables.lock().await;
+        let rows = tables
+            .live_images()
+            .filter(|row| match cursor {
+                Some(cursor) => (row.updated_on, row.id) > (cursor.updated_on(), cursor.id()),
+                None => true,
+            })
+            .collect();
+        let rows = Tables::sorted(rows, SortKey::UpdatedOn, SortDirection::Ascending);
+        Ok(page(rows.into_iter().map(ImageRow::to_image), count, 0))
+    }
+
+    async fn query_images_by_tags(
+        &self,
+        tags: &[String],
+        tag_match: TagMatch,
+        count: i64,
+        offset: i64,
+        sort: SortKey,
+    ) -> Result<Vec<Image>, batch_query_image_port::QueryError> {
+        let tables = self.tables.lock().await;
+        let tagged = tables.tagged(tags, tag_match);
+        let rows = tables
+            .live_images()
+            .filter(|row| tagged.contains(&row.id))
+            .collect();
+        let rows = Tables::sorted(rows, sort, SortDirection::Ascending);
+        Ok(page(
+            rows.into_iter().map(ImageRow::to_image),
+            count,
+            offset,
+        ))
+    }
+}
+#[async_trait]
+impl AlbumsPort for ImagesInMemoryDS {
+    async fn create_album(&self, name: &str, parent_id: Option<i64>) -> Result<Album, AlbumsError> {
+        let mut tables = self.tables.lock().await;
+        if let Some(parent_id) = parent_id {
+            tables.album_row(parent_id)?;
+        }
+        tables.last_album_id += 1;
+        let id = tables.last_album_id;
+        tables.albums.insert(
+            id,
+            AlbumRow {
+                owner_id: current_user_id(),
+                name: name.to_string(),
+                parent_id,
+                cover_image_id: None,
+                created_on: Utc::now(),
+                images: vec![],
+            },
+        );
+        tables.album(id)
+    }
+
+    async fn query_album(&self, id: i64) -> Result<Album, AlbumsError> {
+        self.tables.lock().await.album(id)
+    }
+
+    async fn query_albums(&self, parent_id: Option<i64>) -> Result<Vec<Album>, AlbumsError> {
+        let tables = self.tables.lock().await;
+        let mut albums = tables
+            .albums
+            .iter()
+            .filter(|(_, album)| album.parent_id == parent_id && owned(album.owner_id))
+            .map(|(id, _)| tables.album(*id))
+            .collect::<Result<Vec<Album>, AlbumsError>>()?;
+        // Ids are already ascending, the stable sort keeps them so per name.
+        albums.sort_by(|a, b| a.name().cmp(b.name()));
+        Ok(albums)
+    }
+
+    async fn update_album(&self, id: i64, update: &AlbumUpdate) -> Result<Album, AlbumsError> {
+        let mut tables = self.tables.lock().await;
+        let album = tables
+            .albums
+            .get(&id)
+            .filter(|album| owned(album.owner_id))
+            .ok_or(AlbumsError::AlbumNotFound)?;
+        if let Some(Some(cover_image_id)) = update.cover_image_id {
+            if !album.images.contains(&cover_image_id) {
+                return Err(AlbumsError::ImageNotFound);
+            }
+        }
+        if let Some(Some(parent_id)) = update.parent_id {
+            // Moving an album below one of its own descendants would detach
+            // the whole branch into a cycle.
+            let mut ancestor = Some(parent_id);
+            while let Some(ancestor_id) = ancestor {
+                if ancestor_id == id {
+                    return Err(AlbumsError::InvalidParent);
+                }
+                ancestor = tables
+                    .albums
+                    .get(&ancestor_id)
+                    .filter(|album| owned(album.owner_id))
+                    .ok_or(AlbumsError::AlbumNotFound)?
+                    .parent_id;
+            }
+        }
+        let album = tables.album_row(id)?;
+        if let Some(name) = &update.name {
+            album.name = name.clone();
+        }
+        if let Some(parent_id) = update.parent_id {
+            album.parent_id = parent_id;
+        }
+        if let Some(cover_image_id) = update.cover_image_id {
+            album.cover_image_id = cover_image_id;
+        }
+        tables.album(id)
+    }
+
+    async fn delete_album(&self, id: i64, cascade: bool) -> Result<(), AlbumsError> {
+        let mut tables = self.tables.lock().await;
+        tables.album_row(id)?;
+        let subtree = tables.album_subtree(id);
+        if cascade {
+            let images = subtree
+                .iter()
+                .flat_map(|album_id| tables.albums[album_id].images.clone())
+                .collect::<Vec<i64>>();
+            tables.trash_images(&images, Utc::now());
+        }
+        for album_id in subtree {
+            tables.albums.remove(&album_id);
+        }
+        Ok(())
+    }
+
+    async fn add_images(&self, album_id: i64, image_ids: &[i64]) -> Result<(), AlbumsError> {
+        let mut tables = self.tables.lock().await;
+        tables.album_row(album_id)?;
+        if image_ids
+            .iter()
+            .any(|image_id| tables.live_image(*image_id).is_none())
+        {
+            return Err(AlbumsError::ImageNotFound);
+        }
+        let album = tables.album_row(album_id)?;
+        for image_id in image_ids {
+            if !album.images.contains(image_id) {
+                album.images.push(*image_id);
+            }
+        }
+        Ok(())
+    }
+
+    async fn remove_image(&self, album_id: i64, image_id: i64) -> Result<(), AlbumsError> {
+        let mut tables = self.tables.lock().await;
+        let album = tables.album_row(album_id)?;
+        let position = album
+            .images
+            .iter()
+            .position(|member| *member == image_id)
+            .ok_or(AlbumsError::ImageNotFound)?;
+        album.images.remove(position);
+        if album.cover_image_id == Some(image_id) {
+            album.cover_image_id = None;
+        }
+        Ok(())
+    }
+
+    async fn reorder_images(&self, album_id: i64, image_ids: &[i64]) -> Result<(), AlbumsError> {
+        let mut tables = self.tables.lock().await;
+        let album = tables.album_row(album_id)?;
+        let mut members = album.images.clone();
+        let mut requested = image_ids.to_vec();
+        members.sort_unstable();
+        requested.sort_unstable();
+        if members != requested {
+            return Err(AlbumsError::InvalidOrder);
+        }
+        album.images = image_ids.to_vec();
+        Ok(())
+    }
+
+    async fn query_album_images(
+        &self,
+        album_id: i64,
+        count: i64,
+        offset: i64,
+    ) -> Result<Vec<Image>, AlbumsError> {
+        let tables = self.tables.lock().await;
+        let album = tables
+            .albums
+            .get(&album_id)
+            .filter(|album| owned(album.owner_id))
+            .ok_or(AlbumsError::AlbumNotFound)?;
+        let images = album
+            .images
+            .iter()
+            .filter_map(|image_id| tables.live_image(*image_id))
+            .map(ImageRow::to_image);
+        Ok(page(images, count, offset))
+    }
+}
+#[async_trait]
+impl SearchImagesPort for ImagesInMemoryDS {
+    async fn search_images(
+        &self,
+        query: &SearchQuery,
+    ) -> Result<Vec<Image>, query_image_port::QueryError> {
+        let tables = self.tables.lock().await;
+        let tagged = (!query.tags.is_empty()).then(|| tables.tagged(&query.tags, query.tag_match));
+        let rows = tables
+            .live_images()
+            .filter(|row| {
+                within(
+                    Some(row.created_on),
+                    query.created_after,
+                    query.created_before,
+                ) && within(
+                    row.captured_on(),
+                    query.captured_after,
+                    query.captured_before,
+                ) && within(row.width(), query.min_width, query.max_width)
+                    && within(row.height(), query.min_height, query.max_height)
+            })
+            .filter(|row| match (query.shape, row.width(), row.height()) {
+                (None, _, _) => true,
+                (Some(Shape::Landscape), Some(width), Some(height)) => width > height,
+                (Some(Shape::Portrait), Some(width), Some(height)) => width < height,
+                (Some(Shape::Square), Some(width), Some(height)) => width == height,
+                _ => false,
+            })
+            .filter(|row| match &query.original_format {
+                Some(original_format) => row
+                    .metadata
+                    .as_ref()
+                    .is_some_and(|metadata| metadata.original_format() == original_format),
+                None => true,
+            })
+            .filter(|row| match &tagged {
+                Some(tagged) => tagged.contains(&row.id),
+                None => true,
+            })
+            .collect();
+        let rows = Tables::sorted(rows, query.sort, query.direction);
+        Ok(page(
+            rows.into_iter().map(ImageRow::to_image),
+            query.count,
+            query.offset,
+        ))
+    }
+}
+#[async_trait]
+impl TagsPort for ImagesInMemoryDS {
+    async fn add_tags(&self, image_id: i64, tags: &[String]) -> Result<Vec<String>, TagsError> {
+        let mut tables = self.tables.lock().await;
+        if tables.live_image(image_id).is_none() {
+            return Err(TagsError::ImageNotFound);
+        }
+        for tag in tags {
+            tables.tags.entry(tag.clone()).or_default().insert(image_id);
+        }
+        Ok(tables.image_tags(image_id))
+    }
+
+    async fn remove_tag(&self, image_id: i64, tag: &str) -> Result<(), TagsError> {
+        let mut tables = self.tables.lock().await;
+        if !tables.images.get(&image_id).is_some_and(ImageRow::is_owned) {
+            return Err(TagsError::TagNotFound);
+        }
+        let images = tables.tags.get_mut(tag).ok_or(TagsError::TagNotFound)?;
+        if !images.remove(&image_id) {
+            return Err(TagsError::TagNotFound);
+        }
+        // Tags left without images are dropped.
+        if images.is_empty() {
+            tables.tags.remove(tag);
+        }
+        Ok(())
+    }
+
+    async fn query_tags(&self) -> Result<Vec<Tag>, TagsError> {
+        let tables = self.tables.lock().await;
+        Ok(tables
+            .tags
+            .iter()
+            .map(|(name, images)| {
+                let count = images
+                    .iter()
+                    .filter(|image_id| tables.live_image(**image_id).is_some())
+                    .count();
+                Tag::new(name.clone(), count as i64)
+            })
+            .filter(|tag| tag.count() > 0)
+            .collect())
+    }
+
+    async fn rename_tag(&self, from: &str, to: &str) -> Result<(), TagsError> {
+        let mut tables = self.tables.lock().await;
+        let mut images = tables.tags.remove(from).ok_or(TagsError::TagNotFound)?;
+        // Tag names are shared between users, on behalf of one only their
+        // images move.
+        let kept = images
+            .iter()
+            .copied()
+            .filter(|image_id| !tables.images.get(image_id).is_some_and(ImageRow::is_owned))
+            .collect::<BTreeSet<i64>>();
+        if kept.len() == images.len() {
+            tables.tags.insert(from.to_string(), images);
+            return Err(TagsError::TagNotFound);
+        }
+        if !kept.is_empty() {
+            images.retain(|image_id| !kept.contains(image_id));
+            tables.tags.insert(from.to_string(), kept);
+        }
+        // Renaming onto an existing tag merges the two.
+        tables
+            .tags
+            .entry(to.to_string())
+            .or_default()
+            .extend(images);
+        Ok(())
+    }
+}
+#[async_trait]
+impl SimilarImagesPort for ImagesInMemoryDS {
+    async fn query_perceptual_hashes(
+        &self,
+        after: i64,
+    ) -> Result<Vec<(i64, u64)>, query_image_port::QueryError> {
+        let tables = self.tables.lock().await;
+        Ok(tables
+            .images
+            .values()
+            .filter(|row| row.id > after)
+            .filter_map(|row| Some((row.id, row.perceptual_hash?)))
+            .collect())
+    }
+
+    async fn query_images_by_ids(
+        &self,
+        indexes: Vec<i64>,
+    ) -> Result<Vec<Image>, query_image_port::QueryError> {
+        let tables = self.tables.lock().await;
+        Ok(tables
+            .live_images()
+            .filter(|row| indexes.contains(&row.id))
+            .map(ImageRow::to_image)
+            .collect())
+    }
+}
+#[async_trait]
+impl DeleteImagePort for ImagesInMemoryDS {
+    async fn delete_image(&self, index: i64) -> Result<Vec<String>, DeleteImageError> {
+        let (row, renditions) = self
+            .tables
+            .lock()
+            .await
+            .remove_image(index)
+            .ok_or(DeleteImageError::RecordNotFound)?;
+        let mut paths = vec![row.path];
+        paths.extend(row.original_path);
+        paths.extend(renditions);
+        Ok(paths)
+    }
+    async fn delete_image_with(
+        &self,
+        index: i64,
+        remove: RemoveFiles,
+    ) -> Result<Vec<String>, DeleteImageError> {
+        let mut tables = self.tables.lock().await;
+        let paths = tables
+            .image_paths(index)
+            .ok_or(DeleteImageError::RecordNotFound)?;
+        if let Err(e) = remove(paths.clone()).await {
+            error!("Error deleting image {}; message: {}", index, e.to_string());
+            return Err(DeleteImageError::InternalError);
+        }
+        tables.remove_image(index);
+        Ok(paths)
+    }
+}
+#[async_trait]
+impl BatchDeleteImagePort for ImagesInMemoryDS {
+    async fn batch_delete_image(&self, indexes: Vec<i64>) -> Result<Vec<String>, BatchDeleteError> {
+        Ok(self.tables.lock().await.remove_images(&indexes))
+    }
+}
+#[async_trait]
+impl TrashPort for ImagesInMemoryDS {
+    async fn trash_images(
+        &self,
+        indexes: &[i64],
+        deleted_at: DateTime<Utc>,
+    ) -> Result<u64, TrashError> {
+        Ok(self.tables.lock().await.trash_images(indexes, deleted_at))
+    }
+
+    async fn restore_image(&self, index: i64) -> Result<(), TrashError> {
+        let mut tables = self.tables.lock().await;
+        let row = tables
+            .images
+            .get_mut(&index)
+            .filter(|row| !row.is_live() && row.is_owned())
+            .ok_or(TrashError::ImageNotFound)?;
+        row.deleted_at = None;
+        Ok(())
+    }
+
+    async fn query_trash(&self, count: i64, offset: i64) -> Result<Vec<Image>, TrashError> {
+        let tables = self.tables.lock().await;
+        let mut rows = tables
+            .images
+            .values()
+            .filter(|row| !row.is_live() && row.is_owned())
+            .collect::<Vec<&ImageRow>>();
+        rows.sort_by_key(|row| Reverse((row.deleted_at, row.id)));
+        Ok(page(
+            rows.into_iter().map(ImageRow::to_image),
+            count,
+            offset,
+        ))
+    }
+
+    async fn purge_trash(&self, before: DateTime<Utc>) -> Result<Vec<String>, TrashError> {
+        let mut tables = self.tables.lock().await;
+        let indexes = tables.trashed_before(before);
+        Ok(tables.remove_images(&indexes))
+    }
+
+    async fn purge_trash_with(
+        &self,
+        before: DateTime<Utc>,
+        remove: RemoveFiles,
+    ) -> Result<Vec<String>, TrashError> {
+        let mut tables = self.tables.lock().await;
+        let indexes = tables.trashed_before(before);
+        if indexes.is_empty() {
+            return Ok(vec![]);
+        }
+        let paths = indexes
+            .iter()
+            .filter_map(|id| tables.image_paths(*id))
+            .flatten()
+            .collect::<Vec<String>>();
+        if let Err(e) = remove(paths).await {
+            error!(
+                "Error purging images trashed before {}; message: {}",
+                before,
+                e.to_string()
+            );
+            return Err(TrashError::InternalError);
+        }
+        Ok(tables.remove_images(&indexes))
+    }
+}
+#[async_trait]
+impl ConsistencyPort for ImagesInMemoryDS {
+    async fn query_image_files(&self) -> Result<Vec<ImageFile>, ConsistencyError> {
+        let tables = self.tables.lock().await;
+        Ok(tables
+            .images
+            .values()
+            .map(|row| {
+                let file = row.to_image_file();
+                match &row.metadata {
+                    Some(metadata) => file.with_size(metadata.stored_size()),
+                    None => file,
+                }
+            })
+            .collect())
+    }
+
+    async fn query_referenced_paths(&self) -> Result<HashSet<String>, ConsistencyError> {
+        let tables = self.tables.lock().await;
+        Ok(tables
+            .images
+            .values()
+            .flat_map(|row| std::iter::once(row.path.clone()).chain(row.original_path.clone()))
+            .chain(
+                tables
+                    .renditions
+                    .iter()
+                    .map(|rendition| rendition.path().to_string()),
+            )
+            .collect())
+    }
+
+    async fn update_image_status(
+        &self,
+        indexes: &[i64],
+        status: ImageStatus,
+    ) -> Result<(), ConsistencyError> {
+        let mut tables = self.tables.lock().await;
+        for id in indexes {
+            if let Some(row) = tables.images.get_mut(id) {
+                row.status = status;
+            }
+        }
+        Ok(())
+    }
+}
+#[async_trait]
+impl LayoutPort for ImagesInMemoryDS {
+    async fn query_image_paths(
+        &self,
+        after: i64,
+        count: i64,
+    ) -> Result<Vec<ImagePaths>, LayoutError> {
+        let tables = self.tables.lock().await;
+        let images = tables
+            .images
+            .range(after + 1..)
+            .map(|(id, row)| ImagePaths {
+                image_id: *id,
+                paths: std::iter::once(row.path.clone())
+                    .chain(row.original_path.clone())
+                    .chain(
+                        tables
+                            .renditions
+                            .iter()
+                            .filter(|rendition| rendition.image_id() == *id)
+                            .map(|rendition| rendition.path().to_string()),
+                    )
+                    .collect(),
+            });
+        Ok(page(images, count, 0))
+    }
+
+    async fn relocate_paths(
+        &self,
+        moves: &[(String, String)],
+        persist: Persist,
+    ) -> Result<(), LayoutError> {
+        let mut tables = self.tables.lock().await;
+        if let Err(e) = persist.await {
+            error!("Error relocating paths {:?}; message: {}", moves, e);
+            return Err(LayoutError::InternalError);
+        }
+        for (from, to) in moves {
+            for row in tables.images.values_mut() {
+                if row.path == *from {
+                    row.path = to.clone();
+                }
+                if row.original_path.as_ref() == Some(from) {
+                    row.original_path = Some(to.clone());
+                }
+            }
+            for rendition in tables.renditions.iter_mut() {
+                if rendition.path() == from {
+                    *rendition = Rendition::new(rendition.image_id(), rendition.size(), to.clone());
+                }
+            }
+        }
+        Ok(())
+    }
+}
+#[async_trait]
+impl ScrubPort for ImagesInMemoryDS {
+    async fn query_scrub_batch(&self, count: i64) -> Result<Vec<ImageFile>, ScrubError> {
+        let tables = self.tables.lock().await;
+        let mut rows = tables.images.values().collect::<Vec<&ImageRow>>();
+        // Never checked images first, then the ones checked longest ago.
+        rows.sort_by_key(|row| (row.checked_on.is_some(), row.checked_on, row.id));
+        let files = rows.into_iter().map(|row| {
+            let file = row.to_image_file();
+            match &row.checksum {
+                Some(checksum) => file.with_checksum(checksum.clone()),
+                None => file,
+            }
+        });
+        Ok(page(files, count, 0))
+    }
+
+    async fn record_scrub(
+        &self,
+        image_id: i64,
+        status: ImageStatus,
+        checksum: Option<String>,
+        checked_on: DateTime<Utc>,
+    ) -> Result<(), ScrubError> {
+        let mut tables = self.tables.lock().await;
+        if let Some(row) = tables.images.get_mut(&image_id) {
+            row.status = status;
+            row.checked_on = Some(checked_on);
+            // A recorded checksum is never replaced.
+            row.checksum = row.checksum.take().or(checksum);
+        }
+        Ok(())
+    }
+
+    async fn count_images_by_status(&self) -> Result<Vec<(ImageStatus, i64)>, ScrubError> {
+        let tables = self.tables.lock().await;
+        Ok(ImageStatus::ALL
+            .into_iter()
+            .map(|status| {
+                let count = tables
+                    .live_images()
+                    .filter(|row| row.status == status)
+                    .count();
+                (status, count as i64)
+            })
+            .filter(|(_, count)| *count > 0)
+            .collect())
+    }
+
+    async fn query_images_by_status(&self, status: ImageStatus) -> Result<Vec<Image>, ScrubError> {
+        let tables = self.tables.lock().await;
+        Ok(tables
+            .live_images()
+            .filter(|row| row.status == status)
+            .map(ImageRow::to_image)
+            .collect())
+    }
+}
+#[async_trait]
+impl RenditionPort for ImagesInMemoryDS {
+    async fn query_rendition(
+        &self,
+        image_id: i64,
+        size: RenditionSize,
+    ) -> Result<Rendition, query_image_port::QueryError> {
+        let tables = self.tables.lock().await;
+        if !tables.images.get(&image_id).is_some_and(ImageRow::is_owned) {
+            return Err(query_image_port::QueryError::RecordNotFound);
+        }
+        tables
+            .renditions
+            .iter()
+            .find(|rendition| rendition.image_id() == image_id && rendition.size() == size)
+            .cloned()
+            .ok_or(query_image_port::QueryError::RecordNotFound)
+    }
+
+    async fn insert_rendition(&self, rendition: &Rendition) -> Result<(), InsertImageError> {
+        let mut tables = self.tables.lock().await;
+        if !tables.images.contains_key(&rendition.image_id()) {
+            error!("Error inserting rendition {:?}; unknown image", rendition);
+            return Err(InsertImageError::InternalError);
+        }
+        let same_key = |stored: &Rendition| {
+            stored.image_id() == rendition.image_id() && stored.size() == rendition.size()
+        };
+        if tables
+            .renditions
+            .iter()
+            .any(|stored| stored.path() == rendition.path() && !same_key(stored))
+        {
+            return Err(InsertImageError::AlreadyExists);
+        }
+        match tables.renditions.iter_mut().find(|stored| same_key(stored)) {
+            Some(stored) => *stored = rendition.clone(),
+            None => tables.renditions.push(rendition.clone()),
+        }
+        Ok(())
+    }
+}
+#[async_trait]
+impl InsertImagePort for ImagesInMemoryDS {
+    async fn insert_image(&self, record: &Image) -> Result<i64, InsertImageError> {
+        let mut tables = self.tables.lock().await;
+        let id = tables.next_image_id(record)?;
+        tables.insert_image(id, record);
+        Ok(id)
+    }
+
+    async fn insert_image_with(
+        &self,
+        record: &Image,
+        persist: Persist,
+    ) -> Result<i64, InsertImageError> {
+        let mut tables = self.tables.lock().await;
+        let id = tables.next_image_id(record)?;
+        if let Err(e) = persist.await {
+            error!(
+                "Error inserting image {:?}; message: {}",
+                record,
+                e.to_string()
+            );
+            return Err(InsertImageError::InternalError);
+        }
+        tables.insert_image(id, record);
+        Ok(id)
+    }
+}
+
+#[async_trait]
+impl ApiKeysPort for ImagesInMemoryDS {
+    async fn insert_api_key(
+        &self,
+        name: &str,
+        key_hash: &str,
+        scopes: &[Scope],
+        created_on: DateTime<Utc>,
+    ) -> Result<ApiKey, ApiKeysError> {
+        let mut tables = self.tables.lock().await;
+        if tables.api_keys.contains_key(key_hash) {
+            return Err(ApiKeysError::AlreadyExists);
+        }
+        tables.last_api_key_id += 1;
+        let key = ApiKey::new(
+            tables.last_api_key_id,
+            name.to_string(),
+            scopes.to_vec(),
+            created_on,
+        );
+        tables.api_keys.insert(key_hash.to_string(), key.clone());
+        Ok(key)
+    }
+
+    async fn query_api_key_by_hash(&self, key_hash: &str) -> Result<ApiKey, ApiKeysError> {
+        self.tables
+            .lock()
+            .await
+            .api_keys
+            .get(key_hash)
+            .cloned()
+            .ok_or(ApiKeysError::KeyNotFound)
+    }
+
+    async fn query_api_keys(&self) -> Result<Vec<ApiKey>, ApiKeysError> {
+        let tables = self.tables.lock().await;
+        let mut keys = tables.api_keys.values().cloned().collect::<Vec<ApiKey>>();
+        keys.sort_by_key(ApiKey::id);
+        Ok(keys)
+    }
+
+    async fn revoke_api_key(&self, id: i64, revoked_on: DateTime<Utc>) -> Result<(), ApiKeysError> {
+        let mut tables = self.tables.lock().await;
+        let key = tables
+            .api_keys
+            .values_mut()
+            .find(|key| key.id() == id && key.revoked_on().is_none())
+            .ok_or(ApiKeysError::KeyNotFound)?;
+        *key = key.clone().with_revoked_on(revoked_on);
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl SharesPort for ImagesInMemoryDS {
+    async fn insert_share(
+        &self,
+        image_id: i64,
+        created_on: DateTime<Utc>,
+        expires_on: DateTime<Utc>,
+    ) -> Result<Share, SharesError> {
+        let mut tables = self.tables.lock().await;
+        if tables.live_image(image_id).is_none() {
+            return Err(SharesError::ImageNotFound);
+        }
+        tables.last_share_id += 1;
+        let share = Share::new(tables.last_share_id, image_id, created_on, expires_on);
+        tables.shares.insert(share.id(), share.clone());
+        Ok(share)
+    }
+
+    async fn query_share(&self, id: i64) -> Result<Share, SharesError> {
+        self.tables
+            .lock()
+            .await
+            .shares
+            .get(&id)
+            .cloned()
+            .ok_or(SharesError::ShareNotFound)
+    }
+
+    async fn revoke_share(&self, id: i64, revoked_on: DateTime<Utc>) -> Result<(), SharesError> {
+        let mut tables = self.tables.lock().await;
+        let share = tables
+            .shares
+            .get(&id)
+            .filter(|share| share.revoked_on().is_none())
+            .filter(|share| {
+                tables
+                    .images
+                    .get(&share.image_id())
+                    .is_some_and(ImageRow::is_owned)
+            })
+            .cloned()
+            .ok_or(SharesError::ShareNotFound)?;
+        tables.shares.insert(id, share.with_revoked_on(revoked_on));
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl UsersPort for ImagesInMemoryDS {
+    async fn insert_user(
+        &self,
+        name: &str,
+        password_hash: &str,
+        created_on: DateTime<Utc>,
+    ) -> Result<User, UsersError> {
+        let mut tables = self.tables.lock().await;
+        if tables.users.contains_key(name) {
+            return Err(UsersError::AlreadyExists);
+        }
+        tables.last_user_id += 1;
+        let user = User::new(tables.last_user_id, name.to_string(), created_on);
+        tables
+            .users
+            .insert(name.to_string(), (user.clone(), password_hash.to_string()));
+        Ok(user)
+    }
+
+    async fn query_user_by_name(&self, name: &str) -> Result<(User, String), UsersError> {
+        self.tables
+            .lock()
+            .await
+            .users
+            .get(name)
+            .cloned()
+            .ok_or(UsersError::UserNotFound)
+    }
+
+    async fn insert_session(
+        &self,
+        user_id: i64,
+        token_hash: &str,
+        expires_on: DateTime<Utc>,
+    ) -> Result<(), UsersError> {
+        let mut tables = self.tables.lock().await;
+        if tables.sessions.contains_key(token_hash) {
+            return Err(UsersError::AlreadyExists);
+        }
+        tables
+            .sessions
+            .insert(token_hash.to_string(), (user_id, expires_on));
+        Ok(())
+    }
+
+    async fn query_session_user(
+        &self,
+        token_hash: &str,
+        now: DateTime<Utc>,
+    ) -> Result<User, UsersError> {
+        let tables = self.tables.lock().await;
+        let (user_id, _) = tables
+            .sessions
+            .get(token_hash)
+            .filter(|(_, expires_on)| *expires_on > now)
+            .ok_or(UsersError::UserNotFound)?;
+        tables
+            .users
+            .values()
+            .find(|(user, _)| user.id() == *user_id)
+            .map(|(user, _)| user.clone())
+            .ok_or(UsersError::UserNotFound)
+    }
+
+    async fn delete_session(&self, token_hash: &str) -> Result<(), UsersError> {
+        self.tables
+            .lock()
+            .await
+            .sessions
+            .remove(token_hash)
+            .map(|_| ())
+            .ok_or(UsersError::UserNotFound)
+    }
+}
+
+impl ImagesInMemoryDS {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::images::domain::user::on_behalf_of;
+
+    fn image(id: i64, updated_on: &str) -> Image {
+        Image::new(
+            id,
+            format!("path/to/image{}", id),
+            updated_on.parse::<DateTime<Utc>>().unwrap(),
+        )
+    }
+
+    async fn repository() -> ImagesInMemoryDS {
+        let storage = ImagesInMemoryDS::new();
+        for (id, updated_on) in [
+            (1, "2023-07-12T19:29:11Z"),
+            (2, "2023-07-10T19:29:11Z"),
+            (3, "2023-07-11T19:29:11Z"),
+        ] {
+            storage.insert_image(&image(id, updated_on)).await.unwrap();
+        }
+        storage
+    }
+
+    #[tokio::test]
+    async fn test_insert_image() {
+        let repository = repository().await;
+        let id = repository
+            .insert_image(&Image::new(0, "path/to/new".to_string(), Utc::now()))
+            .await
+            .unwrap();
+        assert_eq!(id, 4);
+        assert_eq!(
+            repository.query_image(id).await.unwrap().path(),
+            "path/to/new"
+        );
+        let duplicate = repository
+            .insert_image(&Image::new(0, "path/to/image1".to_string(), Utc::now()))
+            .await;
+        assert!(matches!(duplicate, Err(InsertImageError::AlreadyExists)));
+        let failed = repository
+            .insert_image_with(
+                &Image::new(0, "path/to/failed".to_string(), Utc::now()),
+                Box::pin(async { Err(std::io::Error::other("disk full")) }),
+            )
+            .await;
+        assert!(matches!(failed, Err(InsertImageError::InternalError)));
+        assert_eq!(
+            repository
+                .query_images(10, 0, SortKey::UpdatedOn)
+                .await
+                .unwrap()
+                .len(),
+            4
+        );
+    }
+
+    #[tokio::test]
+    async fn test_query_images_order() {
+        let repository = repository().await;
+        let ids = |images: Vec<Image>| images.iter().map(Image::id).collect::<Vec<i64>>();
+        let images = repository
+            .query_images(10, 0, SortKey::UpdatedOn)
+            .await
+            .unwrap();
+        assert_eq!(ids(images), vec![2, 3, 1]);
+        let images = repository
+            .query_images(1, 1, SortKey::UpdatedOn)
+            .await
+            .unwrap();
+        assert_eq!(ids(images), vec![3]);
+        let cursor = Cursor::new("2023-07-10T19:29:11Z".parse().unwrap(), 2);
+        let images = repository
+            .query_images_after(Some(cursor), 10)
+            .await
+            .unwrap();
+        assert_eq!(ids(images), vec![3, 1]);
+        assert!(matches!(
+            repository.query_image(9).await,
+            Err(query_image_port::QueryError::RecordNotFound)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_tags() {
+        let repository = repository().await;
+        let tags = repository
+            .add_tags(1, &["sea".to_string(), "beach".to_string()])
+            .await
+            .unwrap();
+        assert_eq!(tags, vec!["beach".to_string(), "sea".to_string()]);
+        repository.add_tags(2, &["sea".to_string()]).await.unwrap();
+        assert_eq!(
+            repository.add_tags(9, &["sea".to_string()]).await,
+            Err(TagsError::ImageNotFound)
+        );
+        let images = repository
+            .query_images_by_tags(
+                &["sea".to_string(), "beach".to_string()],
+                TagMatch::All,
+                10,
+                0,
+                SortKey::UpdatedOn,
+            )
+            .await
+            .unwrap();
+        assert_eq!(images.len(), 1);
+        assert_eq!(images[0].id(), 1);
+
+        repository.rename_tag("beach", "sea").await.unwrap();
+        assert_eq!(
+            repository.query_tags().await.unwrap(),
+            vec![Tag::new("sea".to_string(), 2)]
+        );
+        assert_eq!(
+            repository.remove_tag(3, "sea").await,
+            Err(TagsError::TagNotFound)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_albums() {
+        let repository = repository().await;
+        let parent = repository.create_album("trips", None).await.unwrap();
+        let album = repository
+            .create_album("sea", Some(parent.id()))
+            .await
+            .unwrap();
+        assert_eq!(
+            repository.create_album("lost", Some(99)).await,
+            Err(AlbumsError::AlbumNotFound)
+        );
+        repository.add_images(album.id(), &[3, 1]).await.unwrap();
+        assert_eq!(
+            repository.add_images(album.id(), &[2, 9]).await,
+            Err(AlbumsError::ImageNotFound)
+        );
+        assert_eq!(
+            repository.reorder_images(album.id(), &[1]).await,
+            Err(AlbumsError::InvalidOrder)
+        );
+        repository
+            .reorder_images(album.id(), &[1, 3])
+            .await
+            .unwrap();
+        let images = repository
+            .query_album_images(album.id(), 10, 0)
+            .await
+            .unwrap();
+        assert_eq!(
+            images.iter().map(Image::id).collect::<Vec<i64>>(),
+            vec![1, 3]
+        );
+        let update = AlbumUpdate {
+            parent_id: Some(Some(album.id())),
+            ..Default::default()
+        };
+        assert_eq!(
+            repository.update_album(parent.id(), &update).await,
+            Err(AlbumsError::InvalidParent)
+        );
+
+        repository.delete_album(parent.id(), true).await.unwrap();
+        assert_eq!(
+            repository.query_album(album.id()).await,
+            Err(AlbumsError::AlbumNotFound)
+        );
+        let trash = repository.query_trash(10, 0).await.unwrap();
+        assert_eq!(trash.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_trash_and_delete() {
+        let repository = repository().await;
+        repository
+            .insert_rendition(&Rendition::new(
+                1,
+                RenditionSize::Thumb,
+                "path/to/image1_thumb.qoi".to_string(),
+            ))
+            .await
+            .unwrap();
+        let deleted_at = "2023-08-01T00:00:00Z".parse::<DateTime<Utc>>().unwrap();
+        assert_eq!(repository.trash_images(&[1, 2, 9], deleted_at).await, Ok(2));
+        assert_eq!(repository.trash_images(&[1], deleted_at).await, Ok(0));
+        repository.restore_image(2).await.unwrap();
+        assert_eq!(
+            repository.restore_image(2).await,
+            Err(TrashError::ImageNotFound)
+        );
+
+        let paths = repository.purge_trash(deleted_at).await.unwrap();
+        assert_eq!(
+            paths,
+            vec![
+                "path/to/image1".to_string(),
+                "path/to/image1_thumb.qoi".to_string()
+            ]
+        );
+        assert!(matches!(
+            repository.query_rendition(1, RenditionSize::Thumb).await,
+            Err(query_image_port::QueryError::RecordNotFound)
+        ));
+        assert_eq!(
+            repository.delete_image(2).await.unwrap(),
+            vec!["path/to/image2".to_string()]
+        );
+        assert!(matches!(
+            repository.delete_image(2).await,
+            Err(DeleteImageError::RecordNotFound)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_relocate_paths() {
+        let repository = repository().await;
+        let moves = vec![("path/to/image1".to_string(), "path/to/a/image1".to_string())];
+        let failed = repository
+            .relocate_paths(
+                &moves,
+                Box::pin(async { Err(std::io::Error::other("disk full")) }),
+            )
+            .await;
+        assert_eq!(failed, Err(LayoutError::InternalError));
+        assert_eq!(
+            repository.query_image(1).await.unwrap().path(),
+            "path/to/image1"
+        );
+        repository
+            .relocate_paths(&moves, Box::pin(async { Ok(()) }))
+            .await
+            .unwrap();
+        let paths = repository.query_image_paths(0, 1).await.unwrap();
+        assert_eq!(
+            paths,
+            vec![ImagePaths {
+                image_id: 1,
+                paths: vec!["path/to/a/image1".to_string()],
+            }]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_shares() {
+        let repository = repository().await;
+        let expires_on = Utc::now();
+        let share = repository
+            .insert_share(1, Utc::now(), expires_on)
+            .await
+            .unwrap();
+        assert_eq!(repository.query_share(share.id()).await, Ok(share.clone()));
+        assert_eq!(
+            repository.insert_share(9, Utc::now(), expires_on).await,
+            Err(SharesError::ImageNotFound)
+        );
+        repository
+            .revoke_share(share.id(), expires_on)
+            .await
+            .unwrap();
+        assert_eq!(
+            repository.revoke_share(share.id(), expires_on).await,
+            Err(SharesError::ShareNotFound)
+        );
+        repository.delete_image(1).await.unwrap();
+        assert_eq!(
+            repository.query_share(share.id()).await,
+            Err(SharesError::ShareNotFound)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_users_and_owners() {
+        let repository = repository().await;
+        let alice = repository
+            .insert_user("alice", "hash", Utc::now())
+            .await
+            .unwrap();
+        assert_eq!(
+            repository.insert_user("alice", "hash", Utc::now()).await,
+            Err(UsersError::AlreadyExists)
+        );
+        repository
+            .insert_session(
+                alice.id(),
+                "session",
+                Utc::now() + chrono::Duration::days(1),
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            repository.query_session_user("session", Utc::now()).await,
+            Ok(alice.clone())
+        );
+        repository.delete_session("session").await.unwrap();
+        assert_eq!(
+            repository.query_session_user("session", Utc::now()).await,
+            Err(UsersError::UserNotFound)
+        );
+
+        let image = Image::new(0, "path/to/alice".to_string(), Utc::now());
+        let id = on_behalf_of(alice.id(), repository.insert_image(&image))
+            .await
+            .unwrap();
+        repository.add_tags(1, &["sea".to_string()]).await.unwrap();
+        on_behalf_of(alice.id(), repository.add_tags(id, &["sea".to_string()]))
+            .await
+            .unwrap();
+        let ids = on_behalf_of(
+            alice.id(),
+            repository.query_images(10, 0, SortKey::UpdatedOn),
+        )
+        .await
+        .unwrap()
+        .iter()
+        .map(Image::id)
+        .collect::<Vec<i64>>();
+        assert_eq!(ids, vec![id]);
+        assert!(matches!(
+            on_behalf_of(alice.id(), repository.query_image(1)).await,
+            Err(query_image_port::QueryError::RecordNotFound)
+        ));
+        assert!(matches!(
+            on_behalf_of(alice.id(), repository.delete_image(1)).await,
+            Err(DeleteImageError::RecordNotFound)
+        ));
+        // Renaming a shared tag only moves the user's own images.
+        on_behalf_of(alice.id(), repository.rename_tag("sea", "ocean"))
+            .await
+            .unwrap();
+        assert_eq!(
+            repository.query_tags().await.unwrap(),
+            vec![
+                Tag::new("ocean".to_string(), 1),
+                Tag::new("sea".to_string(), 1)
+            ]
+        );
+        assert_eq!(
+            repository
+                .query_images(10, 0, SortKey::UpdatedOn)
+                .await
+                .unwrap()
+                .len(),
+            4
+        );
+    }
+}
@@ -0,0 +1,2580 @@
+use std::collections::{HashMap, HashSet};
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::{PgPool, Postgres, QueryBuilder, Row, Transaction};
+use tracing::error;
+
+use super::records::{
+    order_by, owner_filter, AlbumRecord, ApiKeyRecord, ImageRecord, ShareRecord, UserRecord,
+    IMAGE_SELECT,
+};
+use crate::services::images::{
+    domain::{
+        album::{Album, AlbumUpdate},
+        api_key::{format_scopes, ApiKey, Scope},
+        consistency::ImageFile,
+        cursor::Cursor,
+        image::Image,
+        image_status::ImageStatus,
+        rendition::{Rendition, RenditionSize},
+        search::{SearchQuery, Shape},
+        share::Share,
+        sort_key::{SortDirection, SortKey},
+        storage_layout::ImagePaths,
+        tag::{Tag, TagMatch},
+        user::{current_user_id, User},
+    },
+    ports::outgoing::{
+        albums_port::{AlbumsError, AlbumsPort},
+        api_keys_port::{ApiKeysError, ApiKeysPort},
+        batch_delete_image_port::{BatchDeleteError, BatchDeleteImagePort},
+        batch_query_image_port::{self, BatchQueryImagesPort},
+        consistency_port::{ConsistencyError, ConsistencyPort},
+        delete_image_port::{DeleteImageError, DeleteImagePort, RemoveFiles},
+        insert_image_port::{InsertImageError, InsertImagePort, Persist},
+        layout_port::{LayoutError, LayoutPort},
+        query_image_by_hash_port::QueryImageByHashPort,
+        query_image_port::{self, QueryImagePort},
+        rendition_port::RenditionPort,
+        scrub_port::{ScrubError, ScrubPort},
+        search_images_port::SearchImagesPort,
+        shares_port::{SharesError, SharesPort},
+        similar_images_port::SimilarImagesPort,
+        tags_port::{TagsError, TagsPort},
+        trash_port::{TrashError, TrashPort},
+        users_port::{UsersError, UsersPort},
+    },
+};
+
+/// Album columns matching `AlbumRecord`.
+const ALBUM_SELECT: &str = "SELECT id, name, parent_id, cover_image_id, \
+    (SELECT COUNT(*) FROM album_images JOIN images ON images.id = album_images.image_id \
+    WHERE album_id = albums.id AND deleted_at IS NULL) as image_count, created_on FROM albums";
+
+/// Metadata store shared by several replicas. The queries are checked at
+/// runtime, since the offline query data only describes the SQLite schema.
+pub struct ImagesPostgresDS {
+    pool: PgPool,
+}
+
+#[async_trait]
+impl QueryImagePort for ImagesPostgresDS {
+    async fn query_image(&self, index: i64) -> Result<Image, query_image_port::QueryError> {
+        let record = match sqlx::query_as::<_, ImageRecord>(&format!(
+            "{} WHERE id = $1 AND deleted_at IS NULL{}",
+            IMAGE_SELECT,
+            owner_filter("images")
+        ))
+        .bind(index)
+        .fetch_one(&self.pool)
+        .await
+        {
+            Ok(record) => record,
+            Err(e) => {
+                error!(
+                    "Error querying image: {}; message: {}",
+                    index,
+                    e.to_string()
+                );
+                return Err(e.into());
+            }
+        };
+        Ok(record.into())
+    }
+}
+#[async_trait]
+impl QueryImageByHashPort for ImagesPostgresDS {
+    async fn query_image_by_hash(&self, hash: &str) -> Result<Image, query_image_port::QueryError> {
+        let record = match sqlx::query_as::<_, ImageRecord>(&format!(
+            "{} WHERE hash = $1{}",
+            IMAGE_SELECT,
+            owner_filter("images")
+        ))
+        .bind(hash)
+        .fetch_one(&self.pool)
+        .await
+        {
+            Ok(record) => record,
+            Err(e) => {
+                if !matches!(e, sqlx::Error::RowNotFound) {
+                    error!(
+                        "Error querying image with hash: {}; message: {}",
+                        hash,
+                        e.to_string()
+                    );
+                }
+                return Err(e.into());
+            }
+        };
+        Ok(record.into())
+    }
+}
+#[async_trait]
+impl BatchQueryImagesPort for ImagesPostgresDS {
+    async fn query_images(
+        &self,
+        count: i64,
+        offset: i64,
+        sort: SortKey,
+    ) -> Result<Vec<Image>, batch_query_image_port::QueryError> {
+        let query = format!(
+            "{} WHERE deleted_at IS NULL{} ORDER BY {} LIMIT $1 OFFSET $2",
+            IMAGE_SELECT,
+            owner_filter("images"),
+            order_by(sort, SortDirection::Ascending)
+        );
+        let records = match sqlx::query_as::<_, ImageRecord>(&query)
+            .bind(count)
+            .bind(offset)
+            .fetch_all(&self.pool)
+            .await
+        {
+            Ok(records) => records,
+            Err(e) => {
+                error!(
+                    "Error querying {} images with offset {} by {}; message: {}",
+                    count,
+                    offset,
+                    sort,
+                    e.to_string()
+                );
+                return Err(e.into());
+            }
+        };
+
+        Ok(records.into_iter().map(Image::from).collect())
+    }
+
+    async fn query_images_after(
+        &self,
+        cursor: Option<Cursor>,
+        count: i64,
+    ) -> Result<Vec<Image>, batch_query_image_port::QueryError> {
+        let records = match cursor {
+            Some(cursor) => {
+                sqlx::query_as::<_, ImageRecord>(&format!(
+                    "{} WHERE deleted_at IS NULL AND (updated_on > $1 OR (updated_on = $1 AND id > $2)){} \
+                        ORDER BY updated_on, id LIMIT $3",
+                    IMAGE_SELECT,
+                    owner_filter("images")
+                ))
+                .bind(cursor.updated_on().to_string())
+                .bind(cursor.id())
+                .bind(count)
+                .fetch_all(&self.pool)
+                .await
+            }
+            None => {
+                sqlx::query_as::<_, ImageRecord>(&format!(
+                    "{} WHERE deleted_at IS NULL{} ORDER BY updated_on, id LIMIT $1",
+                    IMAGE_SELECT,
+                    owner_filter("images")
+                ))
+                .bind(count)
+                .fetch_all(&self.pool)
+                .await
+            }
+        };
+        let records = match records {
+            Ok(records) => records,
+            Err(e) => {
+                error!(
+                    "Error querying {} images after {:?}; message: {}",
+                    count,
+                    cursor,
+                    e.to_string()
+                );
+                return Err(e.into());
+            }
+        };
+
+        Ok(records.into_iter().map(Image::from).collect())
+    }
+
+    async fn query_images_by_tags(
+        &self,
+        tags: &[String],
+        tag_match: TagMatch,
+        count: i64,
+        offset: i64,
+        sort: SortKey,
+    ) -> Result<Vec<Image>, batch_query_image_port::QueryError> {
+        let having = match tag_match {
+            TagMatch::All => format!("HAVING COUNT(DISTINCT tags.id) = {}", tags.len()),
+            TagMatch::Any => String::new(),
+        };
+        let query = format!(
+            "{} WHERE deleted_at IS NULL{} AND id IN (SELECT image_tags.image_id FROM image_tags \
+                JOIN tags ON tags.id = image_tags.tag_id \
+                WHERE tags.name = ANY($1) GROUP BY image_tags.image_id {}) \
+                ORDER BY {} LIMIT $2 OFFSET $3",
+            IMAGE_SELECT,
+            owner_filter("images"),
+            having,
+            order_by(sort, SortDirection::Ascending)
+        );
+        let records = match sqlx::query_as::<_, ImageRecord>(&query)
+            .bind(tags)
+            .bind(count)
+            .bind(offset)
+            .fetch_all(&self.pool)
+            .await
+        {
+            Ok(records) => records,
+            Err(e) => {
+                error!(
+                    "Error querying images tagged {:?}; message: {}",
+                    tags,
+                    e.to_string()
+                );
+                return Err(e.into());
+            }
+        };
+        Ok(records.into_iter().map(Image::from).collect())
+    }
+}
+#[async_trait]
+impl AlbumsPort for ImagesPostgresDS {
+    async fn create_album(&self, name: &str, parent_id: Option<i64>) -> Result<Album, AlbumsError> {
+        let id = match self.create_album_record(name, parent_id).await {
+            Ok(Some(id)) => id,
+            Ok(None) => return Err(AlbumsError::AlbumNotFound),
+            Err(e) => {
+                error!(
+                    "Error creating album {} below {:?}; message: {}",
+                    name,
+                    parent_id,
+                    e.to_string()
+                );
+                return Err(AlbumsError::InternalError);
+            }
+        };
+        self.query_album(id).await
+    }
+
+    async fn query_album(&self, id: i64) -> Result<Album, AlbumsError> {
+        match sqlx::query_as::<_, AlbumRecord>(&format!(
+            "{} WHERE id = $1{}",
+            ALBUM_SELECT,
+            owner_filter("albums")
+        ))
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await
+        {
+            Ok(Some(record)) => Ok(record.into()),
+            Ok(None) => Err(AlbumsError::AlbumNotFound),
+            Err(e) => {
+                error!("Error querying album {}; message: {}", id, e.to_string());
+                Err(AlbumsError::InternalError)
+            }
+        }
+    }
+
+    async fn query_albums(&self, parent_id: Option<i64>) -> Result<Vec<Album>, AlbumsError> {
+        match sqlx::query_as::<_, AlbumRecord>(&format!(
+            "{} WHERE parent_id IS NOT DISTINCT FROM $1{} ORDER BY name, id",
+            ALBUM_SELECT,
+            owner_filter("albums")
+        ))
+        .bind(parent_id)
+        .fetch_all(&self.pool)
+        .await
+        {
+            Ok(records) => Ok(records.into_iter().map(Album::from).collect()),
+            Err(e) => {
+                error!(
+                    "Error querying albums below {:?}; message: {}",
+                    parent_id,
+                    e.to_string()
+                );
+                Err(AlbumsError::InternalError)
+            }
+        }
+    }
+
+    async fn update_album(&self, id: i64, update: &AlbumUpdate) -> Result<Album, AlbumsError> {
+        match self.update_album_record(id, update).await {
+            Ok(Ok(())) => self.query_album(id).await,
+            Ok(Err(e)) => Err(e),
+            Err(e) => {
+                error!(
+                    "Error updating album {} with {:?}; message: {}",
+                    id,
+                    update,
+                    e.to_string()
+                );
+                Err(AlbumsError::InternalError)
+            }
+        }
+    }
+
+    async fn delete_album(&self, id: i64, cascade: bool) -> Result<(), AlbumsError> {
+        match self.delete_album_records(id, cascade).await {
+            Ok(true) => Ok(()),
+            Ok(false) => Err(AlbumsError::AlbumNotFound),
+            Err(e) => {
+                error!("Error deleting album {}; message: {}", id, e.to_string());
+                Err(AlbumsError::InternalError)
+            }
+        }
+    }
+
+    async fn add_images(&self, album_id: i64, image_ids: &[i64]) -> Result<(), AlbumsError> {
+        match self.add_album_image_records(album_id, image_ids).await {
+            Ok(result) => result,
+            Err(e) => {
+                error!(
+                    "Error adding images {:?} to album {}; message: {}",
+                    image_ids,
+                    album_id,
+                    e.to_string()
+                );
+                Err(AlbumsError::InternalError)
+            }
+        }
+    }
+
+    async fn remove_image(&self, album_id: i64, image_id: i64) -> Result<(), AlbumsError> {
+        match self.remove_album_image_record(album_id, image_id).await {
+            Ok(result) => result,
+            Err(e) => {
+                error!(
+                    "Error removing image {} from album {}; message: {}",
+                    image_id,
+                    album_id,
+                    e.to_string()
+                );
+                Err(AlbumsError::InternalError)
+            }
+        }
+    }
+
+    async fn reorder_images(&self, album_id: i64, image_ids: &[i64]) -> Result<(), AlbumsError> {
+        match self.reorder_album_image_records(album_id, image_ids).await {
+            Ok(result) => result,
+            Err(e) => {
+                error!(
+                    "Error reordering album {} as {:?}; message: {}",
+                    album_id,
+                    image_ids,
+                    e.to_string()
+                );
+                Err(AlbumsError::InternalError)
+            }
+        }
+    }
+
+    async fn query_album_images(
+        &self,
+        album_id: i64,
+        count: i64,
+        offset: i64,
+    ) -> Result<Vec<Image>, AlbumsError> {
+        // Listing an unknown album is an error rather than an empty page.
+        self.query_album(album_id).await?;
+        let query = format!(
+            "{} WHERE deleted_at IS NULL{} AND id IN (SELECT album_images.image_id FROM album_images \
+                WHERE album_images.album_id = $1) \
+                ORDER BY (SELECT position FROM album_images \
+                WHERE album_images.album_id = $1 AND album_images.image_id = images.id) \
+                LIMIT $2 OFFSET $3",
+            IMAGE_SELECT,
+            owner_filter("images")
+        );
+        match sqlx::query_as::<_, ImageRecord>(&query)
+            .bind(album_id)
+            .bind(count)
+            .bind(offset)
+            .fetch_all(&self.pool)
+            .await
+        {
+            Ok(records) => Ok(records.into_iter().map(Image::from).collect()),
+            Err(e) => {
+                error!(
+                    "Error querying {} images of album {} with offset {}; message: {}",
+                    count,
+                    album_id,
+                    offset,
+                    e.to_string()
+                );
+                Err(AlbumsError::InternalError)
+            }
+        }
+    }
+}
+#[async_trait]
+impl SearchImagesPort for ImagesPostgresDS {
+    async fn search_images(
+        &self,
+        query: &SearchQuery,
+    ) -> Result<Vec<Image>, query_image_port::QueryError> {
+        let mut builder = QueryBuilder::<Postgres>::new(IMAGE_SELECT);
+        builder
+            .push(" WHERE deleted_at IS NULL")
+            .push(owner_filter("images"));
+        if let Some(created_after) = query.created_after {
+            builder
+                .push(" AND created_on >= ")
+                .push_bind(created_after.to_string());
+        }
+        if let Some(created_before) = query.created_before {
+            builder
+                .push(" AND created_on <= ")
+                .push_bind(created_before.to_string());
+        }
+        if let Some(captured_after) = query.captured_after {
+            builder
+                .push(" AND captured_on >= ")
+                .push_bind(captured_after.to_string());
+        }
+        if let Some(captured_before) = query.captured_before {
+            builder
+                .push(" AND captured_on <= ")
+                .push_bind(captured_before.to_string());
+        }
+        if let Some(min_width) = query.min_width {
+            builder.push(" AND width >= ").push_bind(min_width as i64);
+        }
+        if let Some(max_width) = query.max_width {
+            builder.push(" AND width <= ").push_bind(max_width as i64);
+        }
+        if let Some(min_height) = query.min_height {
+            builder.push(" AND height >= ").push_bind(min_height as i64);
+        }
+        if let Some(max_height) = query.max_height {
+            builder.push(" AND height <= ").push_bind(max_height as i64);
+        }
+        match query.shape {
+            Some(Shape::Landscape) => builder.push(" AND width > height"),
+            Some(Shape::Portrait) => builder.push(" AND width < height"),
+            Some(Shape::Square) => builder.push(" AND width = height"),
+            None => &mut builder,
+        };
+        if let Some(original_format) = &query.original_format {
+            builder
+                .push(" AND original_format = ")
+                .push_bind(original_format);
+        }
+        if !query.tags.is_empty() {
+            builder
+                .push(
+                    " AND id IN (SELECT image_tags.image_id FROM image_tags \
+                    JOIN tags ON tags.id = image_tags.tag_id WHERE tags.name = ANY(",
+                )
+                .push_bind(&query.tags)
+                .push(") GROUP BY image_tags.image_id");
+            if query.tag_match == TagMatch::All {
+                builder
+                    .push(" HAVING COUNT(DISTINCT tags.id) = ")
+                    .push_bind(query.tags.len() as i64);
+            }
+            builder.push(")");
+        }
+        builder
+            .push(" ORDER BY ")
+            .push(order_by(query.sort, query.direction))
+            .push(" LIMIT ")
+            .push_bind(query.count)
+            .push(" OFFSET ")
+            .push_bind(query.offset);
+        let records = match builder
+            .build_query_as::<ImageRecord>()
+            .fetch_all(&self.pool)
+            .await
+        {
+            Ok(records) => records,
+            Err(e) => {
+                error!("Error searching images {:?}; message: {}", query, e);
+                return Err(e.into());
+            }
+        };
+        Ok(records.into_iter().map(Image::from).collect())
+    }
+}
+#[async_trait]
+impl TagsPort for ImagesPostgresDS {
+    async fn add_tags(&self, image_id: i64, tags: &[String]) -> Result<Vec<String>, TagsError> {
+        match self.add_tag_records(image_id, tags).await {
+            Ok(tags) => Ok(tags),
+            Err(sqlx::Error::RowNotFound) => Err(TagsError::ImageNotFound),
+            Err(e) => {
+                error!(
+                    "Error tagging image {} with {:?}; message: {}",
+                    image_id,
+                    tags,
+                    e.to_string()
+                );
+                Err(TagsError::InternalError)
+            }
+        }
+    }
+
+    async fn remove_tag(&self, image_id: i64, tag: &str) -> Result<(), TagsError> {
+        match self.remove_tag_record(image_id, tag).await {
+            Ok(true) => Ok(()),
+            Ok(false) => Err(TagsError::TagNotFound),
+            Err(e) => {
+                error!(
+                    "Error removing tag {} from image {}; message: {}",
+                    tag,
+                    image_id,
+                    e.to_string()
+                );
+                Err(TagsError::InternalError)
+            }
+        }
+    }
+
+    async fn query_tags(&self) -> Result<Vec<Tag>, TagsError> {
+        match sqlx::query(&format!(
+            "SELECT tags.name, COUNT(image_tags.image_id) as count FROM tags \
+                JOIN image_tags ON image_tags.tag_id = tags.id \
+                JOIN images ON images.id = image_tags.image_id \
+                WHERE images.deleted_at IS NULL{} \
+                GROUP BY tags.id \
+                ORDER BY tags.name",
+            owner_filter("images")
+        ))
+        .fetch_all(&self.pool)
+        .await
+        {
+            Ok(records) => Ok(records
+                .into_iter()
+                .map(|record| Tag::new(record.get("name"), record.get("count")))
+                .collect()),
+            Err(e) => {
+                error!("Error querying tags; message: {}", e.to_string());
+                Err(TagsError::InternalError)
+            }
+        }
+    }
+
+    async fn rename_tag(&self, from: &str, to: &str) -> Result<(), TagsError> {
+        match self.rename_tag_record(from, to).await {
+            Ok(true) => Ok(()),
+            Ok(false) => Err(TagsError::TagNotFound),
+            Err(e) => {
+                error!(
+                    "Error renaming tag {} to {}; message: {}",
+                    from,
+                    to,
+                    e.to_string()
+                );
+                Err(TagsError::InternalError)
+            }
+        }
+    }
+}
+#[async_trait]
+impl SimilarImagesPort for ImagesPostgresDS {
+    async fn query_perceptual_hashes(
+        &self,
+        after: i64,
+    ) -> Result<Vec<(i64, u64)>, query_image_port::QueryError> {
+        let records = match sqlx::query(
+            "SELECT id, perceptual_hash FROM images \
+                WHERE id > $1 AND perceptual_hash IS NOT NULL \
+                ORDER BY id",
+        )
+        .bind(after)
+        .fetch_all(&self.pool)
+        .await
+        {
+            Ok(records) => records,
+            Err(e) => {
+                error!(
+                    "Error querying perceptual hashes after {}; message: {}",
+                    after,
+                    e.to_string()
+                );
+                return Err(e.into());
+            }
+        };
+        Ok(records
+            .into_iter()
+            .map(|record| {
+                (
+                    record.get("id"),
+                    record.get::<i64, &str>("perceptual_hash") as u64,
+                )
+            })
+            .collect())
+    }
+
+    async fn query_images_by_ids(
+        &self,
+        indexes: Vec<i64>,
+    ) -> Result<Vec<Image>, query_image_port::QueryError> {
+        if indexes.is_empty() {
+            return Ok(vec![]);
+        }
+        let records = match sqlx::query_as::<_, ImageRecord>(&format!(
+            "{} WHERE deleted_at IS NULL AND id = ANY($1){}",
+            IMAGE_SELECT,
+            owner_filter("images")
+        ))
+        .bind(&indexes)
+        .fetch_all(&self.pool)
+        .await
+        {
+            Ok(records) => records,
+            Err(e) => {
+                error!(
+                    "Error querying images {:?}; message: {}",
+                    indexes,
+                    e.to_string()
+                );
+                return Err(e.into());
+            }
+        };
+        Ok(records.into_iter().map(Image::from).collect())
+    }
+}
+#[async_trait]
+impl DeleteImagePort for ImagesPostgresDS {
+    async fn delete_image(&self, index: i64) -> Result<Vec<String>, DeleteImageError> {
+        match self.delete_image_records(index, None).await {
+            Ok(paths) => Ok(paths),
+            Err(e) => {
+                error!("Error deleting image {}; message: {}", index, e.to_string());
+                Err(e.into())
+            }
+        }
+    }
+
+    async fn delete_image_with(
+        &self,
+        index: i64,
+        remove: RemoveFiles,
+    ) -> Result<Vec<String>, DeleteImageError> {
+        match self.delete_image_records(index, Some(remove)).await {
+            Ok(paths) => Ok(paths),
+            Err(e) => {
+                error!("Error deleting image {}; message: {}", index, e.to_string());
+                Err(e.into())
+            }
+        }
+    }
+}
+#[async_trait]
+impl BatchDeleteImagePort for ImagesPostgresDS {
+    async fn batch_delete_image(&self, indexes: Vec<i64>) -> Result<Vec<String>, BatchDeleteError> {
+        match self.batch_delete_image_records(&indexes).await {
+            Ok(paths) => Ok(paths),
+            Err(e) => {
+                error!(
+                    "Error deleting images {:?}; message: {}",
+                    indexes,
+                    e.to_string()
+                );
+                Err(e.into())
+            }
+        }
+    }
+}
+#[async_trait]
+impl TrashPort for ImagesPostgresDS {
+    async fn trash_images(
+        &self,
+        indexes: &[i64],
+        deleted_at: DateTime<Utc>,
+    ) -> Result<u64, TrashError> {
+        if indexes.is_empty() {
+            return Ok(0);
+        }
+        match sqlx::query(&format!(
+            "UPDATE images SET deleted_at = $1 WHERE deleted_at IS NULL AND id = ANY($2){}",
+            owner_filter("images")
+        ))
+        .bind(deleted_at.to_string())
+        .bind(indexes)
+        .execute(&self.pool)
+        .await
+        {
+            Ok(result) => Ok(result.rows_affected()),
+            Err(e) => {
+                error!(
+                    "Error trashing images {:?}; message: {}",
+                    indexes,
+                    e.to_string()
+                );
+                Err(TrashError::InternalError)
+            }
+        }
+    }
+
+    async fn restore_image(&self, index: i64) -> Result<(), TrashError> {
+        match sqlx::query(&format!(
+            "UPDATE images SET deleted_at = NULL WHERE id = $1 AND deleted_at IS NOT NULL{}",
+            owner_filter("images")
+        ))
+        .bind(index)
+        .execute(&self.pool)
+        .await
+        {
+            Ok(result) if result.rows_affected() == 0 => Err(TrashError::ImageNotFound),
+            Ok(_) => Ok(()),
+            Err(e) => {
+                error!(
+                    "Error restoring image {}; message: {}",
+                    index,
+                    e.to_string()
+                );
+                Err(TrashError::InternalError)
+            }
+        }
+    }
+
+    async fn query_trash(&self, count: i64, offset: i64) -> Result<Vec<Image>, TrashError> {
+        match sqlx::query_as::<_, ImageRecord>(&format!(
+            "{} WHERE deleted_at IS NOT NULL{} ORDER BY deleted_at DESC, id DESC LIMIT $1 OFFSET $2",
+            IMAGE_SELECT,
+            owner_filter("images")
+        ))
+        .bind(count)
+        .bind(offset)
+        .fetch_all(&self.pool)
+        .await
+        {
+            Ok(records) => Ok(records.into_iter().map(Image::from).collect()),
+            Err(e) => {
+                error!("Error querying trash; message: {}", e.to_string());
+                Err(TrashError::InternalError)
+            }
+        }
+    }
+
+    async fn purge_trash(&self, before: DateTime<Utc>) -> Result<Vec<String>, TrashError> {
+        match self.purge_trash_records(before, None).await {
+            Ok(paths) => Ok(paths),
+            Err(e) => {
+                error!(
+                    "Error purging images trashed before {}; message: {}",
+                    before,
+                    e.to_string()
+                );
+                Err(TrashError::InternalError)
+            }
+        }
+    }
+
+    async fn purge_trash_with(
+        &self,
+        before: DateTime<Utc>,
+        remove: RemoveFiles,
+    ) -> Result<Vec<String>, TrashError> {
+        match self.purge_trash_records(before, Some(remove)).await {
+            Ok(paths) => Ok(paths),
+            Err(e) => {
+                error!(
+                    "Error purging images trashed before {}; message: {}",
+                    before,
+                    e.to_string()
+                );
+                Err(TrashError::InternalError)
+            }
+        }
+    }
+}
+#[async_trait]
+impl ConsistencyPort for ImagesPostgresDS {
+    async fn query_image_files(&self) -> Result<Vec<ImageFile>, ConsistencyError> {
+        let records = match sqlx::query("SELECT id, path, status, stored_size FROM images")
+            .fetch_all(&self.pool)
+            .await
+        {
+            Ok(records) => records,
+            Err(e) => {
+                error!("Error querying image files; message: {}", e.to_string());
+                return Err(ConsistencyError::InternalError);
+            }
+        };
+        Ok(records
+            .into_iter()
+            .map(|record| {
+                let status = record
+                    .get::<String, &str>("status")
+                    .parse::<ImageStatus>()
+                    .unwrap_or_default();
+                let file = ImageFile::new(record.get("id"), record.get("path"), status);
+                match record.get::<Option<i64>, &str>("stored_size") {
+                    Some(size) => file.with_size(size as u64),
+                    None => file,
+                }
+            })
+            .collect())
+    }
+
+    async fn query_referenced_paths(&self) -> Result<HashSet<String>, ConsistencyError> {
+        match sqlx::query(
+            "SELECT path FROM images \
+                UNION SELECT original_path FROM images WHERE original_path IS NOT NULL \
+                UNION SELECT path FROM renditions",
+        )
+        .fetch_all(&self.pool)
+        .await
+        {
+            Ok(records) => Ok(records
+                .into_iter()
+                .map(|record| record.get("path"))
+                .collect()),
+            Err(e) => {
+                error!(
+                    "Error querying referenced paths; message: {}",
+                    e.to_string()
+                );
+                Err(ConsistencyError::InternalError)
+            }
+        }
+    }
+
+    async fn update_image_status(
+        &self,
+        indexes: &[i64],
+        status: ImageStatus,
+    ) -> Result<(), ConsistencyError> {
+        if indexes.is_empty() {
+            return Ok(());
+        }
+        match sqlx::query("UPDATE images SET status = $1 WHERE id = ANY($2)")
+            .bind(status.as_str())
+            .bind(indexes)
+            .execute(&self.pool)
+            .await
+        {
+            Ok(_) => Ok(()),
+            Err(e) => {
+                error!(
+                    "Error marking images {:?} as {}; message: {}",
+                    indexes,
+                    status,
+                    e.to_string()
+                );
+                Err(ConsistencyError::InternalError)
+            }
+        }
+    }
+}
+#[async_trait]
+impl LayoutPort for ImagesPostgresDS {
+    async fn query_image_paths(
+        &self,
+        after: i64,
+        count: i64,
+    ) -> Result<Vec<ImagePaths>, LayoutError> {
+        match self.query_image_path_records(after, count).await {
+            Ok(images) => Ok(images),
+            Err(e) => {
+                error!(
+                    "Error querying image paths after {}; message: {}",
+                    after,
+                    e.to_string()
+                );
+                Err(LayoutError::InternalError)
+            }
+        }
+    }
+
+    async fn relocate_paths(
+        &self,
+        moves: &[(String, String)],
+        persist: Persist,
+    ) -> Result<(), LayoutError> {
+        match self.relocate_path_records(moves, persist).await {
+            Ok(()) => Ok(()),
+            Err(e) => {
+                error!("Error relocating paths {:?}; message: {}", moves, e);
+                Err(LayoutError::InternalError)
+            }
+        }
+    }
+}
+#[async_trait]
+impl ScrubPort for ImagesPostgresDS {
+    async fn query_scrub_batch(&self, count: i64) -> Result<Vec<ImageFile>, ScrubError> {
+        let records = match sqlx::query(
+            "SELECT id, path, status, checksum FROM images \
+                ORDER BY checked_on IS NOT NULL, checked_on, id LIMIT $1",
+        )
+        .bind(count)
+        .fetch_all(&self.pool)
+        .await
+        {
+            Ok(records) => records,
+            Err(e) => {
+                error!("Error querying images to scrub; message: {}", e.to_string());
+                return Err(ScrubError::InternalError);
+            }
+        };
+        Ok(records
+            .into_iter()
+            .map(|record| {
+                let status = record
+                    .get::<String, &str>("status")
+                    .parse::<ImageStatus>()
+                    .unwrap_or_default();
+                let file = ImageFile::new(record.get("id"), record.get("path"), status);
+                match record.get::<Option<String>, &str>("checksum") {
+                    Some(checksum) => file.with_checksum(checksum),
+                    None => file,
+                }
+            })
+            .collect())
+    }
+
+    async fn record_scrub(
+        &self,
+        image_id: i64,
+        status: ImageStatus,
+        checksum: Option<String>,
+        checked_on: DateTime<Utc>,
+    ) -> Result<(), ScrubError> {
+        match sqlx::query(
+            "UPDATE images SET status = $2, checked_on = $3, checksum = COALESCE(checksum, $4) \
+                WHERE id = $1",
+        )
+        .bind(image_id)
+        .bind(status.as_str())
+        .bind(checked_on.to_string())
+        .bind(checksum)
+        .execute(&self.pool)
+        .await
+        {
+            Ok(_) => Ok(()),
+            Err(e) => {
+                error!(
+                    "Error recording image {} as {}; message: {}",
+                    image_id,
+                    status,
+                    e.to_string()
+                );
+                Err(ScrubError::InternalError)
+            }
+        }
+    }
+
+    async fn count_images_by_status(&self) -> Result<Vec<(ImageStatus, i64)>, ScrubError> {
+        match sqlx::query(
+            "SELECT status, COUNT(*) as count FROM images WHERE deleted_at IS NULL GROUP BY status",
+        )
+        .fetch_all(&self.pool)
+        .await
+        {
+            Ok(records) => Ok(records
+                .into_iter()
+                .filter_map(|record| {
+                    let status = record
+                        .get::<String, &str>("status")
+                        .parse::<ImageStatus>()
+                        .ok()?;
+                    Some((status, record.get("count")))
+                })
+                .collect()),
+            Err(e) => {
+                error!(
+                    "Error counting images by status; message: {}",
+                    e.to_string()
+                );
+                Err(ScrubError::InternalError)
+            }
+        }
+    }
+
+    async fn query_images_by_status(&self, status: ImageStatus) -> Result<Vec<Image>, ScrubError> {
+        match sqlx::query_as::<_, ImageRecord>(&format!(
+            "{} WHERE deleted_at IS NULL AND status = $1 ORDER BY id",
+            IMAGE_SELECT
+        ))
+        .bind(status.as_str())
+        .fetch_all(&self.pool)
+        .await
+        {
+            Ok(records) => Ok(records.into_iter().map(Image::from).collect()),
+            Err(e) => {
+                error!(
+                    "Error querying {} images; message: {}",
+                    status,
+                    e.to_string()
+                );
+                Err(ScrubError::InternalError)
+            }
+        }
+    }
+}
+#[async_trait]
+impl RenditionPort for ImagesPostgresDS {
+    async fn query_rendition(
+        &self,
+        image_id: i64,
+        size: RenditionSize,
+    ) -> Result<Rendition, query_image_port::QueryError> {
+        let record = match sqlx::query(&format!(
+            "SELECT image_id, path FROM renditions WHERE image_id = $1 AND size = $2 \
+                AND image_id IN (SELECT id FROM images WHERE TRUE{})",
+            owner_filter("images")
+        ))
+        .bind(image_id)
+        .bind(size.as_str())
+        .fetch_one(&self.pool)
+        .await
+        {
+            Ok(record) => record,
+            Err(e) => {
+                if !matches!(e, sqlx::Error::RowNotFound) {
+                    error!(
+                        "Error querying {} rendition of image {}; message: {}",
+                        size,
+                        image_id,
+                        e.to_string()
+                    );
+                }
+                return Err(e.into());
+            }
+        };
+        Ok(Rendition::new(
+            record.get("image_id"),
+            size,
+            record.get("path"),
+        ))
+    }
+
+    async fn insert_rendition(&self, rendition: &Rendition) -> Result<(), InsertImageError> {
+        match sqlx::query(
+            "INSERT INTO renditions (image_id, size, path, updated_on) VALUES ($1, $2, $3, $4) \
+                ON CONFLICT (image_id, size) DO UPDATE SET path = $3, updated_on = $4",
+        )
+        .bind(rendition.image_id())
+        .bind(rendition.size().as_str())
+        .bind(rendition.path())
+        .bind(Utc::now().to_string())
+        .execute(&self.pool)
+        .await
+        {
+            Ok(_) => Ok(()),
+            Err(e) => {
+                error!(
+                    "Error inserting rendition {:?}; message: {}",
+                    rendition,
+                    e.to_string()
+                );
+                Err(e.into())
+            }
+        }
+    }
+}
+#[async_trait]
+impl InsertImagePort for ImagesPostgresDS {
+    async fn insert_image(&self, record: &Image) -> Result<i64, InsertImageError> {
+        match self.insert_image_record(record, None).await {
+            Ok(id) => Ok(id),
+            Err(e) => {
+                error!(
+                    "Error inserting image {:?}; message: {}",
+                    record,
+                    e.to_string()
+                );
+                Err(e.into())
+            }
+        }
+    }
+
+    async fn insert_image_with(
+        &self,
+        record: &Image,
+        persist: Persist,
+    ) -> Result<i64, InsertImageError> {
+        match self.insert_image_record(record, Some(persist)).await {
+            Ok(id) => Ok(id),
+            Err(e) => {
+                error!(
+                    "Error inserting image {:?}; message: {}",
+                    record,
+                    e.to_string()
+                );
+                Err(e.into())
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl ApiKeysPort for ImagesPostgresDS {
+    async fn insert_api_key(
+        &self,
+        name: &str,
+        key_hash: &str,
+        scopes: &[Scope],
+        created_on: DateTime<Utc>,
+    ) -> Result<ApiKey, ApiKeysError> {
+        match sqlx::query(
+            "INSERT INTO api_keys (name, key_hash, scopes, created_on) VALUES ($1, $2, $3, $4) \
+                RETURNING id",
+        )
+        .bind(name)
+        .bind(key_hash)
+        .bind(format_scopes(scopes))
+        .bind(created_on.to_string())
+        .fetch_one(&self.pool)
+        .await
+        {
+            Ok(record) => Ok(ApiKey::new(
+                record.get("id"),
+                name.to_string(),
+                scopes.to_vec(),
+                created_on,
+            )),
+            Err(e) => {
+                error!("Error inserting API key {}; message: {}", name, e);
+                Err(e.into())
+            }
+        }
+    }
+
+    async fn query_api_key_by_hash(&self, key_hash: &str) -> Result<ApiKey, ApiKeysError> {
+        match sqlx::query_as::<_, ApiKeyRecord>(
+            "SELECT id, name, scopes, created_on, revoked_on FROM api_keys WHERE key_hash = $1",
+        )
+        .bind(key_hash)
+        .fetch_optional(&self.pool)
+        .await
+        {
+            Ok(Some(record)) => Ok(record.into()),
+            Ok(None) => Err(ApiKeysError::KeyNotFound),
+            Err(e) => {
+                error!("Error querying API key; message: {}", e.to_string());
+                Err(ApiKeysError::InternalError)
+            }
+        }
+    }
+
+    async fn query_api_keys(&self) -> Result<Vec<ApiKey>, ApiKeysError> {
+        match sqlx::query_as::<_, ApiKeyRecord>(
+            "SELECT id, name, scopes, created_on, revoked_on FROM api_keys ORDER BY id",
+        )
+        .fetch_all(&self.pool)
+        .await
+        {
+            Ok(records) => Ok(records.into_iter().map(ApiKey::from).collect()),
+            Err(e) => {
+                error!("Error querying API keys; message: {}", e.to_string());
+                Err(ApiKeysError::InternalError)
+            }
+        }
+    }
+
+    async fn revoke_api_key(&self, id: i64, revoked_on: DateTime<Utc>) -> Result<(), ApiKeysError> {
+        match sqlx::query(
+            "UPDATE api_keys SET revoked_on = $1 WHERE id = $2 AND revoked_on IS NULL",
+        )
+        .bind(revoked_on.to_string())
+        .bind(id)
+        .execute(&self.pool)
+        .await
+        {
+            Ok(result) if result.rows_affected() == 0 => Err(ApiKeysError::KeyNotFound),
+            Ok(_) => Ok(()),
+            Err(e) => {
+                error!("Error revoking API key {}; message: {}", id, e.to_string());
+                Err(ApiKeysError::InternalError)
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl UsersPort for ImagesPostgresDS {
+    async fn insert_user(
+        &self,
+        name: &str,
+        password_hash: &str,
+        created_on: DateTime<Utc>,
+    ) -> Result<User, UsersError> {
+        match sqlx::query(
+            "INSERT INTO users (name, password_hash, created_on) VALUES ($1, $2, $3) \
+                RETURNING id",
+        )
+        .bind(name)
+        .bind(password_hash)
+        .bind(created_on.to_string())
+        .fetch_one(&self.pool)
+        .await
+        {
+            Ok(record) => Ok(User::new(record.get("id"), name.to_string(), created_on)),
+            Err(e) => {
+                error!("Error inserting user {}; message: {}", name, e);
+                Err(e.into())
+            }
+        }
+    }
+
+    async fn query_user_by_name(&self, name: &str) -> Result<(User, String), UsersError> {
+        match sqlx::query("SELECT id, name, created_on, password_hash FROM users WHERE name = $1")
+            .bind(name)
+            .fetch_optional(&self.pool)
+            .await
+        {
+            Ok(Some(record)) => Ok((
+                UserRecord {
+                    id: record.get("id"),
+                    name: record.get("name"),
+                    created_on: record.get("created_on"),
+                }
+                .into(),
+                record.get("password_hash"),
+            )),
+            Ok(None) => Err(UsersError::UserNotFound),
+            Err(e) => {
+                error!("Error querying user {}; message: {}", name, e.to_string());
+                Err(UsersError::InternalError)
+            }
+        }
+    }
+
+    async fn insert_session(
+        &self,
+        user_id: i64,
+        token_hash: &str,
+        expires_on: DateTime<Utc>,
+    ) -> Result<(), UsersError> {
+        match sqlx::query(
+            "INSERT INTO sessions (user_id, token_hash, expires_on) VALUES ($1, $2, $3)",
+        )
+        .bind(user_id)
+        .bind(token_hash)
+        .bind(expires_on.to_string())
+        .execute(&self.pool)
+        .await
+        {
+            Ok(_) => Ok(()),
+            Err(e) => {
+                error!(
+                    "Error inserting session of user {}; message: {}",
+                    user_id, e
+                );
+                Err(e.into())
+            }
+        }
+    }
+
+    async fn query_session_user(
+        &self,
+        token_hash: &str,
+        now: DateTime<Utc>,
+    ) -> Result<User, UsersError> {
+        match sqlx::query_as::<_, UserRecord>(
+            "SELECT users.id, users.name, users.created_on FROM sessions \
+                JOIN users ON users.id = sessions.user_id \
+                WHERE sessions.token_hash = $1 AND sessions.expires_on > $2",
+        )
+        .bind(token_hash)
+        .bind(now.to_string())
+        .fetch_optional(&self.pool)
+        .await
+        {
+            Ok(Some(record)) => Ok(record.into()),
+            Ok(None) => Err(UsersError::UserNotFound),
+            Err(e) => {
+                error!("Error querying session; message: {}", e.to_string());
+                Err(UsersError::InternalError)
+            }
+        }
+    }
+
+    async fn delete_session(&self, token_hash: &str) -> Result<(), UsersError> {
+        match sqlx::query("DELETE FROM sessions WHERE token_hash = $1")
+            .bind(token_hash)
+            .execute(&self.pool)
+            .await
+        {
+            Ok(result) if result.rows_affected() == 0 => Err(UsersError::UserNotFound),
+            Ok(_) => Ok(()),
+            Err(e) => {
+                error!("Error deleting session; message: {}", e.to_string());
+                Err(UsersError::InternalError)
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl SharesPort for ImagesPostgresDS {
+    async fn insert_share(
+        &self,
+        image_id: i64,
+        created_on: DateTime<Utc>,
+        expires_on: DateTime<Utc>,
+    ) -> Result<Share, SharesError> {
+        match sqlx::query(&format!(
+            "INSERT INTO shares (image_id, created_on, expires_on) \
+                SELECT id, $2, $3 FROM images WHERE id = $1 AND deleted_at IS NULL{} \
+                RETURNING id",
+            owner_filter("images")
+        ))
+        .bind(image_id)
+        .bind(created_on.to_string())
+        .bind(expires_on.to_string())
+        .fetch_optional(&self.pool)
+        .await
+        {
+            Ok(Some(record)) => Ok(Share::new(
+                record.get("id"),
+                image_id,
+                created_on,
+                expires_on,
+            )),
+            Ok(None) => Err(SharesError::ImageNotFound),
+            Err(e) => {
+                error!("Error sharing image {}; message: {}", image_id, e);
+                Err(SharesError::InternalError)
+            }
+        }
+    }
+
+    async fn query_share(&self, id: i64) -> Result<Share, SharesError> {
+        match sqlx::query_as::<_, ShareRecord>(
+            "SELECT id, image_id, created_on, expires_on, revoked_on FROM shares WHERE id = $1",
+        )
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await
+        {
+            Ok(Some(record)) => Ok(record.into()),
+            Ok(None) => Err(SharesError::ShareNotFound),
+            Err(e) => {
+                error!("Error querying share {}; message: {}", id, e.to_string());
+                Err(SharesError::InternalError)
+            }
+        }
+    }
+
+    async fn revoke_share(&self, id: i64, revoked_on: DateTime<Utc>) -> Result<(), SharesError> {
+        match sqlx::query(&format!(
+            "UPDATE shares SET revoked_on = $1 WHERE id = $2 AND revoked_on IS NULL \
+                AND image_id IN (SELECT id FROM images WHERE TRUE{})",
+            owner_filter("images")
+        ))
+        .bind(revoked_on.to_string())
+        .bind(id)
+        .execute(&self.pool)
+        .await
+        {
+            Ok(result) if result.rows_affected() == 0 => Err(SharesError::ShareNotFound),
+            Ok(_) => Ok(()),
+            Err(e) => {
+                error!("Error revoking share {}; message: {}", id, e.to_string());
+                Err(SharesError::InternalError)
+            }
+        }
+    }
+}
+
+impl ImagesPostgresDS {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// Dropping the transaction on any error, `persist` included, rolls the
+    /// insert back.
+    async fn insert_image_record(
+        &self,
+        record: &Image,
+        persist: Option<Persist>,
+    ) -> Result<i64, sqlx::Error> {
+        let metadata = record.metadata();
+        let mut tx = self.pool.begin().await?;
+        // An id of 0 takes the next one from the sequence.
+        let explicit_id = (record.id() != 0).then_some(record.id());
+        let id: i64 = sqlx::query(
+            "INSERT INTO images (id, path, updated_on, hash, perceptual_hash, created_on, width, \
+                height, color_type, bit_depth, original_format, original_size, stored_size, \
+                filename, original_path, checksum, owner_id) \
+                VALUES (COALESCE($1, nextval(pg_get_serial_sequence('images', 'id'))), $2, $3, \
+                $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17) \
+                RETURNING id",
+        )
+        .bind(explicit_id)
+        .bind(record.path())
+        .bind(record.updated_on().to_string())
+        .bind(record.hash())
+        .bind(record.perceptual_hash().map(|hash| hash as i64))
+        .bind(record.created_on().to_string())
+        .bind(metadata.map(|metadata| metadata.width() as i64))
+        .bind(metadata.map(|metadata| metadata.height() as i64))
+        .bind(metadata.map(|metadata| metadata.color_type()))
+        .bind(metadata.map(|metadata| metadata.bit_depth() as i64))
+        .bind(metadata.map(|metadata| metadata.original_format()))
+        .bind(metadata.map(|metadata| metadata.original_size() as i64))
+        .bind(metadata.map(|metadata| metadata.stored_size() as i64))
+        .bind(metadata.and_then(|metadata| metadata.filename()))
+        .bind(record.original_path())
+        .bind(record.checksum())
+        .bind(current_user_id())
+        .fetch_one(&mut tx)
+        .await?
+        .get("id");
+        if explicit_id.is_some() {
+            // Keeps the sequence ahead of ids inserted explicitly.
+            sqlx::query(
+                "SELECT setval(pg_get_serial_sequence('images', 'id'), \
+                    GREATEST((SELECT MAX(id) FROM images), 1))",
+            )
+            .execute(&mut tx)
+            .await?;
+        }
+        if let Some(exif) = record.exif() {
+            sqlx::query(
+                "INSERT INTO image_exif (image_id, captured_on, make, model, lens, exposure_time, \
+                    f_number, iso, focal_length, latitude, longitude, altitude, orientation) \
+                    VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13)",
+            )
+            .bind(id)
+            .bind(
+                exif.captured_on()
+                    .map(|captured_on| captured_on.to_string()),
+            )
+            .bind(exif.make())
+            .bind(exif.model())
+            .bind(exif.lens())
+            .bind(exif.exposure_time())
+            .bind(exif.f_number())
+            .bind(exif.iso().map(|iso| iso as i64))
+            .bind(exif.focal_length())
+            .bind(exif.latitude())
+            .bind(exif.longitude())
+            .bind(exif.altitude())
+            .bind(exif.orientation().map(|orientation| orientation as i64))
+            .execute(&mut tx)
+            .await?;
+        }
+        if let Some(persist) = persist {
+            persist.await.map_err(sqlx::Error::Io)?;
+        }
+        tx.commit().await?;
+        Ok(id)
+    }
+
+    async fn add_tag_records(
+        &self,
+        image_id: i64,
+        tags: &[String],
+    ) -> Result<Vec<String>, sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+        sqlx::query(&format!(
+            "SELECT id FROM images WHERE id = $1 AND deleted_at IS NULL{}",
+            owner_filter("images")
+        ))
+        .bind(image_id)
+        .fetch_one(&mut tx)
+        .await?;
+        for tag in tags {
+            sqlx::query("INSERT INTO tags (name) VALUES ($1) ON CONFLICT (name) DO NOTHING")
+                .bind(tag)
+                .execute(&mut tx)
+                .await?;
+            sqlx::query(
+                "INSERT INTO image_tags (image_id, tag_id) \
+                    SELECT $1, id FROM tags WHERE name = $2 \
+                    ON CONFLICT (image_id, tag_id) DO NOTHING",
+            )
+            .bind(image_id)
+            .bind(tag)
+            .execute(&mut tx)
+            .await?;
+        }
+        let records = sqlx::query(
+            "SELECT tags.name FROM tags \
+                JOIN image_tags ON image_tags.tag_id = tags.id \
+                WHERE image_tags.image_id = $1 \
+                ORDER BY tags.name",
+        )
+        .bind(image_id)
+        .fetch_all(&mut tx)
+        .await?;
+        tx.commit().await?;
+        Ok(records
+            .into_iter()
+            .map(|record| record.get("name"))
+            .collect())
+    }
+
+    /// Returns false when the image did not have the tag. Tags left without
+    /// images are dropped.
+    async fn remove_tag_record(&self, image_id: i64, tag: &str) -> Result<bool, sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+        let removed = sqlx::query(&format!(
+            "DELETE FROM image_tags \
+                WHERE image_id = $1 AND tag_id = (SELECT id FROM tags WHERE name = $2) \
+                AND image_id IN (SELECT id FROM images WHERE TRUE{})",
+            owner_filter("images")
+        ))
+        .bind(image_id)
+        .bind(tag)
+        .execute(&mut tx)
+        .await?
+        .rows_affected();
+        sqlx::query(
+            "DELETE FROM tags WHERE name = $1 \
+                AND NOT EXISTS (SELECT 1 FROM image_tags WHERE image_tags.tag_id = tags.id)",
+        )
+        .bind(tag)
+        .execute(&mut tx)
+        .await?;
+        tx.commit().await?;
+        Ok(removed > 0)
+    }
+
+    /// Returns false when there is no tag named `from`.
+    async fn rename_tag_record(&self, from: &str, to: &str) -> Result<bool, sqlx::Error> {
+        if let Some(owner_id) = current_user_id() {
+            return self.rename_owned_tag_records(owner_id, from, to).await;
+        }
+        let mut tx = self.pool.begin().await?;
+        let from_id: i64 = match sqlx::query("SELECT id FROM tags WHERE name = $1")
+            .bind(from)
+            .fetch_optional(&mut tx)
+            .await?
+        {
+            Some(record) => record.get("id"),
+            None => return Ok(false),
+        };
+        let to_id = sqlx::query("SELECT id FROM tags WHERE name = $1")
+            .bind(to)
+            .fetch_optional(&mut tx)
+            .await?;
+        match to_id {
+            None => {
+                sqlx::query("UPDATE tags SET name = $1 WHERE id = $2")
+                    .bind(to)
+                    .bind(from_id)
+                    .execute(&mut tx)
+                    .await?;
+            }
+            Some(record) => {
+                sqlx::query(
+                    "INSERT INTO image_tags (image_id, tag_id) \
+                        SELECT image_id, $1 FROM image_tags WHERE tag_id = $2 \
+                        ON CONFLICT (image_id, tag_id) DO NOTHING",
+                )
+                .bind(record.get::<i64, &str>("id"))
+                .bind(from_id)
+                .execute(&mut tx)
+                .await?;
+                sqlx::query("DELETE FROM tags WHERE id = $1")
+                    .bind(from_id)
+                    .execute(&mut tx)
+                    .await?;
+            }
+        }
+        tx.commit().await?;
+        Ok(true)
+    }
+
+    /// Tag names are shared between users, so on behalf of one only their
+    /// images move from `from` to `to`. Returns false when none of them has
+    /// `from`.
+    async fn rename_owned_tag_records(
+        &self,
+        owner_id: i64,
+        from: &str,
+        to: &str,
+    ) -> Result<bool, sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+        sqlx::query("INSERT INTO tags (name) VALUES ($1) ON CONFLICT (name) DO NOTHING")
+            .bind(to)
+            .execute(&mut tx)
+            .await?;
+        sqlx::query(
+            "INSERT INTO image_tags (image_id, tag_id) \
+                SELECT image_tags.image_id, (SELECT id FROM tags WHERE name = $2) FROM image_tags \
+                JOIN tags ON tags.id = image_tags.tag_id \
+                JOIN images ON images.id = image_tags.image_id \
+                WHERE tags.name = $1 AND images.owner_id = $3 \
+                ON CONFLICT (image_id, tag_id) DO NOTHING",
+        )
+        .bind(from)
+        .bind(to)
+        .bind(owner_id)
+        .execute(&mut tx)
+        .await?;
+        let moved = sqlx::query(
+            "DELETE FROM image_tags \
+                WHERE tag_id = (SELECT id FROM tags WHERE name = $1) \
+                AND image_id IN (SELECT id FROM images WHERE owner_id = $2)",
+        )
+        .bind(from)
+        .bind(owner_id)
+        .execute(&mut tx)
+        .await?
+        .rows_affected();
+        if moved == 0 {
+            return Ok(false);
+        }
+        sqlx::query(
+            "DELETE FROM tags WHERE name = $1 \
+                AND NOT EXISTS (SELECT 1 FROM image_tags WHERE image_tags.tag_id = tags.id)",
+        )
+        .bind(from)
+        .execute(&mut tx)
+        .await?;
+        tx.commit().await?;
+        Ok(true)
+    }
+
+    /// Returns None when the parent album does not exist.
+    async fn create_album_record(
+        &self,
+        name: &str,
+        parent_id: Option<i64>,
+    ) -> Result<Option<i64>, sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+        if let Some(parent_id) = parent_id {
+            if !Self::album_exists(&mut tx, parent_id).await? {
+                return Ok(None);
+            }
+        }
+        let id = sqlx::query(
+            "INSERT INTO albums (name, parent_id, created_on, owner_id) VALUES ($1, $2, $3, $4) \
+                RETURNING id",
+        )
+        .bind(name)
+        .bind(parent_id)
+        .bind(Utc::now().to_string())
+        .bind(current_user_id())
+        .fetch_one(&mut tx)
+        .await?
+        .get("id");
+        tx.commit().await?;
+        Ok(Some(id))
+    }
+
+    async fn update_album_record(
+        &self,
+        id: i64,
+        update: &AlbumUpdate,
+    ) -> Result<Result<(), AlbumsError>, sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+        if !Self::album_exists(&mut tx, id).await? {
+            return Ok(Err(AlbumsError::AlbumNotFound));
+        }
+        if let Some(name) = &update.name {
+            sqlx::query("UPDATE albums SET name = $1 WHERE id = $2")
+                .bind(name)
+                .bind(id)
+                .execute(&mut tx)
+                .await?;
+        }
+        if let Some(parent_id) = update.parent_id {
+            if let Some(parent_id) = parent_id {
+                if !Self::album_exists(&mut tx, parent_id).await? {
+                    return Ok(Err(AlbumsError::AlbumNotFound));
+                }
+                // Moving an album below one of its own descendants would
+                // detach the whole branch into a cycle.
+                let cycle: i64 = sqlx::query(
+                    "WITH RECURSIVE ancestors(id) AS ( \
+                        SELECT $1::BIGINT \
+                        UNION \
+                        SELECT albums.parent_id FROM albums JOIN ancestors ON albums.id = ancestors.id \
+                            WHERE albums.parent_id IS NOT NULL \
+                    ) \
+                    SELECT COUNT(*) as count FROM ancestors WHERE id = $2",
+                )
+                .bind(parent_id)
+                .bind(id)
+                .fetch_one(&mut tx)
+                .await?
+                .get("count");
+                if cycle > 0 {
+                    return Ok(Err(AlbumsError::InvalidParent));
+                }
+            }
+            sqlx::query("UPDATE albums SET parent_id = $1 WHERE id = $2")
+                .bind(parent_id)
+                .bind(id)
+                .execute(&mut tx)
+                .await?;
+        }
+        if let Some(cover_image_id) = update.cover_image_id {
+            if let Some(cover_image_id) = cover_image_id {
+                let member = sqlx::query(
+                    "SELECT image_id FROM album_images WHERE album_id = $1 AND image_id = $2",
+                )
+                .bind(id)
+                .bind(cover_image_id)
+                .fetch_optional(&mut tx)
+                .await?;
+                if member.is_none() {
+                    return Ok(Err(AlbumsError::ImageNotFound));
+                }
+            }
+            sqlx::query("UPDATE albums SET cover_image_id = $1 WHERE id = $2")
+                .bind(cover_image_id)
+                .bind(id)
+                .execute(&mut tx)
+                .await?;
+        }
+        tx.commit().await?;
+        Ok(Ok(()))
+    }
+
+    /// Returns false when the album does not exist. Nested albums go with it
+    /// through the `parent_id` foreign key.
+    async fn delete_album_records(&self, id: i64, cascade: bool) -> Result<bool, sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+        if !Self::album_exists(&mut tx, id).await? {
+            return Ok(false);
+        }
+        if cascade {
+            sqlx::query(&format!(
+                "WITH RECURSIVE subtree(id) AS ( \
+                    SELECT $1::BIGINT \
+                    UNION \
+                    SELECT albums.id FROM albums JOIN subtree ON albums.parent_id = subtree.id \
+                ) \
+                UPDATE images SET deleted_at = $2 \
+                    WHERE deleted_at IS NULL{} AND id IN ( \
+                        SELECT image_id FROM album_images \
+                            WHERE album_id IN (SELECT id FROM subtree) \
+                    )",
+                owner_filter("images")
+            ))
+            .bind(id)
+            .bind(Utc::now().to_string())
+            .execute(&mut tx)
+            .await?;
+        }
+        sqlx::query("DELETE FROM albums WHERE id = $1")
+            .bind(id)
+            .execute(&mut tx)
+            .await?;
+        tx.commit().await?;
+        Ok(true)
+    }
+
+    async fn add_album_image_records(
+        &self,
+        album_id: i64,
+        image_ids: &[i64],
+    ) -> Result<Result<(), AlbumsError>, sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+        if !Self::album_exists(&mut tx, album_id).await? {
+            return Ok(Err(AlbumsError::AlbumNotFound));
+        }
+        for image_id in image_ids {
+            let image = sqlx::query(&format!(
+                "SELECT id FROM images WHERE id = $1 AND deleted_at IS NULL{}",
+                owner_filter("images")
+            ))
+            .bind(image_id)
+            .fetch_optional(&mut tx)
+            .await?;
+            if image.is_none() {
+                return Ok(Err(AlbumsError::ImageNotFound));
+            }
+            sqlx::query(
+                "INSERT INTO album_images (album_id, image_id, position) \
+                    SELECT $1, $2, COALESCE(MAX(position) + 1, 0) FROM album_images \
+                    WHERE album_id = $1 \
+                    ON CONFLICT (album_id, image_id) DO NOTHING",
+            )
+            .bind(album_id)
+            .bind(image_id)
+            .execute(&mut tx)
+            .await?;
+        }
+        tx.commit().await?;
+        Ok(Ok(()))
+    }
+
+    /// Removing the cover image leaves the album without one.
+    async fn remove_album_image_record(
+        &self,
+        album_id: i64,
+        image_id: i64,
+    ) -> Result<Result<(), AlbumsError>, sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+        if !Self::album_exists(&mut tx, album_id).await? {
+            return Ok(Err(AlbumsError::AlbumNotFound));
+        }
+        let removed = sqlx::query("DELETE FROM album_images WHERE album_id = $1 AND image_id = $2")
+            .bind(album_id)
+            .bind(image_id)
+            .execute(&mut tx)
+            .await?
+            .rows_affected();
+        if removed == 0 {
+            return Ok(Err(AlbumsError::ImageNotFound));
+        }
+        sqlx::query(
+            "UPDATE albums SET cover_image_id = NULL WHERE id = $1 AND cover_image_id = $2",
+        )
+        .bind(album_id)
+        .bind(image_id)
+        .execute(&mut tx)
+        .await?;
+        tx.commit().await?;
+        Ok(Ok(()))
+    }
+
+    async fn reorder_album_image_records(
+        &self,
+        album_id: i64,
+        image_ids: &[i64],
+    ) -> Result<Result<(), AlbumsError>, sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+        if !Self::album_exists(&mut tx, album_id).await? {
+            return Ok(Err(AlbumsError::AlbumNotFound));
+        }
+        let mut members = sqlx::query("SELECT image_id FROM album_images WHERE album_id = $1")
+            .bind(album_id)
+            .fetch_all(&mut tx)
+            .await?
+            .into_iter()
+            .map(|record| record.get("image_id"))
+            .collect::<Vec<i64>>();
+        let mut requested = image_ids.to_vec();
+        members.sort_unstable();
+        requested.sort_unstable();
+        if members != requested {
+            return Ok(Err(AlbumsError::InvalidOrder));
+        }
+        for (position, image_id) in image_ids.iter().enumerate() {
+            sqlx::query(
+                "UPDATE album_images SET position = $1 WHERE album_id = $2 AND image_id = $3",
+            )
+            .bind(position as i64)
+            .bind(album_id)
+            .bind(image_id)
+            .execute(&mut tx)
+            .await?;
+        }
+        tx.commit().await?;
+        Ok(Ok(()))
+    }
+
+    /// Dropping the transaction on any error, `remove` included, keeps the
+    /// image. The unique path holds back an insert of the same path until
+    /// then.
+    async fn delete_image_records(
+        &self,
+        index: i64,
+        remove: Option<RemoveFiles>,
+    ) -> Result<Vec<String>, sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+        sqlx::query(&format!(
+            "SELECT id FROM images WHERE id = $1{}",
+            owner_filter("images")
+        ))
+        .bind(index)
+        .fetch_one(&mut tx)
+        .await?;
+        let renditions = sqlx::query("DELETE FROM renditions WHERE image_id = $1 RETURNING path")
+            .bind(index)
+            .fetch_all(&mut tx)
+            .await?;
+        let record = sqlx::query("DELETE FROM images WHERE id = $1 RETURNING path, original_path")
+            .bind(index)
+            .fetch_one(&mut tx)
+            .await?;
+
+        let mut paths = vec![record.get("path")];
+        paths.extend(record.get::<Option<String>, &str>("original_path"));
+        paths.extend(
+            renditions
+                .into_iter()
+                .map(|rendition| rendition.get("path")),
+        );
+        if let Some(remove) = remove {
+            remove(paths.clone()).await.map_err(sqlx::Error::Io)?;
+        }
+        tx.commit().await?;
+        Ok(paths)
+    }
+
+    async fn batch_delete_image_records(
+        &self,
+        indexes: &[i64],
+    ) -> Result<Vec<String>, sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+        let indexes = sqlx::query(&format!(
+            "SELECT id FROM images WHERE id = ANY($1){}",
+            owner_filter("images")
+        ))
+        .bind(indexes)
+        .fetch_all(&mut tx)
+        .await?
+        .into_iter()
+        .map(|record| record.get("id"))
+        .collect::<Vec<i64>>();
+        if indexes.is_empty() {
+            return Ok(vec![]);
+        }
+        let paths = Self::delete_image_rows(&mut tx, &indexes).await?;
+        tx.commit().await?;
+        Ok(paths)
+    }
+
+    async fn purge_trash_records(
+        &self,
+        before: DateTime<Utc>,
+        remove: Option<RemoveFiles>,
+    ) -> Result<Vec<String>, sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+        let indexes = sqlx::query(&format!(
+            "SELECT id FROM images WHERE deleted_at IS NOT NULL AND deleted_at <= $1{}",
+            owner_filter("images")
+        ))
+        .bind(before.to_string())
+        .fetch_all(&mut tx)
+        .await?
+        .into_iter()
+        .map(|record| record.get("id"))
+        .collect::<Vec<i64>>();
+        if indexes.is_empty() {
+            return Ok(vec![]);
+        }
+        let paths = Self::delete_image_rows(&mut tx, &indexes).await?;
+        if let Some(remove) = remove {
+            remove(paths.clone()).await.map_err(sqlx::Error::Io)?;
+        }
+        tx.commit().await?;
+        Ok(paths)
+    }
+
+    async fn query_image_path_records(
+        &self,
+        after: i64,
+        count: i64,
+    ) -> Result<Vec<ImagePaths>, sqlx::Error> {
+        let images = sqlx::query(
+            "SELECT id, path, original_path FROM images WHERE id > $1 ORDER BY id LIMIT $2",
+        )
+        .bind(after)
+        .bind(count)
+        .fetch_all(&self.pool)
+        .await?;
+        let last: i64 = match images.last() {
+            Some(last) => last.get("id"),
+            None => return Ok(vec![]),
+        };
+        let mut renditions = HashMap::<i64, Vec<String>>::new();
+        for record in sqlx::query(
+            "SELECT image_id, path FROM renditions WHERE image_id > $1 AND image_id <= $2 ORDER BY id",
+        )
+        .bind(after)
+        .bind(last)
+        .fetch_all(&self.pool)
+        .await?
+        {
+            renditions
+                .entry(record.get("image_id"))
+                .or_default()
+                .push(record.get("path"));
+        }
+        Ok(images
+            .into_iter()
+            .map(|record| {
+                let image_id = record.get("id");
+                ImagePaths {
+                    image_id,
+                    paths: std::iter::once(record.get("path"))
+                        .chain(record.get::<Option<String>, &str>("original_path"))
+                        .chain(renditions.remove(&image_id).unwrap_or_default())
+                        .collect(),
+                }
+            })
+            .collect())
+    }
+
+    /// Dropping the transaction on any error, `persist` included, keeps the
+    /// old paths.
+    async fn relocate_path_records(
+        &self,
+        moves: &[(String, String)],
+        persist: Persist,
+    ) -> Result<(), sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+        for (from, to) in moves {
+            sqlx::query("UPDATE images SET path = $1 WHERE path = $2")
+                .bind(to)
+                .bind(from)
+                .execute(&mut tx)
+                .await?;
+            sqlx::query("UPDATE images SET original_path = $1 WHERE original_path = $2")
+                .bind(to)
+                .bind(from)
+                .execute(&mut tx)
+                .await?;
+            sqlx::query("UPDATE renditions SET path = $1 WHERE path = $2")
+                .bind(to)
+                .bind(from)
+                .execute(&mut tx)
+                .await?;
+        }
+        persist.await.map_err(sqlx::Error::Io)?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn album_exists(
+        tx: &mut Transaction<'_, Postgres>,
+        id: i64,
+    ) -> Result<bool, sqlx::Error> {
+        Ok(sqlx::query(&format!(
+            "SELECT id FROM albums WHERE id = $1{}",
+            owner_filter("albums")
+        ))
+        .bind(id)
+        .fetch_optional(&mut *tx)
+        .await?
+        .is_some())
+    }
+
+    /// Deletes the images and their renditions, returning the paths of
+    /// every file they used.
+    async fn delete_image_rows(
+        tx: &mut Transaction<'_, Postgres>,
+        indexes: &[i64],
+    ) -> Result<Vec<String>, sqlx::Error> {
+        let renditions =
+            sqlx::query("DELETE FROM renditions WHERE image_id = ANY($1) RETURNING path")
+                .bind(indexes)
+                .fetch_all(&mut *tx)
+                .await?;
+        let images =
+            sqlx::query("DELETE FROM images WHERE id = ANY($1) RETURNING path, original_path")
+                .bind(indexes)
+                .fetch_all(&mut *tx)
+                .await?;
+
+        let originals = images
+            .iter()
+            .filter_map(|record| record.get::<Option<String>, &str>("original_path"))
+            .collect::<Vec<String>>();
+        Ok(images
+            .into_iter()
+            .chain(renditions)
+            .map(|record| record.get::<String, &str>("path"))
+            .chain(originals)
+            .collect::<Vec<String>>())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::images::domain::{
+        exif::ExifData, image_metadata::ImageMetadata, user::on_behalf_of,
+    };
+    use rstest::*;
+
+    /// The adapter is only exercised when `PG_DB_URL` points at a migrated
+    /// database.
+    #[fixture]
+    async fn repository() -> Option<ImagesPostgresDS> {
+        let url = std::env::var("PG_DB_URL").ok()?;
+        let pool = PgPool::connect(&url).await.unwrap();
+        Some(ImagesPostgresDS::new(pool))
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn test_insert_and_query_image(
+        repository: impl std::future::Future<Output = Option<ImagesPostgresDS>>,
+    ) {
+        let Some(repository) = repository.await else {
+            return;
+        };
+        let metadata = ImageMetadata::new(3, 2, "Rgb8".to_string(), 8, "png".to_string(), 10, 5)
+            .with_filename("image2000.png".to_string());
+        let exif = ExifData::default()
+            .with_camera(Some("Make".to_string()), None)
+            .with_orientation(6);
+        let image = Image::new(2000, "path/to/image2000".to_string(), Utc::now())
+            .with_hash("hash2000".to_string())
+            .with_perceptual_hash(u64::MAX)
+            .with_metadata(metadata)
+            .with_exif(exif);
+        let persist: Persist = Box::pin(async { Err(std::io::ErrorKind::NotFound.into()) });
+        let result = repository.insert_image_with(&image, persist).await;
+        assert!(matches!(result, Err(InsertImageError::InternalError)));
+        assert!(repository.query_image(2000).await.is_err());
+
+        let id = repository
+            .insert_image_with(&image, Box::pin(async { Ok(()) }))
+            .await
+            .unwrap();
+        assert_eq!(id, 2000);
+        assert!(matches!(
+            repository.insert_image(&image).await,
+            Err(InsertImageError::AlreadyExists)
+        ));
+        let queried = repository.query_image(2000).await.unwrap();
+        assert_eq!(queried.metadata(), image.metadata());
+        assert_eq!(queried.exif(), image.exif());
+        assert_eq!(queried.perceptual_hash(), Some(u64::MAX));
+        let queried = repository.query_image_by_hash("hash2000").await.unwrap();
+        assert_eq!(queried.id(), 2000);
+
+        // Ids assigned by the sequence never collide with explicit ones.
+        let assigned = Image::new(0, "path/to/image2000-assigned".to_string(), Utc::now());
+        let assigned = repository.insert_image(&assigned).await.unwrap();
+        assert!(assigned > 2000);
+        repository.delete_image(assigned).await.unwrap();
+        let paths = repository.delete_image(2000).await.unwrap();
+        assert_eq!(paths, vec!["path/to/image2000".to_string()]);
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn test_tags(repository: impl std::future::Future<Output = Option<ImagesPostgresDS>>) {
+        let Some(repository) = repository.await else {
+            return;
+        };
+        for id in [2010, 2011] {
+            let image = Image::new(id, format!("path/to/image{}", id), Utc::now());
+            repository.insert_image(&image).await.unwrap();
+        }
+        let both = ["tag2010".to_string(), "tag2010-common".to_string()];
+        let tags = repository.add_tags(2010, &both).await.unwrap();
+        assert_eq!(tags, both.to_vec());
+        repository
+            .add_tags(2011, &["tag2010-common".to_string()])
+            .await
+            .unwrap();
+        assert_eq!(
+            repository.add_tags(2019, &both).await,
+            Err(TagsError::ImageNotFound)
+        );
+
+        let tags = repository.query_tags().await.unwrap();
+        assert!(tags.contains(&Tag::new("tag2010-common".to_string(), 2)));
+        let all = repository
+            .query_images_by_tags(&both, TagMatch::All, 10, 0, SortKey::UpdatedOn)
+            .await
+            .unwrap();
+        assert_eq!(all.iter().map(Image::id).collect::<Vec<i64>>(), vec![2010]);
+
+        repository
+            .rename_tag("tag2010", "tag2010-common")
+            .await
+            .unwrap();
+        let tags = repository.query_tags().await.unwrap();
+        assert!(!tags.iter().any(|tag| tag.name() == "tag2010"));
+        repository.remove_tag(2010, "tag2010-common").await.unwrap();
+        assert_eq!(
+            repository.remove_tag(2010, "tag2010-common").await,
+            Err(TagsError::TagNotFound)
+        );
+        repository
+            .batch_delete_image(vec![2010, 2011])
+            .await
+            .unwrap();
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn test_api_keys(
+        repository: impl std::future::Future<Output = Option<ImagesPostgresDS>>,
+    ) {
+        let Some(repository) = repository.await else {
+            return;
+        };
+        let hash = format!("postgres-key-{}", hex::encode(rand::random::<[u8; 8]>()));
+        let key = repository
+            .insert_api_key(
+                "postgres-key",
+                &hash,
+                &[Scope::Read, Scope::Upload],
+                Utc::now(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(key.scopes(), [Scope::Read, Scope::Upload]);
+        assert_eq!(
+            repository
+                .insert_api_key("postgres-key", &hash, &[Scope::Read], Utc::now())
+                .await,
+            Err(ApiKeysError::AlreadyExists)
+        );
+        assert_eq!(
+            repository.query_api_key_by_hash(&hash).await,
+            Ok(key.clone())
+        );
+        assert!(repository.query_api_keys().await.unwrap().contains(&key));
+
+        repository
+            .revoke_api_key(key.id(), Utc::now())
+            .await
+            .unwrap();
+        let revoked = repository.query_api_key_by_hash(&hash).await.unwrap();
+        assert!(revoked.revoked_on().is_some());
+        assert_eq!(
+            repository.revoke_api_key(key.id(), Utc::now()).await,
+            Err(ApiKeysError::KeyNotFound)
+        );
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn test_shares(repository: impl std::future::Future<Output = Option<ImagesPostgresDS>>) {
+        let Some(repository) = repository.await else {
+            return;
+        };
+        let image = Image::new(2150, "path/to/image2150".to_string(), Utc::now());
+        repository.insert_image(&image).await.unwrap();
+        let created_on = "2023-11-11T10:00:00Z".parse::<DateTime<Utc>>().unwrap();
+        let expires_on = "2023-11-12T10:00:00Z".parse::<DateTime<Utc>>().unwrap();
+        let share = repository
+            .insert_share(2150, created_on, expires_on)
+            .await
+            .unwrap();
+        assert_eq!(share.image_id(), 2150);
+        assert_eq!(repository.query_share(share.id()).await, Ok(share.clone()));
+        assert_eq!(
+            repository.insert_share(-1, created_on, expires_on).await,
+            Err(SharesError::ImageNotFound)
+        );
+
+        repository
+            .revoke_share(share.id(), expires_on)
+            .await
+            .unwrap();
+        assert_eq!(
+            repository
+                .query_share(share.id())
+                .await
+                .unwrap()
+                .revoked_on(),
+            Some(expires_on)
+        );
+        assert_eq!(
+            repository.revoke_share(share.id(), expires_on).await,
+            Err(SharesError::ShareNotFound)
+        );
+        repository.delete_image(2150).await.unwrap();
+        assert_eq!(
+            repository.query_share(share.id()).await,
+            Err(SharesError::ShareNotFound)
+        );
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn test_users(repository: impl std::future::Future<Output = Option<ImagesPostgresDS>>) {
+        let Some(repository) = repository.await else {
+            return;
+        };
+        let name = format!("pg-user-{}", hex::encode(rand::random::<[u8; 8]>()));
+        let user = repository
+            .insert_user(&name, "password-hash", Utc::now())
+            .await
+            .unwrap();
+        assert_eq!(
+            repository
+                .insert_user(&name, "other-hash", Utc::now())
+                .await,
+            Err(UsersError::AlreadyExists)
+        );
+        assert_eq!(
+            repository.query_user_by_name(&name).await,
+            Ok((user.clone(), "password-hash".to_string()))
+        );
+        let token_hash = format!("{}-session", name);
+        repository
+            .insert_session(
+                user.id(),
+                &token_hash,
+                Utc::now() + chrono::Duration::days(1),
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            repository.query_session_user(&token_hash, Utc::now()).await,
+            Ok(user.clone())
+        );
+        assert_eq!(
+            repository
+                .query_session_user(&token_hash, Utc::now() + chrono::Duration::days(2))
+                .await,
+            Err(UsersError::UserNotFound)
+        );
+        repository.delete_session(&token_hash).await.unwrap();
+        assert_eq!(
+            repository.delete_session(&token_hash).await,
+            Err(UsersError::UserNotFound)
+        );
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn test_owner_isolation(
+        repository: impl std::future::Future<Output = Option<ImagesPostgresDS>>,
+    ) {
+        let Some(repository) = repository.await else {
+            return;
+        };
+        let suffix = hex::encode(rand::random::<[u8; 8]>());
+        let alice = repository
+            .insert_user(&format!("pg-alice-{}", suffix), "hash", Utc::now())
+            .await
+            .unwrap();
+        let bob = repository
+            .insert_user(&format!("pg-bob-{}", suffix), "hash", Utc::now())
+            .await
+            .unwrap();
+        let hash = format!("pg-hash-{}", suffix);
+        let image =
+            Image::new(0, format!("path/to/alice-{}", suffix), Utc::now()).with_hash(hash.clone());
+        let id = on_behalf_of(alice.id(), repository.insert_image(&image))
+            .await
+            .unwrap();
+        // The same content is no duplicate for another user.
+        let image =
+            Image::new(0, format!("path/to/bob-{}", suffix), Utc::now()).with_hash(hash.clone());
+        let bob_id = on_behalf_of(bob.id(), repository.insert_image(&image))
+            .await
+            .unwrap();
+
+        assert!(on_behalf_of(alice.id(), repository.query_image(id))
+            .await
+            .is_ok());
+        assert!(matches!(
+            on_behalf_of(bob.id(), repository.query_image(id)).await,
+            Err(query_image_port::QueryError::RecordNotFound)
+        ));
+        assert_eq!(
+            on_behalf_of(bob.id(), repository.query_image_by_hash(&hash))
+                .await
+                .unwrap()
+                .id(),
+            bob_id
+        );
+        assert!(on_behalf_of(bob.id(), repository.delete_image(id))
+            .await
+            .is_err());
+        assert!(
+            on_behalf_of(bob.id(), repository.batch_delete_image(vec![id]))
+                .await
+                .unwrap()
+                .is_empty()
+        );
+        let album = on_behalf_of(alice.id(), repository.create_album("Alice", None))
+            .await
+            .unwrap();
+        assert_eq!(
+            on_behalf_of(bob.id(), repository.query_album(album.id())).await,
+            Err(AlbumsError::AlbumNotFound)
+        );
+
+        // Without a user, as for API keys, everything is visible.
+        assert!(repository.query_image(id).await.is_ok());
+        repository.delete_album(album.id(), false).await.unwrap();
+        repository.delete_image(id).await.unwrap();
+        repository.delete_image(bob_id).await.unwrap();
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn test_albums(repository: impl std::future::Future<Output = Option<ImagesPostgresDS>>) {
+        let Some(repository) = repository.await else {
+            return;
+        };
+        for id in 2020..=2022 {
+            let image = Image::new(id, format!("path/to/image{}", id), Utc::now());
+            repository.insert_image(&image).await.unwrap();
+        }
+        let root = repository.create_album("Root 2020", None).await.unwrap();
+        let child = repository
+            .create_album("Child 2020", Some(root.id()))
+            .await
+            .unwrap();
+        let children = repository.query_albums(Some(root.id())).await.unwrap();
+        assert_eq!(children, vec![child.clone()]);
+
+        let ids = |images: Vec<Image>| images.iter().map(Image::id).collect::<Vec<i64>>();
+        repository
+            .add_images(root.id(), &[2021, 2020])
+            .await
+            .unwrap();
+        repository
+            .reorder_images(root.id(), &[2020, 2021])
+            .await
+            .unwrap();
+        let images = repository
+            .query_album_images(root.id(), 10, 0)
+            .await
+            .unwrap();
+        assert_eq!(ids(images), vec![2020, 2021]);
+
+        let update = AlbumUpdate {
+            cover_image_id: Some(Some(2021)),
+            ..AlbumUpdate::default()
+        };
+        let album = repository.update_album(root.id(), &update).await.unwrap();
+        assert_eq!(album.cover_image_id(), Some(2021));
+        assert_eq!(album.image_count(), 2);
+        let update = AlbumUpdate {
+            parent_id: Some(Some(child.id())),
+            ..AlbumUpdate::default()
+        };
+        assert_eq!(
+            repository.update_album(root.id(), &update).await,
+            Err(AlbumsError::InvalidParent)
+        );
+
+        repository.add_images(child.id(), &[2022]).await.unwrap();
+        repository.delete_album(root.id(), true).await.unwrap();
+        assert!(repository.query_image(2022).await.is_err());
+        let paths = repository
+            .batch_delete_image(vec![2020, 2021, 2022])
+            .await
+            .unwrap();
+        assert_eq!(paths.len(), 3);
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn test_search_images(
+        repository: impl std::future::Future<Output = Option<ImagesPostgresDS>>,
+    ) {
+        let Some(repository) = repository.await else {
+            return;
+        };
+        let created_on = "2001-02-03T04:05:06Z".parse::<DateTime<Utc>>().unwrap();
+        for (id, width, height) in [(2030, 300, 200), (2031, 200, 300)] {
+            let metadata = ImageMetadata::new(
+                width,
+                height,
+                "Rgb8".to_string(),
+                8,
+                "png".to_string(),
+                10,
+                10,
+            );
+            let image = Image::new(id, format!("path/to/image{}", id), Utc::now())
+                .with_created_on(created_on + chrono::Duration::seconds(id))
+                .with_metadata(metadata);
+            repository.insert_image(&image).await.unwrap();
+        }
+        repository
+            .add_tags(2031, &["tag2030".to_string()])
+            .await
+            .unwrap();
+        let ids = |images: Vec<Image>| images.iter().map(Image::id).collect::<Vec<i64>>();
+        let in_range = SearchQuery {
+            created_after: Some(created_on),
+            created_before: Some(created_on + chrono::Duration::days(1)),
+            ..SearchQuery::default()
+        };
+        let query = SearchQuery {
+            sort: SortKey::CreatedOn,
+            direction: SortDirection::Descending,
+            ..in_range.clone()
+        };
+        let images = repository.search_images(&query).await.unwrap();
+        assert_eq!(ids(images), vec![2031, 2030]);
+        let query = SearchQuery {
+            min_width: Some(250),
+            shape: Some(Shape::Landscape),
+            ..in_range.clone()
+        };
+        let images = repository.search_images(&query).await.unwrap();
+        assert_eq!(ids(images), vec![2030]);
+        let query = SearchQuery {
+            tags: vec!["tag2030".to_string()],
+            tag_match: TagMatch::All,
+            ..in_range
+        };
+        let images = repository.search_images(&query).await.unwrap();
+        assert_eq!(ids(images), vec![2031]);
+        repository
+            .batch_delete_image(vec![2030, 2031])
+            .await
+            .unwrap();
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn test_trash(repository: impl std::future::Future<Output = Option<ImagesPostgresDS>>) {
+        let Some(repository) = repository.await else {
+            return;
+        };
+        for id in [2040, 2041] {
+            let image = Image::new(id, format!("path/to/image{}", id), Utc::now());
+            repository.insert_image(&image).await.unwrap();
+        }
+        let deleted_at = DateTime::parse_from_rfc3339("2001-02-03T04:05:06Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let trashed = repository
+            .trash_images(&[2040, 2041, i64::MAX], deleted_at)
+            .await
+            .unwrap();
+        assert_eq!(trashed, 2);
+        assert!(repository.query_image(2040).await.is_err());
+        repository.restore_image(2041).await.unwrap();
+        assert_eq!(
+            repository.restore_image(2041).await,
+            Err(TrashError::ImageNotFound)
+        );
+        let paths = repository
+            .purge_trash(deleted_at + chrono::Duration::seconds(1))
+            .await
+            .unwrap();
+        assert_eq!(paths, vec!["path/to/image2040".to_string()]);
+        repository.delete_image(2041).await.unwrap();
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn test_files(repository: impl std::future::Future<Output = Option<ImagesPostgresDS>>) {
+        let Some(repository) = repository.await else {
+            return;
+        };
+        let image = Image::new(2050, "data/abcd2050.qoi".to_string(), Utc::now())
+            .with_original_path("data/abcd2050.png".to_string());
+        repository.insert_image(&image).await.unwrap();
+        let rendition = Rendition::new(
+            2050,
+            RenditionSize::Thumb,
+            "data/abcd2050_thumb.qoi".to_string(),
+        );
+        repository.insert_rendition(&rendition).await.unwrap();
+        let referenced = repository.query_referenced_paths().await.unwrap();
+        assert!(referenced.contains("data/abcd2050.png"));
+        assert!(referenced.contains("data/abcd2050_thumb.qoi"));
+
+        repository
+            .record_scrub(2050, ImageStatus::Corrupted, None, Utc::now())
+            .await
+            .unwrap();
+        let corrupted = repository
+            .query_images_by_status(ImageStatus::Corrupted)
+            .await
+            .unwrap();
+        assert!(corrupted.iter().any(|image| image.id() == 2050));
+
+        let moves = vec![(
+            "data/abcd2050_thumb.qoi".to_string(),
+            "data/ab/cd/abcd2050_thumb.qoi".to_string(),
+        )];
+        repository
+            .relocate_paths(&moves, Box::pin(async { Ok(()) }))
+            .await
+            .unwrap();
+        let images = repository.query_image_paths(2049, 1).await.unwrap();
+        assert_eq!(
+            images[0].paths,
+            vec![
+                "data/abcd2050.qoi".to_string(),
+                "data/abcd2050.png".to_string(),
+                "data/ab/cd/abcd2050_thumb.qoi".to_string(),
+            ]
+        );
+        let paths = repository.delete_image(2050).await.unwrap();
+        assert_eq!(paths.len(), 3);
+    }
+}
@@ -1,58 +1,52 @@
+use std::collections::{HashMap, HashSet};
+
 use async_trait::async_trait;
 use chrono::{DateTime, Utc};
-use sqlx::{Row, SqlitePool};
+use sqlx::{QueryBuilder, Row, Sqlite, SqlitePool, Transaction};
 use tracing::error;
 
+use super::records::{
+    order_by, owner_filter, AlbumRecord, ApiKeyRecord, ImageRecord, ShareRecord, UserRecord,
+    IMAGE_SELECT,
+};
 use crate::services::images::{
-    domain::image::Image,
+    domain::{
+        album::{Album, AlbumUpdate},
+        api_key::{format_scopes, ApiKey, Scope},
+        consistency::ImageFile,
+        cursor::Cursor,
+        image::Image,
+        image_status::ImageStatus,
+        rendition::{Rendition, RenditionSize},
+        search::{SearchQuery, Shape},
+        share::Share,
+        sort_key::{SortDirection, SortKey},
+        storage_layout::ImagePaths,
+        tag::{Tag, TagMatch},
+        user::{current_user_id, User},
+    },
     ports::outgoing::{
+        albums_port::{AlbumsError, AlbumsPort},
+        api_keys_port::{ApiKeysError, ApiKeysPort},
         batch_delete_image_port::{BatchDeleteError, BatchDeleteImagePort},
         batch_query_image_port::{self, BatchQueryImagesPort},
-        delete_image_port::{DeleteImageError, DeleteImagePort},
-        insert_image_port::{InsertImageError, InsertImagePort},
+        consistency_port::{ConsistencyError, ConsistencyPort},
+        delete_image_port::{DeleteImageError, DeleteImagePort, RemoveFiles},
+        insert_image_port::{InsertImageError, InsertImagePort, Persist},
+        layout_port::{LayoutError, LayoutPort},
+        query_image_by_hash_port::QueryImageByHashPort,
         query_image_port::{self, QueryImagePort},
+        rendition_port::RenditionPort,
+        scrub_port::{ScrubError, ScrubPort},
+        search_images_port::SearchImagesPort,
+        shares_port::{SharesError, SharesPort},
+        similar_images_port::SimilarImagesPort,
+        tags_port::{TagsError, TagsPort},
+        trash_port::{TrashError, TrashPort},
+        users_port::{UsersError, UsersPort},
     },
 };
 
-impl From<sqlx::Error> for query_image_port::QueryError {
-    fn from(value: sqlx::Error) -> Self {
-        match value {
-            sqlx::Error::RowNotFound => query_image_port::QueryError::RecordNotFound,
-            _ => query_image_port::QueryError::InternalError,
-        }
-    }
-}
-
-impl From<sqlx::Error> for batch_query_image_port::QueryError {
-    fn from(value: sqlx::Error) -> Self {
-        match value {
-            sqlx::Error::RowNotFound => batch_query_image_port::QueryError::RecordNotFound,
-            _ => batch_query_image_port::QueryError::InternalError,
-        }
-    }
-}
-
-impl From<sqlx::Error> for BatchDeleteError {
-    fn from(_value: sqlx::Error) -> Self {
-        BatchDeleteError::InternalError
-    }
-}
-
-impl From<sqlx::Error> for DeleteImageError {
-    fn from(value: sqlx::Error) -> Self {
-        match value {
-            sqlx::Error::RowNotFound => DeleteImageError::RecordNotFound,
-            _ => DeleteImageError::InternalError,
-        }
-    }
-}
-
-impl From<sqlx::Error> for InsertImageError {
-    fn from(_value: sqlx::Error) -> Self {
-        InsertImageError::InternalError
-    }
-}
-
 pub struct ImagesSqliteDS {
     pool: SqlitePool,
 }
@@ -60,12 +54,20 @@ pub struct ImagesSqliteDS {
 #[async_trait]
 impl QueryImagePort for ImagesSqliteDS {
     async fn query_image(&self, index: i64) -> Result<Image, query_image_port::QueryError> {
-        let record = match sqlx::query!(
+        let owner_id = current_user_id();
+        let record = match sqlx::query_as!(
+            ImageRecord,
             r#"
-                        SELECT id, path, updated_on FROM images 
-                            WHERE id = ?1
+                        SELECT id, path, updated_on, hash, perceptual_hash, created_on, width, height,
+                            color_type, bit_depth, original_format, original_size, stored_size, filename,
+                            original_path, image_id as "exif_id?", captured_on, make, model, lens,
+                            exposure_time, f_number, iso, focal_length, latitude, longitude, altitude,
+                            orientation, deleted_at, status as "status!", checksum
+                            FROM images LEFT JOIN image_exif ON image_id = id
+                            WHERE id = ?1 AND deleted_at IS NULL AND (?2 IS NULL OR owner_id = ?2)
                     "#,
-            index
+            index,
+            owner_id
         )
         .fetch_one(&self.pool)
         .await
@@ -80,13 +82,43 @@ impl QueryImagePort for ImagesSqliteDS {
                 return Err(e.into());
             }
         };
-
-        let created_on = record
-            .updated_on
-            .parse::<DateTime<Utc>>()
-            .unwrap_or(Utc::now());
-        let image = Image::new(record.id, record.path, created_on);
-        Ok(image)
+        Ok(record.into())
+    }
+}
+#[async_trait]
+impl QueryImageByHashPort for ImagesSqliteDS {
+    async fn query_image_by_hash(&self, hash: &str) -> Result<Image, query_image_port::QueryError> {
+        let owner_id = current_user_id();
+        let record = match sqlx::query_as!(
+            ImageRecord,
+            r#"
+                        SELECT id, path, updated_on, hash, perceptual_hash, created_on, width, height,
+                            color_type, bit_depth, original_format, original_size, stored_size, filename,
+                            original_path, image_id as "exif_id?", captured_on, make, model, lens,
+                            exposure_time, f_number, iso, focal_length, latitude, longitude, altitude,
+                            orientation, deleted_at, status as "status!", checksum
+                            FROM images LEFT JOIN image_exif ON image_id = id
+                            WHERE hash = ?1 AND (?2 IS NULL OR owner_id = ?2)
+                    "#,
+            hash,
+            owner_id
+        )
+        .fetch_one(&self.pool)
+        .await
+        {
+            Ok(record) => record,
+            Err(e) => {
+                if !matches!(e, sqlx::Error::RowNotFound) {
+                    error!(
+                        "Error querying image with hash: {}; message: {}",
+                        hash,
+                        e.to_string()
+                    );
+                }
+                return Err(e.into());
+            }
+        };
+        Ok(record.into())
     }
 }
 #[async_trait]
@@ -95,262 +127,3192 @@ impl BatchQueryImagesPort for ImagesSqliteDS {
         &self,
         count: i64,
         offset: i64,
+        sort: SortKey,
     ) -> Result<Vec<Image>, batch_query_image_port::QueryError> {
-        if count < 0 || offset < 0 {}
-        let recs = match sqlx::query!(
-            r#"
-                SELECT id, path, updated_on FROM images 
-                    ORDER BY updated_on
+        let owner_id = current_user_id();
+        let records = match sort {
+            SortKey::UpdatedOn => {
+                sqlx::query_as!(
+                    ImageRecord,
+                    r#"
+                SELECT id as "id!", path as "path!", updated_on as "updated_on!", hash, perceptual_hash,
+                    created_on, width, height, color_type, bit_depth, original_format,
+                    original_size, stored_size, filename, original_path, image_id as "exif_id?",
+                    captured_on, make, model, lens, exposure_time, f_number, iso, focal_length,
+                    latitude, longitude, altitude, orientation, deleted_at, status as "status!", checksum
+                    FROM images LEFT JOIN image_exif ON image_id = id
+                    WHERE deleted_at IS NULL AND (?3 IS NULL OR owner_id = ?3)
+                    ORDER BY updated_on, id
                     LIMIT ?1
                     OFFSET ?2
             "#,
-            count,
-            offset
-        )
-        .fetch_all(&self.pool)
-        .await
-        {
+                    count,
+                    offset,
+                    owner_id
+                )
+                .fetch_all(&self.pool)
+                .await
+            }
+            SortKey::CreatedOn => {
+                sqlx::query_as!(
+                    ImageRecord,
+                    r#"
+                SELECT id as "id!", path as "path!", updated_on as "updated_on!", hash, perceptual_hash,
+                    created_on, width, height, color_type, bit_depth, original_format,
+                    original_size, stored_size, filename, original_path, image_id as "exif_id?",
+                    captured_on, make, model, lens, exposure_time, f_number, iso, focal_length,
+                    latitude, longitude, altitude, orientation, deleted_at, status as "status!", checksum
+                    FROM images LEFT JOIN image_exif ON image_id = id
+                    WHERE deleted_at IS NULL AND (?3 IS NULL OR owner_id = ?3)
+                    ORDER BY created_on, id
+                    LIMIT ?1
+                    OFFSET ?2
+            "#,
+                    count,
+                    offset,
+                    owner_id
+                )
+                .fetch_all(&self.pool)
+                .await
+            }
+            SortKey::CapturedOn => {
+                sqlx::query_as!(
+                    ImageRecord,
+                    r#"
+                SELECT id as "id!", path as "path!", updated_on as "updated_on!", hash, perceptual_hash,
+                    created_on, width, height, color_type, bit_depth, original_format,
+                    original_size, stored_size, filename, original_path, image_id as "exif_id?",
+                    captured_on, make, model, lens, exposure_time, f_number, iso, focal_length,
+                    latitude, longitude, altitude, orientation, deleted_at, status as "status!", checksum
+                    FROM images LEFT JOIN image_exif ON image_id = id
+                    WHERE deleted_at IS NULL AND (?3 IS NULL OR owner_id = ?3)
+                    ORDER BY captured_on IS NULL, captured_on, updated_on, id
+                    LIMIT ?1
+                    OFFSET ?2
+            "#,
+                    count,
+                    offset,
+                    owner_id
+                )
+                .fetch_all(&self.pool)
+                .await
+            }
+        };
+        let records = match records {
             Ok(records) => records,
             Err(e) => {
                 error!(
-                    "Error querying {} images with offset {}; message: {}",
+                    "Error querying {} images with offset {} by {}; message: {}",
                     count,
                     offset,
+                    sort,
                     e.to_string()
                 );
                 return Err(e.into());
             }
         };
 
-        let images = recs
-            .into_iter()
-            .map(|record| {
-                let updated_on = record
-                    .updated_on
-                    .map(|e| e.parse::<DateTime<Utc>>().unwrap_or(Utc::now()))
-                    .unwrap_or(Utc::now());
-                Image::new(record.id.unwrap(), record.path.unwrap(), updated_on)
-            })
-            .collect();
-        Ok(images)
+        Ok(records.into_iter().map(Image::from).collect())
     }
-}
-#[async_trait]
-impl DeleteImagePort for ImagesSqliteDS {
-    async fn delete_image(&self, index: i64) -> Result<String, DeleteImageError> {
-        let record = match sqlx::query!(r#"DELETE FROM images WHERE id = ?1 RETURNING path"#, index)
-            .fetch_one(&self.pool)
-            .await
-        {
-            Ok(record) => record,
+
+    async fn query_images_after(
+        &self,
+        cursor: Option<Cursor>,
+        count: i64,
+    ) -> Result<Vec<Image>, batch_query_image_port::QueryError> {
+        let owner_id = current_user_id();
+        let records = match cursor {
+            Some(cursor) => {
+                let updated_on = cursor.updated_on().to_string();
+                let id = cursor.id();
+                sqlx::query_as!(
+                    ImageRecord,
+                    r#"
+                SELECT id as "id!", path as "path!", updated_on as "updated_on!", hash, perceptual_hash,
+                    created_on, width, height, color_type, bit_depth, original_format,
+                    original_size, stored_size, filename, original_path, image_id as "exif_id?",
+                    captured_on, make, model, lens, exposure_time, f_number, iso, focal_length,
+                    latitude, longitude, altitude, orientation, deleted_at, status as "status!", checksum
+                    FROM images LEFT JOIN image_exif ON image_id = id
+                    WHERE deleted_at IS NULL AND (updated_on > ?1 OR (updated_on = ?1 AND id > ?2))
+                        AND (?4 IS NULL OR owner_id = ?4)
+                    ORDER BY updated_on, id
+                    LIMIT ?3
+            "#,
+                    updated_on,
+                    id,
+                    count,
+                    owner_id
+                )
+                .fetch_all(&self.pool)
+                .await
+            }
+            None => {
+                sqlx::query_as!(
+                    ImageRecord,
+                    r#"
+                SELECT id as "id!", path as "path!", updated_on as "updated_on!", hash, perceptual_hash,
+                    created_on, width, height, color_type, bit_depth, original_format,
+                    original_size, stored_size, filename, original_path, image_id as "exif_id?",
+                    captured_on, make, model, lens, exposure_time, f_number, iso, focal_length,
+                    latitude, longitude, altitude, orientation, deleted_at, status as "status!", checksum
+                    FROM images LEFT JOIN image_exif ON image_id = id
+                    WHERE deleted_at IS NULL AND (?2 IS NULL OR owner_id = ?2)
+                    ORDER BY updated_on, id
+                    LIMIT ?1
+            "#,
+                    count,
+                    owner_id
+                )
+                .fetch_all(&self.pool)
+                .await
+            }
+        };
+        let records = match records {
+            Ok(records) => records,
             Err(e) => {
-                error!("Error deleting image {}; message: {}", index, e.to_string());
+                error!(
+                    "Error querying {} images after {:?}; message: {}",
+                    count,
+                    cursor,
+                    e.to_string()
+                );
                 return Err(e.into());
             }
         };
-        Ok(record.path)
+
+        Ok(records.into_iter().map(Image::from).collect())
     }
-}
-#[async_trait]
-impl BatchDeleteImagePort for ImagesSqliteDS {
-    async fn batch_delete_image(&self, indexes: Vec<i64>) -> Result<Vec<String>, BatchDeleteError> {
+
+    async fn query_images_by_tags(
+        &self,
+        tags: &[String],
+        tag_match: TagMatch,
+        count: i64,
+        offset: i64,
+        sort: SortKey,
+    ) -> Result<Vec<Image>, batch_query_image_port::QueryError> {
+        let having = match tag_match {
+            TagMatch::All => format!("HAVING COUNT(DISTINCT tags.id) = {}", tags.len()),
+            TagMatch::Any => String::new(),
+        };
         let query = format!(
-            "DELETE FROM images WHERE id in ({}) RETURNING path",
-            itertools::join(&indexes, ",")
+            "{} WHERE deleted_at IS NULL{} AND id IN (SELECT image_tags.image_id FROM image_tags \
+                JOIN tags ON tags.id = image_tags.tag_id \
+                WHERE tags.name IN ({}) GROUP BY image_tags.image_id {}) \
+                ORDER BY {} LIMIT ? OFFSET ?",
+            IMAGE_SELECT,
+            owner_filter("images"),
+            itertools::join(tags.iter().map(|_| "?"), ","),
+            having,
+            order_by(sort, SortDirection::Ascending)
         );
-        let records = match sqlx::query(&query).fetch_all(&__self.pool).await {
+        let mut query = sqlx::query_as::<_, ImageRecord>(&query);
+        for tag in tags {
+            query = query.bind(tag);
+        }
+        let records = match query.bind(count).bind(offset).fetch_all(&self.pool).await {
             Ok(records) => records,
             Err(e) => {
                 error!(
-                    "Error deleting images {:?}; message: {}",
-                    indexes,
+                    "Error querying images tagged {:?}; message: {}",
+                    tags,
                     e.to_string()
                 );
                 return Err(e.into());
             }
         };
-        let records = records
-            .into_iter()
-            .map(|record| record.get::<String, &str>("path"))
-            .collect::<Vec<String>>();
-
-        Ok(records)
+        Ok(records.into_iter().map(Image::from).collect())
     }
 }
 #[async_trait]
-impl InsertImagePort for ImagesSqliteDS {
-    async fn insert_image(&self, record: &Image) -> Result<(), InsertImageError> {
-        let id = record.id();
-        let path = record.path();
-        let updated_on = record.updated_on().to_string();
-        let result = if id == 0 {
-            sqlx::query!(
-                r#"
-                INSERT INTO images (path, updated_on) VALUES (?1, ?2)
-                "#,
-                path,
-                updated_on
-            )
-            .execute(&self.pool)
-            .await
-        } else {
-            sqlx::query!(
-                r#"
-                INSERT INTO images (id, path, updated_on) VALUES (?1, ?2, ?3)
-                "#,
-                id,
-                path,
-                updated_on
-            )
-            .execute(&self.pool)
-            .await
+impl AlbumsPort for ImagesSqliteDS {
+    async fn create_album(&self, name: &str, parent_id: Option<i64>) -> Result<Album, AlbumsError> {
+        let id = match self.create_album_record(name, parent_id).await {
+            Ok(Some(id)) => id,
+            Ok(None) => return Err(AlbumsError::AlbumNotFound),
+            Err(e) => {
+                error!(
+                    "Error creating album {} below {:?}; message: {}",
+                    name,
+                    parent_id,
+                    e.to_string()
+                );
+                return Err(AlbumsError::InternalError);
+            }
         };
+        self.query_album(id).await
+    }
 
-        match result {
-            Ok(_) => (),
+    async fn query_album(&self, id: i64) -> Result<Album, AlbumsError> {
+        let owner_id = current_user_id();
+        match sqlx::query_as!(
+            AlbumRecord,
+            r#"
+                SELECT id as "id!", name as "name!", parent_id, cover_image_id,
+                    (SELECT COUNT(*) FROM album_images JOIN images ON images.id = album_images.image_id
+                        WHERE album_id = albums.id AND deleted_at IS NULL) as "image_count!: i64",
+                    created_on as "created_on!"
+                    FROM albums WHERE id = ?1 AND (?2 IS NULL OR owner_id = ?2)
+            "#,
+            id,
+            owner_id
+        )
+        .fetch_optional(&self.pool)
+        .await
+        {
+            Ok(Some(record)) => Ok(record.into()),
+            Ok(None) => Err(AlbumsError::AlbumNotFound),
+            Err(e) => {
+                error!("Error querying album {}; message: {}", id, e.to_string());
+                Err(AlbumsError::InternalError)
+            }
+        }
+    }
+
+    async fn query_albums(&self, parent_id: Option<i64>) -> Result<Vec<Album>, AlbumsError> {
+        let owner_id = current_user_id();
+        match sqlx::query_as!(
+            AlbumRecord,
+            r#"
+                SELECT id as "id!", name as "name!", parent_id, cover_image_id,
+                    (SELECT COUNT(*) FROM album_images JOIN images ON images.id = album_images.image_id
+                        WHERE album_id = albums.id AND deleted_at IS NULL) as "image_count!: i64",
+                    created_on as "created_on!"
+                    FROM albums WHERE parent_id IS ?1 AND (?2 IS NULL OR owner_id = ?2)
+                    ORDER BY name, id
+            "#,
+            parent_id,
+            owner_id
+        )
+        .fetch_all(&self.pool)
+        .await
+        {
+            Ok(records) => Ok(records.into_iter().map(Album::from).collect()),
             Err(e) => {
                 error!(
-                    "Error inserting image {:?}; message: {}",
-                    record,
+                    "Error querying albums below {:?}; message: {}",
+                    parent_id,
                     e.to_string()
                 );
-                return Err(e.into());
+                Err(AlbumsError::InternalError)
             }
         }
-        Ok(())
     }
-}
 
-impl ImagesSqliteDS {
-    #[allow(dead_code)]
-    pub fn new(pool: SqlitePool) -> Self {
-        Self { pool }
+    async fn update_album(&self, id: i64, update: &AlbumUpdate) -> Result<Album, AlbumsError> {
+        match self.update_album_record(id, update).await {
+            Ok(Ok(())) => self.query_album(id).await,
+            Ok(Err(e)) => Err(e),
+            Err(e) => {
+                error!(
+                    "Error updating album {} with {:?}; message: {}",
+                    id,
+                    update,
+                    e.to_string()
+                );
+                Err(AlbumsError::InternalError)
+            }
+        }
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use rstest::*;
-
-    #[fixture]
-    async fn repository() -> ImagesSqliteDS {
-        println!(
-            "{} {:?}",
-            std::env::var("DB_URL").unwrap(),
-            std::env::current_dir().unwrap()
-        );
-        let pool = SqlitePool::connect(&std::env::var("DB_URL").unwrap())
-            .await
-            .unwrap();
-        let storage = ImagesSqliteDS::new(pool);
-        let image1 = image1();
-        let image2 = image2();
-        let image3 = image3();
-        let _ = storage.insert_image(&image1).await;
-        let _ = storage.insert_image(&image2).await;
-        let _ = storage.insert_image(&image3).await;
-        storage
+    async fn delete_album(&self, id: i64, cascade: bool) -> Result<(), AlbumsError> {
+        match self.delete_album_records(id, cascade).await {
+            Ok(true) => Ok(()),
+            Ok(false) => Err(AlbumsError::AlbumNotFound),
+            Err(e) => {
+                error!("Error deleting album {}; message: {}", id, e.to_string());
+                Err(AlbumsError::InternalError)
+            }
+        }
     }
 
-    fn image1() -> Image {
-        Image::new(
-            1,
-            "path/to/image1".to_string(),
-            "2023-07-12T19:29:11.113508Z"
-                .parse::<DateTime<chrono::Utc>>()
-                .unwrap(),
-        )
-    }
-    fn image2() -> Image {
-        Image::new(
-            2,
-            "path/to/image2".to_string(),
-            "2023-07-12T19:29:11.113508Z"
-                .parse::<DateTime<chrono::Utc>>()
-                .unwrap(),
-        )
-    }
-    fn image3() -> Image {
-        Image::new(
-            3,
-            "path/to/image3".to_string(),
-            "2023-07-12T19:29:11.113508Z"
-                .parse::<DateTime<chrono::Utc>>()
-                .unwrap(),
-        )
+    async fn add_images(&self, album_id: i64, image_ids: &[i64]) -> Result<(), AlbumsError> {
+        match self.add_album_image_records(album_id, image_ids).await {
+            Ok(result) => result,
+            Err(e) => {
+                error!(
+                    "Error adding images {:?} to album {}; message: {}",
+                    image_ids,
+                    album_id,
+                    e.to_string()
+                );
+                Err(AlbumsError::InternalError)
+            }
+        }
     }
 
-    #[rstest]
-    #[tokio::test]
-    async fn test_query_images(
-        repository: impl std::future::Future<Output = ImagesSqliteDS>,
-        #[values(image1())] image1: Image,
-        #[values(image2())] image2: Image,
-        #[values(image3())] image3: Image,
-    ) {
-        // Query images
-        let repository = repository.await;
-        let images = repository.query_images(2, 0).await.unwrap();
-        assert_eq!(images.len(), 2);
-        assert!(images.contains(&image1));
-        assert!(images.contains(&image2));
-
-        let images = repository.query_images(2, 1).await.unwrap();
-        assert_eq!(images.len(), 2);
-        assert!(images.contains(&image2));
-        assert!(images.contains(&image3));
+    async fn remove_image(&self, album_id: i64, image_id: i64) -> Result<(), AlbumsError> {
+        match self.remove_album_image_record(album_id, image_id).await {
+            Ok(result) => result,
+            Err(e) => {
+                error!(
+                    "Error removing image {} from album {}; message: {}",
+                    image_id,
+                    album_id,
+                    e.to_string()
+                );
+                Err(AlbumsError::InternalError)
+            }
+        }
+    }
 
-        let images = repository.query_images(1, 2).await.unwrap();
-        assert_eq!(images.len(), 1);
-        assert!(images.contains(&image3));
+    async fn reorder_images(&self, album_id: i64, image_ids: &[i64]) -> Result<(), AlbumsError> {
+        match self.reorder_album_image_records(album_id, image_ids).await {
+            Ok(result) => result,
+            Err(e) => {
+                error!(
+                    "Error reordering album {} as {:?}; message: {}",
+                    album_id,
+                    image_ids,
+                    e.to_string()
+                );
+                Err(AlbumsError::InternalError)
+            }
+        }
     }
 
-    #[rstest]
-    #[case(image1())]
-    #[case(image2())]
-    #[case(image3())]
-    #[tokio::test]
-    async fn test_query_image(
+    async fn query_album_images(
+        &self,
+        album_id: i64,
+        count: i64,
+        offset: i64,
+    ) -> Result<Vec<Image>, AlbumsError> {
+        // Listing an unknown album is an error rather than an empty page.
+        self.query_album(album_id).await?;
+        let query = format!(
+            "{} WHERE deleted_at IS NULL{} AND id IN (SELECT album_images.image_id FROM album_images \
+                WHERE album_images.album_id = ?1) \
+                ORDER BY (SELECT position FROM album_images \
+                WHERE album_images.album_id = ?1 AND album_images.image_id = images.id) \
+                LIMIT ?2 OFFSET ?3",
+            IMAGE_SELECT,
+            owner_filter("images")
+        );
+        match sqlx::query_as::<_, ImageRecord>(&query)
+            .bind(album_id)
+            .bind(count)
+            .bind(offset)
+            .fetch_all(&self.pool)
+            .await
+        {
+            Ok(records) => Ok(records.into_iter().map(Image::from).collect()),
+            Err(e) => {
+                error!(
+                    "Error querying {} images of album {} with offset {}; message: {}",
+                    count,
+                    album_id,
+                    offset,
+                    e.to_string()
+                );
+                Err(AlbumsError::InternalError)
+            }
+        }
+    }
+}
+#[async_trait]
+impl SearchImagesPort for ImagesSqliteDS {
+    async fn search_images(
+        &self,
+        query: &SearchQuery,
+    ) -> Result<Vec<Image>, query_image_port::QueryError> {
+        let mut builder = QueryBuilder::<Sqlite>::new(IMAGE_SELECT);
+        builder
+            .push(" WHERE deleted_at IS NULL")
+            .push(owner_filter("images"));
+        if let Some(created_after) = query.created_after {
+            builder
+                .push(" AND created_on >= ")
+                .push_bind(created_after.to_string());
+        }
+        if let Some(created_before) = query.created_before {
+            builder
+                .push(" AND created_on <= ")
+                .push_bind(created_before.to_string());
+        }
+        if let Some(captured_after) = query.captured_after {
+            builder
+                .push(" AND captured_on >= ")
+                .push_bind(captured_after.to_string());
+        }
+        if let Some(captured_before) = query.captured_before {
+            builder
+                .push(" AND captured_on <= ")
+                .push_bind(captured_before.to_string());
+        }
+        if let Some(min_width) = query.min_width {
+            builder.push(" AND width >= ").push_bind(min_width);
+        }
+        if let Some(max_width) = query.max_width {
+            builder.push(" AND width <= ").push_bind(max_width);
+        }
+        if let Some(min_height) = query.min_height {
+            builder.push(" AND height >= ").push_bind(min_height);
+        }
+        if let Some(max_height) = query.max_height {
+            builder.push(" AND height <= ").push_bind(max_height);
+        }
+        match query.shape {
+            Some(Shape::Landscape) => builder.push(" AND width > height"),
+            Some(Shape::Portrait) => builder.push(" AND width < height"),
+            Some(Shape::Square) => builder.push(" AND width = height"),
+            None => &mut builder,
+        };
+        if let Some(original_format) = &query.original_format {
+            builder
+                .push(" AND original_format = ")
+                .push_bind(original_format);
+        }
+        if !query.tags.is_empty() {
+            builder.push(
+                " AND id IN (SELECT image_tags.image_id FROM image_tags \
+                    JOIN tags ON tags.id = image_tags.tag_id WHERE tags.name IN (",
+            );
+            let mut names = builder.separated(", ");
+            for tag in &query.tags {
+                names.push_bind(tag);
+            }
+            builder.push(") GROUP BY image_tags.image_id");
+            if query.tag_match == TagMatch::All {
+                builder
+                    .push(" HAVING COUNT(DISTINCT tags.id) = ")
+                    .push_bind(query.tags.len() as i64);
+            }
+            builder.push(")");
+        }
+        builder
+            .push(" ORDER BY ")
+            .push(order_by(query.sort, query.direction))
+            .push(" LIMIT ")
+            .push_bind(query.count)
+            .push(" OFFSET ")
+            .push_bind(query.offset);
+        let records = match builder
+            .build_query_as::<ImageRecord>()
+            .fetch_all(&self.pool)
+            .await
+        {
+            Ok(records) => records,
+            Err(e) => {
+                error!("Error searching images {:?}; message: {}", query, e);
+                return Err(e.into());
+            }
+        };
+        Ok(records.into_iter().map(Image::from).collect())
+    }
+}
+#[async_trait]
+impl TagsPort for ImagesSqliteDS {
+    async fn add_tags(&self, image_id: i64, tags: &[String]) -> Result<Vec<String>, TagsError> {
+        match self.add_tag_records(image_id, tags).await {
+            Ok(tags) => Ok(tags),
+            Err(sqlx::Error::RowNotFound) => Err(TagsError::ImageNotFound),
+            Err(e) => {
+                error!(
+                    "Error tagging image {} with {:?}; message: {}",
+                    image_id,
+                    tags,
+                    e.to_string()
+                );
+                Err(TagsError::InternalError)
+            }
+        }
+    }
+
+    async fn remove_tag(&self, image_id: i64, tag: &str) -> Result<(), TagsError> {
+        match self.remove_tag_record(image_id, tag).await {
+            Ok(true) => Ok(()),
+            Ok(false) => Err(TagsError::TagNotFound),
+            Err(e) => {
+                error!(
+                    "Error removing tag {} from image {}; message: {}",
+                    tag,
+                    image_id,
+                    e.to_string()
+                );
+                Err(TagsError::InternalError)
+            }
+        }
+    }
+
+    async fn query_tags(&self) -> Result<Vec<Tag>, TagsError> {
+        let owner_id = current_user_id();
+        match sqlx::query!(
+            r#"
+                SELECT tags.name, COUNT(image_tags.image_id) as "count!: i64" FROM tags
+                    JOIN image_tags ON image_tags.tag_id = tags.id
+                    JOIN images ON images.id = image_tags.image_id
+                    WHERE images.deleted_at IS NULL AND (?1 IS NULL OR images.owner_id = ?1)
+                    GROUP BY tags.id
+                    ORDER BY tags.name
+            "#,
+            owner_id
+        )
+        .fetch_all(&self.pool)
+        .await
+        {
+            Ok(records) => Ok(records
+                .into_iter()
+                .map(|record| Tag::new(record.name, record.count))
+                .collect()),
+            Err(e) => {
+                error!("Error querying tags; message: {}", e.to_string());
+                Err(TagsError::InternalError)
+            }
+        }
+    }
+
+    async fn rename_tag(&self, from: &str, to: &str) -> Result<(), TagsError> {
+        match self.rename_tag_record(from, to).await {
+            Ok(true) => Ok(()),
+            Ok(false) => Err(TagsError::TagNotFound),
+            Err(e) => {
+                error!(
+                    "Error renaming tag {} to {}; message: {}",
+                    from,
+                    to,
+                    e.to_string()
+                );
+                Err(TagsError::InternalError)
+            }
+        }
+    }
+}
+#[async_trait]
+impl SimilarImagesPort for ImagesSqliteDS {
+    async fn query_perceptual_hashes(
+        &self,
+        after: i64,
+    ) -> Result<Vec<(i64, u64)>, query_image_port::QueryError> {
+        let records = match sqlx::query!(
+            r#"
+                SELECT id, perceptual_hash as "perceptual_hash!" FROM images
+                    WHERE id > ?1 AND perceptual_hash IS NOT NULL
+                    ORDER BY id
+            "#,
+            after
+        )
+        .fetch_all(&self.pool)
+        .await
+        {
+            Ok(records) => records,
+            Err(e) => {
+                error!(
+                    "Error querying perceptual hashes after {}; message: {}",
+                    after,
+                    e.to_string()
+                );
+                return Err(e.into());
+            }
+        };
+        Ok(records
+            .into_iter()
+            .map(|record| (record.id, record.perceptual_hash as u64))
+            .collect())
+    }
+
+    async fn query_images_by_ids(
+        &self,
+        indexes: Vec<i64>,
+    ) -> Result<Vec<Image>, query_image_port::QueryError> {
+        if indexes.is_empty() {
+            return Ok(vec![]);
+        }
+        let query = format!(
+            "{} WHERE deleted_at IS NULL{} AND id in ({})",
+            IMAGE_SELECT,
+            owner_filter("images"),
+            itertools::join(&indexes, ",")
+        );
+        let records = match sqlx::query_as::<_, ImageRecord>(&query)
+            .fetch_all(&self.pool)
+            .await
+        {
+            Ok(records) => records,
+            Err(e) => {
+                error!(
+                    "Error querying images {:?}; message: {}",
+                    indexes,
+                    e.to_string()
+                );
+                return Err(e.into());
+            }
+        };
+        Ok(records.into_iter().map(Image::from).collect())
+    }
+}
+#[async_trait]
+impl DeleteImagePort for ImagesSqliteDS {
+    async fn delete_image(&self, index: i64) -> Result<Vec<String>, DeleteImageError> {
+        match self.delete_image_records(index, None).await {
+            Ok(paths) => Ok(paths),
+            Err(e) => {
+                error!("Error deleting image {}; message: {}", index, e.to_string());
+                Err(e.into())
+            }
+        }
+    }
+
+    async fn delete_image_with(
+        &self,
+        index: i64,
+        remove: RemoveFiles,
+    ) -> Result<Vec<String>, DeleteImageError> {
+        match self.delete_image_records(index, Some(remove)).await {
+            Ok(paths) => Ok(paths),
+            Err(e) => {
+                error!("Error deleting image {}; message: {}", index, e.to_string());
+                Err(e.into())
+            }
+        }
+    }
+}
+#[async_trait]
+impl BatchDeleteImagePort for ImagesSqliteDS {
+    async fn batch_delete_image(&self, indexes: Vec<i64>) -> Result<Vec<String>, BatchDeleteError> {
+        match self.batch_delete_image_records(&indexes).await {
+            Ok(paths) => Ok(paths),
+            Err(e) => {
+                error!(
+                    "Error deleting images {:?}; message: {}",
+                    indexes,
+                    e.to_string()
+                );
+                Err(e.into())
+            }
+        }
+    }
+}
+#[async_trait]
+impl TrashPort for ImagesSqliteDS {
+    async fn trash_images(
+        &self,
+        indexes: &[i64],
+        deleted_at: DateTime<Utc>,
+    ) -> Result<u64, TrashError> {
+        if indexes.is_empty() {
+            return Ok(0);
+        }
+        let query = format!(
+            "UPDATE images SET deleted_at = ? WHERE deleted_at IS NULL{} AND id in ({})",
+            owner_filter("images"),
+            itertools::join(indexes, ",")
+        );
+        match sqlx::query(&query)
+            .bind(deleted_at.to_string())
+            .execute(&self.pool)
+            .await
+        {
+            Ok(result) => Ok(result.rows_affected()),
+            Err(e) => {
+                error!(
+                    "Error trashing images {:?}; message: {}",
+                    indexes,
+                    e.to_string()
+                );
+                Err(TrashError::InternalError)
+            }
+        }
+    }
+
+    async fn restore_image(&self, index: i64) -> Result<(), TrashError> {
+        let owner_id = current_user_id();
+        match sqlx::query!(
+            r#"
+                UPDATE images SET deleted_at = NULL
+                    WHERE id = ?1 AND deleted_at IS NOT NULL AND (?2 IS NULL OR owner_id = ?2)
+            "#,
+            index,
+            owner_id
+        )
+        .execute(&self.pool)
+        .await
+        {
+            Ok(result) if result.rows_affected() == 0 => Err(TrashError::ImageNotFound),
+            Ok(_) => Ok(()),
+            Err(e) => {
+                error!(
+                    "Error restoring image {}; message: {}",
+                    index,
+                    e.to_string()
+                );
+                Err(TrashError::InternalError)
+            }
+        }
+    }
+
+    async fn query_trash(&self, count: i64, offset: i64) -> Result<Vec<Image>, TrashError> {
+        match sqlx::query_as::<_, ImageRecord>(&format!(
+            "{} WHERE deleted_at IS NOT NULL{} ORDER BY deleted_at DESC, id DESC LIMIT ? OFFSET ?",
+            IMAGE_SELECT,
+            owner_filter("images")
+        ))
+        .bind(count)
+        .bind(offset)
+        .fetch_all(&self.pool)
+        .await
+        {
+            Ok(records) => Ok(records.into_iter().map(Image::from).collect()),
+            Err(e) => {
+                error!("Error querying trash; message: {}", e.to_string());
+                Err(TrashError::InternalError)
+            }
+        }
+    }
+
+    async fn purge_trash(&self, before: DateTime<Utc>) -> Result<Vec<String>, TrashError> {
+        match self.purge_trash_records(before, None).await {
+            Ok(paths) => Ok(paths),
+            Err(e) => {
+                error!(
+                    "Error purging images trashed before {}; message: {}",
+                    before,
+                    e.to_string()
+                );
+                Err(TrashError::InternalError)
+            }
+        }
+    }
+
+    async fn purge_trash_with(
+        &self,
+        before: DateTime<Utc>,
+        remove: RemoveFiles,
+    ) -> Result<Vec<String>, TrashError> {
+        match self.purge_trash_records(before, Some(remove)).await {
+            Ok(paths) => Ok(paths),
+            Err(e) => {
+                error!(
+                    "Error purging images trashed before {}; message: {}",
+                    before,
+                    e.to_string()
+                );
+                Err(TrashError::InternalError)
+            }
+        }
+    }
+}
+#[async_trait]
+impl ConsistencyPort for ImagesSqliteDS {
+    async fn query_image_files(&self) -> Result<Vec<ImageFile>, ConsistencyError> {
+        let records = match sqlx::query!("SELECT id, path, status, stored_size FROM images")
+            .fetch_all(&self.pool)
+            .await
+        {
+            Ok(records) => records,
+            Err(e) => {
+                error!("Error querying image files; message: {}", e.to_string());
+                return Err(ConsistencyError::InternalError);
+            }
+        };
+        Ok(records
+            .into_iter()
+            .map(|record| {
+                let status = record.status.parse::<ImageStatus>().unwrap_or_default();
+                let file = ImageFile::new(record.id, record.path, status);
+                match record.stored_size {
+                    Some(size) => file.with_size(size as u64),
+                    None => file,
+                }
+            })
+            .collect())
+    }
+
+    async fn query_referenced_paths(&self) -> Result<HashSet<String>, ConsistencyError> {
+        match sqlx::query!(
+            r#"
+            SELECT path as "path!" FROM images
+            UNION SELECT original_path as "path!" FROM images WHERE original_path IS NOT NULL
+            UNION SELECT path as "path!" FROM renditions
+            "#
+        )
+        .fetch_all(&self.pool)
+        .await
+        {
+            Ok(records) => Ok(records.into_iter().map(|record| record.path).collect()),
+            Err(e) => {
+                error!(
+                    "Error querying referenced paths; message: {}",
+                    e.to_string()
+                );
+                Err(ConsistencyError::InternalError)
+            }
+        }
+    }
+
+    async fn update_image_status(
+        &self,
+        indexes: &[i64],
+        status: ImageStatus,
+    ) -> Result<(), ConsistencyError> {
+        if indexes.is_empty() {
+            return Ok(());
+        }
+        let query = format!(
+            "UPDATE images SET status = ? WHERE id in ({})",
+            itertools::join(indexes, ",")
+        );
+        match sqlx::query(&query)
+            .bind(status.as_str())
+            .execute(&self.pool)
+            .await
+        {
+            Ok(_) => Ok(()),
+            Err(e) => {
+                error!(
+                    "Error marking images {:?} as {}; message: {}",
+                    indexes,
+                    status,
+                    e.to_string()
+                );
+                Err(ConsistencyError::InternalError)
+            }
+        }
+    }
+}
+#[async_trait]
+impl LayoutPort for ImagesSqliteDS {
+    async fn query_image_paths(
+        &self,
+        after: i64,
+        count: i64,
+    ) -> Result<Vec<ImagePaths>, LayoutError> {
+        match self.query_image_path_records(after, count).await {
+            Ok(images) => Ok(images),
+            Err(e) => {
+                error!(
+                    "Error querying image paths after {}; message: {}",
+                    after,
+                    e.to_string()
+                );
+                Err(LayoutError::InternalError)
+            }
+        }
+    }
+
+    async fn relocate_paths(
+        &self,
+        moves: &[(String, String)],
+        persist: Persist,
+    ) -> Result<(), LayoutError> {
+        match self.relocate_path_records(moves, persist).await {
+            Ok(()) => Ok(()),
+            Err(e) => {
+                error!("Error relocating paths {:?}; message: {}", moves, e);
+                Err(LayoutError::InternalError)
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl ScrubPort for ImagesSqliteDS {
+    async fn query_scrub_batch(&self, count: i64) -> Result<Vec<ImageFile>, ScrubError> {
+        let records = match sqlx::query!(
+            r#"
+            SELECT id as "id!", path as "path!", status as "status!", checksum FROM images
+                ORDER BY checked_on IS NOT NULL, checked_on, id LIMIT ?1
+            "#,
+            count
+        )
+        .fetch_all(&self.pool)
+        .await
+        {
+            Ok(records) => records,
+            Err(e) => {
+                error!("Error querying images to scrub; message: {}", e.to_string());
+                return Err(ScrubError::InternalError);
+            }
+        };
+        Ok(records
+            .into_iter()
+            .map(|record| {
+                let status = record.status.parse::<ImageStatus>().unwrap_or_default();
+                let file = ImageFile::new(record.id, record.path, status);
+                match record.checksum {
+                    Some(checksum) => file.with_checksum(checksum),
+                    None => file,
+                }
+            })
+            .collect())
+    }
+
+    async fn record_scrub(
+        &self,
+        image_id: i64,
+        status: ImageStatus,
+        checksum: Option<String>,
+        checked_on: DateTime<Utc>,
+    ) -> Result<(), ScrubError> {
+        let status_str = status.as_str();
+        let checked_on = checked_on.to_string();
+        match sqlx::query!(
+            r#"
+            UPDATE images SET status = ?2, checked_on = ?3, checksum = COALESCE(checksum, ?4)
+                WHERE id = ?1
+            "#,
+            image_id,
+            status_str,
+            checked_on,
+            checksum
+        )
+        .execute(&self.pool)
+        .await
+        {
+            Ok(_) => Ok(()),
+            Err(e) => {
+                error!(
+                    "Error recording image {} as {}; message: {}",
+                    image_id,
+                    status,
+                    e.to_string()
+                );
+                Err(ScrubError::InternalError)
+            }
+        }
+    }
+
+    async fn count_images_by_status(&self) -> Result<Vec<(ImageStatus, i64)>, ScrubError> {
+        match sqlx::query!(
+            r#"
+            SELECT status, COUNT(*) as "count!: i64" FROM images
+                WHERE deleted_at IS NULL GROUP BY status
+            "#
+        )
+        .fetch_all(&self.pool)
+        .await
+        {
+            Ok(records) => Ok(records
+                .into_iter()
+                .filter_map(|record| {
+                    let status = record.status.parse::<ImageStatus>().ok()?;
+                    Some((status, record.count))
+                })
+                .collect()),
+            Err(e) => {
+                error!(
+                    "Error counting images by status; message: {}",
+                    e.to_string()
+                );
+                Err(ScrubError::InternalError)
+            }
+        }
+    }
+
+    async fn query_images_by_status(&self, status: ImageStatus) -> Result<Vec<Image>, ScrubError> {
+        match sqlx::query_as::<_, ImageRecord>(&format!(
+            "{} WHERE deleted_at IS NULL AND status = ? ORDER BY id",
+            IMAGE_SELECT
+        ))
+        .bind(status.as_str())
+        .fetch_all(&self.pool)
+        .await
+        {
+            Ok(records) => Ok(records.into_iter().map(Image::from).collect()),
+            Err(e) => {
+                error!(
+                    "Error querying {} images; message: {}",
+                    status,
+                    e.to_string()
+                );
+                Err(ScrubError::InternalError)
+            }
+        }
+    }
+}
+#[async_trait]
+impl RenditionPort for ImagesSqliteDS {
+    async fn query_rendition(
+        &self,
+        image_id: i64,
+        size: RenditionSize,
+    ) -> Result<Rendition, query_image_port::QueryError> {
+        let size_name = size.as_str();
+        let owner_id = current_user_id();
+        let record = match sqlx::query!(
+            r#"
+                SELECT image_id, path FROM renditions
+                    WHERE image_id = ?1 AND size = ?2
+                    AND image_id IN (SELECT id FROM images WHERE ?3 IS NULL OR owner_id = ?3)
+            "#,
+            image_id,
+            size_name,
+            owner_id
+        )
+        .fetch_one(&self.pool)
+        .await
+        {
+            Ok(record) => record,
+            Err(e) => {
+                if !matches!(e, sqlx::Error::RowNotFound) {
+                    error!(
+                        "Error querying {} rendition of image {}; message: {}",
+                        size,
+                        image_id,
+                        e.to_string()
+                    );
+                }
+                return Err(e.into());
+            }
+        };
+        Ok(Rendition::new(record.image_id, size, record.path))
+    }
+
+    async fn insert_rendition(&self, rendition: &Rendition) -> Result<(), InsertImageError> {
+        let image_id = rendition.image_id();
+        let size = rendition.size().as_str();
+        let path = rendition.path();
+        let updated_on = Utc::now().to_string();
+        match sqlx::query!(
+            r#"
+                INSERT INTO renditions (image_id, size, path, updated_on) VALUES (?1, ?2, ?3, ?4)
+                    ON CONFLICT (image_id, size) DO UPDATE SET path = ?3, updated_on = ?4
+            "#,
+            image_id,
+            size,
+            path,
+            updated_on
+        )
+        .execute(&self.pool)
+        .await
+        {
+            Ok(_) => Ok(()),
+            Err(e) => {
+                error!(
+                    "Error inserting rendition {:?}; message: {}",
+                    rendition,
+                    e.to_string()
+                );
+                Err(e.into())
+            }
+        }
+    }
+}
+#[async_trait]
+impl InsertImagePort for ImagesSqliteDS {
+    async fn insert_image(&self, record: &Image) -> Result<i64, InsertImageError> {
+        match self.insert_image_record(record, None).await {
+            Ok(id) => Ok(id),
+            Err(e) => {
+                error!(
+                    "Error inserting image {:?}; message: {}",
+                    record,
+                    e.to_string()
+                );
+                Err(e.into())
+            }
+        }
+    }
+
+    async fn insert_image_with(
+        &self,
+        record: &Image,
+        persist: Persist,
+    ) -> Result<i64, InsertImageError> {
+        match self.insert_image_record(record, Some(persist)).await {
+            Ok(id) => Ok(id),
+            Err(e) => {
+                error!(
+                    "Error inserting image {:?}; message: {}",
+                    record,
+                    e.to_string()
+                );
+                Err(e.into())
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl ApiKeysPort for ImagesSqliteDS {
+    async fn insert_api_key(
+        &self,
+        name: &str,
+        key_hash: &str,
+        scopes: &[Scope],
+        created_on: DateTime<Utc>,
+    ) -> Result<ApiKey, ApiKeysError> {
+        let stored_scopes = format_scopes(scopes);
+        let stored_created_on = created_on.to_string();
+        match sqlx::query!(
+            "INSERT INTO api_keys (name, key_hash, scopes, created_on) VALUES (?1, ?2, ?3, ?4)",
+            name,
+            key_hash,
+            stored_scopes,
+            stored_created_on
+        )
+        .execute(&self.pool)
+        .await
+        {
+            Ok(result) => Ok(ApiKey::new(
+                result.last_insert_rowid(),
+                name.to_string(),
+                scopes.to_vec(),
+                created_on,
+            )),
+            Err(e) => {
+                error!("Error inserting API key {}; message: {}", name, e);
+                Err(e.into())
+            }
+        }
+    }
+
+    async fn query_api_key_by_hash(&self, key_hash: &str) -> Result<ApiKey, ApiKeysError> {
+        match sqlx::query_as!(
+            ApiKeyRecord,
+            r#"
+                SELECT id as "id!", name, scopes, created_on, revoked_on
+                    FROM api_keys WHERE key_hash = ?1
+            "#,
+            key_hash
+        )
+        .fetch_optional(&self.pool)
+        .await
+        {
+            Ok(Some(record)) => Ok(record.into()),
+            Ok(None) => Err(ApiKeysError::KeyNotFound),
+            Err(e) => {
+                error!("Error querying API key; message: {}", e.to_string());
+                Err(ApiKeysError::InternalError)
+            }
+        }
+    }
+
+    async fn query_api_keys(&self) -> Result<Vec<ApiKey>, ApiKeysError> {
+        match sqlx::query_as!(
+            ApiKeyRecord,
+            r#"
+                SELECT id as "id!", name, scopes, created_on, revoked_on
+                    FROM api_keys ORDER BY id
+            "#
+        )
+        .fetch_all(&self.pool)
+        .await
+        {
+            Ok(records) => Ok(records.into_iter().map(ApiKey::from).collect()),
+            Err(e) => {
+                error!("Error querying API keys; message: {}", e.to_string());
+                Err(ApiKeysError::InternalError)
+            }
+        }
+    }
+
+    async fn revoke_api_key(&self, id: i64, revoked_on: DateTime<Utc>) -> Result<(), ApiKeysError> {
+        let revoked_on = revoked_on.to_string();
+        match sqlx::query!(
+            "UPDATE api_keys SET revoked_on = ?1 WHERE id = ?2 AND revoked_on IS NULL",
+            revoked_on,
+            id
+        )
+        .execute(&self.pool)
+        .await
+        {
+            Ok(result) if result.rows_affected() == 0 => Err(ApiKeysError::KeyNotFound),
+            Ok(_) => Ok(()),
+            Err(e) => {
+                error!("Error revoking API key {}; message: {}", id, e.to_string());
+                Err(ApiKeysError::InternalError)
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl UsersPort for ImagesSqliteDS {
+    async fn insert_user(
+        &self,
+        name: &str,
+        password_hash: &str,
+        created_on: DateTime<Utc>,
+    ) -> Result<User, UsersError> {
+        let stored_created_on = created_on.to_string();
+        match sqlx::query!(
+            "INSERT INTO users (name, password_hash, created_on) VALUES (?1, ?2, ?3)",
+            name,
+            password_hash,
+            stored_created_on
+        )
+        .execute(&self.pool)
+        .await
+        {
+            Ok(result) => Ok(User::new(
+                result.last_insert_rowid(),
+                name.to_string(),
+                created_on,
+            )),
+            Err(e) => {
+                error!("Error inserting user {}; message: {}", name, e);
+                Err(e.into())
+            }
+        }
+    }
+
+    async fn query_user_by_name(&self, name: &str) -> Result<(User, String), UsersError> {
+        match sqlx::query!(
+            r#"
+                SELECT id as "id!", name, created_on, password_hash FROM users WHERE name = ?1
+            "#,
+            name
+        )
+        .fetch_optional(&self.pool)
+        .await
+        {
+            Ok(Some(record)) => Ok((
+                UserRecord {
+                    id: record.id,
+                    name: record.name,
+                    created_on: record.created_on,
+                }
+                .into(),
+                record.password_hash,
+            )),
+            Ok(None) => Err(UsersError::UserNotFound),
+            Err(e) => {
+                error!("Error querying user {}; message: {}", name, e.to_string());
+                Err(UsersError::InternalError)
+            }
+        }
+    }
+
+    async fn insert_session(
+        &self,
+        user_id: i64,
+        token_hash: &str,
+        expires_on: DateTime<Utc>,
+    ) -> Result<(), UsersError> {
+        let expires_on = expires_on.to_string();
+        match sqlx::query!(
+            "INSERT INTO sessions (user_id, token_hash, expires_on) VALUES (?1, ?2, ?3)",
+            user_id,
+            token_hash,
+            expires_on
+        )
+        .execute(&self.pool)
+        .await
+        {
+            Ok(_) => Ok(()),
+            Err(e) => {
+                error!(
+                    "Error inserting session of user {}; message: {}",
+                    user_id, e
+                );
+                Err(e.into())
+            }
+        }
+    }
+
+    async fn query_session_user(
+        &self,
+        token_hash: &str,
+        now: DateTime<Utc>,
+    ) -> Result<User, UsersError> {
+        let now = now.to_string();
+        match sqlx::query_as!(
+            UserRecord,
+            r#"
+                SELECT users.id as "id!", users.name, users.created_on FROM sessions
+                    JOIN users ON users.id = sessions.user_id
+                    WHERE sessions.token_hash = ?1 AND sessions.expires_on > ?2
+            "#,
+            token_hash,
+            now
+        )
+        .fetch_optional(&self.pool)
+        .await
+        {
+            Ok(Some(record)) => Ok(record.into()),
+            Ok(None) => Err(UsersError::UserNotFound),
+            Err(e) => {
+                error!("Error querying session; message: {}", e.to_string());
+                Err(UsersError::InternalError)
+            }
+        }
+    }
+
+    async fn delete_session(&self, token_hash: &str) -> Result<(), UsersError> {
+        match sqlx::query!("DELETE FROM sessions WHERE token_hash = ?1", token_hash)
+            .execute(&self.pool)
+            .await
+        {
+            Ok(result) if result.rows_affected() == 0 => Err(UsersError::UserNotFound),
+            Ok(_) => Ok(()),
+            Err(e) => {
+                error!("Error deleting session; message: {}", e.to_string());
+                Err(UsersError::InternalError)
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl SharesPort for ImagesSqliteDS {
+    async fn insert_share(
+        &self,
+        image_id: i64,
+        created_on: DateTime<Utc>,
+        expires_on: DateTime<Utc>,
+    ) -> Result<Share, SharesError> {
+        match self
+            .insert_share_record(image_id, created_on, expires_on)
+            .await
+        {
+            Ok(Some(id)) => Ok(Share::new(id, image_id, created_on, expires_on)),
+            Ok(None) => Err(SharesError::ImageNotFound),
+            Err(e) => {
+                error!("Error sharing image {}; message: {}", image_id, e);
+                Err(SharesError::InternalError)
+            }
+        }
+    }
+
+    async fn query_share(&self, id: i64) -> Result<Share, SharesError> {
+        match sqlx::query_as!(
+            ShareRecord,
+            r#"
+                SELECT id as "id!", image_id, created_on, expires_on, revoked_on FROM shares
+                    WHERE id = ?1
+            "#,
+            id
+        )
+        .fetch_optional(&self.pool)
+        .await
+        {
+            Ok(Some(record)) => Ok(record.into()),
+            Ok(None) => Err(SharesError::ShareNotFound),
+            Err(e) => {
+                error!("Error querying share {}; message: {}", id, e.to_string());
+                Err(SharesError::InternalError)
+            }
+        }
+    }
+
+    async fn revoke_share(&self, id: i64, revoked_on: DateTime<Utc>) -> Result<(), SharesError> {
+        let revoked_on = revoked_on.to_string();
+        let owner_id = current_user_id();
+        match sqlx::query!(
+            "UPDATE shares SET revoked_on = ?1 WHERE id = ?2 AND revoked_on IS NULL \
+                AND image_id IN (SELECT id FROM images WHERE ?3 IS NULL OR owner_id = ?3)",
+            revoked_on,
+            id,
+            owner_id
+        )
+        .execute(&self.pool)
+        .await
+        {
+            Ok(result) if result.rows_affected() == 0 => Err(SharesError::ShareNotFound),
+            Ok(_) => Ok(()),
+            Err(e) => {
+                error!("Error revoking share {}; message: {}", id, e.to_string());
+                Err(SharesError::InternalError)
+            }
+        }
+    }
+}
+
+impl ImagesSqliteDS {
+    #[allow(dead_code)]
+    pub fn new(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    /// Returns None when the image is not live or not the caller's.
+    async fn insert_share_record(
+        &self,
+        image_id: i64,
+        created_on: DateTime<Utc>,
+        expires_on: DateTime<Utc>,
+    ) -> Result<Option<i64>, sqlx::Error> {
+        let owner_id = current_user_id();
+        let created_on = created_on.to_string();
+        let expires_on = expires_on.to_string();
+        let mut tx = self.pool.begin().await?;
+        let image = sqlx::query!(
+            "SELECT id FROM images WHERE id = ?1 AND deleted_at IS NULL \
+                AND (?2 IS NULL OR owner_id = ?2)",
+            image_id,
+            owner_id
+        )
+        .fetch_optional(&mut tx)
+        .await?;
+        if image.is_none() {
+            return Ok(None);
+        }
+        let id = sqlx::query!(
+            "INSERT INTO shares (image_id, created_on, expires_on) VALUES (?1, ?2, ?3)",
+            image_id,
+            created_on,
+            expires_on
+        )
+        .execute(&mut tx)
+        .await?
+        .last_insert_rowid();
+        tx.commit().await?;
+        Ok(Some(id))
+    }
+
+    /// Dropping the transaction on any error, `persist` included, rolls the
+    /// insert back.
+    async fn insert_image_record(
+        &self,
+        record: &Image,
+        persist: Option<Persist>,
+    ) -> Result<i64, sqlx::Error> {
+        let id = record.id();
+        let path = record.path();
+        let updated_on = record.updated_on().to_string();
+        let hash = record.hash();
+        let perceptual_hash = record.perceptual_hash().map(|hash| hash as i64);
+        let created_on = record.created_on().to_string();
+        let metadata = record.metadata();
+        let width = metadata.map(|metadata| metadata.width());
+        let height = metadata.map(|metadata| metadata.height());
+        let color_type = metadata.map(|metadata| metadata.color_type());
+        let bit_depth = metadata.map(|metadata| metadata.bit_depth());
+        let original_format = metadata.map(|metadata| metadata.original_format());
+        let original_size = metadata.map(|metadata| metadata.original_size() as i64);
+        let stored_size = metadata.map(|metadata| metadata.stored_size() as i64);
+        let filename = metadata.and_then(|metadata| metadata.filename());
+        let original_path = record.original_path();
+        let checksum = record.checksum();
+        let owner_id = current_user_id();
+        // An id of 0 lets sqlite assign the next one.
+        let id = (id != 0).then_some(id);
+        let mut tx = self.pool.begin().await?;
+        let id = match sqlx::query!(
+            r#"
+                INSERT INTO images (id, path, updated_on, hash, perceptual_hash, created_on, width, height,
+                    color_type, bit_depth, original_format, original_size, stored_size, filename,
+                    original_path, checksum, owner_id)
+                    VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17)
+            "#,
+            id,
+            path,
+            updated_on,
+            hash,
+            perceptual_hash,
+            created_on,
+            width,
+            height,
+            color_type,
+            bit_depth,
+            original_format,
+            original_size,
+            stored_size,
+            filename,
+            original_path,
+            checksum,
+            owner_id
+        )
+        .execute(&mut tx)
+        .await
+        {
+            Ok(result) => result.last_insert_rowid(),
+            Err(e) => {
+                // The per-owner hash index fails after the row is written, so
+                // the write lock is held until the rollback; do not leave that
+                // to the pool.
+                tx.rollback().await?;
+                return Err(e);
+            }
+        };
+        if let Some(exif) = record.exif() {
+            let captured_on = exif
+                .captured_on()
+                .map(|captured_on| captured_on.to_string());
+            let make = exif.make();
+            let model = exif.model();
+            let lens = exif.lens();
+            let exposure_time = exif.exposure_time();
+            let f_number = exif.f_number();
+            let iso = exif.iso();
+            let focal_length = exif.focal_length();
+            let latitude = exif.latitude();
+            let longitude = exif.longitude();
+            let altitude = exif.altitude();
+            let orientation = exif.orientation();
+            sqlx::query!(
+                r#"
+                INSERT INTO image_exif (image_id, captured_on, make, model, lens, exposure_time,
+                    f_number, iso, focal_length, latitude, longitude, altitude, orientation)
+                    VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)
+                "#,
+                id,
+                captured_on,
+                make,
+                model,
+                lens,
+                exposure_time,
+                f_number,
+                iso,
+                focal_length,
+                latitude,
+                longitude,
+                altitude,
+                orientation
+            )
+            .execute(&mut tx)
+            .await?;
+        }
+        if let Some(persist) = persist {
+            persist.await.map_err(sqlx::Error::Io)?;
+        }
+        tx.commit().await?;
+        Ok(id)
+    }
+
+    async fn add_tag_records(
+        &self,
+        image_id: i64,
+        tags: &[String],
+    ) -> Result<Vec<String>, sqlx::Error> {
+        let owner_id = current_user_id();
+        let mut tx = self.pool.begin().await?;
+        sqlx::query!(
+            "SELECT id FROM images WHERE id = ?1 AND deleted_at IS NULL AND (?2 IS NULL OR owner_id = ?2)",
+            image_id,
+            owner_id
+        )
+        .fetch_one(&mut tx)
+        .await?;
+        for tag in tags {
+            sqlx::query!(
+                "INSERT INTO tags (name) VALUES (?1) ON CONFLICT (name) DO NOTHING",
+                tag
+            )
+            .execute(&mut tx)
+            .await?;
+            sqlx::query!(
+                r#"
+                INSERT INTO image_tags (image_id, tag_id)
+                    SELECT ?1, id FROM tags WHERE name = ?2
+                    ON CONFLICT (image_id, tag_id) DO NOTHING
+                "#,
+                image_id,
+                tag
+            )
+            .execute(&mut tx)
+            .await?;
+        }
+        let records = sqlx::query!(
+            r#"
+                SELECT tags.name FROM tags
+                    JOIN image_tags ON image_tags.tag_id = tags.id
+                    WHERE image_tags.image_id = ?1
+                    ORDER BY tags.name
+            "#,
+            image_id
+        )
+        .fetch_all(&mut tx)
+        .await?;
+        tx.commit().await?;
+        Ok(records.into_iter().map(|record| record.name).collect())
+    }
+
+    /// Returns false when the image did not have the tag. Tags left without
+    /// images are dropped.
+    async fn remove_tag_record(&self, image_id: i64, tag: &str) -> Result<bool, sqlx::Error> {
+        let owner_id = current_user_id();
+        let mut tx = self.pool.begin().await?;
+        let removed = sqlx::query!(
+            r#"
+                DELETE FROM image_tags
+                    WHERE image_id = ?1 AND tag_id = (SELECT id FROM tags WHERE name = ?2)
+                    AND image_id IN (SELECT id FROM images WHERE ?3 IS NULL OR owner_id = ?3)
+            "#,
+            image_id,
+            tag,
+            owner_id
+        )
+        .execute(&mut tx)
+        .await?
+        .rows_affected();
+        sqlx::query!(
+            r#"
+                DELETE FROM tags
+                    WHERE name = ?1
+                    AND NOT EXISTS (SELECT 1 FROM image_tags WHERE image_tags.tag_id = tags.id)
+            "#,
+            tag
+        )
+        .execute(&mut tx)
+        .await?;
+        tx.commit().await?;
+        Ok(removed > 0)
+    }
+
+    /// Returns false when there is no tag named `from`.
+    async fn rename_tag_record(&self, from: &str, to: &str) -> Result<bool, sqlx::Error> {
+        if let Some(owner_id) = current_user_id() {
+            return self.rename_owned_tag_records(owner_id, from, to).await;
+        }
+        let mut tx = self.pool.begin().await?;
+        let from_id = match sqlx::query!("SELECT id FROM tags WHERE name = ?1", from)
+            .fetch_optional(&mut tx)
+            .await?
+        {
+            Some(record) => record.id,
+            None => return Ok(false),
+        };
+        let to_id = sqlx::query!("SELECT id FROM tags WHERE name = ?1", to)
+            .fetch_optional(&mut tx)
+            .await?;
+        match to_id {
+            None => {
+                sqlx::query!("UPDATE tags SET name = ?1 WHERE id = ?2", to, from_id)
+                    .execute(&mut tx)
+                    .await?;
+            }
+            Some(record) => {
+                sqlx::query!(
+                    r#"
+                INSERT INTO image_tags (image_id, tag_id)
+                    SELECT image_id, ?1 FROM image_tags WHERE tag_id = ?2
+                    ON CONFLICT (image_id, tag_id) DO NOTHING
+                    "#,
+                    record.id,
+                    from_id
+                )
+                .execute(&mut tx)
+                .await?;
+                sqlx::query!("DELETE FROM tags WHERE id = ?1", from_id)
+                    .execute(&mut tx)
+                    .await?;
+            }
+        }
+        tx.commit().await?;
+        Ok(true)
+    }
+
+    /// Tag names are shared between users, so on behalf of one only their
+    /// images move from `from` to `to`. Returns false when none of them has
+    /// `from`.
+    async fn rename_owned_tag_records(
+        &self,
+        owner_id: i64,
+        from: &str,
+        to: &str,
+    ) -> Result<bool, sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+        sqlx::query!(
+            "INSERT INTO tags (name) VALUES (?1) ON CONFLICT (name) DO NOTHING",
+            to
+        )
+        .execute(&mut tx)
+        .await?;
+        sqlx::query!(
+            r#"
+                INSERT INTO image_tags (image_id, tag_id)
+                    SELECT image_tags.image_id, (SELECT id FROM tags WHERE name = ?2) FROM image_tags
+                    JOIN tags ON tags.id = image_tags.tag_id
+                    JOIN images ON images.id = image_tags.image_id
+                    WHERE tags.name = ?1 AND images.owner_id = ?3
+                    ON CONFLICT (image_id, tag_id) DO NOTHING
+            "#,
+            from,
+            to,
+            owner_id
+        )
+        .execute(&mut tx)
+        .await?;
+        let moved = sqlx::query!(
+            r#"
+                DELETE FROM image_tags
+                    WHERE tag_id = (SELECT id FROM tags WHERE name = ?1)
+                    AND image_id IN (SELECT id FROM images WHERE owner_id = ?2)
+            "#,
+            from,
+            owner_id
+        )
+        .execute(&mut tx)
+        .await?
+        .rows_affected();
+        if moved == 0 {
+            return Ok(false);
+        }
+        sqlx::query!(
+            r#"
+                DELETE FROM tags
+                    WHERE name = ?1
+                    AND NOT EXISTS (SELECT 1 FROM image_tags WHERE image_tags.tag_id = tags.id)
+            "#,
+            from
+        )
+        .execute(&mut tx)
+        .await?;
+        tx.commit().await?;
+        Ok(true)
+    }
+
+    /// Returns None when the parent album does not exist.
+    async fn create_album_record(
+        &self,
+        name: &str,
+        parent_id: Option<i64>,
+    ) -> Result<Option<i64>, sqlx::Error> {
+        let owner_id = current_user_id();
+        let mut tx = self.pool.begin().await?;
+        if let Some(parent_id) = parent_id {
+            let parent = sqlx::query!(
+                "SELECT id FROM albums WHERE id = ?1 AND (?2 IS NULL OR owner_id = ?2)",
+                parent_id,
+                owner_id
+            )
+            .fetch_optional(&mut tx)
+            .await?;
+            if parent.is_none() {
+                return Ok(None);
+            }
+        }
+        let created_on = Utc::now().to_string();
+        let id = sqlx::query!(
+            "INSERT INTO albums (name, parent_id, created_on, owner_id) VALUES (?1, ?2, ?3, ?4)",
+            name,
+            parent_id,
+            created_on,
+            owner_id
+        )
+        .execute(&mut tx)
+        .await?
+        .last_insert_rowid();
+        tx.commit().await?;
+        Ok(Some(id))
+    }
+
+    async fn update_album_record(
+        &self,
+        id: i64,
+        update: &AlbumUpdate,
+    ) -> Result<Result<(), AlbumsError>, sqlx::Error> {
+        let owner_id = current_user_id();
+        let mut tx = self.pool.begin().await?;
+        let album = sqlx::query!(
+            "SELECT id FROM albums WHERE id = ?1 AND (?2 IS NULL OR owner_id = ?2)",
+            id,
+            owner_id
+        )
+        .fetch_optional(&mut tx)
+        .await?;
+        if album.is_none() {
+            return Ok(Err(AlbumsError::AlbumNotFound));
+        }
+        if let Some(name) = &update.name {
+            sqlx::query!("UPDATE albums SET name = ?1 WHERE id = ?2", name, id)
+                .execute(&mut tx)
+                .await?;
+        }
+        if let Some(parent_id) = update.parent_id {
+            if let Some(parent_id) = parent_id {
+                let parent = sqlx::query!(
+                    "SELECT id FROM albums WHERE id = ?1 AND (?2 IS NULL OR owner_id = ?2)",
+                    parent_id,
+                    owner_id
+                )
+                .fetch_optional(&mut tx)
+                .await?;
+                if parent.is_none() {
+                    return Ok(Err(AlbumsError::AlbumNotFound));
+                }
+                // Moving an album below one of its own descendants would
+                // detach the whole branch into a cycle.
+                let cycle = sqlx::query!(
+                    r#"
+                WITH RECURSIVE ancestors(id) AS (
+                    SELECT ?1
+                    UNION
+                    SELECT albums.parent_id FROM albums JOIN ancestors ON albums.id = ancestors.id
+                        WHERE albums.parent_id IS NOT NULL
+                )
+                SELECT COUNT(*) as "count!: i64" FROM ancestors WHERE id = ?2
+                    "#,
+                    parent_id,
+                    id
+                )
+                .fetch_one(&mut tx)
+                .await?;
+                if cycle.count > 0 {
+                    return Ok(Err(AlbumsError::InvalidParent));
+                }
+            }
+            sqlx::query!(
+                "UPDATE albums SET parent_id = ?1 WHERE id = ?2",
+                parent_id,
+                id
+            )
+            .execute(&mut tx)
+            .await?;
+        }
+        if let Some(cover_image_id) = update.cover_image_id {
+            if let Some(cover_image_id) = cover_image_id {
+                let member = sqlx::query!(
+                    "SELECT image_id FROM album_images WHERE album_id = ?1 AND image_id = ?2",
+                    id,
+                    cover_image_id
+                )
+                .fetch_optional(&mut tx)
+                .await?;
+                if member.is_none() {
+                    return Ok(Err(AlbumsError::ImageNotFound));
+                }
+            }
+            sqlx::query!(
+                "UPDATE albums SET cover_image_id = ?1 WHERE id = ?2",
+                cover_image_id,
+                id
+            )
+            .execute(&mut tx)
+            .await?;
+        }
+        tx.commit().await?;
+        Ok(Ok(()))
+    }
+
+    /// Returns false when the album does not exist. Nested albums go with it
+    /// through the `parent_id` foreign key.
+    async fn delete_album_records(&self, id: i64, cascade: bool) -> Result<bool, sqlx::Error> {
+        let owner_id = current_user_id();
+        let mut tx = self.pool.begin().await?;
+        let album = sqlx::query!(
+            "SELECT id FROM albums WHERE id = ?1 AND (?2 IS NULL OR owner_id = ?2)",
+            id,
+            owner_id
+        )
+        .fetch_optional(&mut tx)
+        .await?;
+        if album.is_none() {
+            return Ok(false);
+        }
+        if cascade {
+            let deleted_at = Utc::now().to_string();
+            sqlx::query!(
+                r#"
+                WITH RECURSIVE subtree(id) AS (
+                    SELECT ?1
+                    UNION
+                    SELECT albums.id FROM albums JOIN subtree ON albums.parent_id = subtree.id
+                )
+                UPDATE images SET deleted_at = ?2
+                    WHERE deleted_at IS NULL AND (?3 IS NULL OR owner_id = ?3) AND id IN (
+                        SELECT image_id FROM album_images
+                            WHERE album_id IN (SELECT id FROM subtree)
+                    )
+                "#,
+                id,
+                deleted_at,
+                owner_id
+            )
+            .execute(&mut tx)
+            .await?;
+        }
+        sqlx::query!("DELETE FROM albums WHERE id = ?1", id)
+            .execute(&mut tx)
+            .await?;
+        tx.commit().await?;
+        Ok(true)
+    }
+
+    async fn add_album_image_records(
+        &self,
+        album_id: i64,
+        image_ids: &[i64],
+    ) -> Result<Result<(), AlbumsError>, sqlx::Error> {
+        let owner_id = current_user_id();
+        let mut tx = self.pool.begin().await?;
+        let album = sqlx::query!(
+            "SELECT id FROM albums WHERE id = ?1 AND (?2 IS NULL OR owner_id = ?2)",
+            album_id,
+            owner_id
+        )
+        .fetch_optional(&mut tx)
+        .await?;
+        if album.is_none() {
+            return Ok(Err(AlbumsError::AlbumNotFound));
+        }
+        for image_id in image_ids {
+            let image = sqlx::query!(
+                "SELECT id FROM images WHERE id = ?1 AND deleted_at IS NULL AND (?2 IS NULL OR owner_id = ?2)",
+                image_id,
+                owner_id
+            )
+            .fetch_optional(&mut tx)
+            .await?;
+            if image.is_none() {
+                return Ok(Err(AlbumsError::ImageNotFound));
+            }
+            sqlx::query!(
+                r#"
+                INSERT INTO album_images (album_id, image_id, position)
+                    SELECT ?1, ?2, COALESCE(MAX(position) + 1, 0) FROM album_images
+                    WHERE album_id = ?1
+                    ON CONFLICT (album_id, image_id) DO NOTHING
+                "#,
+                album_id,
+                image_id
+            )
+            .execute(&mut tx)
+            .await?;
+        }
+        tx.commit().await?;
+        Ok(Ok(()))
+    }
+
+    /// Removing the cover image leaves the album without one.
+    async fn remove_album_image_record(
+        &self,
+        album_id: i64,
+        image_id: i64,
+    ) -> Result<Result<(), AlbumsError>, sqlx::Error> {
+        let owner_id = current_user_id();
+        let mut tx = self.pool.begin().await?;
+        let album = sqlx::query!(
+            "SELECT id FROM albums WHERE id = ?1 AND (?2 IS NULL OR owner_id = ?2)",
+            album_id,
+            owner_id
+        )
+        .fetch_optional(&mut tx)
+        .await?;
+        if album.is_none() {
+            return Ok(Err(AlbumsError::AlbumNotFound));
+        }
+        let removed = sqlx::query!(
+            "DELETE FROM album_images WHERE album_id = ?1 AND image_id = ?2",
+            album_id,
+            image_id
+        )
+        .execute(&mut tx)
+        .await?
+        .rows_affected();
+        if removed == 0 {
+            return Ok(Err(AlbumsError::ImageNotFound));
+        }
+        sqlx::query!(
+            "UPDATE albums SET cover_image_id = NULL WHERE id = ?1 AND cover_image_id = ?2",
+            album_id,
+            image_id
+        )
+        .execute(&mut tx)
+        .await?;
+        tx.commit().await?;
+        Ok(Ok(()))
+    }
+
+    async fn reorder_album_image_records(
+        &self,
+        album_id: i64,
+        image_ids: &[i64],
+    ) -> Result<Result<(), AlbumsError>, sqlx::Error> {
+        let owner_id = current_user_id();
+        let mut tx = self.pool.begin().await?;
+        let album = sqlx::query!(
+            "SELECT id FROM albums WHERE id = ?1 AND (?2 IS NULL OR owner_id = ?2)",
+            album_id,
+            owner_id
+        )
+        .fetch_optional(&mut tx)
+        .await?;
+        if album.is_none() {
+            return Ok(Err(AlbumsError::AlbumNotFound));
+        }
+        let mut members = sqlx::query!(
+            "SELECT image_id FROM album_images WHERE album_id = ?1",
+            album_id
+        )
+        .fetch_all(&mut tx)
+        .await?
+        .into_iter()
+        .map(|record| record.image_id)
+        .collect::<Vec<i64>>();
+        let mut requested = image_ids.to_vec();
+        members.sort_unstable();
+        requested.sort_unstable();
+        if members != requested {
+            return Ok(Err(AlbumsError::InvalidOrder));
+        }
+        for (position, image_id) in image_ids.iter().enumerate() {
+            let position = position as i64;
+            sqlx::query!(
+                "UPDATE album_images SET position = ?1 WHERE album_id = ?2 AND image_id = ?3",
+                position,
+                album_id,
+                image_id
+            )
+            .execute(&mut tx)
+            .await?;
+        }
+        tx.commit().await?;
+        Ok(Ok(()))
+    }
+
+    /// Dropping the transaction on any error, `remove` included, keeps the
+    /// image.
+    async fn delete_image_records(
+        &self,
+        index: i64,
+        remove: Option<RemoveFiles>,
+    ) -> Result<Vec<String>, sqlx::Error> {
+        let owner_id = current_user_id();
+        let mut tx = self.pool.begin().await?;
+        sqlx::query!(
+            "SELECT id FROM images WHERE id = ?1 AND (?2 IS NULL OR owner_id = ?2)",
+            index,
+            owner_id
+        )
+        .fetch_one(&mut tx)
+        .await?;
+        let renditions = sqlx::query!(
+            r#"DELETE FROM renditions WHERE image_id = ?1 RETURNING path as "path!""#,
+            index
+        )
+        .fetch_all(&mut tx)
+        .await?;
+        let record = sqlx::query!(
+            r#"DELETE FROM images WHERE id = ?1 RETURNING path, original_path"#,
+            index
+        )
+        .fetch_one(&mut tx)
+        .await?;
+
+        let mut paths = vec![record.path];
+        paths.extend(record.original_path);
+        paths.extend(renditions.into_iter().map(|rendition| rendition.path));
+        if let Some(remove) = remove {
+            remove(paths.clone()).await.map_err(sqlx::Error::Io)?;
+        }
+        tx.commit().await?;
+        Ok(paths)
+    }
+
+    async fn batch_delete_image_records(
+        &self,
+        indexes: &[i64],
+    ) -> Result<Vec<String>, sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+        let indexes = sqlx::query(&format!(
+            "SELECT id FROM images WHERE id in ({}){}",
+            itertools::join(indexes, ","),
+            owner_filter("images")
+        ))
+        .fetch_all(&mut tx)
+        .await?
+        .into_iter()
+        .map(|record| record.get::<i64, &str>("id"))
+        .collect::<Vec<i64>>();
+        if indexes.is_empty() {
+            return Ok(vec![]);
+        }
+        let paths = Self::delete_image_rows(&mut tx, &indexes).await?;
+        tx.commit().await?;
+        Ok(paths)
+    }
+
+    async fn purge_trash_records(
+        &self,
+        before: DateTime<Utc>,
+        remove: Option<RemoveFiles>,
+    ) -> Result<Vec<String>, sqlx::Error> {
+        let before = before.to_string();
+        let owner_id = current_user_id();
+        let mut tx = self.pool.begin().await?;
+        let indexes = sqlx::query!(
+            r#"
+                SELECT id FROM images
+                    WHERE deleted_at IS NOT NULL AND deleted_at <= ?1 AND (?2 IS NULL OR owner_id = ?2)
+            "#,
+            before,
+            owner_id
+        )
+        .fetch_all(&mut tx)
+        .await?
+        .into_iter()
+        .map(|record| record.id)
+        .collect::<Vec<i64>>();
+        if indexes.is_empty() {
+            return Ok(vec![]);
+        }
+        let paths = Self::delete_image_rows(&mut tx, &indexes).await?;
+        if let Some(remove) = remove {
+            remove(paths.clone()).await.map_err(sqlx::Error::Io)?;
+        }
+        tx.commit().await?;
+        Ok(paths)
+    }
+
+    async fn query_image_path_records(
+        &self,
+        after: i64,
+        count: i64,
+    ) -> Result<Vec<ImagePaths>, sqlx::Error> {
+        let images = sqlx::query!(
+            "SELECT id, path, original_path FROM images WHERE id > ?1 ORDER BY id LIMIT ?2",
+            after,
+            count
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        let last = match images.last() {
+            Some(last) => last.id,
+            None => return Ok(vec![]),
+        };
+        let mut renditions = HashMap::<i64, Vec<String>>::new();
+        for record in sqlx::query!(
+            "SELECT image_id, path FROM renditions WHERE image_id > ?1 AND image_id <= ?2 ORDER BY id",
+            after,
+            last
+        )
+        .fetch_all(&self.pool)
+        .await?
+        {
+            renditions
+                .entry(record.image_id)
+                .or_default()
+                .push(record.path);
+        }
+        Ok(images
+            .into_iter()
+            .map(|record| ImagePaths {
+                image_id: record.id,
+                paths: std::iter::once(record.path)
+                    .chain(record.original_path)
+                    .chain(renditions.remove(&record.id).unwrap_or_default())
+                    .collect(),
+            })
+            .collect())
+    }
+
+    /// Dropping the transaction on any error, `persist` included, keeps the
+    /// old paths.
+    async fn relocate_path_records(
+        &self,
+        moves: &[(String, String)],
+        persist: Persist,
+    ) -> Result<(), sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+        for (from, to) in moves {
+            sqlx::query!("UPDATE images SET path = ?1 WHERE path = ?2", to, from)
+                .execute(&mut tx)
+                .await?;
+            sqlx::query!(
+                "UPDATE images SET original_path = ?1 WHERE original_path = ?2",
+                to,
+                from
+            )
+            .execute(&mut tx)
+            .await?;
+            sqlx::query!("UPDATE renditions SET path = ?1 WHERE path = ?2", to, from)
+                .execute(&mut tx)
+                .await?;
+        }
+        persist.await.map_err(sqlx::Error::Io)?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Deletes the images and their renditions, returning the paths of
+    /// every file they used.
+    async fn delete_image_rows(
+        tx: &mut Transaction<'_, Sqlite>,
+        indexes: &[i64],
+    ) -> Result<Vec<String>, sqlx::Error> {
+        let indexes = itertools::join(indexes, ",");
+        let renditions = sqlx::query(&format!(
+            "DELETE FROM renditions WHERE image_id in ({}) RETURNING path",
+            indexes
+        ))
+        .fetch_all(&mut *tx)
+        .await?;
+        let images = sqlx::query(&format!(
+            "DELETE FROM images WHERE id in ({}) RETURNING path, original_path",
+            indexes
+        ))
+        .fetch_all(&mut *tx)
+        .await?;
+
+        let originals = images
+            .iter()
+            .filter_map(|record| record.get::<Option<String>, &str>("original_path"))
+            .collect::<Vec<String>>();
+        Ok(images
+            .into_iter()
+            .chain(renditions)
+            .map(|record| record.get::<String, &str>("path"))
+            .chain(originals)
+            .collect::<Vec<String>>())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::images::domain::{
+        exif::ExifData, image_metadata::ImageMetadata, user::on_behalf_of,
+    };
+    use rstest::*;
+
+    #[fixture]
+    async fn repository() -> ImagesSqliteDS {
+        println!(
+            "{} {:?}",
+            std::env::var("DB_URL").unwrap(),
+            std::env::current_dir().unwrap()
+        );
+        let pool = SqlitePool::connect(&std::env::var("DB_URL").unwrap())
+            .await
+            .unwrap();
+        let storage = ImagesSqliteDS::new(pool);
+        let image1 = image1();
+        let image2 = image2();
+        let image3 = image3();
+        let _ = storage.insert_image(&image1).await;
+        let _ = storage.insert_image(&image2).await;
+        let _ = storage.insert_image(&image3).await;
+        storage
+    }
+
+    fn image1() -> Image {
+        Image::new(
+            1,
+            "path/to/image1".to_string(),
+            "2023-07-12T19:29:11.113508Z"
+                .parse::<DateTime<chrono::Utc>>()
+                .unwrap(),
+        )
+    }
+    fn image2() -> Image {
+        Image::new(
+            2,
+            "path/to/image2".to_string(),
+            "2023-07-12T19:29:11.113508Z"
+                .parse::<DateTime<chrono::Utc>>()
+                .unwrap(),
+        )
+    }
+    fn image3() -> Image {
+        Image::new(
+            3,
+            "path/to/image3".to_string(),
+            "2023-07-12T19:29:11.113508Z"
+                .parse::<DateTime<chrono::Utc>>()
+                .unwrap(),
+        )
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn test_query_images(
+        repository: impl std::future::Future<Output = ImagesSqliteDS>,
+        #[values(image1())] image1: Image,
+        #[values(image2())] image2: Image,
+        #[values(image3())] image3: Image,
+    ) {
+        // Query images
+        let repository = repository.await;
+        let images = repository
+            .query_images(2, 0, SortKey::UpdatedOn)
+            .await
+            .unwrap();
+        assert_eq!(images.len(), 2);
+        assert!(images.contains(&image1));
+        assert!(images.contains(&image2));
+
+        let images = repository
+            .query_images(2, 1, SortKey::UpdatedOn)
+            .await
+            .unwrap();
+        assert_eq!(images.len(), 2);
+        assert!(images.contains(&image2));
+        assert!(images.contains(&image3));
+
+        let images = repository
+            .query_images(1, 2, SortKey::UpdatedOn)
+            .await
+            .unwrap();
+        assert_eq!(images.len(), 1);
+        assert!(images.contains(&image3));
+    }
+
+    #[rstest]
+    #[case(image1())]
+    #[case(image2())]
+    #[case(image3())]
+    #[tokio::test]
+    async fn test_query_image(
         repository: impl std::future::Future<Output = ImagesSqliteDS>,
         #[case] expected_image: Image,
     ) {
         let repository = repository.await;
-        let index = expected_image.id();
-        // Query image
-        let image = repository.query_image(index).await.unwrap();
-        assert_eq!(expected_image, image);
+        let index = expected_image.id();
+        // Query image
+        let image = repository.query_image(index).await.unwrap();
+        assert_eq!(expected_image, image);
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn test_delete_image(repository: impl std::future::Future<Output = ImagesSqliteDS>) {
+        let repository = repository.await;
+        // Insert some test images
+        let image = Image::new(5, "path/to/image5".to_string(), Utc::now());
+        repository.insert_image(&image).await.unwrap();
+
+        // Delete image
+        let paths = repository.delete_image(5).await.unwrap();
+        assert_eq!(paths, vec!["path/to/image5".to_string()]);
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn test_delete_image_keeps_row_when_files_stay(
+        repository: impl std::future::Future<Output = ImagesSqliteDS>,
+    ) {
+        let repository = repository.await;
+        let image = Image::new(1160, "path/to/image1160".to_string(), Utc::now());
+        repository.insert_image(&image).await.unwrap();
+
+        let remove: RemoveFiles = Box::new(|paths| {
+            assert_eq!(paths, vec!["path/to/image1160".to_string()]);
+            Box::pin(async { Err(std::io::Error::other("read-only")) })
+        });
+        assert!(repository.delete_image_with(1160, remove).await.is_err());
+        assert!(repository.query_image(1160).await.is_ok());
+        let remove: RemoveFiles = Box::new(|_paths| Box::pin(async { Ok(()) }));
+        repository.delete_image_with(1160, remove).await.unwrap();
+        assert!(repository.query_image(1160).await.is_err());
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn test_trash(repository: impl std::future::Future<Output = ImagesSqliteDS>) {
+        let repository = repository.await;
+        for id in 1100..=1102 {
+            let image = Image::new(id, format!("path/to/image{}", id), Utc::now());
+            repository.insert_image(&image).await.unwrap();
+        }
+        // Old enough not to be purged along with the trash of other tests.
+        let deleted_at = DateTime::parse_from_rfc3339("2001-02-03T04:05:06Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let trashed = repository
+            .trash_images(&[1100, 1101, i64::MAX], deleted_at)
+            .await
+            .unwrap();
+        assert_eq!(trashed, 2);
+        assert!(repository.query_image(1100).await.is_err());
+        let trash = repository.query_trash(50, 0).await.unwrap();
+        let trash = trash
+            .iter()
+            .filter(|image| image.id() >= 1100 && image.id() <= 1102)
+            .collect::<Vec<&Image>>();
+        assert_eq!(trash.len(), 2);
+        assert_eq!(trash[0].deleted_at(), Some(deleted_at));
+
+        repository.restore_image(1101).await.unwrap();
+        assert!(repository.query_image(1101).await.is_ok());
+        assert_eq!(
+            repository.restore_image(1102).await,
+            Err(TrashError::ImageNotFound)
+        );
+
+        let paths = repository
+            .purge_trash(deleted_at + chrono::Duration::seconds(1))
+            .await
+            .unwrap();
+        assert_eq!(paths, vec!["path/to/image1100".to_string()]);
+        let paths = repository
+            .batch_delete_image(vec![1101, 1102])
+            .await
+            .unwrap();
+        assert_eq!(paths.len(), 2);
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn test_insert_image(repository: impl std::future::Future<Output = ImagesSqliteDS>) {
+        let repository = repository.await;
+        // Insert image
+        let image = Image::new(
+            4,
+            "path/to/image4".to_string(),
+            "2023-07-12T20:38:39.443964Z"
+                .parse::<DateTime<chrono::Utc>>()
+                .unwrap(),
+        );
+        repository.insert_image(&image).await.unwrap();
+
+        let queried_image = repository.query_image(4).await.unwrap();
+        assert_eq!(queried_image, image);
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn test_insert_image_returns_id(
+        repository: impl std::future::Future<Output = ImagesSqliteDS>,
+    ) {
+        let repository = repository.await;
+        let image = Image::new(0, "path/to/image9".to_string(), Utc::now());
+        let id = repository.insert_image(&image).await.unwrap();
+
+        let queried_image = repository.query_image(id).await.unwrap();
+        assert_eq!(queried_image.path(), "path/to/image9");
+        repository.delete_image(id).await.unwrap();
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn test_insert_image_with_failed_persist_rolls_back(
+        repository: impl std::future::Future<Output = ImagesSqliteDS>,
+    ) {
+        let repository = repository.await;
+        let image = Image::new(1110, "path/to/image1110".to_string(), Utc::now());
+        let persist: Persist = Box::pin(async { Err(std::io::ErrorKind::NotFound.into()) });
+        let result = repository.insert_image_with(&image, persist).await;
+        assert!(matches!(result, Err(InsertImageError::InternalError)));
+        assert!(repository.query_image(1110).await.is_err());
+
+        let id = repository
+            .insert_image_with(&image, Box::pin(async { Ok(()) }))
+            .await
+            .unwrap();
+        assert_eq!(id, 1110);
+        repository.delete_image(id).await.unwrap();
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn test_consistency(repository: impl std::future::Future<Output = ImagesSqliteDS>) {
+        let repository = repository.await;
+        let image = Image::new(1120, "path/to/image1120".to_string(), Utc::now())
+            .with_original_path("path/to/original1120.png".to_string());
+        repository.insert_image(&image).await.unwrap();
+        let rendition = Rendition::new(
+            1120,
+            RenditionSize::Thumb,
+            "path/to/image1120_thumb.qoi".to_string(),
+        );
+        repository.insert_rendition(&rendition).await.unwrap();
+
+        let referenced = repository.query_referenced_paths().await.unwrap();
+        assert!(referenced.contains("path/to/image1120"));
+        assert!(referenced.contains("path/to/original1120.png"));
+        assert!(referenced.contains("path/to/image1120_thumb.qoi"));
+
+        repository
+            .update_image_status(&[1120], ImageStatus::Missing)
+            .await
+            .unwrap();
+        let files = repository.query_image_files().await.unwrap();
+        let file = files.iter().find(|file| file.image_id() == 1120).unwrap();
+        assert_eq!(file.status(), ImageStatus::Missing);
+        let image = repository.query_image(1120).await.unwrap();
+        assert_eq!(image.status(), ImageStatus::Missing);
+        repository.delete_image(1120).await.unwrap();
     }
 
     #[rstest]
     #[tokio::test]
-    async fn test_delete_image(repository: impl std::future::Future<Output = ImagesSqliteDS>) {
+    async fn test_scrub(repository: impl std::future::Future<Output = ImagesSqliteDS>) {
         let repository = repository.await;
-        // Insert some test images
-        let image = Image::new(5, "path/to/image5".to_string(), Utc::now());
+        let image = Image::new(1130, "path/to/image1130".to_string(), Utc::now());
+        repository.insert_image(&image).await.unwrap();
+        let image = Image::new(1131, "path/to/image1131".to_string(), Utc::now())
+            .with_checksum("abc".to_string());
         repository.insert_image(&image).await.unwrap();
 
-        // Delete image
-        let path = repository.delete_image(5).await.unwrap();
-        assert_eq!(path, "path/to/image5".to_string());
+        repository
+            .record_scrub(1130, ImageStatus::Ok, Some("def".to_string()), Utc::now())
+            .await
+            .unwrap();
+        repository
+            .record_scrub(
+                1131,
+                ImageStatus::Corrupted,
+                Some("def".to_string()),
+                Utc::now(),
+            )
+            .await
+            .unwrap();
+        let image = repository.query_image(1130).await.unwrap();
+        assert_eq!(image.checksum(), Some("def"));
+        let image = repository.query_image(1131).await.unwrap();
+        assert_eq!(image.checksum(), Some("abc"));
+        assert_eq!(image.status(), ImageStatus::Corrupted);
+
+        let corrupted = repository
+            .query_images_by_status(ImageStatus::Corrupted)
+            .await
+            .unwrap();
+        assert!(corrupted.iter().any(|image| image.id() == 1131));
+        let counts = repository.count_images_by_status().await.unwrap();
+        assert!(counts
+            .iter()
+            .any(|(status, count)| *status == ImageStatus::Corrupted && *count >= 1));
+        let batch = repository.query_scrub_batch(i64::MAX).await.unwrap();
+        let file = batch.iter().find(|file| file.image_id() == 1130).unwrap();
+        assert_eq!(file.checksum(), Some("def"));
+        repository
+            .batch_delete_image(vec![1130, 1131])
+            .await
+            .unwrap();
     }
 
     #[rstest]
     #[tokio::test]
-    async fn test_insert_image(repository: impl std::future::Future<Output = ImagesSqliteDS>) {
+    async fn test_layout(repository: impl std::future::Future<Output = ImagesSqliteDS>) {
         let repository = repository.await;
-        // Insert image
-        let image = Image::new(
-            4,
-            "path/to/image4".to_string(),
-            "2023-07-12T20:38:39.443964Z"
-                .parse::<DateTime<chrono::Utc>>()
-                .unwrap(),
+        let image = Image::new(1140, "data/abcd1140.qoi".to_string(), Utc::now())
+            .with_original_path("data/abcd1140.png".to_string());
+        repository.insert_image(&image).await.unwrap();
+        let rendition = Rendition::new(
+            1140,
+            RenditionSize::Thumb,
+            "data/abcd1140_thumb.qoi".to_string(),
+        );
+        repository.insert_rendition(&rendition).await.unwrap();
+
+        let images = repository.query_image_paths(1139, 1).await.unwrap();
+        assert_eq!(
+            images,
+            vec![ImagePaths {
+                image_id: 1140,
+                paths: vec![
+                    "data/abcd1140.qoi".to_string(),
+                    "data/abcd1140.png".to_string(),
+                    "data/abcd1140_thumb.qoi".to_string(),
+                ],
+            }]
+        );
+
+        let moves = images[0]
+            .paths
+            .iter()
+            .map(|path| (path.clone(), path.replace("data/", "data/ab/cd/")))
+            .collect::<Vec<(String, String)>>();
+        let persist: Persist = Box::pin(async { Err(std::io::ErrorKind::NotFound.into()) });
+        let result = repository.relocate_paths(&moves, persist).await;
+        assert!(matches!(result, Err(LayoutError::InternalError)));
+        let image = repository.query_image(1140).await.unwrap();
+        assert_eq!(image.path(), "data/abcd1140.qoi");
+
+        repository
+            .relocate_paths(&moves, Box::pin(async { Ok(()) }))
+            .await
+            .unwrap();
+        let image = repository.query_image(1140).await.unwrap();
+        assert_eq!(image.path(), "data/ab/cd/abcd1140.qoi");
+        assert_eq!(image.original_path(), Some("data/ab/cd/abcd1140.png"));
+        let rendition = repository
+            .query_rendition(1140, RenditionSize::Thumb)
+            .await
+            .unwrap();
+        assert_eq!(rendition.path(), "data/ab/cd/abcd1140_thumb.qoi");
+        repository.delete_image(1140).await.unwrap();
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn test_image_metadata(repository: impl std::future::Future<Output = ImagesSqliteDS>) {
+        let repository = repository.await;
+        let updated_on = "2023-08-26T11:00:00Z".parse::<DateTime<Utc>>().unwrap();
+        let created_on = "2023-08-20T09:30:00Z".parse::<DateTime<Utc>>().unwrap();
+        let metadata = ImageMetadata::new(
+            640,
+            480,
+            "Rgba8".to_string(),
+            8,
+            "png".to_string(),
+            2048,
+            1024,
+        )
+        .with_filename("photo.png".to_string());
+        let image = Image::new(1040, "path/to/image1040".to_string(), updated_on)
+            .with_created_on(created_on)
+            .with_metadata(metadata)
+            .with_original_path("path/to/originals/image1040.png".to_string());
+        repository.insert_image(&image).await.unwrap();
+
+        let queried_image = repository.query_image(1040).await.unwrap();
+        assert_eq!(queried_image, image);
+        let paths = repository.delete_image(1040).await.unwrap();
+        assert_eq!(
+            paths,
+            vec![
+                "path/to/image1040".to_string(),
+                "path/to/originals/image1040.png".to_string()
+            ]
         );
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn test_image_exif(repository: impl std::future::Future<Output = ImagesSqliteDS>) {
+        let repository = repository.await;
+        let captured_on = "1999-01-01T08:00:00Z".parse::<DateTime<Utc>>().unwrap();
+        let exif = ExifData::default()
+            .with_captured_on(captured_on)
+            .with_camera(Some("Yaiss".to_string()), Some("Camera One".to_string()))
+            .with_exposure(Some("1/125 s".to_string()), Some(2.8), Some(200), None)
+            .with_location(Some(41.15), Some(-8.61), None)
+            .with_orientation(6);
+        let updated_on = Utc::now();
+        let image =
+            Image::new(1050, "path/to/image1050".to_string(), updated_on).with_exif(exif.clone());
         repository.insert_image(&image).await.unwrap();
+        // Captured and updated together, so only the id orders them.
+        let twin = Image::new(1051, "path/to/image1051".to_string(), updated_on).with_exif(exif);
+        repository.insert_image(&twin).await.unwrap();
 
-        let queried_image = repository.query_image(4).await.unwrap();
+        let queried_image = repository.query_image(1050).await.unwrap();
         assert_eq!(queried_image, image);
+        let images = repository
+            .query_images(1, 0, SortKey::CapturedOn)
+            .await
+            .unwrap();
+        assert_eq!(images, vec![image]);
+        let images = repository
+            .query_images(1, 1, SortKey::CapturedOn)
+            .await
+            .unwrap();
+        assert_eq!(images, vec![twin]);
+
+        repository.delete_image(1050).await.unwrap();
+        repository.delete_image(1051).await.unwrap();
+        let images = repository
+            .query_images(50, 0, SortKey::CapturedOn)
+            .await
+            .unwrap();
+        assert!(!images.iter().any(|image| image.id() == 1050));
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn test_tags(repository: impl std::future::Future<Output = ImagesSqliteDS>) {
+        let repository = repository.await;
+        for id in [1060, 1061] {
+            let image = Image::new(id, format!("path/to/image{}", id), Utc::now());
+            repository.insert_image(&image).await.unwrap();
+        }
+        let both = ["tag1060".to_string(), "tag1060-common".to_string()];
+        let tags = repository.add_tags(1060, &both).await.unwrap();
+        assert_eq!(tags, both.to_vec());
+        repository
+            .add_tags(1061, &["tag1060-common".to_string()])
+            .await
+            .unwrap();
+        let missing = repository.add_tags(1069, &both).await;
+        assert_eq!(missing, Err(TagsError::ImageNotFound));
+
+        let tags = repository.query_tags().await.unwrap();
+        assert!(tags.contains(&Tag::new("tag1060-common".to_string(), 2)));
+        let all = repository
+            .query_images_by_tags(&both, TagMatch::All, 10, 0, SortKey::UpdatedOn)
+            .await
+            .unwrap();
+        assert_eq!(all.iter().map(Image::id).collect::<Vec<i64>>(), vec![1060]);
+        let any = repository
+            .query_images_by_tags(&both, TagMatch::Any, 10, 0, SortKey::UpdatedOn)
+            .await
+            .unwrap();
+        assert_eq!(any.len(), 2);
+
+        repository
+            .rename_tag("tag1060", "tag1060-common")
+            .await
+            .unwrap();
+        let tags = repository.query_tags().await.unwrap();
+        assert!(!tags.iter().any(|tag| tag.name() == "tag1060"));
+        assert_eq!(
+            repository.rename_tag("tag1060", "other").await,
+            Err(TagsError::TagNotFound)
+        );
+
+        repository.remove_tag(1060, "tag1060-common").await.unwrap();
+        assert_eq!(
+            repository.remove_tag(1060, "tag1060-common").await,
+            Err(TagsError::TagNotFound)
+        );
+        repository.delete_image(1060).await.unwrap();
+        repository.delete_image(1061).await.unwrap();
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn test_api_keys(repository: impl std::future::Future<Output = ImagesSqliteDS>) {
+        let repository = repository.await;
+        let hash = format!("sqlite-key-{}", hex::encode(rand::random::<[u8; 8]>()));
+        let key = repository
+            .insert_api_key(
+                "sqlite-key",
+                &hash,
+                &[Scope::Read, Scope::Upload],
+                Utc::now(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(key.scopes(), [Scope::Read, Scope::Upload]);
+        assert_eq!(
+            repository
+                .insert_api_key("sqlite-key", &hash, &[Scope::Read], Utc::now())
+                .await,
+            Err(ApiKeysError::AlreadyExists)
+        );
+        assert_eq!(
+            repository.query_api_key_by_hash(&hash).await,
+            Ok(key.clone())
+        );
+        assert!(repository.query_api_keys().await.unwrap().contains(&key));
+
+        repository
+            .revoke_api_key(key.id(), Utc::now())
+            .await
+            .unwrap();
+        let revoked = repository.query_api_key_by_hash(&hash).await.unwrap();
+        assert!(revoked.revoked_on().is_some());
+        assert_eq!(
+            repository.revoke_api_key(key.id(), Utc::now()).await,
+            Err(ApiKeysError::KeyNotFound)
+        );
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn test_shares(repository: impl std::future::Future<Output = ImagesSqliteDS>) {
+        let repository = repository.await;
+        let image = Image::new(1150, "path/to/image1150".to_string(), Utc::now());
+        repository.insert_image(&image).await.unwrap();
+        let created_on = "2023-11-11T10:00:00Z".parse::<DateTime<Utc>>().unwrap();
+        let expires_on = "2023-11-12T10:00:00Z".parse::<DateTime<Utc>>().unwrap();
+        let share = repository
+            .insert_share(1150, created_on, expires_on)
+            .await
+            .unwrap();
+        assert_eq!(share.image_id(), 1150);
+        assert_eq!(repository.query_share(share.id()).await, Ok(share.clone()));
+        assert_eq!(
+            repository.insert_share(-1, created_on, expires_on).await,
+            Err(SharesError::ImageNotFound)
+        );
+
+        repository
+            .revoke_share(share.id(), expires_on)
+            .await
+            .unwrap();
+        assert_eq!(
+            repository
+                .query_share(share.id())
+                .await
+                .unwrap()
+                .revoked_on(),
+            Some(expires_on)
+        );
+        assert_eq!(
+            repository.revoke_share(share.id(), expires_on).await,
+            Err(SharesError::ShareNotFound)
+        );
+        repository.delete_image(1150).await.unwrap();
+        assert_eq!(
+            repository.query_share(share.id()).await,
+            Err(SharesError::ShareNotFound)
+        );
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn test_users(repository: impl std::future::Future<Output = ImagesSqliteDS>) {
+        let repository = repository.await;
+        let name = format!("sqlite-user-{}", hex::encode(rand::random::<[u8; 8]>()));
+        let user = repository
+            .insert_user(&name, "password-hash", Utc::now())
+            .await
+            .unwrap();
+        assert_eq!(
+            repository
+                .insert_user(&name, "other-hash", Utc::now())
+                .await,
+            Err(UsersError::AlreadyExists)
+        );
+        assert_eq!(
+            repository.query_user_by_name(&name).await,
+            Ok((user.clone(), "password-hash".to_string()))
+        );
+        let token_hash = format!("{}-session", name);
+        repository
+            .insert_session(
+                user.id(),
+                &token_hash,
+                Utc::now() + chrono::Duration::days(1),
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            repository.query_session_user(&token_hash, Utc::now()).await,
+            Ok(user.clone())
+        );
+        assert_eq!(
+            repository
+                .query_session_user(&token_hash, Utc::now() + chrono::Duration::days(2))
+                .await,
+            Err(UsersError::UserNotFound)
+        );
+        repository.delete_session(&token_hash).await.unwrap();
+        assert_eq!(
+            repository.delete_session(&token_hash).await,
+            Err(UsersError::UserNotFound)
+        );
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn test_owner_isolation(repository: impl std::future::Future<Output = ImagesSqliteDS>) {
+        let repository = repository.await;
+        let suffix = hex::encode(rand::random::<[u8; 8]>());
+        let alice = repository
+            .insert_user(&format!("sqlite-alice-{}", suffix), "hash", Utc::now())
+            .await
+            .unwrap();
+        let bob = repository
+            .insert_user(&format!("sqlite-bob-{}", suffix), "hash", Utc::now())
+            .await
+            .unwrap();
+        let hash = format!("sqlite-hash-{}", suffix);
+        let image =
+            Image::new(0, format!("path/to/alice-{}", suffix), Utc::now()).with_hash(hash.clone());
+        let id = on_behalf_of(alice.id(), repository.insert_image(&image))
+            .await
+            .unwrap();
+        // The same content is no duplicate for another user.
+        let image =
+            Image::new(0, format!("path/to/bob-{}", suffix), Utc::now()).with_hash(hash.clone());
+        let bob_id = on_behalf_of(bob.id(), repository.insert_image(&image))
+            .await
+            .unwrap();
+
+        assert!(on_behalf_of(alice.id(), repository.query_image(id))
+            .await
+            .is_ok());
+        assert!(matches!(
+            on_behalf_of(bob.id(), repository.query_image(id)).await,
+            Err(query_image_port::QueryError::RecordNotFound)
+        ));
+        assert_eq!(
+            on_behalf_of(bob.id(), repository.query_image_by_hash(&hash))
+                .await
+                .unwrap()
+                .id(),
+            bob_id
+        );
+        assert!(on_behalf_of(bob.id(), repository.delete_image(id))
+            .await
+            .is_err());
+        assert!(
+            on_behalf_of(bob.id(), repository.batch_delete_image(vec![id]))
+                .await
+                .unwrap()
+                .is_empty()
+        );
+        let album = on_behalf_of(alice.id(), repository.create_album("Alice", None))
+            .await
+            .unwrap();
+        assert_eq!(
+            on_behalf_of(bob.id(), repository.query_album(album.id())).await,
+            Err(AlbumsError::AlbumNotFound)
+        );
+
+        // Without a user, as for API keys, everything is visible.
+        assert!(repository.query_image(id).await.is_ok());
+        repository.delete_album(album.id(), false).await.unwrap();
+        repository.delete_image(id).await.unwrap();
+        repository.delete_image(bob_id).await.unwrap();
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn test_albums(repository: impl std::future::Future<Output = ImagesSqliteDS>) {
+        let repository = repository.await;
+        for id in 1090..=1093 {
+            let image = Image::new(id, format!("path/to/image{}", id), Utc::now());
+            repository.insert_image(&image).await.unwrap();
+        }
+        let root = repository.create_album("Root 1090", None).await.unwrap();
+        let child = repository
+            .create_album("Child 1090", Some(root.id()))
+            .await
+            .unwrap();
+        assert_eq!(child.parent_id(), Some(root.id()));
+        assert_eq!(
+            repository.create_album("Orphan", Some(i64::MAX)).await,
+            Err(AlbumsError::AlbumNotFound)
+        );
+        let children = repository.query_albums(Some(root.id())).await.unwrap();
+        assert_eq!(children, vec![child.clone()]);
+
+        let ids = |images: Vec<Image>| images.iter().map(Image::id).collect::<Vec<i64>>();
+        repository
+            .add_images(root.id(), &[1091, 1090])
+            .await
+            .unwrap();
+        repository
+            .add_images(root.id(), &[1092, 1091])
+            .await
+            .unwrap();
+        let images = repository
+            .query_album_images(root.id(), 10, 0)
+            .await
+            .unwrap();
+        assert_eq!(ids(images), vec![1091, 1090, 1092]);
+        assert_eq!(
+            repository.add_images(root.id(), &[i64::MAX]).await,
+            Err(AlbumsError::ImageNotFound)
+        );
+
+        repository
+            .reorder_images(root.id(), &[1092, 1091, 1090])
+            .await
+            .unwrap();
+        let images = repository
+            .query_album_images(root.id(), 2, 1)
+            .await
+            .unwrap();
+        assert_eq!(ids(images), vec![1091, 1090]);
+        assert_eq!(
+            repository.reorder_images(root.id(), &[1090]).await,
+            Err(AlbumsError::InvalidOrder)
+        );
+
+        let update = AlbumUpdate {
+            name: Some("Renamed 1090".to_string()),
+            cover_image_id: Some(Some(1092)),
+            ..AlbumUpdate::default()
+        };
+        let album = repository.update_album(root.id(), &update).await.unwrap();
+        assert_eq!(album.name(), "Renamed 1090");
+        assert_eq!(album.cover_image_id(), Some(1092));
+        assert_eq!(album.image_count(), 3);
+        let update = AlbumUpdate {
+            parent_id: Some(Some(child.id())),
+            ..AlbumUpdate::default()
+        };
+        assert_eq!(
+            repository.update_album(root.id(), &update).await,
+            Err(AlbumsError::InvalidParent)
+        );
+        repository.remove_image(root.id(), 1092).await.unwrap();
+        let album = repository.query_album(root.id()).await.unwrap();
+        assert_eq!(album.cover_image_id(), None);
+        assert_eq!(
+            repository.remove_image(root.id(), 1092).await,
+            Err(AlbumsError::ImageNotFound)
+        );
+
+        repository.add_images(child.id(), &[1093]).await.unwrap();
+        repository.delete_album(root.id(), true).await.unwrap();
+        assert_eq!(
+            repository.query_album(child.id()).await,
+            Err(AlbumsError::AlbumNotFound)
+        );
+        assert!(repository.query_image(1093).await.is_err());
+        assert!(repository.query_image(1092).await.is_ok());
+        let paths = repository
+            .batch_delete_image(vec![1090, 1091, 1092, 1093])
+            .await
+            .unwrap();
+        assert_eq!(paths.len(), 4);
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn test_query_images_after(
+        repository: impl std::future::Future<Output = ImagesSqliteDS>,
+    ) {
+        let repository = repository.await;
+        // Far future timestamps keep the other tests' images out of the walk.
+        let updated_on = "2999-01-01T00:00:00Z".parse::<DateTime<Utc>>().unwrap();
+        for id in [1082, 1080, 1081] {
+            let image = Image::new(id, format!("path/to/image{}", id), updated_on);
+            repository.insert_image(&image).await.unwrap();
+        }
+        let later = Image::new(
+            1083,
+            "path/to/image1083".to_string(),
+            updated_on + chrono::Duration::seconds(1),
+        );
+        repository.insert_image(&later).await.unwrap();
+        let ids = |images: Vec<Image>| images.iter().map(Image::id).collect::<Vec<i64>>();
+
+        let cursor = Cursor::new(updated_on - chrono::Duration::seconds(1), i64::MAX);
+        let page = repository
+            .query_images_after(Some(cursor), 2)
+            .await
+            .unwrap();
+        assert_eq!(ids(page), vec![1080, 1081]);
+        let cursor = Cursor::new(updated_on, 1081);
+        let page = repository
+            .query_images_after(Some(cursor), 2)
+            .await
+            .unwrap();
+        assert_eq!(ids(page), vec![1082, 1083]);
+        let cursor = Cursor::new(updated_on + chrono::Duration::seconds(1), 1083);
+        let page = repository
+            .query_images_after(Some(cursor), 2)
+            .await
+            .unwrap();
+        assert!(page.is_empty());
+
+        repository
+            .batch_delete_image(vec![1080, 1081, 1082, 1083])
+            .await
+            .unwrap();
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn test_search_images(repository: impl std::future::Future<Output = ImagesSqliteDS>) {
+        let repository = repository.await;
+        let created_on = "2001-02-03T04:05:06Z".parse::<DateTime<Utc>>().unwrap();
+        for (id, width, height) in [(1070, 300, 200), (1071, 200, 300), (1072, 200, 200)] {
+            let metadata = ImageMetadata::new(
+                width,
+                height,
+                "Rgb8".to_string(),
+                8,
+                "png".to_string(),
+                10,
+                10,
+            );
+            let image = Image::new(id, format!("path/to/image{}", id), Utc::now())
+                .with_created_on(created_on + chrono::Duration::seconds(id))
+                .with_metadata(metadata);
+            repository.insert_image(&image).await.unwrap();
+        }
+        repository
+            .add_tags(1071, &["tag1070".to_string()])
+            .await
+            .unwrap();
+        let ids = |images: Vec<Image>| images.iter().map(Image::id).collect::<Vec<i64>>();
+        let in_range = SearchQuery {
+            created_after: Some(created_on),
+            created_before: Some(created_on + chrono::Duration::days(1)),
+            ..SearchQuery::default()
+        };
+
+        let query = SearchQuery {
+            sort: SortKey::CreatedOn,
+            direction: SortDirection::Descending,
+            ..in_range.clone()
+        };
+        let images = repository.search_images(&query).await.unwrap();
+        assert_eq!(ids(images), vec![1072, 1071, 1070]);
+        let query = SearchQuery {
+            shape: Some(Shape::Landscape),
+            ..in_range.clone()
+        };
+        let images = repository.search_images(&query).await.unwrap();
+        assert_eq!(ids(images), vec![1070]);
+        let query = SearchQuery {
+            max_width: Some(250),
+            min_height: Some(250),
+            original_format: Some("png".to_string()),
+            ..in_range.clone()
+        };
+        let images = repository.search_images(&query).await.unwrap();
+        assert_eq!(ids(images), vec![1071]);
+        let query = SearchQuery {
+            tags: vec!["tag1070".to_string()],
+            ..in_range.clone()
+        };
+        let images = repository.search_images(&query).await.unwrap();
+        assert_eq!(ids(images), vec![1071]);
+        let query = SearchQuery {
+            original_format: Some("jpeg".to_string()),
+            ..in_range
+        };
+        assert!(repository.search_images(&query).await.unwrap().is_empty());
+
+        repository
+            .batch_delete_image(vec![1070, 1071, 1072])
+            .await
+            .unwrap();
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn test_query_image_by_hash(
+        repository: impl std::future::Future<Output = ImagesSqliteDS>,
+    ) {
+        let repository = repository.await;
+        let image = Image::new(1010, "path/to/image1010".to_string(), Utc::now())
+            .with_hash("hash1010".to_string());
+        repository.insert_image(&image).await.unwrap();
+
+        let queried_image = repository.query_image_by_hash("hash1010").await.unwrap();
+        assert_eq!(queried_image.id(), 1010);
+        assert_eq!(queried_image.hash(), Some("hash1010"));
+
+        let duplicate = Image::new(0, "path/to/image1010-copy".to_string(), Utc::now())
+            .with_hash("hash1010".to_string());
+        let result = repository.insert_image(&duplicate).await;
+        assert!(matches!(result, Err(InsertImageError::AlreadyExists)));
+
+        repository.delete_image(1010).await.unwrap();
+        let result = repository.query_image_by_hash("hash1010").await;
+        assert!(matches!(
+            result,
+            Err(query_image_port::QueryError::RecordNotFound)
+        ));
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn test_query_perceptual_hashes(
+        repository: impl std::future::Future<Output = ImagesSqliteDS>,
+    ) {
+        let repository = repository.await;
+        let image = Image::new(1020, "path/to/image1020".to_string(), Utc::now())
+            .with_perceptual_hash(u64::MAX);
+        repository.insert_image(&image).await.unwrap();
+
+        let hashes = repository.query_perceptual_hashes(1019).await.unwrap();
+        assert!(hashes.contains(&(1020, u64::MAX)));
+        let hashes = repository.query_perceptual_hashes(1020).await.unwrap();
+        assert!(!hashes.iter().any(|(id, _)| *id == 1020));
+
+        let images = repository.query_images_by_ids(vec![1020, 1]).await.unwrap();
+        assert_eq!(images.len(), 2);
+        assert!(images.contains(&image));
+        repository.delete_image(1020).await.unwrap();
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn test_renditions(repository: impl std::future::Future<Output = ImagesSqliteDS>) {
+        let repository = repository.await;
+        let image = Image::new(1030, "path/to/image1030".to_string(), Utc::now());
+        repository.insert_image(&image).await.unwrap();
+        let thumb = Rendition::new(
+            1030,
+            RenditionSize::Thumb,
+            "path/to/image1030_thumb".to_string(),
+        );
+        repository.insert_rendition(&thumb).await.unwrap();
+
+        let queried = repository
+            .query_rendition(1030, RenditionSize::Thumb)
+            .await
+            .unwrap();
+        assert_eq!(queried, thumb);
+        let missing = repository.query_rendition(1030, RenditionSize::Large).await;
+        assert!(matches!(
+            missing,
+            Err(query_image_port::QueryError::RecordNotFound)
+        ));
+
+        let paths = repository.delete_image(1030).await.unwrap();
+        assert_eq!(
+            paths,
+            vec![
+                "path/to/image1030".to_string(),
+                "path/to/image1030_thumb".to_string()
+            ]
+        );
+        let deleted = repository.query_rendition(1030, RenditionSize::Thumb).await;
+        assert!(deleted.is_err());
     }
 
     #[rstest]
@@ -364,11 +3326,18 @@ mod tests {
         let image2 = Image::new(8, "path/to/image8".to_string(), Utc::now());
         repository.insert_image(&image).await.unwrap();
         repository.insert_image(&image2).await.unwrap();
+        let rendition = Rendition::new(
+            8,
+            RenditionSize::Preview,
+            "path/to/image8_preview".to_string(),
+        );
+        repository.insert_rendition(&rendition).await.unwrap();
 
         // Delete image
         let paths = repository.batch_delete_image(vec![7, 8]).await.unwrap();
         assert!(paths.contains(&"path/to/image7".to_string()));
         assert!(paths.contains(&"path/to/image8".to_string()));
+        assert!(paths.contains(&"path/to/image8_preview".to_string()));
         // Query images
         let image7_error = repository.query_image(7).await;
         let image8_error = repository.query_image(8).await;
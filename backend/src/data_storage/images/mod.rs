@@ -1 +1,4 @@
+pub mod images_in_memory_ds;
+pub mod images_postgres_ds;
 pub mod images_sqlite_ds;
+mod records;
@@ -0,0 +1,370 @@
+use chrono::{DateTime, Utc};
+
+use crate::services::images::{
+    domain::{
+        album::Album,
+        api_key::{parse_scopes, ApiKey},
+        exif::ExifData,
+        image::Image,
+        image_metadata::ImageMetadata,
+        image_status::ImageStatus,
+        share::Share,
+        sort_key::{SortDirection, SortKey},
+        user::{current_user_id, User},
+    },
+    ports::outgoing::{
+        api_keys_port::ApiKeysError, batch_delete_image_port::BatchDeleteError,
+        batch_query_image_port, delete_image_port::DeleteImageError,
+        insert_image_port::InsertImageError, query_image_port, shares_port::SharesError,
+        users_port::UsersError,
+    },
+};
+
+/// SQLSTATE of a rejected duplicate key.
+const POSTGRES_UNIQUE_VIOLATION: &str = "23505";
+
+impl From<sqlx::Error> for query_image_port::QueryError {
+    fn from(value: sqlx::Error) -> Self {
+        match value {
+            sqlx::Error::RowNotFound => query_image_port::QueryError::RecordNotFound,
+            _ => query_image_port::QueryError::InternalError,
+        }
+    }
+}
+
+impl From<sqlx::Error> for batch_query_image_port::QueryError {
+    fn from(value: sqlx::Error) -> Self {
+        match value {
+            sqlx::Error::RowNotFound => batch_query_image_port::QueryError::RecordNotFound,
+            _ => batch_query_image_port::QueryError::InternalError,
+        }
+    }
+}
+
+impl From<sqlx::Error> for BatchDeleteError {
+    fn from(_value: sqlx::Error) -> Self {
+        BatchDeleteError::InternalError
+    }
+}
+
+impl From<sqlx::Error> for DeleteImageError {
+    fn from(value: sqlx::Error) -> Self {
+        match value {
+            sqlx::Error::RowNotFound => DeleteImageError::RecordNotFound,
+            _ => DeleteImageError::InternalError,
+        }
+    }
+}
+
+impl From<sqlx::Error> for InsertImageError {
+    fn from(value: sqlx::Error) -> Self {
+        match value {
+            sqlx::Error::Database(e) if is_unique_violation(e.as_ref()) => {
+                InsertImageError::AlreadyExists
+            }
+            _ => InsertImageError::InternalError,
+        }
+    }
+}
+
+impl From<sqlx::Error> for ApiKeysError {
+    fn from(value: sqlx::Error) -> Self {
+        match value {
+            sqlx::Error::RowNotFound => ApiKeysError::KeyNotFound,
+            sqlx::Error::Database(e) if is_unique_violation(e.as_ref()) => {
+                ApiKeysError::AlreadyExists
+            }
+            _ => ApiKeysError::InternalError,
+        }
+    }
+}
+
+impl From<sqlx::Error> for UsersError {
+    fn from(value: sqlx::Error) -> Self {
+        match value {
+            sqlx::Error::RowNotFound => UsersError::UserNotFound,
+            sqlx::Error::Database(e) if is_unique_violation(e.as_ref()) => {
+                UsersError::AlreadyExists
+            }
+            _ => UsersError::InternalError,
+        }
+    }
+}
+
+impl From<sqlx::Error> for SharesError {
+    fn from(value: sqlx::Error) -> Self {
+        match value {
+            sqlx::Error::RowNotFound => SharesError::ShareNotFound,
+            _ => SharesError::InternalError,
+        }
+    }
+}
+
+fn is_unique_violation(e: &dyn sqlx::error::DatabaseError) -> bool {
+    e.message().contains("UNIQUE constraint failed")
+        || e.code().as_deref() == Some(POSTGRES_UNIQUE_VIOLATION)
+}
+
+/// Column list matching `ImageRecord`, for queries built at runtime.
+pub(super) const IMAGE_SELECT: &str =
+    "SELECT id, path, updated_on, hash, perceptual_hash, created_on, \
+    width, height, color_type, bit_depth, original_format, original_size, stored_size, filename, \
+    original_path, image_id as exif_id, captured_on, make, model, lens, exposure_time, f_number, \
+    iso, focal_length, latitude, longitude, altitude, orientation, deleted_at, status, checksum \
+    FROM images LEFT JOIN image_exif ON image_id = id";
+
+/// Limits a query built at runtime to the rows of `table` owned by the
+/// current user, if any. The id is inlined like the image ids of those
+/// queries.
+pub(super) fn owner_filter(table: &str) -> String {
+    match current_user_id() {
+        Some(owner_id) => format!(" AND {}.owner_id = {}", table, owner_id),
+        None => String::new(),
+    }
+}
+
+pub(super) fn order_by(sort: SortKey, direction: SortDirection) -> String {
+    let direction = match direction {
+        SortDirection::Ascending => "ASC",
+        SortDirection::Descending => "DESC",
+    };
+    match sort {
+        SortKey::UpdatedOn => format!("updated_on {0}, id {0}", direction),
+        SortKey::CreatedOn => format!("created_on {0}, id {0}", direction),
+        SortKey::CapturedOn => format!(
+            "captured_on IS NULL, captured_on {0}, updated_on {0}, id {0}",
+            direction
+        ),
+    }
+}
+
+#[derive(sqlx::FromRow)]
+pub(super) struct ImageRecord {
+    pub(super) id: i64,
+    pub(super) path: String,
+    pub(super) updated_on: String,
+    pub(super) hash: Option<String>,
+    pub(super) perceptual_hash: Option<i64>,
+    pub(super) created_on: Option<String>,
+    pub(super) width: Option<i64>,
+    pub(super) height: Option<i64>,
+    pub(super) color_type: Option<String>,
+    pub(super) bit_depth: Option<i64>,
+    pub(super) original_format: Option<String>,
+    pub(super) original_size: Option<i64>,
+    pub(super) stored_size: Option<i64>,
+    pub(super) filename: Option<String>,
+    pub(super) original_path: Option<String>,
+    pub(super) exif_id: Option<i64>,
+    pub(super) captured_on: Option<String>,
+    pub(super) make: Option<String>,
+    pub(super) model: Option<String>,
+    pub(super) lens: Option<String>,
+    pub(super) exposure_time: Option<String>,
+    pub(super) f_number: Option<f64>,
+    pub(super) iso: Option<i64>,
+    pub(super) focal_length: Option<f64>,
+    pub(super) latitude: Option<f64>,
+    pub(super) longitude: Option<f64>,
+    pub(super) altitude: Option<f64>,
+    pub(super) orientation: Option<i64>,
+    pub(super) deleted_at: Option<String>,
+    pub(super) status: String,
+    pub(super) checksum: Option<String>,
+}
+
+#[derive(sqlx::FromRow)]
+pub(super) struct AlbumRecord {
+    pub(super) id: i64,
+    pub(super) name: String,
+    pub(super) parent_id: Option<i64>,
+    pub(super) cover_image_id: Option<i64>,
+    pub(super) image_count: i64,
+    pub(super) created_on: String,
+}
+
+#[derive(sqlx::FromRow)]
+pub(super) struct ApiKeyRecord {
+    pub(super) id: i64,
+    pub(super) name: String,
+    pub(super) scopes: String,
+    pub(super) created_on: String,
+    pub(super) revoked_on: Option<String>,
+}
+
+impl From<ApiKeyRecord> for ApiKey {
+    fn from(record: ApiKeyRecord) -> Self {
+        let created_on = record
+            .created_on
+            .parse::<DateTime<Utc>>()
+            .unwrap_or(Utc::now());
+        // Unknown scopes grant nothing rather than failing the request.
+        let scopes = parse_scopes(&record.scopes).unwrap_or_default();
+        let key = ApiKey::new(record.id, record.name, scopes, created_on);
+        match record
+            .revoked_on
+            .and_then(|revoked_on| revoked_on.parse::<DateTime<Utc>>().ok())
+        {
+            Some(revoked_on) => key.with_revoked_on(revoked_on),
+            None => key,
+        }
+    }
+}
+
+#[derive(sqlx::FromRow)]
+pub(super) struct ShareRecord {
+    pub(super) id: i64,
+    pub(super) image_id: i64,
+    pub(super) created_on: String,
+    pub(super) expires_on: String,
+    pub(super) revoked_on: Option<String>,
+}
+
+impl From<ShareRecord> for Share {
+    fn from(record: ShareRecord) -> Self {
+        let created_on = record
+            .created_on
+            .parse::<DateTime<Utc>>()
+            .unwrap_or(Utc::now());
+        // An unreadable expiry makes the share expired rather than eternal.
+        let expires_on = record
+            .expires_on
+            .parse::<DateTime<Utc>>()
+            .unwrap_or(DateTime::<Utc>::MIN_UTC);
+        let share = Share::new(record.id, record.image_id, created_on, expires_on);
+        match record
+            .revoked_on
+            .and_then(|revoked_on| revoked_on.parse::<DateTime<Utc>>().ok())
+        {
+            Some(revoked_on) => share.with_revoked_on(revoked_on),
+            None => share,
+        }
+    }
+}
+
+#[derive(sqlx::FromRow)]
+pub(super) struct UserRecord {
+    pub(super) id: i64,
+    pub(super) name: String,
+    pub(super) created_on: String,
+}
+
+impl From<UserRecord> for User {
+    fn from(record: UserRecord) -> Self {
+        let created_on = record
+            .created_on
+            .parse::<DateTime<Utc>>()
+            .unwrap_or(Utc::now());
+        User::new(record.id, record.name, created_on)
+    }
+}
+
+impl From<AlbumRecord> for Album {
+    fn from(record: AlbumRecord) -> Self {
+        let created_on = record
+            .created_on
+            .parse::<DateTime<Utc>>()
+            .unwrap_or(Utc::now());
+        let mut album =
+            Album::new(record.id, record.name, created_on).with_image_count(record.image_count);
+        if let Some(parent_id) = record.parent_id {
+            album = album.with_parent_id(parent_id);
+        }
+        if let Some(cover_image_id) = record.cover_image_id {
+            album = album.with_cover_image_id(cover_image_id);
+        }
+        album
+    }
+}
+
+impl From<ImageRecord> for Image {
+    fn from(record: ImageRecord) -> Self {
+        let updated_on = record
+            .updated_on
+            .parse::<DateTime<Utc>>()
+            .unwrap_or(Utc::now());
+        let created_on = record
+            .created_on
+            .and_then(|created_on| created_on.parse::<DateTime<Utc>>().ok())
+            .unwrap_or(updated_on);
+        let mut image = Image::new(record.id, record.path, updated_on).with_created_on(created_on);
+        if let Some(hash) = record.hash {
+            image = image.with_hash(hash);
+        }
+        if let Some(perceptual_hash) = record.perceptual_hash {
+            image = image.with_perceptual_hash(perceptual_hash as u64);
+        }
+        // Images uploaded before metadata was recorded have none of these.
+        if let (
+            Some(width),
+            Some(height),
+            Some(color_type),
+            Some(bit_depth),
+            Some(original_format),
+            Some(original_size),
+            Some(stored_size),
+        ) = (
+            record.width,
+            record.height,
+            record.color_type,
+            record.bit_depth,
+            record.original_format,
+            record.original_size,
+            record.stored_size,
+        ) {
+            let mut metadata = ImageMetadata::new(
+                width as u32,
+                height as u32,
+                color_type,
+                bit_depth as u8,
+                original_format,
+                original_size as u64,
+                stored_size as u64,
+            );
+            if let Some(filename) = record.filename {
+                metadata = metadata.with_filename(filename);
+            }
+            image = image.with_metadata(metadata);
+        }
+        if let Some(original_path) = record.original_path {
+            image = image.with_original_path(original_path);
+        }
+        if let Some(deleted_at) = record
+            .deleted_at
+            .and_then(|deleted_at| deleted_at.parse::<DateTime<Utc>>().ok())
+        {
+            image = image.with_deleted_at(deleted_at);
+        }
+        if let Ok(status) = record.status.parse::<ImageStatus>() {
+            image = image.with_status(status);
+        }
+        if let Some(checksum) = record.checksum {
+            image = image.with_checksum(checksum);
+        }
+        if record.exif_id.is_some() {
+            let mut exif = ExifData::default()
+                .with_camera(record.make, record.model)
+                .with_exposure(
+                    record.exposure_time,
+                    record.f_number,
+                    record.iso.map(|iso| iso as u32),
+                    record.focal_length,
+                )
+                .with_location(record.latitude, record.longitude, record.altitude);
+            if let Some(captured_on) = record
+                .captured_on
+                .and_then(|captured_on| captured_on.parse::<DateTime<Utc>>().ok())
+            {
+                exif = exif.with_captured_on(captured_on);
+            }
+            if let Some(lens) = record.lens {
+                exif = exif.with_lens(lens);
+            }
+            if let Some(orientation) = record.orientation {
+                exif = exif.with_orientation(orientation as u16);
+            }
+            image = image.with_exif(exif);
+        }
+        image
+    }
+}
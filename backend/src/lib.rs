@@ -1,6 +1,7 @@
 pub mod configuration;
 pub mod data_storage;
 pub mod error;
+pub mod metrics;
 pub mod server;
 pub mod services;
 pub mod state;
@@ -0,0 +1,197 @@
+use std::time::Duration;
+
+use prometheus::{
+    Encoder, HistogramOpts, HistogramVec, IntCounter, IntCounterVec, IntGauge, IntGaugeVec, Opts,
+    Registry, TextEncoder,
+};
+
+use crate::services::images::{
+    domain::image_status::ImageStatus, ports::outgoing::metrics_port::MetricsPort,
+};
+
+/// Every metric the server exposes at `/metrics`, in its own registry.
+pub struct Metrics {
+    registry: Registry,
+    requests: IntCounterVec,
+    request_duration: HistogramVec,
+    requests_in_flight: IntGauge,
+    response_bytes: IntCounterVec,
+    uploads: IntCounterVec,
+    decode_failures: IntCounterVec,
+    stored_bytes: IntCounter,
+    images: IntGaugeVec,
+}
+
+impl Metrics {
+    pub fn new() -> Self {
+        let registry = Registry::new_custom(Some("yaiss".to_string()), None)
+            .expect("Error creating metrics registry");
+        let requests = IntCounterVec::new(
+            Opts::new(
+                "http_requests_total",
+                "Requests by route, method and status",
+            ),
+            &["route", "method", "status"],
+        )
+        .expect("Invalid metric");
+        let request_duration = HistogramVec::new(
+            HistogramOpts::new(
+                "http_request_duration_seconds",
+                "Time until the response headers, by route and method",
+            ),
+            &["route", "method"],
+        )
+        .expect("Invalid metric");
+        let requests_in_flight = IntGauge::new("http_requests_in_flight", "Requests being handled")
+            .expect("Invalid metric");
+        let response_bytes = IntCounterVec::new(
+            Opts::new(
+                "http_response_bytes_total",
+                "Response body bytes sent by route",
+            ),
+            &["route"],
+        )
+        .expect("Invalid metric");
+        let uploads = IntCounterVec::new(
+            Opts::new("uploads_total", "Decoded uploads, stored or already stored"),
+            &["result"],
+        )
+        .expect("Invalid metric");
+        let decode_failures = IntCounterVec::new(
+            Opts::new(
+                "decode_failures_total",
+                "Uploads that did not decode, by format",
+            ),
+            &["format"],
+        )
+        .expect("Invalid metric");
+        let stored_bytes = IntCounter::new(
+            "stored_bytes_total",
+            "Bytes written for new images, originals included",
+        )
+        .expect("Invalid metric");
+        let images = IntGaugeVec::new(
+            Opts::new("images", "Stored images by integrity status"),
+            &["status"],
+        )
+        .expect("Invalid metric");
+        registry.register(Box::new(requests.clone())).unwrap();
+        registry
+            .register(Box::new(request_duration.clone()))
+            .unwrap();
+        registry
+            .register(Box::new(requests_in_flight.clone()))
+            .unwrap();
+        registry.register(Box::new(response_bytes.clone())).unwrap();
+        registry.register(Box::new(uploads.clone())).unwrap();
+        registry
+            .register(Box::new(decode_failures.clone()))
+            .unwrap();
+        registry.register(Box::new(stored_bytes.clone())).unwrap();
+        registry.register(Box::new(images.clone())).unwrap();
+        Self {
+            registry,
+            requests,
+            request_duration,
+            requests_in_flight,
+            response_bytes,
+            uploads,
+            decode_failures,
+            stored_bytes,
+            images,
+        }
+    }
+
+    pub fn requests_in_flight(&self) -> &IntGauge {
+        &self.requests_in_flight
+    }
+
+    pub fn record_request(&self, route: &str, method: &str, status: u16, elapsed: Duration) {
+        self.requests
+            .with_label_values(&[route, method, &status.to_string()])
+            .inc();
+        self.request_duration
+            .with_label_values(&[route, method])
+            .observe(elapsed.as_secs_f64());
+    }
+
+    /// The counter the body of a response to `route` adds its bytes to as
+    /// they are sent.
+    pub fn response_bytes(&self, route: &str) -> IntCounter {
+        self.response_bytes.with_label_values(&[route])
+    }
+
+    /// Replaces the image counts; statuses without images are reported as 0.
+    pub fn set_image_counts(&self, counts: &[(ImageStatus, i64)]) {
+        for status in ImageStatus::ALL {
+            let count = counts
+                .iter()
+                .find(|(counted, _)| *counted == status)
+                .map_or(0, |(_, count)| *count);
+            self.images.with_label_values(&[status.as_str()]).set(count);
+        }
+    }
+
+    /// The Prometheus text exposition of every metric.
+    pub fn render(&self) -> String {
+        let mut buffer = vec![];
+        TextEncoder::new()
+            .encode(&self.registry.gather(), &mut buffer)
+            .expect("Error encoding metrics");
+        String::from_utf8(buffer).expect("Invalid metrics encoding")
+    }
+}
+
+impl Default for Metrics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MetricsPort for Metrics {
+    fn record_stored_image(&self, bytes: u64) {
+        self.uploads.with_label_values(&["stored"]).inc();
+        self.stored_bytes.inc_by(bytes);
+    }
+
+    fn record_duplicate_upload(&self) {
+        self.uploads.with_label_values(&["duplicate"]).inc();
+    }
+
+    fn record_decode_failure(&self, format: &str) {
+        self.decode_failures.with_label_values(&[format]).inc();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn render_recorded_metrics() {
+        let metrics = Metrics::new();
+        metrics.record_request("/api/v1/images/:id", "GET", 200, Duration::from_millis(5));
+        metrics.response_bytes("/api/v1/images/:id").inc_by(42);
+        metrics.record_stored_image(100);
+        metrics.record_stored_image(50);
+        metrics.record_duplicate_upload();
+        metrics.record_decode_failure("png");
+        metrics.set_image_counts(&[(ImageStatus::Ok, 3)]);
+
+        let text = metrics.render();
+        for line in [
+            r#"yaiss_http_requests_total{method="GET",route="/api/v1/images/:id",status="200"} 1"#,
+            r#"yaiss_http_request_duration_seconds_count{method="GET",route="/api/v1/images/:id"} 1"#,
+            r#"yaiss_http_response_bytes_total{route="/api/v1/images/:id"} 42"#,
+            "yaiss_http_requests_in_flight 0",
+            r#"yaiss_uploads_total{result="stored"} 2"#,
+            r#"yaiss_uploads_total{result="duplicate"} 1"#,
+            r#"yaiss_decode_failures_total{format="png"} 1"#,
+            "yaiss_stored_bytes_total 150",
+            r#"yaiss_images{status="ok"} 3"#,
+            r#"yaiss_images{status="missing"} 0"#,
+        ] {
+            assert!(text.contains(line), "missing {} in\n{}", line, text);
+        }
+    }
+}
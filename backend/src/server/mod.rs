@@ -3,34 +3,64 @@ use std::time::Duration;
 
 use axum::{
     http::{
-        header::{ACCEPT, ACCESS_CONTROL_ALLOW_ORIGIN, AUTHORIZATION, ORIGIN},
-        Method,
+        header::{ACCEPT, ACCESS_CONTROL_ALLOW_ORIGIN, AUTHORIZATION, HOST, ORIGIN},
+        HeaderMap, Method, StatusCode, Uri,
     },
+    middleware,
+    response::{IntoResponse, Redirect},
     routing::get,
     Router,
 };
-use axum_server::Handle;
+use axum_server::{tls_rustls::RustlsConfig, Handle};
+use tokio::task::JoinHandle;
 use tower_http::cors::{Any, CorsLayer};
-use tracing::{event, Level};
+use tracing::{error, event, info, warn, Level};
 
-use crate::configuration::Configuration;
+use crate::configuration::{Configuration, TlsSettings};
+use crate::services::images::{
+    image_scrubber::ImageScrubber,
+    image_trash::ImageTrash,
+    ports::incoming::{
+        consistency_service::ConsistencyService, scrub_service::ScrubService,
+        trash_service::TrashService,
+    },
+};
 use crate::state::State;
 use crate::web;
 
+/// How often images past their trash retention are looked for.
+const PURGE_INTERVAL: Duration = Duration::from_secs(60 * 60);
+/// The scrubber reads its configured number of files once per interval.
+const SCRUB_INTERVAL: Duration = Duration::from_secs(60);
+
 pub struct Server {
     handle: Option<Handle>,
     address: SocketAddr,
     router: Router<()>,
+    state: State,
+    tasks: Vec<JoinHandle<()>>,
+    checked_on_startup: bool,
+    tls: Option<TlsSettings>,
+    /// Shared with the running listener, so a reload swaps the certificate
+    /// for new handshakes and leaves open connections alone.
+    rustls_config: Option<RustlsConfig>,
+    redirect_handle: Option<Handle>,
 }
 
 impl Server {
     pub fn new(state: State, configuration: &Configuration) -> Self {
-        let router = Self::create_router(state);
+        let router = Self::create_router(state.clone());
         let sock_address = SocketAddr::from(configuration.address());
         Self {
             handle: Some(Handle::new()),
             address: sock_address,
             router,
+            state,
+            tasks: vec![],
+            checked_on_startup: false,
+            tls: configuration.tls(),
+            rustls_config: None,
+            redirect_handle: None,
         }
     }
 
@@ -43,29 +73,82 @@ impl Server {
                 handle
             }
         };
-        let server = axum_server::bind(self.address)
-            .handle(handle)
-            .serve(self.router.clone().into_make_service());
-        tokio::spawn(async {
-            event!(Level::INFO, "Starting server");
-            server.await.unwrap();
-        });
+        let service = self.router.clone().into_make_service();
+        match self.tls.clone() {
+            None => {
+                let server = axum_server::bind(self.address)
+                    .handle(handle)
+                    .serve(service);
+                tokio::spawn(async {
+                    event!(Level::INFO, "Starting server");
+                    server.await.unwrap();
+                });
+            }
+            Some(tls) => {
+                let config = tokio::task::block_in_place(|| {
+                    tokio::runtime::Handle::current()
+                        .block_on(RustlsConfig::from_pem_file(&tls.cert_path, &tls.key_path))
+                })
+                .expect("Error loading TLS certificate");
+                self.rustls_config = Some(config.clone());
+                let server = axum_server::bind_rustls(self.address, config)
+                    .handle(handle)
+                    .serve(service);
+                tokio::spawn(async {
+                    event!(Level::INFO, "Starting server with TLS");
+                    server.await.unwrap();
+                });
+                if let Some(port) = tls.redirect_port {
+                    self.spawn_redirect(port);
+                }
+            }
+        }
+        self.start_tasks();
     }
 
-    pub async fn reload(&mut self, state: State, configuration: Configuration) {
+    pub async fn reload(&mut self, state: State, configuration: &Configuration) {
         let sock_address = SocketAddr::from(configuration.address());
-        if self.address == sock_address {
+        let tls = configuration.tls();
+        if self.address == sock_address && self.tls == tls {
+            // Only the background task settings are applied; the router keeps
+            // its state, so the tasks stay on the same storage as the handlers.
+            self.stop_tasks();
+            self.state = self.state.clone().with_job_settings(&state);
+            self.start_tasks();
+            self.reload_certificate().await;
             return;
         }
         self.stop().await;
 
         self.address = sock_address;
-        self.router = Self::create_router(state);
+        self.tls = tls;
+        self.router = Self::create_router(state.clone());
+        self.state = state;
 
         self.serve()
     }
 
+    /// Reads the certificate and key again; the one in use is kept when
+    /// they do not load.
+    pub async fn reload_certificate(&self) {
+        let (Some(tls), Some(config)) = (&self.tls, &self.rustls_config) else {
+            return;
+        };
+        match config
+            .reload_from_pem_file(&tls.cert_path, &tls.key_path)
+            .await
+        {
+            Ok(()) => info!("Reloaded TLS certificate"),
+            Err(e) => error!("Error reloading TLS certificate: {}", e),
+        }
+    }
+
     pub async fn stop(&mut self) {
+        self.stop_tasks();
+        self.rustls_config = None;
+        if let Some(handle) = self.redirect_handle.take() {
+            handle.shutdown();
+        }
         if self.handle.is_none() {
             return;
         }
@@ -79,16 +162,144 @@ impl Server {
         event!(Level::INFO, "Stopping server");
     }
 
+    /// Answers plain HTTP on `port` with a redirect to the same path over
+    /// HTTPS.
+    fn spawn_redirect(&mut self, port: u16) {
+        let https_port = self.address.port();
+        let handle = Handle::new();
+        self.redirect_handle = Some(handle.clone());
+        let router = Router::new().fallback(move |headers: HeaderMap, uri: Uri| async move {
+            match https_uri(&headers, &uri, https_port) {
+                Some(location) => Redirect::permanent(&location).into_response(),
+                None => StatusCode::BAD_REQUEST.into_response(),
+            }
+        });
+        let server = axum_server::bind(SocketAddr::new(self.address.ip(), port))
+            .handle(handle)
+            .serve(router.into_make_service());
+        tokio::spawn(async {
+            event!(Level::INFO, "Starting HTTP redirect");
+            if let Err(e) = server.await {
+                error!("Error serving the HTTP redirect: {}", e);
+            }
+        });
+    }
+
+    fn start_tasks(&mut self) {
+        if !self.tasks.is_empty() {
+            return;
+        }
+        self.tasks.push(self.spawn_purge());
+        if let Some(task) = self.spawn_consistency_check() {
+            self.tasks.push(task);
+        }
+        if let Some(task) = self.spawn_scrubber() {
+            self.tasks.push(task);
+        }
+    }
+
+    fn stop_tasks(&mut self) {
+        for task in self.tasks.drain(..) {
+            task.abort();
+        }
+    }
+
+    /// Periodically deletes the images kept in the trash past the
+    /// configured retention, along with their files.
+    fn spawn_purge(&self) -> JoinHandle<()> {
+        let retention = self.state.trash_retention();
+        let trash = ImageTrash::new(self.state.storage(), self.state.blob_store());
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(PURGE_INTERVAL);
+            loop {
+                interval.tick().await;
+                if let Err(e) = trash.purge(retention).await {
+                    error!("Error purging the trash: {}", e);
+                }
+            }
+        })
+    }
+
+    /// Re-reads the stored files a few at a time, oldest checked first, and
+    /// records the ones that went missing or rotted.
+    fn spawn_scrubber(&self) -> Option<JoinHandle<()>> {
+        let rate = self.state.scrub_rate();
+        if rate <= 0 {
+            return None;
+        }
+        let scrubber = ImageScrubber::new(self.state.storage(), self.state.blob_store());
+        Some(tokio::spawn(async move {
+            let mut interval = tokio::time::interval_at(
+                tokio::time::Instant::now() + SCRUB_INTERVAL,
+                SCRUB_INTERVAL,
+            );
+            loop {
+                interval.tick().await;
+                match scrubber.scrub(rate).await {
+                    Ok(summary) if summary.missing + summary.corrupted > 0 => warn!(
+                        "Scrubbed {} files: {} missing, {} corrupted",
+                        summary.checked, summary.missing, summary.corrupted
+                    ),
+                    Ok(_) => {}
+                    Err(e) => error!("Error scrubbing stored files: {}", e),
+                }
+            }
+        }))
+    }
+
+    /// Logs the consistency report as JSON at startup and on the configured
+    /// interval, whichever are enabled.
+    fn spawn_consistency_check(&mut self) -> Option<JoinHandle<()>> {
+        let schedule = self.state.consistency_schedule().clone();
+        let on_startup = schedule.on_startup && !self.checked_on_startup;
+        self.checked_on_startup = true;
+        if !on_startup && schedule.interval.is_none() {
+            return None;
+        }
+        let consistency = web::admin::consistency_service(&self.state);
+        Some(tokio::spawn(async move {
+            let check = || async {
+                match consistency.check(schedule.repair).await {
+                    Ok(report) => {
+                        let consistent = report.is_consistent();
+                        let report = web::admin::ConsistencyReportJson::from(report);
+                        let report = serde_json::to_string(&report).unwrap_or_default();
+                        if consistent {
+                            info!("Consistency report: {}", report);
+                        } else {
+                            warn!("Consistency report: {}", report);
+                        }
+                    }
+                    Err(e) => error!("Error checking consistency: {}", e),
+                }
+            };
+            if on_startup {
+                check().await;
+            }
+            if let Some(period) = schedule.interval {
+                let mut interval =
+                    tokio::time::interval_at(tokio::time::Instant::now() + period, period);
+                loop {
+                    interval.tick().await;
+                    check().await;
+                }
+            }
+        }))
+    }
+
     fn create_router(state: State) -> Router {
         let cors = CorsLayer::new()
             .allow_origin(Any)
             .allow_methods([Method::GET])
             .allow_headers([AUTHORIZATION, ORIGIN, ACCEPT, ACCESS_CONTROL_ALLOW_ORIGIN]);
+        let metrics = state.metrics();
         Router::new()
             .route("/", get(hello_world))
-            .merge(web::images::router(state))
+            .merge(web::metrics::router(state.clone()))
+            .merge(web::router(state))
             .layer(cors)
             .fallback(web::handler_404)
+            .layer(middleware::from_fn_with_state(metrics, web::metrics::track))
     }
 }
 
@@ -96,14 +307,48 @@ async fn hello_world() -> &'static str {
     "Hello world!"
 }
 
+/// Where a plain HTTP request is sent: the host it asked for, without its
+/// port, on the HTTPS port.
+fn https_uri(headers: &HeaderMap, uri: &Uri, https_port: u16) -> Option<String> {
+    let host = headers.get(HOST)?.to_str().ok()?;
+    let host = host.parse::<axum::http::uri::Authority>().ok()?;
+    let path = uri.path_and_query().map_or("/", |path| path.as_str());
+    Some(match https_port {
+        443 => format!("https://{}{}", host.host(), path),
+        port => format!("https://{}:{}{}", host.host(), port, path),
+    })
+}
+
 #[cfg(test)]
 mod tests {
+    use std::sync::Arc;
+
     use super::*;
+    use ini::Ini;
     use tokio::time::sleep;
 
+    fn configuration() -> Configuration {
+        Configuration::from_ini(ini())
+    }
+
+    fn ini() -> Ini {
+        let mut ini = Ini::new();
+        ini.with_section(Some("SERVER"))
+            .set("address", "0.0.0.0")
+            .set("port", "3000");
+        ini.with_section(Some("DATABASE")).set("url", "memory://");
+        ini.with_section(Some("IMAGE_SERVICE"))
+            .set("base_path", "data");
+        ini.with_section(Some("BLOB_STORE"))
+            .set("backend", "memory");
+        ini.with_section(Some("AUTH"))
+            .set("bootstrap_key", "bootstrap");
+        ini
+    }
+
     #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
     async fn start_and_stop() {
-        let configuration = Configuration::new();
+        let configuration = configuration();
         let state = State::new(&configuration);
         let mut sh = Server::new(state, &configuration);
         sh.serve();
@@ -115,6 +360,24 @@ mod tests {
             .await
             .expect("failed to read payload");
         assert_eq!(response, "Hello world!");
+        reqwest::get("http://0.0.0.0:3000/api/v1/shared/1")
+            .await
+            .expect("failed to perform GET /api/v1/shared/1");
+        let response = reqwest::get("http://0.0.0.0:3000/metrics")
+            .await
+            .expect("failed to perform GET /metrics");
+        assert_eq!(response.status(), reqwest::StatusCode::UNAUTHORIZED);
+        let metrics = reqwest::Client::new()
+            .get("http://0.0.0.0:3000/metrics")
+            .bearer_auth("bootstrap")
+            .send()
+            .await
+            .expect("failed to perform GET /metrics")
+            .text()
+            .await
+            .expect("failed to read payload");
+        assert!(metrics.contains(r#"route="/",status="200"} 1"#));
+        assert!(metrics.contains(r#"route="/api/v1/shared/:id","#));
         sh.stop().await;
 
         let response = reqwest::get("http://0.0.0.0:3000/")
@@ -122,4 +385,101 @@ mod tests {
             .expect_err("expected error");
         assert!(response.is_request());
     }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+    async fn serve_tls_and_redirect() {
+        let dir = std::env::temp_dir().join("yaiss_server_tls");
+        std::fs::create_dir_all(&dir).unwrap();
+        let certificate = rcgen::generate_simple_self_signed(vec!["localhost".to_string()])
+            .expect("failed to generate certificate");
+        let cert_path = dir.join("localhost.crt");
+        let key_path = dir.join("localhost.key");
+        std::fs::write(&cert_path, certificate.serialize_pem().unwrap()).unwrap();
+        std::fs::write(&key_path, certificate.serialize_private_key_pem()).unwrap();
+        let mut ini = ini();
+        ini.with_section(Some("SERVER"))
+            .set("address", "127.0.0.1")
+            .set("port", "3443")
+            .set("tls_cert_path", cert_path.to_str().unwrap())
+            .set("tls_key_path", key_path.to_str().unwrap())
+            .set("http_redirect_port", "3080");
+        let configuration = Configuration::from_ini(ini);
+        let state = State::new(&configuration);
+        let mut sh = Server::new(state, &configuration);
+        sh.serve();
+        sleep(Duration::from_millis(500)).await;
+        let client = reqwest::Client::builder()
+            .danger_accept_invalid_certs(true)
+            .redirect(reqwest::redirect::Policy::none())
+            .build()
+            .unwrap();
+        let response = client
+            .get("https://localhost:3443/")
+            .send()
+            .await
+            .expect("failed to perform GET /")
+            .text()
+            .await
+            .expect("failed to read payload");
+        assert_eq!(response, "Hello world!");
+        sh.reload_certificate().await;
+        let response = client
+            .get("https://localhost:3443/")
+            .send()
+            .await
+            .expect("failed to perform GET / after reload");
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let response = client
+            .get("http://localhost:3080/api/v1/images?page=2")
+            .send()
+            .await
+            .expect("failed to perform GET on the redirect port");
+        assert_eq!(response.status(), StatusCode::PERMANENT_REDIRECT);
+        assert_eq!(
+            response.headers()["location"],
+            "https://localhost:3443/api/v1/images?page=2"
+        );
+        sh.stop().await;
+
+        client
+            .get("http://localhost:3080/")
+            .send()
+            .await
+            .expect_err("expected error");
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+    async fn reload_on_same_address_keeps_storage() {
+        let configuration = configuration();
+        let state = State::new(&configuration);
+        let storage = state.storage();
+        let mut sh = Server::new(state, &configuration);
+
+        let mut ini = ini();
+        ini.with_section(Some("SCRUBBER"))
+            .set("files_per_minute", "0");
+        let configuration = Configuration::from_ini(ini);
+        sh.reload(State::new(&configuration), &configuration).await;
+        assert!(Arc::ptr_eq(&sh.state.storage(), &storage));
+        assert_eq!(sh.state.scrub_rate(), 0);
+        sh.stop().await;
+    }
+
+    #[test]
+    fn https_uri_drops_the_request_port() {
+        let mut headers = HeaderMap::new();
+        headers.insert(HOST, "example.com:8080".parse().unwrap());
+        let uri = "/images/1?size=small".parse::<Uri>().unwrap();
+        assert_eq!(
+            https_uri(&headers, &uri, 443).as_deref(),
+            Some("https://example.com/images/1?size=small")
+        );
+        assert_eq!(
+            https_uri(&headers, &uri, 8443).as_deref(),
+            Some("https://example.com:8443/images/1?size=small")
+        );
+        assert_eq!(https_uri(&HeaderMap::new(), &uri, 443), None);
+    }
 }
@@ -0,0 +1,244 @@
+use async_trait::async_trait;
+use chrono::Utc;
+
+use super::{
+    domain::api_key::{generate_secret, hash_secret, ApiKey, Scope, MAX_API_KEY_NAME_LENGTH},
+    ports::{
+        incoming::api_keys_service::{ApiKeysService, ApiKeysServiceError},
+        outgoing::api_keys_port::{ApiKeysError, ApiKeysPort},
+    },
+};
+
+impl From<ApiKeysError> for ApiKeysServiceError {
+    fn from(value: ApiKeysError) -> Self {
+        match value {
+            ApiKeysError::KeyNotFound => ApiKeysServiceError::KeyNotFound,
+            ApiKeysError::AlreadyExists | ApiKeysError::InternalError => {
+                ApiKeysServiceError::InternalError
+            }
+        }
+    }
+}
+
+pub struct ApiKeys<Storage>
+where
+    Storage: ApiKeysPort + Send + Sync,
+{
+    storage: Storage,
+    bootstrap_hash: Option<String>,
+}
+
+#[async_trait]
+impl<Storage> ApiKeysService for ApiKeys<Storage>
+where
+    Storage: ApiKeysPort + Send + Sync,
+{
+    async fn create_key(
+        &self,
+        name: String,
+        mut scopes: Vec<Scope>,
+    ) -> Result<(ApiKey, String), ApiKeysServiceError> {
+        let name = name.trim();
+        if name.is_empty() || name.chars().count() > MAX_API_KEY_NAME_LENGTH {
+            return Err(ApiKeysServiceError::InvalidRequest(format!(
+                "Invalid API key name: {:?}",
+                name
+            )));
+        }
+        scopes.sort_unstable();
+        scopes.dedup();
+        if scopes.is_empty() {
+            return Err(ApiKeysServiceError::InvalidRequest(
+                "No scopes given".to_string(),
+            ));
+        }
+        let secret = generate_secret();
+        let key = self
+            .storage
+            .insert_api_key(name, &hash_secret(&secret), &scopes, Utc::now())
+            .await?;
+        Ok((key, secret))
+    }
+
+    async fn keys(&self) -> Result<Vec<ApiKey>, ApiKeysServiceError> {
+        self.storage
+            .query_api_keys()
+            .await
+            .map_err(|err| err.into())
+    }
+
+    async fn revoke_key(&self, id: i64) -> Result<(), ApiKeysServiceError> {
+        self.storage
+            .revoke_api_key(id, Utc::now())
+            .await
+            .map_err(|err| err.into())
+    }
+
+    async fn authenticate(&self, secret: &str) -> Result<ApiKey, ApiKeysServiceError> {
+        let hash = hash_secret(secret);
+        if self.bootstrap_hash.as_ref() == Some(&hash) {
+            return Ok(ApiKey::new(
+                0,
+                "bootstrap".to_string(),
+                vec![Scope::Admin],
+                Utc::now(),
+            ));
+        }
+        match self.storage.query_api_key_by_hash(&hash).await {
+            Ok(key) if key.revoked_on().is_none() => Ok(key),
+            Ok(_) | Err(ApiKeysError::KeyNotFound) => Err(ApiKeysServiceError::InvalidKey),
+            Err(e) => Err(e.into()),
+        }
+    }
+}
+
+impl<Storage> ApiKeys<Storage>
+where
+    Storage: ApiKeysPort + Send + Sync,
+{
+    pub fn new(storage: Storage) -> Self {
+        Self {
+            storage,
+            bootstrap_hash: None,
+        }
+    }
+
+    /// Accepts `secret` as an admin key without storing it, so the first
+    /// keys can be created.
+    pub fn with_bootstrap_key(mut self, secret: &str) -> Self {
+        self.bootstrap_hash = Some(hash_secret(secret));
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use async_trait::async_trait;
+    use chrono::{DateTime, Utc};
+    use mockall::{mock, predicate};
+
+    use crate::services::images::{
+        api_keys::ApiKeys,
+        domain::api_key::{hash_secret, ApiKey, Scope},
+        ports::{
+            incoming::api_keys_service::{ApiKeysService, ApiKeysServiceError},
+            outgoing::api_keys_port::{ApiKeysError, ApiKeysPort},
+        },
+    };
+
+    mock! {
+        DS {}
+        #[async_trait]
+        impl ApiKeysPort for DS {
+            async fn insert_api_key(
+                &self,
+                name: &str,
+                key_hash: &str,
+                scopes: &[Scope],
+                created_on: DateTime<Utc>,
+            ) -> Result<ApiKey, ApiKeysError>;
+            async fn query_api_key_by_hash(&self, key_hash: &str) -> Result<ApiKey, ApiKeysError>;
+            async fn query_api_keys(&self) -> Result<Vec<ApiKey>, ApiKeysError>;
+            async fn revoke_api_key(&self, id: i64, revoked_on: DateTime<Utc>)
+                -> Result<(), ApiKeysError>;
+        }
+    }
+
+    #[tokio::test]
+    async fn test_create_key_stores_only_the_hash() {
+        let mut mock = MockDS::new();
+        mock.expect_insert_api_key()
+            .withf(|name, hash, scopes, _| {
+                name == "uploader"
+                    && hash.len() == 64
+                    && !hash.starts_with("yaiss_")
+                    && scopes == [Scope::Read, Scope::Upload]
+            })
+            .returning(|name, _, scopes, created_on| {
+                Ok(ApiKey::new(
+                    1,
+                    name.to_string(),
+                    scopes.to_vec(),
+                    created_on,
+                ))
+            });
+        let service = ApiKeys::new(mock);
+        let (key, secret) = service
+            .create_key(
+                " uploader ".to_string(),
+                vec![Scope::Upload, Scope::Read, Scope::Upload],
+            )
+            .await
+            .unwrap();
+        assert_eq!(key.id(), 1);
+        assert!(secret.starts_with("yaiss_"));
+    }
+
+    #[tokio::test]
+    async fn test_create_key_rejects_invalid_requests() {
+        let service = ApiKeys::new(MockDS::new());
+        let result = service
+            .create_key("  ".to_string(), vec![Scope::Read])
+            .await;
+        assert!(matches!(
+            result,
+            Err(ApiKeysServiceError::InvalidRequest(_))
+        ));
+        let result = service.create_key("reader".to_string(), vec![]).await;
+        assert!(matches!(
+            result,
+            Err(ApiKeysServiceError::InvalidRequest(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_authenticate() {
+        let mut mock = MockDS::new();
+        mock.expect_query_api_key_by_hash()
+            .with(predicate::eq(hash_secret("active")))
+            .returning(|_| {
+                Ok(ApiKey::new(
+                    1,
+                    "a".to_string(),
+                    vec![Scope::Read],
+                    Utc::now(),
+                ))
+            });
+        mock.expect_query_api_key_by_hash()
+            .with(predicate::eq(hash_secret("revoked")))
+            .returning(|_| {
+                Ok(
+                    ApiKey::new(2, "r".to_string(), vec![Scope::Read], Utc::now())
+                        .with_revoked_on(Utc::now()),
+                )
+            });
+        mock.expect_query_api_key_by_hash()
+            .with(predicate::eq(hash_secret("unknown")))
+            .returning(|_| Err(ApiKeysError::KeyNotFound));
+        let service = ApiKeys::new(mock).with_bootstrap_key("bootstrap");
+        assert_eq!(service.authenticate("active").await.unwrap().id(), 1);
+        assert_eq!(
+            service.authenticate("revoked").await,
+            Err(ApiKeysServiceError::InvalidKey)
+        );
+        assert_eq!(
+            service.authenticate("unknown").await,
+            Err(ApiKeysServiceError::InvalidKey)
+        );
+        let bootstrap = service.authenticate("bootstrap").await.unwrap();
+        assert!(bootstrap.allows(Scope::Delete));
+    }
+
+    #[tokio::test]
+    async fn test_revoke_unknown_key() {
+        let mut mock = MockDS::new();
+        mock.expect_revoke_api_key()
+            .with(predicate::eq(7), predicate::always())
+            .returning(|_, _| Err(ApiKeysError::KeyNotFound));
+        let service = ApiKeys::new(mock);
+        assert_eq!(
+            service.revoke_key(7).await,
+            Err(ApiKeysServiceError::KeyNotFound)
+        );
+    }
+}
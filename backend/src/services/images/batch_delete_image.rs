@@ -1,28 +1,34 @@
 use async_trait::async_trait;
+use chrono::Utc;
 use tracing::error;
 
 use super::ports::{
     incoming::batch_delete_image_service::{BatchDeleteImageService, BatchDeleteImageServiceError},
-    outgoing::batch_delete_image_port::BatchDeleteImagePort,
+    outgoing::{
+        batch_delete_image_port::BatchDeleteImagePort, blob_store_port::DynBlobStore,
+        trash_port::TrashPort,
+    },
 };
 
 const MAX_IMAGES: usize = 50;
 
 pub struct BatchDeleteImage<Storage>
 where
-    Storage: BatchDeleteImagePort + Send + Sync,
+    Storage: BatchDeleteImagePort + TrashPort + Send + Sync,
 {
     storage: Storage,
+    blob_store: DynBlobStore,
 }
 
 #[async_trait]
 impl<Storage> BatchDeleteImageService for BatchDeleteImage<Storage>
 where
-    Storage: BatchDeleteImagePort + Send + Sync,
+    Storage: BatchDeleteImagePort + TrashPort + Send + Sync,
 {
     async fn batch_delete_image(
         &self,
         indexes: Vec<i64>,
+        permanent: bool,
     ) -> Result<(), BatchDeleteImageServiceError> {
         let len = indexes.len();
         let max = 50;
@@ -34,6 +40,11 @@ where
             Err(BatchDeleteImageServiceError::TooManyImagesToDelete(
                 max as u64,
             ))
+        } else if !permanent {
+            match self.storage.trash_images(&indexes, Utc::now()).await {
+                Ok(_) => Ok(()),
+                Err(_) => Err(BatchDeleteImageServiceError::InternalError),
+            }
         } else {
             let paths = match self.storage.batch_delete_image(indexes).await {
                 Ok(paths) => paths,
@@ -42,9 +53,8 @@ where
 
             let mut err: Option<BatchDeleteImageServiceError> = None;
             for path in paths {
-                let result = std::fs::remove_file(&path);
-                if result.is_err() {
-                    error!("Error removing file {}", path);
+                if let Err(e) = self.blob_store.delete(&path).await {
+                    error!("Error removing file {}: {}", path, e);
                     err = Some(BatchDeleteImageServiceError::InternalError);
                 }
             }
@@ -58,27 +68,39 @@ where
 
 impl<Storage> BatchDeleteImage<Storage>
 where
-    Storage: BatchDeleteImagePort + Send + Sync,
+    Storage: BatchDeleteImagePort + TrashPort + Send + Sync,
 {
-    pub fn new(storage: Storage) -> Self {
-        Self { storage }
+    pub fn new(storage: Storage, blob_store: DynBlobStore) -> Self {
+        Self {
+            storage,
+            blob_store,
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use std::env;
+    use std::{env, sync::Arc, time::SystemTime};
 
     use async_trait::async_trait;
+    use chrono::{DateTime, Utc};
     use mockall::mock;
 
-    use crate::services::images::{
-        batch_delete_image::BatchDeleteImage,
-        ports::{
-            incoming::batch_delete_image_service::{
-                BatchDeleteImageService, BatchDeleteImageServiceError,
+    use crate::{
+        data_storage::blobs::{fs_blob_store::FsBlobStore, memory_blob_store::MemoryBlobStore},
+        services::images::{
+            batch_delete_image::BatchDeleteImage,
+            domain::image::Image,
+            ports::{
+                incoming::batch_delete_image_service::{
+                    BatchDeleteImageService, BatchDeleteImageServiceError,
+                },
+                outgoing::{
+                    batch_delete_image_port::{BatchDeleteError, BatchDeleteImagePort},
+                    delete_image_port::RemoveFiles,
+                    trash_port::{TrashError, TrashPort},
+                },
             },
-            outgoing::batch_delete_image_port::{BatchDeleteError, BatchDeleteImagePort},
         },
     };
 
@@ -88,37 +110,40 @@ mod tests {
         impl BatchDeleteImagePort for DS {
             async fn batch_delete_image(&self, index: Vec<i64>) -> Result<Vec<String>, BatchDeleteError>;
         }
+        #[async_trait]
+        impl TrashPort for DS {
+            async fn trash_images(&self, indexes: &[i64], deleted_at: DateTime<Utc>) -> Result<u64, TrashError>;
+            async fn restore_image(&self, index: i64) -> Result<(), TrashError>;
+            async fn query_trash(&self, count: i64, offset: i64) -> Result<Vec<Image>, TrashError>;
+            async fn purge_trash(&self, before: DateTime<Utc>) -> Result<Vec<String>, TrashError>;
+            async fn purge_trash_with(&self, before: DateTime<Utc>, remove: RemoveFiles) -> Result<Vec<String>, TrashError>;
+        }
     }
 
     #[tokio::test]
     async fn test_batch_delete_image() {
-        let path = env::current_dir().unwrap();
-        std::fs::write(path.join("1"), "some content").unwrap();
-        std::fs::write(path.join("2"), "some content").unwrap();
+        let blob_store = Arc::new(MemoryBlobStore::new());
+        blob_store.insert("data/1.qoi", "some content", SystemTime::now());
+        blob_store.insert("data/2.qoi", "some content", SystemTime::now());
         let mut mock = MockDS::new();
         mock.expect_batch_delete_image().returning(move |_i| {
-            anyhow::Result::Ok(vec![
-                path.join("1").to_str().unwrap().to_string(),
-                path.join("2").to_str().unwrap().to_string(),
-            ])
+            anyhow::Result::Ok(vec!["data/1.qoi".to_string(), "data/2.qoi".to_string()])
         });
-        let suu = BatchDeleteImage::new(mock);
-        let result = suu.batch_delete_image(vec![1, 2]).await;
+        let suu = BatchDeleteImage::new(mock, blob_store.clone());
+        let result = suu.batch_delete_image(vec![1, 2], true).await;
         assert!(result.is_ok());
+        assert!(blob_store.keys().is_empty());
     }
 
     #[tokio::test]
     async fn test_batch_delete_image_fs_error() {
-        let path = env::current_dir().unwrap();
+        // A directory cannot be removed like a file.
+        let path = env::temp_dir();
         let mut mock = MockDS::new();
-        mock.expect_batch_delete_image().returning(move |_i| {
-            anyhow::Result::Ok(vec![
-                path.join("1").to_str().unwrap().to_string(),
-                path.join("2").to_str().unwrap().to_string(),
-            ])
-        });
-        let suu = BatchDeleteImage::new(mock);
-        let result = suu.batch_delete_image(vec![1, 2]).await;
+        mock.expect_batch_delete_image()
+            .returning(move |_i| anyhow::Result::Ok(vec![path.display().to_string()]));
+        let suu = BatchDeleteImage::new(mock, Arc::new(FsBlobStore::new()));
+        let result = suu.batch_delete_image(vec![1, 2], true).await;
         assert!(result.is_err());
         assert_eq!(result, Err(BatchDeleteImageServiceError::InternalError));
     }
@@ -128,8 +153,8 @@ mod tests {
         let mut mock = MockDS::new();
         mock.expect_batch_delete_image()
             .returning(move |_i| Err(BatchDeleteError::InternalError));
-        let suu = BatchDeleteImage::new(mock);
-        let result = suu.batch_delete_image(vec![1, 2]).await;
+        let suu = BatchDeleteImage::new(mock, Arc::new(MemoryBlobStore::new()));
+        let result = suu.batch_delete_image(vec![1, 2], true).await;
         assert!(result.is_err());
         assert_eq!(result, Err(BatchDeleteImageServiceError::InternalError));
     }
@@ -138,12 +163,24 @@ mod tests {
     async fn test_batch_delete_image_more_than_fifty_error() {
         let mock = MockDS::new();
         let indexes = vec![0; 51];
-        let suu = BatchDeleteImage::new(mock);
-        let result = suu.batch_delete_image(indexes).await;
+        let suu = BatchDeleteImage::new(mock, Arc::new(MemoryBlobStore::new()));
+        let result = suu.batch_delete_image(indexes, false).await;
         assert!(result.is_err());
         assert_eq!(
             result,
             Err(BatchDeleteImageServiceError::TooManyImagesToDelete(50))
         );
     }
+
+    #[tokio::test]
+    async fn test_batch_delete_image_moves_to_trash() {
+        let mut mock = MockDS::new();
+        mock.expect_trash_images()
+            .withf(|indexes, _d| indexes == [1, 2])
+            .returning(|_i, _d| Ok(2));
+        mock.expect_batch_delete_image().never();
+        let suu = BatchDeleteImage::new(mock, Arc::new(MemoryBlobStore::new()));
+        let result = suu.batch_delete_image(vec![1, 2], false).await;
+        assert_eq!(result, Ok(()));
+    }
 }
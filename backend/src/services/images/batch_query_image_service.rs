@@ -2,7 +2,12 @@ use async_trait::async_trait;
 use tracing::error;
 
 use super::{
-    domain::image::Image,
+    domain::{
+        cursor::{Cursor, ImagePage},
+        image::Image,
+        sort_key::SortKey,
+        tag::{normalize_tag, TagMatch},
+    },
     ports::{
         incoming::batch_query_image_service::{
             BatchQueryImageService, BatchQueryImageServiceError,
@@ -38,17 +43,55 @@ where
         &self,
         count: i64,
         offset: i64,
+        sort: SortKey,
     ) -> Result<Vec<Image>, BatchQueryImageServiceError> {
-        if count <= 0 || offset < 0 {
-            return Err(BatchQueryImageServiceError::InvalidRequest);
-        }
+        Self::validate(count, offset)?;
+        self.storage
+            .query_images(count, offset, sort)
+            .await
+            .map_err(|err| err.into())
+    }
 
-        if count as usize > MAX_IMAGES {
-            error!("Error too many images request amount {}", count);
-            return Err(BatchQueryImageServiceError::TooManyImagesRequested);
+    async fn batch_query_image_page(
+        &self,
+        cursor: Option<Cursor>,
+        count: i64,
+    ) -> Result<ImagePage, BatchQueryImageServiceError> {
+        Self::validate(count, 0)?;
+        // One extra row tells whether another page follows.
+        let mut images = self.storage.query_images_after(cursor, count + 1).await?;
+        let next_cursor = if images.len() as i64 > count {
+            images.truncate(count as usize);
+            images.last().map(Cursor::from)
+        } else {
+            None
+        };
+        Ok(ImagePage::new(images, next_cursor))
+    }
+
+    async fn batch_query_image_by_tags(
+        &self,
+        tags: Vec<String>,
+        tag_match: TagMatch,
+        count: i64,
+        offset: i64,
+        sort: SortKey,
+    ) -> Result<Vec<Image>, BatchQueryImageServiceError> {
+        Self::validate(count, offset)?;
+        let mut tags = tags
+            .iter()
+            .map(|tag| normalize_tag(tag))
+            .collect::<Result<Vec<String>, String>>()
+            .map_err(BatchQueryImageServiceError::InvalidTag)?;
+        if tags.is_empty() {
+            return Err(BatchQueryImageServiceError::InvalidTag(
+                "No tags given".to_string(),
+            ));
         }
+        tags.sort();
+        tags.dedup();
         self.storage
-            .query_images(count, offset)
+            .query_images_by_tags(&tags, tag_match, count, offset, sort)
             .await
             .map_err(|err| err.into())
     }
@@ -61,6 +104,18 @@ where
     pub fn new(storage: Storage) -> Self {
         Self { storage }
     }
+
+    fn validate(count: i64, offset: i64) -> Result<(), BatchQueryImageServiceError> {
+        if count <= 0 || offset < 0 {
+            return Err(BatchQueryImageServiceError::InvalidRequest);
+        }
+
+        if count as usize > MAX_IMAGES {
+            error!("Error too many images request amount {}", count);
+            return Err(BatchQueryImageServiceError::TooManyImagesRequested);
+        }
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -70,7 +125,12 @@ mod tests {
 
     use crate::services::images::{
         batch_query_image_service::BatchQueryImage,
-        domain::image::Image,
+        domain::{
+            cursor::{Cursor, ImagePage},
+            image::Image,
+            sort_key::SortKey,
+            tag::TagMatch,
+        },
         ports::{
             incoming::batch_query_image_service::{
                 BatchQueryImageService, BatchQueryImageServiceError,
@@ -83,7 +143,9 @@ mod tests {
         DS {}
         #[async_trait]
         impl BatchQueryImagesPort for DS {
-            async fn query_images(&self, count: i64, offset: i64) -> Result<Vec<Image>, QueryError>;
+            async fn query_images(&self, count: i64, offset: i64, sort: SortKey) -> Result<Vec<Image>, QueryError>;
+            async fn query_images_after(&self, cursor: Option<Cursor>, count: i64) -> Result<Vec<Image>, QueryError>;
+            async fn query_images_by_tags(&self, tags: &[String], tag_match: TagMatch, count: i64, offset: i64, sort: SortKey) -> Result<Vec<Image>, QueryError>;
         }
     }
 
@@ -91,7 +153,7 @@ mod tests {
     async fn test_batch_get_image_count_is_zero() {
         let mock = MockDS::new();
         let suu = BatchQueryImage::new(mock);
-        let result = suu.batch_query_image(0, 0).await;
+        let result = suu.batch_query_image(0, 0, SortKey::UpdatedOn).await;
         assert!(result.is_err());
         assert_eq!(result, Err(BatchQueryImageServiceError::InvalidRequest));
     }
@@ -100,7 +162,7 @@ mod tests {
     async fn test_batch_get_image_offset_below_zero() {
         let mock = MockDS::new();
         let suu = BatchQueryImage::new(mock);
-        let result = suu.batch_query_image(0, -1).await;
+        let result = suu.batch_query_image(0, -1, SortKey::UpdatedOn).await;
         assert!(result.is_err());
         assert_eq!(result, Err(BatchQueryImageServiceError::InvalidRequest));
     }
@@ -109,9 +171,9 @@ mod tests {
     async fn test_batch_get_image_ds_error() {
         let mut mock = MockDS::new();
         mock.expect_query_images()
-            .returning(move |_c, _o| Err(QueryError::InternalError));
+            .returning(move |_c, _o, _s| Err(QueryError::InternalError));
         let suu = BatchQueryImage::new(mock);
-        let result = suu.batch_query_image(1, 1).await;
+        let result = suu.batch_query_image(1, 1, SortKey::UpdatedOn).await;
         assert!(result.is_err());
         assert_eq!(result, Err(BatchQueryImageServiceError::InternalError));
     }
@@ -120,7 +182,7 @@ mod tests {
     async fn test_batch_get_image_too_many_images() {
         let mock = MockDS::new();
         let suu = BatchQueryImage::new(mock);
-        let result = suu.batch_query_image(100, 1).await;
+        let result = suu.batch_query_image(100, 1, SortKey::UpdatedOn).await;
         assert!(result.is_err());
         assert_eq!(
             result,
@@ -132,10 +194,81 @@ mod tests {
     async fn test_batch_get_image_not_found() {
         let mut mock = MockDS::new();
         mock.expect_query_images()
-            .returning(move |_c, _o| Err(QueryError::RecordNotFound));
+            .returning(move |_c, _o, _s| Err(QueryError::RecordNotFound));
         let suu = BatchQueryImage::new(mock);
-        let result = suu.batch_query_image(1, 1).await;
+        let result = suu.batch_query_image(1, 1, SortKey::UpdatedOn).await;
         assert!(result.is_err());
         assert_eq!(result, Err(BatchQueryImageServiceError::NoRecordsFound));
     }
+
+    #[tokio::test]
+    async fn test_batch_get_image_by_tags_normalizes_tags() {
+        let mut mock = MockDS::new();
+        mock.expect_query_images_by_tags()
+            .withf(|tags, tag_match, count, offset, _sort| {
+                tags == ["beach", "sunset"]
+                    && *tag_match == TagMatch::Any
+                    && (*count, *offset) == (10, 0)
+            })
+            .returning(|_t, _m, _c, _o, _s| Ok(vec![]));
+        let suu = BatchQueryImage::new(mock);
+        let result = suu
+            .batch_query_image_by_tags(
+                vec![
+                    "Sunset".to_string(),
+                    "beach".to_string(),
+                    "BEACH".to_string(),
+                ],
+                TagMatch::Any,
+                10,
+                0,
+                SortKey::UpdatedOn,
+            )
+            .await;
+        assert_eq!(result, Ok(vec![]));
+    }
+
+    #[tokio::test]
+    async fn test_batch_get_image_by_tags_invalid_tag() {
+        let mock = MockDS::new();
+        let suu = BatchQueryImage::new(mock);
+        let result = suu
+            .batch_query_image_by_tags(
+                vec![" ".to_string()],
+                TagMatch::All,
+                10,
+                0,
+                SortKey::UpdatedOn,
+            )
+            .await;
+        assert!(matches!(
+            result,
+            Err(BatchQueryImageServiceError::InvalidTag(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_batch_get_image_page_sets_next_cursor() {
+        let now = chrono::Utc::now();
+        let images = (1..=3)
+            .map(|id| Image::new(id, format!("path/{}", id), now))
+            .collect::<Vec<Image>>();
+        let mut mock = MockDS::new();
+        let returned = images.clone();
+        mock.expect_query_images_after()
+            .withf(|cursor, count| cursor.is_none() && *count == 3)
+            .returning(move |_c, _n| Ok(returned.clone()));
+        mock.expect_query_images_after()
+            .withf(|cursor, count| cursor.is_some() && *count == 4)
+            .returning(move |_c, _n| Ok(vec![]));
+        let suu = BatchQueryImage::new(mock);
+
+        let result = suu.batch_query_image_page(None, 2).await;
+        let expected = ImagePage::new(images[..2].to_vec(), Some(Cursor::new(now, 2)));
+        assert_eq!(result, Ok(expected));
+        let result = suu
+            .batch_query_image_page(Some(Cursor::new(now, 3)), 3)
+            .await;
+        assert_eq!(result, Ok(ImagePage::new(vec![], None)));
+    }
 }
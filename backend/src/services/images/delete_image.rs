@@ -1,57 +1,101 @@
 use async_trait::async_trait;
+use chrono::Utc;
 use tracing::error;
 
 use super::ports::{
     incoming::delete_image_service::{DeleteImageService, DeleteImageServiceError},
-    outgoing::delete_image_port::DeleteImagePort,
+    outgoing::{
+        blob_store_port::DynBlobStore,
+        delete_image_port::{DeleteImageError, DeleteImagePort, RemoveFiles},
+        trash_port::TrashPort,
+    },
 };
 
 pub struct DeleteImage<Storage>
 where
-    Storage: DeleteImagePort + Send + Sync,
+    Storage: DeleteImagePort + TrashPort + Send + Sync,
 {
     storage: Storage,
+    blob_store: DynBlobStore,
 }
 
 #[async_trait]
 impl<Storage> DeleteImageService for DeleteImage<Storage>
 where
-    Storage: DeleteImagePort + Send + Sync,
+    Storage: DeleteImagePort + TrashPort + Send + Sync,
 {
-    async fn delete_image(&self, index: i64) -> Result<(), DeleteImageServiceError> {
-        let path = match self.storage.delete_image(index).await {
-            Ok(path) => path,
-            Err(_) => return Err(DeleteImageServiceError::ImageNotFound),
-        };
-        if std::fs::remove_file(&path).is_err() {
-            error!("Error removing file {}", path);
-            return Err(DeleteImageServiceError::InternalError);
+    async fn delete_image(
+        &self,
+        index: i64,
+        permanent: bool,
+    ) -> Result<(), DeleteImageServiceError> {
+        if !permanent {
+            return match self.storage.trash_images(&[index], Utc::now()).await {
+                Ok(0) => Err(DeleteImageServiceError::ImageNotFound),
+                Ok(_) => Ok(()),
+                Err(_) => Err(DeleteImageServiceError::InternalError),
+            };
+        }
+        let blob_store = self.blob_store.clone();
+        let remove: RemoveFiles = Box::new(move |paths| {
+            Box::pin(async move {
+                for path in paths {
+                    if let Err(e) = blob_store.delete(&path).await {
+                        error!("Error removing file {}: {}", path, e);
+                        return Err(std::io::Error::other(e));
+                    }
+                }
+                Ok(())
+            })
+        });
+        // The files go before the row is committed: their paths come from
+        // the content hash, and a new upload of the same pixels must not
+        // have its file removed afterwards.
+        match self.storage.delete_image_with(index, remove).await {
+            Ok(_) => Ok(()),
+            Err(DeleteImageError::RecordNotFound) => Err(DeleteImageServiceError::ImageNotFound),
+            Err(DeleteImageError::InternalError) => Err(DeleteImageServiceError::InternalError),
         }
-        Ok(())
     }
 }
 
 impl<Storage> DeleteImage<Storage>
 where
-    Storage: DeleteImagePort + Send + Sync,
+    Storage: DeleteImagePort + TrashPort + Send + Sync,
 {
-    pub fn new(storage: Storage) -> Self {
-        Self { storage }
+    pub fn new(storage: Storage, blob_store: DynBlobStore) -> Self {
+        Self {
+            storage,
+            blob_store,
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use std::env;
+    use std::{env, sync::Arc, time::SystemTime};
 
     use async_trait::async_trait;
+    use chrono::{DateTime, Utc};
     use mockall::mock;
 
-    use crate::services::images::{
-        delete_image::DeleteImage,
-        ports::{
-            incoming::delete_image_service::{DeleteImageService, DeleteImageServiceError},
-            outgoing::delete_image_port::{DeleteImageError, DeleteImagePort},
+    use crate::{
+        data_storage::{
+            blobs::{fs_blob_store::FsBlobStore, memory_blob_store::MemoryBlobStore},
+            images::images_in_memory_ds::ImagesInMemoryDS,
+        },
+        services::images::{
+            delete_image::DeleteImage,
+            domain::image::Image,
+            ports::{
+                incoming::delete_image_service::{DeleteImageService, DeleteImageServiceError},
+                outgoing::{
+                    delete_image_port::{DeleteImageError, DeleteImagePort, RemoveFiles},
+                    insert_image_port::InsertImagePort,
+                    query_image_port::QueryImagePort,
+                    trash_port::{TrashError, TrashPort},
+                },
+            },
         },
     };
 
@@ -59,42 +103,98 @@ mod tests {
         DS {}
         #[async_trait]
         impl DeleteImagePort for DS {
-            async fn delete_image(&self, index: i64) -> Result<String, DeleteImageError>;
+            async fn delete_image(&self, index: i64) -> Result<Vec<String>, DeleteImageError>;
+            async fn delete_image_with(&self, index: i64, remove: RemoveFiles) -> Result<Vec<String>, DeleteImageError>;
+        }
+        #[async_trait]
+        impl TrashPort for DS {
+            async fn trash_images(&self, indexes: &[i64], deleted_at: DateTime<Utc>) -> Result<u64, TrashError>;
+            async fn restore_image(&self, index: i64) -> Result<(), TrashError>;
+            async fn query_trash(&self, count: i64, offset: i64) -> Result<Vec<Image>, TrashError>;
+            async fn purge_trash(&self, before: DateTime<Utc>) -> Result<Vec<String>, TrashError>;
+            async fn purge_trash_with(&self, before: DateTime<Utc>, remove: RemoveFiles) -> Result<Vec<String>, TrashError>;
         }
     }
 
     #[tokio::test]
     async fn test_delete_image() {
-        let path = env::current_dir().unwrap();
-        std::fs::write(path.join("2"), "some content").unwrap();
+        let blob_store = Arc::new(MemoryBlobStore::new());
+        blob_store.insert("data/2.qoi", "some content", SystemTime::now());
+        blob_store.insert("data/2_thumb.qoi", "some content", SystemTime::now());
+        blob_store.insert("data/3.qoi", "some content", SystemTime::now());
         let mut mock = MockDS::new();
-        mock.expect_delete_image()
-            .returning(move |_i| anyhow::Result::Ok(path.join("2").to_str().unwrap().to_string()));
-        let suu = DeleteImage::new(mock);
-        let result = suu.delete_image(1).await;
+        mock.expect_delete_image_with()
+            .returning(move |_i, remove| {
+                let paths = vec!["data/2.qoi".to_string(), "data/2_thumb.qoi".to_string()];
+                futures::executor::block_on(remove(paths.clone())).unwrap();
+                Ok(paths)
+            });
+        let suu = DeleteImage::new(mock, blob_store.clone());
+        let result = suu.delete_image(1, true).await;
         assert!(result.is_ok());
+        assert_eq!(blob_store.keys(), vec!["data/3.qoi"]);
     }
 
     #[tokio::test]
     async fn test_delete_image_ds_error() {
         let mut mock = MockDS::new();
-        mock.expect_delete_image()
-            .returning(move |_i| Err(DeleteImageError::RecordNotFound));
-        let suu = DeleteImage::new(mock);
-        let result = suu.delete_image(1).await;
+        mock.expect_delete_image_with()
+            .returning(move |_i, _r| Err(DeleteImageError::RecordNotFound));
+        let suu = DeleteImage::new(mock, Arc::new(MemoryBlobStore::new()));
+        let result = suu.delete_image(1, true).await;
         assert!(result.is_err());
         assert_eq!(result, Err(DeleteImageServiceError::ImageNotFound));
     }
 
     #[tokio::test]
     async fn test_delete_image_fs_error() {
-        let path = env::current_dir().unwrap();
+        // A directory cannot be removed like a file.
+        let path = env::temp_dir();
         let mut mock = MockDS::new();
-        mock.expect_delete_image()
-            .returning(move |_i| anyhow::Result::Ok(path.join("1").to_str().unwrap().to_string()));
-        let suu = DeleteImage::new(mock);
-        let result = suu.delete_image(1).await;
+        mock.expect_delete_image_with()
+            .returning(move |_i, remove| {
+                let paths = vec![path.display().to_string()];
+                futures::executor::block_on(remove(paths.clone()))
+                    .map(|_| paths)
+                    .map_err(|_| DeleteImageError::InternalError)
+            });
+        let suu = DeleteImage::new(mock, Arc::new(FsBlobStore::new()));
+        let result = suu.delete_image(1, true).await;
         assert!(result.is_err());
         assert_eq!(result, Err(DeleteImageServiceError::InternalError));
     }
+
+    #[tokio::test]
+    async fn test_delete_image_moves_to_trash() {
+        let mut mock = MockDS::new();
+        mock.expect_trash_images()
+            .withf(|indexes, _d| indexes == [1])
+            .returning(|_i, _d| Ok(1));
+        mock.expect_trash_images()
+            .withf(|indexes, _d| indexes == [2])
+            .returning(|_i, _d| Ok(0));
+        mock.expect_delete_image_with().never();
+        let suu = DeleteImage::new(mock, Arc::new(MemoryBlobStore::new()));
+        assert_eq!(suu.delete_image(1, false).await, Ok(()));
+        assert_eq!(
+            suu.delete_image(2, false).await,
+            Err(DeleteImageServiceError::ImageNotFound)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_delete_image_keeps_row_when_files_stay() {
+        let storage = Arc::new(ImagesInMemoryDS::new());
+        let path = env::temp_dir().display().to_string();
+        storage
+            .insert_image(&Image::new(1, path, Utc::now()))
+            .await
+            .unwrap();
+        let suu = DeleteImage::new(storage.clone(), Arc::new(FsBlobStore::new()));
+        assert_eq!(
+            suu.delete_image(1, true).await,
+            Err(DeleteImageServiceError::InternalError)
+        );
+        assert!(storage.query_image(1).await.is_ok());
+    }
 }
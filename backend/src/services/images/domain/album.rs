@@ -0,0 +1,99 @@
+use chrono::{DateTime, Utc};
+
+pub const MAX_ALBUM_NAME_LENGTH: usize = 255;
+
+/// A named, possibly nested, group of images kept in a user chosen order.
+#[derive(PartialEq, Debug, Clone)]
+pub struct Album {
+    id: i64,
+    name: String,
+    parent_id: Option<i64>,
+    cover_image_id: Option<i64>,
+    image_count: i64,
+    created_on: DateTime<Utc>,
+}
+
+impl Album {
+    pub fn new(id: i64, name: String, created_on: DateTime<Utc>) -> Self {
+        Self {
+            id,
+            name,
+            parent_id: None,
+            cover_image_id: None,
+            image_count: 0,
+            created_on,
+        }
+    }
+
+    pub fn with_parent_id(mut self, parent_id: i64) -> Self {
+        self.parent_id = Some(parent_id);
+        self
+    }
+
+    pub fn with_cover_image_id(mut self, cover_image_id: i64) -> Self {
+        self.cover_image_id = Some(cover_image_id);
+        self
+    }
+
+    pub fn with_image_count(mut self, image_count: i64) -> Self {
+        self.image_count = image_count;
+        self
+    }
+
+    pub fn id(&self) -> i64 {
+        self.id
+    }
+
+    pub fn name(&self) -> &str {
+        self.name.as_ref()
+    }
+
+    pub fn parent_id(&self) -> Option<i64> {
+        self.parent_id
+    }
+
+    pub fn cover_image_id(&self) -> Option<i64> {
+        self.cover_image_id
+    }
+
+    pub fn image_count(&self) -> i64 {
+        self.image_count
+    }
+
+    pub fn created_on(&self) -> DateTime<Utc> {
+        self.created_on
+    }
+}
+
+/// Changes to an album; unset fields are left alone while `Some(None)`
+/// clears the parent or cover.
+#[derive(PartialEq, Debug, Clone, Default)]
+pub struct AlbumUpdate {
+    pub name: Option<String>,
+    pub parent_id: Option<Option<i64>>,
+    pub cover_image_id: Option<Option<i64>>,
+}
+
+/// Album names are stored trimmed.
+pub fn normalize_album_name(name: &str) -> Result<String, String> {
+    let name = name.trim();
+    if name.is_empty() || name.chars().count() > MAX_ALBUM_NAME_LENGTH {
+        return Err(format!("Invalid album name: {:?}", name));
+    }
+    Ok(name.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::normalize_album_name;
+
+    #[test]
+    fn normalize_album_name_trims() {
+        assert_eq!(
+            normalize_album_name("  Holidays 2023 "),
+            Ok("Holidays 2023".to_string())
+        );
+        assert!(normalize_album_name(" ").is_err());
+        assert!(normalize_album_name(&"a".repeat(256)).is_err());
+    }
+}
@@ -0,0 +1,181 @@
+use std::{fmt::Display, str::FromStr};
+
+use chrono::{DateTime, Utc};
+use sha2::{Digest, Sha256};
+
+pub const MAX_API_KEY_NAME_LENGTH: usize = 255;
+const SECRET_PREFIX: &str = "yaiss_";
+
+/// What an API key may do. `Admin` allows everything.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Scope {
+    Read,
+    /// Adding images and changing them, their tags and albums.
+    Upload,
+    /// Deleting images and albums.
+    Delete,
+    /// Scraping `/metrics`.
+    Metrics,
+    Admin,
+}
+
+impl Scope {
+    pub const ALL: [Scope; 5] = [
+        Scope::Read,
+        Scope::Upload,
+        Scope::Delete,
+        Scope::Metrics,
+        Scope::Admin,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Scope::Read => "read",
+            Scope::Upload => "upload",
+            Scope::Delete => "delete",
+            Scope::Metrics => "metrics",
+            Scope::Admin => "admin",
+        }
+    }
+}
+
+impl Display for Scope {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Scope {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "read" => Ok(Scope::Read),
+            "upload" => Ok(Scope::Upload),
+            "delete" => Ok(Scope::Delete),
+            "metrics" => Ok(Scope::Metrics),
+            "admin" => Ok(Scope::Admin),
+            _ => Err(format!("Unknown scope: {}", s)),
+        }
+    }
+}
+
+/// Scopes as stored, comma separated.
+pub fn format_scopes(scopes: &[Scope]) -> String {
+    scopes
+        .iter()
+        .map(Scope::as_str)
+        .collect::<Vec<&str>>()
+        .join(",")
+}
+
+pub fn parse_scopes(scopes: &str) -> Result<Vec<Scope>, String> {
+    let mut scopes = scopes
+        .split(',')
+        .map(str::trim)
+        .filter(|scope| !scope.is_empty())
+        .map(Scope::from_str)
+        .collect::<Result<Vec<Scope>, String>>()?;
+    scopes.sort_unstable();
+    scopes.dedup();
+    Ok(scopes)
+}
+
+/// A fresh random secret; only its hash is stored.
+pub fn generate_secret() -> String {
+    format!(
+        "{}{}",
+        SECRET_PREFIX,
+        hex::encode(rand::random::<[u8; 32]>())
+    )
+}
+
+pub fn hash_secret(secret: &str) -> String {
+    hex::encode(Sha256::digest(secret.as_bytes()))
+}
+
+#[derive(PartialEq, Debug, Clone)]
+pub struct ApiKey {
+    id: i64,
+    name: String,
+    scopes: Vec<Scope>,
+    created_on: DateTime<Utc>,
+    revoked_on: Option<DateTime<Utc>>,
+}
+
+impl ApiKey {
+    pub fn new(id: i64, name: String, scopes: Vec<Scope>, created_on: DateTime<Utc>) -> Self {
+        Self {
+            id,
+            name,
+            scopes,
+            created_on,
+            revoked_on: None,
+        }
+    }
+
+    pub fn with_revoked_on(mut self, revoked_on: DateTime<Utc>) -> Self {
+        self.revoked_on = Some(revoked_on);
+        self
+    }
+
+    pub fn id(&self) -> i64 {
+        self.id
+    }
+
+    pub fn name(&self) -> &str {
+        self.name.as_ref()
+    }
+
+    pub fn scopes(&self) -> &[Scope] {
+        self.scopes.as_ref()
+    }
+
+    pub fn created_on(&self) -> DateTime<Utc> {
+        self.created_on
+    }
+
+    pub fn revoked_on(&self) -> Option<DateTime<Utc>> {
+        self.revoked_on
+    }
+
+    pub fn allows(&self, scope: Scope) -> bool {
+        self.scopes.contains(&scope) || self.scopes.contains(&Scope::Admin)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+
+    use super::{format_scopes, generate_secret, hash_secret, parse_scopes, ApiKey, Scope};
+
+    #[test]
+    fn test_parse_scopes() {
+        assert_eq!(
+            parse_scopes("upload, read,read"),
+            Ok(vec![Scope::Read, Scope::Upload])
+        );
+        assert_eq!(parse_scopes(""), Ok(vec![]));
+        assert!(parse_scopes("read,write").is_err());
+        assert_eq!(format_scopes(&[Scope::Read, Scope::Delete]), "read,delete");
+    }
+
+    #[test]
+    fn test_allows() {
+        let reader = ApiKey::new(1, "reader".to_string(), vec![Scope::Read], Utc::now());
+        assert!(reader.allows(Scope::Read));
+        assert!(!reader.allows(Scope::Delete));
+        let admin = ApiKey::new(2, "admin".to_string(), vec![Scope::Admin], Utc::now());
+        assert!(Scope::ALL.iter().all(|scope| admin.allows(*scope)));
+    }
+
+    #[test]
+    fn test_secrets() {
+        let secret = generate_secret();
+        assert!(secret.starts_with("yaiss_"));
+        assert_ne!(secret, generate_secret());
+        assert_eq!(hash_secret(&secret), hash_secret(&secret));
+        assert_ne!(hash_secret(&secret), secret);
+    }
+}
@@ -0,0 +1,157 @@
+use std::collections::{btree_map::Entry, BTreeMap};
+
+use super::perceptual_hash::hamming_distance;
+
+/// Burkhard-Keller tree over 64 bit perceptual hashes. Lookups only descend
+/// into the children whose edge distance is within `max_distance` of the
+/// query, so near-duplicate searches do not have to visit every hash.
+#[derive(Debug, Default)]
+pub struct BkTree {
+    root: Option<Node>,
+    len: usize,
+}
+
+#[derive(Debug)]
+struct Node {
+    hash: u64,
+    ids: Vec<i64>,
+    children: BTreeMap<u32, Node>,
+}
+
+impl Node {
+    fn new(hash: u64, id: i64) -> Self {
+        Self {
+            hash,
+            ids: vec![id],
+            children: BTreeMap::new(),
+        }
+    }
+}
+
+impl BkTree {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn insert(&mut self, hash: u64, id: i64) {
+        self.len += 1;
+        let mut node = match self.root.as_mut() {
+            Some(node) => node,
+            None => {
+                self.root = Some(Node::new(hash, id));
+                return;
+            }
+        };
+        loop {
+            let distance = hamming_distance(node.hash, hash);
+            if distance == 0 {
+                node.ids.push(id);
+                return;
+            }
+            node = match node.children.entry(distance) {
+                Entry::Vacant(entry) => {
+                    entry.insert(Node::new(hash, id));
+                    return;
+                }
+                Entry::Occupied(entry) => entry.into_mut(),
+            };
+        }
+    }
+
+    /// Returns every `(id, distance)` pair whose hash lies within
+    /// `max_distance` of `hash`.
+    pub fn find(&self, hash: u64, max_distance: u32) -> Vec<(i64, u32)> {
+        let mut found = vec![];
+        let mut stack: Vec<&Node> = self.root.iter().collect();
+        while let Some(node) = stack.pop() {
+            let distance = hamming_distance(node.hash, hash);
+            if distance <= max_distance {
+                found.extend(node.ids.iter().map(|id| (*id, distance)));
+            }
+            let low = distance.saturating_sub(max_distance);
+            let high = distance.saturating_add(max_distance);
+            stack.extend(node.children.range(low..=high).map(|(_, child)| child));
+        }
+        found
+    }
+
+    /// Every stored `(hash, id)` pair, in no particular order.
+    pub fn entries(&self) -> Vec<(u64, i64)> {
+        let mut entries = Vec::with_capacity(self.len);
+        let mut stack: Vec<&Node> = self.root.iter().collect();
+        while let Some(node) = stack.pop() {
+            entries.extend(node.ids.iter().map(|id| (node.hash, *id)));
+            stack.extend(node.children.values());
+        }
+        entries
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tree() -> BkTree {
+        let mut tree = BkTree::new();
+        tree.insert(0b0000, 1);
+        tree.insert(0b0001, 2);
+        tree.insert(0b0011, 3);
+        tree.insert(0b1111, 4);
+        tree.insert(0b0000, 5);
+        tree.insert(u64::MAX, 6);
+        tree
+    }
+
+    #[test]
+    fn test_insert() {
+        let tree = tree();
+        assert_eq!(tree.len(), 6);
+        let mut entries = tree.entries();
+        entries.sort_by_key(|(_, id)| *id);
+        assert_eq!(
+            entries,
+            vec![
+                (0b0000, 1),
+                (0b0001, 2),
+                (0b0011, 3),
+                (0b1111, 4),
+                (0b0000, 5),
+                (u64::MAX, 6)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_find_exact() {
+        let tree = tree();
+        let mut found = tree.find(0b0000, 0);
+        found.sort();
+        assert_eq!(found, vec![(1, 0), (5, 0)]);
+    }
+
+    #[test]
+    fn test_find_within_distance() {
+        let tree = tree();
+        let mut found = tree.find(0b0000, 2);
+        found.sort();
+        assert_eq!(found, vec![(1, 0), (2, 1), (3, 2), (5, 0)]);
+        let mut found = tree.find(u64::MAX, 60);
+        found.sort();
+        assert_eq!(found, vec![(4, 60), (6, 0)]);
+    }
+
+    #[test]
+    fn test_find_on_empty_tree() {
+        let tree = BkTree::new();
+        assert!(tree.is_empty());
+        assert!(tree.find(0, 64).is_empty());
+    }
+}
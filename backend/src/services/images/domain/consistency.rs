@@ -0,0 +1,95 @@
+use super::image_status::ImageStatus;
+
+/// The file an image row points at, as recorded in the database.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImageFile {
+    image_id: i64,
+    path: String,
+    status: ImageStatus,
+    size: Option<u64>,
+    checksum: Option<String>,
+}
+
+impl ImageFile {
+    pub fn new(image_id: i64, path: String, status: ImageStatus) -> Self {
+        Self {
+            image_id,
+            path,
+            status,
+            size: None,
+            checksum: None,
+        }
+    }
+
+    /// Size the stored file had when it was written.
+    pub fn with_size(mut self, size: u64) -> Self {
+        self.size = Some(size);
+        self
+    }
+
+    /// Checksum recorded when the file was written, if any.
+    pub fn with_checksum(mut self, checksum: String) -> Self {
+        self.checksum = Some(checksum);
+        self
+    }
+
+    pub fn image_id(&self) -> i64 {
+        self.image_id
+    }
+
+    pub fn path(&self) -> &str {
+        self.path.as_ref()
+    }
+
+    pub fn status(&self) -> ImageStatus {
+        self.status
+    }
+
+    pub fn size(&self) -> Option<u64> {
+        self.size
+    }
+
+    pub fn checksum(&self) -> Option<&str> {
+        self.checksum.as_deref()
+    }
+}
+
+/// A file under the images directory that no row refers to.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OrphanFile {
+    path: String,
+    size: u64,
+}
+
+impl OrphanFile {
+    pub fn new(path: String, size: u64) -> Self {
+        Self { path, size }
+    }
+
+    pub fn path(&self) -> &str {
+        self.path.as_ref()
+    }
+
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+}
+
+/// Outcome of comparing the image rows with the files on disk.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ConsistencyReport {
+    /// Rows whose file does not exist.
+    pub missing: Vec<ImageFile>,
+    /// Files no row refers to.
+    pub orphans: Vec<OrphanFile>,
+    /// Rows marked as missing whose file is back in place.
+    pub recovered: Vec<ImageFile>,
+    /// Whether the orphans were quarantined and the rows marked.
+    pub repaired: bool,
+}
+
+impl ConsistencyReport {
+    pub fn is_consistent(&self) -> bool {
+        self.missing.is_empty() && self.orphans.is_empty() && self.recovered.is_empty()
+    }
+}
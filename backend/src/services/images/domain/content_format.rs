@@ -0,0 +1,129 @@
+use std::io::Cursor;
+
+use image::{DynamicImage, ImageError, ImageOutputFormat};
+
+pub const DEFAULT_JPEG_QUALITY: u8 = 85;
+
+/// Encoding in which stored image content can be delivered. `Original` is the
+/// stored QOI file as is, every other format is transcoded on request.
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum ContentFormat {
+    Original,
+    Png,
+    Jpeg(u8),
+    WebP,
+}
+
+impl ContentFormat {
+    /// Parses a format name as used in the `format` query parameter.
+    pub fn from_name(name: &str, quality: u8) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "original" | "qoi" => Some(ContentFormat::Original),
+            "png" => Some(ContentFormat::Png),
+            "jpeg" | "jpg" => Some(ContentFormat::Jpeg(quality)),
+            "webp" => Some(ContentFormat::WebP),
+            _ => None,
+        }
+    }
+
+    /// Maps a media type of an `Accept` header entry to a format. Wildcards
+    /// get PNG, since browsers send them and cannot display QOI.
+    pub fn from_mime(mime: &str, quality: u8) -> Option<Self> {
+        match mime.to_lowercase().as_str() {
+            "image/qoi" => Some(ContentFormat::Original),
+            "image/png" | "image/*" | "*/*" => Some(ContentFormat::Png),
+            "image/jpeg" => Some(ContentFormat::Jpeg(quality)),
+            "image/webp" => Some(ContentFormat::WebP),
+            _ => None,
+        }
+    }
+
+    pub fn mime(&self) -> &'static str {
+        match self {
+            ContentFormat::Original => "image/qoi",
+            ContentFormat::Png => "image/png",
+            ContentFormat::Jpeg(_) => "image/jpeg",
+            ContentFormat::WebP => "image/webp",
+        }
+    }
+
+    /// Decodes QOI content and encodes it in this format. JPEG drops the alpha
+    /// channel and WebP is always lossless.
+    pub fn transcode(&self, qoi: Vec<u8>) -> Result<Vec<u8>, ImageError> {
+        if *self == ContentFormat::Original {
+            return Ok(qoi);
+        }
+        let image = image::load(Cursor::new(qoi), image::ImageFormat::Qoi)?;
+        let (image, format) = match self {
+            ContentFormat::Jpeg(quality) => (
+                DynamicImage::ImageRgb8(image.to_rgb8()),
+                ImageOutputFormat::Jpeg(*quality),
+            ),
+            ContentFormat::WebP => (
+                DynamicImage::ImageRgba8(image.to_rgba8()),
+                ImageOutputFormat::WebP,
+            ),
+            _ => (image, ImageOutputFormat::Png),
+        };
+        let mut bytes = vec![];
+        image.write_to(&mut Cursor::new(&mut bytes), format)?;
+        Ok(bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use image::{DynamicImage, GenericImageView};
+
+    use super::*;
+
+    fn qoi() -> Vec<u8> {
+        let mut bytes = vec![];
+        DynamicImage::new_rgba8(4, 3)
+            .write_to(&mut Cursor::new(&mut bytes), ImageOutputFormat::Qoi)
+            .unwrap();
+        bytes
+    }
+
+    #[test]
+    fn test_transcode() {
+        for (format, image_format) in [
+            (ContentFormat::Png, image::ImageFormat::Png),
+            (ContentFormat::Jpeg(70), image::ImageFormat::Jpeg),
+            (ContentFormat::WebP, image::ImageFormat::WebP),
+        ] {
+            let bytes = format.transcode(qoi()).unwrap();
+            assert_eq!(image::guess_format(&bytes).unwrap(), image_format);
+            let image = image::load_from_memory(&bytes).unwrap();
+            assert_eq!(image.dimensions(), (4, 3));
+        }
+        assert_eq!(ContentFormat::Original.transcode(qoi()).unwrap(), qoi());
+    }
+
+    #[test]
+    fn test_transcode_invalid_content() {
+        assert!(ContentFormat::Png.transcode(vec![0; 16]).is_err());
+    }
+
+    #[test]
+    fn test_from_name_and_mime() {
+        assert_eq!(
+            ContentFormat::from_name("JPG", 50),
+            Some(ContentFormat::Jpeg(50))
+        );
+        assert_eq!(ContentFormat::from_name("gif", 50), None);
+        assert_eq!(
+            ContentFormat::from_mime("image/webp", 50),
+            Some(ContentFormat::WebP)
+        );
+        assert_eq!(
+            ContentFormat::from_mime("*/*", 50),
+            Some(ContentFormat::Png)
+        );
+        assert_eq!(
+            ContentFormat::from_mime("image/QOI", 50),
+            Some(ContentFormat::Original)
+        );
+        assert_eq!(ContentFormat::from_mime("text/html", 50), None);
+    }
+}
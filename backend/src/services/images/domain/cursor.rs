@@ -0,0 +1,110 @@
+use std::{fmt::Display, str::FromStr};
+
+use chrono::{DateTime, Utc};
+
+use super::image::Image;
+
+/// Position in the `(updated_on, id)` ordering of images. Listing after a
+/// cursor is stable under concurrent uploads, unlike an offset.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cursor {
+    updated_on: DateTime<Utc>,
+    id: i64,
+}
+
+impl Cursor {
+    pub fn new(updated_on: DateTime<Utc>, id: i64) -> Self {
+        Self { updated_on, id }
+    }
+
+    pub fn updated_on(&self) -> DateTime<Utc> {
+        self.updated_on
+    }
+
+    pub fn id(&self) -> i64 {
+        self.id
+    }
+}
+
+impl From<&Image> for Cursor {
+    fn from(image: &Image) -> Self {
+        Self::new(image.updated_on(), image.id())
+    }
+}
+
+/// Cursors are handed to clients as an opaque hex string.
+impl Display for Cursor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let raw = format!("{}|{}", self.updated_on.to_rfc3339(), self.id);
+        f.write_str(&hex::encode(raw))
+    }
+}
+
+impl FromStr for Cursor {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("Invalid cursor: {}", s);
+        let raw = hex::decode(s).map_err(|_| invalid())?;
+        let raw = String::from_utf8(raw).map_err(|_| invalid())?;
+        let (updated_on, id) = raw.split_once('|').ok_or_else(invalid)?;
+        let updated_on = DateTime::parse_from_rfc3339(updated_on)
+            .map_err(|_| invalid())?
+            .with_timezone(&Utc);
+        let id = id.parse::<i64>().map_err(|_| invalid())?;
+        Ok(Self::new(updated_on, id))
+    }
+}
+
+/// One page of a cursor listing; `next_cursor` is unset on the last page.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImagePage {
+    images: Vec<Image>,
+    next_cursor: Option<Cursor>,
+}
+
+impl ImagePage {
+    pub fn new(images: Vec<Image>, next_cursor: Option<Cursor>) -> Self {
+        Self {
+            images,
+            next_cursor,
+        }
+    }
+
+    pub fn images(&self) -> &[Image] {
+        self.images.as_ref()
+    }
+
+    pub fn next_cursor(&self) -> Option<Cursor> {
+        self.next_cursor
+    }
+
+    pub fn into_images(self) -> Vec<Image> {
+        self.images
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{DateTime, Utc};
+
+    use super::Cursor;
+
+    #[test]
+    fn cursor_round_trips() {
+        let updated_on = "2023-09-23T10:11:12.123456789Z"
+            .parse::<DateTime<Utc>>()
+            .unwrap();
+        let cursor = Cursor::new(updated_on, 42);
+        assert_eq!(cursor.to_string().parse::<Cursor>(), Ok(cursor));
+    }
+
+    #[test]
+    fn malformed_cursor_is_rejected() {
+        assert_eq!(
+            "zz".parse::<Cursor>(),
+            Err("Invalid cursor: zz".to_string())
+        );
+        assert!(hex::encode("2023|x").parse::<Cursor>().is_err());
+    }
+}
@@ -0,0 +1,348 @@
+use std::io::Cursor;
+
+use chrono::{DateTime, FixedOffset, NaiveDate, TimeZone, Utc};
+use exif::{Exif, Field, In, Reader, Tag, Value};
+use image::DynamicImage;
+
+/// Camera metadata found in the EXIF block of an upload.
+#[derive(PartialEq, Debug, Clone, Default)]
+pub struct ExifData {
+    captured_on: Option<DateTime<Utc>>,
+    make: Option<String>,
+    model: Option<String>,
+    lens: Option<String>,
+    exposure_time: Option<String>,
+    f_number: Option<f64>,
+    iso: Option<u32>,
+    focal_length: Option<f64>,
+    latitude: Option<f64>,
+    longitude: Option<f64>,
+    altitude: Option<f64>,
+    orientation: Option<u16>,
+}
+
+impl ExifData {
+    /// Reads the EXIF block of a JPEG, TIFF or HEIF container, without
+    /// decoding any pixels. Returns `None` when there is no block to read.
+    pub fn read(buffer: &[u8]) -> Option<Self> {
+        let exif = Reader::new()
+            .read_from_container(&mut Cursor::new(buffer))
+            .ok()?;
+        Some(Self::from_exif(&exif))
+    }
+
+    fn from_exif(exif: &Exif) -> Self {
+        let field = |tag: Tag| exif.get_field(tag, In::PRIMARY);
+        Self {
+            captured_on: captured_on(exif),
+            make: field(Tag::Make).and_then(ascii),
+            model: field(Tag::Model).and_then(ascii),
+            lens: field(Tag::LensModel).and_then(ascii),
+            exposure_time: field(Tag::ExposureTime).map(|field| field.display_value().to_string()),
+            f_number: field(Tag::FNumber).and_then(rational),
+            iso: field(Tag::PhotographicSensitivity).and_then(|field| field.value.get_uint(0)),
+            focal_length: field(Tag::FocalLength).and_then(rational),
+            latitude: coordinate(exif, Tag::GPSLatitude, Tag::GPSLatitudeRef, b'S'),
+            longitude: coordinate(exif, Tag::GPSLongitude, Tag::GPSLongitudeRef, b'W'),
+            altitude: field(Tag::GPSAltitude).and_then(rational).map(|altitude| {
+                // A reference of 1 means below sea level.
+                match field(Tag::GPSAltitudeRef).and_then(|field| field.value.get_uint(0)) {
+                    Some(1) => -altitude,
+                    _ => altitude,
+                }
+            }),
+            orientation: field(Tag::Orientation)
+                .and_then(|field| field.value.get_uint(0))
+                .map(|orientation| orientation as u16),
+        }
+    }
+
+    pub fn with_captured_on(mut self, captured_on: DateTime<Utc>) -> Self {
+        self.captured_on = Some(captured_on);
+        self
+    }
+
+    pub fn with_camera(mut self, make: Option<String>, model: Option<String>) -> Self {
+        self.make = make;
+        self.model = model;
+        self
+    }
+
+    pub fn with_lens(mut self, lens: String) -> Self {
+        self.lens = Some(lens);
+        self
+    }
+
+    pub fn with_exposure(
+        mut self,
+        exposure_time: Option<String>,
+        f_number: Option<f64>,
+        iso: Option<u32>,
+        focal_length: Option<f64>,
+    ) -> Self {
+        self.exposure_time = exposure_time;
+        self.f_number = f_number;
+        self.iso = iso;
+        self.focal_length = focal_length;
+        self
+    }
+
+    pub fn with_location(
+        mut self,
+        latitude: Option<f64>,
+        longitude: Option<f64>,
+        altitude: Option<f64>,
+    ) -> Self {
+        self.latitude = latitude;
+        self.longitude = longitude;
+        self.altitude = altitude;
+        self
+    }
+
+    pub fn with_orientation(mut self, orientation: u16) -> Self {
+        self.orientation = Some(orientation);
+        self
+    }
+
+    pub fn captured_on(&self) -> Option<DateTime<Utc>> {
+        self.captured_on
+    }
+
+    pub fn make(&self) -> Option<&str> {
+        self.make.as_deref()
+    }
+
+    pub fn model(&self) -> Option<&str> {
+        self.model.as_deref()
+    }
+
+    pub fn lens(&self) -> Option<&str> {
+        self.lens.as_deref()
+    }
+
+    pub fn exposure_time(&self) -> Option<&str> {
+        self.exposure_time.as_deref()
+    }
+
+    pub fn f_number(&self) -> Option<f64> {
+        self.f_number
+    }
+
+    pub fn iso(&self) -> Option<u32> {
+        self.iso
+    }
+
+    pub fn focal_length(&self) -> Option<f64> {
+        self.focal_length
+    }
+
+    pub fn latitude(&self) -> Option<f64> {
+        self.latitude
+    }
+
+    pub fn longitude(&self) -> Option<f64> {
+        self.longitude
+    }
+
+    pub fn altitude(&self) -> Option<f64> {
+        self.altitude
+    }
+
+    pub fn orientation(&self) -> Option<u16> {
+        self.orientation
+    }
+}
+
+/// Rotates and mirrors the decoded pixels so they are upright, as described
+/// by the EXIF orientation tag (1 to 8).
+pub fn orient(image: DynamicImage, orientation: u16) -> DynamicImage {
+    match orientation {
+        2 => image.fliph(),
+        3 => image.rotate180(),
+        4 => image.flipv(),
+        5 => image.rotate90().fliph(),
+        6 => image.rotate90(),
+        7 => image.rotate270().fliph(),
+        8 => image.rotate270(),
+        _ => image,
+    }
+}
+
+fn ascii(field: &Field) -> Option<String> {
+    match &field.value {
+        Value::Ascii(values) => values
+            .first()
+            .map(|value| String::from_utf8_lossy(value).trim().to_string())
+            .filter(|value| !value.is_empty()),
+        _ => None,
+    }
+}
+
+fn rational(field: &Field) -> Option<f64> {
+    match &field.value {
+        Value::Rational(values) => values.first().map(|value| value.to_f64()),
+        _ => None,
+    }
+}
+
+/// Original capture time, shifted to UTC when the camera recorded its
+/// offset and assumed to be UTC otherwise.
+fn captured_on(exif: &Exif) -> Option<DateTime<Utc>> {
+    let raw = match &exif.get_field(Tag::DateTimeOriginal, In::PRIMARY)?.value {
+        Value::Ascii(values) => values.first()?.clone(),
+        _ => return None,
+    };
+    let mut date_time = exif::DateTime::from_ascii(&raw).ok()?;
+    if let Some(Value::Ascii(values)) = exif
+        .get_field(Tag::OffsetTimeOriginal, In::PRIMARY)
+        .map(|field| &field.value)
+    {
+        if let Some(offset) = values.first() {
+            let _ = date_time.parse_offset(offset);
+        }
+    }
+    let naive = NaiveDate::from_ymd_opt(
+        date_time.year as i32,
+        date_time.month as u32,
+        date_time.day as u32,
+    )?
+    .and_hms_opt(
+        date_time.hour as u32,
+        date_time.minute as u32,
+        date_time.second as u32,
+    )?;
+    match date_time.offset {
+        Some(offset) => FixedOffset::east_opt(offset as i32 * 60)?
+            .from_local_datetime(&naive)
+            .single()
+            .map(|date_time| date_time.with_timezone(&Utc)),
+        None => Some(Utc.from_utc_datetime(&naive)),
+    }
+}
+
+fn coordinate(exif: &Exif, tag: Tag, reference: Tag, negative: u8) -> Option<f64> {
+    let degrees = match &exif.get_field(tag, In::PRIMARY)?.value {
+        Value::Rational(values) if values.len() >= 3 => {
+            values[0].to_f64() + values[1].to_f64() / 60.0 + values[2].to_f64() / 3600.0
+        }
+        _ => return None,
+    };
+    match exif
+        .get_field(reference, In::PRIMARY)
+        .map(|field| &field.value)
+    {
+        Some(Value::Ascii(values)) if values.first()?.first() == Some(&negative) => Some(-degrees),
+        _ => Some(degrees),
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use chrono::{DateTime, Utc};
+    use image::{DynamicImage, GenericImageView, Rgb, RgbImage};
+
+    use super::{orient, ExifData};
+
+    /// Little endian TIFF entry: tag, type, count and the value (or offset).
+    fn entry(tag: u16, kind: u16, count: u32, value: u32) -> Vec<u8> {
+        let mut bytes = tag.to_le_bytes().to_vec();
+        bytes.extend(kind.to_le_bytes());
+        bytes.extend(count.to_le_bytes());
+        bytes.extend(value.to_le_bytes());
+        bytes
+    }
+
+    /// Minimal EXIF block: make, model, orientation, capture time with its
+    /// offset and an f-number of 2.8.
+    pub(crate) fn exif_block(orientation: u16) -> Vec<u8> {
+        const IFD0: u32 = 8;
+        const IFD0_LEN: u32 = 2 + 4 * 12 + 4;
+        const EXIF_IFD: u32 = IFD0 + IFD0_LEN;
+        const EXIF_IFD_LEN: u32 = 2 + 3 * 12 + 4;
+        const DATA: u32 = EXIF_IFD + EXIF_IFD_LEN;
+        let make = b"Yaiss\0".to_vec();
+        let model = b"Camera One\0".to_vec();
+        let date_time = b"2023:08:26 13:30:00\0".to_vec();
+        let offset = b"+02:00\0".to_vec();
+        let f_number = [28u32.to_le_bytes(), 10u32.to_le_bytes()].concat();
+        let make_at = DATA;
+        let model_at = make_at + make.len() as u32;
+        let date_time_at = model_at + model.len() as u32;
+        let f_number_at = date_time_at + date_time.len() as u32;
+
+        let mut tiff = b"II*\0".to_vec();
+        tiff.extend(IFD0.to_le_bytes());
+        tiff.extend(4u16.to_le_bytes());
+        tiff.extend(entry(0x010f, 2, make.len() as u32, make_at));
+        tiff.extend(entry(0x0110, 2, model.len() as u32, model_at));
+        tiff.extend(entry(0x0112, 3, 1, orientation as u32));
+        tiff.extend(entry(0x8769, 4, 1, EXIF_IFD));
+        tiff.extend(0u32.to_le_bytes());
+        tiff.extend(3u16.to_le_bytes());
+        tiff.extend(entry(0x829d, 5, 1, f_number_at));
+        tiff.extend(entry(0x9003, 2, date_time.len() as u32, date_time_at));
+        tiff.extend(entry(0x9011, 2, offset.len() as u32, f_number_at + 8));
+        tiff.extend(0u32.to_le_bytes());
+        tiff.extend(make);
+        tiff.extend(model);
+        tiff.extend(date_time);
+        tiff.extend(f_number);
+        tiff.extend(offset);
+        tiff
+    }
+
+    /// Inserts the EXIF block as an APP1 segment right after the JPEG SOI marker.
+    pub(crate) fn with_exif(jpeg: Vec<u8>, orientation: u16) -> Vec<u8> {
+        let mut app1 = b"Exif\0\0".to_vec();
+        app1.extend(exif_block(orientation));
+        let mut bytes = jpeg[..2].to_vec();
+        bytes.extend([0xff, 0xe1]);
+        bytes.extend((app1.len() as u16 + 2).to_be_bytes());
+        bytes.extend(app1);
+        bytes.extend(&jpeg[2..]);
+        bytes
+    }
+
+    #[test]
+    fn test_read_tiff() {
+        let exif = ExifData::read(&exif_block(6)).unwrap();
+        assert_eq!(exif.make(), Some("Yaiss"));
+        assert_eq!(exif.model(), Some("Camera One"));
+        assert_eq!(exif.orientation(), Some(6));
+        assert_eq!(exif.f_number(), Some(2.8));
+        assert_eq!(
+            exif.captured_on(),
+            Some("2023-08-26T11:30:00Z".parse::<DateTime<Utc>>().unwrap())
+        );
+        assert_eq!(exif.latitude(), None);
+    }
+
+    #[test]
+    fn test_read_jpeg() {
+        let mut jpeg = vec![];
+        DynamicImage::new_rgb8(4, 4)
+            .write_to(
+                &mut std::io::Cursor::new(&mut jpeg),
+                image::ImageOutputFormat::Jpeg(90),
+            )
+            .unwrap();
+        assert_eq!(ExifData::read(&jpeg), None);
+        let exif = ExifData::read(&with_exif(jpeg, 1)).unwrap();
+        assert_eq!(exif.make(), Some("Yaiss"));
+    }
+
+    #[test]
+    fn test_orient() {
+        let mut pixels = RgbImage::new(3, 2);
+        pixels.put_pixel(0, 0, Rgb([255, 0, 0]));
+        let image = DynamicImage::ImageRgb8(pixels);
+        let rotated = orient(image.clone(), 6);
+        assert_eq!(rotated.dimensions(), (2, 3));
+        assert_eq!(rotated.get_pixel(1, 0).0, [255, 0, 0, 255]);
+        let transposed = orient(image.clone(), 5);
+        assert_eq!(transposed.get_pixel(0, 0).0, [255, 0, 0, 255]);
+        let transversed = orient(image.clone(), 7);
+        assert_eq!(transversed.get_pixel(1, 2).0, [255, 0, 0, 255]);
+        assert_eq!(orient(image.clone(), 1), image);
+    }
+}
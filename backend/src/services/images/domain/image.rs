@@ -1,10 +1,21 @@
 use chrono::{DateTime, Utc};
 
+use super::{exif::ExifData, image_metadata::ImageMetadata, image_status::ImageStatus};
+
 #[derive(PartialEq, Debug, Clone)]
 pub struct Image {
     id: i64,
     path: String,
     updated_on: DateTime<Utc>,
+    created_on: DateTime<Utc>,
+    hash: Option<String>,
+    perceptual_hash: Option<u64>,
+    metadata: Option<ImageMetadata>,
+    original_path: Option<String>,
+    exif: Option<ExifData>,
+    deleted_at: Option<DateTime<Utc>>,
+    status: ImageStatus,
+    checksum: Option<String>,
 }
 
 impl Image {
@@ -13,9 +24,66 @@ impl Image {
             id,
             path,
             updated_on,
+            created_on: updated_on,
+            hash: None,
+            perceptual_hash: None,
+            metadata: None,
+            original_path: None,
+            exif: None,
+            deleted_at: None,
+            status: ImageStatus::Ok,
+            checksum: None,
         }
     }
 
+    pub fn with_created_on(mut self, created_on: DateTime<Utc>) -> Self {
+        self.created_on = created_on;
+        self
+    }
+
+    pub fn with_hash(mut self, hash: String) -> Self {
+        self.hash = Some(hash);
+        self
+    }
+
+    pub fn with_perceptual_hash(mut self, perceptual_hash: u64) -> Self {
+        self.perceptual_hash = Some(perceptual_hash);
+        self
+    }
+
+    pub fn with_metadata(mut self, metadata: ImageMetadata) -> Self {
+        self.metadata = Some(metadata);
+        self
+    }
+
+    /// Location of the uploaded bytes, kept only when the deployment asks for it.
+    pub fn with_original_path(mut self, original_path: String) -> Self {
+        self.original_path = Some(original_path);
+        self
+    }
+
+    pub fn with_exif(mut self, exif: ExifData) -> Self {
+        self.exif = Some(exif);
+        self
+    }
+
+    /// Marks the image as in the trash since `deleted_at`.
+    pub fn with_deleted_at(mut self, deleted_at: DateTime<Utc>) -> Self {
+        self.deleted_at = Some(deleted_at);
+        self
+    }
+
+    pub fn with_status(mut self, status: ImageStatus) -> Self {
+        self.status = status;
+        self
+    }
+
+    /// SHA-256 of the stored file, checked by the scrubber.
+    pub fn with_checksum(mut self, checksum: String) -> Self {
+        self.checksum = Some(checksum);
+        self
+    }
+
     pub fn id(&self) -> i64 {
         self.id
     }
@@ -27,4 +95,40 @@ impl Image {
     pub fn updated_on(&self) -> DateTime<Utc> {
         self.updated_on
     }
+
+    pub fn created_on(&self) -> DateTime<Utc> {
+        self.created_on
+    }
+
+    pub fn hash(&self) -> Option<&str> {
+        self.hash.as_deref()
+    }
+
+    pub fn perceptual_hash(&self) -> Option<u64> {
+        self.perceptual_hash
+    }
+
+    pub fn metadata(&self) -> Option<&ImageMetadata> {
+        self.metadata.as_ref()
+    }
+
+    pub fn original_path(&self) -> Option<&str> {
+        self.original_path.as_deref()
+    }
+
+    pub fn exif(&self) -> Option<&ExifData> {
+        self.exif.as_ref()
+    }
+
+    pub fn deleted_at(&self) -> Option<DateTime<Utc>> {
+        self.deleted_at
+    }
+
+    pub fn status(&self) -> ImageStatus {
+        self.status
+    }
+
+    pub fn checksum(&self) -> Option<&str> {
+        self.checksum.as_deref()
+    }
 }
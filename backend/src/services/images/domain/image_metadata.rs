@@ -0,0 +1,129 @@
+use image::{DynamicImage, ImageFormat};
+
+/// Properties recorded when an image is uploaded, so clients can lay out
+/// their views without downloading any pixels.
+#[derive(PartialEq, Debug, Clone)]
+pub struct ImageMetadata {
+    width: u32,
+    height: u32,
+    color_type: String,
+    bit_depth: u8,
+    original_format: String,
+    original_size: u64,
+    stored_size: u64,
+    filename: Option<String>,
+}
+
+impl ImageMetadata {
+    pub fn new(
+        width: u32,
+        height: u32,
+        color_type: String,
+        bit_depth: u8,
+        original_format: String,
+        original_size: u64,
+        stored_size: u64,
+    ) -> Self {
+        Self {
+            width,
+            height,
+            color_type,
+            bit_depth,
+            original_format,
+            original_size,
+            stored_size,
+            filename: None,
+        }
+    }
+
+    /// Describes a decoded image; the bit depth is per channel.
+    pub fn from_decoded(
+        image: &DynamicImage,
+        original_format: Option<ImageFormat>,
+        original_size: u64,
+        stored_size: u64,
+    ) -> Self {
+        let color = image.color();
+        let bit_depth = color.bits_per_pixel() / color.channel_count() as u16;
+        let original_format = original_format
+            .map(|format| format!("{:?}", format).to_lowercase())
+            .unwrap_or_else(|| "unknown".to_string());
+        Self::new(
+            image.width(),
+            image.height(),
+            format!("{:?}", color),
+            bit_depth as u8,
+            original_format,
+            original_size,
+            stored_size,
+        )
+    }
+
+    pub fn with_filename(mut self, filename: String) -> Self {
+        self.filename = Some(filename);
+        self
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    pub fn color_type(&self) -> &str {
+        self.color_type.as_ref()
+    }
+
+    pub fn bit_depth(&self) -> u8 {
+        self.bit_depth
+    }
+
+    pub fn original_format(&self) -> &str {
+        self.original_format.as_ref()
+    }
+
+    pub fn original_size(&self) -> u64 {
+        self.original_size
+    }
+
+    pub fn stored_size(&self) -> u64 {
+        self.stored_size
+    }
+
+    pub fn filename(&self) -> Option<&str> {
+        self.filename.as_deref()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use image::{DynamicImage, ImageFormat};
+
+    use super::ImageMetadata;
+
+    #[test]
+    fn test_from_decoded() {
+        let image = DynamicImage::new_rgba16(4, 3);
+        let metadata = ImageMetadata::from_decoded(&image, Some(ImageFormat::Png), 100, 40)
+            .with_filename("photo.png".to_string());
+        assert_eq!(metadata.width(), 4);
+        assert_eq!(metadata.height(), 3);
+        assert_eq!(metadata.color_type(), "Rgba16");
+        assert_eq!(metadata.bit_depth(), 16);
+        assert_eq!(metadata.original_format(), "png");
+        assert_eq!(metadata.original_size(), 100);
+        assert_eq!(metadata.stored_size(), 40);
+        assert_eq!(metadata.filename(), Some("photo.png"));
+    }
+
+    #[test]
+    fn test_from_decoded_unknown_format() {
+        let image = DynamicImage::new_luma8(1, 1);
+        let metadata = ImageMetadata::from_decoded(&image, None, 1, 1);
+        assert_eq!(metadata.bit_depth(), 8);
+        assert_eq!(metadata.original_format(), "unknown");
+        assert_eq!(metadata.filename(), None);
+    }
+}
@@ -0,0 +1,47 @@
+use std::{fmt::Display, str::FromStr};
+
+/// Whether the file behind an image row was found intact the last time the
+/// storage was checked.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ImageStatus {
+    #[default]
+    Ok,
+    Missing,
+    /// The file no longer matches its checksum or does not decode.
+    Corrupted,
+}
+
+impl ImageStatus {
+    pub const ALL: [ImageStatus; 3] = [
+        ImageStatus::Ok,
+        ImageStatus::Missing,
+        ImageStatus::Corrupted,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ImageStatus::Ok => "ok",
+            ImageStatus::Missing => "missing",
+            ImageStatus::Corrupted => "corrupted",
+        }
+    }
+}
+
+impl Display for ImageStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for ImageStatus {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "ok" => Ok(ImageStatus::Ok),
+            "missing" => Ok(ImageStatus::Missing),
+            "corrupted" => Ok(ImageStatus::Corrupted),
+            _ => Err(format!("Unknown image status: {}", s)),
+        }
+    }
+}
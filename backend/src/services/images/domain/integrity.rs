@@ -0,0 +1,16 @@
+use super::{image::Image, image_status::ImageStatus};
+
+/// Outcome of one scrubbing pass.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct ScrubSummary {
+    pub checked: usize,
+    pub missing: usize,
+    pub corrupted: usize,
+}
+
+/// How many images are in each status, with every corrupted one.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct IntegritySummary {
+    pub counts: Vec<(ImageStatus, i64)>,
+    pub corrupted: Vec<Image>,
+}
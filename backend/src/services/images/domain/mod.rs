@@ -1 +1,20 @@
+pub mod album;
+pub mod api_key;
+pub mod bk_tree;
+pub mod consistency;
+pub mod content_format;
+pub mod cursor;
+pub mod exif;
 pub mod image;
+pub mod image_metadata;
+pub mod image_status;
+pub mod integrity;
+pub mod perceptual_hash;
+pub mod rendition;
+pub mod search;
+pub mod share;
+pub mod similar_image;
+pub mod sort_key;
+pub mod storage_layout;
+pub mod tag;
+pub mod user;
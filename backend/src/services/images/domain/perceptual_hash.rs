@@ -0,0 +1,63 @@
+use image::{imageops::FilterType, DynamicImage};
+
+/// Maximum Hamming distance between two 64 bit perceptual hashes.
+pub const MAX_HAMMING_DISTANCE: u32 = 64;
+
+/// Difference hash (dHash): the image is reduced to a 9x8 grayscale grid and
+/// every bit records whether a pixel is darker than its right neighbour.
+/// Re-encoded or resized copies of a picture end up a few bits apart.
+pub fn dhash(image: &DynamicImage) -> u64 {
+    let small = image.resize_exact(9, 8, FilterType::Triangle).to_luma8();
+    let mut hash = 0u64;
+    for y in 0..8 {
+        for x in 0..8 {
+            hash <<= 1;
+            if small.get_pixel(x, y)[0] < small.get_pixel(x + 1, y)[0] {
+                hash |= 1;
+            }
+        }
+    }
+    hash
+}
+
+pub fn hamming_distance(a: u64, b: u64) -> u32 {
+    (a ^ b).count_ones()
+}
+
+#[cfg(test)]
+mod tests {
+    use image::{DynamicImage, ImageBuffer, Rgb};
+
+    use super::*;
+
+    fn gradient(width: u32, height: u32) -> DynamicImage {
+        let buffer = ImageBuffer::from_fn(width, height, |x, y| {
+            let value = ((x * 255) / width) as u8;
+            Rgb([value, value / 2, ((y * 255) / height) as u8])
+        });
+        DynamicImage::ImageRgb8(buffer)
+    }
+
+    #[test]
+    fn test_resized_copy_is_close() {
+        let original = gradient(64, 48);
+        let resized = original.resize(32, 24, FilterType::Nearest);
+        let distance = hamming_distance(dhash(&original), dhash(&resized));
+        assert!(distance <= 4, "distance was {}", distance);
+    }
+
+    #[test]
+    fn test_different_images_are_far() {
+        let original = gradient(64, 48);
+        let flipped = original.fliph();
+        let distance = hamming_distance(dhash(&original), dhash(&flipped));
+        assert!(distance > 16, "distance was {}", distance);
+    }
+
+    #[test]
+    fn test_hamming_distance() {
+        assert_eq!(hamming_distance(0, 0), 0);
+        assert_eq!(hamming_distance(0b1011, 0b0001), 2);
+        assert_eq!(hamming_distance(0, u64::MAX), MAX_HAMMING_DISTANCE);
+    }
+}
@@ -0,0 +1,154 @@
+use std::{fmt::Display, io::Cursor, path::Path, str::FromStr};
+
+use image::{DynamicImage, ImageError};
+
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Hash)]
+pub enum RenditionSize {
+    Thumb,
+    Preview,
+    Large,
+}
+
+impl RenditionSize {
+    pub const ALL: [RenditionSize; 3] = [
+        RenditionSize::Thumb,
+        RenditionSize::Preview,
+        RenditionSize::Large,
+    ];
+
+    /// Length in pixels of the longest edge of the rendition.
+    pub fn long_edge(&self) -> u32 {
+        match self {
+            RenditionSize::Thumb => 128,
+            RenditionSize::Preview => 512,
+            RenditionSize::Large => 2048,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RenditionSize::Thumb => "thumb",
+            RenditionSize::Preview => "preview",
+            RenditionSize::Large => "large",
+        }
+    }
+
+    /// Scales `image` down so that its longest edge fits the rendition.
+    /// Images that are already small enough are never upscaled.
+    pub fn render(&self, image: &DynamicImage) -> DynamicImage {
+        let edge = self.long_edge();
+        if image.width() <= edge && image.height() <= edge {
+            return image.clone();
+        }
+        image.thumbnail(edge, edge)
+    }
+
+    /// Renders and QOI-encodes `image` for this size.
+    pub fn encode(&self, image: &DynamicImage) -> Result<Vec<u8>, ImageError> {
+        let mut bytes = vec![];
+        self.render(image)
+            .write_to(&mut Cursor::new(&mut bytes), image::ImageOutputFormat::Qoi)?;
+        Ok(bytes)
+    }
+
+    /// Renditions live next to the original: `dir/<stem>_<size>.qoi`.
+    pub fn path_for(&self, original: &str) -> String {
+        let original = Path::new(original);
+        let stem = original
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().to_string())
+            .unwrap_or_default();
+        original
+            .with_file_name(format!("{}_{}", stem, self.as_str()))
+            .with_extension("qoi")
+            .to_string_lossy()
+            .to_string()
+    }
+}
+
+impl Display for RenditionSize {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for RenditionSize {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "thumb" => Ok(RenditionSize::Thumb),
+            "preview" => Ok(RenditionSize::Preview),
+            "large" => Ok(RenditionSize::Large),
+            _ => Err(format!("Unknown rendition size: {}", s)),
+        }
+    }
+}
+
+#[derive(PartialEq, Debug, Clone)]
+pub struct Rendition {
+    image_id: i64,
+    size: RenditionSize,
+    path: String,
+}
+
+impl Rendition {
+    pub fn new(image_id: i64, size: RenditionSize, path: String) -> Self {
+        Self {
+            image_id,
+            size,
+            path,
+        }
+    }
+
+    pub fn image_id(&self) -> i64 {
+        self.image_id
+    }
+
+    pub fn size(&self) -> RenditionSize {
+        self.size
+    }
+
+    pub fn path(&self) -> &str {
+        self.path.as_ref()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use image::{DynamicImage, GenericImageView};
+
+    use super::*;
+
+    #[test]
+    fn test_render_keeps_aspect_ratio() {
+        let image = DynamicImage::new_rgb8(1024, 256);
+        let thumb = RenditionSize::Thumb.render(&image);
+        assert_eq!(thumb.dimensions(), (128, 32));
+        let preview = RenditionSize::Preview.render(&image);
+        assert_eq!(preview.dimensions(), (512, 128));
+    }
+
+    #[test]
+    fn test_render_does_not_upscale() {
+        let image = DynamicImage::new_rgb8(100, 300);
+        let large = RenditionSize::Large.render(&image);
+        assert_eq!(large.dimensions(), (100, 300));
+    }
+
+    #[test]
+    fn test_path_for() {
+        assert_eq!(
+            RenditionSize::Thumb.path_for("data/abcdef.qoi"),
+            "data/abcdef_thumb.qoi"
+        );
+        assert_eq!(RenditionSize::Large.path_for("abc.qoi"), "abc_large.qoi");
+    }
+
+    #[test]
+    fn test_from_str() {
+        assert_eq!("thumb".parse(), Ok(RenditionSize::Thumb));
+        assert_eq!("Preview".parse(), Ok(RenditionSize::Preview));
+        assert!("huge".parse::<RenditionSize>().is_err());
+    }
+}
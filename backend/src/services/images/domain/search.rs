@@ -0,0 +1,74 @@
+use std::str::FromStr;
+
+use chrono::{DateTime, Utc};
+
+use super::{
+    sort_key::{SortDirection, SortKey},
+    tag::TagMatch,
+};
+
+/// Aspect of an image, from its stored width and height.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Shape {
+    Landscape,
+    Portrait,
+    Square,
+}
+
+impl FromStr for Shape {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "landscape" => Ok(Shape::Landscape),
+            "portrait" => Ok(Shape::Portrait),
+            "square" => Ok(Shape::Square),
+            _ => Err(format!("Unknown orientation: {}", s)),
+        }
+    }
+}
+
+/// Filters combined with AND by an image search. Unset filters match every
+/// image; ranges are inclusive.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchQuery {
+    pub created_after: Option<DateTime<Utc>>,
+    pub created_before: Option<DateTime<Utc>>,
+    pub captured_after: Option<DateTime<Utc>>,
+    pub captured_before: Option<DateTime<Utc>>,
+    pub min_width: Option<u32>,
+    pub max_width: Option<u32>,
+    pub min_height: Option<u32>,
+    pub max_height: Option<u32>,
+    pub shape: Option<Shape>,
+    pub original_format: Option<String>,
+    pub tags: Vec<String>,
+    pub tag_match: TagMatch,
+    pub sort: SortKey,
+    pub direction: SortDirection,
+    pub count: i64,
+    pub offset: i64,
+}
+
+impl Default for SearchQuery {
+    fn default() -> Self {
+        Self {
+            created_after: None,
+            created_before: None,
+            captured_after: None,
+            captured_before: None,
+            min_width: None,
+            max_width: None,
+            min_height: None,
+            max_height: None,
+            shape: None,
+            original_format: None,
+            tags: vec![],
+            tag_match: TagMatch::default(),
+            sort: SortKey::default(),
+            direction: SortDirection::default(),
+            count: 50,
+            offset: 0,
+        }
+    }
+}
@@ -0,0 +1,126 @@
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+/// How long a share link stays valid unless the request says otherwise.
+pub const DEFAULT_SHARE_SECONDS: i64 = 24 * 60 * 60;
+pub const MAX_SHARE_SECONDS: i64 = 30 * 24 * 60 * 60;
+
+/// The hex HMAC-SHA256 of a share link. It covers the share, the image and
+/// the expiry, so none of them can be changed in the URL.
+pub fn sign_share(key: &[u8], share_id: i64, image_id: i64, expires: i64) -> String {
+    hex::encode(
+        share_mac(key, share_id, image_id, expires)
+            .finalize()
+            .into_bytes(),
+    )
+}
+
+/// Compares in constant time.
+pub fn verify_share(
+    key: &[u8],
+    share_id: i64,
+    image_id: i64,
+    expires: i64,
+    signature: &str,
+) -> bool {
+    match hex::decode(signature) {
+        Ok(signature) => share_mac(key, share_id, image_id, expires)
+            .verify_slice(&signature)
+            .is_ok(),
+        Err(_) => false,
+    }
+}
+
+fn share_mac(key: &[u8], share_id: i64, image_id: i64, expires: i64) -> Hmac<Sha256> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts any key length");
+    mac.update(format!("{}:{}:{}", share_id, image_id, expires).as_bytes());
+    mac
+}
+
+#[derive(PartialEq, Debug, Clone)]
+pub struct Share {
+    id: i64,
+    image_id: i64,
+    created_on: DateTime<Utc>,
+    expires_on: DateTime<Utc>,
+    revoked_on: Option<DateTime<Utc>>,
+}
+
+impl Share {
+    pub fn new(
+        id: i64,
+        image_id: i64,
+        created_on: DateTime<Utc>,
+        expires_on: DateTime<Utc>,
+    ) -> Self {
+        Self {
+            id,
+            image_id,
+            created_on,
+            expires_on,
+            revoked_on: None,
+        }
+    }
+
+    pub fn with_revoked_on(mut self, revoked_on: DateTime<Utc>) -> Self {
+        self.revoked_on = Some(revoked_on);
+        self
+    }
+
+    pub fn id(&self) -> i64 {
+        self.id
+    }
+
+    pub fn image_id(&self) -> i64 {
+        self.image_id
+    }
+
+    pub fn created_on(&self) -> DateTime<Utc> {
+        self.created_on
+    }
+
+    pub fn expires_on(&self) -> DateTime<Utc> {
+        self.expires_on
+    }
+
+    pub fn revoked_on(&self) -> Option<DateTime<Utc>> {
+        self.revoked_on
+    }
+
+    /// Links carry the expiry as unix seconds.
+    pub fn expires(&self) -> i64 {
+        self.expires_on.timestamp()
+    }
+
+    pub fn is_valid_at(&self, now: DateTime<Utc>) -> bool {
+        self.revoked_on.is_none() && self.expires_on > now
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, Utc};
+
+    use super::*;
+
+    #[test]
+    fn test_signatures() {
+        let signature = sign_share(b"key", 1, 2, 1700000000);
+        assert_eq!(signature.len(), 64);
+        assert!(verify_share(b"key", 1, 2, 1700000000, &signature));
+        assert!(!verify_share(b"other", 1, 2, 1700000000, &signature));
+        assert!(!verify_share(b"key", 1, 3, 1700000000, &signature));
+        assert!(!verify_share(b"key", 1, 2, 1700000001, &signature));
+        assert!(!verify_share(b"key", 1, 2, 1700000000, "not hex"));
+    }
+
+    #[test]
+    fn test_validity() {
+        let now = Utc::now();
+        let share = Share::new(1, 2, now, now + Duration::hours(1));
+        assert!(share.is_valid_at(now));
+        assert!(!share.is_valid_at(now + Duration::hours(2)));
+        assert!(!share.with_revoked_on(now).is_valid_at(now));
+    }
+}
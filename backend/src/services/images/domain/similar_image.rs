@@ -0,0 +1,21 @@
+use super::image::Image;
+
+#[derive(PartialEq, Debug, Clone)]
+pub struct SimilarImage {
+    image: Image,
+    distance: u32,
+}
+
+impl SimilarImage {
+    pub fn new(image: Image, distance: u32) -> Self {
+        Self { image, distance }
+    }
+
+    pub fn image(&self) -> &Image {
+        &self.image
+    }
+
+    pub fn distance(&self) -> u32 {
+        self.distance
+    }
+}
@@ -0,0 +1,59 @@
+use std::{fmt::Display, str::FromStr};
+
+/// Order in which image listings are returned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SortKey {
+    #[default]
+    UpdatedOn,
+    CreatedOn,
+    /// EXIF capture time; images without one are listed last.
+    CapturedOn,
+}
+
+impl SortKey {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SortKey::UpdatedOn => "updated_on",
+            SortKey::CreatedOn => "created_on",
+            SortKey::CapturedOn => "captured_on",
+        }
+    }
+}
+
+impl Display for SortKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for SortKey {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "updated_on" => Ok(SortKey::UpdatedOn),
+            "created_on" => Ok(SortKey::CreatedOn),
+            "captured_on" => Ok(SortKey::CapturedOn),
+            _ => Err(format!("Unknown sort key: {}", s)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SortDirection {
+    #[default]
+    Ascending,
+    Descending,
+}
+
+impl FromStr for SortDirection {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "asc" => Ok(SortDirection::Ascending),
+            "desc" => Ok(SortDirection::Descending),
+            _ => Err(format!("Unknown sort direction: {}", s)),
+        }
+    }
+}
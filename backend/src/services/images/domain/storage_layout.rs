@@ -0,0 +1,139 @@
+use std::{
+    fmt::Display,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+/// Characters of the file name used for each directory level.
+const SHARD_WIDTH: usize = 2;
+const SHARD_DEPTH: usize = 2;
+
+/// How files are spread below their directory. `Sharded` nests each file in
+/// directories named after the start of its file name, the content hash for
+/// images, so `data/abcdef.qoi` goes to `data/ab/cd/abcdef.qoi`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum StorageLayout {
+    #[default]
+    Flat,
+    Sharded,
+}
+
+/// Every file recorded for an image: the QOI file, the original if kept,
+/// and the renditions.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImagePaths {
+    pub image_id: i64,
+    pub paths: Vec<String>,
+}
+
+/// Outcome of moving the stored files into the configured layout.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct LayoutMigration {
+    pub layout: StorageLayout,
+    /// Images with at least one file moved.
+    pub images: usize,
+    pub files: usize,
+    /// Images left where they were because moving them failed.
+    pub failed: usize,
+}
+
+impl StorageLayout {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            StorageLayout::Flat => "flat",
+            StorageLayout::Sharded => "sharded",
+        }
+    }
+
+    /// Where `path` belongs in this layout, wherever it is now.
+    pub fn place(&self, path: &str) -> String {
+        let (directory, file_name) = match Self::unsharded(Path::new(path)) {
+            Some(flat) => flat,
+            None => return path.to_string(),
+        };
+        let mut placed = directory;
+        if *self == StorageLayout::Sharded {
+            if let Some(shards) = Self::shards(&file_name) {
+                placed.extend(shards);
+            }
+        }
+        placed.join(file_name).to_string_lossy().to_string()
+    }
+
+    fn shards(file_name: &str) -> Option<Vec<&str>> {
+        let prefix = file_name.get(..SHARD_WIDTH * SHARD_DEPTH)?;
+        if !prefix.chars().all(|c| c.is_ascii_alphanumeric()) {
+            return None;
+        }
+        Some(
+            (0..SHARD_DEPTH)
+                .map(|level| &prefix[level * SHARD_WIDTH..(level + 1) * SHARD_WIDTH])
+                .collect(),
+        )
+    }
+
+    /// The flat directory and the file name of `path`, with the shard
+    /// directories dropped if it has them.
+    fn unsharded(path: &Path) -> Option<(PathBuf, String)> {
+        let file_name = path.file_name()?.to_string_lossy().to_string();
+        let directory = path.parent().unwrap_or(Path::new(""));
+        if let Some(shards) = Self::shards(&file_name) {
+            let mut ancestors = directory.ancestors();
+            let sharded = shards
+                .iter()
+                .rev()
+                .all(|shard| ancestors.next().and_then(Path::file_name) == Some(shard.as_ref()));
+            if let (true, Some(flat)) = (sharded, ancestors.next()) {
+                return Some((flat.to_path_buf(), file_name));
+            }
+        }
+        Some((directory.to_path_buf(), file_name))
+    }
+}
+
+impl Display for StorageLayout {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for StorageLayout {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "flat" => Ok(StorageLayout::Flat),
+            "sharded" => Ok(StorageLayout::Sharded),
+            _ => Err(format!("Unknown storage layout: {}", s)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::StorageLayout;
+
+    #[rstest]
+    #[case(StorageLayout::Sharded, "data/abcdef.qoi", "data/ab/cd/abcdef.qoi")]
+    #[case(
+        StorageLayout::Sharded,
+        "data/ab/cd/abcdef.qoi",
+        "data/ab/cd/abcdef.qoi"
+    )]
+    #[case(
+        StorageLayout::Sharded,
+        "data/abcdef_thumb.qoi",
+        "data/ab/cd/abcdef_thumb.qoi"
+    )]
+    #[case(StorageLayout::Sharded, "/var/abcdef.png", "/var/ab/cd/abcdef.png")]
+    #[case(StorageLayout::Sharded, "abcdef.qoi", "ab/cd/abcdef.qoi")]
+    #[case(StorageLayout::Sharded, "data/abc.qoi", "data/abc.qoi")]
+    #[case(StorageLayout::Flat, "data/ab/cd/abcdef.qoi", "data/abcdef.qoi")]
+    #[case(StorageLayout::Flat, "data/xy/cd/abcdef.qoi", "data/xy/cd/abcdef.qoi")]
+    #[case(StorageLayout::Flat, "data/abcdef.qoi", "data/abcdef.qoi")]
+    fn test_place(#[case] layout: StorageLayout, #[case] path: &str, #[case] expected: &str) {
+        assert_eq!(layout.place(path), expected);
+    }
+}
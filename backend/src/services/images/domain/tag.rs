@@ -0,0 +1,75 @@
+use std::str::FromStr;
+
+pub const MAX_TAG_LENGTH: usize = 64;
+
+/// A tag name together with the number of images using it.
+#[derive(PartialEq, Debug, Clone)]
+pub struct Tag {
+    name: String,
+    count: i64,
+}
+
+impl Tag {
+    pub fn new(name: String, count: i64) -> Self {
+        Self { name, count }
+    }
+
+    pub fn name(&self) -> &str {
+        self.name.as_ref()
+    }
+
+    pub fn count(&self) -> i64 {
+        self.count
+    }
+}
+
+/// Tags are stored trimmed and in lower case. Commas are rejected since
+/// they separate tags in query strings.
+pub fn normalize_tag(tag: &str) -> Result<String, String> {
+    let tag = tag.trim().to_lowercase();
+    if tag.is_empty() || tag.chars().count() > MAX_TAG_LENGTH || tag.contains(',') {
+        return Err(format!("Invalid tag: {:?}", tag));
+    }
+    Ok(tag)
+}
+
+/// Whether an image needs every requested tag or any one of them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TagMatch {
+    #[default]
+    All,
+    Any,
+}
+
+impl FromStr for TagMatch {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "all" => Ok(TagMatch::All),
+            "any" => Ok(TagMatch::Any),
+            _ => Err(format!("Unknown tag match: {}", s)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{normalize_tag, TagMatch};
+
+    #[test]
+    fn test_normalize_tag() {
+        assert_eq!(normalize_tag("  Beach "), Ok("beach".to_string()));
+        assert_eq!(normalize_tag("ÉTÉ"), Ok("été".to_string()));
+        assert!(normalize_tag("   ").is_err());
+        assert!(normalize_tag("a,b").is_err());
+        assert!(normalize_tag(&"x".repeat(65)).is_err());
+    }
+
+    #[test]
+    fn test_tag_match_from_str() {
+        assert_eq!("all".parse::<TagMatch>(), Ok(TagMatch::All));
+        assert_eq!("any".parse::<TagMatch>(), Ok(TagMatch::Any));
+        assert!("some".parse::<TagMatch>().is_err());
+    }
+}
@@ -0,0 +1,102 @@
+use std::future::Future;
+
+use argon2::{
+    password_hash::{rand_core::OsRng, PasswordHash, PasswordHasher, PasswordVerifier, SaltString},
+    Argon2,
+};
+use chrono::{DateTime, Utc};
+
+use super::api_key::Scope;
+
+pub const MAX_USER_NAME_LENGTH: usize = 64;
+pub const MIN_PASSWORD_LENGTH: usize = 8;
+/// Days a login session stays valid.
+pub const SESSION_DAYS: i64 = 30;
+/// What a logged in user may do with their own images.
+pub const USER_SCOPES: [Scope; 3] = [Scope::Read, Scope::Upload, Scope::Delete];
+
+tokio::task_local! {
+    static CURRENT_USER: i64;
+}
+
+/// Runs `f` on behalf of a user: while it runs the storage adapters only
+/// see, change and create images and albums owned by `user_id`.
+pub async fn on_behalf_of<F: Future>(user_id: i64, f: F) -> F::Output {
+    CURRENT_USER.scope(user_id, f).await
+}
+
+/// The user the current request runs for; None for API keys and background
+/// jobs, which see everything.
+pub fn current_user_id() -> Option<i64> {
+    CURRENT_USER.try_with(|user_id| *user_id).ok()
+}
+
+/// An argon2 PHC string with a random salt.
+pub fn hash_password(password: &str) -> Result<String, String> {
+    Argon2::default()
+        .hash_password(password.as_bytes(), &SaltString::generate(&mut OsRng))
+        .map(|hash| hash.to_string())
+        .map_err(|e| e.to_string())
+}
+
+pub fn verify_password(password: &str, password_hash: &str) -> bool {
+    PasswordHash::new(password_hash)
+        .map(|hash| {
+            Argon2::default()
+                .verify_password(password.as_bytes(), &hash)
+                .is_ok()
+        })
+        .unwrap_or(false)
+}
+
+#[derive(PartialEq, Debug, Clone)]
+pub struct User {
+    id: i64,
+    name: String,
+    created_on: DateTime<Utc>,
+}
+
+impl User {
+    pub fn new(id: i64, name: String, created_on: DateTime<Utc>) -> Self {
+        Self {
+            id,
+            name,
+            created_on,
+        }
+    }
+
+    pub fn id(&self) -> i64 {
+        self.id
+    }
+
+    pub fn name(&self) -> &str {
+        self.name.as_ref()
+    }
+
+    pub fn created_on(&self) -> DateTime<Utc> {
+        self.created_on
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{current_user_id, hash_password, on_behalf_of, verify_password};
+
+    #[test]
+    fn test_passwords() {
+        let hash = hash_password("correct horse").unwrap();
+        assert!(hash.starts_with("$argon2"));
+        assert_ne!(hash, hash_password("correct horse").unwrap());
+        assert!(verify_password("correct horse", &hash));
+        assert!(!verify_password("battery staple", &hash));
+        assert!(!verify_password("correct horse", "not a hash"));
+    }
+
+    #[tokio::test]
+    async fn test_on_behalf_of() {
+        assert_eq!(current_user_id(), None);
+        let user_id = on_behalf_of(7, async { current_user_id() }).await;
+        assert_eq!(user_id, Some(7));
+        assert_eq!(current_user_id(), None);
+    }
+}
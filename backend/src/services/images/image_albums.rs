@@ -0,0 +1,248 @@
+use async_trait::async_trait;
+use tracing::error;
+
+use super::{
+    domain::{
+        album::{normalize_album_name, Album, AlbumUpdate},
+        image::Image,
+    },
+    ports::{
+        incoming::albums_service::{AlbumsService, AlbumsServiceError},
+        outgoing::albums_port::{AlbumsError, AlbumsPort},
+    },
+};
+
+const MAX_IMAGES: i64 = 50;
+
+impl From<AlbumsError> for AlbumsServiceError {
+    fn from(value: AlbumsError) -> Self {
+        match value {
+            AlbumsError::AlbumNotFound => AlbumsServiceError::AlbumNotFound,
+            AlbumsError::ImageNotFound => AlbumsServiceError::ImageNotFound,
+            AlbumsError::InvalidParent | AlbumsError::InvalidOrder => {
+                AlbumsServiceError::InvalidRequest(value.to_string())
+            }
+            AlbumsError::InternalError => AlbumsServiceError::InternalError,
+        }
+    }
+}
+
+pub struct ImageAlbums<Storage>
+where
+    Storage: AlbumsPort + Send + Sync,
+{
+    storage: Storage,
+}
+
+#[async_trait]
+impl<Storage> AlbumsService for ImageAlbums<Storage>
+where
+    Storage: AlbumsPort + Send + Sync,
+{
+    async fn create_album(
+        &self,
+        name: String,
+        parent_id: Option<i64>,
+    ) -> Result<Album, AlbumsServiceError> {
+        let name = normalize_album_name(&name).map_err(AlbumsServiceError::InvalidRequest)?;
+        self.storage
+            .create_album(&name, parent_id)
+            .await
+            .map_err(|err| err.into())
+    }
+
+    async fn album(&self, id: i64) -> Result<Album, AlbumsServiceError> {
+        self.storage.query_album(id).await.map_err(|err| err.into())
+    }
+
+    async fn albums(&self, parent_id: Option<i64>) -> Result<Vec<Album>, AlbumsServiceError> {
+        self.storage
+            .query_albums(parent_id)
+            .await
+            .map_err(|err| err.into())
+    }
+
+    async fn update_album(
+        &self,
+        id: i64,
+        mut update: AlbumUpdate,
+    ) -> Result<Album, AlbumsServiceError> {
+        if let Some(name) = update.name {
+            update.name =
+                Some(normalize_album_name(&name).map_err(AlbumsServiceError::InvalidRequest)?);
+        }
+        if update.parent_id == Some(Some(id)) {
+            return Err(AlbumsError::InvalidParent.into());
+        }
+        self.storage
+            .update_album(id, &update)
+            .await
+            .map_err(|err| err.into())
+    }
+
+    async fn delete_album(&self, id: i64, cascade: bool) -> Result<(), AlbumsServiceError> {
+        self.storage
+            .delete_album(id, cascade)
+            .await
+            .map_err(|err| err.into())
+    }
+
+    async fn add_images(
+        &self,
+        album_id: i64,
+        mut image_ids: Vec<i64>,
+    ) -> Result<(), AlbumsServiceError> {
+        if image_ids.is_empty() {
+            return Err(AlbumsServiceError::InvalidRequest(
+                "No images given".to_string(),
+            ));
+        }
+        // Keep the first occurrence so the requested order is preserved.
+        let mut seen = std::collections::HashSet::new();
+        image_ids.retain(|id| seen.insert(*id));
+        self.storage
+            .add_images(album_id, &image_ids)
+            .await
+            .map_err(|err| err.into())
+    }
+
+    async fn remove_image(&self, album_id: i64, image_id: i64) -> Result<(), AlbumsServiceError> {
+        self.storage
+            .remove_image(album_id, image_id)
+            .await
+            .map_err(|err| err.into())
+    }
+
+    async fn reorder_images(
+        &self,
+        album_id: i64,
+        image_ids: Vec<i64>,
+    ) -> Result<(), AlbumsServiceError> {
+        self.storage
+            .reorder_images(album_id, &image_ids)
+            .await
+            .map_err(|err| err.into())
+    }
+
+    async fn album_images(
+        &self,
+        album_id: i64,
+        count: i64,
+        offset: i64,
+    ) -> Result<Vec<Image>, AlbumsServiceError> {
+        if count <= 0 || offset < 0 {
+            return Err(AlbumsServiceError::InvalidRequest(
+                "Count or offset are below zero".to_string(),
+            ));
+        }
+        if count > MAX_IMAGES {
+            error!("Error too many images request amount {}", count);
+            return Err(AlbumsServiceError::TooManyImagesRequested);
+        }
+        self.storage
+            .query_album_images(album_id, count, offset)
+            .await
+            .map_err(|err| err.into())
+    }
+}
+
+impl<Storage> ImageAlbums<Storage>
+where
+    Storage: AlbumsPort + Send + Sync,
+{
+    pub fn new(storage: Storage) -> Self {
+        Self { storage }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use async_trait::async_trait;
+    use chrono::Utc;
+    use mockall::{mock, predicate};
+
+    use crate::services::images::{
+        domain::{
+            album::{Album, AlbumUpdate},
+            image::Image,
+        },
+        image_albums::ImageAlbums,
+        ports::{
+            incoming::albums_service::{AlbumsService, AlbumsServiceError},
+            outgoing::albums_port::{AlbumsError, AlbumsPort},
+        },
+    };
+
+    mock! {
+        DS {}
+        #[async_trait]
+        impl AlbumsPort for DS {
+            async fn create_album(&self, name: &str, parent_id: Option<i64>) -> Result<Album, AlbumsError>;
+            async fn query_album(&self, id: i64) -> Result<Album, AlbumsError>;
+            async fn query_albums(&self, parent_id: Option<i64>) -> Result<Vec<Album>, AlbumsError>;
+            async fn update_album(&self, id: i64, update: &AlbumUpdate) -> Result<Album, AlbumsError>;
+            async fn delete_album(&self, id: i64, cascade: bool) -> Result<(), AlbumsError>;
+            async fn add_images(&self, album_id: i64, image_ids: &[i64]) -> Result<(), AlbumsError>;
+            async fn remove_image(&self, album_id: i64, image_id: i64) -> Result<(), AlbumsError>;
+            async fn reorder_images(&self, album_id: i64, image_ids: &[i64]) -> Result<(), AlbumsError>;
+            async fn query_album_images(&self, album_id: i64, count: i64, offset: i64) -> Result<Vec<Image>, AlbumsError>;
+        }
+    }
+
+    #[tokio::test]
+    async fn test_create_album_trims_name() {
+        let now = Utc::now();
+        let mut mock = MockDS::new();
+        mock.expect_create_album()
+            .with(predicate::eq("Holidays"), predicate::eq(Some(1)))
+            .returning(move |name, _p| Ok(Album::new(2, name.to_string(), now).with_parent_id(1)));
+        let service = ImageAlbums::new(mock);
+        let result = service
+            .create_album(" Holidays ".to_string(), Some(1))
+            .await;
+        assert_eq!(
+            result,
+            Ok(Album::new(2, "Holidays".to_string(), now).with_parent_id(1))
+        );
+        let result = service.create_album("".to_string(), None).await;
+        assert!(matches!(result, Err(AlbumsServiceError::InvalidRequest(_))));
+    }
+
+    #[tokio::test]
+    async fn test_update_album_rejects_own_parent() {
+        let mock = MockDS::new();
+        let service = ImageAlbums::new(mock);
+        let update = AlbumUpdate {
+            parent_id: Some(Some(3)),
+            ..AlbumUpdate::default()
+        };
+        let result = service.update_album(3, update).await;
+        assert_eq!(
+            result,
+            Err(AlbumsServiceError::InvalidRequest(
+                "Album cannot be nested below itself".to_string()
+            ))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_add_images_dedups_in_order() {
+        let mut mock = MockDS::new();
+        mock.expect_add_images()
+            .withf(|album_id, image_ids| *album_id == 1 && image_ids == [3, 1, 2])
+            .returning(|_a, _i| Ok(()));
+        let service = ImageAlbums::new(mock);
+        let result = service.add_images(1, vec![3, 1, 3, 2, 1]).await;
+        assert_eq!(result, Ok(()));
+        let result = service.add_images(1, vec![]).await;
+        assert!(matches!(result, Err(AlbumsServiceError::InvalidRequest(_))));
+    }
+
+    #[tokio::test]
+    async fn test_album_images_too_many_images() {
+        let mock = MockDS::new();
+        let service = ImageAlbums::new(mock);
+        let result = service.album_images(1, 51, 0).await;
+        assert_eq!(result, Err(AlbumsServiceError::TooManyImagesRequested));
+    }
+}
@@ -0,0 +1,281 @@
+use std::{
+    path::Path,
+    time::{Duration, SystemTime},
+};
+
+use async_trait::async_trait;
+use tracing::error;
+
+use super::{
+    domain::{
+        consistency::{ConsistencyReport, OrphanFile},
+        image_status::ImageStatus,
+    },
+    ports::{
+        incoming::consistency_service::{ConsistencyService, ConsistencyServiceError},
+        outgoing::{
+            blob_store_port::{BlobStoreError, DynBlobStore},
+            consistency_port::{ConsistencyError, ConsistencyPort},
+        },
+    },
+};
+
+/// Files younger than this may belong to an upload that is not committed
+/// yet, so they are never reported as orphans.
+const GRACE_PERIOD: Duration = Duration::from_secs(60);
+
+impl From<ConsistencyError> for ConsistencyServiceError {
+    fn from(value: ConsistencyError) -> Self {
+        match value {
+            ConsistencyError::InternalError => ConsistencyServiceError::InternalError,
+        }
+    }
+}
+
+pub struct ImageConsistency<Storage>
+where
+    Storage: ConsistencyPort + Send + Sync,
+{
+    storage: Storage,
+    blob_store: DynBlobStore,
+    base_path: String,
+    quarantine_path: String,
+}
+
+#[async_trait]
+impl<Storage> ConsistencyService for ImageConsistency<Storage>
+where
+    Storage: ConsistencyPort + Send + Sync,
+{
+    async fn check(&self, repair: bool) -> Result<ConsistencyReport, ConsistencyServiceError> {
+        let mut report = ConsistencyReport::default();
+        for file in self.storage.query_image_files().await? {
+            let exists = self.blob_store.exists(file.path()).await.unwrap_or(false);
+            match (exists, file.status()) {
+                (false, _) => report.missing.push(file),
+                (true, ImageStatus::Missing) => report.recovered.push(file),
+                (true, _) => {}
+            }
+        }
+        let referenced = self.storage.query_referenced_paths().await?;
+        report.orphans = self
+            .stored_files()
+            .await
+            .map_err(|e| {
+                error!("Error walking {}: {}", self.base_path, e);
+                ConsistencyServiceError::InternalError
+            })?
+            .into_iter()
+            .filter(|orphan| !referenced.contains(orphan.path()))
+            .collect();
+        if repair {
+            self.repair(&report).await?;
+            report.repaired = true;
+        }
+        Ok(report)
+    }
+}
+
+impl<Storage> ImageConsistency<Storage>
+where
+    Storage: ConsistencyPort + Send + Sync,
+{
+    pub fn new(
+        storage: Storage,
+        blob_store: DynBlobStore,
+        base_path: String,
+        quarantine_path: String,
+    ) -> Self {
+        Self {
+            storage,
+            blob_store,
+            base_path,
+            quarantine_path,
+        }
+    }
+
+    /// Every settled `.qoi` file below the base path, outside the quarantine.
+    async fn stored_files(&self) -> Result<Vec<OrphanFile>, BlobStoreError> {
+        let quarantine = Path::new(&self.quarantine_path);
+        Ok(self
+            .blob_store
+            .list(&self.base_path)
+            .await?
+            .into_iter()
+            .filter(|blob| {
+                let path = Path::new(&blob.key);
+                let settled = blob
+                    .modified
+                    .and_then(|modified| SystemTime::now().duration_since(modified).ok())
+                    .is_none_or(|age| age >= GRACE_PERIOD);
+                "qoi" == path.extension().unwrap_or_default()
+                    && !path.starts_with(quarantine)
+                    && settled
+            })
+            .map(|blob| OrphanFile::new(blob.key, blob.size))
+            .collect())
+    }
+
+    async fn repair(&self, report: &ConsistencyReport) -> Result<(), ConsistencyServiceError> {
+        let mut err: Option<ConsistencyServiceError> = None;
+        for orphan in &report.orphans {
+            let path = Path::new(orphan.path());
+            let target = Path::new(&self.quarantine_path)
+                .join(path.strip_prefix(&self.base_path).unwrap_or(path));
+            let moved = self
+                .blob_store
+                .rename(orphan.path(), &target.to_string_lossy())
+                .await;
+            if let Err(e) = moved {
+                error!("Error quarantining file {}: {}", orphan.path(), e);
+                err = Some(ConsistencyServiceError::InternalError);
+            }
+        }
+        let missing = report
+            .missing
+            .iter()
+            .filter(|file| file.status() != ImageStatus::Missing)
+            .map(|file| file.image_id())
+            .collect::<Vec<i64>>();
+        if !missing.is_empty() {
+            self.storage
+                .update_image_status(&missing, ImageStatus::Missing)
+                .await?;
+        }
+        let recovered = report
+            .recovered
+            .iter()
+            .map(|file| file.image_id())
+            .collect::<Vec<i64>>();
+        if !recovered.is_empty() {
+            self.storage
+                .update_image_status(&recovered, ImageStatus::Ok)
+                .await?;
+        }
+        match err {
+            Some(error) => Err(error),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashSet, sync::Arc, time::SystemTime};
+
+    use async_trait::async_trait;
+    use mockall::{mock, predicate};
+
+    use crate::{
+        data_storage::blobs::memory_blob_store::MemoryBlobStore,
+        services::images::{
+            domain::{consistency::ImageFile, image_status::ImageStatus},
+            image_consistency::ImageConsistency,
+            ports::{
+                incoming::consistency_service::ConsistencyService,
+                outgoing::consistency_port::{ConsistencyError, ConsistencyPort},
+            },
+        },
+    };
+
+    mock! {
+        DS {}
+        #[async_trait]
+        impl ConsistencyPort for DS {
+            async fn query_image_files(&self) -> Result<Vec<ImageFile>, ConsistencyError>;
+            async fn query_referenced_paths(&self) -> Result<HashSet<String>, ConsistencyError>;
+            async fn update_image_status(&self, indexes: &[i64], status: ImageStatus) -> Result<(), ConsistencyError>;
+        }
+    }
+
+    /// Stores `key` backdated past the grace period.
+    fn settled_file(blob_store: &MemoryBlobStore, key: &str) -> String {
+        blob_store.insert(
+            key,
+            "some content",
+            SystemTime::now() - super::GRACE_PERIOD * 2,
+        );
+        key.to_string()
+    }
+
+    fn mock(files: Vec<ImageFile>, referenced: Vec<String>) -> MockDS {
+        let mut mock = MockDS::new();
+        mock.expect_query_image_files()
+            .returning(move || Ok(files.clone()));
+        mock.expect_query_referenced_paths()
+            .returning(move || Ok(referenced.iter().cloned().collect()));
+        mock
+    }
+
+    #[tokio::test]
+    async fn test_check_reports_missing_and_orphan_files() {
+        let blob_store = Arc::new(MemoryBlobStore::new());
+        let kept = settled_file(&blob_store, "data/kept.qoi");
+        let orphan = settled_file(&blob_store, "data/orphan.qoi");
+        settled_file(&blob_store, "data/original.png");
+        blob_store.insert("data/uploading.qoi", "some content", SystemTime::now());
+        let missing = "data/missing.qoi".to_string();
+        let files = vec![
+            ImageFile::new(1, kept.clone(), ImageStatus::Ok),
+            ImageFile::new(2, missing.clone(), ImageStatus::Ok).with_size(7),
+        ];
+        let mut mock = mock(files, vec![kept]);
+        mock.expect_update_image_status().never();
+        let service = ImageConsistency::new(
+            mock,
+            blob_store,
+            "data".to_string(),
+            "data/quarantine".to_string(),
+        );
+        let report = service.check(false).await.unwrap();
+        assert_eq!(
+            report.missing,
+            vec![ImageFile::new(2, missing, ImageStatus::Ok).with_size(7)]
+        );
+        assert_eq!(report.orphans.len(), 1);
+        assert_eq!(report.orphans[0].path(), orphan);
+        assert_eq!(report.orphans[0].size(), 12);
+        assert!(!report.repaired);
+    }
+
+    #[tokio::test]
+    async fn test_repair_quarantines_orphans_and_marks_rows() {
+        let blob_store = Arc::new(MemoryBlobStore::new());
+        let recovered = settled_file(&blob_store, "data/recovered.qoi");
+        settled_file(&blob_store, "data/orphan.qoi");
+        settled_file(&blob_store, "data/quarantine/earlier.qoi");
+        let files = vec![
+            ImageFile::new(1, recovered.clone(), ImageStatus::Missing),
+            ImageFile::new(2, "data/gone.qoi".to_string(), ImageStatus::Ok),
+            ImageFile::new(3, "data/known.qoi".to_string(), ImageStatus::Missing),
+        ];
+        let mut mock = mock(files, vec![recovered]);
+        mock.expect_update_image_status()
+            .withf(|indexes, status| indexes == [2] && *status == ImageStatus::Missing)
+            .times(1)
+            .returning(|_i, _s| Ok(()));
+        mock.expect_update_image_status()
+            .with(predicate::always(), predicate::eq(ImageStatus::Ok))
+            .times(1)
+            .returning(|_i, _s| Ok(()));
+        let service = ImageConsistency::new(
+            mock,
+            blob_store.clone(),
+            "data".to_string(),
+            "data/quarantine".to_string(),
+        );
+        let report = service.check(true).await.unwrap();
+        assert_eq!(report.missing.len(), 2);
+        assert_eq!(report.recovered.len(), 1);
+        assert_eq!(report.orphans.len(), 1);
+        assert!(report.repaired);
+        assert_eq!(
+            blob_store.keys(),
+            vec![
+                "data/quarantine/earlier.qoi",
+                "data/quarantine/orphan.qoi",
+                "data/recovered.qoi",
+            ]
+        );
+    }
+}
@@ -0,0 +1,258 @@
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+use tracing::{error, info};
+
+use super::{
+    domain::storage_layout::{LayoutMigration, StorageLayout},
+    ports::{
+        incoming::layout_service::{LayoutService, LayoutServiceError},
+        outgoing::{
+            blob_store_port::{BlobStoreError, DynBlobStore},
+            layout_port::{LayoutError, LayoutPort},
+        },
+    },
+};
+
+/// Images whose paths are read per query while migrating.
+const BATCH_SIZE: i64 = 100;
+
+impl From<LayoutError> for LayoutServiceError {
+    fn from(value: LayoutError) -> Self {
+        match value {
+            LayoutError::InternalError => LayoutServiceError::InternalError,
+        }
+    }
+}
+
+pub struct ImageLayout<Storage>
+where
+    Storage: LayoutPort + Send + Sync,
+{
+    storage: Storage,
+    blob_store: DynBlobStore,
+    layout: StorageLayout,
+}
+
+#[async_trait]
+impl<Storage> LayoutService for ImageLayout<Storage>
+where
+    Storage: LayoutPort + Send + Sync,
+{
+    async fn migrate(&self) -> Result<LayoutMigration, LayoutServiceError> {
+        let mut migration = LayoutMigration {
+            layout: self.layout,
+            ..Default::default()
+        };
+        let mut after = 0;
+        loop {
+            let batch = self.storage.query_image_paths(after, BATCH_SIZE).await?;
+            after = match batch.last() {
+                Some(last) => last.image_id,
+                None => break,
+            };
+            for image in batch {
+                let moves = image
+                    .paths
+                    .iter()
+                    .filter_map(|path| {
+                        let placed = self.layout.place(path);
+                        (placed != *path).then(|| (path.clone(), placed))
+                    })
+                    .collect::<Vec<(String, String)>>();
+                if moves.is_empty() {
+                    continue;
+                }
+                match self.relocate(&moves).await {
+                    Ok(()) => {
+                        migration.images += 1;
+                        migration.files += moves.len();
+                    }
+                    Err(e) => {
+                        error!("Error moving the files of image {}: {}", image.image_id, e);
+                        migration.failed += 1;
+                    }
+                }
+            }
+        }
+        if migration.images > 0 {
+            info!(
+                "Moved {} files of {} images into the {} layout",
+                migration.files, migration.images, migration.layout
+            );
+        }
+        Ok(migration)
+    }
+}
+
+impl<Storage> ImageLayout<Storage>
+where
+    Storage: LayoutPort + Send + Sync,
+{
+    pub fn new(storage: Storage, blob_store: DynBlobStore, layout: StorageLayout) -> Self {
+        Self {
+            storage,
+            blob_store,
+            layout,
+        }
+    }
+
+    /// Moves the files while the new paths are being recorded, and moves
+    /// back the ones already moved when that fails.
+    async fn relocate(&self, moves: &[(String, String)]) -> Result<(), LayoutError> {
+        let moved = Arc::new(Mutex::new(vec![]));
+        let blob_store = self.blob_store.clone();
+        let renames = moves.to_vec();
+        let done = moved.clone();
+        let persist = Box::pin(async move {
+            for (from, to) in renames {
+                match blob_store.rename(&from, &to).await {
+                    Ok(()) => done.lock().unwrap().push((from, to)),
+                    // Renditions are regenerated on request, their rows
+                    // move all the same.
+                    Err(BlobStoreError::NotFound) => {}
+                    Err(e) => return Err(std::io::Error::other(e)),
+                }
+            }
+            Ok(())
+        });
+        let result = self.storage.relocate_paths(moves, persist).await;
+        if result.is_err() {
+            let moved = std::mem::take(&mut *moved.lock().unwrap());
+            for (from, to) in moved {
+                if let Err(e) = self.blob_store.rename(&to, &from).await {
+                    error!("Error moving {} back to {}: {}", to, from, e);
+                }
+            }
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{sync::Arc, time::SystemTime};
+
+    use async_trait::async_trait;
+    use mockall::{mock, predicate};
+
+    use crate::{
+        data_storage::blobs::memory_blob_store::MemoryBlobStore,
+        services::images::{
+            domain::storage_layout::{ImagePaths, LayoutMigration, StorageLayout},
+            image_layout::ImageLayout,
+            ports::{
+                incoming::layout_service::LayoutService,
+                outgoing::{
+                    insert_image_port::Persist,
+                    layout_port::{LayoutError, LayoutPort},
+                },
+            },
+        },
+    };
+
+    mock! {
+        DS {}
+        #[async_trait]
+        impl LayoutPort for DS {
+            async fn query_image_paths(&self, after: i64, count: i64) -> Result<Vec<ImagePaths>, LayoutError>;
+            async fn relocate_paths(&self, moves: &[(String, String)], persist: Persist) -> Result<(), LayoutError>;
+        }
+    }
+
+    fn mock(images: Vec<ImagePaths>) -> MockDS {
+        let mut mock = MockDS::new();
+        mock.expect_query_image_paths()
+            .with(predicate::eq(0), predicate::always())
+            .returning(move |_a, _c| Ok(images.clone()));
+        mock.expect_query_image_paths()
+            .with(predicate::gt(0), predicate::always())
+            .returning(|_a, _c| Ok(vec![]));
+        mock
+    }
+
+    #[tokio::test]
+    async fn test_migrate_moves_flat_files_into_shards() {
+        let blob_store = Arc::new(MemoryBlobStore::new());
+        blob_store.insert("data/abcdef.qoi", "image", SystemTime::now());
+        blob_store.insert("data/12/34/123456.qoi", "sharded", SystemTime::now());
+        let mut mock = mock(vec![
+            ImagePaths {
+                image_id: 1,
+                paths: vec![
+                    "data/abcdef.qoi".to_string(),
+                    // Rendition files missing on disk only have their rows moved.
+                    "data/abcdef_thumb.qoi".to_string(),
+                    "data/abcdef_large.qoi".to_string(),
+                ],
+            },
+            ImagePaths {
+                image_id: 2,
+                paths: vec!["data/12/34/123456.qoi".to_string()],
+            },
+        ]);
+        mock.expect_relocate_paths()
+            .withf(|moves, _p| {
+                moves
+                    == [
+                        (
+                            "data/abcdef.qoi".to_string(),
+                            "data/ab/cd/abcdef.qoi".to_string(),
+                        ),
+                        (
+                            "data/abcdef_thumb.qoi".to_string(),
+                            "data/ab/cd/abcdef_thumb.qoi".to_string(),
+                        ),
+                        (
+                            "data/abcdef_large.qoi".to_string(),
+                            "data/ab/cd/abcdef_large.qoi".to_string(),
+                        ),
+                    ]
+            })
+            .times(1)
+            .returning(|_m, persist| {
+                futures::executor::block_on(persist).unwrap();
+                Ok(())
+            });
+        let service = ImageLayout::new(mock, blob_store.clone(), StorageLayout::Sharded);
+        let migration = service.migrate().await.unwrap();
+        assert_eq!(
+            migration,
+            LayoutMigration {
+                layout: StorageLayout::Sharded,
+                images: 1,
+                files: 3,
+                failed: 0,
+            }
+        );
+        assert_eq!(
+            blob_store.keys(),
+            vec!["data/12/34/123456.qoi", "data/ab/cd/abcdef.qoi"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_migrate_moves_files_back_when_commit_fails() {
+        let blob_store = Arc::new(MemoryBlobStore::new());
+        blob_store.insert("data/abcdef.qoi", "image", SystemTime::now());
+        blob_store.insert("data/ab/cd/abcdef.png", "taken", SystemTime::now());
+        let mut mock = mock(vec![ImagePaths {
+            image_id: 1,
+            paths: vec!["data/abcdef.qoi".to_string(), "data/abcdef.png".to_string()],
+        }]);
+        // The files are moved but recording the paths fails afterwards.
+        mock.expect_relocate_paths().returning(|_m, persist| {
+            futures::executor::block_on(persist).unwrap();
+            Err(LayoutError::InternalError)
+        });
+        let service = ImageLayout::new(mock, blob_store.clone(), StorageLayout::Sharded);
+        let migration = service.migrate().await.unwrap();
+        assert_eq!(migration.images, 0);
+        assert_eq!(migration.failed, 1);
+        // Only what this migration moved goes back.
+        assert_eq!(
+            blob_store.keys(),
+            vec!["data/ab/cd/abcdef.png", "data/abcdef.qoi"]
+        );
+    }
+}
@@ -0,0 +1,207 @@
+use std::io::Cursor;
+
+use async_trait::async_trait;
+use tracing::error;
+
+use super::{
+    domain::rendition::{Rendition, RenditionSize},
+    ports::{
+        incoming::rendition_service::{RenditionService, RenditionServiceError},
+        outgoing::{
+            blob_store_port::DynBlobStore,
+            insert_image_port::InsertImageError,
+            query_image_port::{QueryError, QueryImagePort},
+            rendition_port::RenditionPort,
+        },
+    },
+};
+
+pub struct ImageRenditions<Storage>
+where
+    Storage: QueryImagePort + RenditionPort + Send + Sync,
+{
+    storage: Storage,
+    blob_store: DynBlobStore,
+}
+
+#[async_trait]
+impl<Storage> RenditionService for ImageRenditions<Storage>
+where
+    Storage: QueryImagePort + RenditionPort + Send + Sync,
+{
+    async fn query_rendition(
+        &self,
+        index: i64,
+        size: RenditionSize,
+    ) -> Result<Rendition, RenditionServiceError> {
+        if let Ok(rendition) = self.storage.query_rendition(index, size).await {
+            if self
+                .blob_store
+                .exists(rendition.path())
+                .await
+                .unwrap_or(false)
+            {
+                return Ok(rendition);
+            }
+        }
+
+        // The rendition was never generated or its file went missing: render
+        // it from the original and persist it for the next request.
+        let image = match self.storage.query_image(index).await {
+            Ok(image) => image,
+            Err(QueryError::RecordNotFound) => return Err(RenditionServiceError::ImageNotFound),
+            Err(_) => return Err(RenditionServiceError::InternalError),
+        };
+        let original = self.blob_store.read(image.path()).await.map_err(|e| {
+            error!("Error reading original {}: {}", image.path(), e);
+            RenditionServiceError::InternalError
+        })?;
+        let bytes = tokio::task::spawn_blocking(move || {
+            let original = image::load(Cursor::new(original), image::ImageFormat::Qoi)?;
+            size.encode(&original)
+        })
+        .await
+        .map_err(|_| RenditionServiceError::InternalError)?
+        .map_err(|e| {
+            error!("Error rendering {} for image {}: {}", size, index, e);
+            RenditionServiceError::InternalError
+        })?;
+
+        let rendition = Rendition::new(index, size, size.path_for(image.path()));
+        if let Err(e) = self.blob_store.put(rendition.path(), bytes).await {
+            error!("Error writing rendition {}: {}", rendition.path(), e);
+            return Err(RenditionServiceError::InternalError);
+        }
+        match self.storage.insert_rendition(&rendition).await {
+            Ok(()) | Err(InsertImageError::AlreadyExists) => Ok(rendition),
+            Err(_) => Err(RenditionServiceError::InternalError),
+        }
+    }
+}
+
+impl<Storage> ImageRenditions<Storage>
+where
+    Storage: QueryImagePort + RenditionPort + Send + Sync,
+{
+    pub fn new(storage: Storage, blob_store: DynBlobStore) -> Self {
+        Self {
+            storage,
+            blob_store,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{io::Cursor, sync::Arc, time::SystemTime};
+
+    use async_trait::async_trait;
+    use chrono::Utc;
+    use image::GenericImageView;
+    use mockall::{mock, predicate};
+
+    use crate::{
+        data_storage::blobs::memory_blob_store::MemoryBlobStore,
+        services::images::{
+            domain::{
+                image::Image,
+                rendition::{Rendition, RenditionSize},
+            },
+            image_renditions::ImageRenditions,
+            ports::{
+                incoming::rendition_service::{RenditionService, RenditionServiceError},
+                outgoing::{
+                    blob_store_port::BlobStorePort,
+                    insert_image_port::InsertImageError,
+                    query_image_port::{QueryError, QueryImagePort},
+                    rendition_port::RenditionPort,
+                },
+            },
+        },
+    };
+
+    mock! {
+        DS {}
+        #[async_trait]
+        impl QueryImagePort for DS {
+            async fn query_image(&self, index: i64) -> Result<Image, QueryError>;
+        }
+        #[async_trait]
+        impl RenditionPort for DS {
+            async fn query_rendition(&self, image_id: i64, size: RenditionSize) -> Result<Rendition, QueryError>;
+            async fn insert_rendition(&self, rendition: &Rendition) -> Result<(), InsertImageError>;
+        }
+    }
+
+    #[tokio::test]
+    async fn test_query_existing_rendition() {
+        let mut mock = MockDS::new();
+        mock.expect_query_rendition()
+            .with(predicate::eq(1), predicate::eq(RenditionSize::Thumb))
+            .returning(|i, s| Ok(Rendition::new(i, s, "data/1_thumb.qoi".to_string())));
+        mock.expect_query_image().never();
+        let blob_store = Arc::new(MemoryBlobStore::new());
+        blob_store.insert("data/1_thumb.qoi", "some content", SystemTime::now());
+        let suu = ImageRenditions::new(mock, blob_store);
+        let result = suu.query_rendition(1, RenditionSize::Thumb).await;
+        assert_eq!(
+            result,
+            Ok(Rendition::new(
+                1,
+                RenditionSize::Thumb,
+                "data/1_thumb.qoi".to_string()
+            ))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_query_missing_rendition_is_generated() {
+        let original = "data/original.qoi".to_string();
+        let mut bytes = vec![];
+        image::DynamicImage::new_rgb8(300, 150)
+            .write_to(&mut Cursor::new(&mut bytes), image::ImageOutputFormat::Qoi)
+            .unwrap();
+        let blob_store = Arc::new(MemoryBlobStore::new());
+        blob_store.insert(&original, bytes, SystemTime::now());
+
+        let mut mock = MockDS::new();
+        mock.expect_query_rendition()
+            .returning(|_i, _s| Err(QueryError::RecordNotFound));
+        let path = original.clone();
+        mock.expect_query_image()
+            .returning(move |i| Ok(Image::new(i, path.clone(), Utc::now())));
+        mock.expect_insert_rendition()
+            .times(1)
+            .returning(|_r| Ok(()));
+        let suu = ImageRenditions::new(mock, blob_store.clone());
+        let rendition = suu.query_rendition(1, RenditionSize::Thumb).await.unwrap();
+        assert_eq!(rendition.path(), RenditionSize::Thumb.path_for(&original));
+        let thumb = blob_store.read(rendition.path()).await.unwrap();
+        let thumb = image::load_from_memory_with_format(&thumb, image::ImageFormat::Qoi).unwrap();
+        assert_eq!(thumb.dimensions(), (128, 64));
+    }
+
+    #[tokio::test]
+    async fn test_query_rendition_image_not_found() {
+        let mut mock = MockDS::new();
+        mock.expect_query_rendition()
+            .returning(|_i, _s| Err(QueryError::RecordNotFound));
+        mock.expect_query_image()
+            .returning(|_i| Err(QueryError::RecordNotFound));
+        let suu = ImageRenditions::new(mock, Arc::new(MemoryBlobStore::new()));
+        let result = suu.query_rendition(1, RenditionSize::Preview).await;
+        assert_eq!(result, Err(RenditionServiceError::ImageNotFound));
+    }
+
+    #[tokio::test]
+    async fn test_query_rendition_original_missing() {
+        let mut mock = MockDS::new();
+        mock.expect_query_rendition()
+            .returning(|_i, _s| Err(QueryError::RecordNotFound));
+        mock.expect_query_image()
+            .returning(|i| Ok(Image::new(i, "does/not/exist.qoi".to_string(), Utc::now())));
+        let suu = ImageRenditions::new(mock, Arc::new(MemoryBlobStore::new()));
+        let result = suu.query_rendition(1, RenditionSize::Large).await;
+        assert_eq!(result, Err(RenditionServiceError::InternalError));
+    }
+}
@@ -0,0 +1,294 @@
+use async_trait::async_trait;
+use chrono::Utc;
+use image::ImageFormat;
+use sha2::{Digest, Sha256};
+use tracing::{error, warn};
+
+use super::{
+    domain::{
+        consistency::ImageFile,
+        image_status::ImageStatus,
+        integrity::{IntegritySummary, ScrubSummary},
+    },
+    ports::{
+        incoming::scrub_service::{ScrubService, ScrubServiceError},
+        outgoing::{
+            blob_store_port::{BlobStoreError, DynBlobStore},
+            scrub_port::{ScrubError, ScrubPort},
+        },
+    },
+};
+
+impl From<ScrubError> for ScrubServiceError {
+    fn from(value: ScrubError) -> Self {
+        match value {
+            ScrubError::InternalError => ScrubServiceError::InternalError,
+        }
+    }
+}
+
+pub struct ImageScrubber<Storage>
+where
+    Storage: ScrubPort + Send + Sync,
+{
+    storage: Storage,
+    blob_store: DynBlobStore,
+}
+
+#[async_trait]
+impl<Storage> ScrubService for ImageScrubber<Storage>
+where
+    Storage: ScrubPort + Send + Sync,
+{
+    async fn scrub(&self, count: i64) -> Result<ScrubSummary, ScrubServiceError> {
+        if count <= 0 {
+            return Err(ScrubServiceError::InvalidRequest);
+        }
+        let mut summary = ScrubSummary::default();
+        for file in self.storage.query_scrub_batch(count).await? {
+            // A file that could not be read keeps its status but is stamped
+            // as checked, so it goes to the back of the queue instead of
+            // taking the whole batch again on the next pass.
+            let Some((status, checksum)) = self.verify(&file).await else {
+                self.storage
+                    .record_scrub(file.image_id(), file.status(), None, Utc::now())
+                    .await?;
+                continue;
+            };
+            match status {
+                ImageStatus::Ok => {}
+                ImageStatus::Missing => summary.missing += 1,
+                ImageStatus::Corrupted => summary.corrupted += 1,
+            }
+            if status != ImageStatus::Ok && status != file.status() {
+                warn!("Image {} at {} is {}", file.image_id(), file.path(), status);
+            }
+            // Files written before checksums existed get theirs on the
+            // first clean read.
+            let backfill = checksum.filter(|_| file.checksum().is_none());
+            self.storage
+                .record_scrub(file.image_id(), status, backfill, Utc::now())
+                .await?;
+            summary.checked += 1;
+        }
+        Ok(summary)
+    }
+
+    async fn integrity(&self) -> Result<IntegritySummary, ScrubServiceError> {
+        Ok(IntegritySummary {
+            counts: self.storage.count_images_by_status().await?,
+            corrupted: self
+                .storage
+                .query_images_by_status(ImageStatus::Corrupted)
+                .await?,
+        })
+    }
+
+    async fn image_counts(&self) -> Result<Vec<(ImageStatus, i64)>, ScrubServiceError> {
+        Ok(self.storage.count_images_by_status().await?)
+    }
+}
+
+impl<Storage> ImageScrubber<Storage>
+where
+    Storage: ScrubPort + Send + Sync,
+{
+    pub fn new(storage: Storage, blob_store: DynBlobStore) -> Self {
+        Self {
+            storage,
+            blob_store,
+        }
+    }
+
+    /// Status of the file, with its checksum when it reads and decodes.
+    /// `None` when the blob store failed to read it, which says nothing about
+    /// the file itself.
+    async fn verify(&self, file: &ImageFile) -> Option<(ImageStatus, Option<String>)> {
+        let bytes = match self.blob_store.read(file.path()).await {
+            Ok(bytes) => bytes,
+            Err(BlobStoreError::NotFound) => return Some((ImageStatus::Missing, None)),
+            Err(e) => {
+                error!("Error reading {}: {}", file.path(), e);
+                return None;
+            }
+        };
+        let checksum = hex::encode(Sha256::digest(&bytes));
+        if file.checksum().is_some_and(|expected| expected != checksum) {
+            return Some((ImageStatus::Corrupted, None));
+        }
+        match image::load_from_memory_with_format(&bytes, ImageFormat::Qoi) {
+            Ok(_) => Some((ImageStatus::Ok, Some(checksum))),
+            Err(_) => Some((ImageStatus::Corrupted, None)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{io::Cursor, sync::Arc, time::SystemTime};
+
+    use async_trait::async_trait;
+    use chrono::{DateTime, Utc};
+    use mockall::{mock, predicate};
+
+    use bytes::Bytes;
+
+    use crate::{
+        data_storage::{
+            blobs::memory_blob_store::MemoryBlobStore,
+            images::images_in_memory_ds::ImagesInMemoryDS,
+        },
+        services::images::{
+            domain::{consistency::ImageFile, image::Image, image_status::ImageStatus},
+            image_scrubber::ImageScrubber,
+            ports::{
+                incoming::scrub_service::{ScrubService, ScrubServiceError},
+                outgoing::{
+                    blob_store_port::{BlobInfo, BlobStoreError, BlobStorePort, BlobStream},
+                    insert_image_port::InsertImagePort,
+                    scrub_port::{ScrubError, ScrubPort},
+                },
+            },
+        },
+    };
+
+    mock! {
+        DS {}
+        #[async_trait]
+        impl ScrubPort for DS {
+            async fn query_scrub_batch(&self, count: i64) -> Result<Vec<ImageFile>, ScrubError>;
+            async fn record_scrub(&self, image_id: i64, status: ImageStatus, checksum: Option<String>, checked_on: DateTime<Utc>) -> Result<(), ScrubError>;
+            async fn count_images_by_status(&self) -> Result<Vec<(ImageStatus, i64)>, ScrubError>;
+            async fn query_images_by_status(&self, status: ImageStatus) -> Result<Vec<Image>, ScrubError>;
+        }
+    }
+
+    mock! {
+        BlobStore {}
+        #[async_trait]
+        impl BlobStorePort for BlobStore {
+            async fn put(&self, key: &str, bytes: Vec<u8>) -> Result<(), BlobStoreError>;
+            async fn get(&self, key: &str) -> Result<BlobStream, BlobStoreError>;
+            async fn delete(&self, key: &str) -> Result<(), BlobStoreError>;
+            async fn exists(&self, key: &str) -> Result<bool, BlobStoreError>;
+            async fn size(&self, key: &str) -> Result<u64, BlobStoreError>;
+            async fn list(&self, prefix: &str) -> Result<Vec<BlobInfo>, BlobStoreError>;
+            async fn rename(&self, from: &str, to: &str) -> Result<(), BlobStoreError>;
+        }
+    }
+
+    fn qoi() -> Vec<u8> {
+        let mut bytes = vec![];
+        image::DynamicImage::new_rgb8(3, 2)
+            .write_to(&mut Cursor::new(&mut bytes), image::ImageOutputFormat::Qoi)
+            .unwrap();
+        bytes
+    }
+
+    #[tokio::test]
+    async fn test_scrub_detects_missing_and_corrupted_files() {
+        let blob_store = Arc::new(MemoryBlobStore::new());
+        blob_store.insert("data/intact.qoi", qoi(), SystemTime::now());
+        let mut bytes = qoi();
+        let checksum = hex::encode(<sha2::Sha256 as sha2::Digest>::digest(&bytes));
+        bytes[20] ^= 0xff;
+        blob_store.insert("data/rotten.qoi", bytes, SystemTime::now());
+        blob_store.insert("data/garbage.qoi", "not an image", SystemTime::now());
+        let files = vec![
+            ImageFile::new(1, "data/intact.qoi".to_string(), ImageStatus::Ok),
+            ImageFile::new(2, "data/rotten.qoi".to_string(), ImageStatus::Ok)
+                .with_checksum(checksum),
+            ImageFile::new(3, "data/garbage.qoi".to_string(), ImageStatus::Ok),
+            ImageFile::new(4, "data/gone.qoi".to_string(), ImageStatus::Ok),
+        ];
+        let mut mock = MockDS::new();
+        mock.expect_query_scrub_batch()
+            .with(predicate::eq(10))
+            .returning(move |_c| Ok(files.clone()));
+        mock.expect_record_scrub()
+            .withf(|id, status, checksum, _c| {
+                *id == 1 && *status == ImageStatus::Ok && checksum.is_some()
+            })
+            .times(1)
+            .returning(|_i, _s, _c, _o| Ok(()));
+        mock.expect_record_scrub()
+            .withf(|id, status, checksum, _c| {
+                [2, 3].contains(id) && *status == ImageStatus::Corrupted && checksum.is_none()
+            })
+            .times(2)
+            .returning(|_i, _s, _c, _o| Ok(()));
+        mock.expect_record_scrub()
+            .withf(|id, status, _s, _c| *id == 4 && *status == ImageStatus::Missing)
+            .times(1)
+            .returning(|_i, _s, _c, _o| Ok(()));
+        let scrubber = ImageScrubber::new(mock, blob_store);
+        let summary = scrubber.scrub(10).await.unwrap();
+        assert_eq!(summary.checked, 4);
+        assert_eq!(summary.corrupted, 2);
+        assert_eq!(summary.missing, 1);
+    }
+
+    #[tokio::test]
+    async fn test_scrub_leaves_unreadable_files_unchanged() {
+        let mut blob_store = MockBlobStore::new();
+        blob_store
+            .expect_get()
+            .returning(|_k| Err(BlobStoreError::InternalError("timeout".to_string())));
+        let mut mock = MockDS::new();
+        mock.expect_query_scrub_batch().returning(|_c| {
+            Ok(vec![ImageFile::new(
+                1,
+                "data/intact.qoi".to_string(),
+                ImageStatus::Ok,
+            )])
+        });
+        mock.expect_record_scrub()
+            .withf(|id, status, checksum, _c| {
+                *id == 1 && *status == ImageStatus::Ok && checksum.is_none()
+            })
+            .times(1)
+            .returning(|_i, _s, _c, _o| Ok(()));
+        let scrubber = ImageScrubber::new(mock, Arc::new(blob_store));
+        let summary = scrubber.scrub(10).await.unwrap();
+        assert_eq!(summary.checked, 0);
+        assert_eq!(summary.corrupted, 0);
+    }
+
+    #[tokio::test]
+    async fn test_scrub_moves_past_unreadable_files() {
+        let storage = ImagesInMemoryDS::new();
+        for (id, path) in [
+            (1, "data/bad1.qoi"),
+            (2, "data/bad2.qoi"),
+            (3, "data/good.qoi"),
+        ] {
+            storage
+                .insert_image(&Image::new(id, path.to_string(), Utc::now()))
+                .await
+                .unwrap();
+        }
+        let mut blob_store = MockBlobStore::new();
+        blob_store.expect_get().returning(|key| {
+            if key.contains("bad") {
+                return Err(BlobStoreError::InternalError("timeout".to_string()));
+            }
+            let chunk: std::io::Result<Bytes> = Ok(Bytes::from(qoi()));
+            Ok(Box::pin(futures::stream::iter(vec![chunk])) as BlobStream)
+        });
+        let scrubber = ImageScrubber::new(storage, Arc::new(blob_store));
+        // Both unreadable files fill the first batch.
+        assert_eq!(scrubber.scrub(2).await.unwrap().checked, 0);
+        assert_eq!(scrubber.scrub(2).await.unwrap().checked, 1);
+        let summary = scrubber.integrity().await.unwrap();
+        assert!(summary.counts.contains(&(ImageStatus::Ok, 3)));
+    }
+
+    #[tokio::test]
+    async fn test_scrub_rejects_empty_batch() {
+        let scrubber = ImageScrubber::new(MockDS::new(), Arc::new(MemoryBlobStore::new()));
+        assert_eq!(
+            scrubber.scrub(0).await,
+            Err(ScrubServiceError::InvalidRequest)
+        );
+    }
+}
@@ -0,0 +1,198 @@
+use async_trait::async_trait;
+use chrono::{Duration, DurationRound, Utc};
+
+use super::{
+    domain::share::{sign_share, verify_share, Share, DEFAULT_SHARE_SECONDS, MAX_SHARE_SECONDS},
+    ports::{
+        incoming::shares_service::{SharesService, SharesServiceError},
+        outgoing::shares_port::{SharesError, SharesPort},
+    },
+};
+
+impl From<SharesError> for SharesServiceError {
+    fn from(value: SharesError) -> Self {
+        match value {
+            SharesError::ShareNotFound => SharesServiceError::ShareNotFound,
+            SharesError::ImageNotFound => SharesServiceError::ImageNotFound,
+            SharesError::InternalError => SharesServiceError::InternalError,
+        }
+    }
+}
+
+pub struct ImageShares<Storage>
+where
+    Storage: SharesPort + Send + Sync,
+{
+    storage: Storage,
+    signing_key: Vec<u8>,
+}
+
+#[async_trait]
+impl<Storage> SharesService for ImageShares<Storage>
+where
+    Storage: SharesPort + Send + Sync,
+{
+    async fn share_image(
+        &self,
+        image_id: i64,
+        expires_in: Option<i64>,
+    ) -> Result<(Share, String), SharesServiceError> {
+        let expires_in = expires_in.unwrap_or(DEFAULT_SHARE_SECONDS);
+        if !(1..=MAX_SHARE_SECONDS).contains(&expires_in) {
+            return Err(SharesServiceError::InvalidRequest(format!(
+                "Shares expire within 1 to {} seconds",
+                MAX_SHARE_SECONDS
+            )));
+        }
+        let now = Utc::now();
+        // Links carry whole seconds, so store the expiry the same way.
+        let expires_on = (now + Duration::seconds(expires_in))
+            .duration_trunc(Duration::seconds(1))
+            .map_err(|_| SharesServiceError::InternalError)?;
+        let share = self.storage.insert_share(image_id, now, expires_on).await?;
+        let signature = sign_share(
+            &self.signing_key,
+            share.id(),
+            share.image_id(),
+            share.expires(),
+        );
+        Ok((share, signature))
+    }
+
+    async fn revoke_share(&self, id: i64) -> Result<(), SharesServiceError> {
+        self.storage
+            .revoke_share(id, Utc::now())
+            .await
+            .map_err(|err| err.into())
+    }
+
+    async fn verify_link(
+        &self,
+        id: i64,
+        expires: i64,
+        signature: &str,
+    ) -> Result<Share, SharesServiceError> {
+        let share = match self.storage.query_share(id).await {
+            Ok(share) => share,
+            // Unknown ids look like any other bad link.
+            Err(SharesError::ShareNotFound) => return Err(SharesServiceError::InvalidLink),
+            Err(e) => return Err(e.into()),
+        };
+        let signed = verify_share(&self.signing_key, id, share.image_id(), expires, signature);
+        if !signed || expires != share.expires() || !share.is_valid_at(Utc::now()) {
+            return Err(SharesServiceError::InvalidLink);
+        }
+        Ok(share)
+    }
+}
+
+impl<Storage> ImageShares<Storage>
+where
+    Storage: SharesPort + Send + Sync,
+{
+    pub fn new(storage: Storage, signing_key: &str) -> Self {
+        Self {
+            storage,
+            signing_key: signing_key.as_bytes().to_vec(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use async_trait::async_trait;
+    use chrono::{DateTime, Duration, Utc};
+    use mockall::{mock, predicate};
+
+    use crate::services::images::{
+        domain::share::{sign_share, Share},
+        image_shares::ImageShares,
+        ports::{
+            incoming::shares_service::{SharesService, SharesServiceError},
+            outgoing::shares_port::{SharesError, SharesPort},
+        },
+    };
+
+    mock! {
+        DS {}
+        #[async_trait]
+        impl SharesPort for DS {
+            async fn insert_share(
+                &self,
+                image_id: i64,
+                created_on: DateTime<Utc>,
+                expires_on: DateTime<Utc>,
+            ) -> Result<Share, SharesError>;
+            async fn query_share(&self, id: i64) -> Result<Share, SharesError>;
+            async fn revoke_share(&self, id: i64, revoked_on: DateTime<Utc>)
+                -> Result<(), SharesError>;
+        }
+    }
+
+    #[tokio::test]
+    async fn test_share_image_signs_the_link() {
+        let mut mock = MockDS::new();
+        mock.expect_insert_share()
+            .withf(|image_id, created_on, expires_on| {
+                *image_id == 2
+                    && *expires_on - *created_on <= Duration::seconds(60)
+                    && expires_on.timestamp_subsec_nanos() == 0
+            })
+            .returning(|image_id, created_on, expires_on| {
+                Ok(Share::new(5, image_id, created_on, expires_on))
+            });
+        let service = ImageShares::new(mock, "key");
+        let (share, signature) = service.share_image(2, Some(60)).await.unwrap();
+        assert_eq!(signature, sign_share(b"key", 5, 2, share.expires()));
+
+        let result = service.share_image(2, Some(0)).await;
+        assert!(matches!(result, Err(SharesServiceError::InvalidRequest(_))));
+    }
+
+    #[tokio::test]
+    async fn test_verify_link() {
+        let expires_on = DateTime::from_timestamp(Utc::now().timestamp() + 60, 0).unwrap();
+        let share = Share::new(5, 2, Utc::now(), expires_on);
+        let revoked = share.clone().with_revoked_on(Utc::now());
+        let mut mock = MockDS::new();
+        mock.expect_query_share()
+            .with(predicate::eq(5))
+            .returning(move |_| Ok(share.clone()));
+        mock.expect_query_share()
+            .with(predicate::eq(6))
+            .returning(move |_| Ok(revoked.clone()));
+        mock.expect_query_share()
+            .returning(|_| Err(SharesError::ShareNotFound));
+        let service = ImageShares::new(mock, "key");
+        let expires = expires_on.timestamp();
+
+        let signature = sign_share(b"key", 5, 2, expires);
+        assert_eq!(
+            service
+                .verify_link(5, expires, &signature)
+                .await
+                .unwrap()
+                .image_id(),
+            2
+        );
+        let forged = sign_share(b"other", 5, 2, expires);
+        assert_eq!(
+            service.verify_link(5, expires, &forged).await,
+            Err(SharesServiceError::InvalidLink)
+        );
+        let extended = sign_share(b"key", 5, 2, expires + 60);
+        assert_eq!(
+            service.verify_link(5, expires + 60, &extended).await,
+            Err(SharesServiceError::InvalidLink)
+        );
+        let signature = sign_share(b"key", 6, 2, expires);
+        assert_eq!(
+            service.verify_link(6, expires, &signature).await,
+            Err(SharesServiceError::InvalidLink)
+        );
+        assert_eq!(
+            service.verify_link(7, expires, &signature).await,
+            Err(SharesServiceError::InvalidLink)
+        );
+    }
+}
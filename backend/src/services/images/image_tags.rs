@@ -0,0 +1,181 @@
+use async_trait::async_trait;
+
+use super::{
+    domain::tag::{normalize_tag, Tag},
+    ports::{
+        incoming::tags_service::{TagsService, TagsServiceError},
+        outgoing::tags_port::{TagsError, TagsPort},
+    },
+};
+
+impl From<TagsError> for TagsServiceError {
+    fn from(value: TagsError) -> Self {
+        match value {
+            TagsError::ImageNotFound => TagsServiceError::ImageNotFound,
+            TagsError::TagNotFound => TagsServiceError::TagNotFound,
+            TagsError::InternalError => TagsServiceError::InternalError,
+        }
+    }
+}
+
+pub struct ImageTags<Storage>
+where
+    Storage: TagsPort + Send + Sync,
+{
+    storage: Storage,
+}
+
+#[async_trait]
+impl<Storage> TagsService for ImageTags<Storage>
+where
+    Storage: TagsPort + Send + Sync,
+{
+    async fn tag_image(
+        &self,
+        image_id: i64,
+        tags: Vec<String>,
+    ) -> Result<Vec<String>, TagsServiceError> {
+        let mut tags = tags
+            .iter()
+            .map(|tag| normalize_tag(tag))
+            .collect::<Result<Vec<String>, String>>()
+            .map_err(TagsServiceError::InvalidTag)?;
+        if tags.is_empty() {
+            return Err(TagsServiceError::InvalidTag("No tags given".to_string()));
+        }
+        tags.sort();
+        tags.dedup();
+        self.storage
+            .add_tags(image_id, &tags)
+            .await
+            .map_err(|err| err.into())
+    }
+
+    async fn untag_image(&self, image_id: i64, tag: String) -> Result<(), TagsServiceError> {
+        let tag = normalize_tag(&tag).map_err(TagsServiceError::InvalidTag)?;
+        self.storage
+            .remove_tag(image_id, &tag)
+            .await
+            .map_err(|err| err.into())
+    }
+
+    async fn tags(&self) -> Result<Vec<Tag>, TagsServiceError> {
+        self.storage.query_tags().await.map_err(|err| err.into())
+    }
+
+    async fn rename_tag(&self, from: String, to: String) -> Result<(), TagsServiceError> {
+        let from = normalize_tag(&from).map_err(TagsServiceError::InvalidTag)?;
+        let to = normalize_tag(&to).map_err(TagsServiceError::InvalidTag)?;
+        if from == to {
+            return Ok(());
+        }
+        self.storage
+            .rename_tag(&from, &to)
+            .await
+            .map_err(|err| err.into())
+    }
+}
+
+impl<Storage> ImageTags<Storage>
+where
+    Storage: TagsPort + Send + Sync,
+{
+    pub fn new(storage: Storage) -> Self {
+        Self { storage }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use async_trait::async_trait;
+    use mockall::{mock, predicate};
+
+    use crate::services::images::{
+        domain::tag::Tag,
+        image_tags::ImageTags,
+        ports::{
+            incoming::tags_service::{TagsService, TagsServiceError},
+            outgoing::tags_port::{TagsError, TagsPort},
+        },
+    };
+
+    mock! {
+        DS {}
+        #[async_trait]
+        impl TagsPort for DS {
+            async fn add_tags(&self, image_id: i64, tags: &[String]) -> Result<Vec<String>, TagsError>;
+            async fn remove_tag(&self, image_id: i64, tag: &str) -> Result<(), TagsError>;
+            async fn query_tags(&self) -> Result<Vec<Tag>, TagsError>;
+            async fn rename_tag(&self, from: &str, to: &str) -> Result<(), TagsError>;
+        }
+    }
+
+    #[tokio::test]
+    async fn test_tag_image_normalizes_tags() {
+        let mut mock = MockDS::new();
+        mock.expect_add_tags()
+            .withf(|id, tags| *id == 1 && tags == ["beach", "sunset"])
+            .returning(|_i, tags| Ok(tags.to_vec()));
+        let service = ImageTags::new(mock);
+        let result = service
+            .tag_image(
+                1,
+                vec![
+                    "Sunset".to_string(),
+                    " beach".to_string(),
+                    "BEACH".to_string(),
+                ],
+            )
+            .await;
+        assert_eq!(result, Ok(vec!["beach".to_string(), "sunset".to_string()]));
+    }
+
+    #[tokio::test]
+    async fn test_tag_image_invalid_tag() {
+        let mock = MockDS::new();
+        let service = ImageTags::new(mock);
+        let result = service.tag_image(1, vec!["a,b".to_string()]).await;
+        assert!(matches!(result, Err(TagsServiceError::InvalidTag(_))));
+        let result = service.tag_image(1, vec![]).await;
+        assert!(matches!(result, Err(TagsServiceError::InvalidTag(_))));
+    }
+
+    #[tokio::test]
+    async fn test_tag_image_not_found() {
+        let mut mock = MockDS::new();
+        mock.expect_add_tags()
+            .returning(|_i, _t| Err(TagsError::ImageNotFound));
+        let service = ImageTags::new(mock);
+        let result = service.tag_image(1, vec!["beach".to_string()]).await;
+        assert_eq!(result, Err(TagsServiceError::ImageNotFound));
+    }
+
+    #[tokio::test]
+    async fn test_rename_tag() {
+        let mut mock = MockDS::new();
+        mock.expect_rename_tag()
+            .with(predicate::eq("beach"), predicate::eq("seaside"))
+            .times(1)
+            .returning(|_f, _t| Ok(()));
+        let service = ImageTags::new(mock);
+        let result = service
+            .rename_tag("Beach".to_string(), "SEASIDE".to_string())
+            .await;
+        assert_eq!(result, Ok(()));
+        let result = service
+            .rename_tag("seaside".to_string(), "Seaside".to_string())
+            .await;
+        assert_eq!(result, Ok(()));
+    }
+
+    #[tokio::test]
+    async fn test_untag_image_tag_not_found() {
+        let mut mock = MockDS::new();
+        mock.expect_remove_tag()
+            .with(predicate::eq(1), predicate::eq("beach"))
+            .returning(|_i, _t| Err(TagsError::TagNotFound));
+        let service = ImageTags::new(mock);
+        let result = service.untag_image(1, "Beach".to_string()).await;
+        assert_eq!(result, Err(TagsServiceError::TagNotFound));
+    }
+}
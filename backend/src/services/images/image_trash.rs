@@ -0,0 +1,180 @@
+use std::time::Duration;
+
+use async_trait::async_trait;
+use chrono::Utc;
+use tracing::{error, info};
+
+use super::{
+    domain::image::Image,
+    ports::{
+        incoming::trash_service::{TrashService, TrashServiceError},
+        outgoing::{
+            blob_store_port::DynBlobStore,
+            delete_image_port::RemoveFiles,
+            trash_port::{TrashError, TrashPort},
+        },
+    },
+};
+
+const MAX_IMAGES: i64 = 50;
+
+impl From<TrashError> for TrashServiceError {
+    fn from(value: TrashError) -> Self {
+        match value {
+            TrashError::ImageNotFound => TrashServiceError::ImageNotFound,
+            TrashError::InternalError => TrashServiceError::InternalError,
+        }
+    }
+}
+
+pub struct ImageTrash<Storage>
+where
+    Storage: TrashPort + Send + Sync,
+{
+    storage: Storage,
+    blob_store: DynBlobStore,
+}
+
+#[async_trait]
+impl<Storage> TrashService for ImageTrash<Storage>
+where
+    Storage: TrashPort + Send + Sync,
+{
+    async fn trash(&self, count: i64, offset: i64) -> Result<Vec<Image>, TrashServiceError> {
+        if count <= 0 || offset < 0 {
+            return Err(TrashServiceError::InvalidRequest);
+        }
+        if count > MAX_IMAGES {
+            error!("Error too many images request amount {}", count);
+            return Err(TrashServiceError::TooManyImagesRequested);
+        }
+        self.storage
+            .query_trash(count, offset)
+            .await
+            .map_err(|err| err.into())
+    }
+
+    async fn restore_image(&self, index: i64) -> Result<(), TrashServiceError> {
+        self.storage
+            .restore_image(index)
+            .await
+            .map_err(|err| err.into())
+    }
+
+    async fn purge(&self, retention: Duration) -> Result<(), TrashServiceError> {
+        let retention =
+            chrono::Duration::from_std(retention).map_err(|_| TrashServiceError::InvalidRequest)?;
+        let blob_store = self.blob_store.clone();
+        let remove: RemoveFiles = Box::new(move |paths| {
+            Box::pin(async move {
+                if !paths.is_empty() {
+                    info!("Purging {} files from the trash", paths.len());
+                }
+                for path in paths {
+                    if let Err(e) = blob_store.delete(&path).await {
+                        error!("Error removing file {}: {}", path, e);
+                        return Err(std::io::Error::other(e));
+                    }
+                }
+                Ok(())
+            })
+        });
+        // As for a permanent delete, the files go before the rows are
+        // committed so a new upload cannot take a path still being freed.
+        self.storage
+            .purge_trash_with(Utc::now() - retention, remove)
+            .await?;
+        Ok(())
+    }
+}
+
+impl<Storage> ImageTrash<Storage>
+where
+    Storage: TrashPort + Send + Sync,
+{
+    pub fn new(storage: Storage, blob_store: DynBlobStore) -> Self {
+        Self {
+            storage,
+            blob_store,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        sync::Arc,
+        time::{Duration, SystemTime},
+    };
+
+    use async_trait::async_trait;
+    use chrono::{DateTime, Utc};
+    use mockall::{mock, predicate};
+
+    use crate::{
+        data_storage::blobs::memory_blob_store::MemoryBlobStore,
+        services::images::{
+            domain::image::Image,
+            image_trash::ImageTrash,
+            ports::{
+                incoming::trash_service::{TrashService, TrashServiceError},
+                outgoing::{
+                    delete_image_port::RemoveFiles,
+                    trash_port::{TrashError, TrashPort},
+                },
+            },
+        },
+    };
+
+    mock! {
+        DS {}
+        #[async_trait]
+        impl TrashPort for DS {
+            async fn trash_images(&self, indexes: &[i64], deleted_at: DateTime<Utc>) -> Result<u64, TrashError>;
+            async fn restore_image(&self, index: i64) -> Result<(), TrashError>;
+            async fn query_trash(&self, count: i64, offset: i64) -> Result<Vec<Image>, TrashError>;
+            async fn purge_trash(&self, before: DateTime<Utc>) -> Result<Vec<String>, TrashError>;
+            async fn purge_trash_with(&self, before: DateTime<Utc>, remove: RemoveFiles) -> Result<Vec<String>, TrashError>;
+        }
+    }
+
+    #[tokio::test]
+    async fn test_purge_removes_files_past_retention() {
+        let blob_store = Arc::new(MemoryBlobStore::new());
+        blob_store.insert("data/purged.qoi", "some content", SystemTime::now());
+        let paths = vec!["data/purged.qoi".to_string()];
+        let mut mock = MockDS::new();
+        mock.expect_purge_trash_with()
+            .withf(|before, _r| {
+                let age = Utc::now() - *before;
+                age >= chrono::Duration::days(30) && age < chrono::Duration::days(31)
+            })
+            .returning(move |_b, remove| {
+                futures::executor::block_on(remove(paths.clone())).unwrap();
+                Ok(paths.clone())
+            });
+        let service = ImageTrash::new(mock, blob_store.clone());
+        let result = service.purge(Duration::from_secs(30 * 24 * 3600)).await;
+        assert_eq!(result, Ok(()));
+        assert!(blob_store.keys().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_restore_image_not_in_trash() {
+        let mut mock = MockDS::new();
+        mock.expect_restore_image()
+            .with(predicate::eq(1))
+            .returning(|_i| Err(TrashError::ImageNotFound));
+        let service = ImageTrash::new(mock, Arc::new(MemoryBlobStore::new()));
+        let result = service.restore_image(1).await;
+        assert_eq!(result, Err(TrashServiceError::ImageNotFound));
+    }
+
+    #[tokio::test]
+    async fn test_trash_too_many_images() {
+        let mock = MockDS::new();
+        let service = ImageTrash::new(mock, Arc::new(MemoryBlobStore::new()));
+        let result = service.trash(51, 0).await;
+        assert_eq!(result, Err(TrashServiceError::TooManyImagesRequested));
+    }
+}
@@ -1,7 +1,19 @@
+pub mod api_keys;
 pub mod batch_delete_image;
 pub mod batch_query_image_service;
 pub mod delete_image;
 pub mod domain;
+pub mod image_albums;
+pub mod image_consistency;
+pub mod image_layout;
+pub mod image_renditions;
+pub mod image_scrubber;
+pub mod image_shares;
+pub mod image_tags;
+pub mod image_trash;
 pub mod ports;
 pub mod query_image_service;
+pub mod search_images;
+pub mod similar_images;
 pub mod upload_images;
+pub mod users;
@@ -0,0 +1,61 @@
+use std::{error::Error, fmt::Display};
+
+use async_trait::async_trait;
+
+use crate::services::images::domain::{
+    album::{Album, AlbumUpdate},
+    image::Image,
+};
+
+#[async_trait]
+pub trait AlbumsService {
+    async fn create_album(
+        &self,
+        name: String,
+        parent_id: Option<i64>,
+    ) -> Result<Album, AlbumsServiceError>;
+    async fn album(&self, id: i64) -> Result<Album, AlbumsServiceError>;
+    async fn albums(&self, parent_id: Option<i64>) -> Result<Vec<Album>, AlbumsServiceError>;
+    async fn update_album(&self, id: i64, update: AlbumUpdate)
+        -> Result<Album, AlbumsServiceError>;
+    async fn delete_album(&self, id: i64, cascade: bool) -> Result<(), AlbumsServiceError>;
+    async fn add_images(
+        &self,
+        album_id: i64,
+        image_ids: Vec<i64>,
+    ) -> Result<(), AlbumsServiceError>;
+    async fn remove_image(&self, album_id: i64, image_id: i64) -> Result<(), AlbumsServiceError>;
+    async fn reorder_images(
+        &self,
+        album_id: i64,
+        image_ids: Vec<i64>,
+    ) -> Result<(), AlbumsServiceError>;
+    async fn album_images(
+        &self,
+        album_id: i64,
+        count: i64,
+        offset: i64,
+    ) -> Result<Vec<Image>, AlbumsServiceError>;
+}
+
+#[derive(Debug, PartialEq)]
+pub enum AlbumsServiceError {
+    AlbumNotFound,
+    ImageNotFound,
+    InvalidRequest(String),
+    TooManyImagesRequested,
+    InternalError,
+}
+
+impl Display for AlbumsServiceError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AlbumsServiceError::AlbumNotFound => f.write_str("Album not found"),
+            AlbumsServiceError::ImageNotFound => f.write_str("Image not found"),
+            AlbumsServiceError::InvalidRequest(message) => f.write_str(message),
+            AlbumsServiceError::TooManyImagesRequested => f.write_str("Too many images requested"),
+            AlbumsServiceError::InternalError => f.write_str("Internal error"),
+        }
+    }
+}
+impl Error for AlbumsServiceError {}
@@ -0,0 +1,39 @@
+use std::{error::Error, fmt::Display};
+
+use async_trait::async_trait;
+
+use crate::services::images::domain::api_key::{ApiKey, Scope};
+
+#[async_trait]
+pub trait ApiKeysService {
+    /// Returns the key with its secret, which is not kept anywhere.
+    async fn create_key(
+        &self,
+        name: String,
+        scopes: Vec<Scope>,
+    ) -> Result<(ApiKey, String), ApiKeysServiceError>;
+    async fn keys(&self) -> Result<Vec<ApiKey>, ApiKeysServiceError>;
+    async fn revoke_key(&self, id: i64) -> Result<(), ApiKeysServiceError>;
+    /// The active key with this secret.
+    async fn authenticate(&self, secret: &str) -> Result<ApiKey, ApiKeysServiceError>;
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ApiKeysServiceError {
+    InvalidKey,
+    KeyNotFound,
+    InvalidRequest(String),
+    InternalError,
+}
+
+impl Display for ApiKeysServiceError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ApiKeysServiceError::InvalidKey => f.write_str("Invalid API key"),
+            ApiKeysServiceError::KeyNotFound => f.write_str("API key not found"),
+            ApiKeysServiceError::InvalidRequest(message) => f.write_str(message),
+            ApiKeysServiceError::InternalError => f.write_str("Internal error"),
+        }
+    }
+}
+impl Error for ApiKeysServiceError {}
@@ -4,9 +4,12 @@ use async_trait::async_trait;
 
 #[async_trait]
 pub trait BatchDeleteImageService {
+    /// Moves the images to the trash, or deletes them and their files right
+    /// away when `permanent` is set.
     async fn batch_delete_image(
         &self,
         indexes: Vec<i64>,
+        permanent: bool,
     ) -> Result<(), BatchDeleteImageServiceError>;
 }
 
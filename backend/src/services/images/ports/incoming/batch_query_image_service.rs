@@ -2,7 +2,12 @@ use std::{error::Error, fmt::Display};
 
 use async_trait::async_trait;
 
-use crate::services::images::domain::image::Image;
+use crate::services::images::domain::{
+    cursor::{Cursor, ImagePage},
+    image::Image,
+    sort_key::SortKey,
+    tag::TagMatch,
+};
 
 #[async_trait]
 pub trait BatchQueryImageService {
@@ -10,6 +15,20 @@ pub trait BatchQueryImageService {
         &self,
         count: i64,
         offset: i64,
+        sort: SortKey,
+    ) -> Result<Vec<Image>, BatchQueryImageServiceError>;
+    async fn batch_query_image_page(
+        &self,
+        cursor: Option<Cursor>,
+        count: i64,
+    ) -> Result<ImagePage, BatchQueryImageServiceError>;
+    async fn batch_query_image_by_tags(
+        &self,
+        tags: Vec<String>,
+        tag_match: TagMatch,
+        count: i64,
+        offset: i64,
+        sort: SortKey,
     ) -> Result<Vec<Image>, BatchQueryImageServiceError>;
 }
 
@@ -19,6 +38,7 @@ pub enum BatchQueryImageServiceError {
     InternalError,
     InvalidRequest,
     NoRecordsFound,
+    InvalidTag(String),
 }
 
 impl Display for BatchQueryImageServiceError {
@@ -32,6 +52,7 @@ impl Display for BatchQueryImageServiceError {
                 f.write_str("Count or offset are below zero")
             }
             BatchQueryImageServiceError::NoRecordsFound => f.write_str("No records found"),
+            BatchQueryImageServiceError::InvalidTag(message) => f.write_str(message),
         }
     }
 }
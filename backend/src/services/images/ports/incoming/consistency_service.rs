@@ -0,0 +1,27 @@
+use std::{error::Error, fmt::Display};
+
+use async_trait::async_trait;
+
+use crate::services::images::domain::consistency::ConsistencyReport;
+
+#[async_trait]
+pub trait ConsistencyService {
+    /// Compares the image rows with the files on disk. With `repair` the
+    /// orphan files are quarantined and the rows marked by whether their
+    /// file exists.
+    async fn check(&self, repair: bool) -> Result<ConsistencyReport, ConsistencyServiceError>;
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ConsistencyServiceError {
+    InternalError,
+}
+
+impl Display for ConsistencyServiceError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConsistencyServiceError::InternalError => f.write_str("Internal error"),
+        }
+    }
+}
+impl Error for ConsistencyServiceError {}
@@ -4,7 +4,13 @@ use async_trait::async_trait;
 
 #[async_trait]
 pub trait DeleteImageService {
-    async fn delete_image(&self, index: i64) -> Result<(), DeleteImageServiceError>;
+    /// Moves the image to the trash, or deletes it and its files right away
+    /// when `permanent` is set.
+    async fn delete_image(
+        &self,
+        index: i64,
+        permanent: bool,
+    ) -> Result<(), DeleteImageServiceError>;
 }
 
 #[derive(Debug, PartialEq)]
@@ -0,0 +1,26 @@
+use std::{error::Error, fmt::Display};
+
+use async_trait::async_trait;
+
+use crate::services::images::domain::storage_layout::LayoutMigration;
+
+#[async_trait]
+pub trait LayoutService {
+    /// Moves every stored file that is not where the configured layout puts
+    /// it, one image at a time.
+    async fn migrate(&self) -> Result<LayoutMigration, LayoutServiceError>;
+}
+
+#[derive(Debug, PartialEq)]
+pub enum LayoutServiceError {
+    InternalError,
+}
+
+impl Display for LayoutServiceError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LayoutServiceError::InternalError => f.write_str("Internal error"),
+        }
+    }
+}
+impl Error for LayoutServiceError {}
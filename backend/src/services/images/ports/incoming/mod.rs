@@ -1,5 +1,17 @@
+pub mod albums_service;
+pub mod api_keys_service;
 pub mod batch_delete_image_service;
 pub mod batch_query_image_service;
+pub mod consistency_service;
 pub mod delete_image_service;
+pub mod layout_service;
 pub mod query_image_service;
+pub mod rendition_service;
+pub mod scrub_service;
+pub mod search_images_service;
+pub mod shares_service;
+pub mod similar_images_service;
+pub mod tags_service;
+pub mod trash_service;
 pub mod upload_images_service;
+pub mod users_service;
@@ -0,0 +1,30 @@
+use std::{error::Error, fmt::Display};
+
+use async_trait::async_trait;
+
+use crate::services::images::domain::rendition::{Rendition, RenditionSize};
+
+#[async_trait]
+pub trait RenditionService {
+    async fn query_rendition(
+        &self,
+        index: i64,
+        size: RenditionSize,
+    ) -> Result<Rendition, RenditionServiceError>;
+}
+
+#[derive(Debug, PartialEq)]
+pub enum RenditionServiceError {
+    ImageNotFound,
+    InternalError,
+}
+
+impl Display for RenditionServiceError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RenditionServiceError::ImageNotFound => f.write_str("Image not found"),
+            RenditionServiceError::InternalError => f.write_str("Internal error"),
+        }
+    }
+}
+impl Error for RenditionServiceError {}
@@ -0,0 +1,34 @@
+use std::{error::Error, fmt::Display};
+
+use async_trait::async_trait;
+
+use crate::services::images::domain::{
+    image_status::ImageStatus,
+    integrity::{IntegritySummary, ScrubSummary},
+};
+
+#[async_trait]
+pub trait ScrubService {
+    /// Re-reads the next `count` stored files, verifying their checksum and
+    /// that they still decode.
+    async fn scrub(&self, count: i64) -> Result<ScrubSummary, ScrubServiceError>;
+    async fn integrity(&self) -> Result<IntegritySummary, ScrubServiceError>;
+    /// Live images by status, counted without loading any of them.
+    async fn image_counts(&self) -> Result<Vec<(ImageStatus, i64)>, ScrubServiceError>;
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ScrubServiceError {
+    InvalidRequest,
+    InternalError,
+}
+
+impl Display for ScrubServiceError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ScrubServiceError::InvalidRequest => f.write_str("Count must be above zero"),
+            ScrubServiceError::InternalError => f.write_str("Internal error"),
+        }
+    }
+}
+impl Error for ScrubServiceError {}
@@ -0,0 +1,33 @@
+use std::{error::Error, fmt::Display};
+
+use async_trait::async_trait;
+
+use crate::services::images::domain::{image::Image, search::SearchQuery};
+
+#[async_trait]
+pub trait SearchImagesService {
+    async fn search_images(
+        &self,
+        query: SearchQuery,
+    ) -> Result<Vec<Image>, SearchImagesServiceError>;
+}
+
+#[derive(Debug, PartialEq)]
+pub enum SearchImagesServiceError {
+    InvalidRequest(String),
+    TooManyImagesRequested,
+    InternalError,
+}
+
+impl Display for SearchImagesServiceError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SearchImagesServiceError::InvalidRequest(message) => f.write_str(message),
+            SearchImagesServiceError::TooManyImagesRequested => {
+                f.write_str("Too many images requested")
+            }
+            SearchImagesServiceError::InternalError => f.write_str("Internal error"),
+        }
+    }
+}
+impl Error for SearchImagesServiceError {}
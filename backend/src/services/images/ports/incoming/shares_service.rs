@@ -0,0 +1,47 @@
+use std::{error::Error, fmt::Display};
+
+use async_trait::async_trait;
+
+use crate::services::images::domain::share::Share;
+
+#[async_trait]
+pub trait SharesService {
+    /// Returns the share with the signature of its link; `expires_in` is in
+    /// seconds.
+    async fn share_image(
+        &self,
+        image_id: i64,
+        expires_in: Option<i64>,
+    ) -> Result<(Share, String), SharesServiceError>;
+    async fn revoke_share(&self, id: i64) -> Result<(), SharesServiceError>;
+    /// The share a link points at, if its signature holds and it is neither
+    /// expired nor revoked.
+    async fn verify_link(
+        &self,
+        id: i64,
+        expires: i64,
+        signature: &str,
+    ) -> Result<Share, SharesServiceError>;
+}
+
+#[derive(Debug, PartialEq)]
+pub enum SharesServiceError {
+    ImageNotFound,
+    ShareNotFound,
+    InvalidLink,
+    InvalidRequest(String),
+    InternalError,
+}
+
+impl Display for SharesServiceError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SharesServiceError::ImageNotFound => f.write_str("Image not found"),
+            SharesServiceError::ShareNotFound => f.write_str("Share not found"),
+            SharesServiceError::InvalidLink => f.write_str("Invalid or expired share link"),
+            SharesServiceError::InvalidRequest(message) => f.write_str(message),
+            SharesServiceError::InternalError => f.write_str("Internal error"),
+        }
+    }
+}
+impl Error for SharesServiceError {}
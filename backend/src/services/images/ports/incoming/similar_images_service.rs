@@ -0,0 +1,43 @@
+use std::{error::Error, fmt::Display};
+
+use async_trait::async_trait;
+
+use crate::services::images::domain::{image::Image, similar_image::SimilarImage};
+
+#[async_trait]
+pub trait SimilarImagesService {
+    async fn similar_images(
+        &self,
+        index: i64,
+        max_distance: u32,
+    ) -> Result<Vec<SimilarImage>, SimilarImagesServiceError>;
+
+    async fn duplicate_clusters(
+        &self,
+        max_distance: u32,
+    ) -> Result<Vec<Vec<Image>>, SimilarImagesServiceError>;
+}
+
+#[derive(Debug, PartialEq)]
+pub enum SimilarImagesServiceError {
+    ImageNotFound,
+    MissingPerceptualHash,
+    DistanceTooLarge(u32),
+    InternalError,
+}
+
+impl Display for SimilarImagesServiceError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SimilarImagesServiceError::ImageNotFound => f.write_str("Image not found"),
+            SimilarImagesServiceError::MissingPerceptualHash => {
+                f.write_str("Image has no perceptual hash")
+            }
+            SimilarImagesServiceError::DistanceTooLarge(max) => {
+                f.write_str(format!("Distance too large. Max: {}", max).as_str())
+            }
+            SimilarImagesServiceError::InternalError => f.write_str("Internal error"),
+        }
+    }
+}
+impl Error for SimilarImagesServiceError {}
@@ -0,0 +1,37 @@
+use std::{error::Error, fmt::Display};
+
+use async_trait::async_trait;
+
+use crate::services::images::domain::tag::Tag;
+
+#[async_trait]
+pub trait TagsService {
+    async fn tag_image(
+        &self,
+        image_id: i64,
+        tags: Vec<String>,
+    ) -> Result<Vec<String>, TagsServiceError>;
+    async fn untag_image(&self, image_id: i64, tag: String) -> Result<(), TagsServiceError>;
+    async fn tags(&self) -> Result<Vec<Tag>, TagsServiceError>;
+    async fn rename_tag(&self, from: String, to: String) -> Result<(), TagsServiceError>;
+}
+
+#[derive(Debug, PartialEq)]
+pub enum TagsServiceError {
+    ImageNotFound,
+    TagNotFound,
+    InvalidTag(String),
+    InternalError,
+}
+
+impl Display for TagsServiceError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TagsServiceError::ImageNotFound => f.write_str("Image not found"),
+            TagsServiceError::TagNotFound => f.write_str("Tag not found"),
+            TagsServiceError::InvalidTag(message) => f.write_str(message),
+            TagsServiceError::InternalError => f.write_str("Internal error"),
+        }
+    }
+}
+impl Error for TagsServiceError {}
@@ -0,0 +1,34 @@
+use std::{error::Error, fmt::Display, time::Duration};
+
+use async_trait::async_trait;
+
+use crate::services::images::domain::image::Image;
+
+#[async_trait]
+pub trait TrashService {
+    async fn trash(&self, count: i64, offset: i64) -> Result<Vec<Image>, TrashServiceError>;
+    async fn restore_image(&self, index: i64) -> Result<(), TrashServiceError>;
+    /// Permanently deletes the images kept in the trash for longer than
+    /// `retention`.
+    async fn purge(&self, retention: Duration) -> Result<(), TrashServiceError>;
+}
+
+#[derive(Debug, PartialEq)]
+pub enum TrashServiceError {
+    ImageNotFound,
+    InvalidRequest,
+    TooManyImagesRequested,
+    InternalError,
+}
+
+impl Display for TrashServiceError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TrashServiceError::ImageNotFound => f.write_str("Image not found"),
+            TrashServiceError::InvalidRequest => f.write_str("Count or offset are below zero"),
+            TrashServiceError::TooManyImagesRequested => f.write_str("Too many images requested"),
+            TrashServiceError::InternalError => f.write_str("Internal error"),
+        }
+    }
+}
+impl Error for TrashServiceError {}
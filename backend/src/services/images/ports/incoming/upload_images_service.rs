@@ -4,7 +4,11 @@ use async_trait::async_trait;
 
 #[async_trait]
 pub trait UploadImagesService {
-    async fn upload_image(&self, buffer: Vec<u8>) -> Result<(), UploadImagesServiceError>;
+    async fn upload_image(
+        &self,
+        buffer: Vec<u8>,
+        filename: Option<String>,
+    ) -> Result<i64, UploadImagesServiceError>;
 }
 
 #[derive(Debug, PartialEq)]
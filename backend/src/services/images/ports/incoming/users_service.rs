@@ -0,0 +1,40 @@
+use std::{error::Error, fmt::Display};
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+
+use crate::services::images::domain::user::User;
+
+#[async_trait]
+pub trait UsersService {
+    async fn register(&self, name: String, password: String) -> Result<User, UsersServiceError>;
+    /// Returns the user with a new session token and when it expires.
+    async fn login(
+        &self,
+        name: String,
+        password: String,
+    ) -> Result<(User, String, DateTime<Utc>), UsersServiceError>;
+    async fn logout(&self, token: &str) -> Result<(), UsersServiceError>;
+    /// The user of an unexpired session token.
+    async fn authenticate(&self, token: &str) -> Result<User, UsersServiceError>;
+}
+
+#[derive(Debug, PartialEq)]
+pub enum UsersServiceError {
+    InvalidCredentials,
+    AlreadyExists,
+    InvalidRequest(String),
+    InternalError,
+}
+
+impl Display for UsersServiceError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            UsersServiceError::InvalidCredentials => f.write_str("Invalid credentials"),
+            UsersServiceError::AlreadyExists => f.write_str("User already exists"),
+            UsersServiceError::InvalidRequest(message) => f.write_str(message),
+            UsersServiceError::InternalError => f.write_str("Internal error"),
+        }
+    }
+}
+impl Error for UsersServiceError {}
@@ -0,0 +1,103 @@
+use std::{error::Error, fmt::Display, sync::Arc};
+
+use async_trait::async_trait;
+
+use crate::services::images::domain::{
+    album::{Album, AlbumUpdate},
+    image::Image,
+};
+
+#[async_trait]
+pub trait AlbumsPort {
+    async fn create_album(&self, name: &str, parent_id: Option<i64>) -> Result<Album, AlbumsError>;
+    async fn query_album(&self, id: i64) -> Result<Album, AlbumsError>;
+    /// Albums directly below `parent_id`, or the top level ones.
+    async fn query_albums(&self, parent_id: Option<i64>) -> Result<Vec<Album>, AlbumsError>;
+    async fn update_album(&self, id: i64, update: &AlbumUpdate) -> Result<Album, AlbumsError>;
+    /// Deletes the album and its nested albums. With `cascade` the images in
+    /// them are moved to the trash too.
+    async fn delete_album(&self, id: i64, cascade: bool) -> Result<(), AlbumsError>;
+    /// Appends the images not yet in the album, in the given order.
+    async fn add_images(&self, album_id: i64, image_ids: &[i64]) -> Result<(), AlbumsError>;
+    async fn remove_image(&self, album_id: i64, image_id: i64) -> Result<(), AlbumsError>;
+    /// Reorders the album; `image_ids` must list every image in it once.
+    async fn reorder_images(&self, album_id: i64, image_ids: &[i64]) -> Result<(), AlbumsError>;
+    async fn query_album_images(
+        &self,
+        album_id: i64,
+        count: i64,
+        offset: i64,
+    ) -> Result<Vec<Image>, AlbumsError>;
+}
+
+#[async_trait]
+impl<T> AlbumsPort for Arc<T>
+where
+    T: AlbumsPort + Send + Sync + ?Sized,
+{
+    async fn create_album(&self, name: &str, parent_id: Option<i64>) -> Result<Album, AlbumsError> {
+        self.as_ref().create_album(name, parent_id).await
+    }
+
+    async fn query_album(&self, id: i64) -> Result<Album, AlbumsError> {
+        self.as_ref().query_album(id).await
+    }
+
+    async fn query_albums(&self, parent_id: Option<i64>) -> Result<Vec<Album>, AlbumsError> {
+        self.as_ref().query_albums(parent_id).await
+    }
+
+    async fn update_album(&self, id: i64, update: &AlbumUpdate) -> Result<Album, AlbumsError> {
+        self.as_ref().update_album(id, update).await
+    }
+
+    async fn delete_album(&self, id: i64, cascade: bool) -> Result<(), AlbumsError> {
+        self.as_ref().delete_album(id, cascade).await
+    }
+
+    async fn add_images(&self, album_id: i64, image_ids: &[i64]) -> Result<(), AlbumsError> {
+        self.as_ref().add_images(album_id, image_ids).await
+    }
+
+    async fn remove_image(&self, album_id: i64, image_id: i64) -> Result<(), AlbumsError> {
+        self.as_ref().remove_image(album_id, image_id).await
+    }
+
+    async fn reorder_images(&self, album_id: i64, image_ids: &[i64]) -> Result<(), AlbumsError> {
+        self.as_ref().reorder_images(album_id, image_ids).await
+    }
+
+    async fn query_album_images(
+        &self,
+        album_id: i64,
+        count: i64,
+        offset: i64,
+    ) -> Result<Vec<Image>, AlbumsError> {
+        self.as_ref()
+            .query_album_images(album_id, count, offset)
+            .await
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum AlbumsError {
+    AlbumNotFound,
+    ImageNotFound,
+    InvalidParent,
+    InvalidOrder,
+    InternalError,
+}
+
+impl Display for AlbumsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AlbumsError::AlbumNotFound => write!(f, "Album not found"),
+            AlbumsError::ImageNotFound => write!(f, "Image not found"),
+            AlbumsError::InvalidParent => write!(f, "Album cannot be nested below itself"),
+            AlbumsError::InvalidOrder => write!(f, "Order must list every image of the album"),
+            AlbumsError::InternalError => write!(f, "Internal error"),
+        }
+    }
+}
+
+impl Error for AlbumsError {}
@@ -0,0 +1,71 @@
+use std::{error::Error, fmt::Display, sync::Arc};
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+
+use crate::services::images::domain::api_key::{ApiKey, Scope};
+
+#[async_trait]
+pub trait ApiKeysPort {
+    async fn insert_api_key(
+        &self,
+        name: &str,
+        key_hash: &str,
+        scopes: &[Scope],
+        created_on: DateTime<Utc>,
+    ) -> Result<ApiKey, ApiKeysError>;
+    /// Revoked keys are returned too; callers decide what to do with them.
+    async fn query_api_key_by_hash(&self, key_hash: &str) -> Result<ApiKey, ApiKeysError>;
+    async fn query_api_keys(&self) -> Result<Vec<ApiKey>, ApiKeysError>;
+    /// Fails with `KeyNotFound` for unknown and already revoked keys.
+    async fn revoke_api_key(&self, id: i64, revoked_on: DateTime<Utc>) -> Result<(), ApiKeysError>;
+}
+
+#[async_trait]
+impl<T> ApiKeysPort for Arc<T>
+where
+    T: ApiKeysPort + Send + Sync + ?Sized,
+{
+    async fn insert_api_key(
+        &self,
+        name: &str,
+        key_hash: &str,
+        scopes: &[Scope],
+        created_on: DateTime<Utc>,
+    ) -> Result<ApiKey, ApiKeysError> {
+        self.as_ref()
+            .insert_api_key(name, key_hash, scopes, created_on)
+            .await
+    }
+
+    async fn query_api_key_by_hash(&self, key_hash: &str) -> Result<ApiKey, ApiKeysError> {
+        self.as_ref().query_api_key_by_hash(key_hash).await
+    }
+
+    async fn query_api_keys(&self) -> Result<Vec<ApiKey>, ApiKeysError> {
+        self.as_ref().query_api_keys().await
+    }
+
+    async fn revoke_api_key(&self, id: i64, revoked_on: DateTime<Utc>) -> Result<(), ApiKeysError> {
+        self.as_ref().revoke_api_key(id, revoked_on).await
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ApiKeysError {
+    KeyNotFound,
+    AlreadyExists,
+    InternalError,
+}
+
+impl Display for ApiKeysError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ApiKeysError::KeyNotFound => write!(f, "API key not found"),
+            ApiKeysError::AlreadyExists => write!(f, "API key already exists"),
+            ApiKeysError::InternalError => write!(f, "Internal error"),
+        }
+    }
+}
+
+impl Error for ApiKeysError {}
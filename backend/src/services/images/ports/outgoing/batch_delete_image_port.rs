@@ -1,11 +1,23 @@
-use std::{error::Error, fmt::Display};
+use std::{error::Error, fmt::Display, sync::Arc};
 
 use async_trait::async_trait;
 #[async_trait]
 pub trait BatchDeleteImagePort {
+    /// Deletes the images and their renditions, returning every file path
+    /// that belonged to them.
     async fn batch_delete_image(&self, index: Vec<i64>) -> Result<Vec<String>, BatchDeleteError>;
 }
 
+#[async_trait]
+impl<T> BatchDeleteImagePort for Arc<T>
+where
+    T: BatchDeleteImagePort + Send + Sync + ?Sized,
+{
+    async fn batch_delete_image(&self, index: Vec<i64>) -> Result<Vec<String>, BatchDeleteError> {
+        self.as_ref().batch_delete_image(index).await
+    }
+}
+
 #[derive(Debug)]
 pub enum BatchDeleteError {
     TooManyImagesToDelete,
@@ -1,9 +1,68 @@
-use crate::services::images::domain::image::Image;
+use crate::services::images::domain::{
+    cursor::Cursor, image::Image, sort_key::SortKey, tag::TagMatch,
+};
 use async_trait::async_trait;
-use std::{error::Error, fmt::Display};
+use std::{error::Error, fmt::Display, sync::Arc};
 #[async_trait]
 pub trait BatchQueryImagesPort {
-    async fn query_images(&self, count: i64, offset: i64) -> Result<Vec<Image>, QueryError>;
+    async fn query_images(
+        &self,
+        count: i64,
+        offset: i64,
+        sort: SortKey,
+    ) -> Result<Vec<Image>, QueryError>;
+    /// Up to `count` images ordered by `(updated_on, id)`, strictly after
+    /// `cursor` when one is given.
+    async fn query_images_after(
+        &self,
+        cursor: Option<Cursor>,
+        count: i64,
+    ) -> Result<Vec<Image>, QueryError>;
+    /// Images carrying all or any of the given, already normalized, tags.
+    async fn query_images_by_tags(
+        &self,
+        tags: &[String],
+        tag_match: TagMatch,
+        count: i64,
+        offset: i64,
+        sort: SortKey,
+    ) -> Result<Vec<Image>, QueryError>;
+}
+
+#[async_trait]
+impl<T> BatchQueryImagesPort for Arc<T>
+where
+    T: BatchQueryImagesPort + Send + Sync + ?Sized,
+{
+    async fn query_images(
+        &self,
+        count: i64,
+        offset: i64,
+        sort: SortKey,
+    ) -> Result<Vec<Image>, QueryError> {
+        self.as_ref().query_images(count, offset, sort).await
+    }
+
+    async fn query_images_after(
+        &self,
+        cursor: Option<Cursor>,
+        count: i64,
+    ) -> Result<Vec<Image>, QueryError> {
+        self.as_ref().query_images_after(cursor, count).await
+    }
+
+    async fn query_images_by_tags(
+        &self,
+        tags: &[String],
+        tag_match: TagMatch,
+        count: i64,
+        offset: i64,
+        sort: SortKey,
+    ) -> Result<Vec<Image>, QueryError> {
+        self.as_ref()
+            .query_images_by_tags(tags, tag_match, count, offset, sort)
+            .await
+    }
 }
 
 #[derive(Debug)]
@@ -0,0 +1,73 @@
+use std::{error::Error, fmt::Display, sync::Arc, time::SystemTime};
+
+use async_trait::async_trait;
+use bytes::Bytes;
+use futures::{stream::BoxStream, TryStreamExt};
+
+/// The content of a blob, in chunks as they come off the backend.
+pub type BlobStream = BoxStream<'static, std::io::Result<Bytes>>;
+
+pub type DynBlobStore = Arc<dyn BlobStorePort + Send + Sync>;
+
+/// A stored blob, as listed by [`BlobStorePort::list`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct BlobInfo {
+    pub key: String,
+    pub size: u64,
+    pub modified: Option<SystemTime>,
+}
+
+/// Where image files live. Keys are the paths recorded in the database,
+/// with `/` separating directories.
+#[async_trait]
+pub trait BlobStorePort {
+    /// Stores `bytes` under `key`, replacing any previous blob. Readers
+    /// never see a partially written blob.
+    async fn put(&self, key: &str, bytes: Vec<u8>) -> Result<(), BlobStoreError>;
+    async fn get(&self, key: &str) -> Result<BlobStream, BlobStoreError>;
+    /// Removes the blob; removing a missing blob is not an error.
+    async fn delete(&self, key: &str) -> Result<(), BlobStoreError>;
+    async fn exists(&self, key: &str) -> Result<bool, BlobStoreError>;
+    async fn size(&self, key: &str) -> Result<u64, BlobStoreError>;
+    /// Every blob below the `prefix` directory, at any depth.
+    async fn list(&self, prefix: &str) -> Result<Vec<BlobInfo>, BlobStoreError>;
+    async fn rename(&self, from: &str, to: &str) -> Result<(), BlobStoreError>;
+
+    /// The whole content of the blob in memory.
+    async fn read(&self, key: &str) -> Result<Vec<u8>, BlobStoreError> {
+        let stream = self.get(key).await?;
+        stream
+            .try_fold(vec![], |mut content, chunk| async move {
+                content.extend_from_slice(&chunk);
+                Ok(content)
+            })
+            .await
+            .map_err(BlobStoreError::from)
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum BlobStoreError {
+    NotFound,
+    InternalError(String),
+}
+
+impl Display for BlobStoreError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BlobStoreError::NotFound => write!(f, "Blob not found"),
+            BlobStoreError::InternalError(message) => write!(f, "Internal error: {}", message),
+        }
+    }
+}
+
+impl Error for BlobStoreError {}
+
+impl From<std::io::Error> for BlobStoreError {
+    fn from(value: std::io::Error) -> Self {
+        match value.kind() {
+            std::io::ErrorKind::NotFound => BlobStoreError::NotFound,
+            _ => BlobStoreError::InternalError(value.to_string()),
+        }
+    }
+}
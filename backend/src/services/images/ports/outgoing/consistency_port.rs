@@ -0,0 +1,55 @@
+use std::{collections::HashSet, error::Error, fmt::Display, sync::Arc};
+
+use async_trait::async_trait;
+
+use crate::services::images::domain::{consistency::ImageFile, image_status::ImageStatus};
+
+#[async_trait]
+pub trait ConsistencyPort {
+    /// The file of every image row, trashed ones included.
+    async fn query_image_files(&self) -> Result<Vec<ImageFile>, ConsistencyError>;
+    /// Every path a row refers to: stored images, originals and renditions.
+    async fn query_referenced_paths(&self) -> Result<HashSet<String>, ConsistencyError>;
+    async fn update_image_status(
+        &self,
+        indexes: &[i64],
+        status: ImageStatus,
+    ) -> Result<(), ConsistencyError>;
+}
+
+#[async_trait]
+impl<T> ConsistencyPort for Arc<T>
+where
+    T: ConsistencyPort + Send + Sync + ?Sized,
+{
+    async fn query_image_files(&self) -> Result<Vec<ImageFile>, ConsistencyError> {
+        self.as_ref().query_image_files().await
+    }
+
+    async fn query_referenced_paths(&self) -> Result<HashSet<String>, ConsistencyError> {
+        self.as_ref().query_referenced_paths().await
+    }
+
+    async fn update_image_status(
+        &self,
+        indexes: &[i64],
+        status: ImageStatus,
+    ) -> Result<(), ConsistencyError> {
+        self.as_ref().update_image_status(indexes, status).await
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ConsistencyError {
+    InternalError,
+}
+
+impl Display for ConsistencyError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConsistencyError::InternalError => write!(f, "Internal error"),
+        }
+    }
+}
+
+impl Error for ConsistencyError {}
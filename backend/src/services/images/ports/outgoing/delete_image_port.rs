@@ -1,10 +1,44 @@
-use std::{error::Error, fmt::Display};
+use std::{error::Error, fmt::Display, sync::Arc};
 
 use async_trait::async_trait;
+
+use super::insert_image_port::Persist;
+
+/// Removes the files of the deleted images, given every path they used.
+pub type RemoveFiles = Box<dyn FnOnce(Vec<String>) -> Persist + Send>;
+
 // #[automock(type Index = i64;)]
 #[async_trait]
 pub trait DeleteImagePort {
-    async fn delete_image(&self, index: i64) -> Result<String, DeleteImageError>;
+    /// Deletes the image and its renditions, returning every file path that
+    /// belonged to it with the original first.
+    async fn delete_image(&self, index: i64) -> Result<Vec<String>, DeleteImageError>;
+    /// Deletes the image and awaits `remove` on its paths before committing,
+    /// so no new image can take a path while its old file is still there,
+    /// and the row stays when the files cannot be removed.
+    async fn delete_image_with(
+        &self,
+        index: i64,
+        remove: RemoveFiles,
+    ) -> Result<Vec<String>, DeleteImageError>;
+}
+
+#[async_trait]
+impl<T> DeleteImagePort for Arc<T>
+where
+    T: DeleteImagePort + Send + Sync + ?Sized,
+{
+    async fn delete_image(&self, index: i64) -> Result<Vec<String>, DeleteImageError> {
+        self.as_ref().delete_image(index).await
+    }
+
+    async fn delete_image_with(
+        &self,
+        index: i64,
+        remove: RemoveFiles,
+    ) -> Result<Vec<String>, DeleteImageError> {
+        self.as_ref().delete_image_with(index, remove).await
+    }
 }
 
 #[derive(Debug)]
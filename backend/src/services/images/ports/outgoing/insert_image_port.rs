@@ -1,21 +1,52 @@
 use async_trait::async_trait;
 
 use crate::services::images::domain::image::Image;
-use std::{error::Error, fmt::Display};
+use futures::future::BoxFuture;
+use std::{error::Error, fmt::Display, sync::Arc};
 // #[automock(type Index = i64;)]
+/// Stores the files of an image once its row is inserted.
+pub type Persist = BoxFuture<'static, std::io::Result<()>>;
+
 #[async_trait]
 pub trait InsertImagePort {
-    async fn insert_image(&self, record: &Image) -> Result<(), InsertImageError>;
+    async fn insert_image(&self, record: &Image) -> Result<i64, InsertImageError>;
+    /// Inserts the record and awaits `persist` before committing it, so the
+    /// row is rolled back when the files cannot be stored.
+    async fn insert_image_with(
+        &self,
+        record: &Image,
+        persist: Persist,
+    ) -> Result<i64, InsertImageError>;
+}
+
+#[async_trait]
+impl<T> InsertImagePort for Arc<T>
+where
+    T: InsertImagePort + Send + Sync + ?Sized,
+{
+    async fn insert_image(&self, record: &Image) -> Result<i64, InsertImageError> {
+        self.as_ref().insert_image(record).await
+    }
+
+    async fn insert_image_with(
+        &self,
+        record: &Image,
+        persist: Persist,
+    ) -> Result<i64, InsertImageError> {
+        self.as_ref().insert_image_with(record, persist).await
+    }
 }
 
 #[derive(Debug)]
 pub enum InsertImageError {
+    AlreadyExists,
     InternalError,
 }
 
 impl Display for InsertImageError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
+            Self::AlreadyExists => write!(f, "Record already exists"),
             Self::InternalError => write!(f, "Internal error"),
         }
     }
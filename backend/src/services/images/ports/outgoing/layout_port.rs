@@ -0,0 +1,62 @@
+use std::{error::Error, fmt::Display, sync::Arc};
+
+use async_trait::async_trait;
+
+use crate::services::images::domain::storage_layout::ImagePaths;
+
+use super::insert_image_port::Persist;
+
+#[async_trait]
+pub trait LayoutPort {
+    /// The files of up to `count` images with an id above `after`, trashed
+    /// ones included, in id order.
+    async fn query_image_paths(
+        &self,
+        after: i64,
+        count: i64,
+    ) -> Result<Vec<ImagePaths>, LayoutError>;
+    /// Records every path of `moves` under its new value and awaits `persist`
+    /// before committing, so nothing changes when the files cannot be moved.
+    async fn relocate_paths(
+        &self,
+        moves: &[(String, String)],
+        persist: Persist,
+    ) -> Result<(), LayoutError>;
+}
+
+#[async_trait]
+impl<T> LayoutPort for Arc<T>
+where
+    T: LayoutPort + Send + Sync + ?Sized,
+{
+    async fn query_image_paths(
+        &self,
+        after: i64,
+        count: i64,
+    ) -> Result<Vec<ImagePaths>, LayoutError> {
+        self.as_ref().query_image_paths(after, count).await
+    }
+
+    async fn relocate_paths(
+        &self,
+        moves: &[(String, String)],
+        persist: Persist,
+    ) -> Result<(), LayoutError> {
+        self.as_ref().relocate_paths(moves, persist).await
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum LayoutError {
+    InternalError,
+}
+
+impl Display for LayoutError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LayoutError::InternalError => write!(f, "Internal error"),
+        }
+    }
+}
+
+impl Error for LayoutError {}
@@ -0,0 +1,14 @@
+use std::sync::Arc;
+
+pub type DynMetrics = Arc<dyn MetricsPort + Send + Sync>;
+
+/// Counts what the services do, for monitoring. Recording never fails.
+pub trait MetricsPort {
+    /// A new image was stored; `bytes` is everything written for it.
+    fn record_stored_image(&self, bytes: u64);
+    /// The upload was an image already stored.
+    fn record_duplicate_upload(&self);
+    /// The upload could not be decoded; `format` is the one it was guessed
+    /// to be.
+    fn record_decode_failure(&self, format: &str);
+}
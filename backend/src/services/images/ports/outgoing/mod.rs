@@ -1,5 +1,21 @@
+pub mod albums_port;
+pub mod api_keys_port;
 pub mod batch_delete_image_port;
 pub mod batch_query_image_port;
+pub mod blob_store_port;
+pub mod consistency_port;
 pub mod delete_image_port;
 pub mod insert_image_port;
+pub mod layout_port;
+pub mod metrics_port;
+pub mod query_image_by_hash_port;
 pub mod query_image_port;
+pub mod rendition_port;
+pub mod scrub_port;
+pub mod search_images_port;
+pub mod shares_port;
+pub mod similar_images_port;
+pub mod storage_port;
+pub mod tags_port;
+pub mod trash_port;
+pub mod users_port;
@@ -0,0 +1,21 @@
+use std::sync::Arc;
+
+use crate::services::images::domain::image::Image;
+use async_trait::async_trait;
+
+use super::query_image_port::QueryError;
+
+#[async_trait]
+pub trait QueryImageByHashPort {
+    async fn query_image_by_hash(&self, hash: &str) -> Result<Image, QueryError>;
+}
+
+#[async_trait]
+impl<T> QueryImageByHashPort for Arc<T>
+where
+    T: QueryImageByHashPort + Send + Sync + ?Sized,
+{
+    async fn query_image_by_hash(&self, hash: &str) -> Result<Image, QueryError> {
+        self.as_ref().query_image_by_hash(hash).await
+    }
+}
@@ -1,11 +1,21 @@
 use crate::services::images::domain::image::Image;
 use async_trait::async_trait;
-use std::{error::Error, fmt::Display};
+use std::{error::Error, fmt::Display, sync::Arc};
 #[async_trait]
 pub trait QueryImagePort {
     async fn query_image(&self, index: i64) -> Result<Image, QueryError>;
 }
 
+#[async_trait]
+impl<T> QueryImagePort for Arc<T>
+where
+    T: QueryImagePort + Send + Sync + ?Sized,
+{
+    async fn query_image(&self, index: i64) -> Result<Image, QueryError> {
+        self.as_ref().query_image(index).await
+    }
+}
+
 #[derive(Debug)]
 pub enum QueryError {
     RecordNotFound,
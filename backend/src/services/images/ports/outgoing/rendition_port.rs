@@ -0,0 +1,35 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+
+use crate::services::images::domain::rendition::{Rendition, RenditionSize};
+
+use super::{insert_image_port::InsertImageError, query_image_port::QueryError};
+
+#[async_trait]
+pub trait RenditionPort {
+    async fn query_rendition(
+        &self,
+        image_id: i64,
+        size: RenditionSize,
+    ) -> Result<Rendition, QueryError>;
+    async fn insert_rendition(&self, rendition: &Rendition) -> Result<(), InsertImageError>;
+}
+
+#[async_trait]
+impl<T> RenditionPort for Arc<T>
+where
+    T: RenditionPort + Send + Sync + ?Sized,
+{
+    async fn query_rendition(
+        &self,
+        image_id: i64,
+        size: RenditionSize,
+    ) -> Result<Rendition, QueryError> {
+        self.as_ref().query_rendition(image_id, size).await
+    }
+
+    async fn insert_rendition(&self, rendition: &Rendition) -> Result<(), InsertImageError> {
+        self.as_ref().insert_rendition(rendition).await
+    }
+}
@@ -0,0 +1,70 @@
+use std::{error::Error, fmt::Display, sync::Arc};
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+
+use crate::services::images::domain::{
+    consistency::ImageFile, image::Image, image_status::ImageStatus,
+};
+
+#[async_trait]
+pub trait ScrubPort {
+    /// The `count` files checked the longest ago, never checked ones first.
+    async fn query_scrub_batch(&self, count: i64) -> Result<Vec<ImageFile>, ScrubError>;
+    /// Records the outcome of checking an image. `checksum` is only stored
+    /// for images that had none yet.
+    async fn record_scrub(
+        &self,
+        image_id: i64,
+        status: ImageStatus,
+        checksum: Option<String>,
+        checked_on: DateTime<Utc>,
+    ) -> Result<(), ScrubError>;
+    async fn count_images_by_status(&self) -> Result<Vec<(ImageStatus, i64)>, ScrubError>;
+    async fn query_images_by_status(&self, status: ImageStatus) -> Result<Vec<Image>, ScrubError>;
+}
+
+#[async_trait]
+impl<T> ScrubPort for Arc<T>
+where
+    T: ScrubPort + Send + Sync + ?Sized,
+{
+    async fn query_scrub_batch(&self, count: i64) -> Result<Vec<ImageFile>, ScrubError> {
+        self.as_ref().query_scrub_batch(count).await
+    }
+
+    async fn record_scrub(
+        &self,
+        image_id: i64,
+        status: ImageStatus,
+        checksum: Option<String>,
+        checked_on: DateTime<Utc>,
+    ) -> Result<(), ScrubError> {
+        self.as_ref()
+            .record_scrub(image_id, status, checksum, checked_on)
+            .await
+    }
+
+    async fn count_images_by_status(&self) -> Result<Vec<(ImageStatus, i64)>, ScrubError> {
+        self.as_ref().count_images_by_status().await
+    }
+
+    async fn query_images_by_status(&self, status: ImageStatus) -> Result<Vec<Image>, ScrubError> {
+        self.as_ref().query_images_by_status(status).await
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ScrubError {
+    InternalError,
+}
+
+impl Display for ScrubError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ScrubError::InternalError => write!(f, "Internal error"),
+        }
+    }
+}
+
+impl Error for ScrubError {}
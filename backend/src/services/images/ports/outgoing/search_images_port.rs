@@ -0,0 +1,23 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+
+use crate::services::images::domain::{image::Image, search::SearchQuery};
+
+use super::query_image_port::QueryError;
+
+#[async_trait]
+pub trait SearchImagesPort {
+    /// Tags and format in the query are expected to be normalized already.
+    async fn search_images(&self, query: &SearchQuery) -> Result<Vec<Image>, QueryError>;
+}
+
+#[async_trait]
+impl<T> SearchImagesPort for Arc<T>
+where
+    T: SearchImagesPort + Send + Sync + ?Sized,
+{
+    async fn search_images(&self, query: &SearchQuery) -> Result<Vec<Image>, QueryError> {
+        self.as_ref().search_images(query).await
+    }
+}
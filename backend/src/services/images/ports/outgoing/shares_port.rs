@@ -0,0 +1,65 @@
+use std::{error::Error, fmt::Display, sync::Arc};
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+
+use crate::services::images::domain::share::Share;
+
+#[async_trait]
+pub trait SharesPort {
+    /// Fails with `ImageNotFound` unless the image is live.
+    async fn insert_share(
+        &self,
+        image_id: i64,
+        created_on: DateTime<Utc>,
+        expires_on: DateTime<Utc>,
+    ) -> Result<Share, SharesError>;
+    /// Expired and revoked shares are returned too.
+    async fn query_share(&self, id: i64) -> Result<Share, SharesError>;
+    /// Fails with `ShareNotFound` for unknown and already revoked shares.
+    async fn revoke_share(&self, id: i64, revoked_on: DateTime<Utc>) -> Result<(), SharesError>;
+}
+
+#[async_trait]
+impl<T> SharesPort for Arc<T>
+where
+    T: SharesPort + Send + Sync + ?Sized,
+{
+    async fn insert_share(
+        &self,
+        image_id: i64,
+        created_on: DateTime<Utc>,
+        expires_on: DateTime<Utc>,
+    ) -> Result<Share, SharesError> {
+        self.as_ref()
+            .insert_share(image_id, created_on, expires_on)
+            .await
+    }
+
+    async fn query_share(&self, id: i64) -> Result<Share, SharesError> {
+        self.as_ref().query_share(id).await
+    }
+
+    async fn revoke_share(&self, id: i64, revoked_on: DateTime<Utc>) -> Result<(), SharesError> {
+        self.as_ref().revoke_share(id, revoked_on).await
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum SharesError {
+    ShareNotFound,
+    ImageNotFound,
+    InternalError,
+}
+
+impl Display for SharesError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SharesError::ShareNotFound => write!(f, "Share not found"),
+            SharesError::ImageNotFound => write!(f, "Image not found"),
+            SharesError::InternalError => write!(f, "Internal error"),
+        }
+    }
+}
+
+impl Error for SharesError {}
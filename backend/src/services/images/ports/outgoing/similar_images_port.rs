@@ -0,0 +1,30 @@
+use std::sync::Arc;
+
+use crate::services::images::domain::image::Image;
+use async_trait::async_trait;
+
+use super::query_image_port::QueryError;
+
+#[async_trait]
+pub trait SimilarImagesPort {
+    /// Returns `(id, perceptual_hash)` for every image with an id greater than
+    /// `after`, ordered by id, whoever owns it and trashed or not: the index
+    /// built from them is shared, and `query_images_by_ids` drops what the
+    /// caller cannot see.
+    async fn query_perceptual_hashes(&self, after: i64) -> Result<Vec<(i64, u64)>, QueryError>;
+    async fn query_images_by_ids(&self, indexes: Vec<i64>) -> Result<Vec<Image>, QueryError>;
+}
+
+#[async_trait]
+impl<T> SimilarImagesPort for Arc<T>
+where
+    T: SimilarImagesPort + Send + Sync + ?Sized,
+{
+    async fn query_perceptual_hashes(&self, after: i64) -> Result<Vec<(i64, u64)>, QueryError> {
+        self.as_ref().query_perceptual_hashes(after).await
+    }
+
+    async fn query_images_by_ids(&self, indexes: Vec<i64>) -> Result<Vec<Image>, QueryError> {
+        self.as_ref().query_images_by_ids(indexes).await
+    }
+}
@@ -0,0 +1,65 @@
+use std::sync::Arc;
+
+use super::{
+    albums_port::AlbumsPort, api_keys_port::ApiKeysPort,
+    batch_delete_image_port::BatchDeleteImagePort, batch_query_image_port::BatchQueryImagesPort,
+    consistency_port::ConsistencyPort, delete_image_port::DeleteImagePort,
+    insert_image_port::InsertImagePort, layout_port::LayoutPort,
+    query_image_by_hash_port::QueryImageByHashPort, query_image_port::QueryImagePort,
+    rendition_port::RenditionPort, scrub_port::ScrubPort, search_images_port::SearchImagesPort,
+    shares_port::SharesPort, similar_images_port::SimilarImagesPort, tags_port::TagsPort,
+    trash_port::TrashPort, users_port::UsersPort,
+};
+
+/// Shared handle to the metadata store picked from the configuration. Every
+/// port is implemented for `Arc`, so it is handed to the services as is.
+pub type DynStorage = Arc<dyn StoragePort>;
+
+/// Every outgoing port backed by the metadata store.
+pub trait StoragePort:
+    QueryImagePort
+    + QueryImageByHashPort
+    + BatchQueryImagesPort
+    + InsertImagePort
+    + DeleteImagePort
+    + BatchDeleteImagePort
+    + RenditionPort
+    + SearchImagesPort
+    + SimilarImagesPort
+    + TagsPort
+    + AlbumsPort
+    + TrashPort
+    + ConsistencyPort
+    + ScrubPort
+    + LayoutPort
+    + ApiKeysPort
+    + UsersPort
+    + SharesPort
+    + Send
+    + Sync
+{
+}
+
+impl<T> StoragePort for T where
+    T: QueryImagePort
+        + QueryImageByHashPort
+        + BatchQueryImagesPort
+        + InsertImagePort
+        + DeleteImagePort
+        + BatchDeleteImagePort
+        + RenditionPort
+        + SearchImagesPort
+        + SimilarImagesPort
+        + TagsPort
+        + AlbumsPort
+        + TrashPort
+        + ConsistencyPort
+        + ScrubPort
+        + LayoutPort
+        + ApiKeysPort
+        + UsersPort
+        + SharesPort
+        + Send
+        + Sync
+{
+}
@@ -0,0 +1,57 @@
+use std::{error::Error, fmt::Display, sync::Arc};
+
+use async_trait::async_trait;
+
+use crate::services::images::domain::tag::Tag;
+
+#[async_trait]
+pub trait TagsPort {
+    /// Adds the tags to the image and returns all of its tags.
+    async fn add_tags(&self, image_id: i64, tags: &[String]) -> Result<Vec<String>, TagsError>;
+    async fn remove_tag(&self, image_id: i64, tag: &str) -> Result<(), TagsError>;
+    async fn query_tags(&self) -> Result<Vec<Tag>, TagsError>;
+    /// Renames a tag on every image using it, merging into `to` when it
+    /// already exists.
+    async fn rename_tag(&self, from: &str, to: &str) -> Result<(), TagsError>;
+}
+
+#[async_trait]
+impl<T> TagsPort for Arc<T>
+where
+    T: TagsPort + Send + Sync + ?Sized,
+{
+    async fn add_tags(&self, image_id: i64, tags: &[String]) -> Result<Vec<String>, TagsError> {
+        self.as_ref().add_tags(image_id, tags).await
+    }
+
+    async fn remove_tag(&self, image_id: i64, tag: &str) -> Result<(), TagsError> {
+        self.as_ref().remove_tag(image_id, tag).await
+    }
+
+    async fn query_tags(&self) -> Result<Vec<Tag>, TagsError> {
+        self.as_ref().query_tags().await
+    }
+
+    async fn rename_tag(&self, from: &str, to: &str) -> Result<(), TagsError> {
+        self.as_ref().rename_tag(from, to).await
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum TagsError {
+    ImageNotFound,
+    TagNotFound,
+    InternalError,
+}
+
+impl Display for TagsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TagsError::ImageNotFound => write!(f, "Image not found"),
+            TagsError::TagNotFound => write!(f, "Tag not found"),
+            TagsError::InternalError => write!(f, "Internal error"),
+        }
+    }
+}
+
+impl Error for TagsError {}
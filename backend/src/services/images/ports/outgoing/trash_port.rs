@@ -0,0 +1,82 @@
+use std::{error::Error, fmt::Display, sync::Arc};
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+
+use crate::services::images::domain::image::Image;
+
+use super::delete_image_port::RemoveFiles;
+
+#[async_trait]
+pub trait TrashPort {
+    /// Moves the images not yet in the trash there, returning how many moved.
+    async fn trash_images(
+        &self,
+        indexes: &[i64],
+        deleted_at: DateTime<Utc>,
+    ) -> Result<u64, TrashError>;
+    async fn restore_image(&self, index: i64) -> Result<(), TrashError>;
+    /// Trashed images, most recently deleted first.
+    async fn query_trash(&self, count: i64, offset: i64) -> Result<Vec<Image>, TrashError>;
+    /// Permanently deletes the images trashed before `before`, returning
+    /// every file path that belonged to them.
+    async fn purge_trash(&self, before: DateTime<Utc>) -> Result<Vec<String>, TrashError>;
+    /// Like [`TrashPort::purge_trash`], awaiting `remove` on the paths before
+    /// committing.
+    async fn purge_trash_with(
+        &self,
+        before: DateTime<Utc>,
+        remove: RemoveFiles,
+    ) -> Result<Vec<String>, TrashError>;
+}
+
+#[async_trait]
+impl<T> TrashPort for Arc<T>
+where
+    T: TrashPort + Send + Sync + ?Sized,
+{
+    async fn trash_images(
+        &self,
+        indexes: &[i64],
+        deleted_at: DateTime<Utc>,
+    ) -> Result<u64, TrashError> {
+        self.as_ref().trash_images(indexes, deleted_at).await
+    }
+
+    async fn restore_image(&self, index: i64) -> Result<(), TrashError> {
+        self.as_ref().restore_image(index).await
+    }
+
+    async fn query_trash(&self, count: i64, offset: i64) -> Result<Vec<Image>, TrashError> {
+        self.as_ref().query_trash(count, offset).await
+    }
+
+    async fn purge_trash(&self, before: DateTime<Utc>) -> Result<Vec<String>, TrashError> {
+        self.as_ref().purge_trash(before).await
+    }
+
+    async fn purge_trash_with(
+        &self,
+        before: DateTime<Utc>,
+        remove: RemoveFiles,
+    ) -> Result<Vec<String>, TrashError> {
+        self.as_ref().purge_trash_with(before, remove).await
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum TrashError {
+    ImageNotFound,
+    InternalError,
+}
+
+impl Display for TrashError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TrashError::ImageNotFound => write!(f, "Image not found"),
+            TrashError::InternalError => write!(f, "Internal error"),
+        }
+    }
+}
+
+impl Error for TrashError {}
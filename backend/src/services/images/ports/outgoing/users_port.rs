@@ -0,0 +1,95 @@
+use std::{error::Error, fmt::Display, sync::Arc};
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+
+use crate::services::images::domain::user::User;
+
+#[async_trait]
+pub trait UsersPort {
+    async fn insert_user(
+        &self,
+        name: &str,
+        password_hash: &str,
+        created_on: DateTime<Utc>,
+    ) -> Result<User, UsersError>;
+    /// The user with their password hash.
+    async fn query_user_by_name(&self, name: &str) -> Result<(User, String), UsersError>;
+    async fn insert_session(
+        &self,
+        user_id: i64,
+        token_hash: &str,
+        expires_on: DateTime<Utc>,
+    ) -> Result<(), UsersError>;
+    /// Fails with `UserNotFound` for unknown sessions and those expired
+    /// before `now`.
+    async fn query_session_user(
+        &self,
+        token_hash: &str,
+        now: DateTime<Utc>,
+    ) -> Result<User, UsersError>;
+    async fn delete_session(&self, token_hash: &str) -> Result<(), UsersError>;
+}
+
+#[async_trait]
+impl<T> UsersPort for Arc<T>
+where
+    T: UsersPort + Send + Sync + ?Sized,
+{
+    async fn insert_user(
+        &self,
+        name: &str,
+        password_hash: &str,
+        created_on: DateTime<Utc>,
+    ) -> Result<User, UsersError> {
+        self.as_ref()
+            .insert_user(name, password_hash, created_on)
+            .await
+    }
+
+    async fn query_user_by_name(&self, name: &str) -> Result<(User, String), UsersError> {
+        self.as_ref().query_user_by_name(name).await
+    }
+
+    async fn insert_session(
+        &self,
+        user_id: i64,
+        token_hash: &str,
+        expires_on: DateTime<Utc>,
+    ) -> Result<(), UsersError> {
+        self.as_ref()
+            .insert_session(user_id, token_hash, expires_on)
+            .await
+    }
+
+    async fn query_session_user(
+        &self,
+        token_hash: &str,
+        now: DateTime<Utc>,
+    ) -> Result<User, UsersError> {
+        self.as_ref().query_session_user(token_hash, now).await
+    }
+
+    async fn delete_session(&self, token_hash: &str) -> Result<(), UsersError> {
+        self.as_ref().delete_session(token_hash).await
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum UsersError {
+    UserNotFound,
+    AlreadyExists,
+    InternalError,
+}
+
+impl Display for UsersError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            UsersError::UserNotFound => write!(f, "User not found"),
+            UsersError::AlreadyExists => write!(f, "User already exists"),
+            UsersError::InternalError => write!(f, "Internal error"),
+        }
+    }
+}
+
+impl Error for UsersError {}
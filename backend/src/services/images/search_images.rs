@@ -0,0 +1,168 @@
+use async_trait::async_trait;
+use tracing::error;
+
+use super::{
+    domain::{image::Image, search::SearchQuery, tag::normalize_tag},
+    ports::{
+        incoming::search_images_service::{SearchImagesService, SearchImagesServiceError},
+        outgoing::{query_image_port::QueryError, search_images_port::SearchImagesPort},
+    },
+};
+
+const MAX_IMAGES: i64 = 50;
+
+impl From<QueryError> for SearchImagesServiceError {
+    fn from(_value: QueryError) -> Self {
+        SearchImagesServiceError::InternalError
+    }
+}
+
+pub struct SearchImages<Storage>
+where
+    Storage: SearchImagesPort + Send + Sync,
+{
+    storage: Storage,
+}
+
+#[async_trait]
+impl<Storage> SearchImagesService for SearchImages<Storage>
+where
+    Storage: SearchImagesPort + Send + Sync,
+{
+    async fn search_images(
+        &self,
+        mut query: SearchQuery,
+    ) -> Result<Vec<Image>, SearchImagesServiceError> {
+        if query.count <= 0 || query.offset < 0 {
+            return Err(SearchImagesServiceError::InvalidRequest(
+                "Count or offset are below zero".to_string(),
+            ));
+        }
+        if query.count > MAX_IMAGES {
+            error!("Error too many images request amount {}", query.count);
+            return Err(SearchImagesServiceError::TooManyImagesRequested);
+        }
+        Self::check_range("created", query.created_after, query.created_before)?;
+        Self::check_range("captured", query.captured_after, query.captured_before)?;
+        Self::check_range("width", query.min_width, query.max_width)?;
+        Self::check_range("height", query.min_height, query.max_height)?;
+        query.tags = query
+            .tags
+            .iter()
+            .map(|tag| normalize_tag(tag))
+            .collect::<Result<Vec<String>, String>>()
+            .map_err(SearchImagesServiceError::InvalidRequest)?;
+        query.tags.sort();
+        query.tags.dedup();
+        query.original_format = query
+            .original_format
+            .map(|format| format.trim().to_lowercase());
+        self.storage
+            .search_images(&query)
+            .await
+            .map_err(|err| err.into())
+    }
+}
+
+impl<Storage> SearchImages<Storage>
+where
+    Storage: SearchImagesPort + Send + Sync,
+{
+    pub fn new(storage: Storage) -> Self {
+        Self { storage }
+    }
+
+    fn check_range<T: PartialOrd>(
+        name: &str,
+        lower: Option<T>,
+        upper: Option<T>,
+    ) -> Result<(), SearchImagesServiceError> {
+        match (lower, upper) {
+            (Some(lower), Some(upper)) if lower > upper => Err(
+                SearchImagesServiceError::InvalidRequest(format!("Empty {} range", name)),
+            ),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use async_trait::async_trait;
+    use mockall::mock;
+
+    use crate::services::images::{
+        domain::{image::Image, search::SearchQuery},
+        ports::{
+            incoming::search_images_service::{SearchImagesService, SearchImagesServiceError},
+            outgoing::{query_image_port::QueryError, search_images_port::SearchImagesPort},
+        },
+        search_images::SearchImages,
+    };
+
+    mock! {
+        DS {}
+        #[async_trait]
+        impl SearchImagesPort for DS {
+            async fn search_images(&self, query: &SearchQuery) -> Result<Vec<Image>, QueryError>;
+        }
+    }
+
+    #[tokio::test]
+    async fn test_search_images_normalizes_filters() {
+        let mut mock = MockDS::new();
+        mock.expect_search_images()
+            .withf(|query| {
+                query.tags == ["beach"] && query.original_format.as_deref() == Some("jpeg")
+            })
+            .returning(|_q| Ok(vec![]));
+        let service = SearchImages::new(mock);
+        let query = SearchQuery {
+            tags: vec!["Beach".to_string(), "beach ".to_string()],
+            original_format: Some("JPEG".to_string()),
+            ..SearchQuery::default()
+        };
+        assert_eq!(service.search_images(query).await, Ok(vec![]));
+    }
+
+    #[tokio::test]
+    async fn test_search_images_empty_range() {
+        let service = SearchImages::new(MockDS::new());
+        let query = SearchQuery {
+            min_width: Some(200),
+            max_width: Some(100),
+            ..SearchQuery::default()
+        };
+        assert_eq!(
+            service.search_images(query).await,
+            Err(SearchImagesServiceError::InvalidRequest(
+                "Empty width range".to_string()
+            ))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_search_images_too_many_images() {
+        let service = SearchImages::new(MockDS::new());
+        let query = SearchQuery {
+            count: 51,
+            ..SearchQuery::default()
+        };
+        assert_eq!(
+            service.search_images(query).await,
+            Err(SearchImagesServiceError::TooManyImagesRequested)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_search_images_ds_error() {
+        let mut mock = MockDS::new();
+        mock.expect_search_images()
+            .returning(|_q| Err(QueryError::InternalError));
+        let service = SearchImages::new(mock);
+        assert_eq!(
+            service.search_images(SearchQuery::default()).await,
+            Err(SearchImagesServiceError::InternalError)
+        );
+    }
+}
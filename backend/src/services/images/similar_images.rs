@@ -0,0 +1,473 @@
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+
+use async_trait::async_trait;
+use itertools::Itertools;
+use tokio::sync::{Mutex, MutexGuard};
+use tracing::error;
+
+use super::{
+    domain::{bk_tree::BkTree, image::Image, similar_image::SimilarImage},
+    ports::{
+        incoming::similar_images_service::{SimilarImagesService, SimilarImagesServiceError},
+        outgoing::{
+            query_image_port::{QueryError, QueryImagePort},
+            similar_images_port::SimilarImagesPort,
+        },
+    },
+};
+
+const MAX_DISTANCE: u32 = 16;
+/// How long the index keeps the ids of permanently deleted images before it
+/// is built again from the stored rows.
+const REBUILD_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+impl From<QueryError> for SimilarImagesServiceError {
+    fn from(value: QueryError) -> Self {
+        match value {
+            QueryError::RecordNotFound => SimilarImagesServiceError::ImageNotFound,
+            QueryError::InternalError => SimilarImagesServiceError::InternalError,
+        }
+    }
+}
+
+/// In-memory BK-tree over the stored perceptual hashes of every owner. Only
+/// the rows added since `last_id` are fetched on each request; ids of deleted
+/// or trashed images and of other owners' images are dropped when the images
+/// are resolved, so a restored image is found again. The tree is built anew
+/// once it is older than the rebuild interval, which leaves out the images
+/// deleted since.
+struct SimilarityIndex {
+    tree: BkTree,
+    last_id: i64,
+    built_on: Instant,
+}
+
+impl SimilarityIndex {
+    fn new() -> Self {
+        Self {
+            tree: BkTree::new(),
+            last_id: 0,
+            built_on: Instant::now(),
+        }
+    }
+}
+
+pub struct SimilarImages<Storage>
+where
+    Storage: QueryImagePort + SimilarImagesPort + Send + Sync,
+{
+    storage: Storage,
+    index: Mutex<SimilarityIndex>,
+    rebuild_interval: Duration,
+}
+
+#[async_trait]
+impl<Storage> SimilarImagesService for SimilarImages<Storage>
+where
+    Storage: QueryImagePort + SimilarImagesPort + Send + Sync,
+{
+    async fn similar_images(
+        &self,
+        index: i64,
+        max_distance: u32,
+    ) -> Result<Vec<SimilarImage>, SimilarImagesServiceError> {
+        Self::check_distance(max_distance)?;
+        let image = self.storage.query_image(index).await?;
+        let hash = image
+            .perceptual_hash()
+            .ok_or(SimilarImagesServiceError::MissingPerceptualHash)?;
+        let candidates = self
+            .refresh()
+            .await?
+            .tree
+            .find(hash, max_distance)
+            .into_iter()
+            .filter(|(id, _)| *id != index)
+            .collect::<HashMap<i64, u32>>();
+        let images = self
+            .storage
+            .query_images_by_ids(candidates.keys().copied().collect())
+            .await
+            .map_err(|_| SimilarImagesServiceError::InternalError)?;
+        let mut similar = images
+            .into_iter()
+            .map(|image| {
+                let distance = candidates[&image.id()];
+                SimilarImage::new(image, distance)
+            })
+            .collect::<Vec<SimilarImage>>();
+        similar.sort_by_key(|similar| (similar.distance(), similar.image().id()));
+        Ok(similar)
+    }
+
+    async fn duplicate_clusters(
+        &self,
+        max_distance: u32,
+    ) -> Result<Vec<Vec<Image>>, SimilarImagesServiceError> {
+        Self::check_distance(max_distance)?;
+        let neighbours = {
+            let index = self.refresh().await?;
+            let mut neighbours = vec![];
+            for (hash, id) in index.tree.entries() {
+                for (other, _) in index.tree.find(hash, max_distance) {
+                    if other != id {
+                        neighbours.push((id, other));
+                    }
+                }
+            }
+            neighbours
+        };
+
+        // Images the caller cannot see are resolved away first, so they do
+        // not join two of the caller's images into one cluster.
+        let indexes = neighbours
+            .iter()
+            .map(|(id, _)| *id)
+            .unique()
+            .collect::<Vec<i64>>();
+        let mut images = self
+            .storage
+            .query_images_by_ids(indexes)
+            .await
+            .map_err(|_| SimilarImagesServiceError::InternalError)?
+            .into_iter()
+            .map(|image| (image.id(), image))
+            .collect::<HashMap<i64, Image>>();
+        let ids = images.keys().copied().sorted().collect::<Vec<i64>>();
+        let positions = ids
+            .iter()
+            .enumerate()
+            .map(|(position, id)| (*id, position))
+            .collect::<HashMap<i64, usize>>();
+        let mut parents = (0..ids.len()).collect::<Vec<usize>>();
+        for (id, other) in neighbours {
+            if let (Some(a), Some(b)) = (positions.get(&id), positions.get(&other)) {
+                union(&mut parents, *a, *b);
+            }
+        }
+        let mut groups: HashMap<usize, Vec<i64>> = HashMap::new();
+        for (position, id) in ids.iter().enumerate() {
+            groups
+                .entry(find(&mut parents, position))
+                .or_default()
+                .push(*id);
+        }
+        let mut clusters = groups
+            .into_values()
+            .filter(|group| group.len() > 1)
+            .map(|group| {
+                group
+                    .into_iter()
+                    .filter_map(|id| images.remove(&id))
+                    .collect::<Vec<Image>>()
+            })
+            .collect::<Vec<Vec<Image>>>();
+        clusters.sort_by_key(|cluster| cluster[0].id());
+        Ok(clusters)
+    }
+}
+
+impl<Storage> SimilarImages<Storage>
+where
+    Storage: QueryImagePort + SimilarImagesPort + Send + Sync,
+{
+    pub fn new(storage: Storage) -> Self {
+        Self {
+            storage,
+            index: Mutex::new(SimilarityIndex::new()),
+            rebuild_interval: REBUILD_INTERVAL,
+        }
+    }
+
+    pub fn with_rebuild_interval(mut self, rebuild_interval: Duration) -> Self {
+        self.rebuild_interval = rebuild_interval;
+        self
+    }
+
+    fn check_distance(max_distance: u32) -> Result<(), SimilarImagesServiceError> {
+        if max_distance > MAX_DISTANCE {
+            error!("Error distance too large, requested {}", max_distance);
+            return Err(SimilarImagesServiceError::DistanceTooLarge(MAX_DISTANCE));
+        }
+        Ok(())
+    }
+
+    async fn refresh(&self) -> Result<MutexGuard<'_, SimilarityIndex>, SimilarImagesServiceError> {
+        let mut index = self.index.lock().await;
+        if index.built_on.elapsed() >= self.rebuild_interval {
+            *index = SimilarityIndex::new();
+        }
+        let hashes = self
+            .storage
+            .query_perceptual_hashes(index.last_id)
+            .await
+            .map_err(|_| SimilarImagesServiceError::InternalError)?;
+        for (id, hash) in hashes {
+            index.tree.insert(hash, id);
+            index.last_id = index.last_id.max(id);
+        }
+        Ok(index)
+    }
+}
+
+fn find(parents: &mut [usize], position: usize) -> usize {
+    let mut root = position;
+    while parents[root] != root {
+        root = parents[root];
+    }
+    let mut current = position;
+    while parents[current] != root {
+        let next = parents[current];
+        parents[current] = root;
+        current = next;
+    }
+    root
+}
+
+fn union(parents: &mut [usize], a: usize, b: usize) {
+    let a = find(parents, a);
+    let b = find(parents, b);
+    if a != b {
+        parents[b] = a;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{sync::Arc, time::Duration};
+
+    use async_trait::async_trait;
+    use chrono::{DateTime, Utc};
+    use mockall::mock;
+
+    use crate::{
+        data_storage::images::images_in_memory_ds::ImagesInMemoryDS,
+        services::images::{
+            domain::{image::Image, similar_image::SimilarImage, user::on_behalf_of},
+            ports::{
+                incoming::similar_images_service::{
+                    SimilarImagesService, SimilarImagesServiceError,
+                },
+                outgoing::{
+                    delete_image_port::DeleteImagePort,
+                    insert_image_port::InsertImagePort,
+                    query_image_port::{QueryError, QueryImagePort},
+                    similar_images_port::SimilarImagesPort,
+                    trash_port::TrashPort,
+                },
+            },
+            similar_images::SimilarImages,
+        },
+    };
+
+    mock! {
+        DS {}
+        #[async_trait]
+        impl QueryImagePort for DS {
+            async fn query_image(&self, index: i64) -> Result<Image, QueryError>;
+        }
+        #[async_trait]
+        impl SimilarImagesPort for DS {
+            async fn query_perceptual_hashes(&self, after: i64) -> Result<Vec<(i64, u64)>, QueryError>;
+            async fn query_images_by_ids(&self, indexes: Vec<i64>) -> Result<Vec<Image>, QueryError>;
+        }
+    }
+
+    fn image(id: i64, perceptual_hash: u64) -> Image {
+        let updated_on = "2023-08-12T18:30:00Z".parse::<DateTime<Utc>>().unwrap();
+        Image::new(id, format!("path/{}", id), updated_on).with_perceptual_hash(perceptual_hash)
+    }
+
+    fn hashes() -> Vec<(i64, u64)> {
+        vec![
+            (1, 0b0000),
+            (2, 0b0001),
+            (3, 0b0111),
+            (4, u64::MAX),
+            (5, u64::MAX - 1),
+        ]
+    }
+
+    fn mock_with_images(images: Vec<Image>) -> MockDS {
+        let mut mock = MockDS::new();
+        mock.expect_query_perceptual_hashes()
+            .returning(|after| Ok(hashes().into_iter().filter(|(id, _)| *id > after).collect()));
+        mock.expect_query_images_by_ids().returning(move |indexes| {
+            Ok(images
+                .iter()
+                .filter(|image| indexes.contains(&image.id()))
+                .cloned()
+                .collect())
+        });
+        mock
+    }
+
+    #[tokio::test]
+    async fn test_similar_images() {
+        let images = hashes()
+            .into_iter()
+            .map(|(id, hash)| image(id, hash))
+            .collect::<Vec<Image>>();
+        let mut mock = mock_with_images(images);
+        mock.expect_query_image()
+            .returning(|_i| Ok(image(1, 0b0000)));
+        let suu = SimilarImages::new(mock);
+        let result = suu.similar_images(1, 3).await.unwrap();
+        assert_eq!(
+            result,
+            vec![
+                SimilarImage::new(image(2, 0b0001), 1),
+                SimilarImage::new(image(3, 0b0111), 3)
+            ]
+        );
+        // The index is kept between calls and only refreshed incrementally.
+        let result = suu.similar_images(1, 0).await.unwrap();
+        assert!(result.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_similar_images_skips_deleted_images() {
+        let mut mock = mock_with_images(vec![image(3, 0b0111)]);
+        mock.expect_query_image()
+            .returning(|_i| Ok(image(1, 0b0000)));
+        let suu = SimilarImages::new(mock);
+        let result = suu.similar_images(1, 3).await.unwrap();
+        assert_eq!(result, vec![SimilarImage::new(image(3, 0b0111), 3)]);
+    }
+
+    #[tokio::test]
+    async fn test_similar_images_of_each_owner() {
+        let storage = ImagesInMemoryDS::new();
+        for (id, hash) in hashes() {
+            let owner_id = if id == 1 || id == 3 { 1 } else { 2 };
+            on_behalf_of(owner_id, storage.insert_image(&image(id, hash)))
+                .await
+                .unwrap();
+        }
+        let suu = SimilarImages::new(storage);
+        let result = on_behalf_of(1, suu.similar_images(1, 16)).await.unwrap();
+        let ids = result
+            .iter()
+            .map(|similar| similar.image().id())
+            .collect::<Vec<i64>>();
+        assert_eq!(ids, vec![3]);
+        // The index refreshed for the first user still holds the second's.
+        let result = on_behalf_of(2, suu.similar_images(4, 16)).await.unwrap();
+        let ids = result
+            .iter()
+            .map(|similar| similar.image().id())
+            .collect::<Vec<i64>>();
+        assert_eq!(ids, vec![5]);
+
+        // 1 and 3 are only within a distance of 2 through user 2's image 2.
+        let clusters = on_behalf_of(1, suu.duplicate_clusters(2)).await.unwrap();
+        assert!(clusters.is_empty());
+        let clusters = on_behalf_of(2, suu.duplicate_clusters(2)).await.unwrap();
+        assert_eq!(
+            clusters,
+            vec![vec![image(4, u64::MAX), image(5, u64::MAX - 1)]]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_similar_images_finds_restored_image() {
+        let storage = Arc::new(ImagesInMemoryDS::new());
+        storage.insert_image(&image(1, 0b0000)).await.unwrap();
+        storage.insert_image(&image(2, 0b0001)).await.unwrap();
+        storage.trash_images(&[2], Utc::now()).await.unwrap();
+        let suu = SimilarImages::new(storage.clone());
+        assert!(suu.similar_images(1, 3).await.unwrap().is_empty());
+
+        storage.restore_image(2).await.unwrap();
+        let result = suu.similar_images(1, 3).await.unwrap();
+        let ids = result
+            .iter()
+            .map(|similar| similar.image().id())
+            .collect::<Vec<i64>>();
+        assert_eq!(ids, vec![2]);
+    }
+
+    #[tokio::test]
+    async fn test_similarity_index_drops_deleted_images() {
+        let storage = Arc::new(ImagesInMemoryDS::new());
+        storage.insert_image(&image(1, 0b0000)).await.unwrap();
+        storage.insert_image(&image(2, 0b0001)).await.unwrap();
+        let suu = SimilarImages::new(storage.clone()).with_rebuild_interval(Duration::ZERO);
+        assert_eq!(suu.similar_images(1, 3).await.unwrap().len(), 1);
+        assert_eq!(suu.index.lock().await.tree.len(), 2);
+
+        storage.delete_image(2).await.unwrap();
+        assert!(suu.similar_images(1, 3).await.unwrap().is_empty());
+        assert_eq!(suu.index.lock().await.tree.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_similar_images_not_found() {
+        let mut mock = MockDS::new();
+        mock.expect_query_image()
+            .returning(|_i| Err(QueryError::RecordNotFound));
+        let suu = SimilarImages::new(mock);
+        let result = suu.similar_images(1, 3).await;
+        assert_eq!(result, Err(SimilarImagesServiceError::ImageNotFound));
+    }
+
+    #[tokio::test]
+    async fn test_similar_images_without_perceptual_hash() {
+        let mut mock = MockDS::new();
+        mock.expect_query_image()
+            .returning(|_i| Ok(Image::new(1, "path".to_string(), Utc::now())));
+        let suu = SimilarImages::new(mock);
+        let result = suu.similar_images(1, 3).await;
+        assert_eq!(
+            result,
+            Err(SimilarImagesServiceError::MissingPerceptualHash)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_similar_images_distance_too_large() {
+        let mock = MockDS::new();
+        let suu = SimilarImages::new(mock);
+        let result = suu.similar_images(1, 17).await;
+        assert_eq!(result, Err(SimilarImagesServiceError::DistanceTooLarge(16)));
+    }
+
+    #[tokio::test]
+    async fn test_duplicate_clusters() {
+        let images = hashes()
+            .into_iter()
+            .map(|(id, hash)| image(id, hash))
+            .collect::<Vec<Image>>();
+        let mock = mock_with_images(images);
+        let suu = SimilarImages::new(mock);
+        let result = suu.duplicate_clusters(1).await.unwrap();
+        assert_eq!(
+            result,
+            vec![
+                vec![image(1, 0b0000), image(2, 0b0001)],
+                vec![image(4, u64::MAX), image(5, u64::MAX - 1)]
+            ]
+        );
+        let result = suu.duplicate_clusters(2).await.unwrap();
+        assert_eq!(
+            result,
+            vec![
+                vec![image(1, 0b0000), image(2, 0b0001), image(3, 0b0111)],
+                vec![image(4, u64::MAX), image(5, u64::MAX - 1)]
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_duplicate_clusters_ds_error() {
+        let mut mock = MockDS::new();
+        mock.expect_query_perceptual_hashes()
+            .returning(|_a| Err(QueryError::InternalError));
+        let suu = SimilarImages::new(mock);
+        let result = suu.duplicate_clusters(1).await;
+        assert_eq!(result, Err(SimilarImagesServiceError::InternalError));
+    }
+}
@@ -1,121 +1,419 @@
 use crate::services::images::{
-    domain::image::Image,
+    domain::{
+        exif::{orient, ExifData},
+        image::Image,
+        image_metadata::ImageMetadata,
+        perceptual_hash::dhash,
+        rendition::{Rendition, RenditionSize},
+        storage_layout::StorageLayout,
+        user::current_user_id,
+    },
     ports::{
         incoming::upload_images_service::UploadImagesService,
-        outgoing::insert_image_port::InsertImagePort,
+        outgoing::{
+            blob_store_port::DynBlobStore,
+            insert_image_port::{InsertImageError, InsertImagePort},
+            metrics_port::DynMetrics,
+            query_image_by_hash_port::QueryImageByHashPort,
+            query_image_port::QueryError,
+            rendition_port::RenditionPort,
+            trash_port::TrashPort,
+        },
     },
 };
 use async_trait::async_trait;
 use chrono::Utc;
-use rand::{distributions::Alphanumeric, Rng};
+use image::{DynamicImage, ImageFormat};
+use sha2::{Digest, Sha256};
 use std::{
     io::Cursor,
     path::{Path, PathBuf},
 };
+use tracing::error;
 
 use super::ports::incoming::upload_images_service::UploadImagesServiceError;
 
 pub struct UploadImages<Storage>
 where
-    Storage: InsertImagePort + Sync + Send,
+    Storage: InsertImagePort + QueryImageByHashPort + RenditionPort + TrashPort + Sync + Send,
 {
     storage: Storage,
+    blob_store: DynBlobStore,
     base_path: String,
+    originals_path: Option<String>,
+    layout: StorageLayout,
+    metrics: Option<DynMetrics>,
 }
 
 #[async_trait]
 impl<Storage> UploadImagesService for UploadImages<Storage>
 where
-    Storage: InsertImagePort + Sync + Send,
+    Storage: InsertImagePort + QueryImageByHashPort + RenditionPort + TrashPort + Sync + Send,
 {
-    async fn upload_image(&self, buffer: Vec<u8>) -> Result<(), UploadImagesServiceError> {
-        let format = match image::io::Reader::new(Cursor::new(buffer)).with_guessed_format() {
+    async fn upload_image(
+        &self,
+        buffer: Vec<u8>,
+        filename: Option<String>,
+    ) -> Result<i64, UploadImagesServiceError> {
+        let original_size = buffer.len() as u64;
+        let exif = ExifData::read(&buffer);
+        let format = match image::io::Reader::new(Cursor::new(&buffer)).with_guessed_format() {
             Ok(format) => format,
             Err(_) => return Err(UploadImagesServiceError::UnsupportedFormatError),
         };
-        let image = match format.decode() {
+        let original_format = format.format();
+        let mut image = match format.decode() {
             Ok(image) => image,
-            Err(_) => return Err(UploadImagesServiceError::DecodingError),
+            Err(_) => {
+                if let Some(metrics) = &self.metrics {
+                    let format = original_format
+                        .and_then(|format| format.extensions_str().first())
+                        .unwrap_or(&"unknown");
+                    metrics.record_decode_failure(format);
+                }
+                return Err(UploadImagesServiceError::DecodingError);
+            }
         };
-        let mut bytes = vec![];
+        // Stored pixels are always upright; the tag is kept for reference only.
+        if let Some(orientation) = exif.as_ref().and_then(ExifData::orientation) {
+            image = orient(image, orientation);
+        }
+        let hash = Self::hash_pixels(&image);
+        match self.storage.query_image_by_hash(&hash).await {
+            Ok(existing) => {
+                // Uploading a trashed image again takes it back out of the trash.
+                if existing.deleted_at().is_some() {
+                    if let Err(e) = self.storage.restore_image(existing.id()).await {
+                        error!("Error restoring image {}: {}", existing.id(), e);
+                        return Err(UploadImagesServiceError::InternalError);
+                    }
+                }
+                if let Some(metrics) = &self.metrics {
+                    metrics.record_duplicate_upload();
+                }
+                return Ok(existing.id());
+            }
+            Err(QueryError::RecordNotFound) => {}
+            Err(e) => {
+                error!("Error querying image with hash {}: {}", hash, e);
+                return Err(UploadImagesServiceError::InternalError);
+            }
+        }
 
+        let mut bytes = vec![];
         if image
             .write_to(&mut Cursor::new(&mut bytes), image::ImageOutputFormat::Qoi)
             .is_err()
         {
             return Err(UploadImagesServiceError::InternalError);
         }
-        let path = self.generate_path();
-        if tokio::fs::write(&path, bytes).await.is_err() {
-            return Err(UploadImagesServiceError::InternalError);
-        };
-        let image = Image::new(
-            0,
-            path.to_str().expect("Invalid path for image").to_string(),
-            Utc::now(),
+        let checksum = hex::encode(Sha256::digest(&bytes));
+        let perceptual_hash = dhash(&image);
+        let mut metadata =
+            ImageMetadata::from_decoded(&image, original_format, original_size, bytes.len() as u64);
+        if let Some(filename) = filename {
+            metadata = metadata.with_filename(filename);
+        }
+        let file_name = Self::file_name(&hash);
+        let path = self.layout.place(
+            self.generate_path(&file_name)
+                .to_str()
+                .expect("Invalid path for image"),
         );
-        if self.storage.insert_image(&image).await.is_err() {
-            return Err(UploadImagesServiceError::InternalError);
-        };
-
-        Ok(())
+        let mut record = Image::new(0, path.clone(), Utc::now())
+            .with_hash(hash.clone())
+            .with_perceptual_hash(perceptual_hash)
+            .with_checksum(checksum)
+            .with_metadata(metadata);
+        if let Some(exif) = exif {
+            record = record.with_exif(exif);
+        }
+        let mut blobs = vec![(path, bytes)];
+        if let Some(originals_path) = &self.originals_path {
+            let original_path = self.layout.place(
+                Self::original_path(originals_path, &file_name, original_format)
+                    .to_str()
+                    .expect("Invalid path for original"),
+            );
+            record = record.with_original_path(original_path.clone());
+            blobs.push((original_path, buffer));
+        }
+        let keys = blobs
+            .iter()
+            .map(|(key, _)| key.clone())
+            .collect::<Vec<String>>();
+        let stored_bytes = blobs.iter().map(|(_, bytes)| bytes.len() as u64).sum();
+        let blob_store = self.blob_store.clone();
+        let persist = Box::pin(async move {
+            for (key, bytes) in blobs {
+                blob_store
+                    .put(&key, bytes)
+                    .await
+                    .map_err(std::io::Error::other)?;
+            }
+            Ok(())
+        });
+        match self.storage.insert_image_with(&record, persist).await {
+            Ok(id) => {
+                self.store_renditions(id, record.path(), &image).await;
+                if let Some(metrics) = &self.metrics {
+                    metrics.record_stored_image(stored_bytes);
+                }
+                Ok(id)
+            }
+            // A concurrent upload of the same pixels won the race. The row is
+            // rejected before anything is stored, so the files in place are
+            // the winner's and stay.
+            Err(InsertImageError::AlreadyExists) => {
+                if let Some(metrics) = &self.metrics {
+                    metrics.record_duplicate_upload();
+                }
+                self.storage
+                    .query_image_by_hash(&hash)
+                    .await
+                    .map(|existing| existing.id())
+                    .map_err(|_| UploadImagesServiceError::InternalError)
+            }
+            Err(e) => {
+                error!("Error inserting image with hash {}: {}", hash, e);
+                self.discard(&keys).await;
+                Err(UploadImagesServiceError::InternalError)
+            }
+        }
     }
 }
 
 impl<Storage> UploadImages<Storage>
 where
-    Storage: InsertImagePort + Sync + Send,
+    Storage: InsertImagePort + QueryImageByHashPort + RenditionPort + TrashPort + Sync + Send,
 {
-    pub fn new(storage: Storage, base_path: String) -> Self {
-        Self { storage, base_path }
+    pub fn new(storage: Storage, blob_store: DynBlobStore, base_path: String) -> Self {
+        Self {
+            storage,
+            blob_store,
+            base_path,
+            originals_path: None,
+            layout: StorageLayout::Flat,
+            metrics: None,
+        }
+    }
+
+    /// Counts stored, duplicate and undecodable uploads.
+    pub fn with_metrics(mut self, metrics: DynMetrics) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    /// Places new files in `layout`; the migration moves the existing ones.
+    pub fn with_layout(mut self, layout: StorageLayout) -> Self {
+        self.layout = layout;
+        self
+    }
+
+    /// Keeps the uploaded bytes untouched in `originals_path`, next to the
+    /// QOI encoding, for deployments that need a bit-exact round trip.
+    pub fn with_originals_path(mut self, originals_path: String) -> Self {
+        self.originals_path = Some(originals_path);
+        self
+    }
+
+    /// SHA-256 over the decoded pixels, prefixed with the dimensions and color
+    /// type so that two images with the same raw bytes but a different layout
+    /// are not considered equal.
+    fn hash_pixels(image: &DynamicImage) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(image.width().to_le_bytes());
+        hasher.update(image.height().to_le_bytes());
+        hasher.update(format!("{:?}", image.color()).as_bytes());
+        hasher.update(image.as_bytes());
+        hex::encode(hasher.finalize())
+    }
+
+    /// Renditions are a cache: failing to create one here is logged and the
+    /// rendition is generated again on its first request.
+    async fn store_renditions(&self, id: i64, path: &str, image: &DynamicImage) {
+        for size in RenditionSize::ALL {
+            let bytes = match size.encode(image) {
+                Ok(bytes) => bytes,
+                Err(e) => {
+                    error!("Error rendering {} for image {}: {}", size, id, e);
+                    continue;
+                }
+            };
+            let rendition = Rendition::new(id, size, size.path_for(path));
+            if let Err(e) = self.blob_store.put(rendition.path(), bytes).await {
+                error!("Error writing rendition {}: {}", rendition.path(), e);
+                continue;
+            }
+            if let Err(e) = self.storage.insert_rendition(&rendition).await {
+                error!("Error inserting rendition {}: {}", rendition.path(), e);
+            }
+        }
+    }
+
+    /// Removes whatever files a failed insert stored before it was rolled
+    /// back.
+    async fn discard(&self, keys: &[String]) {
+        for key in keys {
+            if let Err(e) = self.blob_store.delete(key).await {
+                error!("Error removing file {}: {}", key, e);
+            }
+        }
+    }
+
+    /// The hash, suffixed with the owner for user uploads: two users storing
+    /// the same pixels get a file each, so either can delete theirs.
+    fn file_name(hash: &str) -> String {
+        match current_user_id() {
+            Some(owner_id) => format!("{}-{}", hash, owner_id),
+            None => hash.to_string(),
+        }
+    }
+
+    fn original_path(
+        originals_path: &str,
+        file_name: &str,
+        format: Option<ImageFormat>,
+    ) -> PathBuf {
+        let extension = format
+            .and_then(|format| format.extensions_str().first())
+            .unwrap_or(&"bin");
+        Path::new(originals_path)
+            .join(file_name)
+            .with_extension(extension)
     }
 
-    fn generate_path(&self) -> PathBuf {
-        let image_filename = rand::thread_rng()
-            .sample_iter(&Alphanumeric)
-            .take(8)
-            .map(char::from)
-            .collect::<String>();
+    fn generate_path(&self, file_name: &str) -> PathBuf {
         Path::new::<std::path::Path>(self.base_path.as_ref())
-            .join(image_filename)
+            .join(file_name)
             .with_extension("qoi")
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use std::{env, io::Cursor};
+    use std::{io::Cursor, sync::Arc};
 
     use async_trait::async_trait;
+    use chrono::{DateTime, Utc};
     use mockall::mock;
 
-    use crate::services::images::{
-        domain::image::Image,
-        ports::{
-            incoming::upload_images_service::UploadImagesService,
-            outgoing::insert_image_port::{InsertImageError, InsertImagePort},
+    use crate::{
+        data_storage::{
+            blobs::memory_blob_store::MemoryBlobStore,
+            images::images_in_memory_ds::ImagesInMemoryDS,
+        },
+        services::images::{
+            domain::{
+                exif::tests as exif_tests,
+                image::Image,
+                rendition::{Rendition, RenditionSize},
+                storage_layout::StorageLayout,
+                user::on_behalf_of,
+            },
+            ports::{
+                incoming::upload_images_service::{UploadImagesService, UploadImagesServiceError},
+                outgoing::{
+                    blob_store_port::BlobStorePort,
+                    delete_image_port::RemoveFiles,
+                    insert_image_port::{InsertImageError, InsertImagePort, Persist},
+                    metrics_port::MetricsPort,
+                    query_image_by_hash_port::QueryImageByHashPort,
+                    query_image_port::QueryError,
+                    rendition_port::RenditionPort,
+                    trash_port::{TrashError, TrashPort},
+                },
+            },
+            upload_images::UploadImages,
         },
-        upload_images::UploadImages,
     };
 
     mock! {
         DS {}
         #[async_trait]
         impl InsertImagePort for DS {
-            async fn insert_image(&self, record: &Image) -> Result<(), InsertImageError>;
+            async fn insert_image(&self, record: &Image) -> Result<i64, InsertImageError>;
+            async fn insert_image_with(&self, record: &Image, persist: Persist) -> Result<i64, InsertImageError>;
+        }
+        #[async_trait]
+        impl QueryImageByHashPort for DS {
+            async fn query_image_by_hash(&self, hash: &str) -> Result<Image, QueryError>;
+        }
+        #[async_trait]
+        impl RenditionPort for DS {
+            async fn query_rendition(&self, image_id: i64, size: RenditionSize) -> Result<Rendition, QueryError>;
+            async fn insert_rendition(&self, rendition: &Rendition) -> Result<(), InsertImageError>;
+        }
+        #[async_trait]
+        impl TrashPort for DS {
+            async fn trash_images(&self, indexes: &[i64], deleted_at: DateTime<Utc>) -> Result<u64, TrashError>;
+            async fn restore_image(&self, index: i64) -> Result<(), TrashError>;
+            async fn query_trash(&self, count: i64, offset: i64) -> Result<Vec<Image>, TrashError>;
+            async fn purge_trash(&self, before: DateTime<Utc>) -> Result<Vec<String>, TrashError>;
+            async fn purge_trash_with(&self, before: DateTime<Utc>, remove: RemoveFiles) -> Result<Vec<String>, TrashError>;
+        }
+    }
+
+    mock! {
+        Metrics {}
+        impl MetricsPort for Metrics {
+            fn record_stored_image(&self, bytes: u64);
+            fn record_duplicate_upload(&self);
+            fn record_decode_failure(&self, format: &str);
         }
     }
 
     #[tokio::test]
     async fn test_upload_image_with_empty_buffer() {
         let mut mock = MockDS::new();
-        mock.expect_insert_image()
-            .returning(|_i| anyhow::Result::Ok(()));
-        let uis = UploadImages::new(mock, "data".to_string());
-        let v = uis.upload_image(vec![]).await;
+        mock.expect_insert_image_with().never();
+        let uis = UploadImages::new(mock, Arc::new(MemoryBlobStore::new()), "data".to_string());
+        let v = uis.upload_image(vec![], None).await;
         assert!(v.is_err());
     }
 
+    #[tokio::test]
+    async fn test_upload_image_records_decode_failure_format() {
+        let mut mock = MockDS::new();
+        mock.expect_insert_image_with().never();
+        let mut metrics = MockMetrics::new();
+        metrics
+            .expect_record_decode_failure()
+            .withf(|format| format == "png")
+            .times(1)
+            .return_const(());
+        let uis = UploadImages::new(mock, Arc::new(MemoryBlobStore::new()), "data".to_string())
+            .with_metrics(Arc::new(metrics));
+        let (mut input, _) = gen_img();
+        input.truncate(20);
+        let result = uis.upload_image(input, None).await;
+        assert_eq!(result, Err(UploadImagesServiceError::DecodingError));
+    }
+
+    #[tokio::test]
+    async fn test_upload_image_records_stored_bytes() {
+        let mut mock = MockDS::new();
+        mock.expect_query_image_by_hash()
+            .returning(|_h| Err(QueryError::RecordNotFound));
+        mock.expect_insert_image_with().returning(|_i, persist| {
+            futures::executor::block_on(persist).unwrap();
+            Ok(7)
+        });
+        mock.expect_insert_rendition().returning(|_r| Ok(()));
+        let (input, expected) = gen_img();
+        let stored_bytes = (expected.len() + input.len()) as u64;
+        let mut metrics = MockMetrics::new();
+        metrics
+            .expect_record_stored_image()
+            .withf(move |bytes| *bytes == stored_bytes)
+            .times(1)
+            .return_const(());
+        let uis = UploadImages::new(mock, Arc::new(MemoryBlobStore::new()), "data".to_string())
+            .with_originals_path("originals".to_string())
+            .with_metrics(Arc::new(metrics));
+        assert_eq!(uis.upload_image(input, None).await, Ok(7));
+    }
+
     fn gen_img() -> (Vec<u8>, Vec<u8>) {
         let imgx = 5;
         let imgy = 2;
@@ -151,21 +449,266 @@ mod tests {
     #[tokio::test]
     async fn test_upload_image_with_generated_buffer() {
         let mut mock = MockDS::new();
-        mock.expect_insert_image()
-            .returning(|_i| anyhow::Result::Ok(()));
-        let path = env::current_dir().unwrap();
-        let uis = UploadImages::new(mock, path.display().to_string());
+        mock.expect_query_image_by_hash()
+            .returning(|_h| Err(QueryError::RecordNotFound));
+        mock.expect_insert_image_with()
+            .withf(|image, _p| {
+                let metadata = image.metadata().unwrap();
+                image.hash().is_some()
+                    && image.perceptual_hash().is_some()
+                    && (metadata.width(), metadata.height()) == (5, 2)
+                    && metadata.color_type() == "Rgb8"
+                    && metadata.original_format() == "png"
+                    && metadata.filename() == Some("gen.png")
+                    && image
+                        .checksum()
+                        .is_some_and(|checksum| checksum.len() == 64)
+            })
+            .returning(|_i, persist| {
+                futures::executor::block_on(persist).unwrap();
+                Ok(7)
+            });
+        mock.expect_insert_rendition()
+            .withf(|rendition| rendition.image_id() == 7)
+            .times(3)
+            .returning(|_r| Ok(()));
+        let blob_store = Arc::new(MemoryBlobStore::new());
+        let uis = UploadImages::new(mock, blob_store.clone(), "data".to_string());
         let (input, expected) = gen_img();
-        let result = uis.upload_image(input.clone()).await;
-        assert!(result.is_ok());
-        let paths = std::fs::read_dir("./").unwrap();
-        for path in paths {
-            let p = path.unwrap().path();
-            if "qoi" == p.extension().unwrap_or_default() {
-                let buffer = std::fs::read(p.clone());
-                assert_eq!(expected, buffer.unwrap());
-                std::fs::remove_file(p).unwrap();
-            }
+        let result = uis
+            .upload_image(input.clone(), Some("gen.png".to_string()))
+            .await;
+        assert_eq!(result, Ok(7));
+        let keys = blob_store.keys();
+        assert_eq!(keys.len(), 4);
+        // The image is smaller than every rendition so all of them match the original.
+        for key in keys {
+            assert!(key.starts_with("data/") && key.ends_with(".qoi"));
+            assert_eq!(expected, blob_store.read(&key).await.unwrap());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_upload_image_hash_query_error() {
+        let mut mock = MockDS::new();
+        mock.expect_query_image_by_hash()
+            .returning(|_h| Err(QueryError::InternalError));
+        mock.expect_insert_image_with().never();
+        let uis = UploadImages::new(mock, Arc::new(MemoryBlobStore::new()), "data".to_string());
+        let (input, _) = gen_img();
+        let result = uis.upload_image(input, None).await;
+        assert_eq!(result, Err(UploadImagesServiceError::InternalError));
+    }
+
+    #[tokio::test]
+    async fn test_upload_same_image_for_two_users() {
+        let blob_store = Arc::new(MemoryBlobStore::new());
+        let uis = UploadImages::new(
+            ImagesInMemoryDS::new(),
+            blob_store.clone(),
+            "data".to_string(),
+        );
+        let (input, _) = gen_img();
+        let first = on_behalf_of(1, uis.upload_image(input.clone(), None))
+            .await
+            .unwrap();
+        let second = on_behalf_of(2, uis.upload_image(input.clone(), None))
+            .await
+            .unwrap();
+        assert_ne!(first, second);
+        assert_eq!(
+            on_behalf_of(2, uis.upload_image(input, None)).await,
+            Ok(second)
+        );
+        // Each user has the image and its three renditions.
+        let keys = blob_store.keys();
+        assert_eq!(keys.len(), 8);
+        assert!(keys.iter().any(|key| key.contains("-1")));
+        assert!(keys.iter().any(|key| key.contains("-2")));
+    }
+
+    #[tokio::test]
+    async fn test_upload_image_keeps_original() {
+        let mut mock = MockDS::new();
+        mock.expect_query_image_by_hash()
+            .returning(|_h| Err(QueryError::RecordNotFound));
+        mock.expect_insert_image_with()
+            .withf(|image, _p| {
+                image
+                    .original_path()
+                    .is_some_and(|path| path.ends_with(".png"))
+            })
+            .returning(|_i, persist| {
+                futures::executor::block_on(persist).unwrap();
+                Ok(9)
+            });
+        mock.expect_insert_rendition().returning(|_r| Ok(()));
+        let blob_store = Arc::new(MemoryBlobStore::new());
+        let uis = UploadImages::new(mock, blob_store.clone(), "data".to_string())
+            .with_originals_path("data/originals".to_string());
+        let (input, _) = gen_img();
+        let result = uis.upload_image(input.clone(), None).await;
+        assert_eq!(result, Ok(9));
+        let kept = blob_store
+            .keys()
+            .into_iter()
+            .find(|key| key.starts_with("data/originals/"))
+            .unwrap();
+        assert_eq!(blob_store.read(&kept).await.unwrap(), input);
+    }
+
+    #[tokio::test]
+    async fn test_upload_image_with_sharded_layout() {
+        let mut mock = MockDS::new();
+        mock.expect_query_image_by_hash()
+            .returning(|_h| Err(QueryError::RecordNotFound));
+        mock.expect_insert_image_with().returning(|_i, persist| {
+            futures::executor::block_on(persist).unwrap();
+            Ok(10)
+        });
+        mock.expect_insert_rendition().returning(|_r| Ok(()));
+        let blob_store = Arc::new(MemoryBlobStore::new());
+        let uis = UploadImages::new(mock, blob_store.clone(), "data".to_string())
+            .with_originals_path("data/originals".to_string())
+            .with_layout(StorageLayout::Sharded);
+        let (input, _) = gen_img();
+        let result = uis.upload_image(input, None).await;
+        assert_eq!(result, Ok(10));
+        let keys = blob_store.keys();
+        assert!(!keys.is_empty());
+        for key in keys {
+            assert_eq!(StorageLayout::Sharded.place(&key), key);
+            assert_ne!(StorageLayout::Flat.place(&key), key);
         }
     }
+
+    #[tokio::test]
+    async fn test_upload_image_applies_exif_orientation() {
+        let mut mock = MockDS::new();
+        mock.expect_query_image_by_hash()
+            .returning(|_h| Err(QueryError::RecordNotFound));
+        mock.expect_insert_image_with()
+            .withf(|image, _p| {
+                let metadata = image.metadata().unwrap();
+                let exif = image.exif().unwrap();
+                (metadata.width(), metadata.height()) == (2, 5)
+                    && exif.orientation() == Some(6)
+                    && exif.make() == Some("Yaiss")
+            })
+            .returning(|_i, persist| {
+                futures::executor::block_on(persist).unwrap();
+                Ok(11)
+            });
+        mock.expect_insert_rendition().returning(|_r| Ok(()));
+        let uis = UploadImages::new(mock, Arc::new(MemoryBlobStore::new()), "data".to_string());
+        let mut jpeg = vec![];
+        image::DynamicImage::new_rgb8(5, 2)
+            .write_to(
+                &mut Cursor::new(&mut jpeg),
+                image::ImageOutputFormat::Jpeg(90),
+            )
+            .unwrap();
+        let result = uis.upload_image(exif_tests::with_exif(jpeg, 6), None).await;
+        assert_eq!(result, Ok(11));
+    }
+
+    #[tokio::test]
+    async fn test_upload_image_already_stored_returns_existing_id() {
+        let mut mock = MockDS::new();
+        mock.expect_query_image_by_hash().returning(|hash| {
+            Ok(Image::new(
+                3,
+                format!("data/{}.qoi", hash),
+                chrono::Utc::now(),
+            ))
+        });
+        mock.expect_insert_image_with().never();
+        let uis = UploadImages::new(
+            mock,
+            Arc::new(MemoryBlobStore::new()),
+            "does/not/exist".to_string(),
+        );
+        let (input, _) = gen_img();
+        let result = uis.upload_image(input, None).await;
+        assert_eq!(result, Ok(3));
+    }
+
+    #[tokio::test]
+    async fn test_upload_image_in_trash_restores_it() {
+        let mut mock = MockDS::new();
+        mock.expect_query_image_by_hash().returning(|hash| {
+            Ok(Image::new(4, format!("data/{}.qoi", hash), Utc::now()).with_deleted_at(Utc::now()))
+        });
+        mock.expect_restore_image()
+            .withf(|index| *index == 4)
+            .times(1)
+            .returning(|_i| Ok(()));
+        mock.expect_insert_image_with().never();
+        let uis = UploadImages::new(
+            mock,
+            Arc::new(MemoryBlobStore::new()),
+            "does/not/exist".to_string(),
+        );
+        let (input, _) = gen_img();
+        let result = uis.upload_image(input, None).await;
+        assert_eq!(result, Ok(4));
+    }
+
+    #[tokio::test]
+    async fn test_upload_image_lost_race_returns_existing_id() {
+        let mut mock = MockDS::new();
+        let mut seq = mockall::Sequence::new();
+        mock.expect_query_image_by_hash()
+            .times(1)
+            .in_sequence(&mut seq)
+            .returning(|_h| Err(QueryError::RecordNotFound));
+        mock.expect_insert_image_with()
+            .times(1)
+            .in_sequence(&mut seq)
+            .returning(|_i, _p| Err(InsertImageError::AlreadyExists));
+        mock.expect_query_image_by_hash()
+            .times(1)
+            .in_sequence(&mut seq)
+            .returning(|hash| Ok(Image::new(4, hash.to_string(), chrono::Utc::now())));
+        let blob_store = Arc::new(MemoryBlobStore::new());
+        let uis = UploadImages::new(mock, blob_store.clone(), "data".to_string());
+        let (input, _) = gen_img();
+        let result = uis.upload_image(input, None).await;
+        assert_eq!(result, Ok(4));
+        assert!(blob_store.keys().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_upload_image_insert_error() {
+        let mut mock = MockDS::new();
+        mock.expect_query_image_by_hash()
+            .returning(|_h| Err(QueryError::RecordNotFound));
+        mock.expect_insert_image_with()
+            .returning(|_i, _p| Err(InsertImageError::InternalError));
+        let blob_store = Arc::new(MemoryBlobStore::new());
+        let uis = UploadImages::new(mock, blob_store.clone(), "data".to_string());
+        let (input, _) = gen_img();
+        let result = uis.upload_image(input, None).await;
+        assert_eq!(result, Err(UploadImagesServiceError::InternalError));
+        assert!(blob_store.keys().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_upload_image_failed_commit_removes_files() {
+        let mut mock = MockDS::new();
+        mock.expect_query_image_by_hash()
+            .returning(|_h| Err(QueryError::RecordNotFound));
+        // The files are stored but the commit fails afterwards.
+        mock.expect_insert_image_with().returning(|_i, persist| {
+            futures::executor::block_on(persist).unwrap();
+            Err(InsertImageError::InternalError)
+        });
+        let blob_store = Arc::new(MemoryBlobStore::new());
+        let uis = UploadImages::new(mock, blob_store.clone(), "data".to_string())
+            .with_originals_path("data/originals".to_string());
+        let (input, _) = gen_img();
+        let result = uis.upload_image(input, None).await;
+        assert_eq!(result, Err(UploadImagesServiceError::InternalError));
+        assert!(blob_store.keys().is_empty());
+    }
 }
@@ -0,0 +1,235 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+
+use super::{
+    domain::{
+        api_key::{generate_secret, hash_secret},
+        user::{
+            hash_password, verify_password, User, MAX_USER_NAME_LENGTH, MIN_PASSWORD_LENGTH,
+            SESSION_DAYS,
+        },
+    },
+    ports::{
+        incoming::users_service::{UsersService, UsersServiceError},
+        outgoing::users_port::{UsersError, UsersPort},
+    },
+};
+
+impl From<UsersError> for UsersServiceError {
+    fn from(value: UsersError) -> Self {
+        match value {
+            UsersError::UserNotFound => UsersServiceError::InvalidCredentials,
+            UsersError::AlreadyExists => UsersServiceError::AlreadyExists,
+            UsersError::InternalError => UsersServiceError::InternalError,
+        }
+    }
+}
+
+pub struct Users<Storage>
+where
+    Storage: UsersPort + Send + Sync,
+{
+    storage: Storage,
+}
+
+#[async_trait]
+impl<Storage> UsersService for Users<Storage>
+where
+    Storage: UsersPort + Send + Sync,
+{
+    async fn register(&self, name: String, password: String) -> Result<User, UsersServiceError> {
+        let name = name.trim();
+        if name.is_empty()
+            || name.chars().count() > MAX_USER_NAME_LENGTH
+            || name.chars().any(char::is_whitespace)
+        {
+            return Err(UsersServiceError::InvalidRequest(format!(
+                "Invalid user name: {:?}",
+                name
+            )));
+        }
+        if password.chars().count() < MIN_PASSWORD_LENGTH {
+            return Err(UsersServiceError::InvalidRequest(format!(
+                "Passwords need at least {} characters",
+                MIN_PASSWORD_LENGTH
+            )));
+        }
+        // Hashing is deliberately slow, so keep it off the async workers.
+        let password_hash = tokio::task::spawn_blocking(move || hash_password(&password))
+            .await
+            .map_err(|_| UsersServiceError::InternalError)?
+            .map_err(|_| UsersServiceError::InternalError)?;
+        self.storage
+            .insert_user(name, &password_hash, Utc::now())
+            .await
+            .map_err(|err| err.into())
+    }
+
+    async fn login(
+        &self,
+        name: String,
+        password: String,
+    ) -> Result<(User, String, DateTime<Utc>), UsersServiceError> {
+        let (user, password_hash) = self.storage.query_user_by_name(name.trim()).await?;
+        let verified =
+            tokio::task::spawn_blocking(move || verify_password(&password, &password_hash))
+                .await
+                .map_err(|_| UsersServiceError::InternalError)?;
+        if !verified {
+            return Err(UsersServiceError::InvalidCredentials);
+        }
+        let token = generate_secret();
+        let expires_on = Utc::now() + Duration::days(SESSION_DAYS);
+        self.storage
+            .insert_session(user.id(), &hash_secret(&token), expires_on)
+            .await?;
+        Ok((user, token, expires_on))
+    }
+
+    async fn logout(&self, token: &str) -> Result<(), UsersServiceError> {
+        self.storage
+            .delete_session(&hash_secret(token))
+            .await
+            .map_err(|err| err.into())
+    }
+
+    async fn authenticate(&self, token: &str) -> Result<User, UsersServiceError> {
+        self.storage
+            .query_session_user(&hash_secret(token), Utc::now())
+            .await
+            .map_err(|err| err.into())
+    }
+}
+
+impl<Storage> Users<Storage>
+where
+    Storage: UsersPort + Send + Sync,
+{
+    pub fn new(storage: Storage) -> Self {
+        Self { storage }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use async_trait::async_trait;
+    use chrono::{DateTime, Utc};
+    use mockall::{mock, predicate};
+
+    use crate::services::images::{
+        domain::{
+            api_key::hash_secret,
+            user::{hash_password, User},
+        },
+        ports::{
+            incoming::users_service::{UsersService, UsersServiceError},
+            outgoing::users_port::{UsersError, UsersPort},
+        },
+        users::Users,
+    };
+
+    mock! {
+        DS {}
+        #[async_trait]
+        impl UsersPort for DS {
+            async fn insert_user(
+                &self,
+                name: &str,
+                password_hash: &str,
+                created_on: DateTime<Utc>,
+            ) -> Result<User, UsersError>;
+            async fn query_user_by_name(&self, name: &str) -> Result<(User, String), UsersError>;
+            async fn insert_session(
+                &self,
+                user_id: i64,
+                token_hash: &str,
+                expires_on: DateTime<Utc>,
+            ) -> Result<(), UsersError>;
+            async fn query_session_user(
+                &self,
+                token_hash: &str,
+                now: DateTime<Utc>,
+            ) -> Result<User, UsersError>;
+            async fn delete_session(&self, token_hash: &str) -> Result<(), UsersError>;
+        }
+    }
+
+    #[tokio::test]
+    async fn test_register_stores_a_password_hash() {
+        let mut mock = MockDS::new();
+        mock.expect_insert_user()
+            .withf(|name, hash, _| name == "alice" && hash.starts_with("$argon2"))
+            .returning(|name, _, created_on| Ok(User::new(1, name.to_string(), created_on)));
+        let service = Users::new(mock);
+        let user = service
+            .register(" alice ".to_string(), "correct horse".to_string())
+            .await
+            .unwrap();
+        assert_eq!(user.id(), 1);
+        let result = service
+            .register("alice".to_string(), "short".to_string())
+            .await;
+        assert!(matches!(result, Err(UsersServiceError::InvalidRequest(_))));
+        let result = service
+            .register("al ice".to_string(), "correct horse".to_string())
+            .await;
+        assert!(matches!(result, Err(UsersServiceError::InvalidRequest(_))));
+    }
+
+    #[tokio::test]
+    async fn test_login() {
+        let password_hash = hash_password("correct horse").unwrap();
+        let mut mock = MockDS::new();
+        mock.expect_query_user_by_name()
+            .with(predicate::eq("alice"))
+            .returning(move |name| {
+                Ok((
+                    User::new(1, name.to_string(), Utc::now()),
+                    password_hash.clone(),
+                ))
+            });
+        mock.expect_query_user_by_name()
+            .returning(|_| Err(UsersError::UserNotFound));
+        mock.expect_insert_session()
+            .withf(|user_id, hash, expires_on| {
+                *user_id == 1 && hash.len() == 64 && *expires_on > Utc::now()
+            })
+            .times(1)
+            .returning(|_, _, _| Ok(()));
+        let service = Users::new(mock);
+        let (user, token, _) = service
+            .login("alice".to_string(), "correct horse".to_string())
+            .await
+            .unwrap();
+        assert_eq!(user.id(), 1);
+        assert!(token.starts_with("yaiss_"));
+        assert_eq!(
+            service
+                .login("alice".to_string(), "battery staple".to_string())
+                .await,
+            Err(UsersServiceError::InvalidCredentials)
+        );
+        assert_eq!(
+            service
+                .login("mallory".to_string(), "correct horse".to_string())
+                .await,
+            Err(UsersServiceError::InvalidCredentials)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_authenticate_by_token_hash() {
+        let mut mock = MockDS::new();
+        mock.expect_query_session_user()
+            .with(predicate::eq(hash_secret("token")), predicate::always())
+            .returning(|_, _| Ok(User::new(1, "alice".to_string(), Utc::now())));
+        mock.expect_query_session_user()
+            .returning(|_, _| Err(UsersError::UserNotFound));
+        let service = Users::new(mock);
+        assert_eq!(service.authenticate("token").await.unwrap().id(), 1);
+        assert_eq!(
+            service.authenticate("expired").await,
+            Err(UsersServiceError::InvalidCredentials)
+        );
+    }
+}
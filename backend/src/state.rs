@@ -1,52 +1,216 @@
-use std::path::Path;
+use std::{path::Path, sync::Arc, time::Duration};
 
-use sqlx::SqlitePool;
+use sqlx::{migrate::Migrator, PgPool, SqlitePool};
 
-use crate::configuration::Configuration;
+use crate::{
+    configuration::{BlobStoreBackend, Configuration, ConsistencySchedule, DatabaseBackend},
+    data_storage::{
+        blobs::{
+            fs_blob_store::FsBlobStore, memory_blob_store::MemoryBlobStore,
+            s3_blob_store::S3BlobStore,
+        },
+        images::{
+            images_in_memory_ds::ImagesInMemoryDS, images_postgres_ds::ImagesPostgresDS,
+            images_sqlite_ds::ImagesSqliteDS,
+        },
+    },
+    metrics::Metrics,
+    services::images::{
+        domain::storage_layout::StorageLayout,
+        ports::outgoing::{blob_store_port::DynBlobStore, storage_port::DynStorage},
+    },
+};
 
 #[derive(Clone)]
 pub struct State {
-    pool: SqlitePool,
+    storage: DynStorage,
+    blob_store: DynBlobStore,
     images_base_path: String,
+    images_originals_path: Option<String>,
+    storage_layout: StorageLayout,
+    trash_retention: Duration,
+    images_quarantine_path: String,
+    consistency_schedule: ConsistencySchedule,
+    scrub_rate: i64,
+    bootstrap_key: Option<String>,
+    share_signing_key: String,
+    metrics: Arc<Metrics>,
 }
 
 impl State {
     pub fn new(configuration: &Configuration) -> Self {
-        let mut pool: Option<SqlitePool> = None;
-        tokio::task::block_in_place(|| {
-            tokio::runtime::Handle::current().block_on(async {
-                let inner_pool = SqlitePool::connect(configuration.database_url())
-                    .await
-                    .unwrap_or_else(|_| {
-                        panic!(
-                            "Failed to create SQLite Pool: {}",
-                            configuration.database_url()
-                        )
-                    });
-                let migrator =
-                    sqlx::migrate::Migrator::new(Path::new(configuration.migrations_path()))
-                        .await
-                        .expect("Failed create migrator");
-                migrator
-                    .run(&inner_pool)
-                    .await
-                    .expect("Failed to run migrations");
-                pool = Some(inner_pool);
-            });
+        let storage = tokio::task::block_in_place(|| {
+            tokio::runtime::Handle::current().block_on(Self::connect(configuration))
         });
-
-        let pool = pool.unwrap();
+        let blob_store = match configuration.blob_store_backend() {
+            BlobStoreBackend::Filesystem => Arc::new(FsBlobStore::new()) as DynBlobStore,
+            BlobStoreBackend::S3(settings) => Arc::new(S3BlobStore::new(settings)) as DynBlobStore,
+            BlobStoreBackend::Memory => Arc::new(MemoryBlobStore::new()) as DynBlobStore,
+        };
         Self {
-            pool,
+            storage,
+            blob_store,
             images_base_path: configuration.images_base_path().to_string(),
+            images_originals_path: configuration.images_originals_path().map(str::to_string),
+            storage_layout: configuration.storage_layout(),
+            trash_retention: configuration.trash_retention(),
+            images_quarantine_path: configuration.images_quarantine_path(),
+            consistency_schedule: configuration.consistency_schedule(),
+            scrub_rate: configuration.scrub_rate(),
+            bootstrap_key: configuration.bootstrap_key().map(str::to_string),
+            share_signing_key: Self::signing_key_or_random(configuration),
+            metrics: Arc::new(Metrics::new()),
         }
     }
 
-    pub fn pool(&self) -> SqlitePool {
-        self.pool.clone()
+    pub fn storage(&self) -> DynStorage {
+        self.storage.clone()
+    }
+
+    pub fn blob_store(&self) -> DynBlobStore {
+        self.blob_store.clone()
     }
 
     pub fn images_base_path(&self) -> &str {
         self.images_base_path.as_ref()
     }
+
+    pub fn images_originals_path(&self) -> Option<&str> {
+        self.images_originals_path.as_deref()
+    }
+
+    pub fn storage_layout(&self) -> StorageLayout {
+        self.storage_layout
+    }
+
+    pub fn trash_retention(&self) -> Duration {
+        self.trash_retention
+    }
+
+    pub fn images_quarantine_path(&self) -> &str {
+        self.images_quarantine_path.as_ref()
+    }
+
+    pub fn consistency_schedule(&self) -> &ConsistencySchedule {
+        &self.consistency_schedule
+    }
+
+    pub fn scrub_rate(&self) -> i64 {
+        self.scrub_rate
+    }
+
+    pub fn bootstrap_key(&self) -> Option<&str> {
+        self.bootstrap_key.as_deref()
+    }
+
+    pub fn share_signing_key(&self) -> &str {
+        &self.share_signing_key
+    }
+
+    pub fn metrics(&self) -> Arc<Metrics> {
+        self.metrics.clone()
+    }
+
+    /// This state with the background job settings of `other`, keeping the
+    /// storage and blob store the handlers already use.
+    pub fn with_job_settings(mut self, other: &State) -> Self {
+        self.trash_retention = other.trash_retention;
+        self.consistency_schedule = other.consistency_schedule.clone();
+        self.scrub_rate = other.scrub_rate;
+        self
+    }
+
+    /// Without a configured key links are signed with a random one, and stop
+    /// working on restart.
+    fn signing_key_or_random(configuration: &Configuration) -> String {
+        match configuration.share_signing_key() {
+            Some(key) => key.to_string(),
+            None => {
+                tracing::warn!("No [SHARES] signing_key configured, share links end on restart");
+                hex::encode(rand::random::<[u8; 32]>())
+            }
+        }
+    }
+
+    /// Opens the configured metadata store and brings its schema up to date.
+    async fn connect(configuration: &Configuration) -> DynStorage {
+        let url = configuration.database_url();
+        match configuration.database_backend() {
+            DatabaseBackend::Sqlite => {
+                let pool = SqlitePool::connect(url)
+                    .await
+                    .unwrap_or_else(|_| panic!("Failed to create SQLite Pool: {}", url));
+                Self::migrator(configuration)
+                    .await
+                    .run(&pool)
+                    .await
+                    .expect("Failed to run migrations");
+                Arc::new(ImagesSqliteDS::new(pool))
+            }
+            DatabaseBackend::Postgres => {
+                let pool = PgPool::connect(url)
+                    .await
+                    .unwrap_or_else(|_| panic!("Failed to create PostgreSQL Pool: {}", url));
+                Self::migrator(configuration)
+                    .await
+                    .run(&pool)
+                    .await
+                    .expect("Failed to run migrations");
+                Arc::new(ImagesPostgresDS::new(pool))
+            }
+            DatabaseBackend::Memory => Arc::new(ImagesInMemoryDS::new()),
+        }
+    }
+
+    /// Each backend has its own migrations, built into the binary unless
+    /// `migrations_path` points elsewhere.
+    async fn migrator(configuration: &Configuration) -> Migrator {
+        match configuration.migrations_path() {
+            Some(path) => Migrator::new(Path::new(path))
+                .await
+                .expect("Failed create migrator"),
+            None => match configuration.database_backend() {
+                DatabaseBackend::Postgres => sqlx::migrate!("sql/postgres_migrations"),
+                _ => sqlx::migrate!("sql/migrations"),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use ini::Ini;
+    use sqlx::migrate::Migrator;
+
+    use super::State;
+    use crate::configuration::Configuration;
+
+    async fn migrations(url: &str) -> Vec<(i64, Vec<u8>)> {
+        let mut ini = Ini::new();
+        ini.with_section(Some("DATABASE")).set("url", url);
+        let migrator = State::migrator(&Configuration::from_ini(ini)).await;
+        checksums(&migrator)
+    }
+
+    fn checksums(migrator: &Migrator) -> Vec<(i64, Vec<u8>)> {
+        migrator
+            .iter()
+            .map(|migration| (migration.version, migration.checksum.to_vec()))
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn migrations_follow_the_database_url() {
+        let sqlite = Migrator::new(Path::new("sql/migrations")).await.unwrap();
+        let postgres = Migrator::new(Path::new("sql/postgres_migrations"))
+            .await
+            .unwrap();
+        assert_eq!(migrations("sqlite:images.db").await, checksums(&sqlite));
+        assert_eq!(
+            migrations("postgres://localhost/images").await,
+            checksums(&postgres)
+        );
+    }
 }
@@ -0,0 +1,98 @@
+use axum::{body::Body, http::Response};
+
+use crate::error::YaissError;
+
+use super::{consistency_response, DynConsistencyService};
+
+/// Reports the state of the storage without changing anything.
+pub async fn check_consistency_handler(
+    axum::extract::State(service): axum::extract::State<DynConsistencyService>,
+) -> Result<Response<Body>, YaissError> {
+    consistency_response(service.check(false).await)
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+
+    use std::sync::Arc;
+
+    use async_trait::async_trait;
+    use axum::{routing::get, Router};
+    use axum_test_helper::TestClient;
+    use mockall::{mock, predicate};
+    use reqwest::StatusCode;
+    use serde_json::{json, Value};
+
+    use crate::{
+        services::images::{
+            domain::{
+                consistency::{ConsistencyReport, ImageFile, OrphanFile},
+                image_status::ImageStatus,
+            },
+            ports::incoming::consistency_service::{ConsistencyService, ConsistencyServiceError},
+        },
+        web::admin::{check_consistency_handler, DynConsistencyService},
+    };
+
+    mock! {
+        pub Service {}
+        #[async_trait]
+        impl ConsistencyService for Service {
+            async fn check(&self, repair: bool) -> Result<ConsistencyReport, ConsistencyServiceError>;
+        }
+    }
+
+    pub fn app(service: MockService) -> TestClient {
+        let consistency_service = Arc::new(service) as DynConsistencyService;
+        let router = Router::new()
+            .route(
+                "/consistency",
+                get(check_consistency_handler::check_consistency_handler),
+            )
+            .with_state(consistency_service);
+        TestClient::new(router)
+    }
+
+    #[tokio::test]
+    async fn on_check_return_report() {
+        let mut mock_service = MockService::new();
+        mock_service
+            .expect_check()
+            .with(predicate::eq(false))
+            .returning(|_r| {
+                Ok(ConsistencyReport {
+                    missing: vec![
+                        ImageFile::new(1, "data/a.qoi".to_string(), ImageStatus::Ok).with_size(40)
+                    ],
+                    orphans: vec![OrphanFile::new("data/b.qoi".to_string(), 12)],
+                    ..ConsistencyReport::default()
+                })
+            });
+        let app = app(mock_service);
+        let response = app.get("/consistency").send().await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response.bytes().await;
+        let body: Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(
+            body,
+            json!({
+                "consistent": false,
+                "repaired": false,
+                "missing": [{"image_id": 1, "path": "data/a.qoi", "status": "ok", "size": 40}],
+                "orphans": [{"path": "data/b.qoi", "size": 12}],
+                "recovered": []
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn on_internal_error_return_internal_server_code() {
+        let mut mock_service = MockService::new();
+        mock_service
+            .expect_check()
+            .returning(|_r| Err(ConsistencyServiceError::InternalError));
+        let app = app(mock_service);
+        let response = app.get("/consistency").send().await;
+        assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    }
+}
@@ -0,0 +1,132 @@
+use axum::{
+    body::{self, Body},
+    http::{Response, StatusCode},
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::{
+    error::YaissError,
+    services::images::{
+        domain::api_key::Scope, ports::incoming::api_keys_service::ApiKeysServiceError,
+    },
+    web::auth::DynApiKeysService,
+};
+
+use super::{api_keys_error_response, ApiKeyJson};
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CreateApiKey {
+    pub name: String,
+    /// Any of `read`, `upload`, `delete` and `admin`.
+    pub scopes: Vec<String>,
+}
+
+/// The created key with its secret, which cannot be looked up again.
+#[derive(Debug, Clone, Serialize)]
+pub struct CreatedApiKeyJson {
+    #[serde(flatten)]
+    api_key: ApiKeyJson,
+    key: String,
+}
+
+pub async fn create_api_key_handler(
+    axum::extract::State(service): axum::extract::State<DynApiKeysService>,
+    request: axum::extract::Json<CreateApiKey>,
+) -> Result<Response<Body>, YaissError> {
+    let request = request.0;
+    let scopes = match request
+        .scopes
+        .iter()
+        .map(|scope| scope.parse::<Scope>())
+        .collect::<Result<Vec<Scope>, String>>()
+    {
+        Ok(scopes) => scopes,
+        Err(message) => {
+            return api_keys_error_response(ApiKeysServiceError::InvalidRequest(message))
+        }
+    };
+    match service.create_key(request.name, scopes).await {
+        Ok((api_key, key)) => Response::builder()
+            .status(StatusCode::CREATED)
+            .header(axum::http::header::CONTENT_TYPE, "application/json")
+            .body(body::Body::from(
+                Json(json!(CreatedApiKeyJson {
+                    api_key: ApiKeyJson::from(api_key),
+                    key,
+                }))
+                .to_string(),
+            ))
+            .map_err(|e| e.into()),
+        Err(e) => api_keys_error_response(e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use std::sync::Arc;
+
+    use axum::{routing::post, Router};
+    use axum_test_helper::TestClient;
+    use chrono::Utc;
+    use mockall::predicate;
+    use reqwest::StatusCode;
+    use serde_json::{json, Value};
+
+    use crate::{
+        services::images::domain::api_key::{ApiKey, Scope},
+        web::{
+            admin::{create_api_key_handler, list_api_keys_handler::tests::MockService},
+            auth::DynApiKeysService,
+        },
+    };
+
+    pub fn app(service: MockService) -> TestClient {
+        let api_keys_service = Arc::new(service) as DynApiKeysService;
+        let router = Router::new()
+            .route("/", post(create_api_key_handler::create_api_key_handler))
+            .with_state(api_keys_service);
+        TestClient::new(router)
+    }
+
+    #[tokio::test]
+    async fn on_create_return_key_with_secret() {
+        let mut mock_service = MockService::new();
+        mock_service
+            .expect_create_key()
+            .with(
+                predicate::eq("uploader".to_string()),
+                predicate::eq(vec![Scope::Read, Scope::Upload]),
+            )
+            .returning(|name, scopes| {
+                Ok((
+                    ApiKey::new(4, name, scopes, Utc::now()),
+                    "yaiss_secret".to_string(),
+                ))
+            });
+        let response = app(mock_service)
+            .post("/")
+            .json(&json!({"name": "uploader", "scopes": ["read", "upload"]}))
+            .send()
+            .await;
+        assert_eq!(response.status(), StatusCode::CREATED);
+        let body: Value = serde_json::from_slice(&response.bytes().await).unwrap();
+        assert_eq!(body["id"], json!(4));
+        assert_eq!(body["scopes"], json!(["read", "upload"]));
+        assert_eq!(body["key"], json!("yaiss_secret"));
+    }
+
+    #[tokio::test]
+    async fn on_unknown_scope_return_bad_request() {
+        let response = app(MockService::new())
+            .post("/")
+            .json(&json!({"name": "writer", "scopes": ["write"]}))
+            .send()
+            .await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let body: Value = serde_json::from_slice(&response.bytes().await).unwrap();
+        assert_eq!(body, json!({"error": "Unknown scope: write"}));
+    }
+}
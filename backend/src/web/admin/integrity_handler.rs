@@ -0,0 +1,132 @@
+use std::sync::Arc;
+
+use axum::{
+    body::{self, Body},
+    http::{Response, StatusCode},
+    Json,
+};
+use serde_json::{json, Map, Value};
+use tracing::error;
+
+use crate::{
+    error::YaissError,
+    services::images::{
+        domain::image_status::ImageStatus, ports::incoming::scrub_service::ScrubService,
+    },
+    web::images::batch_query_image_handler::ImageJson,
+};
+
+pub(crate) type DynScrubService = Arc<dyn ScrubService + Send + Sync>;
+
+/// Image counts per status, with every image the scrubber found corrupted.
+pub async fn integrity_handler(
+    axum::extract::State(service): axum::extract::State<DynScrubService>,
+) -> Result<Response<Body>, YaissError> {
+    let (status, body) = match service.integrity().await {
+        Ok(summary) => {
+            let mut statuses = ImageStatus::ALL
+                .iter()
+                .map(|status| (status.to_string(), json!(0)))
+                .collect::<Map<String, Value>>();
+            for (status, count) in summary.counts {
+                statuses.insert(status.to_string(), json!(count));
+            }
+            let corrupted = summary
+                .corrupted
+                .into_iter()
+                .map(ImageJson::from)
+                .collect::<Vec<ImageJson>>();
+            (
+                StatusCode::OK,
+                json!({ "statuses": statuses, "corrupted": corrupted }),
+            )
+        }
+        Err(e) => {
+            let message = e.to_string();
+            error!("{}", message);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                json!({ "error": message }),
+            )
+        }
+    };
+    Response::builder()
+        .status(status)
+        .header(axum::http::header::CONTENT_TYPE, "application/json")
+        .body(body::Body::from(Json(body).to_string()))
+        .map_err(|e| e.into())
+}
+
+#[cfg(test)]
+mod tests {
+
+    use std::sync::Arc;
+
+    use async_trait::async_trait;
+    use axum::{routing::get, Router};
+    use axum_test_helper::TestClient;
+    use chrono::Utc;
+    use mockall::mock;
+    use reqwest::StatusCode;
+    use serde_json::{json, Value};
+
+    use crate::{
+        services::images::{
+            domain::{
+                image::Image,
+                image_status::ImageStatus,
+                integrity::{IntegritySummary, ScrubSummary},
+            },
+            ports::incoming::scrub_service::{ScrubService, ScrubServiceError},
+        },
+        web::{
+            admin::integrity_handler::{self, DynScrubService},
+            images::batch_query_image_handler::ImageJson,
+        },
+    };
+
+    mock! {
+        pub Service {}
+        #[async_trait]
+        impl ScrubService for Service {
+            async fn scrub(&self, count: i64) -> Result<ScrubSummary, ScrubServiceError>;
+            async fn integrity(&self) -> Result<IntegritySummary, ScrubServiceError>;
+            async fn image_counts(&self) -> Result<Vec<(ImageStatus, i64)>, ScrubServiceError>;
+        }
+    }
+
+    pub fn app(service: MockService) -> TestClient {
+        let scrub_service = Arc::new(service) as DynScrubService;
+        let router = Router::new()
+            .route("/integrity", get(integrity_handler::integrity_handler))
+            .with_state(scrub_service);
+        TestClient::new(router)
+    }
+
+    #[tokio::test]
+    async fn on_integrity_return_counts_and_corrupted_images() {
+        let now = Utc::now();
+        let image = Image::new(2, "some/path".to_string(), now).with_status(ImageStatus::Corrupted);
+        let returned = image.clone();
+        let mut mock_service = MockService::new();
+        mock_service.expect_integrity().returning(move || {
+            Ok(IntegritySummary {
+                counts: vec![(ImageStatus::Ok, 5), (ImageStatus::Corrupted, 1)],
+                corrupted: vec![returned.clone()],
+            })
+        });
+        let app = app(mock_service);
+        let response = app.get("/integrity").send().await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response.bytes().await;
+        let body: Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(
+            body,
+            json!({
+                "statuses": {"ok": 5, "missing": 0, "corrupted": 1},
+                "corrupted": [ImageJson::from(image)]
+            })
+        );
+        assert_eq!(body["corrupted"][0]["status"], "corrupted");
+    }
+}
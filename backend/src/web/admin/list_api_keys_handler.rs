@@ -0,0 +1,102 @@
+use axum::{
+    body::{self, Body},
+    http::{Response, StatusCode},
+    Json,
+};
+use serde_json::json;
+
+use crate::{error::YaissError, web::auth::DynApiKeysService};
+
+use super::{api_keys_error_response, ApiKeyJson};
+
+/// Every key, revoked ones included.
+pub async fn list_api_keys_handler(
+    axum::extract::State(service): axum::extract::State<DynApiKeysService>,
+) -> Result<Response<Body>, YaissError> {
+    match service.keys().await {
+        Ok(keys) => {
+            let keys = keys
+                .into_iter()
+                .map(ApiKeyJson::from)
+                .collect::<Vec<ApiKeyJson>>();
+            Response::builder()
+                .status(StatusCode::OK)
+                .header(axum::http::header::CONTENT_TYPE, "application/json")
+                .body(body::Body::from(
+                    Json(json!({ "api_keys": keys })).to_string(),
+                ))
+                .map_err(|e| e.into())
+        }
+        Err(e) => api_keys_error_response(e),
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+
+    use std::sync::Arc;
+
+    use async_trait::async_trait;
+    use axum::{routing::get, Router};
+    use axum_test_helper::TestClient;
+    use chrono::Utc;
+    use mockall::mock;
+    use reqwest::StatusCode;
+    use serde_json::{json, Value};
+
+    use crate::{
+        services::images::{
+            domain::api_key::{ApiKey, Scope},
+            ports::incoming::api_keys_service::{ApiKeysService, ApiKeysServiceError},
+        },
+        web::{
+            admin::{list_api_keys_handler, ApiKeyJson},
+            auth::DynApiKeysService,
+        },
+    };
+
+    mock! {
+        pub Service {}
+        #[async_trait]
+        impl ApiKeysService for Service {
+            async fn create_key(
+                &self,
+                name: String,
+                scopes: Vec<Scope>,
+            ) -> Result<(ApiKey, String), ApiKeysServiceError>;
+            async fn keys(&self) -> Result<Vec<ApiKey>, ApiKeysServiceError>;
+            async fn revoke_key(&self, id: i64) -> Result<(), ApiKeysServiceError>;
+            async fn authenticate(&self, secret: &str) -> Result<ApiKey, ApiKeysServiceError>;
+        }
+    }
+
+    pub fn app(service: MockService) -> TestClient {
+        let api_keys_service = Arc::new(service) as DynApiKeysService;
+        let router = Router::new()
+            .route("/", get(list_api_keys_handler::list_api_keys_handler))
+            .with_state(api_keys_service);
+        TestClient::new(router)
+    }
+
+    #[tokio::test]
+    async fn on_list_return_keys_without_secrets() {
+        let keys = vec![
+            ApiKey::new(1, "reader".to_string(), vec![Scope::Read], Utc::now()),
+            ApiKey::new(2, "old".to_string(), vec![Scope::Upload], Utc::now())
+                .with_revoked_on(Utc::now()),
+        ];
+        let returned = keys.clone();
+        let mut mock_service = MockService::new();
+        mock_service
+            .expect_keys()
+            .returning(move || Ok(returned.clone()));
+        let response = app(mock_service).get("/").send().await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let body: Value = serde_json::from_slice(&response.bytes().await).unwrap();
+        let keys = keys
+            .into_iter()
+            .map(ApiKeyJson::from)
+            .collect::<Vec<ApiKeyJson>>();
+        assert_eq!(body, json!({ "api_keys": keys }));
+    }
+}
@@ -0,0 +1,117 @@
+use std::sync::Arc;
+
+use axum::{
+    body::{self, Body},
+    http::{Response, StatusCode},
+    Json,
+};
+use serde_json::json;
+use tracing::error;
+
+use crate::{error::YaissError, services::images::ports::incoming::layout_service::LayoutService};
+
+pub(crate) type DynLayoutService = Arc<dyn LayoutService + Send + Sync>;
+
+/// Moves the stored files into the configured layout. Safe to run again: the
+/// files already in place are left alone.
+pub async fn migrate_layout_handler(
+    axum::extract::State(service): axum::extract::State<DynLayoutService>,
+) -> Result<Response<Body>, YaissError> {
+    let (status, body) = match service.migrate().await {
+        Ok(migration) => (
+            StatusCode::OK,
+            json!({
+                "layout": migration.layout.to_string(),
+                "images": migration.images,
+                "files": migration.files,
+                "failed": migration.failed,
+            }),
+        ),
+        Err(e) => {
+            let message = e.to_string();
+            error!("{}", message);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                json!({ "error": message }),
+            )
+        }
+    };
+    Response::builder()
+        .status(status)
+        .header(axum::http::header::CONTENT_TYPE, "application/json")
+        .body(body::Body::from(Json(body).to_string()))
+        .map_err(|e| e.into())
+}
+
+#[cfg(test)]
+mod tests {
+
+    use std::sync::Arc;
+
+    use async_trait::async_trait;
+    use axum::{routing::post, Router};
+    use axum_test_helper::TestClient;
+    use mockall::mock;
+    use reqwest::StatusCode;
+    use serde_json::{json, Value};
+
+    use crate::{
+        services::images::{
+            domain::storage_layout::{LayoutMigration, StorageLayout},
+            ports::incoming::layout_service::{LayoutService, LayoutServiceError},
+        },
+        web::admin::migrate_layout_handler::{self, DynLayoutService},
+    };
+
+    mock! {
+        pub Service {}
+        #[async_trait]
+        impl LayoutService for Service {
+            async fn migrate(&self) -> Result<LayoutMigration, LayoutServiceError>;
+        }
+    }
+
+    pub fn app(service: MockService) -> TestClient {
+        let layout_service = Arc::new(service) as DynLayoutService;
+        let router = Router::new()
+            .route(
+                "/layout/migrate",
+                post(migrate_layout_handler::migrate_layout_handler),
+            )
+            .with_state(layout_service);
+        TestClient::new(router)
+    }
+
+    #[tokio::test]
+    async fn on_migrate_return_moved_counts() {
+        let mut mock_service = MockService::new();
+        mock_service.expect_migrate().returning(|| {
+            Ok(LayoutMigration {
+                layout: StorageLayout::Sharded,
+                images: 3,
+                files: 7,
+                failed: 1,
+            })
+        });
+        let app = app(mock_service);
+        let response = app.post("/layout/migrate").send().await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response.bytes().await;
+        let body: Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(
+            body,
+            json!({"layout": "sharded", "images": 3, "files": 7, "failed": 1})
+        );
+    }
+
+    #[tokio::test]
+    async fn on_migrate_error_return_internal_error() {
+        let mut mock_service = MockService::new();
+        mock_service
+            .expect_migrate()
+            .returning(|| Err(LayoutServiceError::InternalError));
+        let app = app(mock_service);
+        let response = app.post("/layout/migrate").send().await;
+        assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    }
+}
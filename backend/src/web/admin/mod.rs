@@ -0,0 +1,243 @@
+use std::sync::Arc;
+
+use axum::{
+    body::{self, Body},
+    http::{Response, StatusCode},
+    middleware,
+    routing::{delete, get, post},
+    Json, Router,
+};
+use serde::Serialize;
+use serde_json::json;
+use tracing::error;
+
+use crate::{
+    error::YaissError,
+    services::images::{
+        api_keys::ApiKeys,
+        domain::{
+            api_key::{ApiKey, Scope},
+            consistency::{ConsistencyReport, ImageFile, OrphanFile},
+        },
+        image_consistency::ImageConsistency,
+        image_layout::ImageLayout,
+        image_scrubber::ImageScrubber,
+        ports::{
+            incoming::{
+                api_keys_service::ApiKeysServiceError,
+                consistency_service::{ConsistencyService, ConsistencyServiceError},
+            },
+            outgoing::storage_port::DynStorage,
+        },
+    },
+    state::State,
+    web::auth::{self, DynApiKeysService},
+};
+
+use self::{integrity_handler::DynScrubService, migrate_layout_handler::DynLayoutService};
+
+pub mod check_consistency_handler;
+pub mod create_api_key_handler;
+pub mod integrity_handler;
+pub mod list_api_keys_handler;
+pub mod migrate_layout_handler;
+pub mod repair_consistency_handler;
+pub mod revoke_api_key_handler;
+
+pub(crate) type DynConsistencyService = Arc<dyn ConsistencyService + Send + Sync>;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ImageFileJson {
+    image_id: i64,
+    path: String,
+    status: String,
+    size: Option<u64>,
+}
+
+impl From<ImageFile> for ImageFileJson {
+    fn from(value: ImageFile) -> Self {
+        Self {
+            image_id: value.image_id(),
+            path: value.path().to_string(),
+            status: value.status().to_string(),
+            size: value.size(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct OrphanFileJson {
+    path: String,
+    size: u64,
+}
+
+impl From<OrphanFile> for OrphanFileJson {
+    fn from(value: OrphanFile) -> Self {
+        Self {
+            path: value.path().to_string(),
+            size: value.size(),
+        }
+    }
+}
+
+/// Also what the scheduled checks log, so alerts can parse either.
+#[derive(Debug, Clone, Serialize)]
+pub struct ConsistencyReportJson {
+    consistent: bool,
+    repaired: bool,
+    missing: Vec<ImageFileJson>,
+    orphans: Vec<OrphanFileJson>,
+    recovered: Vec<ImageFileJson>,
+}
+
+impl From<ConsistencyReport> for ConsistencyReportJson {
+    fn from(value: ConsistencyReport) -> Self {
+        Self {
+            consistent: value.is_consistent(),
+            repaired: value.repaired,
+            missing: value.missing.into_iter().map(ImageFileJson::from).collect(),
+            orphans: value
+                .orphans
+                .into_iter()
+                .map(OrphanFileJson::from)
+                .collect(),
+            recovered: value
+                .recovered
+                .into_iter()
+                .map(ImageFileJson::from)
+                .collect(),
+        }
+    }
+}
+
+pub(crate) fn consistency_response(
+    result: Result<ConsistencyReport, ConsistencyServiceError>,
+) -> Result<Response<Body>, YaissError> {
+    let (status, body) = match result {
+        Ok(report) => (StatusCode::OK, json!(ConsistencyReportJson::from(report))),
+        Err(e) => {
+            let message = e.to_string();
+            error!("{}", message);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                json!({ "error": message }),
+            )
+        }
+    };
+    Response::builder()
+        .status(status)
+        .header(axum::http::header::CONTENT_TYPE, "application/json")
+        .body(body::Body::from(Json(body).to_string()))
+        .map_err(|e| e.into())
+}
+
+/// Never carries the secret, which is only returned on creation.
+#[derive(Debug, Clone, Serialize)]
+pub struct ApiKeyJson {
+    id: i64,
+    name: String,
+    scopes: Vec<String>,
+    created_on: String,
+    revoked_on: Option<String>,
+}
+
+impl From<ApiKey> for ApiKeyJson {
+    fn from(value: ApiKey) -> Self {
+        Self {
+            id: value.id(),
+            name: value.name().to_string(),
+            scopes: value
+                .scopes()
+                .iter()
+                .map(|scope| scope.to_string())
+                .collect(),
+            created_on: value.created_on().to_string(),
+            revoked_on: value.revoked_on().map(|revoked_on| revoked_on.to_string()),
+        }
+    }
+}
+
+pub(crate) fn api_keys_error_response(
+    e: ApiKeysServiceError,
+) -> Result<Response<Body>, YaissError> {
+    let message = e.to_string();
+    error!("{}", message);
+    let code = match e {
+        ApiKeysServiceError::KeyNotFound => StatusCode::NOT_FOUND,
+        ApiKeysServiceError::InvalidRequest(_) => StatusCode::BAD_REQUEST,
+        ApiKeysServiceError::InvalidKey => StatusCode::UNAUTHORIZED,
+        ApiKeysServiceError::InternalError => StatusCode::INTERNAL_SERVER_ERROR,
+    };
+    Response::builder()
+        .status(code)
+        .header(axum::http::header::CONTENT_TYPE, "application/json")
+        .body(body::Body::from(
+            Json(json!({
+                "error": message,
+            }))
+            .to_string(),
+        ))
+        .map_err(|e| e.into())
+}
+
+pub fn api_keys_service(state: &State) -> DynApiKeysService {
+    let service = ApiKeys::new(state.storage());
+    match state.bootstrap_key() {
+        Some(bootstrap_key) => Arc::new(service.with_bootstrap_key(bootstrap_key)),
+        None => Arc::new(service),
+    }
+}
+
+pub fn consistency_service(state: &State) -> ImageConsistency<DynStorage> {
+    ImageConsistency::new(
+        state.storage(),
+        state.blob_store(),
+        state.images_base_path().to_string(),
+        state.images_quarantine_path().to_string(),
+    )
+}
+
+pub fn router(state: State) -> Router<(), Body> {
+    let consistency_service = Arc::new(consistency_service(&state)) as DynConsistencyService;
+    let storage = state.storage();
+    let scrub_service =
+        Arc::new(ImageScrubber::new(storage, state.blob_store())) as DynScrubService;
+    let storage = state.storage();
+    let layout_service = Arc::new(ImageLayout::new(
+        storage,
+        state.blob_store(),
+        state.storage_layout(),
+    )) as DynLayoutService;
+    let api_keys_service = api_keys_service(&state);
+    Router::new()
+        .route(
+            "/consistency",
+            get(check_consistency_handler::check_consistency_handler),
+        )
+        .route(
+            "/consistency/repair",
+            post(repair_consistency_handler::repair_consistency_handler),
+        )
+        .with_state(consistency_service)
+        .route("/integrity", get(integrity_handler::integrity_handler))
+        .with_state(scrub_service)
+        .route(
+            "/layout/migrate",
+            post(migrate_layout_handler::migrate_layout_handler),
+        )
+        .with_state(layout_service)
+        .route(
+            "/api_keys",
+            get(list_api_keys_handler::list_api_keys_handler)
+                .post(create_api_key_handler::create_api_key_handler),
+        )
+        .route(
+            "/api_keys/:id",
+            delete(revoke_api_key_handler::revoke_api_key_handler),
+        )
+        .with_state(api_keys_service)
+        .route_layer(middleware::from_fn_with_state(
+            Scope::Admin,
+            auth::require_scope,
+        ))
+}
@@ -0,0 +1,65 @@
+use axum::{body::Body, http::Response};
+
+use crate::error::YaissError;
+
+use super::{consistency_response, DynConsistencyService};
+
+/// Quarantines the orphan files and marks the rows whose file is missing.
+pub async fn repair_consistency_handler(
+    axum::extract::State(service): axum::extract::State<DynConsistencyService>,
+) -> Result<Response<Body>, YaissError> {
+    consistency_response(service.check(true).await)
+}
+
+#[cfg(test)]
+mod tests {
+
+    use std::sync::Arc;
+
+    use axum::{routing::post, Router};
+    use axum_test_helper::TestClient;
+    use mockall::predicate;
+    use reqwest::StatusCode;
+    use serde_json::Value;
+
+    use crate::{
+        services::images::domain::consistency::ConsistencyReport,
+        web::admin::{
+            check_consistency_handler::tests::MockService, repair_consistency_handler,
+            DynConsistencyService,
+        },
+    };
+
+    pub fn app(service: MockService) -> TestClient {
+        let consistency_service = Arc::new(service) as DynConsistencyService;
+        let router = Router::new()
+            .route(
+                "/consistency/repair",
+                post(repair_consistency_handler::repair_consistency_handler),
+            )
+            .with_state(consistency_service);
+        TestClient::new(router)
+    }
+
+    #[tokio::test]
+    async fn on_repair_return_repaired_report() {
+        let mut mock_service = MockService::new();
+        mock_service
+            .expect_check()
+            .with(predicate::eq(true))
+            .times(1)
+            .returning(|_r| {
+                Ok(ConsistencyReport {
+                    repaired: true,
+                    ..ConsistencyReport::default()
+                })
+            });
+        let app = app(mock_service);
+        let response = app.post("/consistency/repair").send().await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response.bytes().await;
+        let body: Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["repaired"], true);
+        assert_eq!(body["consistent"], true);
+    }
+}
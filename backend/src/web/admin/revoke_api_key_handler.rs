@@ -0,0 +1,73 @@
+use axum::{
+    body::Body,
+    http::{Response, StatusCode},
+};
+
+use crate::{error::YaissError, web::auth::DynApiKeysService};
+
+use super::api_keys_error_response;
+
+/// Revoked keys stay listed but no longer authenticate.
+pub async fn revoke_api_key_handler(
+    axum::extract::State(service): axum::extract::State<DynApiKeysService>,
+    id: axum::extract::Path<i64>,
+) -> Result<Response<Body>, YaissError> {
+    match service.revoke_key(id.0).await {
+        Ok(()) => Response::builder()
+            .status(StatusCode::NO_CONTENT)
+            .body(Body::empty())
+            .map_err(|e| e.into()),
+        Err(e) => api_keys_error_response(e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use std::sync::Arc;
+
+    use axum::{routing::delete, Router};
+    use axum_test_helper::TestClient;
+    use mockall::predicate;
+    use reqwest::StatusCode;
+
+    use crate::{
+        services::images::ports::incoming::api_keys_service::ApiKeysServiceError,
+        web::{
+            admin::{list_api_keys_handler::tests::MockService, revoke_api_key_handler},
+            auth::DynApiKeysService,
+        },
+    };
+
+    pub fn app(service: MockService) -> TestClient {
+        let api_keys_service = Arc::new(service) as DynApiKeysService;
+        let router = Router::new()
+            .route(
+                "/:id",
+                delete(revoke_api_key_handler::revoke_api_key_handler),
+            )
+            .with_state(api_keys_service);
+        TestClient::new(router)
+    }
+
+    #[tokio::test]
+    async fn on_revoke_return_no_content() {
+        let mut mock_service = MockService::new();
+        mock_service
+            .expect_revoke_key()
+            .with(predicate::eq(3))
+            .returning(|_| Ok(()));
+        let response = app(mock_service).delete("/3").send().await;
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+    }
+
+    #[tokio::test]
+    async fn on_unknown_key_return_not_found() {
+        let mut mock_service = MockService::new();
+        mock_service
+            .expect_revoke_key()
+            .returning(|_| Err(ApiKeysServiceError::KeyNotFound));
+        let response = app(mock_service).delete("/9").send().await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+}
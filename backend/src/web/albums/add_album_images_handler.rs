@@ -0,0 +1,89 @@
+use axum::{
+    body::Body,
+    http::{Response, StatusCode},
+};
+use serde::Deserialize;
+
+use crate::error::YaissError;
+
+use super::{albums_error_response, DynAlbumsService};
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct AlbumImagesJson {
+    pub images: Vec<i64>,
+}
+
+pub async fn add_album_images_handler(
+    axum::extract::State(service): axum::extract::State<DynAlbumsService>,
+    identifier: axum::extract::Path<i64>,
+    images: axum::extract::Json<AlbumImagesJson>,
+) -> Result<Response<Body>, YaissError> {
+    match service.add_images(identifier.0, images.0.images).await {
+        Ok(()) => Response::builder()
+            .status(StatusCode::OK)
+            .body(Body::empty())
+            .map_err(|e| e.into()),
+        Err(e) => albums_error_response(e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use std::sync::Arc;
+
+    use axum::{routing::post, Router};
+    use axum_test_helper::TestClient;
+    use mockall::predicate;
+    use reqwest::StatusCode;
+    use serde_json::json;
+
+    use crate::{
+        services::images::ports::incoming::albums_service::AlbumsServiceError,
+        web::albums::{
+            add_album_images_handler, list_albums_handler::tests::MockService, DynAlbumsService,
+        },
+    };
+
+    pub fn app(service: MockService) -> TestClient {
+        let albums_service = Arc::new(service) as DynAlbumsService;
+        let router = Router::new()
+            .route(
+                "/:identifier/images",
+                post(add_album_images_handler::add_album_images_handler),
+            )
+            .with_state(albums_service);
+        TestClient::new(router)
+    }
+
+    #[tokio::test]
+    async fn on_add_images_return_ok() {
+        let mut mock_service = MockService::new();
+        mock_service
+            .expect_add_images()
+            .with(predicate::eq(1), predicate::eq(vec![3, 2]))
+            .returning(|_a, _i| Ok(()));
+        let app = app(mock_service);
+        let response = app
+            .post("/1/images")
+            .json(&json!({"images": [3, 2]}))
+            .send()
+            .await;
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn on_unknown_image_return_not_found_code() {
+        let mut mock_service = MockService::new();
+        mock_service
+            .expect_add_images()
+            .returning(|_a, _i| Err(AlbumsServiceError::ImageNotFound));
+        let app = app(mock_service);
+        let response = app
+            .post("/1/images")
+            .json(&json!({"images": [99]}))
+            .send()
+            .await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+}
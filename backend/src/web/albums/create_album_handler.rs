@@ -0,0 +1,91 @@
+use axum::{body::Body, http::Response, http::StatusCode};
+use serde::Deserialize;
+
+use crate::error::YaissError;
+
+use super::{album_response, albums_error_response, DynAlbumsService};
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CreateAlbum {
+    pub name: String,
+    #[serde(default)]
+    pub parent_id: Option<i64>,
+}
+
+pub async fn create_album_handler(
+    axum::extract::State(service): axum::extract::State<DynAlbumsService>,
+    album: axum::extract::Json<CreateAlbum>,
+) -> Result<Response<Body>, YaissError> {
+    let album = album.0;
+    match service.create_album(album.name, album.parent_id).await {
+        Ok(album) => album_response(StatusCode::CREATED, album),
+        Err(e) => albums_error_response(e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use std::sync::Arc;
+
+    use axum::{routing::post, Router};
+    use axum_test_helper::TestClient;
+    use chrono::Utc;
+    use mockall::predicate;
+    use reqwest::StatusCode;
+    use serde_json::{json, Value};
+
+    use crate::{
+        services::images::{
+            domain::album::Album, ports::incoming::albums_service::AlbumsServiceError,
+        },
+        web::albums::{
+            create_album_handler, list_albums_handler::tests::MockService, AlbumJson,
+            DynAlbumsService,
+        },
+    };
+
+    pub fn app(service: MockService) -> TestClient {
+        let albums_service = Arc::new(service) as DynAlbumsService;
+        let router = Router::new()
+            .route("/", post(create_album_handler::create_album_handler))
+            .with_state(albums_service);
+        TestClient::new(router)
+    }
+
+    #[tokio::test]
+    async fn on_create_return_created_album() {
+        let album = Album::new(3, "Summer".to_string(), Utc::now()).with_parent_id(1);
+        let returned = album.clone();
+        let mut mock_service = MockService::new();
+        mock_service
+            .expect_create_album()
+            .with(predicate::eq("Summer".to_string()), predicate::eq(Some(1)))
+            .returning(move |_n, _p| Ok(returned.clone()));
+        let app = app(mock_service);
+        let response = app
+            .post("/")
+            .json(&json!({"name": "Summer", "parent_id": 1}))
+            .send()
+            .await;
+        assert_eq!(response.status(), StatusCode::CREATED);
+        let body = response.bytes().await;
+        let body: Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body, json!(AlbumJson::from(album)));
+    }
+
+    #[tokio::test]
+    async fn on_unknown_parent_return_not_found_code() {
+        let mut mock_service = MockService::new();
+        mock_service
+            .expect_create_album()
+            .returning(|_n, _p| Err(AlbumsServiceError::AlbumNotFound));
+        let app = app(mock_service);
+        let response = app
+            .post("/")
+            .json(&json!({"name": "Summer", "parent_id": 99}))
+            .send()
+            .await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+}
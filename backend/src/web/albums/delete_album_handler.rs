@@ -0,0 +1,92 @@
+use axum::{
+    body::Body,
+    extract::{rejection::QueryRejection, Query},
+    http::{Response, StatusCode},
+};
+use serde::Deserialize;
+
+use crate::{
+    error::YaissError, services::images::ports::incoming::albums_service::AlbumsServiceError,
+};
+
+use super::{albums_error_response, DynAlbumsService};
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct DeleteAlbum {
+    /// Also moves the images of the album and of its nested albums to the trash.
+    #[serde(default)]
+    pub cascade: bool,
+}
+
+pub async fn delete_album_handler(
+    axum::extract::State(service): axum::extract::State<DynAlbumsService>,
+    identifier: axum::extract::Path<i64>,
+    params: Result<Query<DeleteAlbum>, QueryRejection>,
+) -> Result<Response<Body>, YaissError> {
+    let params = match params {
+        Ok(Query(params)) => params,
+        Err(rejection) => {
+            return albums_error_response(AlbumsServiceError::InvalidRequest(rejection.body_text()))
+        }
+    };
+    match service.delete_album(identifier.0, params.cascade).await {
+        Ok(()) => Response::builder()
+            .status(StatusCode::OK)
+            .body(Body::empty())
+            .map_err(|e| e.into()),
+        Err(e) => albums_error_response(e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use std::sync::Arc;
+
+    use axum::{routing::delete, Router};
+    use axum_test_helper::TestClient;
+    use mockall::predicate;
+    use reqwest::StatusCode;
+
+    use crate::web::albums::{
+        delete_album_handler, list_albums_handler::tests::MockService, DynAlbumsService,
+    };
+
+    pub fn app(service: MockService) -> TestClient {
+        let albums_service = Arc::new(service) as DynAlbumsService;
+        let router = Router::new()
+            .route(
+                "/:identifier",
+                delete(delete_album_handler::delete_album_handler),
+            )
+            .with_state(albums_service);
+        TestClient::new(router)
+    }
+
+    #[tokio::test]
+    async fn on_delete_keep_images_unless_cascading() {
+        let mut mock_service = MockService::new();
+        mock_service
+            .expect_delete_album()
+            .with(predicate::eq(1), predicate::eq(false))
+            .times(1)
+            .returning(|_i, _c| Ok(()));
+        mock_service
+            .expect_delete_album()
+            .with(predicate::eq(2), predicate::eq(true))
+            .times(1)
+            .returning(|_i, _c| Ok(()));
+        let app = app(mock_service);
+        let response = app.delete("/1").send().await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let response = app.delete("/2?cascade=true").send().await;
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn on_malformed_cascade_return_bad_request_code() {
+        let app = app(MockService::new());
+        let response = app.delete("/1?cascade=yes").send().await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+}
@@ -0,0 +1,122 @@
+use axum::{
+    body::{self, Body},
+    extract::{rejection::QueryRejection, Query},
+    http::{Response, StatusCode},
+    Json,
+};
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::{
+    error::YaissError, services::images::ports::incoming::albums_service::AlbumsServiceError,
+};
+
+use super::{albums_error_response, AlbumJson, DynAlbumsService};
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ListAlbums {
+    /// Lists the albums nested in this one instead of the top level ones.
+    pub parent: Option<i64>,
+}
+
+pub async fn list_albums_handler(
+    axum::extract::State(service): axum::extract::State<DynAlbumsService>,
+    params: Result<Query<ListAlbums>, QueryRejection>,
+) -> Result<Response<Body>, YaissError> {
+    let params = match params {
+        Ok(Query(params)) => params,
+        Err(rejection) => {
+            return albums_error_response(AlbumsServiceError::InvalidRequest(rejection.body_text()))
+        }
+    };
+    match service.albums(params.parent).await {
+        Ok(albums) => {
+            let albums = albums
+                .into_iter()
+                .map(AlbumJson::from)
+                .collect::<Vec<AlbumJson>>();
+            Response::builder()
+                .status(StatusCode::OK)
+                .header(axum::http::header::CONTENT_TYPE, "application/json")
+                .body(body::Body::from(
+                    Json(json!({ "albums": albums })).to_string(),
+                ))
+                .map_err(|e| e.into())
+        }
+        Err(e) => albums_error_response(e),
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+
+    use std::sync::Arc;
+
+    use async_trait::async_trait;
+    use axum::{routing::get, Router};
+    use axum_test_helper::TestClient;
+    use chrono::Utc;
+    use mockall::{mock, predicate};
+    use reqwest::StatusCode;
+    use serde_json::{json, Value};
+
+    use crate::{
+        services::images::{
+            domain::{
+                album::{Album, AlbumUpdate},
+                image::Image,
+            },
+            ports::incoming::albums_service::{AlbumsService, AlbumsServiceError},
+        },
+        web::albums::{list_albums_handler, AlbumJson, DynAlbumsService},
+    };
+
+    mock! {
+        pub Service {}
+        #[async_trait]
+        impl AlbumsService for Service {
+            async fn create_album(&self, name: String, parent_id: Option<i64>) -> Result<Album, AlbumsServiceError>;
+            async fn album(&self, id: i64) -> Result<Album, AlbumsServiceError>;
+            async fn albums(&self, parent_id: Option<i64>) -> Result<Vec<Album>, AlbumsServiceError>;
+            async fn update_album(&self, id: i64, update: AlbumUpdate) -> Result<Album, AlbumsServiceError>;
+            async fn delete_album(&self, id: i64, cascade: bool) -> Result<(), AlbumsServiceError>;
+            async fn add_images(&self, album_id: i64, image_ids: Vec<i64>) -> Result<(), AlbumsServiceError>;
+            async fn remove_image(&self, album_id: i64, image_id: i64) -> Result<(), AlbumsServiceError>;
+            async fn reorder_images(&self, album_id: i64, image_ids: Vec<i64>) -> Result<(), AlbumsServiceError>;
+            async fn album_images(&self, album_id: i64, count: i64, offset: i64) -> Result<Vec<Image>, AlbumsServiceError>;
+        }
+    }
+
+    pub fn app(service: MockService) -> TestClient {
+        let albums_service = Arc::new(service) as DynAlbumsService;
+        let router = Router::new()
+            .route("/", get(list_albums_handler::list_albums_handler))
+            .with_state(albums_service);
+        TestClient::new(router)
+    }
+
+    #[tokio::test]
+    async fn on_parent_list_nested_albums() {
+        let now = Utc::now();
+        let album = Album::new(2, "Summer".to_string(), now).with_parent_id(1);
+        let returned = album.clone();
+        let mut mock_service = MockService::new();
+        mock_service
+            .expect_albums()
+            .with(predicate::eq(Some(1)))
+            .returning(move |_p| Ok(vec![returned.clone()]));
+        let app = app(mock_service);
+        let response = app.get("/?parent=1").send().await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response.bytes().await;
+        let body: Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body, json!({ "albums": [AlbumJson::from(album)] }));
+    }
+
+    #[tokio::test]
+    async fn on_malformed_parent_return_bad_request_code() {
+        let app = app(MockService::new());
+        let response = app.get("/?parent=top").send().await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+}
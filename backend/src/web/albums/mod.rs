@@ -0,0 +1,139 @@
+use std::sync::Arc;
+
+use axum::{
+    body::{self, Body},
+    http::{Response, StatusCode},
+    routing::{delete, get, patch, post},
+    Json, Router,
+};
+use serde::Serialize;
+use serde_json::json;
+use tracing::error;
+
+use crate::{
+    error::YaissError,
+    services::images::{
+        domain::{album::Album, api_key::Scope},
+        image_albums::ImageAlbums,
+        ports::incoming::albums_service::{AlbumsService, AlbumsServiceError},
+    },
+    state::State,
+    web::auth::scoped,
+};
+
+pub mod add_album_images_handler;
+pub mod create_album_handler;
+pub mod delete_album_handler;
+pub mod list_albums_handler;
+pub mod query_album_handler;
+pub mod query_album_images_handler;
+pub mod remove_album_image_handler;
+pub mod reorder_album_images_handler;
+pub mod update_album_handler;
+
+pub(crate) type DynAlbumsService = Arc<dyn AlbumsService + Send + Sync>;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AlbumJson {
+    id: i64,
+    name: String,
+    parent_id: Option<i64>,
+    cover_image_id: Option<i64>,
+    image_count: i64,
+    created_on: String,
+}
+
+impl From<Album> for AlbumJson {
+    fn from(value: Album) -> Self {
+        Self {
+            id: value.id(),
+            name: value.name().to_string(),
+            parent_id: value.parent_id(),
+            cover_image_id: value.cover_image_id(),
+            image_count: value.image_count(),
+            created_on: value.created_on().to_string(),
+        }
+    }
+}
+
+pub(crate) fn album_response(
+    status: StatusCode,
+    album: Album,
+) -> Result<Response<Body>, YaissError> {
+    Response::builder()
+        .status(status)
+        .header(axum::http::header::CONTENT_TYPE, "application/json")
+        .body(body::Body::from(
+            Json(json!(AlbumJson::from(album))).to_string(),
+        ))
+        .map_err(|e| e.into())
+}
+
+pub(crate) fn albums_error_response(e: AlbumsServiceError) -> Result<Response<Body>, YaissError> {
+    let message = e.to_string();
+    error!("{}", message);
+    let code = match e {
+        AlbumsServiceError::AlbumNotFound | AlbumsServiceError::ImageNotFound => {
+            StatusCode::NOT_FOUND
+        }
+        AlbumsServiceError::InvalidRequest(_) | AlbumsServiceError::TooManyImagesRequested => {
+            StatusCode::BAD_REQUEST
+        }
+        AlbumsServiceError::InternalError => StatusCode::INTERNAL_SERVER_ERROR,
+    };
+    Response::builder()
+        .status(code)
+        .header(axum::http::header::CONTENT_TYPE, "application/json")
+        .body(body::Body::from(
+            Json(json!({
+                "error": message,
+            }))
+            .to_string(),
+        ))
+        .map_err(|e| e.into())
+}
+
+pub fn router(state: State) -> Router<(), Body> {
+    let storage = state.storage();
+    let albums_service = Arc::new(ImageAlbums::new(storage)) as DynAlbumsService;
+    Router::new()
+        .route(
+            "/",
+            scoped(Scope::Read, get(list_albums_handler::list_albums_handler)).merge(scoped(
+                Scope::Upload,
+                post(create_album_handler::create_album_handler),
+            )),
+        )
+        .route(
+            "/:identifier",
+            scoped(Scope::Read, get(query_album_handler::query_album_handler))
+                .merge(scoped(
+                    Scope::Upload,
+                    patch(update_album_handler::update_album_handler),
+                ))
+                .merge(scoped(
+                    Scope::Delete,
+                    delete(delete_album_handler::delete_album_handler),
+                )),
+        )
+        .route(
+            "/:identifier/images",
+            scoped(
+                Scope::Read,
+                get(query_album_images_handler::query_album_images_handler),
+            )
+            .merge(scoped(
+                Scope::Upload,
+                post(add_album_images_handler::add_album_images_handler)
+                    .put(reorder_album_images_handler::reorder_album_images_handler),
+            )),
+        )
+        .route(
+            "/:identifier/images/:image",
+            scoped(
+                Scope::Upload,
+                delete(remove_album_image_handler::remove_album_image_handler),
+            ),
+        )
+        .with_state(albums_service)
+}
@@ -0,0 +1,82 @@
+use axum::{body::Body, http::Response, http::StatusCode};
+
+use crate::error::YaissError;
+
+use super::{album_response, albums_error_response, DynAlbumsService};
+
+pub async fn query_album_handler(
+    axum::extract::State(service): axum::extract::State<DynAlbumsService>,
+    identifier: axum::extract::Path<i64>,
+) -> Result<Response<Body>, YaissError> {
+    match service.album(identifier.0).await {
+        Ok(album) => album_response(StatusCode::OK, album),
+        Err(e) => albums_error_response(e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use std::sync::Arc;
+
+    use axum::{routing::get, Router};
+    use axum_test_helper::TestClient;
+    use chrono::Utc;
+    use mockall::predicate;
+    use reqwest::StatusCode;
+    use serde_json::{json, Value};
+
+    use crate::{
+        services::images::{
+            domain::album::Album, ports::incoming::albums_service::AlbumsServiceError,
+        },
+        web::albums::{
+            list_albums_handler::tests::MockService, query_album_handler, AlbumJson,
+            DynAlbumsService,
+        },
+    };
+
+    pub fn app(service: MockService) -> TestClient {
+        let albums_service = Arc::new(service) as DynAlbumsService;
+        let router = Router::new()
+            .route(
+                "/:identifier",
+                get(query_album_handler::query_album_handler),
+            )
+            .with_state(albums_service);
+        TestClient::new(router)
+    }
+
+    #[tokio::test]
+    async fn on_album_existing_return_ok() {
+        let album = Album::new(1, "Summer".to_string(), Utc::now())
+            .with_cover_image_id(5)
+            .with_image_count(2);
+        let returned = album.clone();
+        let mut mock_service = MockService::new();
+        mock_service
+            .expect_album()
+            .with(predicate::eq(1))
+            .returning(move |_i| Ok(returned.clone()));
+        let app = app(mock_service);
+        let response = app.get("/1").send().await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response.bytes().await;
+        let body: Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body, json!(AlbumJson::from(album)));
+    }
+
+    #[tokio::test]
+    async fn on_album_missing_return_not_found_code() {
+        let mut mock_service = MockService::new();
+        mock_service
+            .expect_album()
+            .returning(|_i| Err(AlbumsServiceError::AlbumNotFound));
+        let app = app(mock_service);
+        let response = app.get("/1").send().await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        let body = response.bytes().await;
+        let body: Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body, json!({"error": "Album not found"}));
+    }
+}
@@ -0,0 +1,126 @@
+use axum::{
+    body::{self, Body},
+    extract::{rejection::QueryRejection, Query},
+    http::{Response, StatusCode},
+    Json,
+};
+use serde_json::json;
+
+use crate::{
+    error::YaissError,
+    services::images::ports::incoming::albums_service::AlbumsServiceError,
+    web::images::batch_query_image_handler::{ImageJson, Pagination},
+};
+
+use super::{albums_error_response, DynAlbumsService};
+
+/// Albums keep their own order, so only `count` and `offset` of the
+/// listing parameters apply.
+pub async fn query_album_images_handler(
+    axum::extract::State(service): axum::extract::State<DynAlbumsService>,
+    identifier: axum::extract::Path<i64>,
+    pagination: Result<Query<Pagination>, QueryRejection>,
+) -> Result<Response<Body>, YaissError> {
+    let pagination = match pagination {
+        Ok(Query(pagination)) => pagination,
+        Err(rejection) => {
+            return albums_error_response(AlbumsServiceError::InvalidRequest(rejection.body_text()))
+        }
+    };
+    if pagination.sort.is_some() || pagination.tags.is_some() || pagination.cursor.is_some() {
+        return albums_error_response(AlbumsServiceError::InvalidRequest(
+            "Album listings only support count and offset".to_string(),
+        ));
+    }
+    match service
+        .album_images(identifier.0, pagination.count, pagination.offset)
+        .await
+    {
+        Ok(images) => {
+            let images = images
+                .into_iter()
+                .map(ImageJson::from)
+                .collect::<Vec<ImageJson>>();
+            Response::builder()
+                .status(StatusCode::OK)
+                .header(axum::http::header::CONTENT_TYPE, "application/json")
+                .body(body::Body::from(
+                    Json(json!({ "images": images })).to_string(),
+                ))
+                .map_err(|e| e.into())
+        }
+        Err(e) => albums_error_response(e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use std::sync::Arc;
+
+    use axum::{routing::get, Router};
+    use axum_test_helper::TestClient;
+    use chrono::Utc;
+    use mockall::predicate;
+    use reqwest::StatusCode;
+    use serde_json::{json, Value};
+
+    use crate::{
+        services::images::{
+            domain::image::Image, ports::incoming::albums_service::AlbumsServiceError,
+        },
+        web::{
+            albums::{
+                list_albums_handler::tests::MockService, query_album_images_handler,
+                DynAlbumsService,
+            },
+            images::batch_query_image_handler::ImageJson,
+        },
+    };
+
+    pub fn app(service: MockService) -> TestClient {
+        let albums_service = Arc::new(service) as DynAlbumsService;
+        let router = Router::new()
+            .route(
+                "/:identifier/images",
+                get(query_album_images_handler::query_album_images_handler),
+            )
+            .with_state(albums_service);
+        TestClient::new(router)
+    }
+
+    #[tokio::test]
+    async fn on_album_images_return_page() {
+        let now = Utc::now();
+        let mut mock_service = MockService::new();
+        mock_service
+            .expect_album_images()
+            .with(predicate::eq(1), predicate::eq(10), predicate::eq(20))
+            .returning(move |_a, _c, _o| Ok(vec![Image::new(4, "some/path".to_string(), now)]));
+        let app = app(mock_service);
+        let response = app.get("/1/images?count=10&offset=20").send().await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response.bytes().await;
+        let body: Value = serde_json::from_slice(&body).unwrap();
+        let image_json: ImageJson = Image::new(4, "some/path".to_string(), now).into();
+        assert_eq!(body, json!({ "images": [image_json] }));
+    }
+
+    #[tokio::test]
+    async fn on_unsupported_params_return_bad_request_code() {
+        let app = app(MockService::new());
+        let response = app.get("/1/images?sort=captured_on").send().await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn on_too_many_images_return_bad_request_code() {
+        let mut mock_service = MockService::new();
+        mock_service
+            .expect_album_images()
+            .returning(|_a, _c, _o| Err(AlbumsServiceError::TooManyImagesRequested));
+        let app = app(mock_service);
+        let response = app.get("/1/images?count=51").send().await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+}
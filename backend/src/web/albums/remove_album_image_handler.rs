@@ -0,0 +1,69 @@
+use axum::{
+    body::Body,
+    http::{Response, StatusCode},
+};
+
+use crate::error::YaissError;
+
+use super::{albums_error_response, DynAlbumsService};
+
+pub async fn remove_album_image_handler(
+    axum::extract::State(service): axum::extract::State<DynAlbumsService>,
+    path: axum::extract::Path<(i64, i64)>,
+) -> Result<Response<Body>, YaissError> {
+    let (album_id, image_id) = path.0;
+    match service.remove_image(album_id, image_id).await {
+        Ok(()) => Response::builder()
+            .status(StatusCode::OK)
+            .body(Body::empty())
+            .map_err(|e| e.into()),
+        Err(e) => albums_error_response(e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use std::sync::Arc;
+
+    use axum::{routing::delete, Router};
+    use axum_test_helper::TestClient;
+    use mockall::predicate;
+    use reqwest::StatusCode;
+
+    use crate::{
+        services::images::ports::incoming::albums_service::AlbumsServiceError,
+        web::albums::{
+            list_albums_handler::tests::MockService, remove_album_image_handler, DynAlbumsService,
+        },
+    };
+
+    pub fn app(service: MockService) -> TestClient {
+        let albums_service = Arc::new(service) as DynAlbumsService;
+        let router = Router::new()
+            .route(
+                "/:identifier/images/:image",
+                delete(remove_album_image_handler::remove_album_image_handler),
+            )
+            .with_state(albums_service);
+        TestClient::new(router)
+    }
+
+    #[tokio::test]
+    async fn on_remove_image_return_ok() {
+        let mut mock_service = MockService::new();
+        mock_service
+            .expect_remove_image()
+            .with(predicate::eq(1), predicate::eq(4))
+            .returning(|_a, _i| Ok(()));
+        mock_service
+            .expect_remove_image()
+            .with(predicate::eq(1), predicate::eq(5))
+            .returning(|_a, _i| Err(AlbumsServiceError::ImageNotFound));
+        let app = app(mock_service);
+        let response = app.delete("/1/images/4").send().await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let response = app.delete("/1/images/5").send().await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+}
@@ -0,0 +1,78 @@
+use axum::{
+    body::Body,
+    http::{Response, StatusCode},
+};
+
+use crate::error::YaissError;
+
+use super::{add_album_images_handler::AlbumImagesJson, albums_error_response, DynAlbumsService};
+
+pub async fn reorder_album_images_handler(
+    axum::extract::State(service): axum::extract::State<DynAlbumsService>,
+    identifier: axum::extract::Path<i64>,
+    images: axum::extract::Json<AlbumImagesJson>,
+) -> Result<Response<Body>, YaissError> {
+    match service.reorder_images(identifier.0, images.0.images).await {
+        Ok(()) => Response::builder()
+            .status(StatusCode::OK)
+            .body(Body::empty())
+            .map_err(|e| e.into()),
+        Err(e) => albums_error_response(e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use std::sync::Arc;
+
+    use axum::{routing::put, Router};
+    use axum_test_helper::TestClient;
+    use mockall::predicate;
+    use reqwest::StatusCode;
+    use serde_json::{json, Value};
+
+    use crate::{
+        services::images::ports::incoming::albums_service::AlbumsServiceError,
+        web::albums::{
+            list_albums_handler::tests::MockService, reorder_album_images_handler, DynAlbumsService,
+        },
+    };
+
+    pub fn app(service: MockService) -> TestClient {
+        let albums_service = Arc::new(service) as DynAlbumsService;
+        let router = Router::new()
+            .route(
+                "/:identifier/images",
+                put(reorder_album_images_handler::reorder_album_images_handler),
+            )
+            .with_state(albums_service);
+        TestClient::new(router)
+    }
+
+    #[tokio::test]
+    async fn on_incomplete_order_return_bad_request_code() {
+        let mut mock_service = MockService::new();
+        mock_service
+            .expect_reorder_images()
+            .with(predicate::eq(1), predicate::eq(vec![2]))
+            .returning(|_a, _i| {
+                Err(AlbumsServiceError::InvalidRequest(
+                    "Order must list every image of the album".to_string(),
+                ))
+            });
+        let app = app(mock_service);
+        let response = app
+            .put("/1/images")
+            .json(&json!({"images": [2]}))
+            .send()
+            .await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let body = response.bytes().await;
+        let body: Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(
+            body,
+            json!({"error": "Order must list every image of the album"})
+        );
+    }
+}
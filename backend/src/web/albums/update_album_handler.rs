@@ -0,0 +1,113 @@
+use axum::{body::Body, http::Response, http::StatusCode};
+use serde::{Deserialize, Deserializer};
+
+use crate::{error::YaissError, services::images::domain::album::AlbumUpdate};
+
+use super::{album_response, albums_error_response, DynAlbumsService};
+
+/// Tells an absent field apart from an explicit `null`, which clears it.
+fn nullable<'de, D>(deserializer: D) -> Result<Option<Option<i64>>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Option::<i64>::deserialize(deserializer).map(Some)
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct UpdateAlbum {
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default, deserialize_with = "nullable")]
+    pub parent_id: Option<Option<i64>>,
+    #[serde(default, deserialize_with = "nullable")]
+    pub cover_image_id: Option<Option<i64>>,
+}
+
+impl From<UpdateAlbum> for AlbumUpdate {
+    fn from(value: UpdateAlbum) -> Self {
+        Self {
+            name: value.name,
+            parent_id: value.parent_id,
+            cover_image_id: value.cover_image_id,
+        }
+    }
+}
+
+pub async fn update_album_handler(
+    axum::extract::State(service): axum::extract::State<DynAlbumsService>,
+    identifier: axum::extract::Path<i64>,
+    update: axum::extract::Json<UpdateAlbum>,
+) -> Result<Response<Body>, YaissError> {
+    match service.update_album(identifier.0, update.0.into()).await {
+        Ok(album) => album_response(StatusCode::OK, album),
+        Err(e) => albums_error_response(e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use std::sync::Arc;
+
+    use axum::{routing::patch, Router};
+    use axum_test_helper::TestClient;
+    use chrono::Utc;
+    use mockall::predicate;
+    use reqwest::StatusCode;
+    use serde_json::json;
+
+    use crate::{
+        services::images::{
+            domain::album::{Album, AlbumUpdate},
+            ports::incoming::albums_service::AlbumsServiceError,
+        },
+        web::albums::{
+            list_albums_handler::tests::MockService, update_album_handler, DynAlbumsService,
+        },
+    };
+
+    pub fn app(service: MockService) -> TestClient {
+        let albums_service = Arc::new(service) as DynAlbumsService;
+        let router = Router::new()
+            .route(
+                "/:identifier",
+                patch(update_album_handler::update_album_handler),
+            )
+            .with_state(albums_service);
+        TestClient::new(router)
+    }
+
+    #[tokio::test]
+    async fn on_null_parent_move_album_to_top_level() {
+        let expected = AlbumUpdate {
+            name: Some("Winter".to_string()),
+            parent_id: Some(None),
+            cover_image_id: None,
+        };
+        let mut mock_service = MockService::new();
+        mock_service
+            .expect_update_album()
+            .with(predicate::eq(2), predicate::eq(expected))
+            .returning(|id, _u| Ok(Album::new(id, "Winter".to_string(), Utc::now())));
+        let app = app(mock_service);
+        let response = app
+            .patch("/2")
+            .json(&json!({"name": "Winter", "parent_id": null}))
+            .send()
+            .await;
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn on_invalid_parent_return_bad_request_code() {
+        let mut mock_service = MockService::new();
+        mock_service.expect_update_album().returning(|_i, _u| {
+            Err(AlbumsServiceError::InvalidRequest(
+                "Album cannot be nested below itself".to_string(),
+            ))
+        });
+        let app = app(mock_service);
+        let response = app.patch("/2").json(&json!({"parent_id": 3})).send().await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+}
@@ -0,0 +1,255 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::State,
+    http::{header, HeaderMap, Request, StatusCode},
+    middleware::{self, Next},
+    response::{IntoResponse, Response},
+    routing::MethodRouter,
+    Json,
+};
+use hyper::Body;
+use serde_json::json;
+use tracing::error;
+
+use crate::services::images::{
+    domain::{
+        api_key::{ApiKey, Scope},
+        user::{on_behalf_of, USER_SCOPES},
+    },
+    ports::incoming::{
+        api_keys_service::{ApiKeysService, ApiKeysServiceError},
+        users_service::{UsersService, UsersServiceError},
+    },
+};
+
+pub(crate) type DynApiKeysService = Arc<dyn ApiKeysService + Send + Sync>;
+pub(crate) type DynUsersService = Arc<dyn UsersService + Send + Sync>;
+
+/// Requests authenticate with an API key or a user session token.
+#[derive(Clone)]
+pub struct AuthState {
+    pub api_keys: DynApiKeysService,
+    pub users: DynUsersService,
+}
+
+/// The non-empty `Authorization: Bearer` token.
+pub(crate) fn bearer_token(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .map(str::trim)
+        .filter(|token| !token.is_empty())
+}
+
+fn auth_error_response(status: StatusCode, message: String) -> Response {
+    let mut response = (status, Json(json!({ "error": message }))).into_response();
+    if status == StatusCode::UNAUTHORIZED {
+        response
+            .headers_mut()
+            .insert(header::WWW_AUTHENTICATE, "Bearer".parse().unwrap());
+    }
+    response
+}
+
+/// Resolves the `Authorization: Bearer` key and attaches it to the request,
+/// for `require_scope` to check.
+///
+/// Tokens that are no API key are tried as user sessions. A user gets a key
+/// with `USER_SCOPES` and the rest of the request runs on their behalf, so
+/// storage only shows them their own images.
+pub async fn authenticate<B>(
+    State(state): State<AuthState>,
+    mut request: Request<B>,
+    next: Next<B>,
+) -> Response {
+    let Some(secret) = bearer_token(request.headers()).map(str::to_string) else {
+        return auth_error_response(StatusCode::UNAUTHORIZED, "Missing API key".to_string());
+    };
+    let invalid = match state.api_keys.authenticate(&secret).await {
+        Ok(key) => {
+            request.extensions_mut().insert(key);
+            return next.run(request).await;
+        }
+        Err(e @ ApiKeysServiceError::InvalidKey) => e,
+        Err(e) => {
+            let message = e.to_string();
+            error!("{}", message);
+            return auth_error_response(StatusCode::INTERNAL_SERVER_ERROR, message);
+        }
+    };
+    match state.users.authenticate(&secret).await {
+        Ok(user) => {
+            let key = ApiKey::new(
+                0,
+                user.name().to_string(),
+                USER_SCOPES.to_vec(),
+                user.created_on(),
+            );
+            let user_id = user.id();
+            request.extensions_mut().insert(key);
+            request.extensions_mut().insert(user);
+            on_behalf_of(user_id, next.run(request)).await
+        }
+        Err(UsersServiceError::InvalidCredentials) => {
+            auth_error_response(StatusCode::UNAUTHORIZED, invalid.to_string())
+        }
+        Err(e) => {
+            let message = e.to_string();
+            error!("{}", message);
+            auth_error_response(StatusCode::INTERNAL_SERVER_ERROR, message)
+        }
+    }
+}
+
+pub async fn require_scope<B>(
+    State(scope): State<Scope>,
+    request: Request<B>,
+    next: Next<B>,
+) -> Response {
+    match request.extensions().get::<ApiKey>() {
+        Some(key) if key.allows(scope) => next.run(request).await,
+        Some(_) => auth_error_response(StatusCode::FORBIDDEN, format!("Missing scope: {}", scope)),
+        None => auth_error_response(StatusCode::UNAUTHORIZED, "Missing API key".to_string()),
+    }
+}
+
+/// Only lets requests through when their key has `scope`.
+pub(crate) fn scoped<S>(scope: Scope, route: MethodRouter<S, Body>) -> MethodRouter<S, Body>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    route.route_layer(middleware::from_fn_with_state(scope, require_scope))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use axum::{middleware, routing::get, Extension, Router};
+    use axum_test_helper::TestClient;
+    use chrono::Utc;
+    use mockall::predicate;
+    use reqwest::StatusCode;
+    use serde_json::{json, Value};
+
+    use crate::{
+        services::images::{
+            domain::{
+                api_key::{ApiKey, Scope},
+                user::{current_user_id, User},
+            },
+            ports::incoming::{
+                api_keys_service::ApiKeysServiceError, users_service::UsersServiceError,
+            },
+        },
+        web::{
+            admin::list_api_keys_handler::tests::MockService,
+            auth::{self, scoped, AuthState, DynApiKeysService, DynUsersService},
+            users::register_handler::tests::MockService as MockUsersService,
+        },
+    };
+
+    async fn ok() -> &'static str {
+        "ok"
+    }
+
+    async fn whoami(Extension(user): Extension<User>) -> String {
+        format!("{}:{:?}", user.name(), current_user_id())
+    }
+
+    fn app() -> TestClient {
+        let mut mock_service = MockService::new();
+        mock_service
+            .expect_authenticate()
+            .with(predicate::eq("reader"))
+            .returning(|_| {
+                Ok(ApiKey::new(
+                    1,
+                    "reader".to_string(),
+                    vec![Scope::Read],
+                    Utc::now(),
+                ))
+            });
+        mock_service
+            .expect_authenticate()
+            .returning(|_| Err(ApiKeysServiceError::InvalidKey));
+        let mut mock_users_service = MockUsersService::new();
+        mock_users_service
+            .expect_authenticate()
+            .with(predicate::eq("session"))
+            .returning(|_| Ok(User::new(7, "alice".to_string(), Utc::now())));
+        mock_users_service
+            .expect_authenticate()
+            .returning(|_| Err(UsersServiceError::InvalidCredentials));
+        let state = AuthState {
+            api_keys: Arc::new(mock_service) as DynApiKeysService,
+            users: Arc::new(mock_users_service) as DynUsersService,
+        };
+        let router = Router::new()
+            .route("/read", scoped(Scope::Read, get(ok)))
+            .route("/delete", scoped(Scope::Delete, get(ok)))
+            .route("/admin", scoped(Scope::Admin, get(ok)))
+            .route("/whoami", scoped(Scope::Read, get(whoami)))
+            .layer(middleware::from_fn_with_state(state, auth::authenticate));
+        TestClient::new(router)
+    }
+
+    #[tokio::test]
+    async fn on_session_token_run_on_behalf_of_user() {
+        let app = app();
+        let response = app
+            .get("/whoami")
+            .header("Authorization", "Bearer session")
+            .send()
+            .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.text().await, "alice:Some(7)");
+        let response = app
+            .get("/admin")
+            .header("Authorization", "Bearer session")
+            .send()
+            .await;
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    }
+
+    #[tokio::test]
+    async fn on_scope_granted_pass_through() {
+        let response = app()
+            .get("/read")
+            .header("Authorization", "Bearer reader")
+            .send()
+            .await;
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn on_scope_missing_return_forbidden() {
+        let response = app()
+            .get("/delete")
+            .header("Authorization", "Bearer reader")
+            .send()
+            .await;
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+        let body: Value = serde_json::from_slice(&response.bytes().await).unwrap();
+        assert_eq!(body, json!({"error": "Missing scope: delete"}));
+    }
+
+    #[tokio::test]
+    async fn on_missing_or_invalid_key_return_unauthorized() {
+        let app = app();
+        let response = app.get("/read").send().await;
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        let body: Value = serde_json::from_slice(&response.bytes().await).unwrap();
+        assert_eq!(body, json!({"error": "Missing API key"}));
+        let response = app
+            .get("/read")
+            .header("Authorization", "Bearer stolen")
+            .send()
+            .await;
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        let body: Value = serde_json::from_slice(&response.bytes().await).unwrap();
+        assert_eq!(body, json!({"error": "Invalid API key"}));
+    }
+}
@@ -2,6 +2,7 @@ use std::sync::Arc;
 
 use axum::{
     body::{self, Body},
+    extract::{rejection::QueryRejection, Query},
     http::{Response, StatusCode},
     Json,
 };
@@ -12,14 +13,25 @@ use crate::{
     error::YaissError,
     services::images::ports::incoming::batch_delete_image_service::BatchDeleteImageService,
 };
+
+use super::{batch_query_image_handler::bad_request, delete_image_handler::DeleteParams};
+
 pub(crate) type DynBatchDeleteImageService = Arc<dyn BatchDeleteImageService + Send + Sync>;
 pub async fn batch_delete_image_handler(
     axum::extract::State(service): axum::extract::State<DynBatchDeleteImageService>,
+    params: Result<Query<DeleteParams>, QueryRejection>,
     identifiers: axum::extract::Json<Vec<i64>>,
 ) -> Result<Response<Body>, YaissError> {
+    let params = match params {
+        Ok(Query(params)) => params,
+        Err(rejection) => return bad_request(rejection.body_text()),
+    };
     let service = service.clone();
     let builder = Response::builder();
-    let builder = match service.batch_delete_image(identifiers.0).await {
+    let builder = match service
+        .batch_delete_image(identifiers.0, params.permanent)
+        .await
+    {
         Ok(()) => builder.status(StatusCode::OK).body(Body::empty()),
         Err(e) => {
             let message = e.to_string();
@@ -57,7 +69,7 @@ mod tests {
         pub Service {}
         #[async_trait]
         impl BatchDeleteImageService for Service {
-            async fn batch_delete_image(&self, indexes: Vec<i64>) -> Result<(), BatchDeleteImageServiceError>;
+            async fn batch_delete_image(&self, indexes: Vec<i64>, permanent: bool) -> Result<(), BatchDeleteImageServiceError>;
         }
     }
 
@@ -78,12 +90,12 @@ mod tests {
         let mut mock_service = MockService::new();
         mock_service
             .expect_batch_delete_image()
-            .with(predicate::eq(vec![1i64, 2, 3, 4]))
-            .returning(move |_i| Ok(()));
+            .with(predicate::eq(vec![1i64, 2, 3, 4]), predicate::eq(true))
+            .returning(move |_i, _p| Ok(()));
         let app = app(mock_service);
 
         let response = app
-            .delete("/")
+            .delete("/?permanent=true")
             .header(axum::http::header::CONTENT_TYPE, "application/json")
             .json(&json!(vec![1i64, 2, 3, 4]))
             .send()
@@ -99,8 +111,8 @@ mod tests {
         let mut mock_service = MockService::new();
         mock_service
             .expect_batch_delete_image()
-            .with(predicate::eq(vec![1i64, 2, 3, 4]))
-            .returning(move |_i| Err(BatchDeleteImageServiceError::InternalError));
+            .with(predicate::eq(vec![1i64, 2, 3, 4]), predicate::eq(false))
+            .returning(move |_i, _p| Err(BatchDeleteImageServiceError::InternalError));
         let app = app(mock_service);
         let response = app
             .delete("/")
@@ -119,8 +131,8 @@ mod tests {
         let mut mock_service = MockService::new();
         mock_service
             .expect_batch_delete_image()
-            .with(predicate::eq(vec![0i64; 60]))
-            .returning(move |_i| Err(BatchDeleteImageServiceError::TooManyImagesToDelete(50)));
+            .with(predicate::eq(vec![0i64; 60]), predicate::eq(false))
+            .returning(move |_i, _p| Err(BatchDeleteImageServiceError::TooManyImagesToDelete(50)));
         let app = app(mock_service);
         let response = app
             .delete("/")
@@ -12,30 +12,60 @@ use tracing::error;
 use crate::{
     error::YaissError,
     services::images::{
-        domain::image::Image,
+        domain::{cursor::Cursor, image::Image, sort_key::SortKey, tag::TagMatch},
         ports::incoming::batch_query_image_service::{
             BatchQueryImageService, BatchQueryImageServiceError,
         },
     },
 };
 
+use super::query_image_handler::ExifJson;
+
+fn default_count() -> i64 {
+    50
+}
+
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct Pagination {
+    #[serde(default = "default_count")]
     pub count: i64,
+    #[serde(default)]
     pub offset: i64,
+    #[serde(default)]
+    pub sort: Option<String>,
+    /// Comma separated tags the images must carry.
+    #[serde(default)]
+    pub tags: Option<String>,
+    #[serde(default, rename = "match")]
+    pub tag_match: Option<String>,
+    /// Opaque `next_cursor` of the previous page; empty for the first page.
+    /// Switches the listing to keyset paging over `(updated_on, id)`.
+    #[serde(default)]
+    pub cursor: Option<String>,
 }
 
 impl Pagination {
     pub fn new(count: i64, offset: i64) -> Self {
-        Self { count, offset }
+        Self {
+            count,
+            offset,
+            sort: None,
+            tags: None,
+            tag_match: None,
+            cursor: None,
+        }
     }
 }
 
 impl Default for Pagination {
     fn default() -> Self {
         Self {
-            count: 50,
+            count: default_count(),
             offset: 0,
+            sort: None,
+            tags: None,
+            tag_match: None,
+            cursor: None,
         }
     }
 }
@@ -44,13 +74,39 @@ impl Default for Pagination {
 pub struct ImageJson {
     id: i64,
     updated_on: String,
+    created_on: String,
+    width: Option<u32>,
+    height: Option<u32>,
+    color_type: Option<String>,
+    bit_depth: Option<u8>,
+    original_format: Option<String>,
+    original_size: Option<u64>,
+    stored_size: Option<u64>,
+    filename: Option<String>,
+    exif: Option<ExifJson>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    deleted_at: Option<String>,
+    status: String,
 }
 
 impl From<Image> for ImageJson {
     fn from(value: Image) -> Self {
+        let metadata = value.metadata();
         Self {
             id: value.id(),
             updated_on: value.updated_on().to_string(),
+            created_on: value.created_on().to_string(),
+            width: metadata.map(|metadata| metadata.width()),
+            height: metadata.map(|metadata| metadata.height()),
+            color_type: metadata.map(|metadata| metadata.color_type().to_string()),
+            bit_depth: metadata.map(|metadata| metadata.bit_depth()),
+            original_format: metadata.map(|metadata| metadata.original_format().to_string()),
+            original_size: metadata.map(|metadata| metadata.original_size()),
+            stored_size: metadata.map(|metadata| metadata.stored_size()),
+            filename: metadata.and_then(|metadata| metadata.filename().map(str::to_string)),
+            exif: value.exif().map(ExifJson::from),
+            deleted_at: value.deleted_at().map(|deleted_at| deleted_at.to_string()),
+            status: value.status().to_string(),
         }
     }
 }
@@ -63,10 +119,43 @@ pub async fn batch_query_image_handler(
     let service = service.clone();
     let builder = Response::builder();
     let pagination = pagination.unwrap_or_default();
-    let builder = match service
-        .batch_query_image(pagination.count, pagination.offset)
-        .await
-    {
+    let sort = pagination
+        .sort
+        .as_deref()
+        .map(str::parse::<SortKey>)
+        .unwrap_or(Ok(SortKey::default()));
+    let tag_match = pagination
+        .tag_match
+        .as_deref()
+        .map(str::parse::<TagMatch>)
+        .unwrap_or(Ok(TagMatch::default()));
+    let (sort, tag_match) = match (sort, tag_match) {
+        (Ok(sort), Ok(tag_match)) => (sort, tag_match),
+        (Err(message), _) | (_, Err(message)) => return bad_request(message),
+    };
+    if let Some(cursor) = &pagination.cursor {
+        return cursor_page(service, &pagination, cursor).await;
+    }
+    let result = match &pagination.tags {
+        Some(tags) => {
+            let tags = tags.split(',').map(str::to_string).collect();
+            service
+                .batch_query_image_by_tags(
+                    tags,
+                    tag_match,
+                    pagination.count,
+                    pagination.offset,
+                    sort,
+                )
+                .await
+        }
+        None => {
+            service
+                .batch_query_image(pagination.count, pagination.offset, sort)
+                .await
+        }
+    };
+    let builder = match result {
         Ok(images) => {
             let images = images
                 .into_iter()
@@ -75,22 +164,86 @@ pub async fn batch_query_image_handler(
             let body = Json(json!({ "images": images })).to_string();
             builder.status(StatusCode::OK).body(body::Body::from(body))
         }
-        Err(e) => {
-            let message = e.to_string();
-            error!("{}", message);
-            let status = match e {
-                BatchQueryImageServiceError::InternalError => StatusCode::INTERNAL_SERVER_ERROR,
-                BatchQueryImageServiceError::TooManyImagesRequested => StatusCode::BAD_REQUEST,
-                BatchQueryImageServiceError::InvalidRequest => StatusCode::BAD_REQUEST,
-                BatchQueryImageServiceError::NoRecordsFound => StatusCode::NOT_FOUND,
-            };
-            builder.status(status).body(body::Body::from(
-                Json(json!({
-                    "error": message,
-                }))
-                .to_string(),
-            ))
+        Err(e) => error_response(builder, e),
+    };
+    builder.map_err(|e| e.into())
+}
+
+pub(crate) fn bad_request(message: String) -> Result<Response<Body>, YaissError> {
+    error!("{}", message);
+    Response::builder()
+        .status(StatusCode::BAD_REQUEST)
+        .body(body::Body::from(
+            Json(json!({
+                "error": message,
+            }))
+            .to_string(),
+        ))
+        .map_err(|e| e.into())
+}
+
+fn error_response(
+    builder: axum::http::response::Builder,
+    e: BatchQueryImageServiceError,
+) -> axum::http::Result<Response<Body>> {
+    let message = e.to_string();
+    error!("{}", message);
+    let status = match e {
+        BatchQueryImageServiceError::InternalError => StatusCode::INTERNAL_SERVER_ERROR,
+        BatchQueryImageServiceError::TooManyImagesRequested => StatusCode::BAD_REQUEST,
+        BatchQueryImageServiceError::InvalidRequest => StatusCode::BAD_REQUEST,
+        BatchQueryImageServiceError::NoRecordsFound => StatusCode::NOT_FOUND,
+        BatchQueryImageServiceError::InvalidTag(_) => StatusCode::BAD_REQUEST,
+    };
+    builder.status(status).body(body::Body::from(
+        Json(json!({
+            "error": message,
+        }))
+        .to_string(),
+    ))
+}
+
+/// Keyset paging only walks the default `(updated_on, id)` order of the whole
+/// library, so offsets, other sort keys and tag filters are rejected.
+async fn cursor_page(
+    service: DynBatchQueryImageService,
+    pagination: &Pagination,
+    cursor: &str,
+) -> Result<Response<Body>, YaissError> {
+    if pagination.offset != 0
+        || pagination.tags.is_some()
+        || pagination
+            .sort
+            .as_deref()
+            .unwrap_or(SortKey::UpdatedOn.as_str())
+            != SortKey::UpdatedOn.as_str()
+    {
+        return bad_request("Cursor paging does not support offset, sort or tags".to_string());
+    }
+    let cursor = if cursor.is_empty() {
+        None
+    } else {
+        match cursor.parse::<Cursor>() {
+            Ok(cursor) => Some(cursor),
+            Err(message) => return bad_request(message),
+        }
+    };
+    let builder = Response::builder();
+    let builder = match service
+        .batch_query_image_page(cursor, pagination.count)
+        .await
+    {
+        Ok(page) => {
+            let next_cursor = page.next_cursor().map(|cursor| cursor.to_string());
+            let images = page
+                .into_images()
+                .into_iter()
+                .map(ImageJson::from)
+                .collect::<Vec<ImageJson>>();
+            let body = Json(json!({ "images": images, "next_cursor": next_cursor })).to_string();
+            builder.status(StatusCode::OK).body(body::Body::from(body))
         }
+        Err(e) => error_response(builder, e),
     };
     builder.map_err(|e| e.into())
 }
@@ -110,7 +263,12 @@ mod tests {
 
     use crate::{
         services::images::{
-            domain::image::Image,
+            domain::{
+                cursor::{Cursor, ImagePage},
+                image::Image,
+                sort_key::SortKey,
+                tag::TagMatch,
+            },
             ports::incoming::batch_query_image_service::{
                 BatchQueryImageService, BatchQueryImageServiceError,
             },
@@ -122,7 +280,9 @@ mod tests {
         pub Service {}
         #[async_trait]
         impl BatchQueryImageService for Service {
-            async fn batch_query_image(&self, count: i64, offset: i64) -> Result<Vec<Image>, BatchQueryImageServiceError>;
+            async fn batch_query_image(&self, count: i64, offset: i64, sort: SortKey) -> Result<Vec<Image>, BatchQueryImageServiceError>;
+            async fn batch_query_image_page(&self, cursor: Option<Cursor>, count: i64) -> Result<ImagePage, BatchQueryImageServiceError>;
+            async fn batch_query_image_by_tags(&self, tags: Vec<String>, tag_match: TagMatch, count: i64, offset: i64, sort: SortKey) -> Result<Vec<Image>, BatchQueryImageServiceError>;
         }
     }
 
@@ -144,8 +304,12 @@ mod tests {
         let mut mock_service = MockService::new();
         mock_service
             .expect_batch_query_image()
-            .with(predicate::eq(50), predicate::eq(0))
-            .returning(move |_i, _j| Ok(vec![Image::new(1, "some/path".to_string(), now)]));
+            .with(
+                predicate::eq(50),
+                predicate::eq(0),
+                predicate::eq(SortKey::UpdatedOn),
+            )
+            .returning(move |_i, _j, _s| Ok(vec![Image::new(1, "some/path".to_string(), now)]));
         let app = app(mock_service);
         let response = app
             .get("/?count=50&offset=0")
@@ -165,8 +329,12 @@ mod tests {
         let mut mock_service = MockService::new();
         mock_service
             .expect_batch_query_image()
-            .with(predicate::eq(50), predicate::eq(0))
-            .returning(move |_i, _j| Err(BatchQueryImageServiceError::InternalError));
+            .with(
+                predicate::eq(50),
+                predicate::eq(0),
+                predicate::eq(SortKey::UpdatedOn),
+            )
+            .returning(move |_i, _j, _s| Err(BatchQueryImageServiceError::InternalError));
         let app = app(mock_service);
         let response = app
             .get("/?count=50&offset=0")
@@ -185,8 +353,12 @@ mod tests {
         let mut mock_service = MockService::new();
         mock_service
             .expect_batch_query_image()
-            .with(predicate::eq(50), predicate::eq(0))
-            .returning(move |_i, _j| Err(BatchQueryImageServiceError::NoRecordsFound));
+            .with(
+                predicate::eq(50),
+                predicate::eq(0),
+                predicate::eq(SortKey::UpdatedOn),
+            )
+            .returning(move |_i, _j, _s| Err(BatchQueryImageServiceError::NoRecordsFound));
         let app = app(mock_service);
         let response = app
             .get("/?count=50&offset=0")
@@ -205,8 +377,12 @@ mod tests {
         let mut mock_service = MockService::new();
         mock_service
             .expect_batch_query_image()
-            .with(predicate::eq(51), predicate::eq(0))
-            .returning(move |_i, _j| Err(BatchQueryImageServiceError::TooManyImagesRequested));
+            .with(
+                predicate::eq(51),
+                predicate::eq(0),
+                predicate::eq(SortKey::UpdatedOn),
+            )
+            .returning(move |_i, _j, _s| Err(BatchQueryImageServiceError::TooManyImagesRequested));
         let app = app(mock_service);
         let response = app
             .get("/?count=51&offset=0")
@@ -225,8 +401,12 @@ mod tests {
         let mut mock_service = MockService::new();
         mock_service
             .expect_batch_query_image()
-            .with(predicate::eq(0), predicate::eq(0))
-            .returning(move |_i, _j| Err(BatchQueryImageServiceError::InvalidRequest));
+            .with(
+                predicate::eq(0),
+                predicate::eq(0),
+                predicate::eq(SortKey::UpdatedOn),
+            )
+            .returning(move |_i, _j, _s| Err(BatchQueryImageServiceError::InvalidRequest));
         let app = app(mock_service);
         let response = app
             .get("/?count=0&offset=0")
@@ -239,4 +419,109 @@ mod tests {
         let body: Value = serde_json::from_slice(&body).unwrap();
         assert_eq!(body, json!({"error": "Count or offset are below zero", }));
     }
+
+    #[tokio::test]
+    async fn on_captured_on_sort_query_by_capture_time() {
+        let mut mock_service = MockService::new();
+        mock_service
+            .expect_batch_query_image()
+            .with(
+                predicate::eq(10),
+                predicate::eq(0),
+                predicate::eq(SortKey::CapturedOn),
+            )
+            .returning(move |_i, _j, _s| Ok(vec![]));
+        let app = app(mock_service);
+        let response = app.get("/?count=10&offset=0&sort=captured_on").send().await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let response = app.get("/?count=10&offset=0&sort=size").send().await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let body = response.bytes().await;
+        let body: Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body, json!({"error": "Unknown sort key: size"}));
+    }
+
+    #[tokio::test]
+    async fn on_tags_query_images_by_tags() {
+        let mut mock_service = MockService::new();
+        mock_service
+            .expect_batch_query_image_by_tags()
+            .with(
+                predicate::eq(vec!["beach".to_string(), "sunset".to_string()]),
+                predicate::eq(TagMatch::Any),
+                predicate::eq(10),
+                predicate::eq(0),
+                predicate::eq(SortKey::UpdatedOn),
+            )
+            .returning(move |_t, _m, _i, _j, _s| Ok(vec![]));
+        let app = app(mock_service);
+        let response = app
+            .get("/?count=10&offset=0&tags=beach,sunset&match=any")
+            .send()
+            .await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let response = app
+            .get("/?count=10&offset=0&tags=beach&match=some")
+            .send()
+            .await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn on_cursor_query_next_page() {
+        let now = Utc::now();
+        let cursor = Cursor::new(now, 7);
+        let next_cursor = Cursor::new(now, 8);
+        let mut mock_service = MockService::new();
+        mock_service
+            .expect_batch_query_image_page()
+            .with(predicate::eq(None), predicate::eq(50))
+            .returning(move |_c, _n| Ok(ImagePage::new(vec![], Some(cursor))));
+        mock_service
+            .expect_batch_query_image_page()
+            .with(predicate::eq(Some(cursor)), predicate::eq(1))
+            .returning(move |_c, _n| {
+                Ok(ImagePage::new(
+                    vec![Image::new(8, "some/path".to_string(), now)],
+                    Some(next_cursor),
+                ))
+            });
+        let app = app(mock_service);
+
+        let response = app.get("/?cursor=").send().await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let body: Value = serde_json::from_slice(&response.bytes().await).unwrap();
+        assert_eq!(
+            body,
+            json!({ "images": [], "next_cursor": cursor.to_string() })
+        );
+
+        let response = app
+            .get(&format!("/?count=1&cursor={}", cursor))
+            .send()
+            .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let body: Value = serde_json::from_slice(&response.bytes().await).unwrap();
+        let image_json: ImageJson = Image::new(8, "some/path".to_string(), now).into();
+        assert_eq!(
+            body,
+            json!({ "images": [image_json], "next_cursor": next_cursor.to_string() })
+        );
+    }
+
+    #[tokio::test]
+    async fn on_invalid_cursor_return_bad_request_code() {
+        let app = app(MockService::new());
+        let response = app.get("/?cursor=zz").send().await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let body: Value = serde_json::from_slice(&response.bytes().await).unwrap();
+        assert_eq!(body, json!({"error": "Invalid cursor: zz"}));
+
+        let response = app.get("/?cursor=&sort=captured_on").send().await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let response = app.get("/?cursor=&offset=10").send().await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
 }
@@ -2,9 +2,11 @@ use std::sync::Arc;
 
 use axum::{
     body::{self, Body},
+    extract::{rejection::QueryRejection, Query},
     http::{Response, StatusCode},
     Json,
 };
+use serde::Deserialize;
 use serde_json::json;
 use tracing::error;
 
@@ -13,15 +15,29 @@ use crate::{
     services::images::ports::incoming::delete_image_service::DeleteImageServiceError,
 };
 
+use super::batch_query_image_handler::bad_request;
+
 pub(crate) type DynDeleteImagesService = Arc<dyn DeleteImageService + Send + Sync>;
 
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct DeleteParams {
+    /// Skips the trash and deletes the files right away.
+    #[serde(default)]
+    pub permanent: bool,
+}
+
 pub async fn delete_image_handler(
     axum::extract::State(service): axum::extract::State<DynDeleteImagesService>,
     identifier: axum::extract::Path<i64>,
+    params: Result<Query<DeleteParams>, QueryRejection>,
 ) -> Result<Response<Body>, YaissError> {
+    let params = match params {
+        Ok(Query(params)) => params,
+        Err(rejection) => return bad_request(rejection.body_text()),
+    };
     let service = service.clone();
     let builder = Response::builder();
-    let builder = match service.delete_image(identifier.0).await {
+    let builder = match service.delete_image(identifier.0, params.permanent).await {
         Ok(()) => builder.status(StatusCode::OK).body(Body::empty()),
         Err(e) => {
             let message = e.to_string();
@@ -67,7 +83,7 @@ mod tests {
         pub Service {}
         #[async_trait]
         impl DeleteImageService for Service {
-            async fn delete_image(&self, index: i64) -> Result<(), DeleteImageServiceError>;
+            async fn delete_image(&self, index: i64, permanent: bool) -> Result<(), DeleteImageServiceError>;
         }
     }
 
@@ -88,8 +104,8 @@ mod tests {
         let mut mock_service = MockService::new();
         mock_service
             .expect_delete_image()
-            .with(predicate::eq(1))
-            .returning(move |_i| Ok(()));
+            .with(predicate::eq(1), predicate::eq(false))
+            .returning(move |_i, _p| Ok(()));
         let app = app(mock_service);
         let response = app.delete("/1").send().await;
         assert_eq!(response.status(), StatusCode::OK);
@@ -97,12 +113,32 @@ mod tests {
         assert!(body.is_empty());
     }
 
+    #[tokio::test]
+    async fn on_permanent_skip_trash() {
+        let mut mock_service = MockService::new();
+        mock_service
+            .expect_delete_image()
+            .with(predicate::eq(1), predicate::eq(true))
+            .times(1)
+            .returning(move |_i, _p| Ok(()));
+        let app = app(mock_service);
+        let response = app.delete("/1?permanent=true").send().await;
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn on_malformed_permanent_return_bad_request_code() {
+        let app = app(MockService::new());
+        let response = app.delete("/1?permanent=yes").send().await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
     #[tokio::test]
     async fn on_internal_error_return_internal_server_code() {
         let mut mock_service = MockService::new();
         mock_service
             .expect_delete_image()
-            .returning(move |_i| Err(DeleteImageServiceError::InternalError));
+            .returning(move |_i, _p| Err(DeleteImageServiceError::InternalError));
         let app = app(mock_service);
         let response = app.delete("/1").body(Body::empty()).send().await;
 
@@ -117,7 +153,7 @@ mod tests {
         let mut mock_service = MockService::new();
         mock_service
             .expect_delete_image()
-            .returning(move |_i| Err(DeleteImageServiceError::ImageNotFound));
+            .returning(move |_i, _p| Err(DeleteImageServiceError::ImageNotFound));
         let app = app(mock_service);
         let response = app
             .delete("/1")
@@ -0,0 +1,134 @@
+use axum::{
+    body::{self, Body},
+    http::{Response, StatusCode},
+    Json,
+};
+use serde_json::json;
+use tracing::error;
+
+use crate::{
+    error::YaissError,
+    web::images::{
+        query_image_handler::ImageJson,
+        similar_images_handler::{
+            similar_images_error_status, DynSimilarImagesService, Similarity,
+        },
+    },
+};
+
+pub async fn duplicate_clusters_handler(
+    axum::extract::State(service): axum::extract::State<DynSimilarImagesService>,
+    similarity: Option<axum::extract::Query<Similarity>>,
+) -> Result<Response<Body>, YaissError> {
+    let service = service.clone();
+    let builder = Response::builder();
+    let similarity = similarity.unwrap_or_default();
+    let builder = match service.duplicate_clusters(similarity.max_distance).await {
+        Ok(clusters) => {
+            let clusters = clusters
+                .into_iter()
+                .map(|cluster| {
+                    cluster
+                        .into_iter()
+                        .map(ImageJson::from)
+                        .collect::<Vec<ImageJson>>()
+                })
+                .collect::<Vec<Vec<ImageJson>>>();
+            let body = Json(json!({ "clusters": clusters })).to_string();
+            builder
+                .status(StatusCode::OK)
+                .header(axum::http::header::CONTENT_TYPE, "application/json")
+                .body(body::Body::from(body))
+        }
+        Err(e) => {
+            let message = e.to_string();
+            error!("{}", message);
+            builder
+                .status(similar_images_error_status(&e))
+                .header(axum::http::header::CONTENT_TYPE, "application/json")
+                .body(body::Body::from(
+                    Json(json!({
+                        "error": message,
+                    }))
+                    .to_string(),
+                ))
+        }
+    };
+    builder.map_err(|e| e.into())
+}
+
+#[cfg(test)]
+mod tests {
+
+    use std::sync::Arc;
+
+    use axum::{routing::get, Router};
+    use axum_test_helper::TestClient;
+    use chrono::Utc;
+    use mockall::predicate;
+    use reqwest::StatusCode;
+    use serde_json::{json, Value};
+
+    use crate::{
+        services::images::{
+            domain::image::Image,
+            ports::incoming::similar_images_service::SimilarImagesServiceError,
+        },
+        web::images::{
+            duplicate_clusters_handler,
+            query_image_handler::ImageJson,
+            similar_images_handler::{self, tests::MockService},
+        },
+    };
+
+    pub fn app(service: MockService) -> TestClient {
+        let similar_images_service =
+            Arc::new(service) as similar_images_handler::DynSimilarImagesService;
+        let router = Router::new()
+            .route(
+                "/duplicates",
+                get(duplicate_clusters_handler::duplicate_clusters_handler),
+            )
+            .with_state(similar_images_service);
+        TestClient::new(router)
+    }
+
+    #[tokio::test]
+    async fn on_clusters_return_ok() {
+        let now = Utc::now();
+        let mut mock_service = MockService::new();
+        mock_service
+            .expect_duplicate_clusters()
+            .with(predicate::eq(2))
+            .returning(move |_d| {
+                Ok(vec![vec![
+                    Image::new(1, "some/path".to_string(), now),
+                    Image::new(2, "other/path".to_string(), now),
+                ]])
+            });
+        let app = app(mock_service);
+        let response = app.get("/duplicates?max_distance=2").send().await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response.bytes().await;
+        let body: Value = serde_json::from_slice(&body).unwrap();
+        let cluster: Vec<ImageJson> = vec![
+            Image::new(1, "some/path".to_string(), now).into(),
+            Image::new(2, "other/path".to_string(), now).into(),
+        ];
+        assert_eq!(body, json!({ "clusters": [cluster] }));
+    }
+
+    #[tokio::test]
+    async fn on_internal_error_return_internal_server_code() {
+        let mut mock_service = MockService::new();
+        mock_service
+            .expect_duplicate_clusters()
+            .returning(move |_d| Err(SimilarImagesServiceError::InternalError));
+        let app = app(mock_service);
+        let response = app.get("/duplicates").send().await;
+        assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        let body = response.bytes().await;
+        let body: Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body, json!({"error": "Internal error"}));
+    }
+}
@@ -2,17 +2,26 @@ use std::sync::Arc;
 
 use axum::{
     body::{self, BoxBody, StreamBody},
-    http::{Response, StatusCode},
+    http::{header, HeaderMap, Response, StatusCode},
     Json,
 };
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use serde_json::json;
-use tokio_util::io::ReaderStream;
 
 use crate::{
     error::YaissError,
-    services::images::ports::incoming::query_image_service::{
-        QueryImageService, QueryImageServiceError,
+    services::images::{
+        domain::{
+            content_format::{ContentFormat, DEFAULT_JPEG_QUALITY},
+            rendition::RenditionSize,
+        },
+        ports::{
+            incoming::{
+                query_image_service::{QueryImageService, QueryImageServiceError},
+                rendition_service::{RenditionService, RenditionServiceError},
+            },
+            outgoing::blob_store_port::DynBlobStore,
+        },
     },
 };
 
@@ -22,47 +31,170 @@ pub struct ImageJson {
     updated_on: String,
 }
 
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ContentQuery {
+    pub size: Option<String>,
+    pub format: Option<String>,
+    pub quality: Option<u8>,
+}
+
+/// Picks the response encoding: an explicit `format` query parameter wins,
+/// otherwise the highest weighted supported media type of the `Accept`
+/// header. Without either the stored QOI file is returned.
+fn negotiate_format(
+    content_query: &ContentQuery,
+    accept: Option<&str>,
+) -> Result<ContentFormat, (StatusCode, String)> {
+    let quality = content_query.quality.unwrap_or(DEFAULT_JPEG_QUALITY);
+    if !(1..=100).contains(&quality) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "Quality must be between 1 and 100".to_string(),
+        ));
+    }
+    if let Some(name) = &content_query.format {
+        return ContentFormat::from_name(name, quality).ok_or((
+            StatusCode::NOT_ACCEPTABLE,
+            format!("Unsupported format: {}", name),
+        ));
+    }
+    let accept = match accept.map(str::trim) {
+        None | Some("") => return Ok(ContentFormat::Original),
+        Some(accept) => accept,
+    };
+    let mut ranges = accept
+        .split(',')
+        .map(|range| {
+            let mut parts = range.split(';').map(str::trim);
+            let mime = parts.next().unwrap_or_default();
+            let weight = parts
+                .find_map(|param| param.strip_prefix("q="))
+                .and_then(|weight| weight.parse::<f32>().ok())
+                .unwrap_or(1.0);
+            (mime, weight)
+        })
+        .filter(|(_, weight)| *weight > 0.0)
+        .collect::<Vec<(&str, f32)>>();
+    ranges.sort_by(|a, b| b.1.total_cmp(&a.1));
+    ranges
+        .into_iter()
+        .find_map(|(mime, _)| ContentFormat::from_mime(mime, quality))
+        .ok_or((
+            StatusCode::NOT_ACCEPTABLE,
+            format!("No acceptable format in: {}", accept),
+        ))
+}
+
 pub(crate) type DynQueryImageService = Arc<dyn QueryImageService + Sync + Send>;
+pub(crate) type DynRenditionService = Arc<dyn RenditionService + Sync + Send>;
+
+#[derive(Clone)]
+pub struct ImageContentState {
+    pub(crate) query_image_service: DynQueryImageService,
+    pub(crate) rendition_service: DynRenditionService,
+    pub(crate) blob_store: DynBlobStore,
+}
+
+fn error_response(code: StatusCode, message: String) -> Result<Response<BoxBody>, YaissError> {
+    tracing::error!("{}", message);
+    Response::builder()
+        .status(code)
+        .header(axum::http::header::CONTENT_TYPE, "application/json")
+        .body(body::boxed(
+            Json(json!({
+                "error": message,
+            }))
+            .to_string(),
+        ))
+        .map_err(|e| e.into())
+}
+
 pub async fn get_image_content_handler(
-    axum::extract::State(service): axum::extract::State<DynQueryImageService>,
+    axum::extract::State(state): axum::extract::State<ImageContentState>,
     identifier: axum::extract::Path<i64>,
+    content_query: Option<axum::extract::Query<ContentQuery>>,
+    headers: HeaderMap,
+) -> Result<Response<BoxBody>, YaissError> {
+    let content_query = content_query.map(|query| query.0).unwrap_or_default();
+    image_content_response(&state, identifier.0, content_query, &headers).await
+}
+
+/// Streams the stored file, or the rendition and encoding `content_query`
+/// and the `Accept` header ask for.
+pub(crate) async fn image_content_response(
+    state: &ImageContentState,
+    image_id: i64,
+    content_query: ContentQuery,
+    headers: &HeaderMap,
 ) -> Result<Response<BoxBody>, YaissError> {
-    let service = service.clone();
-    let builder = Response::builder();
-    let builder = match service.query_image(identifier.0).await {
+    let accept = headers
+        .get(header::ACCEPT)
+        .and_then(|accept| accept.to_str().ok());
+    let format = match negotiate_format(&content_query, accept) {
+        Ok(format) => format,
+        Err((code, message)) => return error_response(code, message),
+    };
+    let size = match content_query.size {
+        None => None,
+        Some(size) => match size.parse::<RenditionSize>() {
+            Ok(size) => Some(size),
+            Err(message) => return error_response(StatusCode::BAD_REQUEST, message),
+        },
+    };
+    let path = match size {
+        None => match state.query_image_service.query_image(image_id).await {
+            Ok(image) => image.path().to_string(),
+            Err(e) => {
+                let code = if e == QueryImageServiceError::ImageNotFound {
+                    StatusCode::NOT_FOUND
+                } else {
+                    StatusCode::INTERNAL_SERVER_ERROR
+                };
+                return error_response(code, e.to_string());
+            }
+        },
+        Some(size) => match state
+            .rendition_service
+            .query_rendition(image_id, size)
+            .await
+        {
+            Ok(rendition) => rendition.path().to_string(),
+            Err(e) => {
+                let code = if e == RenditionServiceError::ImageNotFound {
+                    StatusCode::NOT_FOUND
+                } else {
+                    StatusCode::INTERNAL_SERVER_ERROR
+                };
+                return error_response(code, e.to_string());
+            }
+        },
+    };
+    let builder = Response::builder()
+        .status(StatusCode::OK)
+        .header(header::CONTENT_TYPE, format.mime())
+        .header(header::VARY, "Accept");
+    if format == ContentFormat::Original {
+        let stream = state.blob_store.get(&path).await?;
+        let body = StreamBody::new(stream);
+        return builder.body(body::boxed(body)).map_err(|e| e.into());
+    }
+    let qoi = state.blob_store.read(&path).await?;
+    let bytes = match tokio::task::spawn_blocking(move || format.transcode(qoi)).await? {
+        Ok(bytes) => bytes,
         Err(e) => {
-            let message = e.to_string();
-            tracing::error!("{}", message);
-            let code = if e == QueryImageServiceError::ImageNotFound {
-                StatusCode::NOT_FOUND
-            } else {
-                StatusCode::INTERNAL_SERVER_ERROR
-            };
-            builder
-                .status(code)
-                .header(axum::http::header::CONTENT_TYPE, "application/json")
-                .body(body::boxed(
-                    Json(json!({
-                        "error": message,
-                    }))
-                    .to_string(),
-                ))
-        }
-        Ok(image) => {
-            let file = tokio::fs::File::open(image.path()).await?;
-            let stream = ReaderStream::new(file);
-            let body = StreamBody::new(stream);
-            builder
-                .status(StatusCode::OK)
-                .header(axum::http::header::CONTENT_TYPE, "image/qoi")
-                .body(body::boxed(body))
+            return error_response(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Error transcoding {}: {}", path, e),
+            )
         }
     };
-    builder.map_err(|e| e.into())
+    builder
+        .body(body::boxed(body::Full::from(bytes)))
+        .map_err(|e| e.into())
 }
 
 #[cfg(test)]
-mod tests {
+pub(crate) mod tests {
 
     use std::sync::Arc;
 
@@ -75,11 +207,21 @@ mod tests {
     use serde_json::{json, Value};
 
     use crate::{
+        data_storage::blobs::fs_blob_store::FsBlobStore,
         services::images::{
-            domain::image::Image,
-            ports::incoming::query_image_service::{QueryImageService, QueryImageServiceError},
+            domain::{
+                content_format::ContentFormat,
+                image::Image,
+                rendition::{Rendition, RenditionSize},
+            },
+            ports::incoming::{
+                query_image_service::{QueryImageService, QueryImageServiceError},
+                rendition_service::{RenditionService, RenditionServiceError},
+            },
+        },
+        web::images::get_image_content_handler::{
+            self, negotiate_format, ContentQuery, ImageContentState,
         },
-        web::images::get_image_content_handler::{self},
     };
 
     mock! {
@@ -90,15 +232,32 @@ mod tests {
         }
     }
 
+    mock! {
+        pub Renditions {}
+        #[async_trait]
+        impl RenditionService for Renditions {
+            async fn query_rendition(&self, index: i64, size: RenditionSize) -> Result<Rendition, RenditionServiceError>;
+        }
+    }
+
     pub fn app(service: MockService) -> TestClient {
-        let query_image_service =
-            Arc::new(service) as get_image_content_handler::DynQueryImageService;
+        app_with_renditions(service, MockRenditions::new())
+    }
+
+    pub fn app_with_renditions(service: MockService, renditions: MockRenditions) -> TestClient {
+        let state = ImageContentState {
+            query_image_service: Arc::new(service)
+                as get_image_content_handler::DynQueryImageService,
+            rendition_service: Arc::new(renditions)
+                as get_image_content_handler::DynRenditionService,
+            blob_store: Arc::new(FsBlobStore::new()),
+        };
         let router = Router::new()
             .route(
                 "/:identifier",
                 get(get_image_content_handler::get_image_content_handler),
             )
-            .with_state(query_image_service);
+            .with_state(state);
         TestClient::new(router)
     }
 
@@ -111,7 +270,12 @@ mod tests {
             .with(predicate::eq(1))
             .returning(move |_i| Ok(Image::new(1, "Cargo.toml".to_string(), now)));
         let app = app(mock_service);
-        let response = app.get("/1").send().await;
+        // The test client sends `*/*` unless told otherwise.
+        let response = app
+            .get("/1")
+            .header(axum::http::header::ACCEPT, "image/qoi")
+            .send()
+            .await;
         assert_eq!(response.status(), StatusCode::OK);
         let body = response.bytes().await;
         let e = tokio::fs::read("Cargo.toml".to_string()).await.unwrap();
@@ -151,4 +315,147 @@ mod tests {
         let body: Value = serde_json::from_slice(&body).unwrap();
         assert_eq!(body, json!({"error": "Image not found", }));
     }
+
+    #[tokio::test]
+    async fn on_rendition_requested_return_rendition() {
+        let mut mock_renditions = MockRenditions::new();
+        mock_renditions
+            .expect_query_rendition()
+            .with(predicate::eq(1), predicate::eq(RenditionSize::Thumb))
+            .returning(move |i, s| Ok(Rendition::new(i, s, "Cargo.toml".to_string())));
+        let app = app_with_renditions(MockService::new(), mock_renditions);
+        let response = app
+            .get("/1?size=thumb")
+            .header(axum::http::header::ACCEPT, "image/qoi")
+            .send()
+            .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response.bytes().await;
+        let e = tokio::fs::read("Cargo.toml".to_string()).await.unwrap();
+        assert_eq!(body.to_vec(), e);
+    }
+
+    #[tokio::test]
+    async fn on_unknown_size_return_bad_request_code() {
+        let app = app(MockService::new());
+        let response = app.get("/1?size=huge").send().await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let body = response.bytes().await;
+        let body: Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body, json!({"error": "Unknown rendition size: huge"}));
+    }
+
+    #[tokio::test]
+    async fn on_rendition_image_not_found_return_not_found_code() {
+        let mut mock_renditions = MockRenditions::new();
+        mock_renditions
+            .expect_query_rendition()
+            .returning(move |_i, _s| Err(RenditionServiceError::ImageNotFound));
+        let app = app_with_renditions(MockService::new(), mock_renditions);
+        let response = app.get("/1?size=preview").send().await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    fn qoi_file(name: &str) -> String {
+        let path = std::env::temp_dir().join(name);
+        let mut bytes = vec![];
+        image::DynamicImage::new_rgb8(3, 2)
+            .write_to(
+                &mut std::io::Cursor::new(&mut bytes),
+                image::ImageOutputFormat::Qoi,
+            )
+            .unwrap();
+        std::fs::write(&path, bytes).unwrap();
+        path.display().to_string()
+    }
+
+    fn service_for(path: String) -> MockService {
+        let mut mock_service = MockService::new();
+        mock_service
+            .expect_query_image()
+            .returning(move |i| Ok(Image::new(i, path.clone(), Utc::now())));
+        mock_service
+    }
+
+    #[tokio::test]
+    async fn on_format_query_return_transcoded_content() {
+        let path = qoi_file("yaiss_content_format.qoi");
+        let app = app(service_for(path.clone()));
+        let response = app.get("/1?format=png").send().await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()["content-type"], "image/png");
+        let body = response.bytes().await;
+        assert_eq!(image::guess_format(&body).unwrap(), image::ImageFormat::Png);
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn on_accept_header_return_best_supported_format() {
+        let path = qoi_file("yaiss_content_accept.qoi");
+        let app = app(service_for(path.clone()));
+        let response = app
+            .get("/1?quality=60")
+            .header(
+                axum::http::header::ACCEPT,
+                "image/avif;q=1.0, image/jpeg;q=0.8, image/webp;q=0.9",
+            )
+            .send()
+            .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()["content-type"], "image/webp");
+        let body = response.bytes().await;
+        assert_eq!(
+            image::guess_format(&body).unwrap(),
+            image::ImageFormat::WebP
+        );
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn without_accept_header_negotiate_original() {
+        let query = ContentQuery::default();
+        assert_eq!(negotiate_format(&query, None), Ok(ContentFormat::Original));
+        assert_eq!(
+            negotiate_format(&query, Some("*/*")),
+            Ok(ContentFormat::Png)
+        );
+    }
+
+    #[tokio::test]
+    async fn on_wildcard_accept_return_png() {
+        let path = qoi_file("yaiss_content_wildcard.qoi");
+        let app = app(service_for(path.clone()));
+        let response = app
+            .get("/1")
+            .header(axum::http::header::ACCEPT, "text/html, */*;q=0.8")
+            .send()
+            .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()["content-type"], "image/png");
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn on_no_acceptable_format_return_not_acceptable_code() {
+        let app = app(MockService::new());
+        let response = app
+            .get("/1")
+            .header(axum::http::header::ACCEPT, "image/avif, text/html")
+            .send()
+            .await;
+        assert_eq!(response.status(), StatusCode::NOT_ACCEPTABLE);
+
+        let response = app.get("/1?format=gif").send().await;
+        assert_eq!(response.status(), StatusCode::NOT_ACCEPTABLE);
+        let body = response.bytes().await;
+        let body: Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body, json!({"error": "Unsupported format: gif"}));
+    }
+
+    #[tokio::test]
+    async fn on_invalid_quality_return_bad_request_code() {
+        let app = app(MockService::new());
+        let response = app.get("/1?format=jpeg&quality=0").send().await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
 }
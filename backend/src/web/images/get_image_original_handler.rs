@@ -0,0 +1,167 @@
+use axum::{
+    body::{self, BoxBody, StreamBody},
+    http::{header, Response, StatusCode},
+    Json,
+};
+use image::ImageFormat;
+use serde_json::json;
+
+use crate::{
+    error::YaissError,
+    services::images::ports::{
+        incoming::query_image_service::QueryImageServiceError,
+        outgoing::blob_store_port::DynBlobStore,
+    },
+};
+
+use super::query_image_handler::DynQueryImageService;
+
+#[derive(Clone)]
+pub struct ImageOriginalState {
+    pub(crate) query_image_service: DynQueryImageService,
+    pub(crate) blob_store: DynBlobStore,
+}
+
+fn error_response(code: StatusCode, message: String) -> Result<Response<BoxBody>, YaissError> {
+    tracing::error!("{}", message);
+    Response::builder()
+        .status(code)
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(body::boxed(
+            Json(json!({
+                "error": message,
+            }))
+            .to_string(),
+        ))
+        .map_err(|e| e.into())
+}
+
+/// Streams the bytes exactly as they were uploaded, with the MIME type of
+/// the format detected at upload time.
+pub async fn get_image_original_handler(
+    axum::extract::State(state): axum::extract::State<ImageOriginalState>,
+    identifier: axum::extract::Path<i64>,
+) -> Result<Response<BoxBody>, YaissError> {
+    let image = match state.query_image_service.query_image(identifier.0).await {
+        Ok(image) => image,
+        Err(e) => {
+            let code = if e == QueryImageServiceError::ImageNotFound {
+                StatusCode::NOT_FOUND
+            } else {
+                StatusCode::INTERNAL_SERVER_ERROR
+            };
+            return error_response(code, e.to_string());
+        }
+    };
+    let path = match image.original_path() {
+        Some(path) => path,
+        None => {
+            return error_response(
+                StatusCode::NOT_FOUND,
+                format!("Original not kept for image {}", image.id()),
+            )
+        }
+    };
+    let mime = ImageFormat::from_path(path)
+        .map(|format| format.to_mime_type())
+        .unwrap_or("application/octet-stream");
+    let stream = state.blob_store.get(path).await?;
+    Response::builder()
+        .status(StatusCode::OK)
+        .header(header::CONTENT_TYPE, mime)
+        .body(body::boxed(StreamBody::new(stream)))
+        .map_err(|e| e.into())
+}
+
+#[cfg(test)]
+mod tests {
+
+    use std::{sync::Arc, time::SystemTime};
+
+    use async_trait::async_trait;
+    use axum::{routing::get, Router};
+    use axum_test_helper::TestClient;
+    use chrono::Utc;
+    use mockall::mock;
+    use reqwest::StatusCode;
+    use serde_json::{json, Value};
+
+    use crate::{
+        data_storage::blobs::memory_blob_store::MemoryBlobStore,
+        services::images::{
+            domain::image::Image,
+            ports::incoming::query_image_service::{QueryImageService, QueryImageServiceError},
+        },
+        web::images::{
+            get_image_original_handler::{self, ImageOriginalState},
+            query_image_handler::DynQueryImageService,
+        },
+    };
+
+    mock! {
+        pub Service {}
+        #[async_trait]
+        impl QueryImageService for Service {
+            async fn query_image(&self, index: i64) -> Result<Image, QueryImageServiceError>;
+        }
+    }
+
+    pub fn app(service: MockService, blob_store: MemoryBlobStore) -> TestClient {
+        let state = ImageOriginalState {
+            query_image_service: Arc::new(service) as DynQueryImageService,
+            blob_store: Arc::new(blob_store),
+        };
+        let router = Router::new()
+            .route(
+                "/:identifier/original",
+                get(get_image_original_handler::get_image_original_handler),
+            )
+            .with_state(state);
+        TestClient::new(router)
+    }
+
+    #[tokio::test]
+    async fn on_original_kept_return_original_bytes() {
+        let blob_store = MemoryBlobStore::new();
+        blob_store.insert(
+            "data/originals/1.jpg",
+            &b"not really a jpeg"[..],
+            SystemTime::now(),
+        );
+        let mut mock_service = MockService::new();
+        mock_service.expect_query_image().returning(move |i| {
+            Ok(Image::new(i, "some/path".to_string(), Utc::now())
+                .with_original_path("data/originals/1.jpg".to_string()))
+        });
+        let app = app(mock_service, blob_store);
+        let response = app.get("/1/original").send().await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()["content-type"], "image/jpeg");
+        assert_eq!(response.bytes().await.as_ref(), b"not really a jpeg");
+    }
+
+    #[tokio::test]
+    async fn on_original_not_kept_return_not_found_code() {
+        let mut mock_service = MockService::new();
+        mock_service
+            .expect_query_image()
+            .returning(|i| Ok(Image::new(i, "some/path".to_string(), Utc::now())));
+        let app = app(mock_service, MemoryBlobStore::new());
+        let response = app.get("/1/original").send().await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        let body = response.bytes().await;
+        let body: Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body, json!({"error": "Original not kept for image 1"}));
+    }
+
+    #[tokio::test]
+    async fn on_image_not_found_return_not_found_code() {
+        let mut mock_service = MockService::new();
+        mock_service
+            .expect_query_image()
+            .returning(|_i| Err(QueryImageServiceError::ImageNotFound));
+        let app = app(mock_service, MemoryBlobStore::new());
+        let response = app.get("/1/original").send().await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+}
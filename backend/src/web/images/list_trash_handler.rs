@@ -0,0 +1,151 @@
+use std::sync::Arc;
+
+use axum::{
+    body::{self, Body},
+    extract::{rejection::QueryRejection, Query},
+    http::{Response, StatusCode},
+    Json,
+};
+use serde_json::json;
+use tracing::error;
+
+use crate::{
+    error::YaissError,
+    services::images::ports::incoming::trash_service::{TrashService, TrashServiceError},
+};
+
+use super::batch_query_image_handler::{bad_request, ImageJson, Pagination};
+
+pub(crate) type DynTrashService = Arc<dyn TrashService + Send + Sync>;
+
+pub(crate) fn trash_error_response(e: TrashServiceError) -> Result<Response<Body>, YaissError> {
+    let message = e.to_string();
+    error!("{}", message);
+    let status = match e {
+        TrashServiceError::ImageNotFound => StatusCode::NOT_FOUND,
+        TrashServiceError::InvalidRequest => StatusCode::BAD_REQUEST,
+        TrashServiceError::TooManyImagesRequested => StatusCode::BAD_REQUEST,
+        TrashServiceError::InternalError => StatusCode::INTERNAL_SERVER_ERROR,
+    };
+    Response::builder()
+        .status(status)
+        .header(axum::http::header::CONTENT_TYPE, "application/json")
+        .body(body::Body::from(
+            Json(json!({
+                "error": message,
+            }))
+            .to_string(),
+        ))
+        .map_err(|e| e.into())
+}
+
+/// Trashed images, most recently deleted first. Only `count` and `offset`
+/// of the listing parameters apply.
+pub async fn list_trash_handler(
+    axum::extract::State(service): axum::extract::State<DynTrashService>,
+    pagination: Result<Query<Pagination>, QueryRejection>,
+) -> Result<Response<Body>, YaissError> {
+    let pagination = match pagination {
+        Ok(Query(pagination)) => pagination,
+        Err(rejection) => return bad_request(rejection.body_text()),
+    };
+    if pagination.sort.is_some() || pagination.tags.is_some() || pagination.cursor.is_some() {
+        return bad_request("Trash listings only support count and offset".to_string());
+    }
+    match service.trash(pagination.count, pagination.offset).await {
+        Ok(images) => {
+            let images = images
+                .into_iter()
+                .map(ImageJson::from)
+                .collect::<Vec<ImageJson>>();
+            Response::builder()
+                .status(StatusCode::OK)
+                .header(axum::http::header::CONTENT_TYPE, "application/json")
+                .body(body::Body::from(
+                    Json(json!({ "images": images })).to_string(),
+                ))
+                .map_err(|e| e.into())
+        }
+        Err(e) => trash_error_response(e),
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+
+    use std::{sync::Arc, time::Duration};
+
+    use async_trait::async_trait;
+    use axum::{routing::get, Router};
+    use axum_test_helper::TestClient;
+    use chrono::Utc;
+    use mockall::{mock, predicate};
+    use reqwest::StatusCode;
+    use serde_json::{json, Value};
+
+    use crate::{
+        services::images::{
+            domain::image::Image,
+            ports::incoming::trash_service::{TrashService, TrashServiceError},
+        },
+        web::images::{
+            batch_query_image_handler::ImageJson,
+            list_trash_handler::{self, DynTrashService},
+        },
+    };
+
+    mock! {
+        pub Service {}
+        #[async_trait]
+        impl TrashService for Service {
+            async fn trash(&self, count: i64, offset: i64) -> Result<Vec<Image>, TrashServiceError>;
+            async fn restore_image(&self, index: i64) -> Result<(), TrashServiceError>;
+            async fn purge(&self, retention: Duration) -> Result<(), TrashServiceError>;
+        }
+    }
+
+    pub fn app(service: MockService) -> TestClient {
+        let trash_service = Arc::new(service) as DynTrashService;
+        let router = Router::new()
+            .route("/trash", get(list_trash_handler::list_trash_handler))
+            .with_state(trash_service);
+        TestClient::new(router)
+    }
+
+    #[tokio::test]
+    async fn on_trash_return_deleted_images() {
+        let now = Utc::now();
+        let image = Image::new(3, "some/path".to_string(), now).with_deleted_at(now);
+        let returned = image.clone();
+        let mut mock_service = MockService::new();
+        mock_service
+            .expect_trash()
+            .with(predicate::eq(10), predicate::eq(0))
+            .returning(move |_c, _o| Ok(vec![returned.clone()]));
+        let app = app(mock_service);
+        let response = app.get("/trash?count=10").send().await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response.bytes().await;
+        let body: Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body, json!({ "images": [ImageJson::from(image)] }));
+        assert_eq!(body["images"][0]["deleted_at"], json!(now.to_string()));
+    }
+
+    #[tokio::test]
+    async fn on_unsupported_params_return_bad_request_code() {
+        let app = app(MockService::new());
+        let response = app.get("/trash?sort=captured_on").send().await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn on_too_many_images_return_bad_request_code() {
+        let mut mock_service = MockService::new();
+        mock_service
+            .expect_trash()
+            .returning(|_c, _o| Err(TrashServiceError::TooManyImagesRequested));
+        let app = app(mock_service);
+        let response = app.get("/trash?count=51").send().await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+}
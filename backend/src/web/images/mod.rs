@@ -7,70 +7,197 @@ use axum::{
 };
 
 use crate::{
-    data_storage::images::images_sqlite_ds::ImagesSqliteDS,
     services::images::{
         batch_delete_image::BatchDeleteImage, batch_query_image_service::BatchQueryImage,
-        delete_image::DeleteImage, query_image_service::QueryImage, upload_images::UploadImages,
+        delete_image::DeleteImage, domain::api_key::Scope, image_renditions::ImageRenditions,
+        image_tags::ImageTags, image_trash::ImageTrash, query_image_service::QueryImage,
+        search_images::SearchImages, similar_images::SimilarImages, upload_images::UploadImages,
     },
     state::State,
+    web::{auth::scoped, shares, tags::DynTagsService},
 };
 
 use self::{
     batch_query_image_handler::DynBatchQueryImageService,
-    delete_image_handler::DynDeleteImagesService, query_image_handler::DynQueryImageService,
+    delete_image_handler::DynDeleteImagesService,
+    get_image_content_handler::{DynRenditionService, ImageContentState},
+    get_image_original_handler::ImageOriginalState,
+    list_trash_handler::DynTrashService,
+    query_image_handler::DynQueryImageService,
+    search_images_handler::DynSearchImagesService,
+    similar_images_handler::DynSimilarImagesService,
 };
 
 pub mod batch_delete_image_handler;
 pub mod batch_query_image_handler;
 pub mod delete_image_handler;
+pub mod duplicate_clusters_handler;
 pub mod get_image_content_handler;
+pub mod get_image_original_handler;
+pub mod list_trash_handler;
 pub mod query_image_handler;
+pub mod restore_image_handler;
+pub mod search_images_handler;
+pub mod similar_images_handler;
+pub mod tag_image_handler;
+pub mod untag_image_handler;
 pub mod upload_images_handler;
 
 pub fn router(state: State) -> Router<(), Body> {
-    let storage = ImagesSqliteDS::new(state.pool());
-    let batch_delete_image_service = Arc::new(BatchDeleteImage::new(storage))
+    let storage = state.storage();
+    let batch_delete_image_service = Arc::new(BatchDeleteImage::new(storage, state.blob_store()))
         as batch_delete_image_handler::DynBatchDeleteImageService;
-    let storage = ImagesSqliteDS::new(state.pool());
-    let upload_images_service = Arc::new(UploadImages::new(
+    let storage = state.storage();
+    let mut upload_images = UploadImages::new(
         storage,
+        state.blob_store(),
         state.images_base_path().to_string(),
-    )) as upload_images_handler::DynUploadImagesService;
-    let storage = ImagesSqliteDS::new(state.pool());
-    let delete_image_service = Arc::new(DeleteImage::new(storage)) as DynDeleteImagesService;
-    let storage = ImagesSqliteDS::new(state.pool());
+    )
+    .with_layout(state.storage_layout())
+    .with_metrics(state.metrics());
+    if let Some(originals_path) = state.images_originals_path() {
+        upload_images = upload_images.with_originals_path(originals_path.to_string());
+    }
+    let upload_images_service =
+        Arc::new(upload_images) as upload_images_handler::DynUploadImagesService;
+    let storage = state.storage();
+    let delete_image_service =
+        Arc::new(DeleteImage::new(storage, state.blob_store())) as DynDeleteImagesService;
+    let storage = state.storage();
     let query_image_service = Arc::new(QueryImage::new(storage)) as DynQueryImageService;
-    let storage = ImagesSqliteDS::new(state.pool());
+    let storage = state.storage();
+    let rendition_service =
+        Arc::new(ImageRenditions::new(storage, state.blob_store())) as DynRenditionService;
+    let image_content_state = ImageContentState {
+        query_image_service: query_image_service.clone(),
+        rendition_service,
+        blob_store: state.blob_store(),
+    };
+    let image_original_state = ImageOriginalState {
+        query_image_service: query_image_service.clone(),
+        blob_store: state.blob_store(),
+    };
+    let storage = state.storage();
     let batch_query_image_service =
         Arc::new(BatchQueryImage::new(storage)) as DynBatchQueryImageService;
-    let images_routes = Router::new()
-        .route("/", post(upload_images_handler::upload_images_handler))
+    let storage = state.storage();
+    let similar_images_service = Arc::new(SimilarImages::new(storage)) as DynSimilarImagesService;
+    let storage = state.storage();
+    let tags_service = Arc::new(ImageTags::new(storage)) as DynTagsService;
+    let storage = state.storage();
+    let search_images_service = Arc::new(SearchImages::new(storage)) as DynSearchImagesService;
+    let storage = state.storage();
+    let trash_service = Arc::new(ImageTrash::new(storage, state.blob_store())) as DynTrashService;
+    let shares_service = shares::shares_service(&state);
+    let images_routes: Router<(), Body> = Router::new()
+        .route(
+            "/",
+            scoped(
+                Scope::Upload,
+                post(upload_images_handler::upload_images_handler),
+            ),
+        )
         .with_state(upload_images_service)
         .route(
             "/batch_delete",
-            post(batch_delete_image_handler::batch_delete_image_handler),
+            scoped(
+                Scope::Delete,
+                post(batch_delete_image_handler::batch_delete_image_handler),
+            ),
         )
         .with_state(batch_delete_image_service)
         .route(
             "/:identifier",
-            get(query_image_handler::query_image_handler),
+            scoped(Scope::Read, get(query_image_handler::query_image_handler)),
+        )
+        .with_state(query_image_service)
+        .route(
+            "/:identifier/original",
+            scoped(
+                Scope::Read,
+                get(get_image_original_handler::get_image_original_handler),
+            ),
         )
-        .with_state(query_image_service.clone())
+        .with_state(image_original_state)
         .route(
             "/content/:identifier",
-            get(get_image_content_handler::get_image_content_handler),
+            scoped(
+                Scope::Read,
+                get(get_image_content_handler::get_image_content_handler),
+            ),
         )
-        .with_state(query_image_service)
+        .with_state(image_content_state)
         .route(
             "/",
-            get(batch_query_image_handler::batch_query_image_handler),
+            scoped(
+                Scope::Read,
+                get(batch_query_image_handler::batch_query_image_handler),
+            ),
         )
         .with_state(batch_query_image_service)
         .route(
             "/:identifier",
-            delete(delete_image_handler::delete_image_handler),
+            scoped(
+                Scope::Delete,
+                delete(delete_image_handler::delete_image_handler),
+            ),
+        )
+        .with_state(delete_image_service)
+        .route(
+            "/:identifier/similar",
+            scoped(
+                Scope::Read,
+                get(similar_images_handler::similar_images_handler),
+            ),
+        )
+        .with_state(similar_images_service.clone())
+        .route(
+            "/duplicates",
+            scoped(
+                Scope::Read,
+                get(duplicate_clusters_handler::duplicate_clusters_handler),
+            ),
+        )
+        .with_state(similar_images_service)
+        .route(
+            "/:identifier/tags",
+            scoped(Scope::Upload, post(tag_image_handler::tag_image_handler)),
+        )
+        .route(
+            "/:identifier/tags/:tag",
+            scoped(
+                Scope::Upload,
+                delete(untag_image_handler::untag_image_handler),
+            ),
+        )
+        .with_state(tags_service)
+        .route(
+            "/search",
+            scoped(
+                Scope::Read,
+                get(search_images_handler::search_images_handler),
+            ),
+        )
+        .with_state(search_images_service)
+        .route(
+            "/trash",
+            scoped(Scope::Read, get(list_trash_handler::list_trash_handler)),
+        )
+        .route(
+            "/:identifier/restore",
+            scoped(
+                Scope::Upload,
+                post(restore_image_handler::restore_image_handler),
+            ),
+        )
+        .with_state(trash_service)
+        .route(
+            "/:identifier/share",
+            scoped(
+                Scope::Upload,
+                post(shares::share_image_handler::share_image_handler),
+            ),
         )
-        .with_state(delete_image_service);
-    let images_router = Router::new().nest("/images", images_routes);
-    Router::new().nest("/api/v1", images_router)
+        .with_state(shares_service);
+    images_routes
 }
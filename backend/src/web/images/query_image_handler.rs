@@ -12,7 +12,7 @@ use tracing::error;
 use crate::{
     error::YaissError,
     services::images::{
-        domain::image::Image,
+        domain::{exif::ExifData, image::Image},
         ports::incoming::query_image_service::{QueryImageService, QueryImageServiceError},
     },
 };
@@ -21,13 +21,73 @@ use crate::{
 pub struct ImageJson {
     id: i64,
     updated_on: String,
+    created_on: String,
+    width: Option<u32>,
+    height: Option<u32>,
+    color_type: Option<String>,
+    bit_depth: Option<u8>,
+    original_format: Option<String>,
+    original_size: Option<u64>,
+    stored_size: Option<u64>,
+    filename: Option<String>,
+    exif: Option<ExifJson>,
+    status: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ExifJson {
+    captured_on: Option<String>,
+    make: Option<String>,
+    model: Option<String>,
+    lens: Option<String>,
+    exposure_time: Option<String>,
+    f_number: Option<f64>,
+    iso: Option<u32>,
+    focal_length: Option<f64>,
+    latitude: Option<f64>,
+    longitude: Option<f64>,
+    altitude: Option<f64>,
+    orientation: Option<u16>,
+}
+
+impl From<&ExifData> for ExifJson {
+    fn from(value: &ExifData) -> Self {
+        Self {
+            captured_on: value
+                .captured_on()
+                .map(|captured_on| captured_on.to_string()),
+            make: value.make().map(str::to_string),
+            model: value.model().map(str::to_string),
+            lens: value.lens().map(str::to_string),
+            exposure_time: value.exposure_time().map(str::to_string),
+            f_number: value.f_number(),
+            iso: value.iso(),
+            focal_length: value.focal_length(),
+            latitude: value.latitude(),
+            longitude: value.longitude(),
+            altitude: value.altitude(),
+            orientation: value.orientation(),
+        }
+    }
 }
 
 impl From<Image> for ImageJson {
     fn from(value: Image) -> Self {
+        let metadata = value.metadata();
         Self {
             id: value.id(),
             updated_on: value.updated_on().to_string(),
+            created_on: value.created_on().to_string(),
+            width: metadata.map(|metadata| metadata.width()),
+            height: metadata.map(|metadata| metadata.height()),
+            color_type: metadata.map(|metadata| metadata.color_type().to_string()),
+            bit_depth: metadata.map(|metadata| metadata.bit_depth()),
+            original_format: metadata.map(|metadata| metadata.original_format().to_string()),
+            original_size: metadata.map(|metadata| metadata.original_size()),
+            stored_size: metadata.map(|metadata| metadata.stored_size()),
+            filename: metadata.and_then(|metadata| metadata.filename().map(str::to_string)),
+            exif: value.exif().map(ExifJson::from),
+            status: value.status().to_string(),
         }
     }
 }
@@ -75,14 +135,14 @@ mod tests {
     use async_trait::async_trait;
     use axum::{body::Body, routing::get, Router};
     use axum_test_helper::TestClient;
-    use chrono::Utc;
+    use chrono::{DateTime, Utc};
     use mockall::{mock, predicate};
     use reqwest::StatusCode;
     use serde_json::{json, Value};
 
     use crate::{
         services::images::{
-            domain::image::Image,
+            domain::{exif::ExifData, image::Image, image_metadata::ImageMetadata},
             ports::incoming::query_image_service::{QueryImageService, QueryImageServiceError},
         },
         web::images::query_image_handler::{self, ImageJson},
@@ -125,6 +185,84 @@ mod tests {
         assert_eq!(body, json);
     }
 
+    #[tokio::test]
+    async fn on_image_with_metadata_return_all_fields() {
+        let now = "2023-08-26T11:00:00Z".parse::<DateTime<Utc>>().unwrap();
+        let mut mock_service = MockService::new();
+        mock_service.expect_query_image().returning(move |_i| {
+            let metadata = ImageMetadata::new(
+                640,
+                480,
+                "Rgb8".to_string(),
+                8,
+                "jpeg".to_string(),
+                300,
+                900,
+            )
+            .with_filename("cat.jpg".to_string());
+            Ok(Image::new(1, "some/path".to_string(), now).with_metadata(metadata))
+        });
+        let app = app(mock_service);
+        let response = app.get("/1").send().await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response.bytes().await;
+        let body: Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(
+            body,
+            json!({
+                "id": 1,
+                "updated_on": "2023-08-26 11:00:00 UTC",
+                "created_on": "2023-08-26 11:00:00 UTC",
+                "width": 640,
+                "height": 480,
+                "color_type": "Rgb8",
+                "bit_depth": 8,
+                "original_format": "jpeg",
+                "original_size": 300,
+                "stored_size": 900,
+                "filename": "cat.jpg",
+                "exif": null,
+                "status": "ok"
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn on_image_with_exif_return_exif() {
+        let now = Utc::now();
+        let captured_on = "2023-08-26T11:30:00Z".parse::<DateTime<Utc>>().unwrap();
+        let mut mock_service = MockService::new();
+        mock_service.expect_query_image().returning(move |_i| {
+            let exif = ExifData::default()
+                .with_captured_on(captured_on)
+                .with_camera(Some("Yaiss".to_string()), None)
+                .with_orientation(6);
+            Ok(Image::new(1, "some/path".to_string(), now).with_exif(exif))
+        });
+        let app = app(mock_service);
+        let response = app.get("/1").send().await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response.bytes().await;
+        let body: Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(
+            body["exif"],
+            json!({
+                "captured_on": "2023-08-26 11:30:00 UTC",
+                "make": "Yaiss",
+                "model": null,
+                "lens": null,
+                "exposure_time": null,
+                "f_number": null,
+                "iso": null,
+                "focal_length": null,
+                "latitude": null,
+                "longitude": null,
+                "altitude": null,
+                "orientation": 6
+            })
+        );
+    }
+
     #[tokio::test]
     async fn on_internal_error_return_internal_server_code() {
         let mut mock_service = MockService::new();
@@ -0,0 +1,79 @@
+use axum::{
+    body::Body,
+    http::{Response, StatusCode},
+};
+
+use crate::error::YaissError;
+
+use super::list_trash_handler::{trash_error_response, DynTrashService};
+
+pub async fn restore_image_handler(
+    axum::extract::State(service): axum::extract::State<DynTrashService>,
+    identifier: axum::extract::Path<i64>,
+) -> Result<Response<Body>, YaissError> {
+    match service.restore_image(identifier.0).await {
+        Ok(()) => Response::builder()
+            .status(StatusCode::OK)
+            .body(Body::empty())
+            .map_err(|e| e.into()),
+        Err(e) => trash_error_response(e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use std::sync::Arc;
+
+    use axum::{routing::post, Router};
+    use axum_test_helper::TestClient;
+    use mockall::predicate;
+    use reqwest::StatusCode;
+    use serde_json::{json, Value};
+
+    use crate::{
+        services::images::ports::incoming::trash_service::TrashServiceError,
+        web::images::{
+            list_trash_handler::{tests::MockService, DynTrashService},
+            restore_image_handler,
+        },
+    };
+
+    pub fn app(service: MockService) -> TestClient {
+        let trash_service = Arc::new(service) as DynTrashService;
+        let router = Router::new()
+            .route(
+                "/:identifier/restore",
+                post(restore_image_handler::restore_image_handler),
+            )
+            .with_state(trash_service);
+        TestClient::new(router)
+    }
+
+    #[tokio::test]
+    async fn on_image_restored_return_ok() {
+        let mut mock_service = MockService::new();
+        mock_service
+            .expect_restore_image()
+            .with(predicate::eq(1))
+            .times(1)
+            .returning(|_i| Ok(()));
+        let app = app(mock_service);
+        let response = app.post("/1/restore").send().await;
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn on_image_not_in_trash_return_not_found_code() {
+        let mut mock_service = MockService::new();
+        mock_service
+            .expect_restore_image()
+            .returning(|_i| Err(TrashServiceError::ImageNotFound));
+        let app = app(mock_service);
+        let response = app.post("/1/restore").send().await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        let body = response.bytes().await;
+        let body: Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body, json!({"error": "Image not found"}));
+    }
+}
@@ -0,0 +1,282 @@
+use std::sync::Arc;
+
+use axum::{
+    body::{self, Body},
+    extract::{rejection::QueryRejection, Query},
+    http::{Response, StatusCode},
+    Json,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tracing::error;
+
+use crate::{
+    error::YaissError,
+    services::images::{
+        domain::{
+            search::{SearchQuery, Shape},
+            sort_key::{SortDirection, SortKey},
+            tag::TagMatch,
+        },
+        ports::incoming::search_images_service::{SearchImagesService, SearchImagesServiceError},
+    },
+};
+
+use super::batch_query_image_handler::ImageJson;
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct SearchParams {
+    pub count: Option<i64>,
+    pub offset: Option<i64>,
+    /// RFC 3339 timestamps bounding the upload time.
+    pub created_after: Option<String>,
+    pub created_before: Option<String>,
+    /// RFC 3339 timestamps bounding the EXIF capture time.
+    pub captured_after: Option<String>,
+    pub captured_before: Option<String>,
+    pub min_width: Option<u32>,
+    pub max_width: Option<u32>,
+    pub min_height: Option<u32>,
+    pub max_height: Option<u32>,
+    pub orientation: Option<String>,
+    pub format: Option<String>,
+    /// Comma separated tags the images must carry.
+    pub tags: Option<String>,
+    #[serde(rename = "match")]
+    pub tag_match: Option<String>,
+    pub sort: Option<String>,
+    pub direction: Option<String>,
+}
+
+fn parse_time(name: &str, value: &Option<String>) -> Result<Option<DateTime<Utc>>, String> {
+    value
+        .as_deref()
+        .map(|value| {
+            DateTime::parse_from_rfc3339(value)
+                .map(|time| time.with_timezone(&Utc))
+                .map_err(|_| format!("Invalid {}: {}", name, value))
+        })
+        .transpose()
+}
+
+impl TryFrom<SearchParams> for SearchQuery {
+    type Error = String;
+
+    fn try_from(params: SearchParams) -> Result<Self, Self::Error> {
+        let defaults = SearchQuery::default();
+        Ok(SearchQuery {
+            created_after: parse_time("created_after", &params.created_after)?,
+            created_before: parse_time("created_before", &params.created_before)?,
+            captured_after: parse_time("captured_after", &params.captured_after)?,
+            captured_before: parse_time("captured_before", &params.captured_before)?,
+            min_width: params.min_width,
+            max_width: params.max_width,
+            min_height: params.min_height,
+            max_height: params.max_height,
+            shape: params
+                .orientation
+                .as_deref()
+                .map(str::parse::<Shape>)
+                .transpose()?,
+            original_format: params.format,
+            tags: params
+                .tags
+                .map(|tags| tags.split(',').map(str::to_string).collect())
+                .unwrap_or_default(),
+            tag_match: params
+                .tag_match
+                .as_deref()
+                .map(str::parse::<TagMatch>)
+                .transpose()?
+                .unwrap_or_default(),
+            sort: params
+                .sort
+                .as_deref()
+                .map(str::parse::<SortKey>)
+                .transpose()?
+                .unwrap_or_default(),
+            direction: params
+                .direction
+                .as_deref()
+                .map(str::parse::<SortDirection>)
+                .transpose()?
+                .unwrap_or_default(),
+            count: params.count.unwrap_or(defaults.count),
+            offset: params.offset.unwrap_or(defaults.offset),
+        })
+    }
+}
+
+pub(crate) type DynSearchImagesService = Arc<dyn SearchImagesService + Send + Sync>;
+pub async fn search_images_handler(
+    axum::extract::State(service): axum::extract::State<DynSearchImagesService>,
+    params: Result<Query<SearchParams>, QueryRejection>,
+) -> Result<Response<Body>, YaissError> {
+    let builder = Response::builder();
+    let query = params
+        .map_err(|rejection| rejection.body_text())
+        .and_then(|Query(params)| SearchQuery::try_from(params));
+    let query = match query {
+        Ok(query) => query,
+        Err(message) => {
+            error!("{}", message);
+            return builder
+                .status(StatusCode::BAD_REQUEST)
+                .body(body::Body::from(
+                    Json(json!({
+                        "error": message,
+                    }))
+                    .to_string(),
+                ))
+                .map_err(|e| e.into());
+        }
+    };
+    let builder = match service.search_images(query).await {
+        Ok(images) => {
+            let images = images
+                .into_iter()
+                .map(ImageJson::from)
+                .collect::<Vec<ImageJson>>();
+            let body = Json(json!({ "images": images })).to_string();
+            builder.status(StatusCode::OK).body(body::Body::from(body))
+        }
+        Err(e) => {
+            let message = e.to_string();
+            error!("{}", message);
+            let status = match e {
+                SearchImagesServiceError::InternalError => StatusCode::INTERNAL_SERVER_ERROR,
+                SearchImagesServiceError::TooManyImagesRequested => StatusCode::BAD_REQUEST,
+                SearchImagesServiceError::InvalidRequest(_) => StatusCode::BAD_REQUEST,
+            };
+            builder.status(status).body(body::Body::from(
+                Json(json!({
+                    "error": message,
+                }))
+                .to_string(),
+            ))
+        }
+    };
+    builder.map_err(|e| e.into())
+}
+
+#[cfg(test)]
+mod tests {
+
+    use std::sync::Arc;
+
+    use async_trait::async_trait;
+    use axum::{routing::get, Router};
+    use axum_test_helper::TestClient;
+    use chrono::{DateTime, Utc};
+    use mockall::{mock, predicate};
+    use reqwest::StatusCode;
+    use serde_json::{json, Value};
+
+    use crate::{
+        services::images::{
+            domain::{
+                image::Image,
+                search::{SearchQuery, Shape},
+                sort_key::{SortDirection, SortKey},
+            },
+            ports::incoming::search_images_service::{
+                SearchImagesService, SearchImagesServiceError,
+            },
+        },
+        web::images::{batch_query_image_handler::ImageJson, search_images_handler},
+    };
+
+    mock! {
+        pub Service {}
+        #[async_trait]
+        impl SearchImagesService for Service {
+            async fn search_images(&self, query: SearchQuery) -> Result<Vec<Image>, SearchImagesServiceError>;
+        }
+    }
+
+    pub fn app(service: MockService) -> TestClient {
+        let search_images_service =
+            Arc::new(service) as search_images_handler::DynSearchImagesService;
+        let router = Router::new()
+            .route("/search", get(search_images_handler::search_images_handler))
+            .with_state(search_images_service);
+        TestClient::new(router)
+    }
+
+    #[tokio::test]
+    async fn on_filters_search_with_query() {
+        let now = Utc::now();
+        let expected = SearchQuery {
+            created_after: Some("2023-01-01T00:00:00Z".parse::<DateTime<Utc>>().unwrap()),
+            min_width: Some(100),
+            shape: Some(Shape::Portrait),
+            original_format: Some("png".to_string()),
+            tags: vec!["beach".to_string(), "sunset".to_string()],
+            sort: SortKey::CreatedOn,
+            direction: SortDirection::Descending,
+            count: 10,
+            ..SearchQuery::default()
+        };
+        let mut mock_service = MockService::new();
+        mock_service
+            .expect_search_images()
+            .with(predicate::eq(expected))
+            .returning(move |_q| Ok(vec![Image::new(1, "some/path".to_string(), now)]));
+        let app = app(mock_service);
+        let response = app
+            .get("/search?count=10&created_after=2023-01-01T00:00:00Z&min_width=100&orientation=portrait&format=png&tags=beach,sunset&sort=created_on&direction=desc")
+            .send()
+            .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response.bytes().await;
+        let body: Value = serde_json::from_slice(&body).unwrap();
+        let image_json: ImageJson = Image::new(1, "some/path".to_string(), now).into();
+        assert_eq!(body, json!({ "images": vec![image_json] }));
+    }
+
+    #[tokio::test]
+    async fn on_malformed_params_return_bad_request_code() {
+        let mut mock_service = MockService::new();
+        mock_service.expect_search_images().never();
+        let app = app(mock_service);
+
+        let response = app.get("/search?created_after=yesterday").send().await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let body = response.bytes().await;
+        let body: Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body, json!({"error": "Invalid created_after: yesterday"}));
+
+        let response = app.get("/search?orientation=round").send().await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let response = app.get("/search?min_width=wide").send().await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn on_invalid_request_return_bad_request_code() {
+        let mut mock_service = MockService::new();
+        mock_service.expect_search_images().returning(|_q| {
+            Err(SearchImagesServiceError::InvalidRequest(
+                "Empty width range".to_string(),
+            ))
+        });
+        let app = app(mock_service);
+        let response = app.get("/search?min_width=200&max_width=100").send().await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let body = response.bytes().await;
+        let body: Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body, json!({"error": "Empty width range"}));
+    }
+
+    #[tokio::test]
+    async fn on_internal_error_return_internal_server_code() {
+        let mut mock_service = MockService::new();
+        mock_service
+            .expect_search_images()
+            .returning(|_q| Err(SearchImagesServiceError::InternalError));
+        let app = app(mock_service);
+        let response = app.get("/search").send().await;
+        assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    }
+}
@@ -0,0 +1,206 @@
+use std::sync::Arc;
+
+use axum::{
+    body::{self, Body},
+    http::{Response, StatusCode},
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tracing::error;
+
+use crate::{
+    error::YaissError,
+    services::images::{
+        domain::similar_image::SimilarImage,
+        ports::incoming::similar_images_service::{
+            SimilarImagesService, SimilarImagesServiceError,
+        },
+    },
+    web::images::query_image_handler::ImageJson,
+};
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Similarity {
+    pub max_distance: u32,
+}
+
+impl Default for Similarity {
+    fn default() -> Self {
+        Self { max_distance: 8 }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SimilarImageJson {
+    #[serde(flatten)]
+    image: ImageJson,
+    distance: u32,
+}
+
+impl From<SimilarImage> for SimilarImageJson {
+    fn from(value: SimilarImage) -> Self {
+        Self {
+            distance: value.distance(),
+            image: value.image().clone().into(),
+        }
+    }
+}
+
+pub(crate) fn similar_images_error_status(error: &SimilarImagesServiceError) -> StatusCode {
+    match error {
+        SimilarImagesServiceError::ImageNotFound => StatusCode::NOT_FOUND,
+        SimilarImagesServiceError::MissingPerceptualHash => StatusCode::UNPROCESSABLE_ENTITY,
+        SimilarImagesServiceError::DistanceTooLarge(_) => StatusCode::BAD_REQUEST,
+        SimilarImagesServiceError::InternalError => StatusCode::INTERNAL_SERVER_ERROR,
+    }
+}
+
+pub(crate) type DynSimilarImagesService = Arc<dyn SimilarImagesService + Send + Sync>;
+pub async fn similar_images_handler(
+    axum::extract::State(service): axum::extract::State<DynSimilarImagesService>,
+    identifier: axum::extract::Path<i64>,
+    similarity: Option<axum::extract::Query<Similarity>>,
+) -> Result<Response<Body>, YaissError> {
+    let service = service.clone();
+    let builder = Response::builder();
+    let similarity = similarity.unwrap_or_default();
+    let builder = match service
+        .similar_images(identifier.0, similarity.max_distance)
+        .await
+    {
+        Ok(images) => {
+            let images = images
+                .into_iter()
+                .map(SimilarImageJson::from)
+                .collect::<Vec<SimilarImageJson>>();
+            let body = Json(json!({ "images": images })).to_string();
+            builder
+                .status(StatusCode::OK)
+                .header(axum::http::header::CONTENT_TYPE, "application/json")
+                .body(body::Body::from(body))
+        }
+        Err(e) => {
+            let message = e.to_string();
+            error!("{}", message);
+            builder
+                .status(similar_images_error_status(&e))
+                .header(axum::http::header::CONTENT_TYPE, "application/json")
+                .body(body::Body::from(
+                    Json(json!({
+                        "error": message,
+                    }))
+                    .to_string(),
+                ))
+        }
+    };
+    builder.map_err(|e| e.into())
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+
+    use std::sync::Arc;
+
+    use async_trait::async_trait;
+    use axum::{routing::get, Router};
+    use axum_test_helper::TestClient;
+    use chrono::Utc;
+    use mockall::{mock, predicate};
+    use reqwest::StatusCode;
+    use serde_json::{json, Value};
+
+    use crate::{
+        services::images::{
+            domain::{image::Image, similar_image::SimilarImage},
+            ports::incoming::similar_images_service::{
+                SimilarImagesService, SimilarImagesServiceError,
+            },
+        },
+        web::images::{query_image_handler::ImageJson, similar_images_handler},
+    };
+
+    mock! {
+        pub Service {}
+        #[async_trait]
+        impl SimilarImagesService for Service {
+            async fn similar_images(&self, index: i64, max_distance: u32) -> Result<Vec<SimilarImage>, SimilarImagesServiceError>;
+            async fn duplicate_clusters(&self, max_distance: u32) -> Result<Vec<Vec<Image>>, SimilarImagesServiceError>;
+        }
+    }
+
+    pub fn app(service: MockService) -> TestClient {
+        let similar_images_service =
+            Arc::new(service) as similar_images_handler::DynSimilarImagesService;
+        let router = Router::new()
+            .route(
+                "/:identifier/similar",
+                get(similar_images_handler::similar_images_handler),
+            )
+            .with_state(similar_images_service);
+        TestClient::new(router)
+    }
+
+    #[tokio::test]
+    async fn on_similar_images_return_ok() {
+        let now = Utc::now();
+        let mut mock_service = MockService::new();
+        mock_service
+            .expect_similar_images()
+            .with(predicate::eq(1), predicate::eq(4))
+            .returning(move |_i, _d| {
+                Ok(vec![SimilarImage::new(
+                    Image::new(2, "some/path".to_string(), now),
+                    3,
+                )])
+            });
+        let app = app(mock_service);
+        let response = app.get("/1/similar?max_distance=4").send().await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response.bytes().await;
+        let body: Value = serde_json::from_slice(&body).unwrap();
+        let mut image_json = json!(ImageJson::from(Image::new(2, "some/path".to_string(), now)));
+        image_json["distance"] = json!(3);
+        assert_eq!(body, json!({ "images": [image_json] }));
+    }
+
+    #[tokio::test]
+    async fn on_missing_distance_use_default() {
+        let mut mock_service = MockService::new();
+        mock_service
+            .expect_similar_images()
+            .with(predicate::eq(1), predicate::eq(8))
+            .returning(move |_i, _d| Ok(vec![]));
+        let app = app(mock_service);
+        let response = app.get("/1/similar").send().await;
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn on_image_not_found_return_not_found_code() {
+        let mut mock_service = MockService::new();
+        mock_service
+            .expect_similar_images()
+            .returning(move |_i, _d| Err(SimilarImagesServiceError::ImageNotFound));
+        let app = app(mock_service);
+        let response = app.get("/1/similar").send().await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        let body = response.bytes().await;
+        let body: Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body, json!({"error": "Image not found"}));
+    }
+
+    #[tokio::test]
+    async fn on_distance_too_large_return_bad_request_code() {
+        let mut mock_service = MockService::new();
+        mock_service
+            .expect_similar_images()
+            .returning(move |_i, _d| Err(SimilarImagesServiceError::DistanceTooLarge(16)));
+        let app = app(mock_service);
+        let response = app.get("/1/similar?max_distance=40").send().await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let body = response.bytes().await;
+        let body: Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body, json!({"error": "Distance too large. Max: 16"}));
+    }
+}
@@ -0,0 +1,116 @@
+use axum::{
+    body::{self, Body},
+    http::{Response, StatusCode},
+    Json,
+};
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::{
+    error::YaissError,
+    web::tags::{tags_error_response, DynTagsService},
+};
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct TagsJson {
+    pub tags: Vec<String>,
+}
+
+pub async fn tag_image_handler(
+    axum::extract::State(service): axum::extract::State<DynTagsService>,
+    identifier: axum::extract::Path<i64>,
+    tags: axum::extract::Json<TagsJson>,
+) -> Result<Response<Body>, YaissError> {
+    match service.tag_image(identifier.0, tags.0.tags).await {
+        Ok(tags) => Response::builder()
+            .status(StatusCode::OK)
+            .header(axum::http::header::CONTENT_TYPE, "application/json")
+            .body(body::Body::from(
+                Json(json!({ "id": identifier.0, "tags": tags })).to_string(),
+            ))
+            .map_err(|e| e.into()),
+        Err(e) => tags_error_response(e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use std::sync::Arc;
+
+    use axum::{routing::post, Router};
+    use axum_test_helper::TestClient;
+    use mockall::predicate;
+    use reqwest::StatusCode;
+    use serde_json::{json, Value};
+
+    use crate::{
+        services::images::ports::incoming::tags_service::TagsServiceError,
+        web::{
+            images::tag_image_handler,
+            tags::{list_tags_handler::tests::MockService, DynTagsService},
+        },
+    };
+
+    pub fn app(service: MockService) -> TestClient {
+        let tags_service = Arc::new(service) as DynTagsService;
+        let router = Router::new()
+            .route(
+                "/:identifier/tags",
+                post(tag_image_handler::tag_image_handler),
+            )
+            .with_state(tags_service);
+        TestClient::new(router)
+    }
+
+    #[tokio::test]
+    async fn on_tagged_return_image_tags() {
+        let mut mock_service = MockService::new();
+        mock_service
+            .expect_tag_image()
+            .with(predicate::eq(1), predicate::eq(vec!["Beach".to_string()]))
+            .returning(|_i, _t| Ok(vec!["beach".to_string(), "sunset".to_string()]));
+        let app = app(mock_service);
+        let response = app
+            .post("/1/tags")
+            .json(&json!({"tags": ["Beach"]}))
+            .send()
+            .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response.bytes().await;
+        let body: Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body, json!({"id": 1, "tags": ["beach", "sunset"]}));
+    }
+
+    #[tokio::test]
+    async fn on_invalid_tag_return_bad_request_code() {
+        let mut mock_service = MockService::new();
+        mock_service.expect_tag_image().returning(|_i, _t| {
+            Err(TagsServiceError::InvalidTag(
+                "Invalid tag: \"\"".to_string(),
+            ))
+        });
+        let app = app(mock_service);
+        let response = app
+            .post("/1/tags")
+            .json(&json!({"tags": [" "]}))
+            .send()
+            .await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn on_image_not_found_return_not_found_code() {
+        let mut mock_service = MockService::new();
+        mock_service
+            .expect_tag_image()
+            .returning(|_i, _t| Err(TagsServiceError::ImageNotFound));
+        let app = app(mock_service);
+        let response = app
+            .post("/1/tags")
+            .json(&json!({"tags": ["beach"]}))
+            .send()
+            .await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+}
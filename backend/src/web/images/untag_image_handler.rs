@@ -0,0 +1,76 @@
+use axum::{
+    body::Body,
+    http::{Response, StatusCode},
+};
+
+use crate::{
+    error::YaissError,
+    web::tags::{tags_error_response, DynTagsService},
+};
+
+pub async fn untag_image_handler(
+    axum::extract::State(service): axum::extract::State<DynTagsService>,
+    path: axum::extract::Path<(i64, String)>,
+) -> Result<Response<Body>, YaissError> {
+    let (identifier, tag) = path.0;
+    match service.untag_image(identifier, tag).await {
+        Ok(()) => Response::builder()
+            .status(StatusCode::OK)
+            .body(Body::empty())
+            .map_err(|e| e.into()),
+        Err(e) => tags_error_response(e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use std::sync::Arc;
+
+    use axum::{routing::delete, Router};
+    use axum_test_helper::TestClient;
+    use mockall::predicate;
+    use reqwest::StatusCode;
+
+    use crate::{
+        services::images::ports::incoming::tags_service::TagsServiceError,
+        web::{
+            images::untag_image_handler,
+            tags::{list_tags_handler::tests::MockService, DynTagsService},
+        },
+    };
+
+    pub fn app(service: MockService) -> TestClient {
+        let tags_service = Arc::new(service) as DynTagsService;
+        let router = Router::new()
+            .route(
+                "/:identifier/tags/:tag",
+                delete(untag_image_handler::untag_image_handler),
+            )
+            .with_state(tags_service);
+        TestClient::new(router)
+    }
+
+    #[tokio::test]
+    async fn on_untagged_return_ok() {
+        let mut mock_service = MockService::new();
+        mock_service
+            .expect_untag_image()
+            .with(predicate::eq(1), predicate::eq("beach".to_string()))
+            .returning(|_i, _t| Ok(()));
+        let app = app(mock_service);
+        let response = app.delete("/1/tags/beach").send().await;
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn on_tag_not_found_return_not_found_code() {
+        let mut mock_service = MockService::new();
+        mock_service
+            .expect_untag_image()
+            .returning(|_i, _t| Err(TagsServiceError::TagNotFound));
+        let app = app(mock_service);
+        let response = app.delete("/1/tags/beach").send().await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+}
@@ -5,44 +5,102 @@ use axum::{
     debug_handler,
     http::StatusCode,
     response::Response,
+    Json,
 };
 use futures::TryStreamExt;
+use serde::Serialize;
+use serde_json::json;
 use tokio_util::io::StreamReader;
 
 use crate::{
     error::YaissError,
-    services::images::ports::incoming::upload_images_service::UploadImagesService,
+    services::images::{
+        domain::user::{current_user_id, on_behalf_of},
+        ports::incoming::upload_images_service::{UploadImagesService, UploadImagesServiceError},
+    },
 };
 
 pub(crate) type DynUploadImagesService = Arc<dyn UploadImagesService + Send + Sync>;
 
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct UploadResultJson {
+    field: String,
+    filename: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    id: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    message: Option<String>,
+}
+
+impl UploadResultJson {
+    fn new(
+        field: String,
+        filename: Option<String>,
+        result: Result<i64, UploadImagesServiceError>,
+    ) -> Self {
+        let (id, error, message) = match result {
+            Ok(id) => (Some(id), None, None),
+            Err(e) => (None, Some(format!("{:?}", e)), Some(e.to_string())),
+        };
+        Self {
+            field,
+            filename,
+            id,
+            error,
+            message,
+        }
+    }
+
+    fn is_ok(&self) -> bool {
+        self.id.is_some()
+    }
+}
+
 #[debug_handler]
 pub async fn upload_images_handler(
     axum::extract::State(service): axum::extract::State<DynUploadImagesService>,
     mut multipart: axum::extract::Multipart,
 ) -> Result<Response<body::Body>, YaissError> {
-    while let Some(field) = multipart.next_field().await.unwrap() {
-        let mp_with_io_error = field.map_err(|err| io::Error::new(io::ErrorKind::Other, err));
+    let mut results = vec![];
+    while let Some(field) = multipart.next_field().await? {
+        let field_name = field.name().unwrap_or_default().to_string();
+        let filename = field.file_name().map(|name| name.to_string());
+        let mp_with_io_error = field.map_err(io::Error::other);
         let reader = StreamReader::new(mp_with_io_error);
         futures::pin_mut!(reader);
         let mut buffer = vec![];
         tokio::io::copy(&mut reader, &mut buffer).await?;
         let service = service.clone();
-        let handle = tokio::task::spawn(async move { service.upload_image(buffer).await });
-        handle
-            .await
-            .map_err(|e| {
-                tracing::error!("{}", e.to_string());
-                e
-            })
-            .map_err(|e| {
-                tracing::error!("{}", e.to_string());
-                e
-            })??
+        let name = filename.clone();
+        // Spawned tasks do not inherit the task-local user, so pass it on.
+        let user_id = current_user_id();
+        let handle = tokio::task::spawn(async move {
+            let upload = service.upload_image(buffer, name);
+            match user_id {
+                Some(user_id) => on_behalf_of(user_id, upload).await,
+                None => upload.await,
+            }
+        });
+        let result = handle.await.unwrap_or_else(|e| {
+            tracing::error!("{}", e.to_string());
+            Err(UploadImagesServiceError::InternalError)
+        });
+        if let Err(e) = &result {
+            tracing::error!("Error uploading field {}: {}", field_name, e);
+        }
+        results.push(UploadResultJson::new(field_name, filename, result));
     }
+    let status = if results.iter().all(UploadResultJson::is_ok) {
+        StatusCode::CREATED
+    } else {
+        StatusCode::MULTI_STATUS
+    };
     Response::builder()
-        .status(StatusCode::CREATED)
-        .body(body::Body::empty())
+        .status(status)
+        .header(axum::http::header::CONTENT_TYPE, "application/json")
+        .body(body::Body::from(Json(json!(results)).to_string()))
         .map_err(|e| e.into())
 }
 
@@ -56,6 +114,7 @@ mod tests {
     use axum_test_helper::TestClient;
     use mockall::{mock, predicate};
     use reqwest::StatusCode;
+    use serde_json::{json, Value};
 
     use crate::{
         services::images::ports::incoming::upload_images_service::{
@@ -68,7 +127,7 @@ mod tests {
         pub Service {}
         #[async_trait]
         impl UploadImagesService for Service {
-            async fn upload_image(&self, buffer: Vec<u8>) -> Result<(), UploadImagesServiceError>;
+            async fn upload_image(&self, buffer: Vec<u8>, filename: Option<String>) -> Result<i64, UploadImagesServiceError>;
         }
     }
 
@@ -87,8 +146,11 @@ mod tests {
         let data = [0u8; 1024].to_vec();
         mock_service
             .expect_upload_image()
-            .with(predicate::eq(data.clone()))
-            .returning(move |_i| Ok(()));
+            .with(
+                predicate::eq(data.clone()),
+                predicate::eq(Some("file".to_string())),
+            )
+            .returning(move |_i, _f| Ok(1));
         let app = app(mock_service);
         let form = reqwest::multipart::Form::new().part(
             "upload",
@@ -97,16 +159,25 @@ mod tests {
         let response = app.post("/").multipart(form).send().await;
 
         assert_eq!(response.status(), StatusCode::CREATED);
+        let body = response.bytes().await;
+        let body: Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(
+            body,
+            json!([{"field": "upload", "filename": "file", "id": 1}])
+        );
     }
 
     #[tokio::test]
-    async fn on_internal_error_return_internal_server_error_code() {
+    async fn on_internal_error_return_multi_status_code() {
         let mut mock_service = MockService::new();
         let data = [0u8; 1024].to_vec();
         mock_service
             .expect_upload_image()
-            .with(predicate::eq(data.clone()))
-            .returning(move |_i| Err(UploadImagesServiceError::InternalError));
+            .with(
+                predicate::eq(data.clone()),
+                predicate::eq(Some("file".to_string())),
+            )
+            .returning(move |_i, _f| Err(UploadImagesServiceError::InternalError));
         let app = app(mock_service);
         let form = reqwest::multipart::Form::new().part(
             "upload",
@@ -114,24 +185,59 @@ mod tests {
         );
         let response = app.post("/").multipart(form).send().await;
 
-        assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(response.status(), StatusCode::MULTI_STATUS);
+        let body = response.bytes().await;
+        let body: Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(
+            body,
+            json!([{
+                "field": "upload",
+                "filename": "file",
+                "error": "InternalError",
+                "message": "Internal error"
+            }])
+        );
     }
 
     #[tokio::test]
-    async fn on_decoding_error_return_internal_server_error_code() {
+    async fn on_decoding_error_report_failed_field_and_keep_going() {
         let mut mock_service = MockService::new();
-        let data = [0u8; 1024].to_vec();
+        let bad = [0u8; 1024].to_vec();
+        let good = [1u8; 1024].to_vec();
+        mock_service
+            .expect_upload_image()
+            .with(predicate::eq(bad.clone()), predicate::always())
+            .returning(move |_i, _f| Err(UploadImagesServiceError::DecodingError));
         mock_service
             .expect_upload_image()
-            .with(predicate::eq(data.clone()))
-            .returning(move |_i| Err(UploadImagesServiceError::DecodingError));
+            .with(predicate::eq(good.clone()), predicate::always())
+            .returning(move |_i, _f| Ok(2));
         let app = app(mock_service);
-        let form = reqwest::multipart::Form::new().part(
-            "upload",
-            reqwest::multipart::Part::bytes(data).file_name("file"),
-        );
+        let form = reqwest::multipart::Form::new()
+            .part(
+                "first",
+                reqwest::multipart::Part::bytes(bad).file_name("bad.png"),
+            )
+            .part(
+                "second",
+                reqwest::multipart::Part::bytes(good).file_name("good.png"),
+            );
         let response = app.post("/").multipart(form).send().await;
 
-        assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(response.status(), StatusCode::MULTI_STATUS);
+        let body = response.bytes().await;
+        let body: Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(
+            body,
+            json!([
+                {
+                    "field": "first",
+                    "filename": "bad.png",
+                    "error": "DecodingError",
+                    "message": "Decoding error"
+                },
+                {"field": "second", "filename": "good.png", "id": 2}
+            ])
+        );
     }
 }
@@ -0,0 +1,223 @@
+use std::{
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+    time::Instant,
+};
+
+use axum::{
+    body::{self, Bytes, HttpBody},
+    extract::{MatchedPath, State},
+    http::{header, HeaderMap, Request, StatusCode},
+    middleware::{self, Next},
+    response::{IntoResponse, Response},
+    routing::get,
+    Router,
+};
+use hyper::Body;
+use prometheus::{IntCounter, IntGauge};
+use tracing::error;
+
+use crate::{
+    metrics::Metrics,
+    services::images::{domain::api_key::Scope, image_scrubber::ImageScrubber},
+    state,
+    web::{self, admin::integrity_handler::DynScrubService, auth},
+};
+
+/// Requests no route matched share one label, so unknown paths do not grow
+/// the metrics.
+const UNMATCHED_ROUTE: &str = "unmatched";
+
+#[derive(Clone)]
+pub struct MetricsState {
+    metrics: Arc<Metrics>,
+    scrub_service: DynScrubService,
+}
+
+/// Counts every request by route template, method and status, with its
+/// latency and the response bytes as they are streamed.
+pub async fn track<B>(
+    State(metrics): State<Arc<Metrics>>,
+    request: Request<B>,
+    next: Next<B>,
+) -> Response {
+    let route = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map_or(UNMATCHED_ROUTE, MatchedPath::as_str)
+        .to_string();
+    let method = request.method().to_string();
+    let started = Instant::now();
+    let in_flight = InFlight::new(metrics.requests_in_flight().clone());
+    let response = next.run(request).await;
+    drop(in_flight);
+    metrics.record_request(
+        &route,
+        &method,
+        response.status().as_u16(),
+        started.elapsed(),
+    );
+    let bytes = metrics.response_bytes(&route);
+    response.map(|inner| body::boxed(CountingBody { inner, bytes }))
+}
+
+/// The Prometheus text exposition, with the image counts read on every
+/// scrape.
+pub async fn metrics_handler(State(state): State<MetricsState>) -> impl IntoResponse {
+    match state.scrub_service.image_counts().await {
+        Ok(counts) => state.metrics.set_image_counts(&counts),
+        Err(e) => error!("Error counting images for metrics: {}", e),
+    }
+    let mut headers = HeaderMap::new();
+    headers.insert(
+        header::CONTENT_TYPE,
+        prometheus::TEXT_FORMAT.parse().unwrap(),
+    );
+    (StatusCode::OK, headers, state.metrics.render())
+}
+
+/// `/metrics`, for keys with the `metrics` scope: the library size and
+/// corruption counts are not for everyone.
+pub fn router(state: state::State) -> Router<(), Body> {
+    let scrub_service =
+        Arc::new(ImageScrubber::new(state.storage(), state.blob_store())) as DynScrubService;
+    Router::new()
+        .route("/metrics", get(metrics_handler))
+        .with_state(MetricsState {
+            metrics: state.metrics(),
+            scrub_service,
+        })
+        .route_layer(middleware::from_fn_with_state(
+            Scope::Metrics,
+            auth::require_scope,
+        ))
+        .route_layer(middleware::from_fn_with_state(
+            web::auth_state(&state),
+            auth::authenticate,
+        ))
+}
+
+/// Decrements the in-flight gauge even when the request is dropped
+/// half-way.
+struct InFlight(IntGauge);
+
+impl InFlight {
+    fn new(gauge: IntGauge) -> Self {
+        gauge.inc();
+        Self(gauge)
+    }
+}
+
+impl Drop for InFlight {
+    fn drop(&mut self) {
+        self.0.dec();
+    }
+}
+
+/// A response body adding the size of each chunk to `bytes` as it is sent.
+struct CountingBody {
+    inner: body::BoxBody,
+    bytes: IntCounter,
+}
+
+impl HttpBody for CountingBody {
+    type Data = Bytes;
+    type Error = axum::Error;
+
+    fn poll_data(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Self::Data, Self::Error>>> {
+        let poll = Pin::new(&mut self.inner).poll_data(cx);
+        if let Poll::Ready(Some(Ok(data))) = &poll {
+            self.bytes.inc_by(data.len() as u64);
+        }
+        poll
+    }
+
+    fn poll_trailers(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<Option<HeaderMap>, Self::Error>> {
+        Pin::new(&mut self.inner).poll_trailers(cx)
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.inner.is_end_stream()
+    }
+
+    fn size_hint(&self) -> hyper::body::SizeHint {
+        self.inner.size_hint()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use async_trait::async_trait;
+    use axum::{middleware, routing::get, Router};
+    use axum_test_helper::TestClient;
+    use mockall::mock;
+    use reqwest::StatusCode;
+
+    use crate::{
+        metrics::Metrics,
+        services::images::{
+            domain::{
+                image_status::ImageStatus,
+                integrity::{IntegritySummary, ScrubSummary},
+            },
+            ports::incoming::scrub_service::{ScrubService, ScrubServiceError},
+        },
+        web::metrics::{metrics_handler, track, MetricsState},
+    };
+
+    mock! {
+        pub Service {}
+        #[async_trait]
+        impl ScrubService for Service {
+            async fn scrub(&self, count: i64) -> Result<ScrubSummary, ScrubServiceError>;
+            async fn integrity(&self) -> Result<IntegritySummary, ScrubServiceError>;
+            async fn image_counts(&self) -> Result<Vec<(ImageStatus, i64)>, ScrubServiceError>;
+        }
+    }
+
+    #[tokio::test]
+    async fn on_request_expose_route_metrics() {
+        let mut service = MockService::new();
+        service
+            .expect_image_counts()
+            .returning(|| Ok(vec![(ImageStatus::Ok, 4)]));
+        service.expect_integrity().never();
+        let metrics = Arc::new(Metrics::new());
+        let router = Router::new()
+            .route("/images/:id", get(|| async { "12345" }))
+            .route("/metrics", get(metrics_handler))
+            .with_state(MetricsState {
+                metrics: metrics.clone(),
+                scrub_service: Arc::new(service),
+            })
+            .layer(middleware::from_fn_with_state(metrics, track));
+        let client = TestClient::new(router);
+
+        assert_eq!(client.get("/images/1").send().await.text().await, "12345");
+        assert_eq!(
+            client.get("/images/2").send().await.status(),
+            StatusCode::OK
+        );
+        let response = client.get("/metrics").send().await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let text = response.text().await;
+        for line in [
+            r#"yaiss_http_requests_total{method="GET",route="/images/:id",status="200"} 2"#,
+            r#"yaiss_http_request_duration_seconds_count{method="GET",route="/images/:id"} 2"#,
+            r#"yaiss_http_response_bytes_total{route="/images/:id"} 10"#,
+            "yaiss_http_requests_in_flight 1",
+            r#"yaiss_images{status="ok"} 4"#,
+        ] {
+            assert!(text.contains(line), "missing {} in\n{}", line, text);
+        }
+    }
+}
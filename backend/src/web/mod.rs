@@ -1,9 +1,39 @@
-use axum::{body, response::Response, Json};
+use axum::{body, middleware, response::Response, Json, Router};
 use hyper::{Body, StatusCode};
 use serde_json::json;
 
-use crate::error::YaissError;
+use crate::{error::YaissError, state::State};
+pub mod admin;
+pub mod albums;
+pub mod auth;
 pub mod images;
+pub mod metrics;
+pub mod shares;
+pub mod tags;
+pub mod users;
+
+pub fn router(state: State) -> Router<(), Body> {
+    let api_router = Router::new()
+        .nest("/admin", admin::router(state.clone()))
+        .nest("/albums", albums::router(state.clone()))
+        .nest("/images", images::router(state.clone()))
+        .nest("/shares", shares::router(state.clone()))
+        .nest("/tags", tags::router(state.clone()))
+        .layer(middleware::from_fn_with_state(
+            auth_state(&state),
+            auth::authenticate,
+        ))
+        .nest("/shared", shares::public_router(state.clone()))
+        .nest("/users", users::router(state));
+    Router::new().nest("/api/v1", api_router)
+}
+
+pub(crate) fn auth_state(state: &State) -> auth::AuthState {
+    auth::AuthState {
+        api_keys: admin::api_keys_service(state),
+        users: users::users_service(state),
+    }
+}
 pub async fn handler_404() -> Result<Response<Body>, YaissError> {
     let body = Json(json!({
         "error": "resource not found",
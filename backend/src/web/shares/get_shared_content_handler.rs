@@ -0,0 +1,160 @@
+use axum::{
+    body::{self, BoxBody},
+    http::{HeaderMap, Response},
+};
+use serde::Deserialize;
+
+use crate::{
+    error::YaissError,
+    services::images::ports::incoming::shares_service::SharesServiceError,
+    web::images::get_image_content_handler::{
+        image_content_response, ContentQuery, ImageContentState,
+    },
+};
+
+use super::{shares_error_response, DynSharesService};
+
+#[derive(Clone)]
+pub struct SharedContentState {
+    pub(crate) shares_service: DynSharesService,
+    pub(crate) content: ImageContentState,
+}
+
+/// The signed part of the link, next to the content options of
+/// `get_image_content_handler`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct SharedQuery {
+    pub expires: i64,
+    pub signature: String,
+    pub size: Option<String>,
+    pub format: Option<String>,
+    pub quality: Option<u8>,
+}
+
+pub async fn get_shared_content_handler(
+    axum::extract::State(state): axum::extract::State<SharedContentState>,
+    id: axum::extract::Path<i64>,
+    query: Option<axum::extract::Query<SharedQuery>>,
+    headers: HeaderMap,
+) -> Result<Response<BoxBody>, YaissError> {
+    let Some(query) = query.map(|query| query.0) else {
+        return shares_error_response(SharesServiceError::InvalidLink)
+            .map(|response| response.map(body::boxed));
+    };
+    let share = match state
+        .shares_service
+        .verify_link(id.0, query.expires, &query.signature)
+        .await
+    {
+        Ok(share) => share,
+        Err(e) => return shares_error_response(e).map(|response| response.map(body::boxed)),
+    };
+    let content_query = ContentQuery {
+        size: query.size,
+        format: query.format,
+        quality: query.quality,
+    };
+    image_content_response(&state.content, share.image_id(), content_query, &headers).await
+}
+
+#[cfg(test)]
+mod tests {
+
+    use std::sync::Arc;
+
+    use axum::{routing::get, Router};
+    use axum_test_helper::TestClient;
+    use chrono::{Duration, Utc};
+    use mockall::predicate;
+    use reqwest::StatusCode;
+    use serde_json::{json, Value};
+
+    use crate::{
+        data_storage::blobs::fs_blob_store::FsBlobStore,
+        services::images::{
+            domain::{image::Image, share::Share},
+            ports::incoming::shares_service::SharesServiceError,
+        },
+        web::{
+            images::get_image_content_handler::{
+                tests::{MockRenditions, MockService as MockQueryService},
+                DynQueryImageService, DynRenditionService, ImageContentState,
+            },
+            shares::{
+                get_shared_content_handler::{self, SharedContentState},
+                share_image_handler::tests::MockService,
+                DynSharesService,
+            },
+        },
+    };
+
+    fn app(service: MockService) -> TestClient {
+        let mut query_service = MockQueryService::new();
+        query_service
+            .expect_query_image()
+            .with(predicate::eq(2))
+            .returning(|id| Ok(Image::new(id, "Cargo.toml".to_string(), Utc::now())));
+        let state = SharedContentState {
+            shares_service: Arc::new(service) as DynSharesService,
+            content: ImageContentState {
+                query_image_service: Arc::new(query_service) as DynQueryImageService,
+                rendition_service: Arc::new(MockRenditions::new()) as DynRenditionService,
+                blob_store: Arc::new(FsBlobStore::new()),
+            },
+        };
+        let router = Router::new()
+            .route(
+                "/:id",
+                get(get_shared_content_handler::get_shared_content_handler),
+            )
+            .with_state(state);
+        TestClient::new(router)
+    }
+
+    #[tokio::test]
+    async fn on_valid_link_return_content() {
+        let mut mock_service = MockService::new();
+        mock_service
+            .expect_verify_link()
+            .with(
+                predicate::eq(5),
+                predicate::eq(1699783200),
+                predicate::eq("abc123"),
+            )
+            .returning(|id, _, _| {
+                Ok(Share::new(
+                    id,
+                    2,
+                    Utc::now(),
+                    Utc::now() + Duration::hours(1),
+                ))
+            });
+        let response = app(mock_service)
+            .get("/5?expires=1699783200&signature=abc123")
+            .header(axum::http::header::ACCEPT, "image/qoi")
+            .send()
+            .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response.bytes().await;
+        let e = tokio::fs::read("Cargo.toml".to_string()).await.unwrap();
+        assert_eq!(body.to_vec(), e);
+    }
+
+    #[tokio::test]
+    async fn on_invalid_link_return_forbidden() {
+        let mut mock_service = MockService::new();
+        mock_service
+            .expect_verify_link()
+            .returning(|_, _, _| Err(SharesServiceError::InvalidLink));
+        let app = app(mock_service);
+        let response = app
+            .get("/5?expires=1699783200&signature=forged")
+            .send()
+            .await;
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+        let body: Value = serde_json::from_slice(&response.bytes().await).unwrap();
+        assert_eq!(body, json!({"error": "Invalid or expired share link"}));
+        let response = app.get("/5").send().await;
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    }
+}
@@ -0,0 +1,126 @@
+use std::sync::Arc;
+
+use axum::{
+    body::{self, Body},
+    http::{Response, StatusCode},
+    routing::{delete, get},
+    Json, Router,
+};
+use serde::Serialize;
+use serde_json::json;
+use tracing::error;
+
+use crate::{
+    error::YaissError,
+    services::images::{
+        domain::{api_key::Scope, share::Share},
+        image_renditions::ImageRenditions,
+        image_shares::ImageShares,
+        ports::incoming::shares_service::{SharesService, SharesServiceError},
+        query_image_service::QueryImage,
+    },
+    state::State,
+    web::{
+        auth::scoped,
+        images::get_image_content_handler::{
+            DynQueryImageService, DynRenditionService, ImageContentState,
+        },
+    },
+};
+
+use self::get_shared_content_handler::SharedContentState;
+
+pub mod get_shared_content_handler;
+pub mod revoke_share_handler;
+pub mod share_image_handler;
+
+/// Where `public_router` is mounted.
+const SHARED_PATH: &str = "/api/v1/shared";
+
+pub(crate) type DynSharesService = Arc<dyn SharesService + Send + Sync>;
+
+/// The link is the only way to the content without credentials.
+#[derive(Debug, Clone, Serialize)]
+pub struct ShareJson {
+    id: i64,
+    image_id: i64,
+    created_on: String,
+    expires_on: String,
+    url: String,
+}
+
+impl ShareJson {
+    fn new(share: Share, signature: String) -> Self {
+        Self {
+            id: share.id(),
+            image_id: share.image_id(),
+            created_on: share.created_on().to_string(),
+            expires_on: share.expires_on().to_string(),
+            url: format!(
+                "{}/{}?expires={}&signature={}",
+                SHARED_PATH,
+                share.id(),
+                share.expires(),
+                signature
+            ),
+        }
+    }
+}
+
+pub(crate) fn shares_error_response(e: SharesServiceError) -> Result<Response<Body>, YaissError> {
+    let message = e.to_string();
+    error!("{}", message);
+    let code = match e {
+        SharesServiceError::ImageNotFound | SharesServiceError::ShareNotFound => {
+            StatusCode::NOT_FOUND
+        }
+        SharesServiceError::InvalidLink => StatusCode::FORBIDDEN,
+        SharesServiceError::InvalidRequest(_) => StatusCode::BAD_REQUEST,
+        SharesServiceError::InternalError => StatusCode::INTERNAL_SERVER_ERROR,
+    };
+    Response::builder()
+        .status(code)
+        .header(axum::http::header::CONTENT_TYPE, "application/json")
+        .body(body::Body::from(
+            Json(json!({
+                "error": message,
+            }))
+            .to_string(),
+        ))
+        .map_err(|e| e.into())
+}
+
+pub fn shares_service(state: &State) -> DynSharesService {
+    Arc::new(ImageShares::new(state.storage(), state.share_signing_key()))
+}
+
+pub fn router(state: State) -> Router<(), Body> {
+    Router::new()
+        .route(
+            "/:id",
+            scoped(
+                Scope::Upload,
+                delete(revoke_share_handler::revoke_share_handler),
+            ),
+        )
+        .with_state(shares_service(&state))
+}
+
+/// Needs no credentials, the signature of the link is checked instead.
+pub fn public_router(state: State) -> Router<(), Body> {
+    let shared_content_state = SharedContentState {
+        shares_service: shares_service(&state),
+        content: ImageContentState {
+            query_image_service: Arc::new(QueryImage::new(state.storage())) as DynQueryImageService,
+            rendition_service: Arc::new(ImageRenditions::new(state.storage(), state.blob_store()))
+                as DynRenditionService,
+            blob_store: state.blob_store(),
+        },
+    };
+    Router::new()
+        .route(
+            "/:id",
+            get(get_shared_content_handler::get_shared_content_handler),
+        )
+        .with_state(shared_content_state)
+}
@@ -0,0 +1,69 @@
+use axum::{
+    body::Body,
+    http::{Response, StatusCode},
+};
+
+use crate::error::YaissError;
+
+use super::{shares_error_response, DynSharesService};
+
+/// The link stops working before it expires.
+pub async fn revoke_share_handler(
+    axum::extract::State(service): axum::extract::State<DynSharesService>,
+    id: axum::extract::Path<i64>,
+) -> Result<Response<Body>, YaissError> {
+    match service.revoke_share(id.0).await {
+        Ok(()) => Response::builder()
+            .status(StatusCode::NO_CONTENT)
+            .body(Body::empty())
+            .map_err(|e| e.into()),
+        Err(e) => shares_error_response(e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use std::sync::Arc;
+
+    use axum::{routing::delete, Router};
+    use axum_test_helper::TestClient;
+    use mockall::predicate;
+    use reqwest::StatusCode;
+
+    use crate::{
+        services::images::ports::incoming::shares_service::SharesServiceError,
+        web::shares::{
+            revoke_share_handler, share_image_handler::tests::MockService, DynSharesService,
+        },
+    };
+
+    pub fn app(service: MockService) -> TestClient {
+        let shares_service = Arc::new(service) as DynSharesService;
+        let router = Router::new()
+            .route("/:id", delete(revoke_share_handler::revoke_share_handler))
+            .with_state(shares_service);
+        TestClient::new(router)
+    }
+
+    #[tokio::test]
+    async fn on_revoke_return_no_content() {
+        let mut mock_service = MockService::new();
+        mock_service
+            .expect_revoke_share()
+            .with(predicate::eq(5))
+            .returning(|_| Ok(()));
+        let response = app(mock_service).delete("/5").send().await;
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+    }
+
+    #[tokio::test]
+    async fn on_unknown_share_return_not_found() {
+        let mut mock_service = MockService::new();
+        mock_service
+            .expect_revoke_share()
+            .returning(|_| Err(SharesServiceError::ShareNotFound));
+        let response = app(mock_service).delete("/9").send().await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+}
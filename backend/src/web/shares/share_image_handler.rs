@@ -0,0 +1,125 @@
+use axum::{
+    body::{self, Body},
+    http::{Response, StatusCode},
+    Json,
+};
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::error::YaissError;
+
+use super::{shares_error_response, DynSharesService, ShareJson};
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ShareImage {
+    /// Seconds until the link expires, a day if not given.
+    pub expires_in: Option<i64>,
+}
+
+pub async fn share_image_handler(
+    axum::extract::State(service): axum::extract::State<DynSharesService>,
+    identifier: axum::extract::Path<i64>,
+    request: Option<axum::extract::Json<ShareImage>>,
+) -> Result<Response<Body>, YaissError> {
+    let request = request.map(|request| request.0).unwrap_or_default();
+    match service.share_image(identifier.0, request.expires_in).await {
+        Ok((share, signature)) => Response::builder()
+            .status(StatusCode::CREATED)
+            .header(axum::http::header::CONTENT_TYPE, "application/json")
+            .body(body::Body::from(
+                Json(json!(ShareJson::new(share, signature))).to_string(),
+            ))
+            .map_err(|e| e.into()),
+        Err(e) => shares_error_response(e),
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+
+    use std::sync::Arc;
+
+    use async_trait::async_trait;
+    use axum::{routing::post, Router};
+    use axum_test_helper::TestClient;
+    use chrono::{Duration, Utc};
+    use mockall::{mock, predicate};
+    use reqwest::StatusCode;
+    use serde_json::{json, Value};
+
+    use crate::{
+        services::images::{
+            domain::share::Share,
+            ports::incoming::shares_service::{SharesService, SharesServiceError},
+        },
+        web::shares::{share_image_handler, DynSharesService},
+    };
+
+    mock! {
+        pub Service {}
+        #[async_trait]
+        impl SharesService for Service {
+            async fn share_image(
+                &self,
+                image_id: i64,
+                expires_in: Option<i64>,
+            ) -> Result<(Share, String), SharesServiceError>;
+            async fn revoke_share(&self, id: i64) -> Result<(), SharesServiceError>;
+            async fn verify_link(
+                &self,
+                id: i64,
+                expires: i64,
+                signature: &str,
+            ) -> Result<Share, SharesServiceError>;
+        }
+    }
+
+    pub fn app(service: MockService) -> TestClient {
+        let shares_service = Arc::new(service) as DynSharesService;
+        let router = Router::new()
+            .route(
+                "/:identifier/share",
+                post(share_image_handler::share_image_handler),
+            )
+            .with_state(shares_service);
+        TestClient::new(router)
+    }
+
+    #[tokio::test]
+    async fn on_share_return_signed_url() {
+        let expires_on = "2023-11-12T10:00:00Z".parse().unwrap();
+        let mut mock_service = MockService::new();
+        mock_service
+            .expect_share_image()
+            .with(predicate::eq(2), predicate::eq(Some(3600)))
+            .returning(move |image_id, _| {
+                Ok((
+                    Share::new(5, image_id, Utc::now() - Duration::hours(1), expires_on),
+                    "abc123".to_string(),
+                ))
+            });
+        let response = app(mock_service)
+            .post("/2/share")
+            .json(&json!({"expires_in": 3600}))
+            .send()
+            .await;
+        assert_eq!(response.status(), StatusCode::CREATED);
+        let body: Value = serde_json::from_slice(&response.bytes().await).unwrap();
+        assert_eq!(body["id"], json!(5));
+        assert_eq!(
+            body["url"],
+            json!("/api/v1/shared/5?expires=1699783200&signature=abc123")
+        );
+    }
+
+    #[tokio::test]
+    async fn on_unknown_image_return_not_found() {
+        let mut mock_service = MockService::new();
+        mock_service
+            .expect_share_image()
+            .with(predicate::eq(9), predicate::eq(None))
+            .returning(|_, _| Err(SharesServiceError::ImageNotFound));
+        let response = app(mock_service).post("/9/share").send().await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+}
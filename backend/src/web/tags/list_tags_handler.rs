@@ -0,0 +1,119 @@
+use axum::{
+    body::{self, Body},
+    http::{Response, StatusCode},
+    Json,
+};
+use serde::Serialize;
+use serde_json::json;
+
+use crate::{error::YaissError, services::images::domain::tag::Tag};
+
+use super::{tags_error_response, DynTagsService};
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TagJson {
+    name: String,
+    count: i64,
+}
+
+impl From<Tag> for TagJson {
+    fn from(value: Tag) -> Self {
+        Self {
+            name: value.name().to_string(),
+            count: value.count(),
+        }
+    }
+}
+
+pub async fn list_tags_handler(
+    axum::extract::State(service): axum::extract::State<DynTagsService>,
+) -> Result<Response<Body>, YaissError> {
+    match service.tags().await {
+        Ok(tags) => {
+            let tags = tags
+                .into_iter()
+                .map(TagJson::from)
+                .collect::<Vec<TagJson>>();
+            Response::builder()
+                .status(StatusCode::OK)
+                .header(axum::http::header::CONTENT_TYPE, "application/json")
+                .body(body::Body::from(Json(json!({ "tags": tags })).to_string()))
+                .map_err(|e| e.into())
+        }
+        Err(e) => tags_error_response(e),
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+
+    use std::sync::Arc;
+
+    use async_trait::async_trait;
+    use axum::{routing::get, Router};
+    use axum_test_helper::TestClient;
+    use mockall::mock;
+    use reqwest::StatusCode;
+    use serde_json::{json, Value};
+
+    use crate::{
+        services::images::{
+            domain::tag::Tag,
+            ports::incoming::tags_service::{TagsService, TagsServiceError},
+        },
+        web::tags::{list_tags_handler, DynTagsService},
+    };
+
+    mock! {
+        pub Service {}
+        #[async_trait]
+        impl TagsService for Service {
+            async fn tag_image(&self, image_id: i64, tags: Vec<String>) -> Result<Vec<String>, TagsServiceError>;
+            async fn untag_image(&self, image_id: i64, tag: String) -> Result<(), TagsServiceError>;
+            async fn tags(&self) -> Result<Vec<Tag>, TagsServiceError>;
+            async fn rename_tag(&self, from: String, to: String) -> Result<(), TagsServiceError>;
+        }
+    }
+
+    pub fn app(service: MockService) -> TestClient {
+        let tags_service = Arc::new(service) as DynTagsService;
+        let router = Router::new()
+            .route("/", get(list_tags_handler::list_tags_handler))
+            .with_state(tags_service);
+        TestClient::new(router)
+    }
+
+    #[tokio::test]
+    async fn on_tags_return_usage_counts() {
+        let mut mock_service = MockService::new();
+        mock_service.expect_tags().returning(|| {
+            Ok(vec![
+                Tag::new("beach".to_string(), 3),
+                Tag::new("sunset".to_string(), 1),
+            ])
+        });
+        let app = app(mock_service);
+        let response = app.get("/").send().await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response.bytes().await;
+        let body: Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(
+            body,
+            json!({"tags": [
+                {"name": "beach", "count": 3},
+                {"name": "sunset", "count": 1}
+            ]})
+        );
+    }
+
+    #[tokio::test]
+    async fn on_internal_error_return_internal_server_code() {
+        let mut mock_service = MockService::new();
+        mock_service
+            .expect_tags()
+            .returning(|| Err(TagsServiceError::InternalError));
+        let app = app(mock_service);
+        let response = app.get("/").send().await;
+        assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    }
+}
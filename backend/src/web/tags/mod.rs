@@ -0,0 +1,61 @@
+use std::sync::Arc;
+
+use axum::{
+    body::{self, Body},
+    http::{Response, StatusCode},
+    routing::{get, patch},
+    Json, Router,
+};
+use serde_json::json;
+use tracing::error;
+
+use crate::{
+    error::YaissError,
+    services::images::{
+        domain::api_key::Scope,
+        image_tags::ImageTags,
+        ports::incoming::tags_service::{TagsService, TagsServiceError},
+    },
+    state::State,
+    web::auth::scoped,
+};
+
+pub mod list_tags_handler;
+pub mod rename_tag_handler;
+
+pub(crate) type DynTagsService = Arc<dyn TagsService + Send + Sync>;
+
+pub(crate) fn tags_error_response(e: TagsServiceError) -> Result<Response<Body>, YaissError> {
+    let message = e.to_string();
+    error!("{}", message);
+    let code = match e {
+        TagsServiceError::ImageNotFound | TagsServiceError::TagNotFound => StatusCode::NOT_FOUND,
+        TagsServiceError::InvalidTag(_) => StatusCode::BAD_REQUEST,
+        TagsServiceError::InternalError => StatusCode::INTERNAL_SERVER_ERROR,
+    };
+    Response::builder()
+        .status(code)
+        .header(axum::http::header::CONTENT_TYPE, "application/json")
+        .body(body::Body::from(
+            Json(json!({
+                "error": message,
+            }))
+            .to_string(),
+        ))
+        .map_err(|e| e.into())
+}
+
+pub fn router(state: State) -> Router<(), Body> {
+    let storage = state.storage();
+    let tags_service = Arc::new(ImageTags::new(storage)) as DynTagsService;
+    Router::new()
+        .route(
+            "/",
+            scoped(Scope::Read, get(list_tags_handler::list_tags_handler)),
+        )
+        .route(
+            "/:tag",
+            scoped(Scope::Upload, patch(rename_tag_handler::rename_tag_handler)),
+        )
+        .with_state(tags_service)
+}
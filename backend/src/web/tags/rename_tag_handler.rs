@@ -0,0 +1,87 @@
+use axum::{
+    body::Body,
+    http::{Response, StatusCode},
+};
+use serde::Deserialize;
+
+use crate::error::YaissError;
+
+use super::{tags_error_response, DynTagsService};
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct RenameTag {
+    pub name: String,
+}
+
+pub async fn rename_tag_handler(
+    axum::extract::State(service): axum::extract::State<DynTagsService>,
+    tag: axum::extract::Path<String>,
+    rename: axum::extract::Json<RenameTag>,
+) -> Result<Response<Body>, YaissError> {
+    match service.rename_tag(tag.0, rename.0.name).await {
+        Ok(()) => Response::builder()
+            .status(StatusCode::OK)
+            .body(Body::empty())
+            .map_err(|e| e.into()),
+        Err(e) => tags_error_response(e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use std::sync::Arc;
+
+    use axum::{routing::patch, Router};
+    use axum_test_helper::TestClient;
+    use mockall::predicate;
+    use reqwest::StatusCode;
+    use serde_json::json;
+
+    use crate::{
+        services::images::ports::incoming::tags_service::TagsServiceError,
+        web::tags::{list_tags_handler::tests::MockService, rename_tag_handler, DynTagsService},
+    };
+
+    pub fn app(service: MockService) -> TestClient {
+        let tags_service = Arc::new(service) as DynTagsService;
+        let router = Router::new()
+            .route("/:tag", patch(rename_tag_handler::rename_tag_handler))
+            .with_state(tags_service);
+        TestClient::new(router)
+    }
+
+    #[tokio::test]
+    async fn on_rename_return_ok() {
+        let mut mock_service = MockService::new();
+        mock_service
+            .expect_rename_tag()
+            .with(
+                predicate::eq("beach".to_string()),
+                predicate::eq("Seaside".to_string()),
+            )
+            .returning(|_f, _t| Ok(()));
+        let app = app(mock_service);
+        let response = app
+            .patch("/beach")
+            .json(&json!({"name": "Seaside"}))
+            .send()
+            .await;
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn on_unknown_tag_return_not_found_code() {
+        let mut mock_service = MockService::new();
+        mock_service
+            .expect_rename_tag()
+            .returning(|_f, _t| Err(TagsServiceError::TagNotFound));
+        let app = app(mock_service);
+        let response = app
+            .patch("/beach")
+            .json(&json!({"name": "seaside"}))
+            .send()
+            .await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+}
@@ -0,0 +1,113 @@
+use axum::{
+    body::{self, Body},
+    http::{Response, StatusCode},
+    Json,
+};
+use serde::Serialize;
+use serde_json::json;
+
+use crate::{error::YaissError, web::auth::DynUsersService};
+
+use super::{register_handler::Credentials, users_error_response, UserJson};
+
+/// The token goes into `Authorization: Bearer` headers like an API key.
+#[derive(Debug, Clone, Serialize)]
+pub struct SessionJson {
+    user: UserJson,
+    token: String,
+    expires_on: String,
+}
+
+pub async fn login_handler(
+    axum::extract::State(service): axum::extract::State<DynUsersService>,
+    request: axum::extract::Json<Credentials>,
+) -> Result<Response<Body>, YaissError> {
+    let request = request.0;
+    match service.login(request.name, request.password).await {
+        Ok((user, token, expires_on)) => Response::builder()
+            .status(StatusCode::OK)
+            .header(axum::http::header::CONTENT_TYPE, "application/json")
+            .body(body::Body::from(
+                Json(json!(SessionJson {
+                    user: UserJson::from(user),
+                    token,
+                    expires_on: expires_on.to_string(),
+                }))
+                .to_string(),
+            ))
+            .map_err(|e| e.into()),
+        Err(e) => users_error_response(e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use std::sync::Arc;
+
+    use axum::{routing::post, Router};
+    use axum_test_helper::TestClient;
+    use chrono::Utc;
+    use mockall::predicate;
+    use reqwest::StatusCode;
+    use serde_json::{json, Value};
+
+    use crate::{
+        services::images::{domain::user::User, ports::incoming::users_service::UsersServiceError},
+        web::{
+            auth::DynUsersService,
+            users::{login_handler, register_handler::tests::MockService},
+        },
+    };
+
+    pub fn app(service: MockService) -> TestClient {
+        let users_service = Arc::new(service) as DynUsersService;
+        let router = Router::new()
+            .route("/login", post(login_handler::login_handler))
+            .with_state(users_service);
+        TestClient::new(router)
+    }
+
+    #[tokio::test]
+    async fn on_login_return_session_token() {
+        let mut mock_service = MockService::new();
+        mock_service
+            .expect_login()
+            .with(
+                predicate::eq("alice".to_string()),
+                predicate::eq("correct horse".to_string()),
+            )
+            .returning(|name, _| {
+                Ok((
+                    User::new(1, name, Utc::now()),
+                    "yaiss_token".to_string(),
+                    Utc::now(),
+                ))
+            });
+        let response = app(mock_service)
+            .post("/login")
+            .json(&json!({"name": "alice", "password": "correct horse"}))
+            .send()
+            .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let body: Value = serde_json::from_slice(&response.bytes().await).unwrap();
+        assert_eq!(body["user"]["name"], json!("alice"));
+        assert_eq!(body["token"], json!("yaiss_token"));
+    }
+
+    #[tokio::test]
+    async fn on_wrong_password_return_unauthorized() {
+        let mut mock_service = MockService::new();
+        mock_service
+            .expect_login()
+            .returning(|_, _| Err(UsersServiceError::InvalidCredentials));
+        let response = app(mock_service)
+            .post("/login")
+            .json(&json!({"name": "alice", "password": "wrong"}))
+            .send()
+            .await;
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        let body: Value = serde_json::from_slice(&response.bytes().await).unwrap();
+        assert_eq!(body, json!({"error": "Invalid credentials"}));
+    }
+}
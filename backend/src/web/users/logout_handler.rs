@@ -0,0 +1,88 @@
+use axum::{
+    body::Body,
+    http::{HeaderMap, Response, StatusCode},
+};
+
+use crate::{
+    error::YaissError,
+    services::images::ports::incoming::users_service::UsersServiceError,
+    web::auth::{bearer_token, DynUsersService},
+};
+
+use super::users_error_response;
+
+/// Ends the session of the `Authorization: Bearer` token.
+pub async fn logout_handler(
+    axum::extract::State(service): axum::extract::State<DynUsersService>,
+    headers: HeaderMap,
+) -> Result<Response<Body>, YaissError> {
+    let Some(token) = bearer_token(&headers) else {
+        return users_error_response(UsersServiceError::InvalidCredentials);
+    };
+    match service.logout(token).await {
+        Ok(()) => Response::builder()
+            .status(StatusCode::NO_CONTENT)
+            .body(Body::empty())
+            .map_err(|e| e.into()),
+        Err(e) => users_error_response(e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use std::sync::Arc;
+
+    use axum::{routing::post, Router};
+    use axum_test_helper::TestClient;
+    use mockall::predicate;
+    use reqwest::StatusCode;
+
+    use crate::{
+        services::images::ports::incoming::users_service::UsersServiceError,
+        web::{
+            auth::DynUsersService,
+            users::{logout_handler, register_handler::tests::MockService},
+        },
+    };
+
+    pub fn app(service: MockService) -> TestClient {
+        let users_service = Arc::new(service) as DynUsersService;
+        let router = Router::new()
+            .route("/logout", post(logout_handler::logout_handler))
+            .with_state(users_service);
+        TestClient::new(router)
+    }
+
+    #[tokio::test]
+    async fn on_logout_return_no_content() {
+        let mut mock_service = MockService::new();
+        mock_service
+            .expect_logout()
+            .with(predicate::eq("yaiss_token"))
+            .returning(|_| Ok(()));
+        let response = app(mock_service)
+            .post("/logout")
+            .header("Authorization", "Bearer yaiss_token")
+            .send()
+            .await;
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+    }
+
+    #[tokio::test]
+    async fn on_unknown_session_return_unauthorized() {
+        let mut mock_service = MockService::new();
+        mock_service
+            .expect_logout()
+            .returning(|_| Err(UsersServiceError::InvalidCredentials));
+        let app = app(mock_service);
+        let response = app.post("/logout").send().await;
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        let response = app
+            .post("/logout")
+            .header("Authorization", "Bearer expired")
+            .send()
+            .await;
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+}
@@ -0,0 +1,76 @@
+use std::sync::Arc;
+
+use axum::{
+    body::{self, Body},
+    http::{Response, StatusCode},
+    routing::post,
+    Json, Router,
+};
+use serde::Serialize;
+use serde_json::json;
+use tracing::error;
+
+use crate::{
+    error::YaissError,
+    services::images::{
+        domain::user::User, ports::incoming::users_service::UsersServiceError, users::Users,
+    },
+    state::State,
+    web::auth::DynUsersService,
+};
+
+pub mod login_handler;
+pub mod logout_handler;
+pub mod register_handler;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct UserJson {
+    id: i64,
+    name: String,
+    created_on: String,
+}
+
+impl From<User> for UserJson {
+    fn from(value: User) -> Self {
+        Self {
+            id: value.id(),
+            name: value.name().to_string(),
+            created_on: value.created_on().to_string(),
+        }
+    }
+}
+
+pub(crate) fn users_error_response(e: UsersServiceError) -> Result<Response<Body>, YaissError> {
+    let message = e.to_string();
+    error!("{}", message);
+    let code = match e {
+        UsersServiceError::InvalidCredentials => StatusCode::UNAUTHORIZED,
+        UsersServiceError::AlreadyExists => StatusCode::CONFLICT,
+        UsersServiceError::InvalidRequest(_) => StatusCode::BAD_REQUEST,
+        UsersServiceError::InternalError => StatusCode::INTERNAL_SERVER_ERROR,
+    };
+    Response::builder()
+        .status(code)
+        .header(axum::http::header::CONTENT_TYPE, "application/json")
+        .body(body::Body::from(
+            Json(json!({
+                "error": message,
+            }))
+            .to_string(),
+        ))
+        .map_err(|e| e.into())
+}
+
+pub fn users_service(state: &State) -> DynUsersService {
+    Arc::new(Users::new(state.storage()))
+}
+
+/// Public routes: they are how callers get a session token in the first
+/// place.
+pub fn router(state: State) -> Router<(), Body> {
+    Router::new()
+        .route("/", post(register_handler::register_handler))
+        .route("/login", post(login_handler::login_handler))
+        .route("/logout", post(logout_handler::logout_handler))
+        .with_state(users_service(&state))
+}
@@ -0,0 +1,121 @@
+use axum::{
+    body::{self, Body},
+    http::{Response, StatusCode},
+    Json,
+};
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::{error::YaissError, web::auth::DynUsersService};
+
+use super::{users_error_response, UserJson};
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Credentials {
+    pub name: String,
+    pub password: String,
+}
+
+pub async fn register_handler(
+    axum::extract::State(service): axum::extract::State<DynUsersService>,
+    request: axum::extract::Json<Credentials>,
+) -> Result<Response<Body>, YaissError> {
+    let request = request.0;
+    match service.register(request.name, request.password).await {
+        Ok(user) => Response::builder()
+            .status(StatusCode::CREATED)
+            .header(axum::http::header::CONTENT_TYPE, "application/json")
+            .body(body::Body::from(
+                Json(json!(UserJson::from(user))).to_string(),
+            ))
+            .map_err(|e| e.into()),
+        Err(e) => users_error_response(e),
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+
+    use std::sync::Arc;
+
+    use async_trait::async_trait;
+    use axum::{routing::post, Router};
+    use axum_test_helper::TestClient;
+    use chrono::{DateTime, Utc};
+    use mockall::{mock, predicate};
+    use reqwest::StatusCode;
+    use serde_json::{json, Value};
+
+    use crate::{
+        services::images::{
+            domain::user::User,
+            ports::incoming::users_service::{UsersService, UsersServiceError},
+        },
+        web::{auth::DynUsersService, users::register_handler},
+    };
+
+    mock! {
+        pub Service {}
+        #[async_trait]
+        impl UsersService for Service {
+            async fn register(
+                &self,
+                name: String,
+                password: String,
+            ) -> Result<User, UsersServiceError>;
+            async fn login(
+                &self,
+                name: String,
+                password: String,
+            ) -> Result<(User, String, DateTime<Utc>), UsersServiceError>;
+            async fn logout(&self, token: &str) -> Result<(), UsersServiceError>;
+            async fn authenticate(&self, token: &str) -> Result<User, UsersServiceError>;
+        }
+    }
+
+    pub fn app(service: MockService) -> TestClient {
+        let users_service = Arc::new(service) as DynUsersService;
+        let router = Router::new()
+            .route("/", post(register_handler::register_handler))
+            .with_state(users_service);
+        TestClient::new(router)
+    }
+
+    #[tokio::test]
+    async fn on_register_return_created_user() {
+        let mut mock_service = MockService::new();
+        mock_service
+            .expect_register()
+            .with(
+                predicate::eq("alice".to_string()),
+                predicate::eq("correct horse".to_string()),
+            )
+            .returning(|name, _| Ok(User::new(1, name, Utc::now())));
+        let response = app(mock_service)
+            .post("/")
+            .json(&json!({"name": "alice", "password": "correct horse"}))
+            .send()
+            .await;
+        assert_eq!(response.status(), StatusCode::CREATED);
+        let body: Value = serde_json::from_slice(&response.bytes().await).unwrap();
+        assert_eq!(body["id"], json!(1));
+        assert_eq!(body["name"], json!("alice"));
+        assert!(body.get("password").is_none());
+    }
+
+    #[tokio::test]
+    async fn on_taken_name_return_conflict() {
+        let mut mock_service = MockService::new();
+        mock_service
+            .expect_register()
+            .returning(|_, _| Err(UsersServiceError::AlreadyExists));
+        let response = app(mock_service)
+            .post("/")
+            .json(&json!({"name": "alice", "password": "correct horse"}))
+            .send()
+            .await;
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+        let body: Value = serde_json::from_slice(&response.bytes().await).unwrap();
+        assert_eq!(body, json!({"error": "User already exists"}));
+    }
+}